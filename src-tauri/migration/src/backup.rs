@@ -1,5 +1,6 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use chrono::Local;
 use reina_path::get_db_path;
@@ -26,6 +27,15 @@ pub async fn backup_sqlite(version: &str) -> Result<PathBuf, DbErr> {
         .ok()
         .flatten();
 
+    // 保留策略列由较新的迁移添加，旧版本数据库中查询失败时视为不限制
+    let (max_count, max_age_days): (Option<i64>, Option<i64>) =
+        sqlx::query_as("SELECT max_db_backups, max_backup_age_days FROM user LIMIT 1")
+            .fetch_optional(&pool)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+
     pool.close().await;
 
     // 选择目标目录
@@ -43,12 +53,135 @@ pub async fn backup_sqlite(version: &str) -> Result<PathBuf, DbErr> {
     fs::copy(&db_path, &backup_path)
         .map_err(|e| DbErr::Custom(format!("Failed to copy database: {}", e)))?;
 
+    let positive =
+        |value: Option<i64>| value.and_then(|v| u32::try_from(v).ok()).filter(|v| *v > 0);
+    match prune_db_backups(&target_dir, positive(max_count), positive(max_age_days)) {
+        Ok(removed) if !removed.is_empty() => {
+            log::info!("已按保留策略清理 {} 个旧数据库备份", removed.len());
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("清理旧数据库备份失败: {}", e),
+    }
+
     Ok(backup_path)
 }
 
+/// 数据库备份文件（手动、自动与迁移前备份）的文件名前缀与扩展名（含压缩备份）
+pub const DB_BACKUP_PREFIX: &str = "reina_manager_";
+pub const DB_BACKUP_EXTENSIONS: &[&str] = &[".db", ".db.7z"];
+
+/// 按保留策略选出要删除的备份
+///
+/// `backups` 为 (路径, 修改时间)。最新的一个备份总是保留，其余超出数量上限或超过保留天数的都会被选中。
+fn expired_backups(
+    mut backups: Vec<(PathBuf, SystemTime)>,
+    max_count: Option<u32>,
+    max_age_days: Option<u32>,
+    now: SystemTime,
+) -> Vec<PathBuf> {
+    backups.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+    let max_age = max_age_days.map(|days| Duration::from_secs(days as u64 * 24 * 60 * 60));
+
+    backups
+        .into_iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(index, (_, modified))| {
+            let over_count = max_count.is_some_and(|max| *index >= max as usize);
+            let too_old = max_age.is_some_and(|max_age| {
+                now.duration_since(*modified).is_ok_and(|age| age > max_age)
+            });
+            over_count || too_old
+        })
+        .map(|(_, (path, _))| path)
+        .collect()
+}
+
+/// 按数量上限与最长保留天数清理备份目录中的 `reina_manager_*.db` / `reina_manager_*.db.7z`，返回被删除的文件
+///
+/// 两项都为 None 时不做任何事。
+pub fn prune_db_backups(
+    backup_dir: &Path,
+    max_count: Option<u32>,
+    max_age_days: Option<u32>,
+) -> std::io::Result<Vec<PathBuf>> {
+    if max_count.is_none() && max_age_days.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(backup_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if !file_name.starts_with(DB_BACKUP_PREFIX)
+            || !DB_BACKUP_EXTENSIONS
+                .iter()
+                .any(|extension| file_name.ends_with(extension))
+        {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            backups.push((entry.path(), metadata.modified()?));
+        }
+    }
+
+    let expired = expired_backups(backups, max_count, max_age_days, SystemTime::now());
+    for path in &expired {
+        fs::remove_file(path)?;
+    }
+    Ok(expired)
+}
+
 /// 将文件路径转换为 sqlite 连接 URL
 pub fn path_to_sqlite_url(path: &PathBuf) -> Result<String, DbErr> {
     let db_url = url::Url::from_file_path(path)
         .map_err(|_| DbErr::Custom("Invalid database path".to_string()))?;
     Ok(format!("sqlite:{}?mode=rwc", db_url.path()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::expired_backups;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn backups(now: SystemTime, ages_in_days: &[u64]) -> Vec<(PathBuf, SystemTime)> {
+        ages_in_days
+            .iter()
+            .map(|days| {
+                (
+                    PathBuf::from(format!("reina_manager_{}.db", days)),
+                    now - Duration::from_secs(days * DAY),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prunes_by_count_and_age_but_keeps_newest() {
+        let now = SystemTime::now();
+        let names = |paths: Vec<PathBuf>| {
+            let mut names: Vec<String> = paths
+                .into_iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let expired = expired_backups(backups(now, &[3, 0, 1, 2]), Some(2), None, now);
+        assert_eq!(names(expired), ["reina_manager_2.db", "reina_manager_3.db"]);
+
+        let expired = expired_backups(backups(now, &[0, 10, 40]), None, Some(30), now);
+        assert_eq!(names(expired), ["reina_manager_40.db"]);
+
+        let expired = expired_backups(backups(now, &[60, 90]), Some(5), Some(30), now);
+        assert_eq!(names(expired), ["reina_manager_90.db"]);
+
+        assert!(expired_backups(backups(now, &[0, 1]), None, None, now).is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 pub use sea_orm_migration::prelude::*;
 
-mod backup;
+pub mod backup;
 mod m20250927_000001_baseline_migration;
 mod m20250928_000002_split_games_table;
 mod m20250930_000003_add_collections;
@@ -13,6 +13,36 @@ mod m20260331_000009_add_kungal_support;
 mod m20260505_000010_remove_redundant_created_at;
 mod m20260508_000011_bgm_oauth;
 mod m20260525_000012_move_custom_date_to_games;
+mod m20261014_000013_add_launch_attempts;
+mod m20261014_000014_add_session_tags;
+mod m20261014_000015_add_auto_clear_rules;
+mod m20261014_000016_add_savedata_quota;
+mod m20261014_000017_add_session_checkpoints;
+mod m20261014_000018_add_monitor_settings;
+mod m20261014_000019_add_launch_uri;
+mod m20261014_000020_add_manual_sessions;
+mod m20261014_000021_add_notification_settings;
+mod m20261014_000022_add_savedata_sha256;
+mod m20261014_000023_add_screenshots;
+mod m20261014_000024_add_presence_settings;
+mod m20261014_000025_add_screenshot_source;
+mod m20261014_000026_add_window_placement;
+mod m20261014_000027_add_backup_schedule;
+mod m20261014_000028_add_scan_config;
+mod m20261014_000029_add_http_api;
+mod m20261014_000030_add_db_backup_retention;
+mod m20261014_000031_add_launch_args;
+mod m20261014_000032_add_game_runner;
+mod m20261014_000033_add_game_sandbox;
+mod m20261014_000034_add_input_mapping;
+mod m20261014_000035_add_session_process_stats;
+mod m20261014_000036_add_tray_settings;
+mod m20261014_000037_add_log_settings;
+mod m20261014_000038_add_usage_stats;
+mod m20261014_000039_add_setup_progress;
+mod m20261014_000040_add_backup_compression;
+mod m20261014_000041_add_use_trash;
+mod m20261014_000042_add_file_lock_retry;
 
 pub struct Migrator;
 
@@ -32,6 +62,36 @@ impl MigratorTrait for Migrator {
             Box::new(m20260505_000010_remove_redundant_created_at::Migration),
             Box::new(m20260508_000011_bgm_oauth::Migration),
             Box::new(m20260525_000012_move_custom_date_to_games::Migration),
+            Box::new(m20261014_000013_add_launch_attempts::Migration),
+            Box::new(m20261014_000014_add_session_tags::Migration),
+            Box::new(m20261014_000015_add_auto_clear_rules::Migration),
+            Box::new(m20261014_000016_add_savedata_quota::Migration),
+            Box::new(m20261014_000017_add_session_checkpoints::Migration),
+            Box::new(m20261014_000018_add_monitor_settings::Migration),
+            Box::new(m20261014_000019_add_launch_uri::Migration),
+            Box::new(m20261014_000020_add_manual_sessions::Migration),
+            Box::new(m20261014_000021_add_notification_settings::Migration),
+            Box::new(m20261014_000022_add_savedata_sha256::Migration),
+            Box::new(m20261014_000023_add_screenshots::Migration),
+            Box::new(m20261014_000024_add_presence_settings::Migration),
+            Box::new(m20261014_000025_add_screenshot_source::Migration),
+            Box::new(m20261014_000026_add_window_placement::Migration),
+            Box::new(m20261014_000027_add_backup_schedule::Migration),
+            Box::new(m20261014_000028_add_scan_config::Migration),
+            Box::new(m20261014_000029_add_http_api::Migration),
+            Box::new(m20261014_000030_add_db_backup_retention::Migration),
+            Box::new(m20261014_000031_add_launch_args::Migration),
+            Box::new(m20261014_000032_add_game_runner::Migration),
+            Box::new(m20261014_000033_add_game_sandbox::Migration),
+            Box::new(m20261014_000034_add_input_mapping::Migration),
+            Box::new(m20261014_000035_add_session_process_stats::Migration),
+            Box::new(m20261014_000036_add_tray_settings::Migration),
+            Box::new(m20261014_000037_add_log_settings::Migration),
+            Box::new(m20261014_000038_add_usage_stats::Migration),
+            Box::new(m20261014_000039_add_setup_progress::Migration),
+            Box::new(m20261014_000040_add_backup_compression::Migration),
+            Box::new(m20261014_000041_add_use_trash::Migration),
+            Box::new(m20261014_000042_add_file_lock_retry::Migration),
         ]
     }
 }
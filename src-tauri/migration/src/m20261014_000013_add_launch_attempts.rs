@@ -0,0 +1,101 @@
+//! 新增 launch_attempts 表
+//!
+//! 记录每次启动游戏的结果，便于排查"游戏打开后立即退出"一类问题：
+//! - 启动时写入一条记录（成功标志、进程 ID、消息、输出日志路径）
+//! - 捕获输出时，进程退出后回写退出码与最后若干行输出
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LaunchAttempts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LaunchAttempts::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LaunchAttempts::GameId).integer().not_null())
+                    .col(
+                        ColumnDef::new(LaunchAttempts::LaunchedAt)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(LaunchAttempts::Success)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(LaunchAttempts::ProcessId).integer().null())
+                    .col(ColumnDef::new(LaunchAttempts::Message).text().null())
+                    .col(ColumnDef::new(LaunchAttempts::ExitCode).integer().null())
+                    .col(ColumnDef::new(LaunchAttempts::ExitedAt).integer().null())
+                    .col(ColumnDef::new(LaunchAttempts::LogPath).text().null())
+                    .col(ColumnDef::new(LaunchAttempts::OutputTail).text().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_launch_attempts_game")
+                            .from(LaunchAttempts::Table, LaunchAttempts::GameId)
+                            .to(Games::Table, Games::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_launch_attempts_game_launched_at")
+                    .table(LaunchAttempts::Table)
+                    .col(LaunchAttempts::GameId)
+                    .col(LaunchAttempts::LaunchedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LaunchAttempts::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// LaunchAttempts 表的列定义
+#[derive(DeriveIden)]
+enum LaunchAttempts {
+    Table,
+    Id,
+    GameId,
+    LaunchedAt,
+    Success,
+    ProcessId,
+    Message,
+    ExitCode,
+    ExitedAt,
+    LogPath,
+    OutputTail,
+}
+
+/// Games 表引用（用于外键）
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    Id,
+}
@@ -0,0 +1,34 @@
+//! 为 game_sessions 表添加 tags 列
+//!
+//! tags 以 JSON 字符串数组存储会话的游玩上下文标签（如 "route: Ayane"、"replay"），默认为 NULL。
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GameSessions::Table)
+                    .add_column(ColumnDef::new(GameSessions::Tags).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum GameSessions {
+    Table,
+    Tags,
+}
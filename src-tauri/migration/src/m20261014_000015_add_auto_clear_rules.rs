@@ -0,0 +1,34 @@
+//! 为 user 表添加 auto_clear_rules 列
+//!
+//! auto_clear_rules 以 JSON 存储自动标记游戏状态的规则，默认为 NULL（不启用）。
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::AutoClearRules).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    AutoClearRules,
+}
@@ -0,0 +1,56 @@
+//! 存档备份软配额
+//!
+//! 本迁移执行以下操作：
+//! 1. savedata 表新增 auto 列，标记由游戏结束自动创建的检查点（已有记录视为手动备份）
+//! 2. user 表新增 savedata_quota 列，以 JSON 存储备份根目录的配额设置，默认为 NULL（不限制）
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Savedata::Table)
+                    .add_column(
+                        ColumnDef::new(Savedata::Auto)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::SavedataQuota).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum Savedata {
+    Table,
+    Auto,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    SavedataQuota,
+}
@@ -0,0 +1,79 @@
+//! 新增 session_checkpoints 表
+//!
+//! 游戏运行期间每分钟写入一次进行中的会话，正常结束时删除。
+//! 应用崩溃后残留的记录会在下次启动时补记为游戏会话。
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SessionCheckpoints::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SessionCheckpoints::GameId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SessionCheckpoints::StartTime)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SessionCheckpoints::LastHeartbeat)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SessionCheckpoints::TotalSeconds)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_checkpoints_game")
+                            .from(SessionCheckpoints::Table, SessionCheckpoints::GameId)
+                            .to(Games::Table, Games::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SessionCheckpoints::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// SessionCheckpoints 表的列定义
+#[derive(DeriveIden)]
+enum SessionCheckpoints {
+    Table,
+    GameId,
+    StartTime,
+    LastHeartbeat,
+    TotalSeconds,
+}
+
+/// Games 表引用（用于外键）
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    Id,
+}
@@ -0,0 +1,35 @@
+//! 游戏监控设置
+//!
+//! 本迁移执行以下操作：
+//! 1. user 表新增 monitor_settings 列，以 JSON 存储游戏监控相关设置（如挂机判定阈值），默认为 NULL
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::MonitorSettings).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    MonitorSettings,
+}
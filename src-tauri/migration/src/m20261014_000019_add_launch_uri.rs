@@ -0,0 +1,46 @@
+//! 商店 URI 启动
+//!
+//! 本迁移执行以下操作：
+//! 1. games 表新增 launch_uri 列，设置后通过 URI（如 steam://rungameid/...）启动游戏
+//! 2. games 表新增 launch_process_name 列，用于查找商店拉起的游戏进程，未设置时使用 localpath 的文件名
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column(ColumnDef::new(Games::LaunchUri).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column(ColumnDef::new(Games::LaunchProcessName).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    LaunchUri,
+    LaunchProcessName,
+}
@@ -0,0 +1,51 @@
+//! 手动会话
+//!
+//! 本迁移执行以下操作：
+//! 1. game_sessions 表新增 manual 列，标记手动补录或调整的会话（已有记录视为监控记录）
+//! 2. game_sessions 表新增 note 列，保存手动会话的备注，默认为 NULL
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GameSessions::Table)
+                    .add_column(
+                        ColumnDef::new(GameSessions::Manual)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GameSessions::Table)
+                    .add_column(ColumnDef::new(GameSessions::Note).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum GameSessions {
+    Table,
+    Manual,
+    Note,
+}
@@ -0,0 +1,35 @@
+//! 存档备份完整性校验
+//!
+//! 本迁移执行以下操作：
+//! 1. savedata 表新增 sha256 列，记录备份压缩包的 SHA-256（十六进制小写），旧记录为 NULL
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Savedata::Table)
+                    .add_column(ColumnDef::new(Savedata::Sha256).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum Savedata {
+    Table,
+    Sha256,
+}
@@ -0,0 +1,84 @@
+//! 新增 screenshots 表
+//!
+//! 记录游玩过程中截取的游戏截图：
+//! - 图片文件保存在数据目录的 `screenshots/game_<id>` 下，表中只保存路径和元信息
+//! - 删除游戏时级联删除记录（图片文件由应用负责清理）
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Screenshots::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Screenshots::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Screenshots::GameId).integer().not_null())
+                    .col(ColumnDef::new(Screenshots::Path).text().not_null())
+                    .col(ColumnDef::new(Screenshots::CapturedAt).integer().not_null())
+                    .col(ColumnDef::new(Screenshots::Width).integer().null())
+                    .col(ColumnDef::new(Screenshots::Height).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_screenshots_game")
+                            .from(Screenshots::Table, Screenshots::GameId)
+                            .to(Games::Table, Games::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_screenshots_game_captured_at")
+                    .table(Screenshots::Table)
+                    .col(Screenshots::GameId)
+                    .col(Screenshots::CapturedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Screenshots::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Screenshots 表的列定义
+#[derive(DeriveIden)]
+enum Screenshots {
+    Table,
+    Id,
+    GameId,
+    Path,
+    CapturedAt,
+    Width,
+    Height,
+}
+
+/// Games 表引用（用于外键）
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    Id,
+}
@@ -0,0 +1,35 @@
+//! Discord Rich Presence 设置
+//!
+//! 本迁移执行以下操作：
+//! 1. user 表新增 presence_settings 列，以 JSON 存储 Discord 状态展示的开关与隐私选项，默认为 NULL
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::PresenceSettings).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    PresenceSettings,
+}
@@ -0,0 +1,63 @@
+//! 游戏自带截图目录
+//!
+//! 本迁移执行以下操作：
+//! 1. games 表新增 screenshot_dir 列，记录游戏引擎自行保存截图的目录（相对路径基于游戏目录）
+//! 2. screenshots 表新增 source_path 列，记录从该目录复制来的原文件路径，用于重复导入时去重
+//! 3. screenshots 表新增 session_id 列，按截取时间关联到对应的游戏会话（会话删除时置空）
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column(ColumnDef::new(Games::ScreenshotDir).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Screenshots::Table)
+                    .add_column(ColumnDef::new(Screenshots::SourcePath).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite 的 ALTER TABLE 不支持添加外键约束，会话删除时由应用置空
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Screenshots::Table)
+                    .add_column(ColumnDef::new(Screenshots::SessionId).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    ScreenshotDir,
+}
+
+#[derive(DeriveIden)]
+enum Screenshots {
+    Table,
+    SourcePath,
+    SessionId,
+}
@@ -0,0 +1,35 @@
+//! 游戏窗口位置记忆
+//!
+//! 本迁移执行以下操作：
+//! 1. games 表新增 window_placement 列（JSON），保存会话结束前最后记录的游戏窗口位置、大小与 DPI
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column(ColumnDef::new(Games::WindowPlacement).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    WindowPlacement,
+}
@@ -0,0 +1,51 @@
+//! 存档自动备份计划
+//!
+//! 本迁移执行以下操作：
+//! 1. user 表新增 backup_schedule 列（JSON），保存全局的自动备份开关、间隔与自动备份保留数量
+//! 2. games 表新增 backup_schedule 列（JSON），保存单个游戏对上述设置的覆盖，未设置的字段沿用全局设置
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::BackupSchedule).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column(ColumnDef::new(Games::BackupSchedule).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    BackupSchedule,
+}
+
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    BackupSchedule,
+}
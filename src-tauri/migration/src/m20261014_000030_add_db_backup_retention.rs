@@ -0,0 +1,46 @@
+//! 数据库备份保留策略
+//!
+//! 本迁移执行以下操作：
+//! 1. user 表新增 max_db_backups 列，保留的数据库备份数量上限，默认为 NULL（不限制）
+//! 2. user 表新增 max_backup_age_days 列，数据库备份的最长保留天数，默认为 NULL（不限制）
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::MaxDbBackups).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::MaxBackupAgeDays).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    MaxDbBackups,
+    MaxBackupAgeDays,
+}
@@ -0,0 +1,36 @@
+//! 每个游戏的启动参数
+//!
+//! 本迁移执行以下操作：
+//! 1. games 表新增 launch_args 列（JSON 字符串数组），启动时与前端临时传入的参数合并，
+//!    支持 `{gamedir}`、`{savepath}` 等占位符
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column(ColumnDef::new(Games::LaunchArgs).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    LaunchArgs,
+}
@@ -0,0 +1,36 @@
+//! 每个游戏的 Linux 兼容层
+//!
+//! 本迁移执行以下操作：
+//! 1. games 表新增 runner 列（JSON），记录该游戏使用的 wine / Proton / 原生运行方式、
+//!    前缀目录与额外环境变量；未设置时沿用全局的 linux_launch_command
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column(ColumnDef::new(Games::Runner).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum Games {
+    Table,
+    Runner,
+}
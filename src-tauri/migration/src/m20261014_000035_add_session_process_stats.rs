@@ -0,0 +1,35 @@
+//! 会话的进程资源占用
+//!
+//! 本迁移执行以下操作：
+//! 1. game_sessions 表新增 process_stats 列（JSON），记录会话期间主进程内存与 CPU 占用的最小值、平均值和峰值
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GameSessions::Table)
+                    .add_column(ColumnDef::new(GameSessions::ProcessStats).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum GameSessions {
+    Table,
+    ProcessStats,
+}
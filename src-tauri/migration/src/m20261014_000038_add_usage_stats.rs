@@ -0,0 +1,71 @@
+//! 本地功能使用统计
+//!
+//! 本迁移执行以下操作：
+//! 1. 新增 usage_stats 表，按功能与日期累计使用次数，只保存在本地
+//! 2. user 表新增 usage_stats_enabled 列，默认为 NULL（不统计）
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UsageStats::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(UsageStats::Feature).text().not_null())
+                    .col(ColumnDef::new(UsageStats::Date).text().not_null())
+                    .col(
+                        ColumnDef::new(UsageStats::Count)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(UsageStats::LastUsed).integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(UsageStats::Feature)
+                            .col(UsageStats::Date),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::UsageStatsEnabled).boolean().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+/// UsageStats 表的列定义
+#[derive(DeriveIden)]
+enum UsageStats {
+    Table,
+    Feature,
+    /// 本地日期 YYYY-MM-DD
+    Date,
+    Count,
+    /// 最后一次使用的时间（Unix 时间戳，秒）
+    LastUsed,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UsageStatsEnabled,
+}
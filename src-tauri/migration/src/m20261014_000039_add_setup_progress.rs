@@ -0,0 +1,45 @@
+//! 首次启动向导进度
+//!
+//! 本迁移执行以下操作：
+//! 1. user 表新增 setup_progress 列，以 JSON 存储首次启动向导的进度，默认为 NULL
+//! 2. 已有用户记录的视为老用户，标记向导已结束，升级后不再弹出向导
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::SetupProgress).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE "user" SET setup_progress = '{"finished_at":' || strftime('%s', 'now') || '}'"#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    SetupProgress,
+}
@@ -0,0 +1,46 @@
+//! 文件占用重试策略
+//!
+//! 本迁移执行以下操作：
+//! 1. user 表新增 file_lock_retries 列，文件被占用时的最大重试次数，默认为 NULL（5 次）
+//! 2. user 表新增 file_lock_retry_delay_ms 列，首次重试前的等待时间（毫秒），之后每次翻倍，默认为 NULL（100 毫秒）
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::FileLockRetries).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::FileLockRetryDelayMs).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(
+            "此迁移无法回滚，请从备份恢复数据库".to_string(),
+        ))
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    FileLockRetries,
+    FileLockRetryDelayMs,
+}
@@ -1,4 +1,5 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{PoisonError, RwLock};
 
 /// 数据库相关路径常量
 pub const DB_DATA_DIR: &str = "data";
@@ -7,6 +8,33 @@ pub const DB_FILE_NAME: &str = "reina_manager.db";
 // 基础数据目录下的子目录名称
 pub const BACKUP_SUBDIR: &str = "backups";
 pub const RESOURCE_DIR: &str = "resources";
+pub const SCREENSHOT_SUBDIR: &str = "screenshots";
+pub const CRASH_SUBDIR: &str = "crashes";
+pub const COVERS_SUBDIR: &str = "covers";
+pub const LAUNCH_LOG_SUBDIR: &str = "launch_logs";
+
+/// 基础数据目录下除数据库目录外由应用管理的资源目录，切换数据目录时随数据库一起移动
+pub const ASSET_SUBDIRS: &[&str] = &[
+    BACKUP_SUBDIR,
+    COVERS_SUBDIR,
+    SCREENSHOT_SUBDIR,
+    CRASH_SUBDIR,
+    LAUNCH_LOG_SUBDIR,
+];
+
+/// 自定义数据目录指针文件名（位于可执行文件同级目录），内容为数据目录的绝对路径
+pub const DATA_ROOT_POINTER_FILE: &str = "data_root.txt";
+
+/// 指针文件中的自定义数据目录，首次读取后缓存，修改指针文件时更新
+static CUSTOM_DATA_ROOT: RwLock<Option<Option<PathBuf>>> = RwLock::new(None);
+
+fn exe_dir() -> Result<PathBuf, String> {
+    let exe_path = std::env::current_exe().map_err(|e| format!("无法获取可执行文件路径: {}", e))?;
+    exe_path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "无法获取可执行文件父目录".to_string())
+}
 
 /// 判断是否处于便携模式（纯 Rust 版本）
 ///
@@ -25,7 +53,7 @@ pub fn is_portable_mode() -> bool {
 ///
 /// 该目录是应用非数据库资源的统一根目录：
 /// - 便携模式: `<exe>/resources`
-/// - 安装模式: `<system-data>/<identifier>`
+/// - 安装模式: 指针文件中的自定义数据目录，未设置时为 `<system-data>/<identifier>`
 ///
 /// 数据库属于该根目录下的专用子目录 `<base>/data`，不要把本函数当作数据库目录使用。
 pub fn get_base_data_dir() -> Result<PathBuf, String> {
@@ -34,7 +62,7 @@ pub fn get_base_data_dir() -> Result<PathBuf, String> {
     } else {
         let system_dir = get_base_data_dir_for_mode(false)?;
         std::fs::create_dir_all(&system_dir)
-            .map_err(|e| format!("无法创建系统数据目录 {}: {}", system_dir.display(), e))?;
+            .map_err(|e| format!("无法创建数据目录 {}: {}", system_dir.display(), e))?;
         Ok(system_dir)
     }
 }
@@ -43,24 +71,86 @@ pub fn get_base_data_dir() -> Result<PathBuf, String> {
 ///
 /// 返回值语义与 `get_base_data_dir` 一致：
 /// - 便携模式: `<exe>/resources`
-/// - 安装模式: `<system-data>/<identifier>`
+/// - 安装模式: 自定义数据目录或 `<system-data>/<identifier>`
 pub fn get_base_data_dir_for_mode(portable: bool) -> Result<PathBuf, String> {
     if portable {
-        let exe_path =
-            std::env::current_exe().map_err(|e| format!("无法获取可执行文件路径: {}", e))?;
-        let exe_dir = exe_path
-            .parent()
-            .ok_or_else(|| "无法获取可执行文件父目录".to_string())?;
-        Ok(exe_dir.join(RESOURCE_DIR))
+        Ok(exe_dir()?.join(RESOURCE_DIR))
+    } else if let Some(custom_root) = get_custom_data_root() {
+        Ok(custom_root)
     } else {
-        use directories::BaseDirs;
+        get_system_data_dir()
+    }
+}
 
-        let identifier = "com.reinamanager.dev";
+/// 获取系统默认的数据目录 `<system-data>/<identifier>`，不考虑自定义数据目录
+pub fn get_system_data_dir() -> Result<PathBuf, String> {
+    use directories::BaseDirs;
 
-        let base_dirs = BaseDirs::new().ok_or_else(|| "无法获取系统目录信息".to_string())?;
+    let identifier = "com.reinamanager.dev";
 
-        Ok(base_dirs.data_dir().join(identifier))
+    let base_dirs = BaseDirs::new().ok_or_else(|| "无法获取系统目录信息".to_string())?;
+
+    Ok(base_dirs.data_dir().join(identifier))
+}
+
+/// 获取自定义数据目录指针文件路径 `<exe>/data_root.txt`
+pub fn get_data_root_pointer_path() -> Result<PathBuf, String> {
+    Ok(exe_dir()?.join(DATA_ROOT_POINTER_FILE))
+}
+
+/// 解析指针文件内容，只接受绝对路径
+fn parse_data_root_pointer(content: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(content.trim_start_matches('\u{feff}').trim());
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        None
     }
+}
+
+/// 获取安装模式下的自定义数据目录，未设置或指针文件无效时返回 None
+pub fn get_custom_data_root() -> Option<PathBuf> {
+    if let Some(cached) = CUSTOM_DATA_ROOT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        return cached.clone();
+    }
+    let root = get_data_root_pointer_path()
+        .ok()
+        .and_then(|pointer| std::fs::read_to_string(pointer).ok())
+        .and_then(|content| parse_data_root_pointer(&content));
+    *CUSTOM_DATA_ROOT
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(root.clone());
+    root
+}
+
+/// 写入指针文件设置自定义数据目录，传入 None 时删除指针文件恢复系统默认目录
+///
+/// 只修改指针，不移动数据；移动数据由调用方负责。
+pub fn set_custom_data_root(root: Option<&Path>) -> Result<(), String> {
+    let pointer = get_data_root_pointer_path()?;
+    match root {
+        Some(root) => std::fs::write(&pointer, root.to_string_lossy().as_bytes())
+            .map_err(|e| format!("写入数据目录指针文件失败 {}: {}", pointer.display(), e))?,
+        None => match std::fs::remove_file(&pointer) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(format!(
+                    "删除数据目录指针文件失败 {}: {}",
+                    pointer.display(),
+                    e
+                ))
+            }
+        },
+    }
+    *CUSTOM_DATA_ROOT
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(root.map(Path::to_path_buf));
+    Ok(())
 }
 
 /// 获取数据库专用目录 `<base>/data`。
@@ -87,3 +177,33 @@ pub fn get_default_db_backup_path() -> Result<PathBuf, String> {
 pub fn get_default_savedata_backup_path() -> Result<PathBuf, String> {
     Ok(get_base_data_dir()?.join(BACKUP_SUBDIR))
 }
+
+/// 获取游戏截图目录 `<base>/screenshots`
+pub fn get_screenshots_dir() -> Result<PathBuf, String> {
+    Ok(get_base_data_dir()?.join(SCREENSHOT_SUBDIR))
+}
+
+/// 获取崩溃报告目录 `<base>/crashes`
+pub fn get_crashes_dir() -> Result<PathBuf, String> {
+    Ok(get_base_data_dir()?.join(CRASH_SUBDIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_data_root_pointer() {
+        let absolute = if cfg!(windows) {
+            "D:\\ReinaData"
+        } else {
+            "/data/reina"
+        };
+        assert_eq!(
+            parse_data_root_pointer(&format!("\u{feff}{}\r\n", absolute)),
+            Some(PathBuf::from(absolute))
+        );
+        assert_eq!(parse_data_root_pointer("relative/dir"), None);
+        assert_eq!(parse_data_root_pointer("  "), None);
+    }
+}
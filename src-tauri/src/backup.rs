@@ -2,4 +2,12 @@ pub mod archive;
 pub mod common;
 pub mod covers;
 pub mod database;
+pub mod integrity;
+pub mod library;
+pub mod quota;
+pub mod restore_preview;
 pub mod savedata;
+pub mod schedule;
+pub mod self_test;
+pub mod settings;
+pub mod statistics_csv;
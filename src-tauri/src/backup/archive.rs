@@ -1,14 +1,35 @@
 //! 7z 压缩/解压工具模块
 //!
-//! 提供基于 Zstd 的 7z 压缩与解压功能，供存档备份、自定义封面备份等多处复用。
+//! 提供基于 Zstd 的 7z 压缩与解压功能，供存档备份、自定义封面备份、压缩的数据库备份等多处复用。
+//! 存档备份可选使用 AES-256 加密（同时加密文件头，不输入密码无法查看文件列表），
+//! 压缩等级、算法与固实压缩可通过 [`BackupCompression`] 调整。
 
-use sevenz_rust2::{ArchiveWriter, decompress_file, encoder_options::ZstandardOptions};
+use crate::entity::backup_compression::{BackupCompression, CompressionCodec, CompressionLevel};
+use crate::utils::file_lock::retry_on_lock;
+use sevenz_rust2::encoder_options::{AesEncoderOptions, Lzma2Options, ZstandardOptions};
+use sevenz_rust2::{
+    Archive, ArchiveEntry, ArchiveReader, ArchiveWriter, EncoderConfiguration, EncoderMethod,
+    Error as SevenZipError, Password, decompress_file_with_password,
+};
 use std::fs;
 use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// 速度与压缩率折中：使用 Zstd 低压缩等级。
 const ZSTD_COMPRESSION_LEVEL: u32 = 3;
 
+/// 压缩设置对应的编码器等级
+fn codec_level(codec: CompressionCodec, level: CompressionLevel) -> u32 {
+    match (codec, level) {
+        (CompressionCodec::Zstd, CompressionLevel::Fast) => 1,
+        (CompressionCodec::Zstd, CompressionLevel::Normal) => ZSTD_COMPRESSION_LEVEL,
+        (CompressionCodec::Zstd, CompressionLevel::Max) => 19,
+        (CompressionCodec::Lzma2, CompressionLevel::Fast) => 1,
+        (CompressionCodec::Lzma2, CompressionLevel::Normal) => 6,
+        (CompressionCodec::Lzma2, CompressionLevel::Max) => 9,
+    }
+}
+
 /// 创建 7z 压缩包（递归压缩整个目录）
 ///
 /// # Arguments
@@ -20,15 +41,88 @@ const ZSTD_COMPRESSION_LEVEL: u32 = 3;
 pub fn create_7z_archive(
     source_dir: &Path,
     archive_path: &Path,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    create_7z_archive_with_password(source_dir, archive_path, None)
+}
+
+/// 创建 7z 压缩包，提供非空密码时使用 AES-256 加密内容与文件头
+///
+/// # Arguments
+/// * `source_dir` - 源目录路径
+/// * `archive_path` - 目标压缩包路径
+/// * `password` - 加密密码，None 或空字符串表示不加密
+///
+/// # Returns
+/// * `Result<u64, Box<dyn std::error::Error>>` - 压缩包文件大小或错误
+pub fn create_7z_archive_with_password(
+    source_dir: &Path,
+    archive_path: &Path,
+    password: Option<&str>,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    create_7z_archive_with_options(
+        source_dir,
+        archive_path,
+        password,
+        &BackupCompression::default(),
+    )
+}
+
+/// 按指定的压缩设置创建 7z 压缩包，未设置的字段取默认值（Zstd、normal、固实压缩）
+///
+/// # Arguments
+/// * `source_dir` - 源目录路径
+/// * `archive_path` - 目标压缩包路径
+/// * `password` - 加密密码，None 或空字符串表示不加密
+/// * `compression` - 压缩设置
+///
+/// # Returns
+/// * `Result<u64, Box<dyn std::error::Error>>` - 压缩包文件大小或错误
+pub fn create_7z_archive_with_options(
+    source_dir: &Path,
+    archive_path: &Path,
+    password: Option<&str>,
+    compression: &BackupCompression,
 ) -> Result<u64, Box<dyn std::error::Error>> {
     let mut writer = ArchiveWriter::create(archive_path)?;
 
-    let zstd_options = ZstandardOptions::from_level(ZSTD_COMPRESSION_LEVEL);
-    log::debug!("7z 压缩参数: codec=ZSTD, level={}", ZSTD_COMPRESSION_LEVEL);
-    writer.set_content_methods(vec![zstd_options.into()]);
+    let codec = compression.codec.unwrap_or_default();
+    let level = codec_level(codec, compression.level.unwrap_or_default());
+    let solid = compression.solid.unwrap_or(true);
+    let codec_options: EncoderConfiguration = match codec {
+        CompressionCodec::Zstd => ZstandardOptions::from_level(level).into(),
+        CompressionCodec::Lzma2 => Lzma2Options::from_level(level).into(),
+    };
+    match password.filter(|password| !password.is_empty()) {
+        Some(password) => {
+            log::debug!(
+                "7z 压缩参数: codec=AES256+{:?}, level={}, solid={}",
+                codec,
+                level,
+                solid
+            );
+            writer.set_content_methods(vec![
+                AesEncoderOptions::new(Password::new(password)).into(),
+                codec_options,
+            ]);
+            writer.set_encrypt_header(true);
+        }
+        None => {
+            log::debug!(
+                "7z 压缩参数: codec={:?}, level={}, solid={}",
+                codec,
+                level,
+                solid
+            );
+            writer.set_content_methods(vec![codec_options]);
+        }
+    }
 
     // 递归添加源目录中的所有文件，过滤器返回 true 表示包含
-    writer.push_source_path(source_dir, |_| true)?;
+    if solid {
+        writer.push_source_path(source_dir, |_| true)?;
+    } else {
+        writer.push_source_path_non_solid(source_dir, |_| true)?;
+    }
 
     writer.finish()?;
 
@@ -36,6 +130,67 @@ pub fn create_7z_archive(
     Ok(metadata.len())
 }
 
+/// 把单个文件压缩为 7z，压缩包内只有一个以原文件名命名的条目
+///
+/// # Arguments
+/// * `source_file` - 源文件路径
+/// * `archive_path` - 目标压缩包路径
+///
+/// # Returns
+/// * `Result<u64, Box<dyn std::error::Error>>` - 压缩包文件大小或错误
+pub fn compress_file_to_7z(
+    source_file: &Path,
+    archive_path: &Path,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let entry_name = source_file
+        .file_name()
+        .ok_or("无效的源文件路径")?
+        .to_string_lossy()
+        .to_string();
+
+    let mut writer = ArchiveWriter::create(archive_path)?;
+    writer.set_content_methods(vec![
+        ZstandardOptions::from_level(ZSTD_COMPRESSION_LEVEL).into(),
+    ]);
+    writer.push_archive_entry(
+        ArchiveEntry::from_path(source_file, entry_name),
+        Some(fs::File::open(source_file)?),
+    )?;
+    writer.finish()?;
+
+    Ok(fs::metadata(archive_path)?.len())
+}
+
+/// 从 7z 压缩包中解出第一个扩展名匹配（不区分大小写）的文件，写入 `target_path`
+///
+/// # Arguments
+/// * `archive_path` - 压缩包路径
+/// * `extension` - 要查找的扩展名（含点，如 `.db`）
+/// * `target_path` - 解出文件的保存路径
+///
+/// # Returns
+/// * `Result<bool, Box<dyn std::error::Error>>` - 是否找到并解出了文件
+pub fn extract_first_file_from_7z(
+    archive_path: &Path,
+    extension: &str,
+    target_path: &Path,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let extension = extension.to_ascii_lowercase();
+    let mut reader = ArchiveReader::open(archive_path, Password::empty())?;
+    let mut found = false;
+    reader.for_each_entries(|entry, data| {
+        if entry.is_directory || !entry.name().to_ascii_lowercase().ends_with(&extension) {
+            return Ok(true);
+        }
+        let mut file = fs::File::create(target_path)?;
+        std::io::copy(data, &mut file)?;
+        found = true;
+        // 返回 false 停止遍历
+        Ok(false)
+    })?;
+    Ok(found)
+}
+
 /// 解压 7z 压缩包（覆盖模式）
 ///
 /// 解压前会先清空目标目录的所有内容，确保恢复结果完整干净。
@@ -43,28 +198,161 @@ pub fn create_7z_archive(
 /// # Arguments
 /// * `archive_path` - 压缩包路径
 /// * `target_dir` - 目标解压目录
+/// * `password` - 解密密码，未加密的压缩包传 None
 ///
 /// # Returns
 /// * `Result<(), Box<dyn std::error::Error>>` - 成功或错误
 pub fn extract_7z_archive(
     archive_path: &Path,
     target_dir: &Path,
+    password: Option<&str>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    // 清空目标目录前先读取文件头，密码缺失或错误时直接报错，不破坏现有文件
+    let password = password
+        .filter(|password| !password.is_empty())
+        .map(Password::new)
+        .unwrap_or_else(Password::empty);
+    Archive::open_with_password(archive_path, &password)?;
+
     // 如果目标目录存在，先清空内容以实现覆盖
     if target_dir.exists() {
         for entry in fs::read_dir(target_dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_dir() {
-                fs::remove_dir_all(&path)?;
+                retry_on_lock(&path, || fs::remove_dir_all(&path))?;
             } else {
-                fs::remove_file(&path)?;
+                retry_on_lock(&path, || fs::remove_file(&path))?;
             }
         }
     } else {
         fs::create_dir_all(target_dir)?;
     }
 
-    decompress_file(archive_path, target_dir)?;
+    decompress_file_with_password(archive_path, target_dir, password)?;
     Ok(())
 }
+
+/// 压缩包内的单个条目
+#[derive(Debug, Clone)]
+pub struct ArchiveEntryInfo {
+    /// 使用 `/` 分隔的相对路径
+    pub path: String,
+    pub size: u64,
+    pub is_directory: bool,
+    /// 修改时间（Unix 时间戳，秒），压缩包未记录时为 None
+    pub modified: Option<i64>,
+}
+
+/// 读取 7z 压缩包的文件列表（不解压内容）
+///
+/// # Arguments
+/// * `archive_path` - 压缩包路径
+/// * `password` - 解密密码，文件头加密的压缩包必须提供
+///
+/// # Returns
+/// * `Result<Vec<ArchiveEntryInfo>, Box<dyn std::error::Error>>` - 条目列表或错误
+pub fn list_7z_archive_entries(
+    archive_path: &Path,
+    password: Option<&str>,
+) -> Result<Vec<ArchiveEntryInfo>, Box<dyn std::error::Error>> {
+    let password = password
+        .filter(|password| !password.is_empty())
+        .map(Password::new)
+        .unwrap_or_else(Password::empty);
+    let archive = Archive::open_with_password(archive_path, &password)?;
+
+    Ok(archive
+        .files
+        .iter()
+        .filter(|entry| !entry.is_anti_item)
+        .map(|entry| ArchiveEntryInfo {
+            path: entry.name.replace('\\', "/"),
+            size: entry.size,
+            is_directory: entry.is_directory,
+            modified: entry
+                .has_last_modified_date
+                .then(|| SystemTime::from(entry.last_modified_date))
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs() as i64),
+        })
+        .collect())
+}
+
+/// 判断 7z 压缩包是否加密
+///
+/// 文件头加密时不提供密码无法读取；仅内容加密时检查数据块是否使用 AES 编码器。
+pub fn is_7z_archive_encrypted(archive_path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    match Archive::open(archive_path) {
+        Ok(archive) => Ok(archive.blocks.iter().any(|block| {
+            block
+                .coders
+                .iter()
+                .any(|coder| coder.encoder_method_id() == EncoderMethod::ID_AES256_SHA256)
+        })),
+        Err(SevenZipError::PasswordRequired) => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_compression_option() {
+        let root = std::env::temp_dir().join(format!("reina_archive_test_{}", std::process::id()));
+        let source = root.join("source");
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::write(source.join("save.dat"), "save".repeat(1000)).unwrap();
+        fs::write(source.join("sub").join("config.ini"), "[config]").unwrap();
+
+        for codec in [CompressionCodec::Zstd, CompressionCodec::Lzma2] {
+            for solid in [true, false] {
+                let compression = BackupCompression {
+                    level: Some(CompressionLevel::Max),
+                    codec: Some(codec),
+                    solid: Some(solid),
+                };
+                let archive = root.join(format!("{:?}_{}.7z", codec, solid));
+                let target = root.join(format!("{:?}_{}", codec, solid));
+                create_7z_archive_with_options(&source, &archive, Some("secret"), &compression)
+                    .unwrap();
+                assert!(is_7z_archive_encrypted(&archive).unwrap());
+
+                extract_7z_archive(&archive, &target, Some("secret")).unwrap();
+                assert_eq!(
+                    fs::read_to_string(target.join("sub").join("config.ini")).unwrap(),
+                    "[config]"
+                );
+                assert_eq!(
+                    fs::read_to_string(target.join("save.dat")).unwrap().len(),
+                    4000
+                );
+            }
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn per_backup_options_override_global_settings() {
+        let global = BackupCompression {
+            level: Some(CompressionLevel::Fast),
+            codec: Some(CompressionCodec::Lzma2),
+            solid: None,
+        };
+        let options = BackupCompression {
+            level: Some(CompressionLevel::Max),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.or(&global).resolved(),
+            BackupCompression {
+                level: Some(CompressionLevel::Max),
+                codec: Some(CompressionCodec::Lzma2),
+                solid: Some(true),
+            }
+        );
+    }
+}
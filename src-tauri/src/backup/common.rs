@@ -1,5 +1,6 @@
 use crate::database::dto::UpdateSettingsData;
 use crate::database::repository::settings_repository::{DbSettingsExt, SettingsRepository};
+use crate::utils::file_lock::retry_on_lock;
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -10,6 +11,8 @@ use std::path::{Path, PathBuf};
 pub struct BackupOptions {
     pub auto: bool,
     pub max_auto_backups: Option<usize>,
+    /// 数据库备份完成后压缩为 `.db.7z`（仅数据库备份使用）
+    pub compress: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,6 +20,9 @@ pub struct BackupResult {
     pub success: bool,
     pub path: Option<String>,
     pub message: String,
+    /// 按数据库备份保留策略删除的旧备份数量
+    #[serde(default)]
+    pub pruned_backups: usize,
 }
 
 pub async fn resolve_backup_dir(db: &DatabaseConnection) -> Result<PathBuf, String> {
@@ -52,7 +58,7 @@ pub async fn resolve_backup_dir(db: &DatabaseConnection) -> Result<PathBuf, Stri
 pub fn cleanup_auto_backup_files(
     backup_dir: &Path,
     prefix: &str,
-    extension: &str,
+    extensions: &[&str],
     max_count: usize,
 ) -> Result<Vec<String>, String> {
     let max_count = max_count.max(1);
@@ -70,7 +76,11 @@ pub fn cleanup_auto_backup_files(
             continue;
         };
 
-        if file_name.starts_with(prefix) && file_name.ends_with(extension) {
+        if file_name.starts_with(prefix)
+            && extensions
+                .iter()
+                .any(|extension| file_name.ends_with(extension))
+        {
             files.push((file_name.to_string(), path));
         }
     }
@@ -83,7 +93,7 @@ pub fn cleanup_auto_backup_files(
     let remove_count = files.len() - max_count;
     let mut deleted_files = Vec::new();
     for (file_name, path) in files.into_iter().take(remove_count) {
-        fs::remove_file(&path)
+        retry_on_lock(&path, || fs::remove_file(&path))
             .map_err(|e| format!("删除旧自动备份失败 {}: {}", path.to_string_lossy(), e))?;
         deleted_files.push(file_name);
     }
@@ -2,6 +2,8 @@ use crate::backup::archive::create_7z_archive;
 use crate::backup::common::{
     BackupOptions, BackupResult, cleanup_auto_backup_files, resolve_backup_dir,
 };
+use crate::database::db::DbState;
+use crate::utils::metrics::CommandTimer;
 use sea_orm::DatabaseConnection;
 use std::fs;
 use std::path::Path;
@@ -22,9 +24,11 @@ use tauri::{State, command};
 /// ```
 #[command]
 pub async fn backup_custom_covers(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     options: Option<BackupOptions>,
 ) -> Result<BackupResult, String> {
+    let _timer = CommandTimer::start("backup_custom_covers");
+    let db = db.get();
     let options = options.unwrap_or_default();
     let result = backup_custom_covers_archive(&db, options.auto).await?;
 
@@ -32,9 +36,12 @@ pub async fn backup_custom_covers(
         && let Some(max_auto_backups) = options.max_auto_backups
     {
         let backup_dir = resolve_backup_dir(&db).await?;
-        if let Err(e) =
-            cleanup_auto_backup_files(&backup_dir, "custom_covers_auto_", ".7z", max_auto_backups)
-        {
+        if let Err(e) = cleanup_auto_backup_files(
+            &backup_dir,
+            "custom_covers_auto_",
+            &[".7z"],
+            max_auto_backups,
+        ) {
             log::warn!("清理旧自定义封面自动备份失败: {}", e);
         }
     }
@@ -47,19 +54,20 @@ pub async fn backup_custom_covers_archive(
     auto: bool,
 ) -> Result<BackupResult, String> {
     // 1. 获取封面根目录
-    let covers_dir = reina_path::get_base_data_dir()?.join("covers");
+    let covers_dir = reina_path::get_base_data_dir()?.join(reina_path::COVERS_SUBDIR);
     if !covers_dir.exists() {
         return Ok(BackupResult {
             success: true,
             path: None,
             message: "没有自定义封面需要备份".to_string(),
+            pruned_backups: 0,
         });
     }
 
     // 2. 创建临时目录，内层套 covers 文件夹方便用户直接覆盖
     let timestamp = chrono::Local::now().timestamp_millis();
     let temp_dir = std::env::temp_dir().join(format!("reina_covers_{}", timestamp));
-    let covers_temp_dir = temp_dir.join("covers");
+    let covers_temp_dir = temp_dir.join(reina_path::COVERS_SUBDIR);
 
     // 3. 遍历 covers 目录，仅复制自定义封面文件
     let mut has_covers = false;
@@ -77,6 +85,7 @@ pub async fn backup_custom_covers_archive(
             success: true,
             path: None,
             message: "没有自定义封面需要备份".to_string(),
+            pruned_backups: 0,
         });
     }
 
@@ -121,11 +130,12 @@ pub async fn backup_custom_covers_archive(
         success: true,
         path: Some(archive_path.to_string_lossy().to_string()),
         message: "自定义封面备份成功".to_string(),
+        pruned_backups: 0,
     })
 }
 
 pub fn delete_all_covers_dir() -> Result<(), String> {
-    let covers_dir = reina_path::get_base_data_dir()?.join("covers");
+    let covers_dir = reina_path::get_base_data_dir()?.join(reina_path::COVERS_SUBDIR);
 
     if !covers_dir.exists() {
         return Ok(());
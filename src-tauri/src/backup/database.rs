@@ -1,14 +1,30 @@
+use crate::backup::archive::{compress_file_to_7z, extract_first_file_from_7z};
 use crate::backup::common::{
     BackupOptions, BackupResult, cleanup_auto_backup_files, resolve_backup_dir,
 };
 use crate::backup::covers::{backup_custom_covers_archive, delete_all_covers_dir};
-use crate::database::db::close_connection;
-use sea_orm::{ConnectionTrait, DatabaseConnection};
+use crate::database::db::{DbState, close_connection, reconnect};
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::game::cover::DownloadState;
+use crate::game::monitor::active_sessions;
+use crate::game::screenshot::init_screenshot_hotkey;
+use crate::game::session_hotkeys::init_session_hotkeys;
+use crate::utils::file_lock::{load_retry_policy, retry_on_lock};
+use crate::utils::http_api::init_http_api;
+use crate::utils::metrics::CommandTimer;
+use crate::utils::usage_stats::{UsageFeature, record_usage};
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
-use tauri::{State, command};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State, command};
+use url::Url;
 
+use migration::backup::{DB_BACKUP_EXTENSIONS, prune_db_backups};
+use migration::{Migrator, MigratorTrait};
 use reina_path::get_db_path;
 
 /// 数据库导入结果
@@ -42,30 +58,155 @@ fn generate_auto_backup_filename() -> String {
 /// - 优先使用 user.db_backup_path（如果设置且非空）
 /// - 否则使用默认路径
 ///
+/// # Arguments
+///
+/// * `options` - 备份选项，`auto` 为 true 时执行退出时的自动冷备份；
+///   `compress` 为 true 时把备份压缩为 `reina_manager_*.db.7z`（旧版 `backup_path` 调用不压缩）
+/// * `backup_path` - 已弃用：旧版 `utils/db.rs` 中 `backup_database(backup_path)` 的参数，
+///   旧前端仍会传入；传入时备份写到该位置并记录弃用警告，新代码请改用 `db_backup_path` 设置
+///
 /// # Returns
 ///
 /// 备份结果，包含备份文件的路径
 #[command]
 pub async fn backup_database(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     options: Option<BackupOptions>,
+    backup_path: Option<String>,
 ) -> Result<BackupResult, String> {
+    let _timer = CommandTimer::start("backup_database");
+    let db = db.get();
     let options = options.unwrap_or_default();
+    let legacy_path = backup_path.filter(|path| !path.trim().is_empty());
+
     if options.auto {
-        return backup_database_file_cold(&db, options.max_auto_backups).await;
+        if let Some(path) = legacy_path {
+            log::warn!(
+                "backup_database 的 backup_path 参数已弃用，自动备份忽略该参数: {}",
+                path
+            );
+        }
+        return backup_database_file_cold(&db, options.max_auto_backups, options.compress).await;
     }
 
-    let result = backup_database_file(&db).await?;
+    if let Some(path) = legacy_path {
+        log::warn!(
+            "backup_database(backup_path) 为旧版调用方式，已弃用；请改用 db_backup_path 设置。本次备份到: {}",
+            path
+        );
+        let target_path = resolve_legacy_backup_target(Path::new(&path))?;
+        return vacuum_into(&db, &target_path).await;
+    }
+
+    let result = backup_database_file(&db, options.compress).await?;
 
     Ok(result)
 }
 
-pub async fn backup_database_file(db: &DatabaseConnection) -> Result<BackupResult, String> {
+/// 解析旧版调用传入的备份位置
+///
+/// 旧版既有传目录也有传完整文件路径的调用：以 `.db` 结尾视为文件路径，否则视为目录并生成文件名。
+fn resolve_legacy_backup_target(path: &Path) -> Result<PathBuf, String> {
+    let is_file = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("db"));
+    let (dir, target) = if is_file {
+        (path.parent().map(Path::to_path_buf), path.to_path_buf())
+    } else {
+        (
+            Some(path.to_path_buf()),
+            path.join(generate_backup_filename()),
+        )
+    };
+    if let Some(dir) = dir.filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
+    }
+    if target.exists() {
+        return Err(format!("备份文件已存在: {}", target.display()));
+    }
+    Ok(target)
+}
+
+/// 按 `max_db_backups` / `max_backup_age_days` 清理备份目录中的旧数据库备份，返回删除的数量
+///
+/// 新备份已经写入，清理失败只记录日志，不影响本次备份结果。
+fn apply_backup_retention(backup_dir: &Path, retention: (Option<u32>, Option<u32>)) -> usize {
+    let (max_count, max_age_days) = retention;
+    match prune_db_backups(backup_dir, max_count, max_age_days) {
+        Ok(removed) => {
+            if !removed.is_empty() {
+                log::info!(
+                    "已按保留策略清理 {} 个旧数据库备份 max_count={:?} max_age_days={:?}",
+                    removed.len(),
+                    max_count,
+                    max_age_days
+                );
+            }
+            removed.len()
+        }
+        Err(e) => {
+            log::warn!("清理旧数据库备份失败: {}", e);
+            0
+        }
+    }
+}
+
+/// 把备份文件压缩为同名 `.7z`（`reina_manager_*.db.7z`）并删除未压缩的副本，返回压缩包路径
+fn compress_backup(backup_path: &Path) -> Result<PathBuf, String> {
+    let mut archive_name = backup_path.as_os_str().to_owned();
+    archive_name.push(".7z");
+    let archive_path = PathBuf::from(archive_name);
+
+    if let Err(e) = compress_file_to_7z(backup_path, &archive_path) {
+        let _ = fs::remove_file(&archive_path);
+        return Err(format!("压缩数据库备份失败: {}", e));
+    }
+    fs::remove_file(backup_path).map_err(|e| format!("删除未压缩的数据库备份失败: {}", e))?;
+    Ok(archive_path)
+}
+
+/// 按需压缩备份结果中的文件；压缩失败时保留未压缩的备份，只记录警告
+fn compress_backup_result(result: &mut BackupResult) {
+    let Some(path) = result.path.as_deref() else {
+        return;
+    };
+    match compress_backup(Path::new(path)) {
+        Ok(archive_path) => {
+            log::info!("数据库备份已压缩: {}", archive_path.display());
+            result.path = Some(archive_path.to_string_lossy().replace('\\', "/"));
+        }
+        Err(e) => {
+            log::warn!("{}，保留未压缩的备份: {}", e, path);
+        }
+    }
+}
+
+pub async fn backup_database_file(
+    db: &DatabaseConnection,
+    compress: bool,
+) -> Result<BackupResult, String> {
     // 生成备份文件名并确定目标路径
     let backup_name = generate_backup_filename();
     let backup_dir = resolve_backup_dir(db).await?;
+    let retention = db.get_settings().await?.db_backup_retention();
     let target_path = backup_dir.join(&backup_name);
 
+    let mut result = vacuum_into(db, &target_path).await?;
+    if compress {
+        result = tokio::task::spawn_blocking(move || {
+            compress_backup_result(&mut result);
+            result
+        })
+        .await
+        .map_err(|e| format!("压缩数据库备份失败: {}", e))?;
+    }
+    result.pruned_backups = apply_backup_retention(&backup_dir, retention);
+    record_usage(db, UsageFeature::DatabaseBackup).await;
+    Ok(result)
+}
+
+/// 使用 VACUUM INTO 将数据库热备份到指定文件
+async fn vacuum_into(db: &DatabaseConnection, target_path: &Path) -> Result<BackupResult, String> {
     // 将路径转换为字符串
     // SQLite 在 Windows 上也支持正斜杠，使用正斜杠可以避免转义问题
     let target_path_str = target_path
@@ -89,31 +230,47 @@ pub async fn backup_database_file(db: &DatabaseConnection) -> Result<BackupResul
         success: true,
         path: Some(target_path_str),
         message: "数据库备份成功".to_string(),
+        pruned_backups: 0,
     })
 }
 
 async fn backup_database_file_cold(
     db: &DatabaseConnection,
     max_auto_backups: Option<usize>,
+    compress: bool,
 ) -> Result<BackupResult, String> {
     // 自动冷备份用于退出流程，会关闭连接；关闭前必须先读取配置。
     let backup_dir = resolve_backup_dir(db).await?;
+    let retention = db.get_settings().await?.db_backup_retention();
     let db_path = get_db_path()?;
     close_connection(db.clone())
         .await
         .map_err(|e| format!("关闭数据库连接失败: {}", e))?;
     log::info!("数据库连接已关闭，准备执行文件操作");
 
-    let result = copy_database_file_cold(&db_path, &backup_dir, true)?;
+    // 复制时文件被占用会阻塞重试，放到阻塞线程中执行
+    tokio::task::spawn_blocking(move || {
+        let mut result = copy_database_file_cold(&db_path, &backup_dir, true)?;
+        if compress {
+            compress_backup_result(&mut result);
+        }
 
-    if let Some(max_auto_backups) = max_auto_backups
-        && let Err(e) =
-            cleanup_auto_backup_files(&backup_dir, "reina_manager_auto_", ".db", max_auto_backups)
-    {
-        log::warn!("清理旧数据库自动备份失败: {}", e);
-    }
+        if let Some(max_auto_backups) = max_auto_backups
+            && let Err(e) = cleanup_auto_backup_files(
+                &backup_dir,
+                "reina_manager_auto_",
+                DB_BACKUP_EXTENSIONS,
+                max_auto_backups,
+            )
+        {
+            log::warn!("清理旧数据库自动备份失败: {}", e);
+        }
+        result.pruned_backups = apply_backup_retention(&backup_dir, retention);
 
-    Ok(result)
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("数据库冷备份失败: {}", e))?
 }
 
 fn copy_database_file_cold(
@@ -132,7 +289,8 @@ fn copy_database_file_cold(
     };
     let backup_file_path = backup_dir.join(&backup_name);
 
-    fs::copy(db_path, &backup_file_path).map_err(|e| format!("数据库冷备份失败: {}", e))?;
+    retry_on_lock(db_path, || fs::copy(db_path, &backup_file_path))
+        .map_err(|e| format!("数据库冷备份失败: {}", e))?;
 
     let path_str = backup_file_path.to_string_lossy().to_string();
     log::info!("数据库冷备份成功: {}", path_str);
@@ -141,23 +299,141 @@ fn copy_database_file_cold(
         success: true,
         path: Some(path_str),
         message: "数据库备份成功".to_string(),
+        pruned_backups: 0,
     })
 }
 
+/// 导入的数据库必须包含的业务表，旧版（tauri-plugin-sql 时期）的数据库同样有这两张表
+const REQUIRED_TABLES: &[&str] = &["games", "user"];
+
+/// `PRAGMA integrity_check` 最多返回的问题条数
+const INTEGRITY_CHECK_MAX_ERRORS: usize = 10;
+
+/// 只读打开待导入的数据库，确认文件未损坏且是本应用的数据库
+///
+/// - `PRAGMA integrity_check` 必须返回 `ok`
+/// - 必须包含 `REQUIRED_TABLES` 中的表
+/// - `seaql_migrations` 中不能有当前版本不认识的迁移（来自更新版本的数据库），
+///   没有迁移记录的旧版数据库会在导入后重连时由基线迁移升级
+async fn validate_import_database(path: &Path) -> Result<(), String> {
+    let db_url = std::path::absolute(path)
+        .ok()
+        .and_then(|path| Url::from_file_path(path).ok())
+        .ok_or_else(|| format!("无效的数据库路径: {}", path.display()))?;
+    let mut options = ConnectOptions::new(format!("sqlite:{}?mode=ro", db_url.path()));
+    options.max_connections(1).sqlx_logging(false);
+    let conn = Database::connect(options)
+        .await
+        .map_err(|e| format!("无法打开待导入的数据库: {}", e))?;
+
+    let result = check_import_database(&conn).await;
+    if let Err(e) = close_connection(conn).await {
+        log::warn!("关闭待导入数据库的连接失败: {}", e);
+    }
+    result
+}
+
+async fn check_import_database(conn: &DatabaseConnection) -> Result<(), String> {
+    let problems: Vec<String> = conn
+        .query_all(Statement::from_string(
+            DbBackend::Sqlite,
+            format!("PRAGMA integrity_check({})", INTEGRITY_CHECK_MAX_ERRORS),
+        ))
+        .await
+        .map_err(|e| format!("数据库文件已损坏或不是 SQLite 数据库: {}", e))?
+        .iter()
+        .filter_map(|row| row.try_get_by_index::<String>(0).ok())
+        .filter(|message| message != "ok")
+        .collect();
+    if !problems.is_empty() {
+        return Err(format!(
+            "数据库完整性检查未通过，文件可能已损坏: {}",
+            problems.join("; ")
+        ));
+    }
+
+    let tables: HashSet<String> = conn
+        .query_all(Statement::from_string(
+            DbBackend::Sqlite,
+            "SELECT name FROM sqlite_master WHERE type = 'table'",
+        ))
+        .await
+        .map_err(|e| format!("读取数据库表结构失败: {}", e))?
+        .iter()
+        .filter_map(|row| row.try_get_by_index::<String>(0).ok())
+        .collect();
+    let missing: Vec<&str> = REQUIRED_TABLES
+        .iter()
+        .copied()
+        .filter(|table| !tables.contains(*table))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "该文件不是 ReinaManager 的数据库（缺少数据表: {}）",
+            missing.join(", ")
+        ));
+    }
+
+    if !tables.contains("seaql_migrations") {
+        log::info!("待导入的数据库没有 seaql_migrations，按旧版数据库处理");
+        return Ok(());
+    }
+    let known: HashSet<String> = Migrator::migrations()
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect();
+    let unknown: Vec<String> = conn
+        .query_all(Statement::from_string(
+            DbBackend::Sqlite,
+            "SELECT version FROM seaql_migrations ORDER BY version",
+        ))
+        .await
+        .map_err(|e| format!("读取数据库迁移记录失败: {}", e))?
+        .iter()
+        .filter_map(|row| row.try_get_by_index::<String>(0).ok())
+        .filter(|version| !known.contains(version))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "该数据库来自更新版本的 ReinaManager，请先升级应用后再导入（未知迁移: {}）",
+            unknown.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// 离开作用域时删除的临时文件
+struct TempFileGuard(PathBuf);
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
 /// 导入数据库文件（覆盖现有数据库）
 ///
 /// # Arguments
 ///
-/// * `source_path` - 要导入的数据库文件路径
+/// * `source_path` - 要导入的数据库文件路径，可以是 `.db` 或压缩备份 `.db.7z`
 ///
 /// # Returns
 ///
-/// 导入结果，包含备份路径（如果备份成功）
+/// 导入结果，包含备份路径（如果备份成功）。导入后原地重建数据库连接，无需重启应用。
+/// 有游戏正在运行时拒绝导入，否则监控会把会话与统计写进导入后的数据库。
 #[command]
 pub async fn import_database(
+    app: AppHandle,
     source_path: String,
-    db: State<'_, DatabaseConnection>,
+    db_state: State<'_, DbState>,
+    cover_state: State<'_, DownloadState>,
 ) -> Result<ImportResult, String> {
+    let _timer = CommandTimer::start("import_database");
+    if !active_sessions().is_empty() {
+        return Err("有游戏正在运行，请先退出游戏再导入数据库".to_string());
+    }
+
+    let db = db_state.get();
     let src_path = Path::new(&source_path);
 
     // 检查源文件是否存在
@@ -166,9 +442,16 @@ pub async fn import_database(
     }
 
     // 检查文件扩展名
-    if src_path.extension().and_then(|e| e.to_str()) != Some("db") {
-        return Err("无效的数据库文件，请选择 .db 文件".to_string());
-    }
+    let compressed = match src_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        Some("db") => false,
+        Some("7z") => true,
+        _ => return Err("无效的数据库文件，请选择 .db 或 .db.7z 文件".to_string()),
+    };
 
     // 获取当前数据库路径（自动判断便携模式）
     let target_db_path = get_db_path()?;
@@ -180,6 +463,35 @@ pub async fn import_database(
         return Err("不能导入当前正在使用的数据库文件".to_string());
     }
 
+    // 压缩备份先解压到数据库目录下的临时文件，解压失败时不会关闭连接或改动任何数据
+    let extracted = if compressed {
+        let temp_path =
+            target_db_path.with_file_name(format!("import_{}.db.tmp", std::process::id()));
+        let archive_path = src_path.to_path_buf();
+        let extract_path = temp_path.clone();
+        let guard = TempFileGuard(temp_path);
+        let found = tokio::task::spawn_blocking(move || {
+            extract_first_file_from_7z(&archive_path, ".db", &extract_path)
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("解压数据库备份失败: {}", e))?;
+        match found {
+            Ok(true) => Some(guard),
+            Ok(false) => return Err("压缩包中没有找到 .db 数据库文件".to_string()),
+            Err(e) => return Err(format!("解压数据库备份失败: {}", e)),
+        }
+    } else {
+        None
+    };
+    let copy_source = extracted
+        .as_ref()
+        .map_or(src_path, |guard| guard.0.as_path());
+
+    // 校验通过前不关闭连接，也不改动任何文件
+    validate_import_database(copy_source).await?;
+    log::info!("待导入的数据库校验通过: {}", copy_source.display());
+
     // 步骤1：关闭连接前读取备份目录配置，关闭后无法再查询设置
     let backup_dir = resolve_backup_dir(&db).await?;
 
@@ -187,33 +499,57 @@ pub async fn import_database(
     backup_custom_covers_archive(&db, false).await?;
 
     // 步骤3：关闭数据库连接，后续对数据库文件做冷备份和覆盖
-    close_connection(db.inner().clone())
+    close_connection(db.clone())
         .await
         .map_err(|e| format!("关闭数据库连接失败: {}", e))?;
     log::info!("数据库连接已关闭，准备冷备份和导入");
 
-    // 步骤4：冷备份当前数据库文件，避免覆盖后无法回滚
-    let result_backup_path = match copy_database_file_cold(&target_db_path, &backup_dir, false) {
-        Ok(result) => result.path,
-        Err(e) => {
-            log::warn!("导入前备份失败: {}，继续导入", e);
-            None
-        }
-    };
+    // 文件被占用时会阻塞重试，冷备份与覆盖放到阻塞线程中执行
+    let copy_source = copy_source.to_path_buf();
+    let copy_target = target_db_path.clone();
+    let imported = tokio::task::spawn_blocking(move || {
+        let target_db_path = copy_target;
+        // 步骤4：冷备份当前数据库文件，避免覆盖后无法回滚
+        let result_backup_path = match copy_database_file_cold(&target_db_path, &backup_dir, false)
+        {
+            Ok(result) => result.path,
+            Err(e) => {
+                log::warn!("导入前备份失败: {}，继续导入", e);
+                None
+            }
+        };
+
+        // 步骤5：删除整个封面目录。云端封面缓存会按新数据库重新下载，
+        // 自定义封面已单独备份，不自动恢复到新库。
+        delete_all_covers_dir()?;
+        log::info!("导入数据库前已清空封面目录");
+
+        // 步骤6：复制文件覆盖现有数据库
+        retry_on_lock(&target_db_path, || fs::copy(&copy_source, &target_db_path))
+            .map_err(|e| format!("复制数据库文件失败: {}", e))?;
+        log::info!("数据库文件已复制: {} -> {:?}", source_path, target_db_path);
+        Ok::<_, String>(result_backup_path)
+    })
+    .await
+    .map_err(|e| format!("导入数据库失败: {}", e))
+    .and_then(|result| result);
 
-    // 步骤5：删除整个封面目录。云端封面缓存会按新数据库重新下载，
-    // 自定义封面已单独备份，不自动恢复到新库。
-    delete_all_covers_dir()?;
-    log::info!("导入数据库前已清空封面目录");
+    // 步骤7：无论导入是否成功都重新连接数据库（失败时连回原数据库），并替换托管状态中的连接
+    if imported.is_ok() {
+        cover_state.reset_for_new_database().await;
+    }
+    reconnect(&db_state).await?;
+    let result_backup_path = imported?;
 
-    // 步骤6：复制文件覆盖现有数据库
-    fs::copy(src_path, &target_db_path).map_err(|e| format!("复制数据库文件失败: {}", e))?;
-    log::info!("数据库文件已复制: {} -> {:?}", source_path, target_db_path);
+    // 按新数据库中的设置重新应用启动时初始化的后台功能
+    load_retry_policy(&db_state.get()).await;
+    init_screenshot_hotkey(app.clone()).await;
+    init_session_hotkeys(app.clone()).await;
+    init_http_api(app).await;
 
-    // 导入成功，前端将负责重启应用以重新连接数据库
     Ok(ImportResult {
         success: true,
-        message: "数据库导入成功，已备份自定义封面并清空封面缓存，应用将自动重启".to_string(),
+        message: "数据库导入成功，已备份自定义封面并清空封面缓存".to_string(),
         backup_path: result_backup_path,
     })
 }
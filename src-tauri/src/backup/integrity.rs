@@ -0,0 +1,126 @@
+//! 存档备份完整性校验
+//!
+//! 创建备份时把压缩包的 SHA-256 写入 savedata 表，恢复前重新计算并比对，
+//! 避免磁盘静默损坏或同步盘冲突产生的坏包覆盖用户当前的完好存档。
+
+use super::savedata::{resolve_savedata_backup_root, savedata_backup_path};
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::utils::metrics::CommandTimer;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// 旧备份补算哈希的结果
+#[derive(Debug, Serialize)]
+pub struct RehashResult {
+    /// 成功补算的记录数
+    pub hashed: usize,
+    /// 备份文件已不存在的记录 ID
+    pub missing: Vec<i32>,
+    /// 读取失败的记录 ID
+    pub failed: Vec<i32>,
+}
+
+/// 计算文件的 SHA-256（十六进制小写）
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 在阻塞线程池中计算文件的 SHA-256，避免大文件读取占用异步运行时
+pub async fn sha256_file_async(path: PathBuf) -> io::Result<String> {
+    tokio::task::spawn_blocking(move || sha256_file(&path))
+        .await
+        .map_err(io::Error::other)?
+}
+
+/// 校验备份文件与记录中的 SHA-256 是否一致
+///
+/// 没有可比对的记录（旧备份或手动放入的文件）时直接通过。
+pub async fn verify_backup_file(db: &DatabaseConnection, backup_path: &Path) -> Result<(), String> {
+    let Some(file_name) = backup_path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(());
+    };
+    let Some(expected) = GamesRepository::find_savedata_record_by_file(db, file_name)
+        .await
+        .map_err(|e| format!("获取备份记录失败: {}", e))?
+        .and_then(|record| record.sha256)
+    else {
+        log::debug!("备份没有 SHA-256 记录，跳过校验: {}", file_name);
+        return Ok(());
+    };
+
+    let actual = sha256_file_async(backup_path.to_path_buf())
+        .await
+        .map_err(|e| format!("读取备份文件失败: {}", e))?;
+    if !actual.eq_ignore_ascii_case(&expected) {
+        log::error!(
+            "存档备份校验失败 file={} expected={} actual={}",
+            file_name,
+            expected,
+            actual
+        );
+        return Err(
+            "备份文件校验失败，文件可能已损坏或被修改，已取消恢复以保护当前存档".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// 为没有 SHA-256 的旧备份记录补算哈希
+///
+/// 补算时无法判断旧文件是否已经损坏，只能以当前内容作为之后比对的基准。
+///
+/// # Returns
+/// * `Result<RehashResult, String>` - 补算结果或错误消息
+#[tauri::command]
+pub async fn rehash_existing_backups(db: State<'_, DbState>) -> Result<RehashResult, String> {
+    let _timer = CommandTimer::start("rehash_existing_backups");
+    let db = db.get();
+    let records = GamesRepository::get_unhashed_savedata_records(&db)
+        .await
+        .map_err(|e| format!("获取备份记录失败: {}", e))?;
+    let backup_root = resolve_savedata_backup_root(&db).await?;
+
+    let mut result = RehashResult {
+        hashed: 0,
+        missing: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for record in records {
+        let path = savedata_backup_path(&backup_root, record.game_id, &record.file);
+        if !path.is_file() {
+            result.missing.push(record.id);
+            continue;
+        }
+
+        match sha256_file_async(path.clone()).await {
+            Ok(sha256) => {
+                GamesRepository::set_savedata_sha256(&db, record.id, sha256)
+                    .await
+                    .map_err(|e| format!("更新备份记录失败: {}", e))?;
+                result.hashed += 1;
+            }
+            Err(e) => {
+                log::warn!("计算备份哈希失败 {}: {}", path.display(), e);
+                result.failed.push(record.id);
+            }
+        }
+    }
+
+    log::info!(
+        "旧备份哈希补算完成 hashed={} missing={} failed={}",
+        result.hashed,
+        result.missing.len(),
+        result.failed.len()
+    );
+    Ok(result)
+}
@@ -0,0 +1,399 @@
+//! 游戏库 JSON 导出与导入
+//!
+//! 把游戏、合集、统计、存档索引和设置序列化为单个带版本号的 JSON 文件，
+//! 作为独立于 .db 文件的文本迁移方式。导入只允许写入空数据库，并保留原有 ID。
+
+use crate::backup::common::BackupResult;
+use crate::database::db::DbState;
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::entity::prelude::*;
+use crate::entity::{
+    collections, game_collection_link, game_sessions, game_statistics, games, savedata, user,
+};
+use crate::utils::metrics::CommandTimer;
+use sea_orm::{
+    ActiveModelTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, IntoActiveModel,
+    PaginatorTrait, QueryOrder, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::{State, command};
+
+/// 当前导出格式版本，结构发生不兼容变化时递增
+const LIBRARY_EXPORT_VERSION: u32 = 1;
+
+/// 每条 INSERT 语句写入的行数，避免超出 SQLite 参数数量上限
+const INSERT_CHUNK_SIZE: usize = 200;
+
+/// 游戏库导出文件
+///
+/// 版本 1 之后各表新增的列在实体中都标注了 `#[serde(default)]`，旧版本的导出文件缺少这些字段时取默认值，
+/// 因此新增列不必递增版本号。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LibraryExport {
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: i64,
+    pub games: Vec<games::Model>,
+    pub collections: Vec<collections::Model>,
+    pub game_collection_links: Vec<game_collection_link::Model>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<Vec<game_statistics::Model>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sessions: Option<Vec<game_sessions::Model>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub savedata: Option<Vec<savedata::Model>>,
+    /// 用户设置（不含 BGM / VNDB 令牌）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<user::Model>,
+}
+
+/// 游戏库导入结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LibraryImportResult {
+    pub games: usize,
+    pub collections: usize,
+    pub statistics: usize,
+    pub sessions: usize,
+    pub savedata_records: usize,
+    pub settings_restored: bool,
+}
+
+/// 合集按父子关系排序，保证父合集先于子合集写入
+fn order_collections(collections: Vec<collections::Model>) -> Vec<collections::Model> {
+    let ids: HashSet<i32> = collections.iter().map(|c| c.id).collect();
+    let mut inserted = HashSet::new();
+    let mut pending = collections;
+    let mut ordered = Vec::with_capacity(pending.len());
+
+    while !pending.is_empty() {
+        let (ready, rest): (Vec<_>, Vec<_>) = pending.into_iter().partition(|c| {
+            c.parent_id
+                .is_none_or(|parent| inserted.contains(&parent) || !ids.contains(&parent))
+        });
+        // 剩余条目构成环时按原顺序写入，交给数据库约束处理
+        if ready.is_empty() {
+            ordered.extend(rest);
+            break;
+        }
+        inserted.extend(ready.iter().map(|c| c.id));
+        ordered.extend(ready);
+        pending = rest;
+    }
+
+    ordered
+}
+
+/// 数据库中是否没有任何游戏、合集、统计、会话与存档备份记录
+///
+/// 旧版本删除游戏时不级联，统计与会话可能在游戏删除后残留，导入保留原有 ID，残留行会与导入的数据冲突。
+async fn is_empty_library(db: &DatabaseConnection) -> Result<bool, DbErr> {
+    Ok(Games::find().count(db).await? == 0
+        && Collections::find().count(db).await? == 0
+        && GameStatistics::find().count(db).await? == 0
+        && GameSessions::find().count(db).await? == 0
+        && Savedata::find().count(db).await? == 0)
+}
+
+/// 分块批量插入，保留模型中的主键
+async fn insert_models<E>(txn: &DatabaseTransaction, models: Vec<E::Model>) -> Result<usize, DbErr>
+where
+    E: EntityTrait,
+    E::Model: IntoActiveModel<E::ActiveModel>,
+{
+    let total = models.len();
+    let mut models = models.into_iter().peekable();
+
+    while models.peek().is_some() {
+        let chunk: Vec<E::ActiveModel> = models
+            .by_ref()
+            .take(INSERT_CHUNK_SIZE)
+            .map(|model| model.into_active_model().reset_all())
+            .collect();
+        E::insert_many(chunk).exec_without_returning(txn).await?;
+    }
+
+    Ok(total)
+}
+
+/// 导出整个游戏库为 JSON 文件
+///
+/// # Arguments
+/// * `path` - 导出文件路径
+/// * `include_stats` - 是否包含游戏统计与会话记录
+/// * `include_savedata_index` - 是否包含存档备份记录（仅索引，不含备份文件本身）
+///
+/// # Returns
+/// * `Result<BackupResult, String>` - 导出结果或错误消息
+#[command]
+pub async fn export_library(
+    db: State<'_, DbState>,
+    path: String,
+    include_stats: bool,
+    include_savedata_index: bool,
+) -> Result<BackupResult, String> {
+    let _timer = CommandTimer::start("export_library");
+    export_library_to(&db.get(), path, include_stats, include_savedata_index).await
+}
+
+/// 导出整个游戏库为 JSON 文件，供命令与命令行模式共用
+pub async fn export_library_to(
+    db: &DatabaseConnection,
+    path: String,
+    include_stats: bool,
+    include_savedata_index: bool,
+) -> Result<BackupResult, String> {
+    let games = Games::find()
+        .order_by_asc(games::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("读取游戏数据失败: {}", e))?;
+    let collections = Collections::find()
+        .order_by_asc(collections::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("读取合集数据失败: {}", e))?;
+    let game_collection_links = GameCollectionLink::find()
+        .order_by_asc(game_collection_link::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("读取合集关联失败: {}", e))?;
+
+    let (statistics, sessions) = if include_stats {
+        let statistics = GameStatistics::find()
+            .all(db)
+            .await
+            .map_err(|e| format!("读取游戏统计失败: {}", e))?;
+        let sessions = GameSessions::find()
+            .order_by_asc(game_sessions::Column::SessionId)
+            .all(db)
+            .await
+            .map_err(|e| format!("读取游戏会话失败: {}", e))?;
+        (Some(statistics), Some(sessions))
+    } else {
+        (None, None)
+    };
+
+    let savedata = if include_savedata_index {
+        Some(
+            Savedata::find()
+                .order_by_asc(savedata::Column::Id)
+                .all(db)
+                .await
+                .map_err(|e| format!("读取存档记录失败: {}", e))?,
+        )
+    } else {
+        None
+    };
+
+    // 令牌属于账号凭据，不写入可能被分享或同步的导出文件
+    let mut settings = SettingsRepository::get_all_settings(db)
+        .await
+        .map_err(|e| format!("读取用户设置失败: {}", e))?;
+    settings.bgm_auth = None;
+    settings.vndb_token = None;
+
+    let export = LibraryExport {
+        version: LIBRARY_EXPORT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().timestamp(),
+        games,
+        collections,
+        game_collection_links,
+        statistics,
+        sessions,
+        savedata,
+        settings: Some(settings),
+    };
+
+    let json =
+        serde_json::to_string_pretty(&export).map_err(|e| format!("序列化游戏库失败: {}", e))?;
+    if let Some(parent) = Path::new(&path).parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+    fs::write(&path, json).map_err(|e| format!("写入导出文件失败: {}", e))?;
+
+    log::info!(
+        "游戏库导出成功: {} games={} collections={}",
+        path,
+        export.games.len(),
+        export.collections.len()
+    );
+
+    Ok(BackupResult {
+        success: true,
+        path: Some(path),
+        message: "游戏库导出成功".to_string(),
+        pruned_backups: 0,
+    })
+}
+
+/// 从 JSON 导出文件恢复游戏库
+///
+/// 只能导入到没有游戏、合集、游玩记录与存档备份记录的空数据库；所有数据在同一事务内写入，
+/// 设置会覆盖当前设置，但保留当前的 BGM / VNDB 令牌。
+///
+/// # Arguments
+/// * `path` - 导出文件路径
+///
+/// # Returns
+/// * `Result<LibraryImportResult, String>` - 导入结果或错误消息
+#[command]
+pub async fn import_library(
+    db: State<'_, DbState>,
+    path: String,
+) -> Result<LibraryImportResult, String> {
+    let _timer = CommandTimer::start("import_library");
+    let db = &db.get();
+
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let export: LibraryExport = serde_json::from_str(content.trim_start_matches('\u{feff}'))
+        .map_err(|e| format!("解析导入文件失败: {}", e))?;
+
+    if export.version > LIBRARY_EXPORT_VERSION {
+        return Err(format!(
+            "导入文件版本 {} 高于当前支持的版本 {}，请升级应用后重试",
+            export.version, LIBRARY_EXPORT_VERSION
+        ));
+    }
+
+    if !is_empty_library(db)
+        .await
+        .map_err(|e| format!("检查现有数据失败: {}", e))?
+    {
+        return Err("只能导入到空数据库，请先清空游戏、合集、游玩记录与存档备份记录".to_string());
+    }
+
+    // 令牌保存在系统凭据管理器时数据库中对应的列为空，保持原样即可
+    let current_settings = SettingsRepository::get_stored_settings(db)
+        .await
+        .map_err(|e| format!("读取用户设置失败: {}", e))?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+
+    let games = insert_models::<Games>(&txn, export.games)
+        .await
+        .map_err(|e| format!("写入游戏数据失败: {}", e))?;
+    let collections = insert_models::<Collections>(&txn, order_collections(export.collections))
+        .await
+        .map_err(|e| format!("写入合集数据失败: {}", e))?;
+    insert_models::<GameCollectionLink>(&txn, export.game_collection_links)
+        .await
+        .map_err(|e| format!("写入合集关联失败: {}", e))?;
+
+    let statistics = insert_models::<GameStatistics>(&txn, export.statistics.unwrap_or_default())
+        .await
+        .map_err(|e| format!("写入游戏统计失败: {}", e))?;
+    let sessions = insert_models::<GameSessions>(&txn, export.sessions.unwrap_or_default())
+        .await
+        .map_err(|e| format!("写入游戏会话失败: {}", e))?;
+    let savedata_records = insert_models::<Savedata>(&txn, export.savedata.unwrap_or_default())
+        .await
+        .map_err(|e| format!("写入存档记录失败: {}", e))?;
+
+    let settings_restored = match export.settings {
+        Some(settings) => {
+            let settings = user::Model {
+                id: current_settings.id,
+                bgm_auth: current_settings.bgm_auth,
+                vndb_token: current_settings.vndb_token,
+                ..settings
+            };
+            settings
+                .into_active_model()
+                .reset_all()
+                .update(&txn)
+                .await
+                .map_err(|e| format!("写入用户设置失败: {}", e))?;
+            true
+        }
+        None => false,
+    };
+
+    txn.commit()
+        .await
+        .map_err(|e| format!("提交事务失败: {}", e))?;
+
+    log::info!(
+        "游戏库导入成功: {} games={} collections={} sessions={}",
+        path,
+        games,
+        collections,
+        sessions
+    );
+
+    Ok(LibraryImportResult {
+        games,
+        collections,
+        statistics,
+        sessions,
+        savedata_records,
+        settings_restored,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 最初的版本 1 导出格式：各表只有当时的列
+    const V1_EXPORT: &str = r#"{
+        "version": 1,
+        "app_version": "0.9.0",
+        "exported_at": 1760000000,
+        "games": [{
+            "id": 1, "bgm_id": "123", "vndb_id": null, "ymgal_id": null, "kun_id": null,
+            "id_type": "bgm", "date": "2020-01-01", "localpath": "D:/Games/A/a.exe",
+            "savepath": null, "autosave": 0, "maxbackups": 20, "clear": 1, "le_launch": 0,
+            "magpie": 0, "vndb_data": null, "bgm_data": null, "ymgal_data": null,
+            "kun_data": null, "custom_data": null, "created_at": 1700000000, "updated_at": null
+        }],
+        "collections": [{
+            "id": 1, "name": "分组", "parent_id": null, "sort_order": 0, "icon": null,
+            "created_at": null, "updated_at": null
+        }],
+        "game_collection_links": [
+            {"id": 1, "game_id": 1, "collection_id": 1, "sort_order": 0, "created_at": null}
+        ],
+        "statistics": [
+            {"game_id": 1, "total_time": 90, "session_count": 1, "last_played": 1700003600, "daily_stats": null}
+        ],
+        "sessions": [{
+            "session_id": 1, "game_id": 1, "start_time": 1700000000, "end_time": 1700005400,
+            "duration": 90, "date": "2023-11-14", "tags": null
+        }],
+        "savedata": [
+            {"id": 1, "game_id": 1, "file": "savedata_1_1700000000.7z", "backup_time": 1700000000, "file_size": 1024}
+        ],
+        "settings": {
+            "id": 1, "bgm_auth": null, "vndb_token": null, "save_root_path": null,
+            "db_backup_path": null, "le_path": null, "magpie_path": null
+        }
+    }"#;
+
+    #[test]
+    fn imports_version_1_exports() {
+        let export: LibraryExport = serde_json::from_str(V1_EXPORT).unwrap();
+        assert_eq!(export.games[0].launch_uri, None);
+        let session = &export.sessions.as_ref().unwrap()[0];
+        assert!(!session.manual);
+        assert_eq!(session.note, None);
+        assert!(!export.savedata.as_ref().unwrap()[0].auto);
+        assert_eq!(export.settings.as_ref().unwrap().use_trash, None);
+
+        // 用当前格式重新导出后仍能读回相同的数据
+        let json = serde_json::to_string(&export).unwrap();
+        let reimported: LibraryExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(reimported.games, export.games);
+        assert_eq!(reimported.sessions, export.sessions);
+        assert_eq!(reimported.savedata, export.savedata);
+        assert_eq!(reimported.settings, export.settings);
+    }
+}
@@ -0,0 +1,236 @@
+//! 存档备份根目录的软配额
+//!
+//! 目录用量通过遍历备份根目录计算，结果缓存一段时间，避免每次备份都重新扫描。
+//! 新备份写入后累加到缓存中，超过缓存有效期再重新扫描校正。
+
+use super::savedata::{delete_backup_record, resolve_savedata_backup_root, savedata_backup_path};
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::savedata_quota::SavedataQuota;
+use crate::utils::metrics::CommandTimer;
+use parking_lot::Mutex;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Runtime, State};
+use walkdir::WalkDir;
+
+/// 配额警告事件名
+pub const QUOTA_WARNING_EVENT: &str = "savedata-quota-warning";
+
+/// 用量缓存有效期
+const USAGE_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+struct UsageCache {
+    root: PathBuf,
+    used_bytes: u64,
+    refreshed_at: Instant,
+}
+
+static USAGE_CACHE: Mutex<Option<UsageCache>> = Mutex::new(None);
+
+/// 备份目录用量
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedataUsage {
+    pub used_bytes: u64,
+    /// 未设置配额时为 None
+    pub limit_bytes: Option<u64>,
+}
+
+/// `savedata-quota-warning` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaWarningEvent {
+    pub used_bytes: u64,
+    pub limit_bytes: u64,
+    /// 本次自动删除的检查点数量
+    pub pruned_count: usize,
+    pub over_limit: bool,
+}
+
+/// 遍历目录计算文件总大小
+fn scan_dir_size(root: &Path) -> u64 {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// 获取备份目录用量，缓存过期、根目录变化或 `refresh` 为 true 时重新扫描
+fn cached_usage(root: &Path, refresh: bool) -> u64 {
+    let mut cache = USAGE_CACHE.lock();
+    if !refresh
+        && let Some(cached) = cache.as_ref()
+        && cached.root == root
+        && cached.refreshed_at.elapsed() < USAGE_CACHE_TTL
+    {
+        return cached.used_bytes;
+    }
+
+    let used_bytes = scan_dir_size(root);
+    *cache = Some(UsageCache {
+        root: root.to_path_buf(),
+        used_bytes,
+        refreshed_at: Instant::now(),
+    });
+    used_bytes
+}
+
+/// 调整缓存中的用量，缓存已过期或不属于该根目录时返回 None
+fn adjust_cached_usage(root: &Path, added: u64, removed: u64) -> Option<u64> {
+    let mut cache = USAGE_CACHE.lock();
+    let cached = cache
+        .as_mut()
+        .filter(|cached| cached.root == root && cached.refreshed_at.elapsed() < USAGE_CACHE_TTL)?;
+    cached.used_bytes = cached
+        .used_bytes
+        .saturating_add(added)
+        .saturating_sub(removed);
+    Some(cached.used_bytes)
+}
+
+/// 删除最旧的自动检查点直到用量回到配额以内，返回删除数量与释放的字节数
+///
+/// `keep_file` 为本次刚创建的备份文件名，不参与清理
+async fn prune_auto_checkpoints(
+    db: &DatabaseConnection,
+    root: &Path,
+    mut used_bytes: u64,
+    limit_bytes: u64,
+    keep_file: &str,
+) -> Result<(usize, u64), String> {
+    let records = GamesRepository::get_auto_savedata_records(db)
+        .await
+        .map_err(|e| format!("获取自动检查点记录失败: {}", e))?;
+
+    let mut pruned = 0;
+    let mut freed = 0u64;
+    for record in records {
+        if used_bytes <= limit_bytes {
+            break;
+        }
+        if record.file == keep_file {
+            continue;
+        }
+
+        let path = savedata_backup_path(root, record.game_id, &record.file);
+        let size = tokio::fs::metadata(&path)
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        if let Some(error) = delete_backup_record(db, &path, record.id, false).await {
+            log::warn!("配额清理删除检查点失败: {}", error);
+            continue;
+        }
+
+        used_bytes = used_bytes.saturating_sub(size);
+        freed += size;
+        pruned += 1;
+    }
+
+    Ok((pruned, freed))
+}
+
+/// 新备份写入后检查配额
+///
+/// 配额是软限制：不会阻止备份，只在接近或超出时发出事件，并按设置清理最旧的自动检查点。
+pub async fn enforce_savedata_quota<R: Runtime>(
+    app: &AppHandle<R>,
+    db: &DatabaseConnection,
+    root: &Path,
+    new_backup_file: &str,
+    new_backup_size: u64,
+) -> Result<(), String> {
+    let Some(quota) = db.get_settings().await?.savedata_quota else {
+        return Ok(());
+    };
+    let SavedataQuota {
+        limit_bytes,
+        warn_ratio,
+        auto_prune,
+    } = quota;
+    if limit_bytes == 0 {
+        return Ok(());
+    }
+
+    // 缓存有效时累加新备份大小；否则重新扫描，扫描结果已包含新备份。
+    // 扫描会递归遍历整个备份目录，放到阻塞线程中执行
+    let scan_root = root.to_path_buf();
+    let mut used_bytes = tokio::task::spawn_blocking(move || {
+        adjust_cached_usage(&scan_root, new_backup_size, 0)
+            .unwrap_or_else(|| cached_usage(&scan_root, true))
+    })
+    .await
+    .map_err(|e| format!("计算备份目录用量失败: {}", e))?;
+
+    let mut pruned_count = 0;
+    if used_bytes > limit_bytes && auto_prune {
+        let (pruned, freed) =
+            prune_auto_checkpoints(db, root, used_bytes, limit_bytes, new_backup_file).await?;
+        pruned_count = pruned;
+        used_bytes =
+            adjust_cached_usage(root, 0, freed).unwrap_or(used_bytes.saturating_sub(freed));
+        log::info!(
+            "存档备份配额清理完成 pruned={} freed={} bytes used={} limit={}",
+            pruned,
+            freed,
+            used_bytes,
+            limit_bytes
+        );
+    }
+
+    if used_bytes as f64 >= limit_bytes as f64 * warn_ratio.clamp(0.0, 1.0) {
+        let event = QuotaWarningEvent {
+            used_bytes,
+            limit_bytes,
+            pruned_count,
+            over_limit: used_bytes > limit_bytes,
+        };
+        log::warn!(
+            "存档备份目录接近配额 used={} limit={}",
+            used_bytes,
+            limit_bytes
+        );
+        if let Err(e) = app.emit(QUOTA_WARNING_EVENT, &event) {
+            log::warn!("无法发送 {} 事件: {}", QUOTA_WARNING_EVENT, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// 获取存档备份目录的当前用量
+///
+/// # Arguments
+/// * `refresh` - 为 true 时忽略缓存重新扫描
+///
+/// # Returns
+/// * `Result<SavedataUsage, String>` - 用量或错误消息
+#[tauri::command]
+pub async fn get_savedata_usage(
+    db: State<'_, DbState>,
+    refresh: Option<bool>,
+) -> Result<SavedataUsage, String> {
+    let _timer = CommandTimer::start("get_savedata_usage");
+    let db = db.get();
+    let root = resolve_savedata_backup_root(&db).await?;
+    let limit_bytes = db
+        .get_settings()
+        .await?
+        .savedata_quota
+        .map(|quota| quota.limit_bytes);
+    let refresh = refresh.unwrap_or(false);
+    let used_bytes = tokio::task::spawn_blocking(move || cached_usage(&root, refresh))
+        .await
+        .map_err(|e| format!("计算备份目录用量失败: {}", e))?;
+
+    Ok(SavedataUsage {
+        used_bytes,
+        limit_bytes,
+    })
+}
@@ -0,0 +1,183 @@
+//! 存档恢复预览
+//!
+//! 恢复前列出备份压缩包内的文件，并可与目标目录对比，提前告知哪些文件会被覆盖或删除。
+//! 恢复采用覆盖模式（先清空目标目录），因此目标目录中备份里没有的文件也会被列为删除。
+
+use super::archive::{ArchiveEntryInfo, is_7z_archive_encrypted, list_7z_archive_entries};
+use crate::utils::metrics::CommandTimer;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// 备份内的单个文件
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupEntry {
+    /// 使用 `/` 分隔的相对路径
+    pub path: String,
+    pub size: u64,
+    /// 修改时间（Unix 时间戳，秒）
+    pub modified: Option<i64>,
+    pub is_directory: bool,
+}
+
+/// 会被覆盖的本地文件
+#[derive(Debug, Clone, Serialize)]
+pub struct OverwrittenFile {
+    pub path: String,
+    pub backup_size: u64,
+    pub backup_modified: Option<i64>,
+    pub local_size: u64,
+    pub local_modified: Option<i64>,
+    /// 本地文件比备份中的更新，恢复会丢失较新的进度
+    pub local_newer: bool,
+}
+
+/// 恢复冲突报告（dry-run）
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreConflictReport {
+    pub target_path: String,
+    /// 本地已存在、恢复时会被覆盖的文件
+    pub overwritten: Vec<OverwrittenFile>,
+    /// 本地不存在、恢复时新增的文件
+    pub added: Vec<String>,
+    /// 备份中没有、恢复时会被删除的本地文件
+    pub removed: Vec<String>,
+}
+
+/// 备份内容列表
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupContents {
+    pub entries: Vec<BackupEntry>,
+    pub total_size: u64,
+    /// 仅在提供目标路径时生成
+    pub conflicts: Option<RestoreConflictReport>,
+}
+
+/// 本地文件的大小与修改时间
+struct LocalFile {
+    size: u64,
+    modified: Option<i64>,
+}
+
+/// 读取目标目录下的所有文件，键为使用 `/` 分隔的相对路径
+fn read_local_files(root: &Path) -> BTreeMap<String, LocalFile> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry
+                .path()
+                .strip_prefix(root)
+                .ok()?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs() as i64);
+            Some((
+                relative,
+                LocalFile {
+                    size: metadata.len(),
+                    modified,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// 对比备份条目与目标目录
+fn build_conflict_report(entries: &[ArchiveEntryInfo], target: &Path) -> RestoreConflictReport {
+    let mut local = if target.exists() {
+        read_local_files(target)
+    } else {
+        BTreeMap::new()
+    };
+
+    let mut overwritten = Vec::new();
+    let mut added = Vec::new();
+    for entry in entries.iter().filter(|entry| !entry.is_directory) {
+        match local.remove(&entry.path) {
+            Some(file) => overwritten.push(OverwrittenFile {
+                path: entry.path.clone(),
+                backup_size: entry.size,
+                backup_modified: entry.modified,
+                local_size: file.size,
+                local_modified: file.modified,
+                local_newer: matches!(
+                    (file.modified, entry.modified),
+                    (Some(local), Some(backup)) if local > backup
+                ),
+            }),
+            None => added.push(entry.path.clone()),
+        }
+    }
+
+    RestoreConflictReport {
+        target_path: target.to_string_lossy().to_string(),
+        overwritten,
+        added,
+        removed: local.into_keys().collect(),
+    }
+}
+
+/// 列出存档备份内的文件，可选生成恢复冲突报告
+///
+/// # Arguments
+/// * `backup_file_path` - 备份文件完整路径
+/// * `password` - 加密备份的密码，未加密的备份传 None
+/// * `target_path` - 可选恢复目标路径，提供时对比目标目录生成冲突报告（不修改任何文件）
+///
+/// # Returns
+/// * `Result<BackupContents, String>` - 备份内容或错误消息
+#[tauri::command]
+pub async fn list_savedata_backup_contents(
+    backup_file_path: String,
+    password: Option<String>,
+    target_path: Option<String>,
+) -> Result<BackupContents, String> {
+    let _timer = CommandTimer::start("list_savedata_backup_contents");
+    tokio::task::spawn_blocking(move || {
+        let backup_path = Path::new(&backup_file_path);
+        if !backup_path.exists() {
+            return Err("备份文件不存在".to_string());
+        }
+
+        let mut entries =
+            list_7z_archive_entries(backup_path, password.as_deref()).map_err(|e| {
+                if password.is_none() && is_7z_archive_encrypted(backup_path).unwrap_or(false) {
+                    "备份已加密，请提供密码".to_string()
+                } else {
+                    format!("读取备份文件失败: {}", e)
+                }
+            })?;
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let conflicts = target_path
+            .as_deref()
+            .map(|target| build_conflict_report(&entries, Path::new(target)));
+
+        Ok(BackupContents {
+            total_size: entries.iter().map(|entry| entry.size).sum(),
+            entries: entries
+                .into_iter()
+                .map(|entry| BackupEntry {
+                    path: entry.path,
+                    size: entry.size,
+                    modified: entry.modified,
+                    is_directory: entry.is_directory,
+                })
+                .collect(),
+            conflicts,
+        })
+    })
+    .await
+    .map_err(|e| format!("读取备份文件失败: {}", e))?
+}
@@ -1,11 +1,22 @@
-use super::archive::{create_7z_archive, extract_7z_archive};
+use super::archive::{create_7z_archive_with_options, extract_7z_archive, is_7z_archive_encrypted};
+use super::integrity::{sha256_file_async, verify_backup_file};
+use super::quota::enforce_savedata_quota;
+use super::schedule::cleanup_old_auto_backups;
+use crate::database::db::DbState;
 use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::entity::backup_compression::BackupCompression;
+use crate::utils::file_lock::retry_on_lock;
+use crate::utils::metrics::CommandTimer;
+use crate::utils::notification::{NotificationCategory, notify};
+use crate::utils::trash::{remove_path, trash_enabled};
+use crate::utils::usage_stats::{UsageFeature, record_usage};
 use chrono::Utc;
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tauri::{State, command};
+use tauri::{AppHandle, State, command};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BackupInfo {
@@ -13,6 +24,12 @@ pub struct BackupInfo {
     pub backup_time: i64,
     pub file_size: u64,
     pub backup_path: String,
+    pub encrypted: bool,
+    /// 压缩包的 SHA-256，保存备份记录时一并写入
+    pub sha256: String,
+    /// 实际使用的压缩设置（所有字段均已补全）
+    #[serde(default)]
+    pub compression: BackupCompression,
 }
 /// 创建游戏存档备份
 ///
@@ -26,17 +43,63 @@ pub struct BackupInfo {
 /// * `app` - Tauri应用句柄
 /// * `game_id` - 游戏ID
 /// * `source_path` - 源存档文件夹路径
+/// * `password` - 可选密码，提供时生成 AES-256 加密的压缩包
+/// * `auto` - 是否为会话结束后的自动备份，为 true 时先按自动备份计划清理旧的自动备份（见 `schedule` 模块）
+/// * `compression` - 本次备份的压缩设置，未设置的字段沿用全局设置 user.backup_compression
+///
+/// 备份写入后检查存档备份配额（见 `quota` 模块），配额检查失败不影响备份结果。
 ///
 /// # Returns
 /// * `Result<BackupInfo, String>` - 备份信息或错误消息
 #[tauri::command]
 pub async fn create_savedata_backup(
-    db: State<'_, DatabaseConnection>,
+    app: AppHandle,
+    db: State<'_, DbState>,
     game_id: i64,
     source_path: String,
+    password: Option<String>,
+    auto: Option<bool>,
+    compression: Option<BackupCompression>,
 ) -> Result<BackupInfo, String> {
-    let source_path = Path::new(&source_path);
-
+    let _timer = CommandTimer::start("create_savedata_backup");
+    let db = db.get();
+    let (info, backup_root) = write_savedata_backup(
+        &db,
+        game_id,
+        Path::new(&source_path),
+        password,
+        auto,
+        compression,
+    )
+    .await?;
+
+    if let Err(e) =
+        enforce_savedata_quota(&app, &db, &backup_root, &info.folder_name, info.file_size).await
+    {
+        log::warn!("检查存档备份配额失败: {}", e);
+    }
+    notify(
+        &app,
+        NotificationCategory::Backup,
+        "存档备份完成",
+        &format!("已创建存档备份 {}", info.folder_name),
+    )
+    .await;
+
+    Ok(info)
+}
+
+/// 压缩存档目录并写入备份目录，返回备份信息与备份根目录
+///
+/// 不检查配额、不发送通知，也不写入备份记录，供命令与命令行模式共用。
+pub async fn write_savedata_backup(
+    db: &DatabaseConnection,
+    game_id: i64,
+    source_path: &Path,
+    password: Option<String>,
+    auto: Option<bool>,
+    compression: Option<BackupCompression>,
+) -> Result<(BackupInfo, PathBuf), String> {
     // 验证源路径是否存在
     if !source_path.exists() {
         return Err("源存档文件夹不存在".to_string());
@@ -46,7 +109,7 @@ pub async fn create_savedata_backup(
         return Err("源路径必须是一个文件夹".to_string());
     }
 
-    let backup_root = resolve_savedata_backup_root(&db).await?;
+    let backup_root = resolve_savedata_backup_root(db).await?;
 
     // 创建游戏专属备份目录
     let game_backup_dir = backup_root.join(format!("game_{}", game_id));
@@ -54,7 +117,10 @@ pub async fn create_savedata_backup(
     fs::create_dir_all(&game_backup_dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
 
     // 检查并清理超出限制的备份（异步处理）
-    cleanup_old_backups(&db, &game_backup_dir, game_id).await?;
+    if auto.unwrap_or(false) {
+        cleanup_old_auto_backups(db, &game_backup_dir, game_id as i32).await?;
+    }
+    cleanup_old_backups(db, &game_backup_dir, game_id).await?;
 
     // 生成备份文件名（带时间戳）
     let now = Utc::now();
@@ -62,53 +128,105 @@ pub async fn create_savedata_backup(
     let backup_filename = format!("savedata_{}_{}.7z", game_id, now.format("%Y%m%d_%H%M%S"));
     let backup_file_path = game_backup_dir.join(&backup_filename);
 
+    // 单次备份的设置优先，其次是全局设置
+    let global = SettingsRepository::get_stored_settings(db)
+        .await
+        .map_err(|e| format!("读取用户设置失败: {}", e))?
+        .backup_compression
+        .unwrap_or_default();
+    let compression = compression.unwrap_or_default().or(&global).resolved();
+
     // 创建7z压缩包
-    let backup_size = create_7z_archive(source_path, &backup_file_path)
-        .map_err(|e| format!("创建压缩包失败: {}", e))?;
+    let password = password.filter(|password| !password.is_empty());
+    let backup_size = create_7z_archive_with_options(
+        source_path,
+        &backup_file_path,
+        password.as_deref(),
+        &compression,
+    )
+    .map_err(|e| format!("创建压缩包失败: {}", e))?;
+    let sha256 = sha256_file_async(backup_file_path.clone())
+        .await
+        .map_err(|e| format!("计算备份校验值失败: {}", e))?;
 
     log::info!(
-        "存档备份创建成功 game_id={} file={} size={} bytes",
+        "存档备份创建成功 game_id={} file={} size={} bytes encrypted={} compression={:?}",
         game_id,
         backup_filename,
-        backup_size
+        backup_size,
+        password.is_some(),
+        compression
     );
+    record_usage(db, UsageFeature::SavedataBackup).await;
 
-    Ok(BackupInfo {
-        folder_name: backup_filename,
-        backup_time: timestamp,
-        file_size: backup_size,
-        backup_path: backup_file_path.to_string_lossy().to_string(),
-    })
+    Ok((
+        BackupInfo {
+            folder_name: backup_filename,
+            backup_time: timestamp,
+            file_size: backup_size,
+            backup_path: backup_file_path.to_string_lossy().to_string(),
+            encrypted: password.is_some(),
+            sha256,
+            compression,
+        },
+        backup_root,
+    ))
 }
 
 /// 恢复存档备份
 ///
+/// 备份记录中存有 SHA-256 时先校验压缩包，不一致则拒绝恢复。
+///
 /// # Arguments
 /// * `backup_file_path` - 备份文件完整路径
 /// * `target_path` - 目标恢复路径
+/// * `password` - 加密备份的密码，未加密的备份传 None
 ///
 /// # Returns
 /// * `Result<(), String>` - 成功或错误消息
 #[tauri::command]
 pub async fn restore_savedata_backup(
+    db: State<'_, DbState>,
     backup_file_path: String,
     target_path: String,
+    password: Option<String>,
 ) -> Result<(), String> {
+    let _timer = CommandTimer::start("restore_savedata_backup");
+    let db = db.get();
     let backup_path = Path::new(&backup_file_path);
     let target_path = Path::new(&target_path);
 
+    // 恢复前会清空目标目录，相对路径会按工作目录解析，直接拒绝
+    if !target_path.is_absolute() {
+        return Err(format!("目标路径必须是绝对路径: {}", target_path.display()));
+    }
+
     // 验证备份文件是否存在
-    if !backup_path.exists() {
+    if !backup_path.is_file() {
         return Err("备份文件不存在".to_string());
     }
+
+    verify_backup_file(&db, backup_path).await?;
 
     // 确保目标路径存在
     if !target_path.exists() {
         fs::create_dir_all(target_path).map_err(|e| format!("创建目标目录失败: {}", e))?;
     }
 
-    // 解压7z文件
-    extract_7z_archive(backup_path, target_path).map_err(|e| format!("解压备份失败: {}", e))?;
+    // 解压7z文件，清理目标目录时文件被占用会阻塞重试，放到阻塞线程中执行
+    let archive_path = backup_path.to_path_buf();
+    let extract_path = target_path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        extract_7z_archive(&archive_path, &extract_path, password.as_deref()).map_err(|e| {
+            if password.is_none() && is_7z_archive_encrypted(&archive_path).unwrap_or(false) {
+                "备份已加密，请提供密码".to_string()
+            } else {
+                format!("解压备份失败: {}", e)
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("解压备份失败: {}", e))??;
 
     log::info!(
         "存档备份恢复成功 file={}",
@@ -122,6 +240,24 @@ pub async fn restore_savedata_backup(
     Ok(())
 }
 
+/// 检查存档备份是否加密
+///
+/// # Arguments
+/// * `backup_file_path` - 备份文件完整路径
+///
+/// # Returns
+/// * `Result<bool, String>` - 是否加密或错误消息
+#[tauri::command]
+pub async fn is_backup_encrypted(backup_file_path: String) -> Result<bool, String> {
+    let _timer = CommandTimer::start("is_backup_encrypted");
+    let backup_path = Path::new(&backup_file_path);
+    if !backup_path.exists() {
+        return Err("备份文件不存在".to_string());
+    }
+
+    is_7z_archive_encrypted(backup_path).map_err(|e| format!("读取备份文件失败: {}", e))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MoveResult {
     pub success: bool,
@@ -131,8 +267,16 @@ pub struct MoveResult {
 /// 移动存档备份文件夹到新位置
 #[command]
 pub async fn move_backup_folder(old_path: String, new_path: String) -> Result<MoveResult, String> {
-    let old_backup_path = Path::new(&old_path);
-    let new_backup_path = Path::new(&new_path);
+    let _timer = CommandTimer::start("move_backup_folder");
+    // 文件被占用时会阻塞重试，放到阻塞线程中执行
+    tokio::task::spawn_blocking(move || move_backup_folder_blocking(&old_path, &new_path))
+        .await
+        .map_err(|e| format!("移动文件夹失败: {}", e))?
+}
+
+fn move_backup_folder_blocking(old_path: &str, new_path: &str) -> Result<MoveResult, String> {
+    let old_backup_path = Path::new(old_path);
+    let new_backup_path = Path::new(new_path);
 
     if !old_backup_path.exists() {
         return Ok(MoveResult {
@@ -158,7 +302,9 @@ pub async fn move_backup_folder(old_path: String, new_path: String) -> Result<Mo
         });
     }
 
-    match fs::rename(old_backup_path, new_backup_path) {
+    match retry_on_lock(old_backup_path, || {
+        fs::rename(old_backup_path, new_backup_path)
+    }) {
         Ok(_) => Ok(MoveResult {
             success: true,
             message: "备份文件夹移动成功".to_string(),
@@ -194,7 +340,7 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), Box<dyn std::error::
         if ty.is_dir() {
             copy_dir_recursive(&src_path, &dst_path)?;
         } else {
-            fs::copy(&src_path, &dst_path)?;
+            retry_on_lock(&src_path, || fs::copy(&src_path, &dst_path))?;
         }
     }
 
@@ -209,19 +355,24 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), Box<dyn std::error::
 /// * `db` - 数据库连接
 /// * `backup_file_path` - 备份文件完整路径
 /// * `backup_id` - 数据库记录 ID
+/// * `use_trash` - 是否把备份文件移到回收站，自动清理时传 false
 ///
 /// # Returns
 /// * `Option<String>` - 如果有错误返回错误信息，否则返回 None
-async fn delete_backup_record(
+pub(super) async fn delete_backup_record(
     db: &DatabaseConnection,
     backup_file_path: &Path,
     backup_id: i32,
+    use_trash: bool,
 ) -> Option<String> {
     let mut errors: Vec<String> = Vec::new();
     // 删除备份文件（如果存在），失败时收集错误
 
-    if let Err(e) = fs::remove_file(backup_file_path) {
-        errors.push(format!("删除备份文件失败 {:?}: {}", backup_file_path, e));
+    let path = backup_file_path.to_path_buf();
+    match tokio::task::spawn_blocking(move || remove_path(&path, use_trash)).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => errors.push(format!("删除备份文件失败 {:?}: {}", backup_file_path, e)),
+        Err(e) => errors.push(format!("删除备份文件失败 {:?}: {}", backup_file_path, e)),
     }
 
     // 无论文件删除是否成功，都继续删除数据库记录
@@ -238,7 +389,7 @@ async fn delete_backup_record(
 
 /// 删除备份文件和数据库记录
 ///
-/// 二合一功能：同时删除备份文件和对应的数据库记录
+/// 二合一功能：同时删除备份文件和对应的数据库记录，备份文件按 user.use_trash 设置移到回收站或永久删除
 /// 即使文件删除失败，也会删除数据库记录，最后返回所有错误
 ///
 /// # Arguments
@@ -249,10 +400,9 @@ async fn delete_backup_record(
 /// # Returns
 /// * `Result<(), String>` - 成功或错误消息
 #[tauri::command]
-pub async fn delete_savedata_backup(
-    db: State<'_, DatabaseConnection>,
-    backup_id: i32,
-) -> Result<(), String> {
+pub async fn delete_savedata_backup(db: State<'_, DbState>, backup_id: i32) -> Result<(), String> {
+    let _timer = CommandTimer::start("delete_savedata_backup");
+    let db = db.get();
     // 先从数据库获取备份记录
     let record = GamesRepository::get_savedata_record_by_id(&db, backup_id)
         .await
@@ -260,11 +410,11 @@ pub async fn delete_savedata_backup(
         .ok_or_else(|| "备份记录不存在".to_string())?;
 
     let backup_root = resolve_savedata_backup_root(&db).await?;
-    let game_backup_dir = backup_root.join(format!("game_{}", record.game_id));
-    let backup_path = game_backup_dir.join(&record.file);
+    let backup_path = savedata_backup_path(&backup_root, record.game_id, &record.file);
 
     // 使用通用函数删除备份记录
-    if let Some(error) = delete_backup_record(&db, &backup_path, backup_id).await {
+    let use_trash = trash_enabled(&db).await;
+    if let Some(error) = delete_backup_record(&db, &backup_path, backup_id, use_trash).await {
         return Err(error);
     }
 
@@ -277,7 +427,29 @@ pub async fn delete_savedata_backup(
     Ok(())
 }
 
-async fn resolve_savedata_backup_root(db: &DatabaseConnection) -> Result<PathBuf, String> {
+/// 备份记录对应的压缩包路径：`<备份根目录>/game_<id>/<文件名>`
+///
+/// 记录中应只保存文件名，但旧版本或在另一个平台上写入的记录可能带有 `/` 或 `\` 分隔的目录。
+/// 两种分隔符都按目录处理并只取最后一段，保证路径在各平台上一致且不会越出游戏的备份目录。
+pub(crate) fn savedata_backup_path(backup_root: &Path, game_id: i32, file: &str) -> PathBuf {
+    backup_root
+        .join(format!("game_{}", game_id))
+        .join(savedata_file_name(file))
+}
+
+/// 备份记录中的文件名，去掉 `/` 或 `\` 分隔的目录部分；无法取得有效文件名时替换掉分隔符
+pub(crate) fn savedata_file_name(file: &str) -> String {
+    file.rsplit(['/', '\\'])
+        .next()
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("_{}", file.replace(['/', '\\'], "_")))
+}
+
+pub(crate) async fn resolve_savedata_backup_root(
+    db: &DatabaseConnection,
+) -> Result<PathBuf, String> {
     use crate::database::repository::settings_repository::DbSettingsExt;
     let settings = db.get_settings().await?;
 
@@ -338,9 +510,9 @@ async fn cleanup_old_backups(
 
     // 使用通用函数删除文件和数据库记录
     for record in records_to_delete {
-        let backup_file_path = backup_dir.join(&record.file);
+        let backup_file_path = backup_dir.join(savedata_file_name(&record.file));
 
-        if let Some(error) = delete_backup_record(db, &backup_file_path, record.id).await {
+        if let Some(error) = delete_backup_record(db, &backup_file_path, record.id, false).await {
             errors.push(error);
         }
     }
@@ -362,3 +534,35 @@ async fn cleanup_old_backups(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_file_names_ignore_both_separators() {
+        assert_eq!(savedata_file_name("savedata_1.7z"), "savedata_1.7z");
+        assert_eq!(savedata_file_name("game_1/savedata_1.7z"), "savedata_1.7z");
+        assert_eq!(savedata_file_name(r"game_1\savedata_1.7z"), "savedata_1.7z");
+        assert_eq!(
+            savedata_file_name(r"C:\Users\me\backups\game_1\savedata_1.7z"),
+            "savedata_1.7z"
+        );
+    }
+
+    #[test]
+    fn record_paths_stay_inside_the_game_backup_dir() {
+        let root = std::env::temp_dir().join("reina_backups");
+        let game_dir = root.join("game_3");
+        for file in ["savedata_3.7z", r"..\..\savedata_3.7z", "../savedata_3.7z"] {
+            assert_eq!(
+                savedata_backup_path(&root, 3, file),
+                game_dir.join("savedata_3.7z")
+            );
+        }
+        for file in ["", "..", r"game_3\..", "/"] {
+            let path = savedata_backup_path(&root, 3, file);
+            assert_eq!(path.parent(), Some(game_dir.as_path()), "{:?}", file);
+        }
+    }
+}
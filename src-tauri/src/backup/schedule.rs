@@ -0,0 +1,425 @@
+//! 存档自动备份计划
+//!
+//! 全局计划存储在 user.backup_schedule，单个游戏可以在 games.backup_schedule 中覆盖其中的字段：
+//! 每天都玩的 RPG 可以每次会话都备份并多留几份，已经通关的游戏则可以拉长间隔或直接关闭。
+//! 游戏没有覆盖开关时沿用旧的 games.autosave 开关，两者都没有时再看全局设置。
+//! games.autosave 默认为 0，只有开启（1）时才视为覆盖，否则每个游戏都会忽略全局开关。
+//!
+//! 设置了 `play_interval_minutes` 时，监控循环还会在会话进行中按累计游玩时间定时备份（见 [`PlayBackupTimer`]），
+//! 这些备份与会话结束后的自动备份一样记为 auto，共用 `max_auto_backups` 与 maxbackups 的清理。
+
+use super::quota::enforce_savedata_quota;
+use super::savedata::{delete_backup_record, savedata_file_name, write_savedata_backup};
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::backup_schedule::BackupSchedule;
+use crate::utils::metrics::CommandTimer;
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// 合并全局设置与游戏覆盖后的自动备份计划
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ResolvedSchedule {
+    pub enabled: bool,
+    pub interval_minutes: u32,
+    /// None 表示不单独限制自动备份数量
+    pub max_auto_backups: Option<u32>,
+    /// 会话进行中的备份间隔（累计游玩分钟），0 表示不在会话中备份
+    pub play_interval_minutes: u32,
+}
+
+impl ResolvedSchedule {
+    /// 按 游戏覆盖 > games.autosave > 全局设置 的顺序合并
+    pub fn resolve(
+        global: &BackupSchedule,
+        overrides: &BackupSchedule,
+        legacy_autosave: Option<i32>,
+    ) -> Self {
+        Self {
+            enabled: overrides
+                .enabled
+                .or(legacy_autosave
+                    .filter(|autosave| *autosave == 1)
+                    .map(|_| true))
+                .or(global.enabled)
+                .unwrap_or(false),
+            interval_minutes: overrides
+                .interval_minutes
+                .or(global.interval_minutes)
+                .unwrap_or(0),
+            max_auto_backups: overrides
+                .max_auto_backups
+                .or(global.max_auto_backups)
+                .filter(|max| *max > 0),
+            play_interval_minutes: overrides
+                .play_interval_minutes
+                .or(global.play_interval_minutes)
+                .unwrap_or(0),
+        }
+    }
+
+    /// 下一次允许自动备份的时间（秒级时间戳），从未自动备份或不限间隔时为 None
+    pub fn next_auto_backup(&self, last_auto_backup: Option<i64>) -> Option<i64> {
+        if self.interval_minutes == 0 {
+            return None;
+        }
+        last_auto_backup.map(|last| last + self.interval_minutes as i64 * 60)
+    }
+
+    /// 当前是否应执行自动备份
+    pub fn is_due(&self, last_auto_backup: Option<i64>, now: i64) -> bool {
+        self.enabled
+            && self
+                .next_auto_backup(last_auto_backup)
+                .is_none_or(|next| now >= next)
+    }
+}
+
+/// `get_backup_settings` 的返回结果
+#[derive(Debug, Clone, Serialize)]
+pub struct GameBackupSettings {
+    pub game_id: i32,
+    #[serde(flatten)]
+    pub effective: ResolvedSchedule,
+    /// 该游戏全部备份的数量上限（games.maxbackups）
+    pub max_backups: Option<i32>,
+    pub last_auto_backup: Option<i64>,
+    pub next_auto_backup: Option<i64>,
+    /// 现在结束会话是否会触发自动备份
+    pub due: bool,
+    /// 游戏自身的覆盖设置
+    pub overrides: BackupSchedule,
+    /// 全局设置
+    pub global: BackupSchedule,
+}
+
+/// 读取游戏的自动备份计划
+pub(super) async fn load_game_backup_settings(
+    db: &DatabaseConnection,
+    game_id: i32,
+) -> Result<GameBackupSettings, String> {
+    let global = db.get_settings().await?.backup_schedule.unwrap_or_default();
+    let game = GamesRepository::find_by_id(db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏信息失败: {}", e))?
+        .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+    let last_auto_backup = GamesRepository::get_savedata_records(db, game_id)
+        .await
+        .map_err(|e| format!("获取备份记录失败: {}", e))?
+        .iter()
+        .filter(|record| record.auto)
+        .map(|record| record.backup_time as i64)
+        .max();
+
+    let overrides = game.backup_schedule.unwrap_or_default();
+    let effective = ResolvedSchedule::resolve(&global, &overrides, game.autosave);
+    Ok(GameBackupSettings {
+        game_id,
+        effective,
+        max_backups: game.maxbackups,
+        last_auto_backup,
+        next_auto_backup: effective.next_auto_backup(last_auto_backup),
+        due: effective.is_due(last_auto_backup, Utc::now().timestamp()),
+        overrides,
+        global,
+    })
+}
+
+/// 创建自动备份前清理超出 `max_auto_backups` 的旧自动备份，为新备份留出位置
+pub(super) async fn cleanup_old_auto_backups(
+    db: &DatabaseConnection,
+    backup_dir: &Path,
+    game_id: i32,
+) -> Result<(), String> {
+    let settings = load_game_backup_settings(db, game_id).await?;
+    let Some(max_auto_backups) = settings.effective.max_auto_backups else {
+        return Ok(());
+    };
+
+    let mut records: Vec<_> = GamesRepository::get_savedata_records(db, game_id)
+        .await
+        .map_err(|e| format!("获取备份记录失败: {}", e))?
+        .into_iter()
+        .filter(|record| record.auto)
+        .collect();
+    if records.len() < max_auto_backups as usize {
+        return Ok(());
+    }
+
+    records.sort_by_key(|record| record.backup_time);
+    let to_delete_count = records.len() - (max_auto_backups as usize - 1);
+    let mut errors = Vec::new();
+    for record in &records[..to_delete_count] {
+        if let Some(error) = delete_backup_record(
+            db,
+            &backup_dir.join(savedata_file_name(&record.file)),
+            record.id,
+            false,
+        )
+        .await
+        {
+            errors.push(error);
+        }
+    }
+
+    log::debug!(
+        "旧自动备份清理完成 game_id={} deleted_count={}",
+        game_id,
+        to_delete_count
+    );
+    if !errors.is_empty() {
+        log::warn!(
+            "清理旧自动备份时遇到 {} 个错误:\n{}",
+            errors.len(),
+            errors.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// 会话进行中的定时自动备份
+///
+/// 按累计游玩时间（不含挂机与暂停计时）计时，每满 `play_interval_minutes` 分钟在后台备份一次存档；
+/// 上一次备份尚未完成时跳过本次，不会阻塞监控循环。
+pub struct PlayBackupTimer {
+    /// 备份间隔（秒），0 表示不备份
+    interval_secs: u64,
+    /// 下一次备份时的累计游玩秒数
+    next_at: u64,
+    running: Arc<AtomicBool>,
+}
+
+impl PlayBackupTimer {
+    pub fn new(play_interval_minutes: u32) -> Self {
+        let interval_secs = u64::from(play_interval_minutes) * 60;
+        Self {
+            interval_secs,
+            next_at: interval_secs,
+            running: Arc::default(),
+        }
+    }
+
+    /// 读取游戏生效的会话中备份间隔，读取失败时不备份
+    pub async fn load<R: Runtime>(app_handle: &AppHandle<R>, game_id: u32) -> Self {
+        let Some(db) = app_handle.try_state::<DbState>().map(|state| state.get()) else {
+            return Self::new(0);
+        };
+        match load_game_backup_settings(&db, game_id as i32).await {
+            Ok(settings) => Self::new(settings.effective.play_interval_minutes),
+            Err(e) => {
+                log::warn!("读取会话中自动备份设置失败 (game_id: {}): {}", game_id, e);
+                Self::new(0)
+            }
+        }
+    }
+
+    /// 累计游玩时间达到下一次备份时间时返回 true 并推进计时；积压多个间隔时只备份一次
+    fn take_due(&mut self, accumulated_seconds: u64) -> bool {
+        if self.interval_secs == 0 || accumulated_seconds < self.next_at {
+            return false;
+        }
+        self.next_at = (accumulated_seconds / self.interval_secs + 1) * self.interval_secs;
+        true
+    }
+
+    /// 由监控循环每次计时后调用，到达间隔时在后台创建备份
+    pub fn tick<R: Runtime>(
+        &mut self,
+        app_handle: &AppHandle<R>,
+        game_id: u32,
+        accumulated_seconds: u64,
+    ) {
+        if !self.take_due(accumulated_seconds) {
+            return;
+        }
+        if self.running.swap(true, Ordering::AcqRel) {
+            log::debug!("上一次会话中自动备份尚未完成，跳过本次 game_id={}", game_id);
+            return;
+        }
+        let app_handle = app_handle.clone();
+        let running = self.running.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = run_play_backup(&app_handle, game_id).await {
+                log::warn!("会话中自动备份失败 (game_id: {}): {}", game_id, e);
+            }
+            running.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// 备份一次正在运行的游戏的存档，写入 auto 备份记录并检查配额
+async fn run_play_backup<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    game_id: u32,
+) -> Result<(), String> {
+    let db = app_handle
+        .try_state::<DbState>()
+        .map(|state| state.get())
+        .ok_or_else(|| "数据库尚未初始化".to_string())?;
+    let game = GamesRepository::find_by_id(&db, game_id as i32)
+        .await
+        .map_err(|e| format!("获取游戏信息失败: {}", e))?
+        .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+    let Some(save_path) = game.savepath.filter(|path| !path.trim().is_empty()) else {
+        log::debug!("游戏未设置存档路径，跳过会话中自动备份 game_id={}", game_id);
+        return Ok(());
+    };
+
+    let (info, backup_root) = write_savedata_backup(
+        &db,
+        game_id as i64,
+        Path::new(&save_path),
+        None,
+        Some(true),
+        None,
+    )
+    .await?;
+    GamesRepository::save_savedata_record(
+        &db,
+        game_id as i32,
+        &info.folder_name,
+        info.backup_time as i32,
+        info.file_size as i32,
+        true,
+        Some(info.sha256),
+    )
+    .await
+    .map_err(|e| format!("保存备份记录失败: {}", e))?;
+
+    if let Err(e) = enforce_savedata_quota(
+        app_handle,
+        &db,
+        &backup_root,
+        &info.folder_name,
+        info.file_size,
+    )
+    .await
+    {
+        log::warn!("检查存档备份配额失败: {}", e);
+    }
+    log::info!(
+        "会话中自动备份完成 game_id={} file={}",
+        game_id,
+        info.folder_name
+    );
+    Ok(())
+}
+
+/// 获取游戏生效的自动备份计划（合并全局设置与游戏覆盖）
+///
+/// # Arguments
+/// * `game_id` - 游戏ID
+///
+/// # Returns
+/// * `Result<GameBackupSettings, String>` - 生效的设置、覆盖来源与下次自动备份时间
+#[tauri::command]
+pub async fn get_backup_settings(
+    db: State<'_, DbState>,
+    game_id: i32,
+) -> Result<GameBackupSettings, String> {
+    let _timer = CommandTimer::start("get_backup_settings");
+    let db = db.get();
+    load_game_backup_settings(&db, game_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(
+        enabled: Option<bool>,
+        interval_minutes: Option<u32>,
+        max_auto_backups: Option<u32>,
+    ) -> BackupSchedule {
+        BackupSchedule {
+            enabled,
+            interval_minutes,
+            max_auto_backups,
+            play_interval_minutes: None,
+        }
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_legacy_and_global() {
+        let global = schedule(Some(true), Some(60), Some(5));
+        let overrides = schedule(Some(false), None, Some(10));
+
+        let resolved = ResolvedSchedule::resolve(&global, &overrides, Some(1));
+        assert!(!resolved.enabled);
+        assert_eq!(resolved.interval_minutes, 60);
+        assert_eq!(resolved.max_auto_backups, Some(10));
+
+        let resolved = ResolvedSchedule::resolve(&global, &BackupSchedule::default(), None);
+        assert!(resolved.enabled);
+    }
+
+    #[test]
+    fn disabled_legacy_autosave_falls_back_to_global() {
+        let global = schedule(Some(true), None, None);
+        let resolved = ResolvedSchedule::resolve(&global, &BackupSchedule::default(), Some(0));
+        assert!(resolved.enabled);
+
+        let resolved = ResolvedSchedule::resolve(
+            &schedule(Some(false), None, None),
+            &BackupSchedule::default(),
+            Some(1),
+        );
+        assert!(resolved.enabled);
+    }
+
+    #[test]
+    fn zero_max_auto_backups_means_unlimited() {
+        let resolved = ResolvedSchedule::resolve(
+            &schedule(None, None, Some(5)),
+            &schedule(None, None, Some(0)),
+            None,
+        );
+        assert_eq!(resolved.max_auto_backups, None);
+    }
+
+    #[test]
+    fn due_respects_interval() {
+        let resolved = ResolvedSchedule {
+            enabled: true,
+            interval_minutes: 30,
+            max_auto_backups: None,
+            play_interval_minutes: 0,
+        };
+        assert!(resolved.is_due(None, 1_000));
+        assert!(!resolved.is_due(Some(1_000), 1_000 + 29 * 60));
+        assert!(resolved.is_due(Some(1_000), 1_000 + 30 * 60));
+
+        let every_session = ResolvedSchedule {
+            interval_minutes: 0,
+            ..resolved
+        };
+        assert!(every_session.is_due(Some(1_000), 1_000));
+
+        let disabled = ResolvedSchedule {
+            enabled: false,
+            ..resolved
+        };
+        assert!(!disabled.is_due(None, 1_000));
+    }
+
+    #[test]
+    fn play_backups_follow_accumulated_time() {
+        let mut timer = PlayBackupTimer::new(30);
+        assert!(!timer.take_due(29 * 60));
+        assert!(timer.take_due(30 * 60));
+        assert!(!timer.take_due(30 * 60 + 1));
+        // 积压多个间隔（如监控恢复后）只备份一次
+        assert!(timer.take_due(95 * 60));
+        assert!(!timer.take_due(119 * 60));
+        assert!(timer.take_due(120 * 60));
+
+        let mut disabled = PlayBackupTimer::new(0);
+        assert!(!disabled.take_due(u64::MAX));
+    }
+}
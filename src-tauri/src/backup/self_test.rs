@@ -0,0 +1,286 @@
+//! 备份/恢复流程自检
+//!
+//! 在临时目录中生成合成存档，依次执行 创建 → 校验 → 恢复 → 对比，最后清理所有临时文件。
+//! 压缩包写入真实的存档备份根目录，以便同时检验该目录的写入权限与杀毒软件拦截情况。
+
+use super::archive::{
+    create_7z_archive_with_password, extract_7z_archive, is_7z_archive_encrypted,
+};
+use super::savedata::resolve_savedata_backup_root;
+use crate::database::db::DbState;
+use crate::utils::metrics::CommandTimer;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::State;
+use walkdir::WalkDir;
+
+/// 自检使用的固定密码，仅用于验证加密流程
+const SELF_TEST_PASSWORD: &str = "reina-self-test";
+
+/// 单个步骤的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestStep {
+    pub name: String,
+    pub success: bool,
+    pub message: String,
+    pub duration_ms: u64,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub backup_root: String,
+    pub steps: Vec<SelfTestStep>,
+}
+
+/// 自检过程中创建的临时路径
+struct SelfTestPaths {
+    work_dir: PathBuf,
+    source_dir: PathBuf,
+    restore_dir: PathBuf,
+    archive_path: PathBuf,
+}
+
+impl SelfTestPaths {
+    fn new(backup_root: &Path) -> Self {
+        let tag = format!(
+            "reina_self_test_{}_{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_millis()
+        );
+        let work_dir = std::env::temp_dir().join(&tag);
+        Self {
+            source_dir: work_dir.join("source"),
+            restore_dir: work_dir.join("restore"),
+            archive_path: backup_root.join(format!("{}.7z", tag)),
+            work_dir,
+        }
+    }
+
+    /// 删除所有临时文件，返回遇到的错误
+    fn cleanup(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.work_dir.exists()
+            && let Err(e) = fs::remove_dir_all(&self.work_dir)
+        {
+            errors.push(format!(
+                "删除临时目录失败 {}: {}",
+                self.work_dir.display(),
+                e
+            ));
+        }
+        if self.archive_path.exists()
+            && let Err(e) = fs::remove_file(&self.archive_path)
+        {
+            errors.push(format!(
+                "删除测试压缩包失败 {}: {}",
+                self.archive_path.display(),
+                e
+            ));
+        }
+        errors
+    }
+}
+
+/// 生成合成存档：嵌套目录、空文件、非 ASCII 文件名与随机二进制内容
+fn write_synthetic_files(source_dir: &Path) -> Result<usize, String> {
+    let mut random = vec![0u8; 256 * 1024];
+    getrandom::fill(&mut random).map_err(|e| format!("生成随机数据失败: {}", e))?;
+
+    let files: Vec<(&str, Vec<u8>)> = vec![
+        ("save01.dat", random),
+        (
+            "config.ini",
+            b"[system]\nvolume=80\ntext_speed=3\n".to_vec(),
+        ),
+        ("empty.sav", Vec::new()),
+        (
+            "存档/セーブ_02.dat",
+            "玲奈 Reina セーブデータ".as_bytes().to_vec(),
+        ),
+        (
+            "nested/deep/level/system.bin",
+            (0..=255u8).cycle().take(4096).collect(),
+        ),
+    ];
+
+    for (relative, content) in &files {
+        let path = source_dir.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        fs::write(&path, content).map_err(|e| format!("写入 {} 失败: {}", relative, e))?;
+    }
+
+    Ok(files.len())
+}
+
+/// 读取目录下所有文件，键为使用 `/` 分隔的相对路径
+fn read_tree(root: &Path) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let mut tree = BTreeMap::new();
+    for entry in WalkDir::new(root) {
+        let entry = entry.map_err(|e| format!("遍历目录失败: {}", e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .map_err(|e| format!("计算相对路径失败: {}", e))?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let content =
+            fs::read(entry.path()).map_err(|e| format!("读取 {} 失败: {}", relative, e))?;
+        tree.insert(relative, content);
+    }
+    Ok(tree)
+}
+
+/// 对比源目录与恢复目录，返回差异描述
+fn compare_trees(source: &Path, restored: &Path) -> Result<Vec<String>, String> {
+    let source = read_tree(source)?;
+    let restored = read_tree(restored)?;
+    let mut differences = Vec::new();
+
+    for (path, content) in &source {
+        match restored.get(path) {
+            None => differences.push(format!("缺少文件: {}", path)),
+            Some(restored_content) if restored_content != content => {
+                differences.push(format!("内容不一致: {}", path))
+            }
+            Some(_) => {}
+        }
+    }
+    for path in restored.keys().filter(|path| !source.contains_key(*path)) {
+        differences.push(format!("多出文件: {}", path));
+    }
+
+    Ok(differences)
+}
+
+/// 执行一个步骤并记录耗时，返回步骤是否成功
+fn run_step<F>(steps: &mut Vec<SelfTestStep>, name: &str, step: F) -> bool
+where
+    F: FnOnce() -> Result<String, String>,
+{
+    let started = Instant::now();
+    let (success, message) = match step() {
+        Ok(message) => (true, message),
+        Err(message) => (false, message),
+    };
+    if !success {
+        log::warn!("备份自检步骤失败 step={} message={}", name, message);
+    }
+    steps.push(SelfTestStep {
+        name: name.to_string(),
+        success,
+        message,
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+    success
+}
+
+fn run_pipeline(paths: &SelfTestPaths, password: Option<&str>) -> Vec<SelfTestStep> {
+    let mut steps = Vec::new();
+
+    let ok = run_step(&mut steps, "prepare", || {
+        fs::create_dir_all(&paths.source_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+        if let Some(parent) = paths.archive_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建备份目录失败: {}", e))?;
+        }
+        let count = write_synthetic_files(&paths.source_dir)?;
+        Ok(format!("已生成 {} 个测试文件", count))
+    }) && run_step(&mut steps, "create", || {
+        let size =
+            create_7z_archive_with_password(&paths.source_dir, &paths.archive_path, password)
+                .map_err(|e| format!("创建压缩包失败: {}", e))?;
+        Ok(format!("压缩包大小 {} 字节", size))
+    }) && run_step(&mut steps, "verify", || {
+        let size = fs::metadata(&paths.archive_path)
+            .map_err(|e| format!("压缩包不存在或无法读取（可能被杀毒软件隔离）: {}", e))?
+            .len();
+        if size == 0 {
+            return Err("压缩包为空".to_string());
+        }
+        let encrypted = is_7z_archive_encrypted(&paths.archive_path)
+            .map_err(|e| format!("读取压缩包失败: {}", e))?;
+        if encrypted != password.is_some() {
+            return Err(format!(
+                "加密状态不符：预期 {}，实际 {}",
+                password.is_some(),
+                encrypted
+            ));
+        }
+        Ok("压缩包可读取".to_string())
+    }) && run_step(&mut steps, "restore", || {
+        extract_7z_archive(&paths.archive_path, &paths.restore_dir, password)
+            .map_err(|e| format!("解压失败: {}", e))?;
+        Ok("解压完成".to_string())
+    }) && run_step(&mut steps, "compare", || {
+        let differences = compare_trees(&paths.source_dir, &paths.restore_dir)?;
+        if differences.is_empty() {
+            Ok("恢复内容与源文件一致".to_string())
+        } else {
+            Err(differences.join("; "))
+        }
+    });
+
+    if !ok {
+        log::warn!("备份自检未通过 archive={}", paths.archive_path.display());
+    }
+    steps
+}
+
+/// 一键自检存档备份/恢复流程
+///
+/// 用于确认 7z 压缩、备份目录权限与杀毒软件等环境不会损坏存档备份。
+///
+/// # Arguments
+/// * `include_encryption` - 是否使用加密压缩包测试
+///
+/// # Returns
+/// * `Result<SelfTestReport, String>` - 自检报告；只有无法确定备份目录时才返回错误
+#[tauri::command]
+pub async fn self_test_backup_pipeline(
+    db: State<'_, DbState>,
+    include_encryption: Option<bool>,
+) -> Result<SelfTestReport, String> {
+    let _timer = CommandTimer::start("self_test_backup_pipeline");
+    let db = db.get();
+    let backup_root = resolve_savedata_backup_root(&db).await?;
+    let password = include_encryption
+        .unwrap_or(false)
+        .then_some(SELF_TEST_PASSWORD);
+
+    let root = backup_root.clone();
+    let steps = tokio::task::spawn_blocking(move || {
+        let paths = SelfTestPaths::new(&root);
+        let mut steps = run_pipeline(&paths, password);
+        run_step(&mut steps, "cleanup", || {
+            let errors = paths.cleanup();
+            if errors.is_empty() {
+                Ok("临时文件已清理".to_string())
+            } else {
+                Err(errors.join("; "))
+            }
+        });
+        steps
+    })
+    .await
+    .map_err(|e| format!("备份自检执行失败: {}", e))?;
+
+    let passed = steps.iter().all(|step| step.success);
+    log::info!("备份自检完成 passed={} steps={}", passed, steps.len());
+
+    Ok(SelfTestReport {
+        passed,
+        backup_root: backup_root.to_string_lossy().to_string(),
+        steps,
+    })
+}
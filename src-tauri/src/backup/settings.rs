@@ -0,0 +1,249 @@
+//! 设置 JSON 导出与导入
+//!
+//! 只导出 user 表中的设置（LE / Magpie 路径、扫描配置、备份策略等），便于换机时单独迁移设置，
+//! 不必导入整个数据库。BGM / VNDB 令牌与 HTTP API 访问令牌默认不导出。
+//! 监控设置中的焦点窗口探测命令会被当作 shell 命令执行，导入时默认保留本机的命令，
+//! 调用方明确同意后才使用导入文件中的命令。
+
+use crate::backup::common::BackupResult;
+use crate::database::db::DbState;
+use crate::database::dto::UpdateSettingsData;
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::database::service::emit_settings_changed;
+use crate::entity::monitor_settings::MonitorSettings;
+use crate::entity::user;
+use crate::utils::file_lock::load_retry_policy;
+use crate::utils::metrics::CommandTimer;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, State, command};
+
+/// 当前导出格式版本，结构发生不兼容变化时递增
+const SETTINGS_EXPORT_VERSION: u32 = 1;
+
+/// 设置导出文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettingsExport {
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: i64,
+    /// 是否包含令牌
+    #[serde(default)]
+    pub include_secrets: bool,
+    pub settings: user::Model,
+}
+
+/// 设置导入结果
+#[derive(Debug, Serialize)]
+pub struct SettingsImportResult {
+    /// 写入的设置项（camelCase）
+    pub keys: Vec<&'static str>,
+    /// 是否使用了导入文件中的令牌
+    pub secrets_restored: bool,
+    /// 导入文件中的焦点窗口探测命令是否因未获同意而被忽略
+    pub probe_command_skipped: bool,
+}
+
+/// 导入文件中的探测命令与当前不同时写入 `keys` 的设置项，前端据此提示用户
+const PROBE_COMMAND_KEY: &str = "foregroundProbeCommand";
+
+/// 去掉账号凭据与访问令牌
+fn strip_secrets(settings: &mut user::Model) {
+    settings.bgm_auth = None;
+    settings.vndb_token = None;
+    if let Some(http_api) = settings.http_api.as_mut() {
+        http_api.token = None;
+    }
+}
+
+/// 焦点窗口探测命令（未设置或为空时为 None）
+fn probe_command(settings: &user::Model) -> Option<&str> {
+    settings
+        .monitor_settings
+        .as_ref()
+        .and_then(|monitor| monitor.foreground_probe_command.as_deref())
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+}
+
+/// 把导入的设置转换为更新数据；文件不含令牌时保留当前令牌，
+/// `allow_probe_command` 为 false 时保留当前的焦点窗口探测命令
+fn settings_update(
+    mut imported: user::Model,
+    current: &user::Model,
+    include_secrets: bool,
+    allow_probe_command: bool,
+) -> UpdateSettingsData {
+    if !allow_probe_command {
+        let current_command = current
+            .monitor_settings
+            .as_ref()
+            .and_then(|monitor| monitor.foreground_probe_command.clone());
+        if let Some(monitor) = imported.monitor_settings.as_mut() {
+            monitor.foreground_probe_command = current_command;
+        } else if current_command.is_some() {
+            imported.monitor_settings = Some(MonitorSettings {
+                foreground_probe_command: current_command,
+                ..Default::default()
+            });
+        }
+    }
+
+    let (bgm_auth, vndb_token, http_api) = if include_secrets {
+        (imported.bgm_auth, imported.vndb_token, imported.http_api)
+    } else {
+        let current_token = current.http_api.as_ref().and_then(|api| api.token.clone());
+        let http_api = imported.http_api.map(|mut api| {
+            api.token = current_token;
+            api
+        });
+        (
+            current.bgm_auth.clone(),
+            current.vndb_token.clone(),
+            http_api,
+        )
+    };
+
+    UpdateSettingsData {
+        bgm_auth: Some(bgm_auth),
+        vndb_token: Some(vndb_token),
+        save_root_path: Some(imported.save_root_path),
+        db_backup_path: Some(imported.db_backup_path),
+        le_path: Some(imported.le_path),
+        magpie_path: Some(imported.magpie_path),
+        auto_clear_rules: Some(imported.auto_clear_rules),
+        savedata_quota: Some(imported.savedata_quota),
+        monitor_settings: Some(imported.monitor_settings),
+        notification_settings: Some(imported.notification_settings),
+        presence_settings: Some(imported.presence_settings),
+        backup_schedule: Some(imported.backup_schedule),
+        scan_config: Some(imported.scan_config),
+        http_api: Some(http_api),
+        tray_settings: Some(imported.tray_settings),
+        log_settings: Some(imported.log_settings),
+        usage_stats_enabled: Some(imported.usage_stats_enabled),
+        // 首次启动向导进度只属于本机，不随设置导入
+        setup_progress: None,
+        backup_compression: Some(imported.backup_compression),
+        use_trash: Some(imported.use_trash),
+        max_db_backups: Some(imported.max_db_backups),
+        max_backup_age_days: Some(imported.max_backup_age_days),
+        file_lock_retries: Some(imported.file_lock_retries),
+        file_lock_retry_delay_ms: Some(imported.file_lock_retry_delay_ms),
+    }
+}
+
+/// 导出设置为 JSON 文件
+///
+/// # Arguments
+/// * `path` - 导出文件路径
+/// * `include_secrets` - 是否包含 BGM / VNDB 令牌与 HTTP API 访问令牌
+///
+/// # Returns
+/// * `Result<BackupResult, String>` - 导出结果或错误消息
+#[command]
+pub async fn export_settings(
+    db: State<'_, DbState>,
+    path: String,
+    include_secrets: Option<bool>,
+) -> Result<BackupResult, String> {
+    let _timer = CommandTimer::start("export_settings");
+    let include_secrets = include_secrets.unwrap_or(false);
+    let mut settings = SettingsRepository::get_all_settings(&db.get())
+        .await
+        .map_err(|e| format!("读取用户设置失败: {}", e))?;
+    if !include_secrets {
+        strip_secrets(&mut settings);
+    }
+
+    let export = SettingsExport {
+        version: SETTINGS_EXPORT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().timestamp(),
+        include_secrets,
+        settings,
+    };
+
+    let json =
+        serde_json::to_string_pretty(&export).map_err(|e| format!("序列化设置失败: {}", e))?;
+    if let Some(parent) = Path::new(&path).parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+    fs::write(&path, json).map_err(|e| format!("写入导出文件失败: {}", e))?;
+
+    log::info!("设置导出成功: {} include_secrets={}", path, include_secrets);
+
+    Ok(BackupResult {
+        success: true,
+        path: Some(path),
+        message: "设置导出成功".to_string(),
+        pruned_backups: 0,
+    })
+}
+
+/// 从 JSON 导出文件恢复设置
+///
+/// 覆盖当前的全部设置；导出文件不含令牌时保留当前的令牌。完成后广播 `settings-changed` 事件。
+/// 导入文件中的焦点窗口探测命令会在监控时通过 shell 执行，默认不导入；使用了与当前不同的命令时
+/// 返回的 `keys` 中包含 `foregroundProbeCommand`。
+///
+/// # Arguments
+/// * `path` - 导出文件路径
+/// * `allow_probe_command` - 是否使用导入文件中的焦点窗口探测命令，默认否
+///
+/// # Returns
+/// * `Result<SettingsImportResult, String>` - 导入结果或错误消息
+#[command]
+pub async fn import_settings(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    path: String,
+    allow_probe_command: Option<bool>,
+) -> Result<SettingsImportResult, String> {
+    let _timer = CommandTimer::start("import_settings");
+    let db = &db.get();
+
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let export: SettingsExport = serde_json::from_str(content.trim_start_matches('\u{feff}'))
+        .map_err(|e| format!("解析导入文件失败: {}", e))?;
+
+    if export.version > SETTINGS_EXPORT_VERSION {
+        return Err(format!(
+            "导入文件版本 {} 高于当前支持的版本 {}，请升级应用后重试",
+            export.version, SETTINGS_EXPORT_VERSION
+        ));
+    }
+
+    let current = SettingsRepository::get_all_settings(db)
+        .await
+        .map_err(|e| format!("读取用户设置失败: {}", e))?;
+    let allow_probe_command = allow_probe_command.unwrap_or(false);
+    let probe_command_changed = probe_command(&export.settings) != probe_command(&current);
+    let update = settings_update(
+        export.settings,
+        &current,
+        export.include_secrets,
+        allow_probe_command,
+    );
+    let mut keys = update.changed_keys();
+    if probe_command_changed && allow_probe_command {
+        keys.push(PROBE_COMMAND_KEY);
+    }
+
+    SettingsRepository::update_settings(db, update)
+        .await
+        .map_err(|e| format!("写入用户设置失败: {}", e))?;
+    load_retry_policy(db).await;
+    emit_settings_changed(&app, keys.clone());
+
+    log::info!("设置导入成功: {}", path);
+
+    Ok(SettingsImportResult {
+        keys,
+        secrets_restored: export.include_secrets,
+        probe_command_skipped: probe_command_changed && !allow_probe_command,
+    })
+}
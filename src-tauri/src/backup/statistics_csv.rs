@@ -0,0 +1,268 @@
+//! 游戏统计 CSV 导出
+//!
+//! 为需要自行分析游玩数据的用户导出 CSV（Excel / pandas 可直接读取）：
+//! 会话明细每行一次会话，每日汇总每行一个游戏在一天内的游玩时长。
+//! 会话按页读取并边读边写，会话数量很多时也不会一次性载入内存。
+
+use crate::backup::common::BackupResult;
+use crate::database::db::DbState;
+use crate::database::repository::game_stats_repository::GameStatsRepository;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::entity::prelude::*;
+use crate::entity::{game_sessions, game_statistics, games};
+use crate::utils::metrics::CommandTimer;
+use chrono::{Local, TimeZone};
+use sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, QueryOrder};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tauri::{State, command};
+
+/// 每次从数据库读取的会话数量
+const SESSION_PAGE_SIZE: u64 = 500;
+
+/// CSV 导出范围
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvExportScope {
+    /// 会话明细：每行一次会话
+    Sessions,
+    /// 每日汇总：每行一个游戏在一天内的游玩时长
+    Daily,
+}
+
+/// 转义 CSV 字段：包含分隔符、引号或换行时加引号，并把引号写成两个
+fn escape_csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 写入一行 CSV
+fn write_row<W: Write>(writer: &mut W, fields: &[&str]) -> std::io::Result<()> {
+    let line: Vec<String> = fields.iter().map(|field| escape_csv_field(field)).collect();
+    writeln!(writer, "{}", line.join(","))
+}
+
+/// 把 Unix 时间戳格式化为本地时间
+fn format_local_time(timestamp: i32) -> String {
+    Local
+        .timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+/// 读取游戏 ID 到显示名称的映射，没有名称时为空字符串
+async fn load_game_names(
+    db: &DatabaseConnection,
+    use_cn: bool,
+) -> Result<HashMap<i32, String>, String> {
+    let games = Games::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("读取游戏数据失败: {}", e))?;
+    Ok(games
+        .iter()
+        .map(|game: &games::Model| {
+            let name = GamesRepository::get_display_name(game, use_cn).unwrap_or_default();
+            (game.id, name.to_string())
+        })
+        .collect())
+}
+
+/// 逐页写入会话明细，返回写入的行数
+async fn write_sessions<W: Write>(
+    db: &DatabaseConnection,
+    writer: &mut W,
+    names: &HashMap<i32, String>,
+) -> Result<usize, String> {
+    let io_err = |e: std::io::Error| format!("写入 CSV 失败: {}", e);
+    write_row(
+        writer,
+        &[
+            "session_id",
+            "game_id",
+            "game_name",
+            "date",
+            "start_time",
+            "end_time",
+            "duration_minutes",
+            "manual",
+            "tags",
+            "note",
+        ],
+    )
+    .map_err(io_err)?;
+
+    let mut paginator = GameSessions::find()
+        .order_by_asc(game_sessions::Column::SessionId)
+        .paginate(db, SESSION_PAGE_SIZE);
+    let mut rows = 0;
+
+    while let Some(sessions) = paginator
+        .fetch_and_next()
+        .await
+        .map_err(|e| format!("读取游戏会话失败: {}", e))?
+    {
+        for session in sessions {
+            let tags = session
+                .tags
+                .as_ref()
+                .map(|tags| tags.0.join(";"))
+                .unwrap_or_default();
+            write_row(
+                writer,
+                &[
+                    &session.session_id.to_string(),
+                    &session.game_id.to_string(),
+                    names.get(&session.game_id).map_or("", String::as_str),
+                    &session.date,
+                    &format_local_time(session.start_time),
+                    &format_local_time(session.end_time),
+                    &session.duration.to_string(),
+                    if session.manual { "true" } else { "false" },
+                    &tags,
+                    session.note.as_deref().unwrap_or_default(),
+                ],
+            )
+            .map_err(io_err)?;
+            rows += 1;
+        }
+    }
+
+    Ok(rows)
+}
+
+/// 按游戏写入每日汇总，同一游戏内按日期升序，返回写入的行数
+async fn write_daily<W: Write>(
+    db: &DatabaseConnection,
+    writer: &mut W,
+    names: &HashMap<i32, String>,
+) -> Result<usize, String> {
+    let io_err = |e: std::io::Error| format!("写入 CSV 失败: {}", e);
+    write_row(
+        writer,
+        &["date", "game_id", "game_name", "playtime_minutes"],
+    )
+    .map_err(io_err)?;
+
+    let statistics = GameStatistics::find()
+        .order_by_asc(game_statistics::Column::GameId)
+        .all(db)
+        .await
+        .map_err(|e| format!("读取游戏统计失败: {}", e))?;
+    let mut rows = 0;
+
+    for stats in statistics {
+        let Some(json) = stats.daily_stats.as_deref() else {
+            continue;
+        };
+        let mut daily = match GameStatsRepository::parse_daily_stats(json) {
+            Ok(daily) => daily,
+            Err(e) => {
+                log::warn!("跳过无法解析的每日统计 game_id={}: {}", stats.game_id, e);
+                continue;
+            }
+        };
+        daily.sort_by(|a, b| a.date.cmp(&b.date));
+
+        let game_id = stats.game_id.to_string();
+        let name = names.get(&stats.game_id).map_or("", String::as_str);
+        for day in daily.iter().filter(|day| day.playtime > 0) {
+            write_row(
+                writer,
+                &[&day.date, &game_id, name, &day.playtime.to_string()],
+            )
+            .map_err(io_err)?;
+            rows += 1;
+        }
+    }
+
+    Ok(rows)
+}
+
+/// 导出游戏统计为 CSV 文件
+///
+/// 文件以 UTF-8 BOM 开头，Excel 打开时可正确识别中文游戏名；时间为本地时间。
+///
+/// # Arguments
+/// * `path` - 导出文件路径
+/// * `scope` - 导出范围：`sessions` 会话明细 / `daily` 每日汇总
+/// * `language` - 界面语言，`zh-CN` 时优先使用中文游戏名
+///
+/// # Returns
+/// * `Result<BackupResult, String>` - 导出结果或错误消息
+#[command]
+pub async fn export_statistics_csv(
+    db: State<'_, DbState>,
+    path: String,
+    scope: CsvExportScope,
+    language: Option<String>,
+) -> Result<BackupResult, String> {
+    let _timer = CommandTimer::start("export_statistics_csv");
+    let db = &db.get();
+    let names = load_game_names(db, language.as_deref() == Some("zh-CN")).await?;
+
+    if let Some(parent) = Path::new(&path).parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+    let file = File::create(&path).map_err(|e| format!("创建导出文件失败: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all("\u{feff}".as_bytes())
+        .map_err(|e| format!("写入 CSV 失败: {}", e))?;
+
+    let rows = match scope {
+        CsvExportScope::Sessions => write_sessions(db, &mut writer, &names).await?,
+        CsvExportScope::Daily => write_daily(db, &mut writer, &names).await?,
+    };
+    writer
+        .flush()
+        .map_err(|e| format!("写入 CSV 失败: {}", e))?;
+
+    log::info!(
+        "游戏统计 CSV 导出成功: {} scope={:?} rows={}",
+        path,
+        scope,
+        rows
+    );
+
+    Ok(BackupResult {
+        success: true,
+        path: Some(path),
+        message: format!("已导出 {} 行统计数据", rows),
+        pruned_backups: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_fields_are_not_quoted() {
+        assert_eq!(escape_csv_field("Summer Pockets"), "Summer Pockets");
+        assert_eq!(escape_csv_field("千恋＊万花"), "千恋＊万花");
+    }
+
+    #[test]
+    fn special_characters_are_quoted_and_escaped() {
+        assert_eq!(escape_csv_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_csv_field("line1\nline2"), "\"line1\nline2\"");
+    }
+
+    #[test]
+    fn rows_join_escaped_fields() {
+        let mut buffer = Vec::new();
+        write_row(&mut buffer, &["1", "a,b", ""]).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "1,\"a,b\",\n");
+    }
+}
@@ -0,0 +1,193 @@
+//! 命令行参数
+//!
+//! 供脚本与计划任务调用，不必打开界面：
+//! - `--launch <id>`：启动游戏；应用已在运行时交给当前实例处理，否则正常启动应用后再启动游戏，
+//!   游戏时间照常统计
+//! - `--backup-db`：备份数据库到设置中的备份目录
+//! - `--backup-save <id>`：备份游戏存档（使用游戏设置中的存档路径）并写入备份记录
+//! - `--export-library <path>`：导出游戏库 JSON
+//!
+//! 除 `--launch` 外都在创建窗口前执行，完成后以退出码结束进程：0 成功，1 执行失败，2 参数错误。
+//! 未识别的参数（如开机自启附带的参数、`reina://` 链接）保持原样交给应用处理。
+
+use crate::backup::database::backup_database_file;
+use crate::backup::library::export_library_to;
+use crate::backup::savedata::write_savedata_backup;
+use crate::database::db::{close_connection, establish_connection};
+use crate::database::repository::games_repository::GamesRepository;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::DatabaseConnection;
+use std::path::{Path, PathBuf};
+
+/// 命令行指定的操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliCommand {
+    Launch(u32),
+    BackupDb,
+    BackupSave(u32),
+    ExportLibrary(PathBuf),
+}
+
+impl CliCommand {
+    /// 是否在不创建窗口的情况下执行
+    pub fn is_headless(&self) -> bool {
+        !matches!(self, Self::Launch(_))
+    }
+}
+
+fn parse_game_id(flag: &str, value: Option<String>) -> Result<u32, String> {
+    let value = value.ok_or_else(|| format!("{} 需要游戏 ID", flag))?;
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| format!("{} 的游戏 ID 无效: {}", flag, value))
+}
+
+/// 从命令行参数（不含程序路径）中解析操作，没有指定操作时返回 None
+pub fn parse_args<I>(args: I) -> Result<Option<CliCommand>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut command = None;
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--launch" => CliCommand::Launch(parse_game_id(&arg, args.next())?),
+            "--backup-db" => CliCommand::BackupDb,
+            "--backup-save" => CliCommand::BackupSave(parse_game_id(&arg, args.next())?),
+            "--export-library" => CliCommand::ExportLibrary(
+                args.next()
+                    .filter(|path| !path.trim().is_empty())
+                    .map(PathBuf::from)
+                    .ok_or_else(|| "--export-library 需要导出文件路径".to_string())?,
+            ),
+            _ => continue,
+        };
+        if command.replace(parsed).is_some() {
+            return Err("一次只能指定一个操作".to_string());
+        }
+    }
+    Ok(command)
+}
+
+/// 备份游戏存档并写入备份记录
+async fn backup_save(db: &DatabaseConnection, game_id: u32) -> Result<String, String> {
+    let game = GamesRepository::find_by_id(db, game_id as i32)
+        .await
+        .map_err(|e| format!("查询游戏失败: {}", e))?
+        .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+    let save_path = game
+        .savepath
+        .filter(|path| !path.trim().is_empty())
+        .ok_or_else(|| format!("游戏 {} 未设置存档路径", game_id))?;
+
+    let (info, _) =
+        write_savedata_backup(db, game_id as i64, Path::new(&save_path), None, None, None).await?;
+    GamesRepository::save_savedata_record(
+        db,
+        game_id as i32,
+        &info.folder_name,
+        info.backup_time as i32,
+        info.file_size as i32,
+        false,
+        Some(info.sha256),
+    )
+    .await
+    .map_err(|e| format!("保存备份记录失败: {}", e))?;
+    Ok(info.backup_path)
+}
+
+async fn execute(db: &DatabaseConnection, command: CliCommand) -> Result<String, String> {
+    match command {
+        CliCommand::BackupDb => backup_database_file(db, false)
+            .await
+            .map(|result| result.path.unwrap_or_default()),
+        CliCommand::BackupSave(game_id) => backup_save(db, game_id).await,
+        CliCommand::ExportLibrary(path) => {
+            export_library_to(db, path.to_string_lossy().to_string(), true, true)
+                .await
+                .map(|result| result.path.unwrap_or_default())
+        }
+        CliCommand::Launch(_) => Err("启动游戏需要运行应用".to_string()),
+    }
+}
+
+/// 发布版 Windows 程序没有控制台，附加到启动它的终端以便输出结果
+fn attach_console() {
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use windows::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// 执行无界面的操作，返回进程退出码
+///
+/// 只执行数据库迁移，不补记未结束的游戏会话，避免影响正在运行的实例。
+pub fn run_headless(command: CliCommand) -> i32 {
+    attach_console();
+    tauri::async_runtime::block_on(async move {
+        let db = match establish_connection().await {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("无法建立数据库连接: {}", e);
+                return 1;
+            }
+        };
+        let result = match Migrator::up(&db, None).await {
+            Ok(()) => execute(&db, command).await,
+            Err(e) => Err(format!("数据库迁移失败: {}", e)),
+        };
+        let _ = close_connection(db).await;
+        match result {
+            Ok(output) => {
+                println!("{}", output);
+                0
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        }
+    })
+}
+
+/// 参数错误时输出原因，返回进程退出码
+pub fn report_usage_error(error: &str) -> i32 {
+    attach_console();
+    eprintln!("{}", error);
+    eprintln!("用法: --launch <id> | --backup-db | --backup-save <id> | --export-library <path>");
+    2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<CliCommand>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_commands_and_ignores_other_args() {
+        assert_eq!(parse(&["--flag1", "--flag2"]), Ok(None));
+        assert_eq!(
+            parse(&["reina://launch/3", "--launch", "3"]),
+            Ok(Some(CliCommand::Launch(3)))
+        );
+        assert_eq!(parse(&["--backup-db"]), Ok(Some(CliCommand::BackupDb)));
+        assert_eq!(
+            parse(&["--export-library", "out.json"]),
+            Ok(Some(CliCommand::ExportLibrary(PathBuf::from("out.json"))))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert!(parse(&["--launch"]).is_err());
+        assert!(parse(&["--backup-save", "abc"]).is_err());
+        assert!(parse(&["--export-library"]).is_err());
+        assert!(parse(&["--backup-db", "--launch", "1"]).is_err());
+    }
+}
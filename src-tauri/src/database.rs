@@ -1,5 +1,7 @@
 pub mod db;
 pub mod dto;
+pub mod integrity;
+pub mod metadata_diff;
 pub mod repository;
 pub mod service;
 
@@ -1,8 +1,13 @@
+use migration::{Migrator, MigratorTrait};
+use parking_lot::RwLock;
 use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr, RuntimeErr};
 use std::fs;
 use std::time::Duration;
 use url::Url;
 
+use crate::database::repository::session_checkpoints_repository::SessionCheckpointsRepository;
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::utils::metrics;
 use reina_path::{get_db_path, is_portable_mode};
 
 // ==================== 数据库连接管理 ====================
@@ -61,7 +66,14 @@ pub async fn establish_connection() -> Result<DatabaseConnection, DbErr> {
     }
 
     // 6. 连接数据库
-    Database::connect(options).await
+    let mut conn = Database::connect(options).await?;
+
+    // 7. 记录慢查询，供性能统计使用
+    conn.set_metric_callback(|info| {
+        metrics::record_query(info.statement, info.elapsed, info.failed);
+    });
+
+    Ok(conn)
 }
 
 /// 关闭数据库连接
@@ -69,3 +81,66 @@ pub async fn close_connection(conn: DatabaseConnection) -> Result<(), DbErr> {
     conn.close().await?;
     Ok(())
 }
+
+/// 托管在 Tauri 状态中的数据库连接
+///
+/// 托管状态注册后无法替换，导入数据库覆盖文件后要换成新连接，只能由这里持有连接并原地替换。
+/// 命令开头用 `get()` 取出当前连接（与状态共享连接池，克隆开销很小），后台任务每轮重新获取，
+/// 不要跨越重连长期保存取出的连接。
+pub struct DbState(RwLock<DatabaseConnection>);
+
+impl DbState {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self(RwLock::new(conn))
+    }
+
+    /// 当前的数据库连接
+    pub fn get(&self) -> DatabaseConnection {
+        self.0.read().clone()
+    }
+
+    /// 换成新连接，返回旧连接
+    pub fn replace(&self, conn: DatabaseConnection) -> DatabaseConnection {
+        std::mem::replace(&mut *self.0.write(), conn)
+    }
+}
+
+/// 执行数据库迁移，补记上次运行中途崩溃而未结束的游戏会话，并把明文令牌迁移到系统凭据管理器
+///
+/// 启动时与导入数据库后重连时都会调用；迁移失败只记录日志，与启动流程保持一致。
+pub async fn prepare_connection(conn: &DatabaseConnection) {
+    log::debug!("开始执行数据库迁移...");
+    match Migrator::up(conn, None).await {
+        Ok(_) => log::info!("数据库迁移完成"),
+        Err(e) => log::error!("数据库迁移失败: {}", e),
+    }
+
+    match SessionCheckpointsRepository::reconcile_orphans(conn).await {
+        Ok(0) => {}
+        Ok(count) => log::info!("已补记 {} 个未正常结束的游戏会话", count),
+        Err(e) => log::warn!("补记未结束的游戏会话失败: {}", e),
+    }
+
+    match SettingsRepository::migrate_secrets(conn).await {
+        Ok(true) => log::info!("已将数据库中的令牌迁移到系统凭据管理器"),
+        Ok(false) => {}
+        Err(e) => log::warn!("迁移令牌到系统凭据管理器失败: {}", e),
+    }
+}
+
+/// 重新打开数据库文件并替换托管状态中的连接，用于导入数据库覆盖文件之后
+///
+/// 调用前旧连接应已关闭；这里仍会再关闭一次被替换下来的连接，确保连接池不残留。
+pub async fn reconnect(state: &DbState) -> Result<(), String> {
+    let conn = establish_connection()
+        .await
+        .map_err(|e| format!("重新连接数据库失败: {}", e))?;
+    prepare_connection(&conn).await;
+
+    let old = state.replace(conn);
+    if let Err(e) = close_connection(old).await {
+        log::debug!("关闭旧数据库连接失败: {}", e);
+    }
+    log::info!("数据库连接已重建");
+    Ok(())
+}
@@ -3,13 +3,32 @@
 //! 用于前后端数据交互的结构定义。
 //! 重构后采用单表架构，元数据以 JSON 列形式嵌入 games 表。
 
+use crate::entity::auto_clear_rules::AutoClearRules;
+use crate::entity::backup_compression::BackupCompression;
+use crate::entity::backup_schedule::BackupSchedule;
 use crate::entity::bgm_data::BgmData;
 use crate::entity::custom_data::CustomData;
+use crate::entity::game_runner::GameRunner;
 use crate::entity::games;
+use crate::entity::http_api_settings::HttpApiSettings;
+use crate::entity::input_mapping::InputMapping;
 use crate::entity::kun_data::KunData;
+use crate::entity::launch_args::LaunchArgs;
+use crate::entity::log_settings::LogSettings;
+use crate::entity::monitor_settings::MonitorSettings;
+use crate::entity::notification_settings::NotificationSettings;
+use crate::entity::presence_settings::PresenceSettings;
+use crate::entity::sandbox_config::SandboxConfig;
+use crate::entity::savedata_quota::SavedataQuota;
+use crate::entity::scan_config::ScanConfig;
+use crate::entity::setup_progress::SetupProgress;
+use crate::entity::tray_settings::TraySettings;
 use crate::entity::user::BgmAuth;
 use crate::entity::vndb_data::VndbData;
+use crate::entity::window_placement::WindowPlacement;
 use crate::entity::ymgal_data::YmgalData;
+use crate::entity::{collections, user};
+use crate::game::monitor::ActiveSessionInfo;
 use serde::{Deserialize, Deserializer, Serialize};
 
 /// 辅助函数：支持 Option<Option<T>> 的反序列化
@@ -58,6 +77,7 @@ impl InsertGameData {
         self.date = clean_option_string(self.date);
         self.localpath = clean_option_string(self.localpath);
         self.savepath = clean_option_string(self.savepath);
+        self.custom_data = self.custom_data.map(CustomData::with_normalized_review);
         self
     }
 }
@@ -73,6 +93,18 @@ impl UpdateGameData {
         self.date = clean_double_option_string(self.date);
         self.localpath = clean_double_option_string(self.localpath);
         self.savepath = clean_double_option_string(self.savepath);
+        self.launch_uri = clean_double_option_string(self.launch_uri);
+        self.launch_process_name = clean_double_option_string(self.launch_process_name);
+        self.screenshot_dir = clean_double_option_string(self.screenshot_dir);
+        self.launch_args = self
+            .launch_args
+            .map(|args| args.and_then(LaunchArgs::normalized));
+        self.custom_data = self
+            .custom_data
+            .map(|data| data.map(CustomData::with_normalized_review));
+        self.backup_schedule = self
+            .backup_schedule
+            .map(|schedule| schedule.filter(|schedule| !schedule.is_empty()));
         self
     }
 }
@@ -139,6 +171,42 @@ pub struct UpdateSettingsData {
     pub le_path: Option<Option<String>>,
     #[serde(default, deserialize_with = "double_option")]
     pub magpie_path: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub auto_clear_rules: Option<Option<AutoClearRules>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub savedata_quota: Option<Option<SavedataQuota>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub monitor_settings: Option<Option<MonitorSettings>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub notification_settings: Option<Option<NotificationSettings>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub presence_settings: Option<Option<PresenceSettings>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub backup_schedule: Option<Option<BackupSchedule>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub scan_config: Option<Option<ScanConfig>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub http_api: Option<Option<HttpApiSettings>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub tray_settings: Option<Option<TraySettings>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub log_settings: Option<Option<LogSettings>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub usage_stats_enabled: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub setup_progress: Option<Option<SetupProgress>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub backup_compression: Option<Option<BackupCompression>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub use_trash: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub max_db_backups: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub max_backup_age_days: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub file_lock_retries: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub file_lock_retry_delay_ms: Option<Option<i32>>,
 }
 
 /// 清洗 UpdateSettingsData 中的空字符串
@@ -153,6 +221,42 @@ impl UpdateSettingsData {
         self.magpie_path = clean_double_option_string(self.magpie_path);
         self
     }
+
+    /// 本次更新涉及的设置项（前端使用的 camelCase 名称）
+    pub fn changed_keys(&self) -> Vec<&'static str> {
+        [
+            (self.bgm_auth.is_some(), "bgmAuth"),
+            (self.vndb_token.is_some(), "vndbToken"),
+            (self.save_root_path.is_some(), "saveRootPath"),
+            (self.db_backup_path.is_some(), "dbBackupPath"),
+            (self.le_path.is_some(), "lePath"),
+            (self.magpie_path.is_some(), "magpiePath"),
+            (self.auto_clear_rules.is_some(), "autoClearRules"),
+            (self.savedata_quota.is_some(), "savedataQuota"),
+            (self.monitor_settings.is_some(), "monitorSettings"),
+            (self.notification_settings.is_some(), "notificationSettings"),
+            (self.presence_settings.is_some(), "presenceSettings"),
+            (self.backup_schedule.is_some(), "backupSchedule"),
+            (self.scan_config.is_some(), "scanConfig"),
+            (self.http_api.is_some(), "httpApi"),
+            (self.tray_settings.is_some(), "traySettings"),
+            (self.log_settings.is_some(), "logSettings"),
+            (self.usage_stats_enabled.is_some(), "usageStatsEnabled"),
+            (self.setup_progress.is_some(), "setupProgress"),
+            (self.backup_compression.is_some(), "backupCompression"),
+            (self.use_trash.is_some(), "useTrash"),
+            (self.max_db_backups.is_some(), "maxDbBackups"),
+            (self.max_backup_age_days.is_some(), "maxBackupAgeDays"),
+            (self.file_lock_retries.is_some(), "fileLockRetries"),
+            (
+                self.file_lock_retry_delay_ms.is_some(),
+                "fileLockRetryDelayMs",
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(changed, key)| changed.then_some(key))
+        .collect()
+    }
 }
 
 /// 用于插入游戏的数据结构（单表架构）
@@ -191,21 +295,60 @@ pub struct BatchOperationError {
     pub message: String,
 }
 
+/// 批量操作中单项的处理结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    Inserted,
+    /// 本地路径或数据源 ID 与已有游戏（或同一批中靠前的项）重复
+    Skipped,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub status: BatchItemStatus,
+    /// 插入后的游戏 ID；跳过时为与之重复的游戏 ID
+    pub id: Option<i32>,
+    /// 跳过或失败的原因
+    pub message: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BatchOperationResult {
     pub total: usize,
     pub success: usize,
     pub failed: usize,
+    #[serde(default)]
+    pub skipped: usize,
     pub ids: Vec<i32>,
     pub games: Vec<games::Model>,
     pub errors: Vec<BatchOperationError>,
+    /// 每一项的处理结果，与传入顺序一致
+    #[serde(default)]
+    pub items: Vec<BatchItemResult>,
+}
+
+/// 启动时一次性获取的初始数据，减少前端启动阶段的多次 IPC 往返
+#[derive(Clone, Debug, Serialize)]
+pub struct InitialAppState {
+    pub settings: user::Model,
+    /// 所有合集的扁平列表，前端按 parent_id 组装树
+    pub collections: Vec<collections::Model>,
+    /// 按指定排序的第一页游戏
+    pub games: Vec<games::Model>,
+    pub total_games: u64,
+    pub active_sessions: Vec<ActiveSessionInfo>,
+    /// 今天所有游戏的游戏时间（分钟）
+    pub today_playtime: i32,
 }
 
 /// 用于更新游戏的数据结构（单表架构）
 ///
 /// 所有字段均为 Option，允许部分更新。
 /// 使用 Option<Option<T>> 来区分"未提供"和"设为 null"。
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct UpdateGameData {
     // === 外部 ID ===
     #[serde(default, deserialize_with = "double_option")]
@@ -235,6 +378,30 @@ pub struct UpdateGameData {
     pub le_launch: Option<Option<i32>>,
     #[serde(default, deserialize_with = "double_option")]
     pub magpie: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub launch_uri: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub launch_process_name: Option<Option<String>>,
+    /// 传入 null 或空数组时清除启动参数
+    #[serde(default, deserialize_with = "double_option")]
+    pub launch_args: Option<Option<LaunchArgs>>,
+    /// 传入 null 时恢复为全局的 linux_launch_command
+    #[serde(default, deserialize_with = "double_option")]
+    pub runner: Option<Option<GameRunner>>,
+    /// 传入 null 时关闭沙盒
+    #[serde(default, deserialize_with = "double_option")]
+    pub sandbox: Option<Option<SandboxConfig>>,
+    /// 传入 null 时删除手柄映射
+    #[serde(default, deserialize_with = "double_option")]
+    pub input_mapping: Option<Option<InputMapping>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub screenshot_dir: Option<Option<String>>,
+    /// 传入 null 时清除记录的窗口位置
+    #[serde(default, deserialize_with = "double_option")]
+    pub window_placement: Option<Option<WindowPlacement>>,
+    /// 覆盖全局自动备份计划，传入 null 时恢复为全局设置
+    #[serde(default, deserialize_with = "double_option")]
+    pub backup_schedule: Option<Option<BackupSchedule>>,
     // === JSON 元数据 ===
     #[serde(default, deserialize_with = "double_option")]
     pub vndb_data: Option<Option<VndbData>>,
@@ -0,0 +1,297 @@
+//! 数据完整性检查
+//!
+//! 旧版本删除游戏时没有开启外键级联，手动删除备份文件、移动游戏目录也会让数据库记录失效。
+//! `run_integrity_check` 只读地找出这些问题，前端展示后由用户勾选，再交给 `fix_integrity_issues` 清理。
+
+use crate::backup::savedata::{resolve_savedata_backup_root, savedata_backup_path};
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::entity::prelude::*;
+use crate::entity::{game_collection_link, game_statistics, games, savedata};
+use crate::utils::metrics::CommandTimer;
+use sea_orm::sea_query::{Expr, Query};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+/// 备份文件已不存在的存档备份记录
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingSavedataFile {
+    pub id: i32,
+    pub game_id: i32,
+    pub file: String,
+}
+
+/// 指向不存在游戏的合集关联
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanedCollectionLink {
+    pub id: i32,
+    pub game_id: i32,
+    pub collection_id: i32,
+}
+
+/// 本地路径已不存在的游戏
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingLocalPath {
+    pub game_id: i32,
+    pub localpath: String,
+}
+
+/// 完整性检查报告
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub missing_savedata_files: Vec<MissingSavedataFile>,
+    /// 游戏已删除但仍存在的统计行（game_id）
+    pub orphaned_statistics: Vec<i32>,
+    pub orphaned_collection_links: Vec<OrphanedCollectionLink>,
+    /// 可执行文件不存在的游戏；游戏位于未连接的移动硬盘上时也会出现在这里
+    pub missing_local_paths: Vec<MissingLocalPath>,
+}
+
+/// 要修复的问题，取自 `IntegrityReport` 中对应项的 ID
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct IntegritySelection {
+    /// 删除这些存档备份记录（仅在备份文件确实不存在时生效）
+    pub savedata_record_ids: Vec<i32>,
+    /// 删除这些游戏的统计行（仅在游戏确实不存在时生效）
+    pub statistics_game_ids: Vec<i32>,
+    /// 删除这些合集关联（仅在游戏确实不存在时生效）
+    pub collection_link_ids: Vec<i32>,
+    /// 清空这些游戏的 localpath（仅在路径确实不存在时生效）
+    pub localpath_game_ids: Vec<i32>,
+}
+
+/// 修复结果
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityFixResult {
+    pub removed_savedata_records: u64,
+    pub removed_statistics: u64,
+    pub removed_collection_links: u64,
+    pub cleared_localpaths: u64,
+}
+
+/// 所有游戏 ID 的子查询
+fn game_ids_subquery() -> sea_orm::sea_query::SelectStatement {
+    Query::select()
+        .column(games::Column::Id)
+        .from(games::Entity)
+        .to_owned()
+}
+
+/// 从选中的存档备份记录与游戏中筛出文件此刻仍不存在的项
+async fn still_missing(
+    db: &DatabaseConnection,
+    savedata_record_ids: &[i32],
+    localpath_game_ids: &[i32],
+) -> Result<(Vec<i32>, Vec<i32>), String> {
+    let backup_root = resolve_savedata_backup_root(db).await?;
+    let records = if savedata_record_ids.is_empty() {
+        Vec::new()
+    } else {
+        Savedata::find()
+            .filter(savedata::Column::Id.is_in(savedata_record_ids.iter().copied()))
+            .all(db)
+            .await
+            .map_err(|e| format!("获取备份记录失败: {}", e))?
+    };
+    let local_paths: Vec<(i32, String)> = if localpath_game_ids.is_empty() {
+        Vec::new()
+    } else {
+        Games::find()
+            .select_only()
+            .column(games::Column::Id)
+            .column(games::Column::Localpath)
+            .filter(games::Column::Id.is_in(localpath_game_ids.iter().copied()))
+            .filter(games::Column::Localpath.is_not_null())
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| format!("获取游戏路径失败: {}", e))?
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let savedata_record_ids = records
+            .into_iter()
+            .filter(|record| {
+                !savedata_backup_path(&backup_root, record.game_id, &record.file).is_file()
+            })
+            .map(|record| record.id)
+            .collect();
+        let localpath_game_ids = local_paths
+            .into_iter()
+            .filter(|(_, localpath)| !Path::new(localpath).exists())
+            .map(|(game_id, _)| game_id)
+            .collect();
+        (savedata_record_ids, localpath_game_ids)
+    })
+    .await
+    .map_err(|e| format!("检查数据完整性失败: {}", e))
+}
+
+/// 检查数据库记录与磁盘文件的一致性，不修改任何数据
+///
+/// # Returns
+/// * `Result<IntegrityReport, String>` - 各类问题的列表
+#[tauri::command]
+pub async fn run_integrity_check(db: State<'_, DbState>) -> Result<IntegrityReport, String> {
+    let _timer = CommandTimer::start("run_integrity_check");
+    let db = db.get();
+    let backup_root = resolve_savedata_backup_root(&db).await?;
+    let records = Savedata::find()
+        .order_by_asc(savedata::Column::GameId)
+        .all(&db)
+        .await
+        .map_err(|e| format!("获取备份记录失败: {}", e))?;
+
+    let orphaned_statistics = GameStatistics::find()
+        .select_only()
+        .column(game_statistics::Column::GameId)
+        .filter(Expr::col(game_statistics::Column::GameId).not_in_subquery(game_ids_subquery()))
+        .into_tuple::<i32>()
+        .all(&db)
+        .await
+        .map_err(|e| format!("检查游戏统计失败: {}", e))?;
+
+    let orphaned_collection_links = GameCollectionLink::find()
+        .filter(
+            Expr::col(game_collection_link::Column::GameId).not_in_subquery(game_ids_subquery()),
+        )
+        .all(&db)
+        .await
+        .map_err(|e| format!("检查合集关联失败: {}", e))?
+        .into_iter()
+        .map(|link| OrphanedCollectionLink {
+            id: link.id,
+            game_id: link.game_id,
+            collection_id: link.collection_id,
+        })
+        .collect();
+
+    let local_paths = GamesRepository::get_localpaths_by_id(&db)
+        .await
+        .map_err(|e| format!("获取游戏路径失败: {}", e))?;
+
+    tokio::task::spawn_blocking(move || {
+        let missing_savedata_files = records
+            .into_iter()
+            .filter(|record| {
+                !savedata_backup_path(&backup_root, record.game_id, &record.file).is_file()
+            })
+            .map(|record| MissingSavedataFile {
+                id: record.id,
+                game_id: record.game_id,
+                file: record.file,
+            })
+            .collect();
+        let missing_local_paths = local_paths
+            .into_iter()
+            .filter(|(_, localpath)| !Path::new(localpath).exists())
+            .map(|(game_id, localpath)| MissingLocalPath { game_id, localpath })
+            .collect();
+
+        IntegrityReport {
+            missing_savedata_files,
+            orphaned_statistics,
+            orphaned_collection_links,
+            missing_local_paths,
+        }
+    })
+    .await
+    .map_err(|e| format!("检查数据完整性失败: {}", e))
+}
+
+/// 清理用户选中的完整性问题（同一事务）
+///
+/// 每一项在修复前都会重新检查：统计行与合集关联只在对应游戏确实不存在时删除，
+/// 存档备份记录与 localpath 只在文件此刻仍不存在时清理。这样移动硬盘重新连接后，
+/// 用之前的报告修复也不会误删有效数据。
+///
+/// # Arguments
+/// * `selection` - 要修复的问题
+///
+/// # Returns
+/// * `Result<IntegrityFixResult, String>` - 各类问题实际修复的数量
+#[tauri::command]
+pub async fn fix_integrity_issues(
+    db: State<'_, DbState>,
+    selection: IntegritySelection,
+) -> Result<IntegrityFixResult, String> {
+    let _timer = CommandTimer::start("fix_integrity_issues");
+    let db = db.get();
+    let (savedata_record_ids, localpath_game_ids) = still_missing(
+        &db,
+        &selection.savedata_record_ids,
+        &selection.localpath_game_ids,
+    )
+    .await?;
+
+    let result = async {
+        let txn = db.begin().await?;
+
+        let removed_savedata_records = if savedata_record_ids.is_empty() {
+            0
+        } else {
+            Savedata::delete_many()
+                .filter(savedata::Column::Id.is_in(savedata_record_ids))
+                .exec(&txn)
+                .await?
+                .rows_affected
+        };
+
+        let removed_statistics = if selection.statistics_game_ids.is_empty() {
+            0
+        } else {
+            GameStatistics::delete_many()
+                .filter(game_statistics::Column::GameId.is_in(selection.statistics_game_ids))
+                .filter(
+                    Expr::col(game_statistics::Column::GameId).not_in_subquery(game_ids_subquery()),
+                )
+                .exec(&txn)
+                .await?
+                .rows_affected
+        };
+
+        let removed_collection_links = if selection.collection_link_ids.is_empty() {
+            0
+        } else {
+            GameCollectionLink::delete_many()
+                .filter(game_collection_link::Column::Id.is_in(selection.collection_link_ids))
+                .filter(
+                    Expr::col(game_collection_link::Column::GameId)
+                        .not_in_subquery(game_ids_subquery()),
+                )
+                .exec(&txn)
+                .await?
+                .rows_affected
+        };
+
+        let cleared_localpaths = if localpath_game_ids.is_empty() {
+            0
+        } else {
+            Games::update_many()
+                .col_expr(
+                    games::Column::Localpath,
+                    Expr::value(Option::<String>::None),
+                )
+                .filter(games::Column::Id.is_in(localpath_game_ids))
+                .exec(&txn)
+                .await?
+                .rows_affected
+        };
+
+        txn.commit().await?;
+        Ok::<_, DbErr>(IntegrityFixResult {
+            removed_savedata_records,
+            removed_statistics,
+            removed_collection_links,
+            cleared_localpaths,
+        })
+    }
+    .await
+    .map_err(|e| format!("修复数据完整性问题失败: {}", e))?;
+
+    log::info!("数据完整性问题已修复: {:?}", result);
+    Ok(result)
+}
@@ -0,0 +1,203 @@
+//! 元数据批量更新的预览与选择性应用
+//!
+//! 批量刷新元数据前先逐字段对比 JSON 列的新旧值，用户勾选后只提交被接受的字段。
+
+use crate::database::dto::UpdateGameData;
+use crate::entity::games;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// 可对比的元数据列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataColumn {
+    BgmData,
+    VndbData,
+    YmgalData,
+    KunData,
+    CustomData,
+    /// games.date 普通列，字段名固定为 `date`
+    Date,
+}
+
+/// 单个字段的新旧值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub column: MetadataColumn,
+    pub field: String,
+    pub old_value: Value,
+    pub new_value: Value,
+}
+
+/// 单个游戏的差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameMetadataDiff {
+    pub game_id: i32,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// 用户接受的单个字段变更，`value` 为 null 表示删除该字段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptedFieldChange {
+    pub game_id: i32,
+    pub column: MetadataColumn,
+    pub field: String,
+    pub value: Value,
+}
+
+/// 把 JSON 列序列化为对象，列为空时返回空对象
+fn to_object<T: Serialize>(data: Option<&T>) -> Map<String, Value> {
+    match data.map(serde_json::to_value) {
+        Some(Ok(Value::Object(map))) => map,
+        _ => Map::new(),
+    }
+}
+
+/// 逐字段对比，值相同或双方都为空的字段不输出
+fn diff_objects(
+    column: MetadataColumn,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    diffs: &mut Vec<FieldDiff>,
+) {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let old_value = old.get(key).cloned().unwrap_or(Value::Null);
+        let new_value = new.get(key).cloned().unwrap_or(Value::Null);
+        if old_value != new_value {
+            diffs.push(FieldDiff {
+                column,
+                field: key.clone(),
+                old_value,
+                new_value,
+            });
+        }
+    }
+}
+
+/// 对比单列：`None` 表示更新中未提供该列，不参与对比
+fn diff_column<T: Serialize>(
+    column: MetadataColumn,
+    old: Option<&T>,
+    new: Option<&Option<T>>,
+    diffs: &mut Vec<FieldDiff>,
+) {
+    if let Some(new) = new {
+        diff_objects(column, &to_object(old), &to_object(new.as_ref()), diffs);
+    }
+}
+
+/// 计算游戏当前数据与待更新数据之间的字段差异
+pub fn diff_game(game: &games::Model, update: &UpdateGameData) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+
+    diff_column(
+        MetadataColumn::BgmData,
+        game.bgm_data.as_ref(),
+        update.bgm_data.as_ref(),
+        &mut diffs,
+    );
+    diff_column(
+        MetadataColumn::VndbData,
+        game.vndb_data.as_ref(),
+        update.vndb_data.as_ref(),
+        &mut diffs,
+    );
+    diff_column(
+        MetadataColumn::YmgalData,
+        game.ymgal_data.as_ref(),
+        update.ymgal_data.as_ref(),
+        &mut diffs,
+    );
+    diff_column(
+        MetadataColumn::KunData,
+        game.kun_data.as_ref(),
+        update.kun_data.as_ref(),
+        &mut diffs,
+    );
+    diff_column(
+        MetadataColumn::CustomData,
+        game.custom_data.as_ref(),
+        update.custom_data.as_ref(),
+        &mut diffs,
+    );
+
+    if let Some(new_date) = &update.date
+        && new_date != &game.date
+    {
+        diffs.push(FieldDiff {
+            column: MetadataColumn::Date,
+            field: "date".to_string(),
+            old_value: game.date.clone().map(Value::String).unwrap_or(Value::Null),
+            new_value: new_date.clone().map(Value::String).unwrap_or(Value::Null),
+        });
+    }
+
+    diffs
+}
+
+/// 在当前 JSON 列上应用被接受的字段，全部字段被删除时整列置空
+fn patch_column<T>(
+    current: Option<&T>,
+    changes: &[&AcceptedFieldChange],
+) -> Result<Option<Option<T>>, String>
+where
+    T: Serialize + DeserializeOwned,
+{
+    if changes.is_empty() {
+        return Ok(None);
+    }
+
+    let mut object = to_object(current);
+    for change in changes {
+        if change.value.is_null() {
+            object.remove(&change.field);
+        } else {
+            object.insert(change.field.clone(), change.value.clone());
+        }
+    }
+
+    if object.is_empty() {
+        return Ok(Some(None));
+    }
+    serde_json::from_value(Value::Object(object))
+        .map(|data| Some(Some(data)))
+        .map_err(|e| format!("字段值格式错误: {}", e))
+}
+
+/// 把单个游戏被接受的变更转换为 `UpdateGameData`
+pub fn build_update(
+    game: &games::Model,
+    changes: &[AcceptedFieldChange],
+) -> Result<UpdateGameData, String> {
+    let mut by_column: BTreeMap<MetadataColumn, Vec<&AcceptedFieldChange>> = BTreeMap::new();
+    for change in changes {
+        by_column.entry(change.column).or_default().push(change);
+    }
+    let column = |column| by_column.get(&column).map(Vec::as_slice).unwrap_or(&[]);
+
+    let date = column(MetadataColumn::Date)
+        .last()
+        .map(|change| match &change.value {
+            Value::String(date) => Some(date.clone()),
+            _ => None,
+        });
+
+    Ok(UpdateGameData {
+        bgm_data: patch_column(game.bgm_data.as_ref(), column(MetadataColumn::BgmData))?,
+        vndb_data: patch_column(game.vndb_data.as_ref(), column(MetadataColumn::VndbData))?,
+        ymgal_data: patch_column(game.ymgal_data.as_ref(), column(MetadataColumn::YmgalData))?,
+        kun_data: patch_column(game.kun_data.as_ref(), column(MetadataColumn::KunData))?,
+        custom_data: patch_column(
+            game.custom_data.as_ref(),
+            column(MetadataColumn::CustomData),
+        )?,
+        date,
+        ..Default::default()
+    })
+}
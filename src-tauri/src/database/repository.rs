@@ -1,4 +1,8 @@
 pub mod collections_repository;
 pub mod game_stats_repository;
 pub mod games_repository;
+pub mod launch_attempts_repository;
+pub mod screenshots_repository;
+pub mod session_checkpoints_repository;
 pub mod settings_repository;
+pub mod usage_stats_repository;
@@ -1,4 +1,5 @@
 use crate::database::dto::{InsertCollectionData, UpdateCollectionData};
+use crate::database::repository::games_repository::{GamesRepository, TagMatchMode};
 use crate::entity::prelude::*;
 use crate::entity::{collections, game_collection_link};
 use sea_orm::{sea_query::Expr, *};
@@ -7,6 +8,9 @@ use serde::{Deserialize, Serialize};
 /// 合集数据仓库
 pub struct CollectionsRepository;
 
+/// 稀疏排序的间隔：重新编号时相邻项的 sort_order 相差该值，之后的拖拽只需改动被移动的项
+const SORT_ORDER_GAP: i64 = 1024;
+
 /// 带游戏数量的分类
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategoryWithCount {
@@ -17,6 +21,62 @@ pub struct CategoryWithCount {
     pub game_count: u64,
 }
 
+/// 删除合集时游戏关联的去向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollectionReassignment {
+    /// 移到指定合集
+    Collection { id: i32 },
+    /// 移到被删除合集的上级分组
+    Parent,
+}
+
+/// 删除合集的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteCollectionResult {
+    /// 删除的合集数量（不含级联删除的子合集）
+    pub deleted: u64,
+    /// 移到目标合集的游戏数
+    pub moved_links: u64,
+    /// 已在目标合集中、无需移动的游戏数
+    pub skipped_links: u64,
+}
+
+/// 按标签填充合集的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PopulateCollectionResult {
+    /// 匹配标签的游戏数
+    pub matched: u64,
+    /// 新加入合集的游戏数
+    pub added: u64,
+    /// 已在合集中的游戏数
+    pub skipped: u64,
+}
+
+/// 批量移动 / 复制游戏的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionTransferResult {
+    /// 新加入目标合集的游戏数
+    pub added: u64,
+    /// 已在目标合集中、无需加入的游戏数
+    pub skipped: u64,
+    /// 从来源合集移除的游戏数（复制时为 0）
+    pub removed: u64,
+}
+
+/// 任意层级的合集树节点
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionTreeNode {
+    pub id: i32,
+    pub name: String,
+    pub parent_id: Option<i32>,
+    pub sort_order: i32,
+    pub icon: Option<String>,
+    /// 该合集及所有子孙合集中的游戏数（去重）
+    pub game_count: u64,
+    pub children: Vec<CollectionTreeNode>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct GameCollectionPair {
     game_id: i32,
@@ -165,8 +225,119 @@ impl CollectionsRepository {
         Ok(())
     }
 
-    async fn update_game_collection_sort_orders(
+    /// 计算把项目排成 `ordered` 顺序所需的 sort_order 更新
+    ///
+    /// `ordered` 为目标顺序下的 `(id, 当前 sort_order)`。当前排序值构成最长递增子序列的项保持不动，
+    /// 其余项插入到相邻保留项之间的空位；空位不足或超出范围时整体按 `SORT_ORDER_GAP` 重新编号（压缩）。
+    fn plan_sparse_reorder(ordered: &[(i32, i32)]) -> Vec<(i32, i32)> {
+        let orders: Vec<i64> = ordered.iter().map(|(_, order)| *order as i64).collect();
+        let keep = Self::longest_increasing_mask(&orders);
+        let mut assigned = orders.clone();
+        let mut previous: Option<i64> = None;
+        let mut index = 0;
+
+        while index < orders.len() {
+            if keep[index] {
+                previous = Some(orders[index]);
+                index += 1;
+                continue;
+            }
+
+            let start = index;
+            while index < orders.len() && !keep[index] {
+                index += 1;
+            }
+            let run = (index - start) as i64;
+            let next = orders.get(index).copied();
+            let (base, step) = match (previous, next) {
+                (Some(low), Some(high)) => (low, (high - low) / (run + 1)),
+                (Some(low), None) => (low, SORT_ORDER_GAP),
+                (None, Some(high)) => (high - SORT_ORDER_GAP * (run + 1), SORT_ORDER_GAP),
+                (None, None) => (-SORT_ORDER_GAP, SORT_ORDER_GAP),
+            };
+            let last = base + step * run;
+            if step < 1 || base < i32::MIN as i64 || last > i32::MAX as i64 {
+                return Self::plan_compaction(ordered);
+            }
+            for offset in 0..run {
+                assigned[start + offset as usize] = base + step * (offset + 1);
+            }
+            previous = Some(last);
+        }
+
+        ordered
+            .iter()
+            .zip(assigned)
+            .filter(|((_, current), new)| *current as i64 != *new)
+            .map(|((id, _), new)| (*id, new as i32))
+            .collect()
+    }
+
+    /// 按目标顺序整体重新编号为 0, GAP, 2*GAP...
+    fn plan_compaction(ordered: &[(i32, i32)]) -> Vec<(i32, i32)> {
+        ordered
+            .iter()
+            .enumerate()
+            .map(|(index, (id, current))| (*id, *current, index as i64 * SORT_ORDER_GAP))
+            .filter(|(_, current, new)| *current as i64 != *new)
+            .map(|(id, _, new)| (id, new as i32))
+            .collect()
+    }
+
+    /// 标记严格递增的最长子序列（O(n log n)）
+    fn longest_increasing_mask(values: &[i64]) -> Vec<bool> {
+        // tails[k]：长度为 k+1 的递增子序列中结尾最小的元素下标
+        let mut tails: Vec<usize> = Vec::new();
+        let mut parents: Vec<Option<usize>> = vec![None; values.len()];
+
+        for (index, value) in values.iter().enumerate() {
+            let position = tails.partition_point(|&tail| values[tail] < *value);
+            parents[index] = position.checked_sub(1).map(|prev| tails[prev]);
+            if position == tails.len() {
+                tails.push(index);
+            } else {
+                tails[position] = index;
+            }
+        }
+
+        let mut mask = vec![false; values.len()];
+        let mut cursor = tails.last().copied();
+        while let Some(index) = cursor {
+            mask[index] = true;
+            cursor = parents[index];
+        }
+        mask
+    }
+
+    /// 按目标顺序排列 `current`（id → 当前 sort_order），不在 `ordered_ids` 中的项按原顺序排在末尾
+    fn arrange_by_ids(
+        current: Vec<(i32, i32)>,
+        key_of: impl Fn(i32) -> i32,
+        ordered_ids: &[i32],
+    ) -> Result<Vec<(i32, i32)>, DbErr> {
+        use std::collections::HashMap;
+
+        let mut by_key: HashMap<i32, (i32, i32)> = current
+            .iter()
+            .map(|(id, order)| (key_of(*id), (*id, *order)))
+            .collect();
+        let mut arranged = Vec::with_capacity(current.len());
+        for key in Self::unique_ids(ordered_ids.to_vec()) {
+            let item = by_key
+                .remove(&key)
+                .ok_or_else(|| DbErr::Custom(format!("ID {} 不在待排序的列表中", key)))?;
+            arranged.push(item);
+        }
+
+        let mut rest: Vec<(i32, i32)> = by_key.into_values().collect();
+        rest.sort_by_key(|(id, order)| (*order, *id));
+        arranged.extend(rest);
+        Ok(arranged)
+    }
+
+    async fn update_sort_orders(
         txn: &DatabaseTransaction,
+        table: &str,
         updates: Vec<(i32, i32)>,
     ) -> Result<(), DbErr> {
         if updates.is_empty() {
@@ -184,8 +355,8 @@ impl CollectionsRepository {
             .collect::<Vec<_>>()
             .join(", ");
         let sql = format!(
-            "UPDATE game_collection_link SET sort_order = CASE {} END WHERE id IN ({})",
-            case_clause, ids
+            "UPDATE {} SET sort_order = CASE {} END WHERE id IN ({})",
+            table, case_clause, ids
         );
 
         txn.execute(Statement::from_string(DatabaseBackend::Sqlite, sql))
@@ -266,6 +437,115 @@ impl CollectionsRepository {
             .await
     }
 
+    /// 获取所有合集（扁平列表），按父级与排序字段排列
+    pub async fn find_all(db: &DatabaseConnection) -> Result<Vec<collections::Model>, DbErr> {
+        Collections::find()
+            .order_by_asc(collections::Column::ParentId)
+            .order_by_asc(collections::Column::SortOrder)
+            .all(db)
+            .await
+    }
+
+    /// 获取完整的合集树（任意层级），每个节点的游戏数包含所有子孙合集
+    ///
+    /// 共两次查询：读取全部合集，再用一条递归 CTE + GROUP BY 统计每个合集子树中的去重游戏数。
+    pub async fn get_collection_tree(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<CollectionTreeNode>, DbErr> {
+        let collections = Self::find_all(db).await?;
+        let counts = Self::count_games_in_subtrees(db).await?;
+
+        Ok(Self::build_collection_tree(collections, &counts))
+    }
+
+    /// 统计每个合集及其子孙合集中的游戏数（去重），没有游戏的合集不在结果中
+    async fn count_games_in_subtrees(
+        db: &DatabaseConnection,
+    ) -> Result<std::collections::HashMap<i32, u64>, DbErr> {
+        // UNION 会去掉重复行，数据中残留父子环时递归也能终止
+        let sql = r#"
+            WITH RECURSIVE subtree(root_id, collection_id) AS (
+                SELECT id, id FROM collections
+                UNION
+                SELECT subtree.root_id, collections.id
+                FROM subtree
+                JOIN collections ON collections.parent_id = subtree.collection_id
+            )
+            SELECT subtree.root_id AS root_id,
+                   COUNT(DISTINCT game_collection_link.game_id) AS game_count
+            FROM subtree
+            JOIN game_collection_link ON game_collection_link.collection_id = subtree.collection_id
+            GROUP BY subtree.root_id
+        "#;
+
+        let rows = db
+            .query_all(Statement::from_string(DatabaseBackend::Sqlite, sql))
+            .await?;
+        rows.into_iter()
+            .map(|row| {
+                let root_id: i32 = row.try_get("", "root_id")?;
+                let game_count: i64 = row.try_get("", "game_count")?;
+                Ok((root_id, game_count as u64))
+            })
+            .collect()
+    }
+
+    /// 由扁平的合集列表与每个子树的游戏数构建合集树
+    ///
+    /// 父合集不存在的合集视为根节点；同级按 sort_order 排列。
+    fn build_collection_tree(
+        collections: Vec<collections::Model>,
+        counts: &std::collections::HashMap<i32, u64>,
+    ) -> Vec<CollectionTreeNode> {
+        use std::collections::{HashMap, HashSet};
+
+        fn build(
+            collection: collections::Model,
+            children_of: &mut HashMap<i32, Vec<collections::Model>>,
+            counts: &HashMap<i32, u64>,
+        ) -> CollectionTreeNode {
+            let children = children_of
+                .remove(&collection.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|child| build(child, children_of, counts))
+                .collect();
+
+            CollectionTreeNode {
+                id: collection.id,
+                name: collection.name,
+                parent_id: collection.parent_id,
+                sort_order: collection.sort_order,
+                icon: collection.icon,
+                game_count: counts.get(&collection.id).copied().unwrap_or(0),
+                children,
+            }
+        }
+
+        let ids: HashSet<i32> = collections.iter().map(|collection| collection.id).collect();
+
+        let mut roots = Vec::new();
+        let mut children_of: HashMap<i32, Vec<collections::Model>> = HashMap::new();
+        for collection in collections {
+            match collection.parent_id {
+                Some(parent_id) if ids.contains(&parent_id) => {
+                    children_of.entry(parent_id).or_default().push(collection)
+                }
+                _ => roots.push(collection),
+            }
+        }
+        for siblings in children_of.values_mut() {
+            siblings.sort_by_key(|collection| collection.sort_order);
+        }
+        roots.sort_by_key(|collection| collection.sort_order);
+
+        // 每个节点只会从 children_of 中取出一次，数据中残留的环不会导致无限递归
+        roots
+            .into_iter()
+            .map(|root| build(root, &mut children_of, counts))
+            .collect()
+    }
+
     /// 获取子合集
     pub async fn find_children(
         db: &DatabaseConnection,
@@ -295,6 +575,21 @@ impl CollectionsRepository {
             active.name = Set(n);
         }
         if let Some(p) = data.parent_id {
+            if let Some(parent_id) = p {
+                if Collections::find_by_id(parent_id).one(db).await?.is_none() {
+                    return Err(DbErr::RecordNotFound(
+                        "Parent collection not found".to_string(),
+                    ));
+                }
+                if Self::collect_subtree_ids(db, id)
+                    .await?
+                    .contains(&parent_id)
+                {
+                    return Err(DbErr::Custom(
+                        "不能把合集移动到自身或其子合集下".to_string(),
+                    ));
+                }
+            }
             active.parent_id = Set(p);
         }
         if let Some(s) = data.sort_order {
@@ -309,9 +604,111 @@ impl CollectionsRepository {
         active.update(db).await
     }
 
-    /// 删除合集（会级联删除子合集和游戏关联）
-    pub async fn delete(db: &DatabaseConnection, id: i32) -> Result<DeleteResult, DbErr> {
-        Collections::delete_by_id(id).exec(db).await
+    /// 合集及其所有子孙合集的 ID
+    async fn collect_subtree_ids<C: ConnectionTrait>(db: &C, id: i32) -> Result<Vec<i32>, DbErr> {
+        let all = Collections::find()
+            .select_only()
+            .column(collections::Column::Id)
+            .column(collections::Column::ParentId)
+            .into_tuple::<(i32, Option<i32>)>()
+            .all(db)
+            .await?;
+
+        let mut subtree = vec![id];
+        let mut index = 0;
+        while index < subtree.len() {
+            let parent = subtree[index];
+            subtree.extend(
+                all.iter()
+                    .filter(|(_, parent_id)| *parent_id == Some(parent))
+                    .map(|(child, _)| *child),
+            );
+            index += 1;
+        }
+        Ok(subtree)
+    }
+
+    /// 删除合集（会级联删除子合集和游戏关联），可在同一事务中先把其中的游戏移到另一个合集
+    ///
+    /// 游戏按原合集与原顺序追加到目标合集末尾，已在目标合集中的游戏跳过。
+    /// 目标不能是被删除的合集或其子合集。
+    pub async fn delete_with_reassignment(
+        db: &DatabaseConnection,
+        id: i32,
+        reassign_to: Option<CollectionReassignment>,
+    ) -> Result<DeleteCollectionResult, DbErr> {
+        let txn = db.begin().await?;
+        let collection = Collections::find_by_id(id)
+            .one(&txn)
+            .await?
+            .ok_or(DbErr::RecordNotFound("Collection not found".to_string()))?;
+
+        let mut moved_links = 0;
+        let mut skipped_links = 0;
+        if let Some(reassignment) = reassign_to {
+            let target_id = match reassignment {
+                CollectionReassignment::Collection { id } => id,
+                CollectionReassignment::Parent => collection
+                    .parent_id
+                    .ok_or(DbErr::Custom("根分组没有上级，无法移动游戏".to_string()))?,
+            };
+            let subtree = Self::collect_subtree_ids(&txn, id).await?;
+            if subtree.contains(&target_id) {
+                return Err(DbErr::Custom(
+                    "不能把游戏移到被删除的合集或其子合集中".to_string(),
+                ));
+            }
+            if Collections::find_by_id(target_id)
+                .one(&txn)
+                .await?
+                .is_none()
+            {
+                return Err(DbErr::RecordNotFound(format!(
+                    "Collection {} not found",
+                    target_id
+                )));
+            }
+
+            let game_ids = Self::unique_ids(
+                GameCollectionLink::find()
+                    .filter(game_collection_link::Column::CollectionId.is_in(subtree))
+                    .order_by_asc(game_collection_link::Column::CollectionId)
+                    .order_by_asc(game_collection_link::Column::SortOrder)
+                    .all(&txn)
+                    .await?
+                    .into_iter()
+                    .map(|link| link.game_id)
+                    .collect(),
+            );
+            let current_links = GameCollectionLink::find()
+                .filter(game_collection_link::Column::CollectionId.eq(target_id))
+                .filter(game_collection_link::Column::GameId.is_in(game_ids.clone()))
+                .all(&txn)
+                .await?;
+            let target_pairs = game_ids
+                .iter()
+                .map(|game_id| GameCollectionPair {
+                    game_id: *game_id,
+                    collection_id: target_id,
+                })
+                .collect::<Vec<_>>();
+            let diff = Self::diff_game_collection_pairs(&current_links, &target_pairs);
+            moved_links = diff.to_insert.len() as u64;
+            skipped_links = (game_ids.len() - diff.to_insert.len()) as u64;
+            let inserts = Self::build_append_inserts(&txn, diff.to_insert).await?;
+            Self::insert_game_collection_links(&txn, inserts).await?;
+        }
+
+        let deleted = Collections::delete_by_id(id)
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        txn.commit().await?;
+        Ok(DeleteCollectionResult {
+            deleted,
+            moved_links,
+            skipped_links,
+        })
     }
 
     // ==================== 游戏-合集关联操作 ====================
@@ -397,6 +794,151 @@ impl CollectionsRepository {
         Ok(())
     }
 
+    /// 在事务中把游戏按给定顺序追加到目标合集末尾，返回 (新加入数, 跳过数)
+    async fn append_games_to_collection(
+        txn: &DatabaseTransaction,
+        game_ids: &[i32],
+        collection_id: i32,
+    ) -> Result<(u64, u64), DbErr> {
+        if Collections::find_by_id(collection_id)
+            .one(txn)
+            .await?
+            .is_none()
+        {
+            return Err(DbErr::RecordNotFound("Collection not found".to_string()));
+        }
+
+        let current_links = GameCollectionLink::find()
+            .filter(game_collection_link::Column::CollectionId.eq(collection_id))
+            .filter(game_collection_link::Column::GameId.is_in(game_ids.to_vec()))
+            .all(txn)
+            .await?;
+        let target_pairs = game_ids
+            .iter()
+            .map(|game_id| GameCollectionPair {
+                game_id: *game_id,
+                collection_id,
+            })
+            .collect::<Vec<_>>();
+        let diff = Self::diff_game_collection_pairs(&current_links, &target_pairs);
+        let added = diff.to_insert.len() as u64;
+        let inserts = Self::build_append_inserts(txn, diff.to_insert).await?;
+        Self::insert_game_collection_links(txn, inserts).await?;
+
+        Ok((added, game_ids.len() as u64 - added))
+    }
+
+    /// 把游戏从一个合集移动到另一个合集（同一事务）
+    ///
+    /// 只移动确实在来源合集中的游戏，按它们在来源合集中的顺序追加到目标合集末尾；
+    /// 已在目标合集中的游戏不重复加入，但同样从来源合集移除。
+    pub async fn move_games_between_collections(
+        db: &DatabaseConnection,
+        game_ids: Vec<i32>,
+        from_id: i32,
+        to_id: i32,
+    ) -> Result<CollectionTransferResult, DbErr> {
+        let game_ids = Self::unique_ids(game_ids);
+        if from_id == to_id {
+            return Err(DbErr::Custom("来源合集与目标合集相同".to_string()));
+        }
+        if game_ids.is_empty() {
+            return Ok(CollectionTransferResult {
+                added: 0,
+                skipped: 0,
+                removed: 0,
+            });
+        }
+
+        let txn = db.begin().await?;
+        let source_links = GameCollectionLink::find()
+            .filter(game_collection_link::Column::CollectionId.eq(from_id))
+            .filter(game_collection_link::Column::GameId.is_in(game_ids))
+            .order_by_asc(game_collection_link::Column::SortOrder)
+            .all(&txn)
+            .await?;
+        let ordered_ids = source_links
+            .iter()
+            .map(|link| link.game_id)
+            .collect::<Vec<_>>();
+
+        let (added, skipped) = Self::append_games_to_collection(&txn, &ordered_ids, to_id).await?;
+        let removed = GameCollectionLink::delete_many()
+            .filter(
+                game_collection_link::Column::Id
+                    .is_in(source_links.iter().map(|link| link.id).collect::<Vec<_>>()),
+            )
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        txn.commit().await?;
+
+        Ok(CollectionTransferResult {
+            added,
+            skipped,
+            removed,
+        })
+    }
+
+    /// 把游戏复制到合集（同一事务），按传入顺序追加到末尾，已在合集中的游戏跳过
+    pub async fn copy_games_to_collection(
+        db: &DatabaseConnection,
+        game_ids: Vec<i32>,
+        to_id: i32,
+    ) -> Result<CollectionTransferResult, DbErr> {
+        let game_ids = Self::unique_ids(game_ids);
+        let txn = db.begin().await?;
+        let (added, skipped) = Self::append_games_to_collection(&txn, &game_ids, to_id).await?;
+        txn.commit().await?;
+
+        Ok(CollectionTransferResult {
+            added,
+            skipped,
+            removed: 0,
+        })
+    }
+
+    /// 把元数据标签匹配的游戏批量加入合集（在同一事务中查询并追加到末尾），已在合集中的游戏跳过
+    pub async fn populate_from_tags(
+        db: &DatabaseConnection,
+        collection_id: i32,
+        tags: Vec<String>,
+        match_mode: TagMatchMode,
+    ) -> Result<PopulateCollectionResult, DbErr> {
+        let txn = db.begin().await?;
+        if Collections::find_by_id(collection_id)
+            .one(&txn)
+            .await?
+            .is_none()
+        {
+            return Err(DbErr::RecordNotFound("Collection not found".to_string()));
+        }
+
+        let game_ids = GamesRepository::find_ids_by_tags(&txn, &tags, match_mode).await?;
+        let current_links = GameCollectionLink::find()
+            .filter(game_collection_link::Column::CollectionId.eq(collection_id))
+            .all(&txn)
+            .await?;
+        let target_pairs = game_ids
+            .iter()
+            .map(|game_id| GameCollectionPair {
+                game_id: *game_id,
+                collection_id,
+            })
+            .collect::<Vec<_>>();
+        let diff = Self::diff_game_collection_pairs(&current_links, &target_pairs);
+        let added = diff.to_insert.len() as u64;
+        let inserts = Self::build_append_inserts(&txn, diff.to_insert).await?;
+        Self::insert_game_collection_links(&txn, inserts).await?;
+        txn.commit().await?;
+
+        Ok(PopulateCollectionResult {
+            matched: game_ids.len() as u64,
+            added,
+            skipped: game_ids.len() as u64 - added,
+        })
+    }
+
     /// 设置单个游戏所在的合集列表
     pub async fn set_game_collections(
         db: &DatabaseConnection,
@@ -451,12 +993,79 @@ impl CollectionsRepository {
 
         Self::delete_game_collection_links(&txn, diff.to_delete_link_ids).await?;
         Self::insert_game_collection_links(&txn, diff.to_insert).await?;
-        Self::update_game_collection_sort_orders(&txn, diff.to_update_sort_orders).await?;
+        Self::update_sort_orders(&txn, "game_collection_link", diff.to_update_sort_orders).await?;
         txn.commit().await?;
 
         Ok(())
     }
 
+    /// 调整合集内游戏的顺序（拖拽排序）
+    ///
+    /// `ordered_game_ids` 为目标顺序的游戏 ID，未列出的游戏保持原相对顺序排在末尾。
+    /// 采用稀疏排序，通常只更新被移动的游戏，空位不足时在同一事务内整体重新编号。
+    ///
+    /// 返回实际更新的记录数。
+    pub async fn reorder_collection(
+        db: &DatabaseConnection,
+        collection_id: i32,
+        ordered_game_ids: Vec<i32>,
+    ) -> Result<usize, DbErr> {
+        use std::collections::HashMap;
+
+        let txn = db.begin().await?;
+        let links = GameCollectionLink::find()
+            .filter(game_collection_link::Column::CollectionId.eq(collection_id))
+            .all(&txn)
+            .await?;
+        let game_of_link: HashMap<i32, i32> =
+            links.iter().map(|link| (link.id, link.game_id)).collect();
+        let current = links
+            .iter()
+            .map(|link| (link.id, link.sort_order))
+            .collect();
+
+        let arranged =
+            Self::arrange_by_ids(current, |link_id| game_of_link[&link_id], &ordered_game_ids)?;
+        let updates = Self::plan_sparse_reorder(&arranged);
+        let count = updates.len();
+        Self::update_sort_orders(&txn, "game_collection_link", updates).await?;
+        txn.commit().await?;
+
+        Ok(count)
+    }
+
+    /// 调整同一父级下合集的顺序（拖拽排序）
+    ///
+    /// `parent_id` 为 None 时调整根分组的顺序；其余规则同 `reorder_collection`。
+    ///
+    /// 返回实际更新的记录数。
+    pub async fn reorder_collections(
+        db: &DatabaseConnection,
+        parent_id: Option<i32>,
+        ordered_ids: Vec<i32>,
+    ) -> Result<usize, DbErr> {
+        let txn = db.begin().await?;
+        let siblings = Collections::find()
+            .filter(match parent_id {
+                Some(parent_id) => collections::Column::ParentId.eq(parent_id),
+                None => collections::Column::ParentId.is_null(),
+            })
+            .all(&txn)
+            .await?;
+        let current = siblings
+            .iter()
+            .map(|collection| (collection.id, collection.sort_order))
+            .collect();
+
+        let arranged = Self::arrange_by_ids(current, |id| id, &ordered_ids)?;
+        let updates = Self::plan_sparse_reorder(&arranged);
+        let count = updates.len();
+        Self::update_sort_orders(&txn, "collections", updates).await?;
+        txn.commit().await?;
+
+        Ok(count)
+    }
+
     // ==================== 前端友好的组合 API ====================
 
     /// 批量获取多个分组的游戏数量
@@ -572,3 +1181,154 @@ impl CollectionsRepository {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection(id: i32, parent_id: Option<i32>, sort_order: i32) -> collections::Model {
+        collections::Model {
+            id,
+            name: format!("collection {}", id),
+            parent_id,
+            sort_order,
+            icon: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn collection_tree_nests_arbitrarily() {
+        let collections = vec![
+            collection(1, None, 0),
+            collection(2, Some(1), 1),
+            collection(3, Some(2), 0),
+            collection(4, Some(1), 0),
+            collection(5, Some(99), 0),
+        ];
+        let counts = [(1, 4), (2, 3), (3, 2)].into_iter().collect();
+
+        let tree = CollectionsRepository::build_collection_tree(collections, &counts);
+        assert_eq!(
+            tree.iter().map(|node| node.id).collect::<Vec<_>>(),
+            vec![1, 5]
+        );
+        let root = &tree[0];
+        assert_eq!(root.game_count, 4);
+        assert_eq!(
+            root.children.iter().map(|node| node.id).collect::<Vec<_>>(),
+            vec![4, 2]
+        );
+        assert_eq!(root.children[0].game_count, 0);
+        let nested = &root.children[1];
+        assert_eq!(nested.game_count, 3);
+        assert_eq!(nested.children[0].id, 3);
+        assert_eq!(nested.children[0].game_count, 2);
+    }
+
+    #[test]
+    fn subtree_counts_follow_nested_collections() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let db = Database::connect("sqlite::memory:").await.unwrap();
+            let backend = db.get_database_backend();
+            let schema = Schema::new(backend);
+            db.execute(backend.build(&schema.create_table_from_entity(Games)))
+                .await
+                .unwrap();
+            db.execute(backend.build(&schema.create_table_from_entity(Collections)))
+                .await
+                .unwrap();
+            db.execute(backend.build(&schema.create_table_from_entity(GameCollectionLink)))
+                .await
+                .unwrap();
+            db.execute_unprepared(
+                "INSERT INTO games (id, id_type) VALUES (1, 'custom'), (2, 'custom'), (3, 'custom');
+                 INSERT INTO collections (id, name, parent_id, sort_order) VALUES
+                     (1, 'root', NULL, 0), (2, 'child', 1, 0), (3, 'grandchild', 2, 0),
+                     (4, 'other', NULL, 1), (5, 'empty', 1, 1);
+                 INSERT INTO game_collection_link (game_id, collection_id, sort_order) VALUES
+                     (1, 1, 0), (1, 3, 0), (2, 2, 0), (3, 1, 1), (3, 4, 0);",
+            )
+            .await
+            .unwrap();
+
+            let counts = CollectionsRepository::count_games_in_subtrees(&db)
+                .await
+                .unwrap();
+            // 游戏 1 同时在 1 与 3 中，只计一次
+            assert_eq!(counts.get(&1), Some(&3));
+            assert_eq!(counts.get(&2), Some(&2));
+            assert_eq!(counts.get(&3), Some(&1));
+            assert_eq!(counts.get(&4), Some(&1));
+            assert_eq!(counts.get(&5), None);
+
+            let tree = CollectionsRepository::get_collection_tree(&db)
+                .await
+                .unwrap();
+            assert_eq!(tree[0].game_count, 3);
+            assert_eq!(tree[0].children[0].children[0].game_count, 1);
+        });
+    }
+
+    /// 按更新应用后得到的排序值
+    fn apply(ordered: &[(i32, i32)], updates: &[(i32, i32)]) -> Vec<i32> {
+        ordered
+            .iter()
+            .map(|(id, order)| {
+                updates
+                    .iter()
+                    .find(|(updated, _)| updated == id)
+                    .map_or(*order, |(_, new)| *new)
+            })
+            .collect()
+    }
+
+    fn is_strictly_increasing(orders: &[i32]) -> bool {
+        orders.windows(2).all(|pair| pair[0] < pair[1])
+    }
+
+    #[test]
+    fn unchanged_order_needs_no_updates() {
+        let ordered = [(1, 0), (2, 1024), (3, 2048)];
+        assert!(CollectionsRepository::plan_sparse_reorder(&ordered).is_empty());
+    }
+
+    #[test]
+    fn moving_one_item_into_a_gap_updates_only_that_item() {
+        // 把 3 拖到 1 和 2 之间
+        let ordered = [(1, 0), (3, 2048), (2, 1024), (4, 3072)];
+        let updates = CollectionsRepository::plan_sparse_reorder(&ordered);
+        assert_eq!(updates.len(), 1);
+        assert!(is_strictly_increasing(&apply(&ordered, &updates)));
+    }
+
+    #[test]
+    fn dense_orders_are_compacted_when_there_is_no_gap() {
+        let ordered = [(1, 0), (3, 2), (2, 1)];
+        let updates = CollectionsRepository::plan_sparse_reorder(&ordered);
+        assert_eq!(apply(&ordered, &updates), vec![0, 1024, 2048]);
+    }
+
+    #[test]
+    fn items_moved_to_the_ends_get_orders_outside_the_kept_range() {
+        let ordered = [(4, 3072), (2, 1024), (3, 2048), (1, 0)];
+        let updates = CollectionsRepository::plan_sparse_reorder(&ordered);
+        assert_eq!(updates.len(), 2);
+        assert!(is_strictly_increasing(&apply(&ordered, &updates)));
+    }
+
+    #[test]
+    fn unlisted_items_keep_their_relative_order_at_the_end() {
+        let current = vec![(10, 0), (11, 1), (12, 2), (13, 3)];
+        let arranged = CollectionsRepository::arrange_by_ids(current, |id| id, &[12, 10]).unwrap();
+        let ids: Vec<i32> = arranged.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![12, 10, 11, 13]);
+    }
+
+    #[test]
+    fn unknown_ids_are_rejected() {
+        let current = vec![(10, 0)];
+        assert!(CollectionsRepository::arrange_by_ids(current, |id| id, &[99]).is_err());
+    }
+}
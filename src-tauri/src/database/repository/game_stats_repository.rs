@@ -1,7 +1,13 @@
+use crate::database::repository::games_repository::PlayStatus;
 use crate::entity::prelude::*;
-use crate::entity::{game_sessions, game_statistics};
+use crate::entity::process_stats::ProcessStats;
+use crate::entity::session_tags::SessionTags;
+use crate::entity::{game_sessions, game_statistics, games, screenshots};
+use chrono::{Local, Months, NaiveDate, TimeZone};
+use sea_orm::sea_query::{Expr, SimpleExpr};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// 每日统计数据结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +16,119 @@ pub struct DailyStats {
     pub playtime: i32,
 }
 
+/// 星期 × 小时的游玩时长汇总
+///
+/// `weekday` 取值 0-6（0 为周日），`hour` 取值 0-23，均按会话开始时的本地时间计算
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayHabitBucket {
+    pub weekday: i32,
+    pub hour: i32,
+    pub total_duration: i64,
+    pub session_count: i64,
+}
+
+/// 游玩习惯统计
+///
+/// `by_weekday` 固定 7 项、`by_hour` 固定 24 项，分别是 `buckets` 按星期和小时的合计
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayHabits {
+    pub by_weekday: Vec<i64>,
+    pub by_hour: Vec<i64>,
+    pub buckets: Vec<PlayHabitBucket>,
+}
+
+/// 单个标签的会话汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionTagSummary {
+    pub tag: String,
+    pub session_count: i32,
+    /// 总时长（分钟）
+    pub total_duration: i32,
+    pub last_played: i32,
+}
+
+/// 单月的游玩时长
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthlyPlaytime {
+    /// `YYYY-MM`
+    pub month: String,
+    /// 游玩时长（分钟）
+    pub playtime: i32,
+}
+
+/// 游玩时长排行中的一项
+#[derive(Debug, Clone, Serialize)]
+pub struct TopPlayedGame {
+    pub game_id: i32,
+    /// 总时长（分钟）
+    pub total_time: i32,
+    pub session_count: i32,
+    pub last_played: Option<i32>,
+}
+
+/// 游戏库总览
+///
+/// `monthly_playtime` 固定 12 项，从 11 个月前到本月按时间顺序排列
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryOverview {
+    pub total_games: u64,
+    /// 状态为「玩过」的游戏数
+    pub cleared_games: u64,
+    /// 通关比例，0 ~ 1，没有游戏时为 0
+    pub completion_ratio: f64,
+    /// 所有游戏的总时长（分钟）
+    pub total_playtime: i64,
+    pub monthly_playtime: Vec<MonthlyPlaytime>,
+    /// 总时长前 10 的游戏
+    pub top_games: Vec<TopPlayedGame>,
+    /// 截至今天连续游玩的天数；今天还没玩时从昨天起算
+    pub current_streak: u32,
+}
+
+/// 年度报告中带游玩时长的名称（开发商 / 标签）
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedPlaytime {
+    pub name: String,
+    /// 当年游玩时长（分钟）
+    pub playtime: i32,
+    pub game_count: i32,
+}
+
+/// 年度报告中单个游戏的汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct YearReportGame {
+    pub game_id: i32,
+    /// 当年游玩时长（分钟）
+    pub playtime: i32,
+    pub session_count: i32,
+}
+
+/// 年度报告
+///
+/// 按会话统计，`monthly_playtime` 固定 12 项（1 ~ 12 月）；跨天会话按午夜前后拆分，
+/// 手动补录的会话计入所在日期。开发商与标签取自元数据 JSON，按当年游玩时长排序。
+#[derive(Debug, Clone, Serialize)]
+pub struct YearReport {
+    pub year: i32,
+    /// 当年总游玩时长（分钟）
+    pub total_playtime: i32,
+    pub monthly_playtime: Vec<i32>,
+    /// 实际游玩的会话数（不含手动调整）
+    pub session_count: i32,
+    pub days_played: i32,
+    pub games_played: i32,
+    /// 当年添加到库中的游戏 ID
+    pub new_game_ids: Vec<i32>,
+    /// 当年最长的一次会话
+    pub longest_session: Option<game_sessions::Model>,
+    /// 游玩时长前 10 的游戏
+    pub top_games: Vec<YearReportGame>,
+    /// 游玩时长前 5 的开发商
+    pub top_developers: Vec<NamedPlaytime>,
+    /// 游玩时长前 10 的标签
+    pub top_tags: Vec<NamedPlaytime>,
+}
+
 #[derive(Debug, Clone, Serialize, FromQueryResult)]
 pub struct GameLastPlayed {
     pub game_id: i32,
@@ -23,13 +142,16 @@ impl GameStatsRepository {
     // ==================== 游戏会话操作 ====================
 
     /// 记录游戏会话
-    pub async fn record_session(
-        db: &DatabaseConnection,
+    ///
+    /// `process_stats` 为游戏监控采样的进程资源占用，补记 / 导入的会话传 None
+    pub async fn record_session<C: ConnectionTrait>(
+        db: &C,
         game_id: i32,
         start_time: i32,
         end_time: i32,
         duration: i32,
         date: String,
+        process_stats: Option<ProcessStats>,
     ) -> Result<i32, DbErr> {
         let session = game_sessions::ActiveModel {
             session_id: NotSet,
@@ -38,21 +160,148 @@ impl GameStatsRepository {
             end_time: Set(end_time),
             duration: Set(duration),
             date: Set(date),
+            tags: NotSet,
+            manual: NotSet,
+            note: NotSet,
+            process_stats: Set(process_stats),
         };
 
         let result = session.insert(db).await?;
         Ok(result.session_id)
     }
 
-    /// 获取游戏会话历史
+    /// 补录一条手动会话并重新计算统计，返回新会话
+    ///
+    /// 时长按起止时间四舍五入到分钟，日期取开始时间的本地日期。
+    pub async fn add_manual_session(
+        db: &DatabaseConnection,
+        game_id: i32,
+        start_time: i32,
+        end_time: i32,
+        note: Option<String>,
+    ) -> Result<game_sessions::Model, DbErr> {
+        if end_time <= start_time {
+            return Err(DbErr::Custom("结束时间必须晚于开始时间".to_string()));
+        }
+        if end_time as i64 > Local::now().timestamp() {
+            return Err(DbErr::Custom("结束时间不能晚于当前时间".to_string()));
+        }
+
+        let seconds = end_time - start_time;
+        let duration = seconds / 60 + i32::from(seconds % 60 >= 30);
+        let date = Local
+            .timestamp_opt(start_time as i64, 0)
+            .single()
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+
+        let txn = db.begin().await?;
+        let session =
+            Self::insert_manual_session(&txn, game_id, start_time, end_time, duration, date, note)
+                .await?;
+        Self::recompute_statistics(&txn, game_id).await?;
+        txn.commit().await?;
+
+        Ok(session)
+    }
+
+    /// 以一条手动会话调整游戏总时长，返回调整后的总时长（分钟）
+    ///
+    /// 调整会话的起止时间均为当前时间，`delta_minutes` 可为负数，
+    /// 不参与每日统计，只影响总时长；调整后总时长不能小于 0。
+    pub async fn adjust_total_time(
+        db: &DatabaseConnection,
+        game_id: i32,
+        delta_minutes: i32,
+        note: Option<String>,
+    ) -> Result<i32, DbErr> {
+        if delta_minutes == 0 {
+            return Err(DbErr::Custom("调整时长不能为 0".to_string()));
+        }
+
+        let txn = db.begin().await?;
+        let current_total: i32 = GameSessions::find()
+            .filter(game_sessions::Column::GameId.eq(game_id))
+            .all(&txn)
+            .await?
+            .iter()
+            .map(|session| session.duration)
+            .sum();
+        if current_total + delta_minutes < 0 {
+            return Err(DbErr::Custom(format!(
+                "调整后总时长不能小于 0（当前 {} 分钟）",
+                current_total
+            )));
+        }
+
+        let now = Local::now();
+        let timestamp = now.timestamp() as i32;
+        Self::insert_manual_session(
+            &txn,
+            game_id,
+            timestamp,
+            timestamp,
+            delta_minutes,
+            now.format("%Y-%m-%d").to_string(),
+            note,
+        )
+        .await?;
+        let total = Self::recompute_statistics(&txn, game_id).await?;
+        txn.commit().await?;
+
+        Ok(total)
+    }
+
+    async fn insert_manual_session<C: ConnectionTrait>(
+        db: &C,
+        game_id: i32,
+        start_time: i32,
+        end_time: i32,
+        duration: i32,
+        date: String,
+        note: Option<String>,
+    ) -> Result<game_sessions::Model, DbErr> {
+        game_sessions::ActiveModel {
+            session_id: NotSet,
+            game_id: Set(game_id),
+            start_time: Set(start_time),
+            end_time: Set(end_time),
+            duration: Set(duration),
+            date: Set(date),
+            tags: NotSet,
+            manual: Set(true),
+            note: Set(Self::normalize_note(note)),
+            process_stats: NotSet,
+        }
+        .insert(db)
+        .await
+    }
+
+    /// 去除备注首尾空白，空备注视为 None
+    fn normalize_note(note: Option<String>) -> Option<String> {
+        note.map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty())
+    }
+
+    /// 会话包含指定标签的过滤条件（tags 为 JSON 字符串数组）
+    fn has_tag(tag: String) -> SimpleExpr {
+        Expr::cust_with_values(
+            r#"EXISTS (SELECT 1 FROM json_each("game_sessions"."tags") WHERE json_each.value = ?)"#,
+            [tag],
+        )
+    }
+
+    /// 获取游戏会话历史，可按标签筛选
     pub async fn get_sessions(
         db: &DatabaseConnection,
         game_id: i32,
         limit: u64,
         offset: u64,
+        tag: Option<String>,
     ) -> Result<Vec<game_sessions::Model>, DbErr> {
         GameSessions::find()
             .filter(game_sessions::Column::GameId.eq(game_id))
+            .apply_if(tag, |query, tag| query.filter(Self::has_tag(tag)))
             .order_by_desc(game_sessions::Column::StartTime)
             .limit(limit)
             .offset(offset)
@@ -60,11 +309,12 @@ impl GameStatsRepository {
             .await
     }
 
-    /// 获取指定游戏范围内的全局最近会话
+    /// 获取指定游戏范围内的全局最近会话，可按标签筛选
     pub async fn get_recent_sessions_for_all(
         db: &DatabaseConnection,
         game_ids: Vec<i32>,
         limit: u64,
+        tag: Option<String>,
     ) -> Result<Vec<game_sessions::Model>, DbErr> {
         if game_ids.is_empty() {
             return Ok(Vec::new());
@@ -72,6 +322,7 @@ impl GameStatsRepository {
 
         let sessions = GameSessions::find()
             .filter(game_sessions::Column::GameId.is_in(game_ids))
+            .apply_if(tag, |query, tag| query.filter(Self::has_tag(tag)))
             .order_by_desc(game_sessions::Column::StartTime)
             .limit(limit)
             .all(db)
@@ -80,19 +331,209 @@ impl GameStatsRepository {
         Ok(sessions)
     }
 
+    /// 获取游戏已有会话的开始时间，用于导入时去重
+    pub async fn get_session_start_times<C: ConnectionTrait>(
+        db: &C,
+        game_id: i32,
+    ) -> Result<Vec<i32>, DbErr> {
+        GameSessions::find()
+            .select_only()
+            .column(game_sessions::Column::StartTime)
+            .filter(game_sessions::Column::GameId.eq(game_id))
+            .into_tuple::<i32>()
+            .all(db)
+            .await
+    }
+
+    /// 设置会话标签（整体替换），传入空列表时清除标签
+    pub async fn tag_session(
+        db: &DatabaseConnection,
+        session_id: i32,
+        tags: Vec<String>,
+    ) -> Result<game_sessions::Model, DbErr> {
+        let mut session: game_sessions::ActiveModel = GameSessions::find_by_id(session_id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound(format!(
+                "Session {} not found",
+                session_id
+            )))?
+            .into();
+
+        session.tags = Set(SessionTags::normalized(tags));
+        session.update(db).await
+    }
+
+    /// 设置会话备注，传入 None 或空字符串时清除备注
+    pub async fn update_session_note(
+        db: &DatabaseConnection,
+        session_id: i32,
+        note: Option<String>,
+    ) -> Result<game_sessions::Model, DbErr> {
+        let mut session: game_sessions::ActiveModel = GameSessions::find_by_id(session_id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound(format!(
+                "Session {} not found",
+                session_id
+            )))?
+            .into();
+
+        session.note = Set(Self::normalize_note(note));
+        session.update(db).await
+    }
+
+    /// 按标签汇总单个游戏的会话，用于游戏报告
+    pub async fn get_session_tag_summary(
+        db: &DatabaseConnection,
+        game_id: i32,
+    ) -> Result<Vec<SessionTagSummary>, DbErr> {
+        let sessions = GameSessions::find()
+            .filter(game_sessions::Column::GameId.eq(game_id))
+            .filter(game_sessions::Column::Tags.is_not_null())
+            .all(db)
+            .await?;
+
+        let mut summaries: Vec<SessionTagSummary> = Vec::new();
+        for session in sessions {
+            let Some(tags) = session.tags else {
+                continue;
+            };
+            for tag in tags.0 {
+                match summaries.iter_mut().find(|summary| summary.tag == tag) {
+                    Some(summary) => {
+                        summary.session_count += 1;
+                        summary.total_duration += session.duration;
+                        summary.last_played = summary.last_played.max(session.end_time);
+                    }
+                    None => summaries.push(SessionTagSummary {
+                        tag,
+                        session_count: 1,
+                        total_duration: session.duration,
+                        last_played: session.end_time,
+                    }),
+                }
+            }
+        }
+
+        summaries.sort_by(|a, b| {
+            b.total_duration
+                .cmp(&a.total_duration)
+                .then_with(|| a.tag.cmp(&b.tag))
+        });
+        Ok(summaries)
+    }
+
+    /// 按星期与小时汇总游玩时长，在数据库内一次分组完成
+    ///
+    /// `start_date` / `end_date` 为 `YYYY-MM-DD`，按会话日期闭区间过滤；
+    /// `game_id` 为空时统计所有游戏。
+    pub async fn get_play_habits(
+        db: &DatabaseConnection,
+        start_date: Option<String>,
+        end_date: Option<String>,
+        game_id: Option<i32>,
+    ) -> Result<PlayHabits, DbErr> {
+        let rows = GameSessions::find()
+            .select_only()
+            .column_as(
+                Expr::cust("CAST(strftime('%w', start_time, 'unixepoch', 'localtime') AS INTEGER)"),
+                "weekday",
+            )
+            .column_as(
+                Expr::cust("CAST(strftime('%H', start_time, 'unixepoch', 'localtime') AS INTEGER)"),
+                "hour",
+            )
+            .column_as(game_sessions::Column::Duration.sum(), "total_duration")
+            .column_as(game_sessions::Column::SessionId.count(), "session_count")
+            // 排除调整总时长的手动会话（起止时间相同，没有对应的游玩时段）
+            .filter(
+                Expr::col(game_sessions::Column::EndTime)
+                    .gt(Expr::col(game_sessions::Column::StartTime)),
+            )
+            .apply_if(start_date, |query, date| {
+                query.filter(game_sessions::Column::Date.gte(date))
+            })
+            .apply_if(end_date, |query, date| {
+                query.filter(game_sessions::Column::Date.lte(date))
+            })
+            .apply_if(game_id, |query, id| {
+                query.filter(game_sessions::Column::GameId.eq(id))
+            })
+            .group_by(Expr::cust("weekday"))
+            .group_by(Expr::cust("hour"))
+            .into_tuple::<(i32, i32, i64, i64)>()
+            .all(db)
+            .await?;
+
+        let mut habits = PlayHabits {
+            by_weekday: vec![0; 7],
+            by_hour: vec![0; 24],
+            buckets: Vec::with_capacity(rows.len()),
+        };
+        for (weekday, hour, total_duration, session_count) in rows {
+            if let Some(total) = habits.by_weekday.get_mut(weekday as usize) {
+                *total += total_duration;
+            }
+            if let Some(total) = habits.by_hour.get_mut(hour as usize) {
+                *total += total_duration;
+            }
+            habits.buckets.push(PlayHabitBucket {
+                weekday,
+                hour,
+                total_duration,
+                session_count,
+            });
+        }
+        Ok(habits)
+    }
+
+    /// 获取游戏实际游玩的会话时段 `(session_id, start_time, end_time)`，用于按时间关联截图
+    pub async fn get_session_ranges(
+        db: &DatabaseConnection,
+        game_id: i32,
+    ) -> Result<Vec<(i32, i32, i32)>, DbErr> {
+        GameSessions::find()
+            .select_only()
+            .column(game_sessions::Column::SessionId)
+            .column(game_sessions::Column::StartTime)
+            .column(game_sessions::Column::EndTime)
+            .filter(game_sessions::Column::GameId.eq(game_id))
+            .filter(
+                Expr::col(game_sessions::Column::EndTime)
+                    .gt(Expr::col(game_sessions::Column::StartTime)),
+            )
+            .order_by_asc(game_sessions::Column::StartTime)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
     /// 删除游戏会话
     pub async fn delete_session(
         db: &DatabaseConnection,
         session_id: i32,
     ) -> Result<DeleteResult, DbErr> {
-        GameSessions::delete_by_id(session_id).exec(db).await
+        let txn = db.begin().await?;
+        // screenshots.session_id 没有外键约束，删除会话时手动解除关联
+        Screenshots::update_many()
+            .col_expr(
+                screenshots::Column::SessionId,
+                Expr::value(Option::<i32>::None),
+            )
+            .filter(screenshots::Column::SessionId.eq(session_id))
+            .exec(&txn)
+            .await?;
+        let result = GameSessions::delete_by_id(session_id).exec(&txn).await?;
+        txn.commit().await?;
+        Ok(result)
     }
 
     // ==================== 游戏统计操作 ====================
 
     /// 更新游戏统计信息
-    pub async fn update_statistics(
-        db: &DatabaseConnection,
+    pub async fn update_statistics<C: ConnectionTrait>(
+        db: &C,
         game_id: i32,
         total_time: i32,
         session_count: i32,
@@ -136,9 +577,113 @@ impl GameStatsRepository {
         Ok(())
     }
 
+    /// 根据会话记录重新计算游戏统计
+    ///
+    /// 计算规则与前端 `gameStats` 保持一致：跨天会话按午夜前后的秒数比例分配分钟数，
+    /// 已有但会话中不存在的日期保留，今天的数据取实时记录与会话计算中的较大值。
+    /// 返回重新计算后的总时长（分钟）。
+    pub async fn recompute_statistics<C: ConnectionTrait>(
+        db: &C,
+        game_id: i32,
+    ) -> Result<i32, DbErr> {
+        let sessions = GameSessions::find()
+            .filter(game_sessions::Column::GameId.eq(game_id))
+            .all(db)
+            .await?;
+
+        let total_time = sessions.iter().map(|s| s.duration).sum();
+        // 调整总时长的手动会话起止时间相同，不计入会话次数与最后游玩时间
+        let played: Vec<&game_sessions::Model> = sessions
+            .iter()
+            .filter(|s| s.end_time > s.start_time)
+            .collect();
+        let session_count = played.len();
+        let last_played = played.iter().map(|s| s.end_time).max();
+
+        let mut daily_stats = match Self::get_statistics(db, game_id)
+            .await?
+            .and_then(|stats| stats.daily_stats)
+        {
+            Some(json) => Self::parse_daily_stats(&json).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let today = Local::now().format("%Y-%m-%d").to_string();
+
+        for (date, playtime) in Self::split_sessions_by_day(&sessions) {
+            match daily_stats.iter_mut().find(|item| item.date == date) {
+                Some(item) if date == today => item.playtime = item.playtime.max(playtime),
+                Some(item) => item.playtime = playtime,
+                None => daily_stats.push(DailyStats { date, playtime }),
+            }
+        }
+        daily_stats.sort_by(|a, b| b.date.cmp(&a.date));
+
+        Self::update_statistics(
+            db,
+            game_id,
+            total_time,
+            session_count as i32,
+            last_played,
+            daily_stats,
+        )
+        .await?;
+
+        Ok(total_time)
+    }
+
+    /// 把会话时长按本地日期汇总，跨天会话在每个本地午夜处拆分，按各天占的时间比例分配时长
+    fn split_sessions_by_day(sessions: &[game_sessions::Model]) -> HashMap<String, i32> {
+        let mut by_day: HashMap<String, i32> = HashMap::new();
+
+        for session in sessions {
+            if session.duration <= 0 || session.end_time <= session.start_time {
+                continue;
+            }
+            let (Some(start), Some(end)) = (
+                Local.timestamp_opt(session.start_time as i64, 0).single(),
+                Local.timestamp_opt(session.end_time as i64, 0).single(),
+            ) else {
+                continue;
+            };
+
+            // 按到某一时刻为止的累计比例取整，各天之和恰好等于会话时长
+            let start_ts = start.timestamp();
+            let end_ts = end.timestamp();
+            let total_seconds = (end_ts - start_ts) as f64;
+            let minutes_until = |ts: i64| {
+                (((ts - start_ts) as f64 / total_seconds) * session.duration as f64).round() as i32
+            };
+
+            let mut date = start.date_naive();
+            let mut segment_start = start_ts;
+            while segment_start < end_ts {
+                let next_midnight = date
+                    .succ_opt()
+                    .and_then(|next| next.and_hms_opt(0, 0, 0))
+                    .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+                    .map(|dt| dt.timestamp());
+                // 无法确定下一个午夜时把剩余时长都计入当天
+                let segment_end = next_midnight.map_or(end_ts, |midnight| midnight.min(end_ts));
+                let minutes = minutes_until(segment_end) - minutes_until(segment_start);
+                if minutes > 0 {
+                    *by_day
+                        .entry(date.format("%Y-%m-%d").to_string())
+                        .or_default() += minutes;
+                }
+                segment_start = segment_end;
+                match date.succ_opt() {
+                    Some(next) => date = next,
+                    None => break,
+                }
+            }
+        }
+
+        by_day
+    }
+
     /// 获取游戏统计信息
-    pub async fn get_statistics(
-        db: &DatabaseConnection,
+    pub async fn get_statistics<C: ConnectionTrait>(
+        db: &C,
         game_id: i32,
     ) -> Result<Option<game_statistics::Model>, DbErr> {
         GameStatistics::find_by_id(game_id).one(db).await
@@ -173,6 +718,391 @@ impl GameStatsRepository {
         Ok(0)
     }
 
+    /// 获取指定日期所有游戏的游戏时间总和（分钟）
+    pub async fn get_total_playtime_on(db: &DatabaseConnection, date: &str) -> Result<i32, DbErr> {
+        let daily_stats: Vec<Option<String>> = GameStatistics::find()
+            .select_only()
+            .column(game_statistics::Column::DailyStats)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let mut total = 0;
+        for daily_stats_json in daily_stats.into_iter().flatten() {
+            let daily_stats = Self::parse_daily_stats(&daily_stats_json).map_err(DbErr::Custom)?;
+            total += daily_stats
+                .iter()
+                .filter(|stat| stat.date == date)
+                .map(|stat| stat.playtime)
+                .sum::<i32>();
+        }
+
+        Ok(total)
+    }
+
+    /// 获取日期区间内（含两端，格式 YYYY-MM-DD）所有游戏的游戏时间总和（分钟）
+    pub async fn get_total_playtime_between(
+        db: &DatabaseConnection,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<i32, DbErr> {
+        let daily_stats: Vec<Option<String>> = GameStatistics::find()
+            .select_only()
+            .column(game_statistics::Column::DailyStats)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let mut total = 0;
+        for daily_stats_json in daily_stats.into_iter().flatten() {
+            let daily_stats = Self::parse_daily_stats(&daily_stats_json).map_err(DbErr::Custom)?;
+            total += daily_stats
+                .iter()
+                .filter(|stat| stat.date.as_str() >= start_date && stat.date.as_str() <= end_date)
+                .map(|stat| stat.playtime)
+                .sum::<i32>();
+        }
+
+        Ok(total)
+    }
+
+    /// 按日汇总所有游戏的会话时长，用于日历热力图
+    ///
+    /// `start_date` / `end_date` 为 `YYYY-MM-DD`（闭区间）；跨天会话按午夜前后拆分，
+    /// 调整总时长的手动会话不计入。只返回有游玩时间的日期。
+    pub async fn get_daily_playtime_range(
+        db: &DatabaseConnection,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<BTreeMap<String, i32>, DbErr> {
+        // 会话日期记在开始日，前一天开始的跨天会话也可能落在区间内
+        let query_start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.pred_opt())
+            .map_or_else(
+                || start_date.to_string(),
+                |date| date.format("%Y-%m-%d").to_string(),
+            );
+        let sessions = GameSessions::find()
+            .filter(game_sessions::Column::Date.gte(query_start))
+            .filter(game_sessions::Column::Date.lte(end_date))
+            .all(db)
+            .await?;
+
+        Ok(Self::split_sessions_by_day(&sessions)
+            .into_iter()
+            .filter(|(date, minutes)| {
+                *minutes > 0 && date.as_str() >= start_date && date.as_str() <= end_date
+            })
+            .collect())
+    }
+
+    /// 获取游戏库总览，一次返回统计面板需要的汇总数据
+    pub async fn get_library_overview(db: &DatabaseConnection) -> Result<LibraryOverview, DbErr> {
+        let total_games = Games::find().count(db).await?;
+        let cleared_games = Games::find()
+            .filter(games::Column::Clear.eq(i32::from(PlayStatus::Played)))
+            .count(db)
+            .await?;
+        let statistics = GameStatistics::find().all(db).await?;
+
+        let today = Local::now().date_naive();
+        let months: Vec<String> = (0..12)
+            .rev()
+            .filter_map(|offset| today.checked_sub_months(Months::new(offset)))
+            .map(|date| date.format("%Y-%m").to_string())
+            .collect();
+        let mut monthly: HashMap<&str, i32> = months.iter().map(|m| (m.as_str(), 0)).collect();
+        let mut played_dates = HashSet::new();
+        let mut total_playtime = 0i64;
+
+        for stats in &statistics {
+            total_playtime += stats.total_time.unwrap_or(0) as i64;
+            let Some(json) = stats.daily_stats.as_deref() else {
+                continue;
+            };
+            for stat in Self::parse_daily_stats(json).map_err(DbErr::Custom)? {
+                if stat.playtime <= 0 {
+                    continue;
+                }
+                if let Some(total) = stat.date.get(..7).and_then(|m| monthly.get_mut(m)) {
+                    *total += stat.playtime;
+                }
+                if let Ok(date) = NaiveDate::parse_from_str(&stat.date, "%Y-%m-%d") {
+                    played_dates.insert(date);
+                }
+            }
+        }
+
+        let mut current_streak = 0;
+        let mut day = if played_dates.contains(&today) {
+            Some(today)
+        } else {
+            today.pred_opt()
+        };
+        while let Some(date) = day.filter(|date| played_dates.contains(date)) {
+            current_streak += 1;
+            day = date.pred_opt();
+        }
+
+        let mut top_games: Vec<TopPlayedGame> = statistics
+            .iter()
+            .filter(|stats| stats.total_time.unwrap_or(0) > 0)
+            .map(|stats| TopPlayedGame {
+                game_id: stats.game_id,
+                total_time: stats.total_time.unwrap_or(0),
+                session_count: stats.session_count.unwrap_or(0),
+                last_played: stats.last_played,
+            })
+            .collect();
+        top_games.sort_by(|a, b| {
+            b.total_time
+                .cmp(&a.total_time)
+                .then_with(|| a.game_id.cmp(&b.game_id))
+        });
+        top_games.truncate(10);
+
+        Ok(LibraryOverview {
+            total_games,
+            cleared_games,
+            completion_ratio: if total_games == 0 {
+                0.0
+            } else {
+                cleared_games as f64 / total_games as f64
+            },
+            total_playtime,
+            monthly_playtime: months
+                .iter()
+                .map(|month| MonthlyPlaytime {
+                    month: month.clone(),
+                    playtime: monthly[month.as_str()],
+                })
+                .collect(),
+            top_games,
+            current_streak,
+        })
+    }
+
+    /// 生成年度报告
+    pub async fn generate_year_report(
+        db: &DatabaseConnection,
+        year: i32,
+    ) -> Result<YearReport, DbErr> {
+        let year_prefix = format!("{:04}-", year);
+        // 跨年会话的日期记在开始日，多取前一年最后一天，拆分后再按年份过滤
+        let sessions = GameSessions::find()
+            .filter(game_sessions::Column::Date.gte(format!("{:04}-12-31", year - 1)))
+            .filter(game_sessions::Column::Date.lte(format!("{:04}-12-31", year)))
+            .all(db)
+            .await?;
+
+        let mut monthly_playtime = vec![0; 12];
+        let mut days_played = HashSet::new();
+        let add_to_month = |monthly: &mut Vec<i32>, date: &str, minutes: i32| {
+            if let Some(month) = date
+                .strip_prefix(year_prefix.as_str())
+                .and_then(|rest| rest.get(..2))
+                .and_then(|month| month.parse::<usize>().ok())
+                .filter(|month| (1..=12).contains(month))
+            {
+                monthly[month - 1] += minutes;
+            }
+        };
+
+        for (date, minutes) in Self::split_sessions_by_day(&sessions) {
+            if minutes > 0 && date.starts_with(year_prefix.as_str()) {
+                add_to_month(&mut monthly_playtime, &date, minutes);
+                days_played.insert(date);
+            }
+        }
+
+        let mut per_game: HashMap<i32, YearReportGame> = HashMap::new();
+        let mut session_count = 0;
+        let mut longest_session: Option<&game_sessions::Model> = None;
+        for session in &sessions {
+            let played = session.end_time > session.start_time;
+            if !played {
+                // split_sessions_by_day 不含手动调整，按记录日期补上
+                add_to_month(&mut monthly_playtime, &session.date, session.duration);
+            }
+            if !session.date.starts_with(year_prefix.as_str()) {
+                continue;
+            }
+            let game = per_game
+                .entry(session.game_id)
+                .or_insert_with(|| YearReportGame {
+                    game_id: session.game_id,
+                    playtime: 0,
+                    session_count: 0,
+                });
+            game.playtime += session.duration;
+            if played {
+                game.session_count += 1;
+                session_count += 1;
+                if longest_session.is_none_or(|longest| session.duration > longest.duration) {
+                    longest_session = Some(session);
+                }
+            }
+        }
+
+        // 开发商与标签按游戏当年的游玩时长加权
+        let played_ids: Vec<i32> = per_game.keys().copied().collect();
+        let games = Games::find()
+            .filter(games::Column::Id.is_in(played_ids))
+            .all(db)
+            .await?;
+        let mut developers: HashMap<String, NamedPlaytime> = HashMap::new();
+        let mut tags: HashMap<String, NamedPlaytime> = HashMap::new();
+        let add_named = |map: &mut HashMap<String, NamedPlaytime>, name: &str, minutes: i32| {
+            let entry = map
+                .entry(name.to_string())
+                .or_insert_with(|| NamedPlaytime {
+                    name: name.to_string(),
+                    playtime: 0,
+                    game_count: 0,
+                });
+            entry.playtime += minutes;
+            entry.game_count += 1;
+        };
+        for game in &games {
+            let minutes = per_game.get(&game.id).map_or(0, |g| g.playtime);
+            if let Some(developer) = Self::metadata_developer(game) {
+                add_named(&mut developers, developer, minutes);
+            }
+            let mut seen = HashSet::new();
+            for tag in Self::metadata_tags(game) {
+                if seen.insert(tag) {
+                    add_named(&mut tags, tag, minutes);
+                }
+            }
+        }
+
+        let start = Local
+            .with_ymd_and_hms(year, 1, 1, 0, 0, 0)
+            .earliest()
+            .map(|dt| dt.timestamp() as i32);
+        let end = Local
+            .with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0)
+            .earliest()
+            .map(|dt| dt.timestamp() as i32);
+        let new_game_ids = match (start, end) {
+            (Some(start), Some(end)) => {
+                Games::find()
+                    .select_only()
+                    .column(games::Column::Id)
+                    .filter(games::Column::CreatedAt.gte(start))
+                    .filter(games::Column::CreatedAt.lt(end))
+                    .order_by_asc(games::Column::CreatedAt)
+                    .into_tuple::<i32>()
+                    .all(db)
+                    .await?
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(YearReport {
+            year,
+            total_playtime: per_game.values().map(|g| g.playtime).sum(),
+            monthly_playtime,
+            session_count,
+            days_played: days_played.len() as i32,
+            games_played: per_game.len() as i32,
+            new_game_ids,
+            longest_session: longest_session.cloned(),
+            top_games: Self::ranked(per_game.into_values(), 10, |g| g.playtime),
+            top_developers: Self::ranked(developers.into_values(), 5, |d| d.playtime),
+            top_tags: Self::ranked(tags.into_values(), 10, |t| t.playtime),
+        })
+    }
+
+    /// 按元数据标签汇总所有游戏的总时长，取前 `limit` 个标签
+    pub async fn get_tag_playtime(
+        db: &DatabaseConnection,
+        limit: usize,
+    ) -> Result<Vec<NamedPlaytime>, DbErr> {
+        let totals: HashMap<i32, i32> = GameStatistics::find()
+            .select_only()
+            .column(game_statistics::Column::GameId)
+            .column(game_statistics::Column::TotalTime)
+            .filter(game_statistics::Column::TotalTime.gt(0))
+            .into_tuple::<(i32, i32)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+        let games = Games::find()
+            .filter(games::Column::Id.is_in(totals.keys().copied()))
+            .all(db)
+            .await?;
+
+        let mut tags: HashMap<String, NamedPlaytime> = HashMap::new();
+        for game in &games {
+            let minutes = totals.get(&game.id).copied().unwrap_or(0);
+            let mut seen = HashSet::new();
+            for tag in Self::metadata_tags(game).filter(|tag| seen.insert(*tag)) {
+                let entry = tags
+                    .entry(tag.to_string())
+                    .or_insert_with(|| NamedPlaytime {
+                        name: tag.to_string(),
+                        playtime: 0,
+                        game_count: 0,
+                    });
+                entry.playtime += minutes;
+                entry.game_count += 1;
+            }
+        }
+        Ok(Self::ranked(tags.into_values(), limit, |t| t.playtime))
+    }
+
+    /// 过滤掉时长为 0 的项，按时长降序取前 `limit` 项
+    fn ranked<T>(
+        items: impl IntoIterator<Item = T>,
+        limit: usize,
+        playtime: impl Fn(&T) -> i32,
+    ) -> Vec<T> {
+        let mut items: Vec<T> = items
+            .into_iter()
+            .filter(|item| playtime(item) > 0)
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(playtime(item)));
+        items.truncate(limit);
+        items
+    }
+
+    /// 元数据中的开发商，优先级：自定义 > BGM > VNDB > 月幕 > Kun
+    fn metadata_developer(game: &games::Model) -> Option<&str> {
+        game.custom_data
+            .as_ref()
+            .and_then(|d| d.developer.as_deref())
+            .or_else(|| game.bgm_data.as_ref().and_then(|d| d.developer.as_deref()))
+            .or_else(|| game.vndb_data.as_ref().and_then(|d| d.developer.as_deref()))
+            .or_else(|| {
+                game.ymgal_data
+                    .as_ref()
+                    .and_then(|d| d.developer.as_deref())
+            })
+            .or_else(|| game.kun_data.as_ref().and_then(|d| d.developer.as_deref()))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// 元数据中的标签，取第一个有标签的数据源（自定义 > BGM > VNDB > Kun）
+    fn metadata_tags(game: &games::Model) -> impl Iterator<Item = &str> {
+        [
+            game.custom_data.as_ref().and_then(|d| d.tags.as_ref()),
+            game.bgm_data.as_ref().and_then(|d| d.tags.as_ref()),
+            game.vndb_data.as_ref().and_then(|d| d.tags.as_ref()),
+            game.kun_data.as_ref().and_then(|d| d.tags.as_ref()),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|tags| !tags.is_empty())
+        .into_iter()
+        .flatten()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+    }
+
     /// 批量获取游戏统计信息
     pub async fn get_statistics_batch(
         db: &DatabaseConnection,
@@ -238,3 +1168,53 @@ impl GameStatsRepository {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn session(
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        duration: i32,
+    ) -> game_sessions::Model {
+        game_sessions::Model {
+            session_id: 1,
+            game_id: 1,
+            start_time: start.timestamp() as i32,
+            end_time: end.timestamp() as i32,
+            duration,
+            date: start.format("%Y-%m-%d").to_string(),
+            tags: None,
+            manual: false,
+            note: None,
+            process_stats: None,
+        }
+    }
+
+    fn local(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn splits_sessions_at_every_midnight() {
+        // 3 月 1 日 22:00 到 3 月 4 日 02:00，共 52 小时
+        let by_day = GameStatsRepository::split_sessions_by_day(&[session(
+            local(1, 22),
+            local(4, 2),
+            52 * 60,
+        )]);
+        assert_eq!(by_day.get("2026-03-01"), Some(&120));
+        assert_eq!(by_day.get("2026-03-02"), Some(&1440));
+        assert_eq!(by_day.get("2026-03-03"), Some(&1440));
+        assert_eq!(by_day.get("2026-03-04"), Some(&120));
+        assert_eq!(by_day.values().sum::<i32>(), 52 * 60);
+
+        // 时长小于经过时间（如暂停过）时按比例分配，总和不变
+        let by_day =
+            GameStatsRepository::split_sessions_by_day(&[session(local(1, 23), local(3, 1), 100)]);
+        assert_eq!(by_day.values().sum::<i32>(), 100);
+        assert_eq!(by_day.len(), 3);
+    }
+}
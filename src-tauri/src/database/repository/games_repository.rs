@@ -4,14 +4,17 @@
 //! 移除了多表事务代码，简化为单表 CRUD 操作。
 
 use crate::database::dto::{
-    BatchOperationError, BatchOperationResult, InsertGameData, UpdateGameData,
+    BatchItemResult, BatchItemStatus, BatchOperationError, BatchOperationResult, InsertGameData,
+    UpdateGameData,
 };
 use crate::entity::prelude::*;
+#[cfg(target_os = "windows")]
+use crate::entity::window_placement::WindowPlacement;
 use crate::entity::{game_statistics, games, savedata};
 use sea_orm::sea_query::Expr;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 /// 游戏数据排序选项
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -44,6 +47,71 @@ pub enum GameType {
     IsCustom,
 }
 
+/// 游戏状态，存储在 games.clear 列中（1-5）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "i32", into = "i32")]
+pub enum PlayStatus {
+    /// 想玩
+    Wish = 1,
+    /// 玩过
+    Played = 2,
+    /// 在玩
+    Playing = 3,
+    /// 搁置
+    OnHold = 4,
+    /// 弃坑
+    Dropped = 5,
+}
+
+impl PlayStatus {
+    pub const ALL: [PlayStatus; 5] = [
+        Self::Wish,
+        Self::Playing,
+        Self::Played,
+        Self::OnHold,
+        Self::Dropped,
+    ];
+}
+
+impl TryFrom<i32> for PlayStatus {
+    type Error = String;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Wish),
+            2 => Ok(Self::Played),
+            3 => Ok(Self::Playing),
+            4 => Ok(Self::OnHold),
+            5 => Ok(Self::Dropped),
+            _ => Err(format!("无效的游戏状态: {}", value)),
+        }
+    }
+}
+
+impl From<PlayStatus> for i32 {
+    fn from(status: PlayStatus) -> Self {
+        status as i32
+    }
+}
+
+/// 按标签筛选时的匹配方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatchMode {
+    /// 包含任一标签
+    #[default]
+    Any,
+    /// 包含全部标签
+    All,
+}
+
+/// 单个游戏状态的游戏数量
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayStatusCount {
+    pub status: PlayStatus,
+    pub count: u64,
+}
+
 /// 游戏数据仓库（单表架构）
 pub struct GamesRepository;
 
@@ -58,6 +126,7 @@ impl GamesRepository {
             total,
             success: 0,
             failed: total,
+            skipped: 0,
             ids: Vec::new(),
             games: Vec::new(),
             errors: (0..total)
@@ -66,6 +135,14 @@ impl GamesRepository {
                     message: message.clone(),
                 })
                 .collect(),
+            items: (0..total)
+                .map(|index| BatchItemResult {
+                    index,
+                    status: BatchItemStatus::Failed,
+                    id: None,
+                    message: Some(message.clone()),
+                })
+                .collect(),
         }
     }
 
@@ -166,6 +243,15 @@ impl GamesRepository {
             clear: Set(Some(game.clear.unwrap_or(Self::DEFAULT_PLAY_STATUS))),
             le_launch: NotSet,
             magpie: NotSet,
+            launch_uri: NotSet,
+            launch_process_name: NotSet,
+            launch_args: NotSet,
+            runner: NotSet,
+            sandbox: NotSet,
+            input_mapping: NotSet,
+            screenshot_dir: NotSet,
+            window_placement: NotSet,
+            backup_schedule: NotSet,
             vndb_data: Set(game.vndb_data),
             bgm_data: Set(game.bgm_data),
             ymgal_data: Set(game.ymgal_data),
@@ -196,6 +282,15 @@ impl GamesRepository {
             clear: updates.clear.map_or(NotSet, Set),
             le_launch: updates.le_launch.map_or(NotSet, Set),
             magpie: updates.magpie.map_or(NotSet, Set),
+            launch_uri: updates.launch_uri.map_or(NotSet, Set),
+            launch_process_name: updates.launch_process_name.map_or(NotSet, Set),
+            launch_args: updates.launch_args.map_or(NotSet, Set),
+            runner: updates.runner.map_or(NotSet, Set),
+            sandbox: updates.sandbox.map_or(NotSet, Set),
+            input_mapping: updates.input_mapping.map_or(NotSet, Set),
+            screenshot_dir: updates.screenshot_dir.map_or(NotSet, Set),
+            window_placement: updates.window_placement.map_or(NotSet, Set),
+            backup_schedule: updates.backup_schedule.map_or(NotSet, Set),
             vndb_data: updates.vndb_data.map_or(NotSet, Set),
             bgm_data: updates.bgm_data.map_or(NotSet, Set),
             ymgal_data: updates.ymgal_data.map_or(NotSet, Set),
@@ -222,6 +317,76 @@ impl GamesRepository {
         game_active.insert(db).await
     }
 
+    /// 批量插入时用于判重的键：本地路径与各数据源 ID
+    fn duplicate_keys(
+        localpath: Option<&str>,
+        [bgm_id, vndb_id, ymgal_id, kun_id]: [Option<&str>; 4],
+    ) -> Vec<(&'static str, String)> {
+        let localpath = localpath.map(|path| {
+            let path = path.trim();
+            // Windows 路径不区分大小写
+            if cfg!(windows) {
+                path.replace('/', "\\").to_lowercase()
+            } else {
+                path.to_string()
+            }
+        });
+        [
+            ("本地路径", localpath),
+            ("BGM ID", bgm_id.map(str::to_string)),
+            ("VNDB ID", vndb_id.map(str::to_string)),
+            ("月幕 ID", ymgal_id.map(str::to_string)),
+            ("Kun ID", kun_id.map(str::to_string)),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| value.filter(|v| !v.is_empty()).map(|v| (label, v)))
+        .collect()
+    }
+
+    /// 已有游戏的判重键 → 游戏 ID
+    async fn load_duplicate_keys<C: ConnectionTrait>(
+        db: &C,
+    ) -> Result<HashMap<(&'static str, String), i32>, DbErr> {
+        let rows = Games::find()
+            .select_only()
+            .columns([
+                games::Column::Id,
+                games::Column::Localpath,
+                games::Column::BgmId,
+                games::Column::VndbId,
+                games::Column::YmgalId,
+                games::Column::KunId,
+            ])
+            .into_tuple::<(
+                i32,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+            )>()
+            .all(db)
+            .await?;
+
+        let mut keys = HashMap::new();
+        for (id, localpath, bgm_id, vndb_id, ymgal_id, kun_id) in rows {
+            let ids = [
+                bgm_id.as_deref(),
+                vndb_id.as_deref(),
+                ymgal_id.as_deref(),
+                kun_id.as_deref(),
+            ];
+            for key in Self::duplicate_keys(localpath.as_deref(), ids) {
+                keys.entry(key).or_insert(id);
+            }
+        }
+        Ok(keys)
+    }
+
+    /// 在同一事务中批量插入游戏
+    ///
+    /// 本地路径或任一数据源 ID 与已有游戏（或同一批中靠前的项）重复的项会被跳过，
+    /// `items` 中按传入顺序记录每一项是插入、跳过还是失败。
     pub async fn insert_batch(
         db: &DatabaseConnection,
         games: Vec<InsertGameData>,
@@ -231,24 +396,69 @@ impl GamesRepository {
         let mut ids = Vec::with_capacity(total);
         let mut inserted_games = Vec::with_capacity(total);
         let mut errors = Vec::new();
+        let mut items = Vec::with_capacity(total);
         let txn = match db.begin().await {
             Ok(txn) => txn,
             Err(error) => return Self::build_batch_failure_result(total, error.to_string()),
         };
+        let mut known_keys = match Self::load_duplicate_keys(&txn).await {
+            Ok(keys) => keys,
+            Err(error) => return Self::build_batch_failure_result(total, error.to_string()),
+        };
 
         for (index, game) in games.into_iter().enumerate() {
-            let game_active =
-                Self::build_insert_active_model(Self::normalize_insert_date(game.cleaned()), now);
+            let game = Self::normalize_insert_date(game.cleaned());
+            let keys = Self::duplicate_keys(
+                game.localpath.as_deref(),
+                [
+                    game.bgm_id.as_deref(),
+                    game.vndb_id.as_deref(),
+                    game.ymgal_id.as_deref(),
+                    game.kun_id.as_deref(),
+                ],
+            );
+            if let Some(((label, _), existing_id)) = keys
+                .iter()
+                .find_map(|key| known_keys.get(key).map(|id| (key, *id)))
+            {
+                items.push(BatchItemResult {
+                    index,
+                    status: BatchItemStatus::Skipped,
+                    id: Some(existing_id),
+                    message: Some(format!("{}与游戏 {} 重复", label, existing_id)),
+                });
+                continue;
+            }
 
-            match game_active.insert(&txn).await {
+            match Self::build_insert_active_model(game, now)
+                .insert(&txn)
+                .await
+            {
                 Ok(result) => {
+                    for key in keys {
+                        known_keys.insert(key, result.id);
+                    }
+                    items.push(BatchItemResult {
+                        index,
+                        status: BatchItemStatus::Inserted,
+                        id: Some(result.id),
+                        message: None,
+                    });
                     ids.push(result.id);
                     inserted_games.push(result);
                 }
-                Err(error) => errors.push(BatchOperationError {
-                    index,
-                    message: error.to_string(),
-                }),
+                Err(error) => {
+                    items.push(BatchItemResult {
+                        index,
+                        status: BatchItemStatus::Failed,
+                        id: None,
+                        message: Some(error.to_string()),
+                    });
+                    errors.push(BatchOperationError {
+                        index,
+                        message: error.to_string(),
+                    });
+                }
             }
         }
 
@@ -260,9 +470,11 @@ impl GamesRepository {
             total,
             success: ids.len(),
             failed: errors.len(),
+            skipped: total - ids.len() - errors.len(),
             ids,
             games: inserted_games,
             errors,
+            items,
         }
     }
 
@@ -320,15 +532,44 @@ impl GamesRepository {
         Games::find_by_id(id).one(db).await
     }
 
+    /// 根据 ID 列表查询游戏，结果按传入顺序排列
+    pub async fn find_by_ids(
+        db: &DatabaseConnection,
+        ids: &[i32],
+    ) -> Result<Vec<games::Model>, DbErr> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut games: HashMap<i32, games::Model> = Games::find()
+            .filter(games::Column::Id.is_in(ids.iter().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|game| (game.id, game))
+            .collect();
+
+        Ok(ids.iter().filter_map(|id| games.remove(id)).collect())
+    }
+
     /// 获取所有游戏，支持按类型筛选和排序
     pub async fn find_all(
         db: &DatabaseConnection,
         game_type: GameType,
+        play_status: Option<PlayStatus>,
         sort_option: SortOption,
         sort_order: SortOrder,
         language: Option<String>,
     ) -> Result<Vec<games::Model>, DbErr> {
-        Self::find_with_sort(db, game_type, sort_option, sort_order, language).await
+        Self::find_with_sort(
+            db,
+            game_type,
+            play_status,
+            sort_option,
+            sort_order,
+            language,
+        )
+        .await
     }
 
     /// 只返回排序后的 ID 列表，不返回完整游戏数据
@@ -340,17 +581,25 @@ impl GamesRepository {
     pub async fn find_ids(
         db: &DatabaseConnection,
         game_type: GameType,
+        play_status: Option<PlayStatus>,
         sort_option: SortOption,
         sort_order: SortOrder,
         language: Option<String>,
     ) -> Result<Vec<i32>, DbErr> {
         match sort_option {
             SortOption::Addtime | SortOption::Datetime | SortOption::LastPlayed => {
-                Self::find_ids_sql(db, game_type, sort_option, sort_order).await
+                Self::find_ids_sql(db, game_type, play_status, sort_option, sort_order).await
             }
             _ => {
-                let games =
-                    Self::find_with_sort(db, game_type, sort_option, sort_order, language).await?;
+                let games = Self::find_with_sort(
+                    db,
+                    game_type,
+                    play_status,
+                    sort_option,
+                    sort_order,
+                    language,
+                )
+                .await?;
                 Ok(games.into_iter().map(|g| g.id).collect())
             }
         }
@@ -377,6 +626,71 @@ impl GamesRepository {
         Games::find().count(db).await
     }
 
+    /// 批量设置游戏状态，返回更新的行数
+    pub async fn set_play_status(
+        db: &DatabaseConnection,
+        game_ids: Vec<i32>,
+        status: PlayStatus,
+    ) -> Result<u64, DbErr> {
+        if game_ids.is_empty() {
+            return Ok(0);
+        }
+        let now = chrono::Utc::now().timestamp() as i32;
+        let result = Games::update_many()
+            .col_expr(games::Column::Clear, Expr::value(i32::from(status)))
+            .col_expr(games::Column::UpdatedAt, Expr::value(now))
+            .filter(games::Column::Id.is_in(game_ids))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// 保存会话中记录的游戏窗口位置，不更新 updated_at（不属于用户编辑）
+    #[cfg(target_os = "windows")]
+    pub async fn set_window_placement(
+        db: &DatabaseConnection,
+        game_id: i32,
+        placement: WindowPlacement,
+    ) -> Result<(), DbErr> {
+        games::ActiveModel {
+            id: Set(game_id),
+            window_placement: Set(Some(placement)),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+        Ok(())
+    }
+
+    /// 按游戏状态统计游戏数量，所有状态都会返回（没有游戏时为 0）
+    pub async fn count_by_status(db: &DatabaseConnection) -> Result<Vec<PlayStatusCount>, DbErr> {
+        let rows: Vec<(Option<i32>, i64)> = Games::find()
+            .select_only()
+            .column(games::Column::Clear)
+            .column_as(games::Column::Id.count(), "count")
+            .group_by(games::Column::Clear)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let mut counts: HashMap<PlayStatus, u64> = HashMap::new();
+        for (clear, count) in rows {
+            // 未设置或无法识别的状态按缺省状态统计，与前端显示一致
+            let status = clear
+                .and_then(|value| PlayStatus::try_from(value).ok())
+                .unwrap_or(PlayStatus::Wish);
+            *counts.entry(status).or_default() += count as u64;
+        }
+
+        Ok(PlayStatus::ALL
+            .into_iter()
+            .map(|status| PlayStatusCount {
+                status,
+                count: counts.get(&status).copied().unwrap_or(0),
+            })
+            .collect())
+    }
+
     /// 获取所有游戏的 BGM ID
     pub async fn get_all_bgm_ids(db: &DatabaseConnection) -> Result<Vec<(i32, String)>, DbErr> {
         Games::find()
@@ -434,10 +748,66 @@ impl GamesRepository {
             .map(|paths| paths.into_iter().collect())
     }
 
+    /// 获取所有设置了本地路径的游戏 (id, localpath)
+    pub async fn get_localpaths_by_id(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<(i32, String)>, DbErr> {
+        Games::find()
+            .select_only()
+            .column(games::Column::Id)
+            .column(games::Column::Localpath)
+            .filter(games::Column::Localpath.is_not_null())
+            .order_by_asc(games::Column::Id)
+            .into_tuple::<(i32, String)>()
+            .all(db)
+            .await
+    }
+
+    /// 在 SQL 中按元数据标签筛选游戏 ID（合并自定义 / BGM / VNDB / Kun 的标签，忽略 ASCII 大小写）
+    pub async fn find_ids_by_tags<C: ConnectionTrait>(
+        db: &C,
+        tags: &[String],
+        match_mode: TagMatchMode,
+    ) -> Result<Vec<i32>, DbErr> {
+        let tags: Vec<String> = tags
+            .iter()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        if tags.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut condition = match match_mode {
+            TagMatchMode::Any => Condition::any(),
+            TagMatchMode::All => Condition::all(),
+        };
+        for tag in tags {
+            condition = condition.add(Expr::cust_with_values(
+                r#"EXISTS (SELECT 1 FROM (
+                    SELECT value FROM json_each("games"."custom_data", '$.tags')
+                    UNION ALL SELECT value FROM json_each("games"."bgm_data", '$.tags')
+                    UNION ALL SELECT value FROM json_each("games"."vndb_data", '$.tags')
+                    UNION ALL SELECT value FROM json_each("games"."kun_data", '$.tags')
+                ) WHERE lower(trim(value)) = ?)"#,
+                [tag],
+            ));
+        }
+
+        Games::find()
+            .select_only()
+            .column(games::Column::Id)
+            .filter(condition)
+            .order_by_asc(games::Column::Id)
+            .into_tuple::<i32>()
+            .all(db)
+            .await
+    }
+
     // ==================== 私有方法 ====================
 
-    /// 通用的查询构建器：应用类型筛选
-    fn build_base_query(game_type: GameType) -> Select<Games> {
+    /// 通用的查询构建器：应用类型与游戏状态筛选
+    fn build_base_query(game_type: GameType, play_status: Option<PlayStatus>) -> Select<Games> {
         let mut query = Games::find();
 
         query = match game_type {
@@ -450,7 +820,19 @@ impl GamesRepository {
                     .add(games::Column::IdType.eq("Whitecloud")),
             ),
         };
-        query
+        query.apply_if(play_status, |query, status| {
+            query.filter(Self::play_status_condition(status))
+        })
+    }
+
+    /// 游戏状态筛选条件，未设置状态（NULL）的旧数据视为想玩
+    fn play_status_condition(status: PlayStatus) -> Condition {
+        let condition = Condition::any().add(games::Column::Clear.eq(i32::from(status)));
+        if i32::from(status) == Self::DEFAULT_PLAY_STATUS {
+            condition.add(games::Column::Clear.is_null())
+        } else {
+            condition
+        }
     }
 
     /// 发行日期排序：无日期的游戏始终置末尾，升序/降序只影响非空日期。
@@ -541,11 +923,16 @@ impl GamesRepository {
 
     /// 从游戏记录中提取用于排序的显示名称
     ///
-    /// 优先级与前端 `getGameDisplayName` 保持一致：
-    /// `custom_data.name` > `name_cn`（仅 zh-CN）> 按 `id_type` 取 `name`
-    ///
     /// 返回值为排序键字符串：zh-CN 时汉字转拼音，其他情况转小写
     fn get_sort_name(game: &games::Model, use_cn: bool) -> Option<String> {
+        Self::get_display_name(game, use_cn).map(|n| Self::to_sort_key(n, use_cn))
+    }
+
+    /// 从游戏记录中提取显示名称
+    ///
+    /// 优先级与前端 `getGameDisplayName` 保持一致：
+    /// `custom_data.name` > `name_cn`（仅 zh-CN）> 按 `id_type` 取 `name`
+    pub fn get_display_name(game: &games::Model, use_cn: bool) -> Option<&str> {
         // 1. 自定义名称最高优先 (使用 as_deref 转为 &str)
         if let Some(name) = game
             .custom_data
@@ -553,7 +940,7 @@ impl GamesRepository {
             .and_then(|d| d.name.as_deref())
             .filter(|n| !n.is_empty())
         {
-            return Some(Self::to_sort_key(name, use_cn));
+            return Some(name);
         }
 
         // 定义局部宏：处理不同数据源的提取与 fallback 逻辑。
@@ -581,7 +968,7 @@ impl GamesRepository {
         }
 
         // 2. 根据 id_type 获取最终名称的引用 (&str)
-        let name_ref = match game.id_type.as_str() {
+        match game.id_type.as_str() {
             "bgm" => extract_name!(game.bgm_data),
             "vndb" => extract_name!(game.vndb_data),
             "ymgal" => extract_name!(game.ymgal_data),
@@ -591,10 +978,7 @@ impl GamesRepository {
                 .or_else(|| extract_name!(game.vndb_data))
                 .or_else(|| extract_name!(game.ymgal_data))
                 .or_else(|| kun_extract_name!(game.kun_data)),
-        };
-
-        // 3. 统一在这里进行内存分配，根据语言转换为合适的排序键
-        name_ref.map(|n| Self::to_sort_key(n, use_cn))
+        }
     }
 
     /// 将名称转换为排序键
@@ -628,12 +1012,13 @@ impl GamesRepository {
     async fn find_ids_sql(
         db: &DatabaseConnection,
         game_type: GameType,
+        play_status: Option<PlayStatus>,
         sort_option: SortOption,
         sort_order: SortOrder,
     ) -> Result<Vec<i32>, DbErr> {
         match sort_option {
             SortOption::Addtime => {
-                let mut query = Self::build_base_query(game_type)
+                let mut query = Self::build_base_query(game_type, play_status)
                     .select_only()
                     .column(games::Column::Id);
                 query = match sort_order {
@@ -643,7 +1028,7 @@ impl GamesRepository {
                 query.into_tuple::<i32>().all(db).await
             }
             SortOption::Datetime => {
-                let query = Self::build_base_query(game_type)
+                let query = Self::build_base_query(game_type, play_status)
                     .select_only()
                     .column(games::Column::Id);
                 Self::apply_date_order(query, sort_order)
@@ -652,7 +1037,7 @@ impl GamesRepository {
                     .await
             }
             SortOption::LastPlayed => {
-                let query = Self::build_base_query(game_type)
+                let query = Self::build_base_query(game_type, play_status)
                     .select_only()
                     .column(games::Column::Id);
                 Self::apply_last_played_order(query, sort_order)
@@ -668,13 +1053,14 @@ impl GamesRepository {
     async fn find_with_sort(
         db: &DatabaseConnection,
         game_type: GameType,
+        play_status: Option<PlayStatus>,
         sort_option: SortOption,
         sort_order: SortOrder,
         language: Option<String>,
     ) -> Result<Vec<games::Model>, DbErr> {
         match sort_option {
             SortOption::Addtime => {
-                let mut query = Self::build_base_query(game_type);
+                let mut query = Self::build_base_query(game_type, play_status);
                 query = match sort_order {
                     SortOrder::Asc => query.order_by_asc(games::Column::Id),
                     SortOrder::Desc => query.order_by_desc(games::Column::Id),
@@ -682,24 +1068,31 @@ impl GamesRepository {
                 query.all(db).await
             }
             SortOption::Datetime => {
-                Self::apply_date_order(Self::build_base_query(game_type), sort_order)
+                Self::apply_date_order(Self::build_base_query(game_type, play_status), sort_order)
                     .all(db)
                     .await
             }
             SortOption::LastPlayed => {
-                Self::apply_last_played_order(Self::build_base_query(game_type), sort_order)
-                    .all(db)
-                    .await
+                Self::apply_last_played_order(
+                    Self::build_base_query(game_type, play_status),
+                    sort_order,
+                )
+                .all(db)
+                .await
             }
             SortOption::BGMRank => {
                 // bgm_data.score：按“排名”语义排序，升序时高分靠前，rank 作为同分补充依据
-                let mut games = Self::build_base_query(game_type).all(db).await?;
+                let mut games = Self::build_base_query(game_type, play_status)
+                    .all(db)
+                    .await?;
                 Self::sort_by_bgm_rank(&mut games, sort_order);
                 Ok(games)
             }
             SortOption::VNDBRank => {
                 // vndb_data.score：数值越大越靠前，无 score 或 score=0 置末尾
-                let mut games = Self::build_base_query(game_type).all(db).await?;
+                let mut games = Self::build_base_query(game_type, play_status)
+                    .all(db)
+                    .await?;
                 let desc = matches!(sort_order, SortOrder::Asc);
                 Self::sort_by_optional_key(&mut games, desc, |g| {
                     g.vndb_data
@@ -711,7 +1104,9 @@ impl GamesRepository {
             }
             SortOption::UserRatingRank => {
                 // custom_data.user_rating：按“排名”语义排序，升序时高分靠前，无评分或 0 置末尾
-                let mut games = Self::build_base_query(game_type).all(db).await?;
+                let mut games = Self::build_base_query(game_type, play_status)
+                    .all(db)
+                    .await?;
                 let desc = matches!(sort_order, SortOrder::Asc);
                 Self::sort_by_optional_key(&mut games, desc, |g| {
                     g.custom_data
@@ -724,7 +1119,9 @@ impl GamesRepository {
             SortOption::Namesort => {
                 // 名称排序：应用层排序，名称来自 JSON 列
                 // 名称选择优先级与前端 getGameDisplayName 一致
-                let mut games = Self::build_base_query(game_type).all(db).await?;
+                let mut games = Self::build_base_query(game_type, play_status)
+                    .all(db)
+                    .await?;
                 let desc = matches!(sort_order, SortOrder::Desc);
                 let use_cn = language.as_deref().map(|l| l == "zh-CN").unwrap_or(false);
                 Self::sort_by_optional_key(&mut games, desc, |g| Self::get_sort_name(g, use_cn));
@@ -742,6 +1139,8 @@ impl GamesRepository {
         file_name: &str,
         backup_time: i32,
         file_size: i32,
+        auto: bool,
+        sha256: Option<String>,
     ) -> Result<i32, DbErr> {
         let savedata_record = savedata::ActiveModel {
             id: NotSet,
@@ -749,6 +1148,8 @@ impl GamesRepository {
             file: Set(file_name.to_string()),
             backup_time: Set(backup_time),
             file_size: Set(file_size),
+            auto: Set(auto),
+            sha256: Set(sha256),
         };
         let result = savedata_record.insert(db).await?;
         Ok(result.id)
@@ -774,6 +1175,17 @@ impl GamesRepository {
             .await
     }
 
+    /// 获取所有游戏的自动检查点记录（按时间正序，最旧的在前）
+    pub async fn get_auto_savedata_records(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<savedata::Model>, DbErr> {
+        Savedata::find()
+            .filter(savedata::Column::Auto.eq(true))
+            .order_by_asc(savedata::Column::BackupTime)
+            .all(db)
+            .await
+    }
+
     /// 根据 ID 获取备份记录
     pub async fn get_savedata_record_by_id(
         db: &DatabaseConnection,
@@ -782,6 +1194,44 @@ impl GamesRepository {
         Savedata::find_by_id(backup_id).one(db).await
     }
 
+    /// 根据备份文件名获取备份记录
+    pub async fn find_savedata_record_by_file(
+        db: &DatabaseConnection,
+        file_name: &str,
+    ) -> Result<Option<savedata::Model>, DbErr> {
+        Savedata::find()
+            .filter(savedata::Column::File.eq(file_name))
+            .one(db)
+            .await
+    }
+
+    /// 获取尚未记录 SHA-256 的备份记录
+    pub async fn get_unhashed_savedata_records(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<savedata::Model>, DbErr> {
+        Savedata::find()
+            .filter(savedata::Column::Sha256.is_null())
+            .order_by_asc(savedata::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// 写入备份记录的 SHA-256
+    pub async fn set_savedata_sha256(
+        db: &DatabaseConnection,
+        backup_id: i32,
+        sha256: String,
+    ) -> Result<(), DbErr> {
+        savedata::ActiveModel {
+            id: Set(backup_id),
+            sha256: Set(Some(sha256)),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+        Ok(())
+    }
+
     /// 删除备份记录
     pub async fn delete_savedata_record(
         db: &DatabaseConnection,
@@ -0,0 +1,103 @@
+use crate::entity::launch_attempts;
+use crate::entity::prelude::*;
+use sea_orm::*;
+use std::path::Path;
+
+/// 启动记录仓库
+pub struct LaunchAttemptsRepository;
+
+impl LaunchAttemptsRepository {
+    /// 写入一条启动记录，返回记录 ID
+    pub async fn record_attempt(
+        db: &DatabaseConnection,
+        game_id: i32,
+        success: bool,
+        process_id: Option<u32>,
+        message: Option<String>,
+        log_path: Option<String>,
+    ) -> Result<i32, DbErr> {
+        let attempt = launch_attempts::ActiveModel {
+            id: NotSet,
+            game_id: Set(game_id),
+            launched_at: Set(chrono::Utc::now().timestamp() as i32),
+            success: Set(success),
+            process_id: Set(process_id.map(|pid| pid as i32)),
+            message: Set(message),
+            exit_code: Set(None),
+            exited_at: Set(None),
+            log_path: Set(log_path),
+            output_tail: Set(None),
+        };
+
+        let result = attempt.insert(db).await?;
+        Ok(result.id)
+    }
+
+    /// 进程退出后回写退出码与输出末尾内容（仅 Windows 直接启动的进程会捕获输出）
+    #[cfg(target_os = "windows")]
+    pub async fn complete_attempt(
+        db: &DatabaseConnection,
+        attempt_id: i32,
+        exit_code: Option<i32>,
+        output_tail: Option<String>,
+    ) -> Result<(), DbErr> {
+        let attempt = launch_attempts::ActiveModel {
+            id: Set(attempt_id),
+            exit_code: Set(exit_code),
+            exited_at: Set(Some(chrono::Utc::now().timestamp() as i32)),
+            output_tail: Set(output_tail),
+            ..Default::default()
+        };
+
+        attempt.update(db).await?;
+        Ok(())
+    }
+
+    /// 获取指定游戏最近的启动记录（按时间倒序）
+    pub async fn get_recent_attempts(
+        db: &DatabaseConnection,
+        game_id: i32,
+        limit: u64,
+    ) -> Result<Vec<launch_attempts::Model>, DbErr> {
+        LaunchAttempts::find()
+            .filter(launch_attempts::Column::GameId.eq(game_id))
+            .order_by_desc(launch_attempts::Column::LaunchedAt)
+            .order_by_desc(launch_attempts::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+    /// 把位于 `from` 目录下的启动日志路径改写到 `to` 目录下，返回改写的记录数
+    pub async fn rebase_log_paths(
+        db: &DatabaseConnection,
+        from: &Path,
+        to: &Path,
+    ) -> Result<u64, DbErr> {
+        let rows: Vec<(i32, String)> = LaunchAttempts::find()
+            .select_only()
+            .column(launch_attempts::Column::Id)
+            .column(launch_attempts::Column::LogPath)
+            .filter(launch_attempts::Column::LogPath.is_not_null())
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let txn = db.begin().await?;
+        let mut count = 0;
+        for (id, path) in rows {
+            let Ok(rest) = Path::new(&path).strip_prefix(from) else {
+                continue;
+            };
+            launch_attempts::ActiveModel {
+                id: Set(id),
+                log_path: Set(Some(to.join(rest).to_string_lossy().to_string())),
+                ..Default::default()
+            }
+            .update(&txn)
+            .await?;
+            count += 1;
+        }
+        txn.commit().await?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,141 @@
+use crate::entity::prelude::*;
+use crate::entity::screenshots;
+use sea_orm::*;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// 游戏截图仓库
+pub struct ScreenshotsRepository;
+
+impl ScreenshotsRepository {
+    /// 写入一条截图记录
+    pub async fn insert_screenshot(
+        db: &DatabaseConnection,
+        game_id: i32,
+        path: String,
+        captured_at: i32,
+        width: Option<i32>,
+        height: Option<i32>,
+    ) -> Result<screenshots::Model, DbErr> {
+        screenshots::ActiveModel {
+            id: NotSet,
+            game_id: Set(game_id),
+            path: Set(path),
+            captured_at: Set(captured_at),
+            width: Set(width),
+            height: Set(height),
+            source_path: Set(None),
+            session_id: Set(None),
+        }
+        .insert(db)
+        .await
+    }
+
+    /// 批量写入截图记录，返回写入数量
+    pub async fn insert_screenshots(
+        db: &DatabaseConnection,
+        records: Vec<screenshots::ActiveModel>,
+    ) -> Result<usize, DbErr> {
+        let count = records.len();
+        if count == 0 {
+            return Ok(0);
+        }
+        Screenshots::insert_many(records).exec(db).await?;
+        Ok(count)
+    }
+
+    /// 分页获取指定游戏的截图（按截取时间倒序）
+    pub async fn get_screenshots(
+        db: &DatabaseConnection,
+        game_id: i32,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<screenshots::Model>, DbErr> {
+        Screenshots::find()
+            .filter(screenshots::Column::GameId.eq(game_id))
+            .order_by_desc(screenshots::Column::CapturedAt)
+            .order_by_desc(screenshots::Column::Id)
+            .limit(limit)
+            .offset(offset)
+            .all(db)
+            .await
+    }
+
+    /// 获取指定游戏已记录的截图路径，用于导入时去重
+    pub async fn get_paths_by_game(
+        db: &DatabaseConnection,
+        game_id: i32,
+    ) -> Result<HashSet<String>, DbErr> {
+        let paths: Vec<String> = Screenshots::find()
+            .select_only()
+            .column(screenshots::Column::Path)
+            .filter(screenshots::Column::GameId.eq(game_id))
+            .into_tuple()
+            .all(db)
+            .await?;
+        Ok(paths.into_iter().collect())
+    }
+
+    /// 获取指定游戏从自带截图目录复制过的原文件路径，用于重复导入时去重
+    pub async fn get_source_paths_by_game(
+        db: &DatabaseConnection,
+        game_id: i32,
+    ) -> Result<HashSet<String>, DbErr> {
+        let paths: Vec<String> = Screenshots::find()
+            .select_only()
+            .column(screenshots::Column::SourcePath)
+            .filter(screenshots::Column::GameId.eq(game_id))
+            .filter(screenshots::Column::SourcePath.is_not_null())
+            .into_tuple()
+            .all(db)
+            .await?;
+        Ok(paths.into_iter().collect())
+    }
+
+    /// 根据 ID 获取截图记录
+    pub async fn find_by_id(
+        db: &DatabaseConnection,
+        id: i32,
+    ) -> Result<Option<screenshots::Model>, DbErr> {
+        Screenshots::find_by_id(id).one(db).await
+    }
+
+    /// 删除截图记录
+    pub async fn delete(db: &DatabaseConnection, id: i32) -> Result<DeleteResult, DbErr> {
+        Screenshots::delete_by_id(id).exec(db).await
+    }
+    /// 把位于 `from` 目录下的截图路径改写到 `to` 目录下，返回改写的记录数
+    ///
+    /// 迁移数据目录后截图文件换了位置，记录中的绝对路径随之更新。
+    pub async fn rebase_paths(
+        db: &DatabaseConnection,
+        from: &Path,
+        to: &Path,
+    ) -> Result<u64, DbErr> {
+        let rows: Vec<(i32, String)> = Screenshots::find()
+            .select_only()
+            .column(screenshots::Column::Id)
+            .column(screenshots::Column::Path)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let txn = db.begin().await?;
+        let mut count = 0;
+        for (id, path) in rows {
+            let Ok(rest) = Path::new(&path).strip_prefix(from) else {
+                continue;
+            };
+            screenshots::ActiveModel {
+                id: Set(id),
+                path: Set(to.join(rest).to_string_lossy().to_string()),
+                ..Default::default()
+            }
+            .update(&txn)
+            .await?;
+            count += 1;
+        }
+        txn.commit().await?;
+        Ok(count)
+    }
+}
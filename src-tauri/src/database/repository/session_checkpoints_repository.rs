@@ -0,0 +1,105 @@
+use crate::database::repository::game_stats_repository::GameStatsRepository;
+use crate::entity::prelude::*;
+use crate::entity::session_checkpoints;
+use chrono::{Local, TimeZone};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use std::collections::HashSet;
+
+/// 补记会话的最短时长（秒），与前端记录会话的阈值一致
+const MIN_RECOVERED_SECONDS: i32 = 60;
+
+/// 进行中会话检查点仓库
+pub struct SessionCheckpointsRepository;
+
+impl SessionCheckpointsRepository {
+    /// 写入或覆盖指定游戏的检查点
+    pub async fn save_checkpoint(
+        db: &DatabaseConnection,
+        game_id: i32,
+        start_time: i32,
+        total_seconds: i32,
+    ) -> Result<(), DbErr> {
+        let checkpoint = session_checkpoints::ActiveModel {
+            game_id: Set(game_id),
+            start_time: Set(start_time),
+            last_heartbeat: Set(chrono::Utc::now().timestamp() as i32),
+            total_seconds: Set(total_seconds),
+        };
+
+        SessionCheckpoints::insert(checkpoint)
+            .on_conflict(
+                OnConflict::column(session_checkpoints::Column::GameId)
+                    .update_columns([
+                        session_checkpoints::Column::StartTime,
+                        session_checkpoints::Column::LastHeartbeat,
+                        session_checkpoints::Column::TotalSeconds,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// 删除指定游戏的检查点（会话正常结束时调用）
+    pub async fn delete_checkpoint(db: &DatabaseConnection, game_id: i32) -> Result<(), DbErr> {
+        SessionCheckpoints::delete_by_id(game_id).exec(db).await?;
+        Ok(())
+    }
+
+    /// 将上次运行残留的检查点补记为游戏会话，并清空检查点表
+    ///
+    /// 会话结束时间取最后一次心跳，时长按前台累计秒数四舍五入到分钟；
+    /// 不足一分钟的检查点直接丢弃。
+    ///
+    /// # Returns
+    /// 补记的会话数量
+    pub async fn reconcile_orphans(db: &DatabaseConnection) -> Result<usize, DbErr> {
+        let checkpoints = SessionCheckpoints::find().all(db).await?;
+        if checkpoints.is_empty() {
+            return Ok(0);
+        }
+
+        let txn = db.begin().await?;
+        let mut affected_games = HashSet::new();
+
+        for checkpoint in checkpoints
+            .iter()
+            .filter(|checkpoint| checkpoint.total_seconds >= MIN_RECOVERED_SECONDS)
+        {
+            let minutes = checkpoint.total_seconds / 60;
+            let duration = if checkpoint.total_seconds % 60 >= 30 {
+                minutes + 1
+            } else {
+                minutes
+            };
+            let date = Local
+                .timestamp_opt(checkpoint.start_time as i64, 0)
+                .single()
+                .map(|dt| dt.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+
+            GameStatsRepository::record_session(
+                &txn,
+                checkpoint.game_id,
+                checkpoint.start_time,
+                checkpoint.last_heartbeat,
+                duration,
+                date,
+                None,
+            )
+            .await?;
+            affected_games.insert(checkpoint.game_id);
+        }
+
+        for game_id in &affected_games {
+            GameStatsRepository::recompute_statistics(&txn, *game_id).await?;
+        }
+
+        SessionCheckpoints::delete_many().exec(&txn).await?;
+        txn.commit().await?;
+
+        Ok(affected_games.len())
+    }
+}
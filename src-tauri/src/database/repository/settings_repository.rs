@@ -2,6 +2,7 @@ use crate::database::dto::UpdateSettingsData;
 use crate::entity::prelude::*;
 use crate::entity::user;
 use crate::entity::user::Model;
+use crate::utils::secrets::{SecretKey, get_secret, set_secret};
 use sea_orm::*;
 
 /// 用户设置仓库
@@ -34,6 +35,24 @@ impl SettingsRepository {
                 db_backup_path: Set(None),
                 le_path: Set(None),
                 magpie_path: Set(None),
+                auto_clear_rules: Set(None),
+                savedata_quota: Set(None),
+                monitor_settings: Set(None),
+                notification_settings: Set(None),
+                presence_settings: Set(None),
+                backup_schedule: Set(None),
+                scan_config: Set(None),
+                http_api: Set(None),
+                tray_settings: Set(None),
+                log_settings: Set(None),
+                usage_stats_enabled: Set(None),
+                setup_progress: Set(None),
+                backup_compression: Set(None),
+                use_trash: Set(None),
+                max_db_backups: Set(None),
+                max_backup_age_days: Set(None),
+                file_lock_retries: Set(None),
+                file_lock_retry_delay_ms: Set(None),
             };
 
             user.insert(db).await?;
@@ -42,8 +61,8 @@ impl SettingsRepository {
         Ok(())
     }
 
-    /// 获取所有设置
-    pub async fn get_all_settings(db: &DatabaseConnection) -> Result<user::Model, DbErr> {
+    /// 获取数据库中保存的设置，不读取系统凭据管理器中的令牌
+    pub async fn get_stored_settings(db: &DatabaseConnection) -> Result<user::Model, DbErr> {
         Self::ensure_user_exists(db).await?;
 
         User::find_by_id(1)
@@ -52,6 +71,62 @@ impl SettingsRepository {
             .ok_or(DbErr::RecordNotFound("User record not found".to_string()))
     }
 
+    /// 获取所有设置
+    ///
+    /// 数据库中没有的令牌从系统凭据管理器读取。
+    pub async fn get_all_settings(db: &DatabaseConnection) -> Result<user::Model, DbErr> {
+        let mut settings = Self::get_stored_settings(db).await?;
+
+        if settings.bgm_auth.is_none() {
+            settings.bgm_auth = get_secret(SecretKey::BgmAuth)
+                .await
+                .and_then(|json| serde_json::from_str(&json).ok());
+        }
+        if settings.vndb_token.is_none() {
+            settings.vndb_token = get_secret(SecretKey::VndbToken).await;
+        }
+        Ok(settings)
+    }
+
+    /// 把令牌写入系统凭据管理器，成功时返回 true；失败时记录日志，由调用方改存数据库
+    async fn store_secret(key: SecretKey, value: Option<&str>) -> bool {
+        match set_secret(key, value).await {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("系统凭据管理器不可用，令牌保存到数据库: {}", e);
+                false
+            }
+        }
+    }
+
+    /// 把数据库中明文保存的令牌迁移到系统凭据管理器，返回是否迁移了令牌
+    ///
+    /// 连接数据库时执行；凭据管理器不可用时令牌保留在数据库中，下次启动再尝试。
+    pub async fn migrate_secrets(db: &DatabaseConnection) -> Result<bool, DbErr> {
+        let Some(user) = User::find_by_id(1).one(db).await? else {
+            return Ok(false);
+        };
+        if user.bgm_auth.is_none() && user.vndb_token.is_none() {
+            return Ok(false);
+        }
+
+        Self::update_settings(
+            db,
+            UpdateSettingsData {
+                bgm_auth: user.bgm_auth.map(Some),
+                vndb_token: user.vndb_token.map(Some),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let migrated = User::find_by_id(1)
+            .one(db)
+            .await?
+            .is_some_and(|user| user.bgm_auth.is_none() && user.vndb_token.is_none());
+        Ok(migrated)
+    }
+
     /// 批量更新设置
     pub async fn update_settings(
         db: &DatabaseConnection,
@@ -68,12 +143,20 @@ impl SettingsRepository {
 
         let mut active: user::ActiveModel = user.into();
 
+        // 令牌优先保存到系统凭据管理器，数据库中对应的列置空
         if let Some(auth) = data.bgm_auth {
-            active.bgm_auth = Set(auth);
+            let json = auth
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .map_err(|e| DbErr::Custom(format!("序列化 BGM 授权信息失败: {}", e)))?;
+            let stored = Self::store_secret(SecretKey::BgmAuth, json.as_deref()).await;
+            active.bgm_auth = Set(if stored { None } else { auth });
         }
 
         if let Some(token) = data.vndb_token {
-            active.vndb_token = Set(token);
+            let stored = Self::store_secret(SecretKey::VndbToken, token.as_deref()).await;
+            active.vndb_token = Set(if stored { None } else { token });
         }
 
         if let Some(path) = data.save_root_path {
@@ -92,6 +175,78 @@ impl SettingsRepository {
             active.magpie_path = Set(path);
         }
 
+        if let Some(rules) = data.auto_clear_rules {
+            active.auto_clear_rules = Set(rules);
+        }
+
+        if let Some(quota) = data.savedata_quota {
+            active.savedata_quota = Set(quota);
+        }
+
+        if let Some(settings) = data.monitor_settings {
+            active.monitor_settings = Set(settings);
+        }
+
+        if let Some(settings) = data.notification_settings {
+            active.notification_settings = Set(settings);
+        }
+
+        if let Some(settings) = data.presence_settings {
+            active.presence_settings = Set(settings);
+        }
+
+        if let Some(schedule) = data.backup_schedule {
+            active.backup_schedule = Set(schedule);
+        }
+
+        if let Some(config) = data.scan_config {
+            active.scan_config = Set(config);
+        }
+
+        if let Some(http_api) = data.http_api {
+            active.http_api = Set(http_api);
+        }
+
+        if let Some(settings) = data.tray_settings {
+            active.tray_settings = Set(settings);
+        }
+
+        if let Some(settings) = data.log_settings {
+            active.log_settings = Set(settings);
+        }
+
+        if let Some(enabled) = data.usage_stats_enabled {
+            active.usage_stats_enabled = Set(enabled);
+        }
+
+        if let Some(progress) = data.setup_progress {
+            active.setup_progress = Set(progress);
+        }
+
+        if let Some(compression) = data.backup_compression {
+            active.backup_compression = Set(compression);
+        }
+
+        if let Some(use_trash) = data.use_trash {
+            active.use_trash = Set(use_trash);
+        }
+
+        if let Some(max) = data.max_db_backups {
+            active.max_db_backups = Set(max);
+        }
+
+        if let Some(days) = data.max_backup_age_days {
+            active.max_backup_age_days = Set(days);
+        }
+
+        if let Some(retries) = data.file_lock_retries {
+            active.file_lock_retries = Set(retries);
+        }
+
+        if let Some(delay) = data.file_lock_retry_delay_ms {
+            active.file_lock_retry_delay_ms = Set(delay);
+        }
+
         active.update(db).await?;
         Ok(())
     }
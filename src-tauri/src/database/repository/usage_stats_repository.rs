@@ -0,0 +1,52 @@
+use crate::entity::prelude::*;
+use crate::entity::usage_stats;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::*;
+
+/// 本地功能使用统计仓库
+pub struct UsageStatsRepository;
+
+impl UsageStatsRepository {
+    /// 指定功能在指定日期的使用次数加一
+    pub async fn increment(
+        db: &DatabaseConnection,
+        feature: &str,
+        date: &str,
+        now: i32,
+    ) -> Result<(), DbErr> {
+        let record = usage_stats::ActiveModel {
+            feature: Set(feature.to_string()),
+            date: Set(date.to_string()),
+            count: Set(1),
+            last_used: Set(now),
+        };
+
+        UsageStats::insert(record)
+            .on_conflict(
+                OnConflict::columns([usage_stats::Column::Feature, usage_stats::Column::Date])
+                    .value(
+                        usage_stats::Column::Count,
+                        Expr::col((UsageStats, usage_stats::Column::Count)).add(1),
+                    )
+                    .update_column(usage_stats::Column::LastUsed)
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// 所有统计记录，按功能与日期排序
+    pub async fn find_all(db: &DatabaseConnection) -> Result<Vec<usage_stats::Model>, DbErr> {
+        UsageStats::find()
+            .order_by_asc(usage_stats::Column::Feature)
+            .order_by_asc(usage_stats::Column::Date)
+            .all(db)
+            .await
+    }
+
+    /// 清空统计记录，返回删除的行数
+    pub async fn clear(db: &DatabaseConnection) -> Result<u64, DbErr> {
+        Ok(UsageStats::delete_many().exec(db).await?.rows_affected)
+    }
+}
@@ -1,27 +1,50 @@
 use sea_orm::DatabaseConnection;
-use tauri::State;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter, State};
 
+use crate::database::db::DbState;
 use crate::database::dto::{
-    BatchOperationResult, InsertCollectionData, InsertGameData, UpdateCollectionData,
-    UpdateGameData, UpdateSettingsData,
+    BatchOperationResult, InitialAppState, InsertCollectionData, InsertGameData,
+    UpdateCollectionData, UpdateGameData, UpdateSettingsData,
+};
+use crate::database::metadata_diff::{
+    AcceptedFieldChange, GameMetadataDiff, build_update, diff_game,
 };
 use crate::database::repository::{
-    collections_repository::{CategoryWithCount, CollectionsRepository},
-    game_stats_repository::{DailyStats, GameLastPlayed, GameStatsRepository},
-    games_repository::{GameType, GamesRepository, SortOption, SortOrder},
+    collections_repository::{
+        CategoryWithCount, CollectionReassignment, CollectionTransferResult, CollectionTreeNode,
+        CollectionsRepository, DeleteCollectionResult, PopulateCollectionResult,
+    },
+    game_stats_repository::{
+        DailyStats, GameLastPlayed, GameStatsRepository, LibraryOverview, PlayHabits,
+        SessionTagSummary, YearReport,
+    },
+    games_repository::{
+        GameType, GamesRepository, PlayStatus, PlayStatusCount, SortOption, SortOrder, TagMatchMode,
+    },
+    launch_attempts_repository::LaunchAttemptsRepository,
     settings_repository::SettingsRepository,
 };
-use crate::entity::{games, savedata, user};
+use crate::entity::process_stats::ProcessStats;
+use crate::entity::{games, launch_attempts, savedata, user};
+use crate::game::auto_clear::check_playtime_rule;
 use crate::game::cover::{DownloadState, delete_game_cover_dir};
+use crate::game::monitor::active_sessions;
+use crate::utils::file_lock::load_retry_policy;
+use crate::utils::metrics::CommandTimer;
+use crate::utils::notification::notify_playtime_milestone;
 
 // ==================== 游戏数据相关 ====================
 
 /// 插入游戏数据（单表架构）
 #[tauri::command]
 pub async fn insert_game(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game: InsertGameData,
 ) -> Result<games::Model, String> {
+    let _timer = CommandTimer::start("insert_game");
+    let db = db.get();
     GamesRepository::insert(&db, game)
         .await
         .map_err(|e| format!("插入游戏数据失败: {}", e))
@@ -29,35 +52,49 @@ pub async fn insert_game(
 
 #[tauri::command]
 pub async fn insert_games_batch(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     games: Vec<InsertGameData>,
 ) -> Result<BatchOperationResult, String> {
+    let _timer = CommandTimer::start("insert_games_batch");
+    let db = db.get();
     Ok(GamesRepository::insert_batch(&db, games).await)
 }
 
 /// 根据 ID 查询游戏数据
 #[tauri::command]
 pub async fn find_game_by_id(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     id: i32,
 ) -> Result<Option<games::Model>, String> {
+    let _timer = CommandTimer::start("find_game_by_id");
+    let db = db.get();
     GamesRepository::find_by_id(&db, id)
         .await
         .map_err(|e| format!("查询游戏数据失败: {}", e))
 }
 
-/// 获取所有游戏数据，支持按类型筛选和排序
+/// 获取所有游戏数据，支持按类型、游戏状态筛选和排序
 #[tauri::command]
 pub async fn find_all_games(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_type: GameType,
+    play_status: Option<PlayStatus>,
     sort_option: SortOption,
     sort_order: SortOrder,
     language: Option<String>,
 ) -> Result<Vec<games::Model>, String> {
-    GamesRepository::find_all(&db, game_type, sort_option, sort_order, language)
-        .await
-        .map_err(|e| format!("获取游戏数据失败: {}", e))
+    let _timer = CommandTimer::start("find_all_games");
+    let db = db.get();
+    GamesRepository::find_all(
+        &db,
+        game_type,
+        play_status,
+        sort_option,
+        sort_order,
+        language,
+    )
+    .await
+    .map_err(|e| format!("获取游戏数据失败: {}", e))
 }
 
 /// 只返回排序/筛选后的游戏 ID 列表
@@ -66,24 +103,60 @@ pub async fn find_all_games(
 /// 避免数 MB 级 JSON 反复穿过 IPC 桥梁。
 #[tauri::command]
 pub async fn find_game_ids(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_type: GameType,
+    play_status: Option<PlayStatus>,
     sort_option: SortOption,
     sort_order: SortOrder,
     language: Option<String>,
 ) -> Result<Vec<i32>, String> {
-    GamesRepository::find_ids(&db, game_type, sort_option, sort_order, language)
+    let _timer = CommandTimer::start("find_game_ids");
+    let db = db.get();
+    GamesRepository::find_ids(
+        &db,
+        game_type,
+        play_status,
+        sort_option,
+        sort_order,
+        language,
+    )
+    .await
+    .map_err(|e| format!("获取游戏 ID 列表失败: {}", e))
+}
+
+/// 批量设置游戏状态，返回更新的游戏数量
+#[tauri::command]
+pub async fn set_play_status(
+    db: State<'_, DbState>,
+    game_ids: Vec<i32>,
+    status: PlayStatus,
+) -> Result<u64, String> {
+    let _timer = CommandTimer::start("set_play_status");
+    let db = db.get();
+    GamesRepository::set_play_status(&db, game_ids, status)
+        .await
+        .map_err(|e| format!("设置游戏状态失败: {}", e))
+}
+
+/// 按游戏状态统计游戏数量
+#[tauri::command]
+pub async fn count_by_status(db: State<'_, DbState>) -> Result<Vec<PlayStatusCount>, String> {
+    let _timer = CommandTimer::start("count_by_status");
+    let db = db.get();
+    GamesRepository::count_by_status(&db)
         .await
-        .map_err(|e| format!("获取游戏 ID 列表失败: {}", e))
+        .map_err(|e| format!("统计游戏状态失败: {}", e))
 }
 
 /// 更新游戏数据（单表架构）
 #[tauri::command]
 pub async fn update_game(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
     updates: UpdateGameData,
 ) -> Result<games::Model, String> {
+    let _timer = CommandTimer::start("update_game");
+    let db = db.get();
     GamesRepository::update(&db, game_id, updates)
         .await
         .map_err(|e| format!("更新游戏数据失败: {}", e))
@@ -92,10 +165,12 @@ pub async fn update_game(
 /// 删除游戏
 #[tauri::command]
 pub async fn delete_game(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     cover_state: State<'_, DownloadState>,
     id: i32,
 ) -> Result<u64, String> {
+    let _timer = CommandTimer::start("delete_game");
+    let db = db.get();
     let rows_affected = GamesRepository::delete(&db, id)
         .await
         .map(|result| result.rows_affected)
@@ -122,10 +197,12 @@ pub async fn delete_game(
 /// 批量删除游戏
 #[tauri::command]
 pub async fn delete_games_batch(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     cover_state: State<'_, DownloadState>,
     ids: Vec<i32>,
 ) -> Result<u64, String> {
+    let _timer = CommandTimer::start("delete_games_batch");
+    let db = db.get();
     let rows_affected = GamesRepository::delete_many(&db, ids.clone())
         .await
         .map(|result| result.rows_affected)
@@ -159,7 +236,9 @@ pub async fn delete_games_batch(
 
 /// 获取游戏总数
 #[tauri::command]
-pub async fn count_games(db: State<'_, DatabaseConnection>) -> Result<u64, String> {
+pub async fn count_games(db: State<'_, DbState>) -> Result<u64, String> {
+    let _timer = CommandTimer::start("count_games");
+    let db = db.get();
     GamesRepository::count(&db)
         .await
         .map_err(|e| format!("获取游戏总数失败: {}", e))
@@ -167,10 +246,9 @@ pub async fn count_games(db: State<'_, DatabaseConnection>) -> Result<u64, Strin
 
 /// 检查 BGM ID 是否已存在
 #[tauri::command]
-pub async fn game_exists_by_bgm_id(
-    db: State<'_, DatabaseConnection>,
-    bgm_id: String,
-) -> Result<bool, String> {
+pub async fn game_exists_by_bgm_id(db: State<'_, DbState>, bgm_id: String) -> Result<bool, String> {
+    let _timer = CommandTimer::start("game_exists_by_bgm_id");
+    let db = db.get();
     GamesRepository::exists_bgm_id(&db, &bgm_id)
         .await
         .map_err(|e| format!("检查 BGM ID 是否存在失败: {}", e))
@@ -179,9 +257,11 @@ pub async fn game_exists_by_bgm_id(
 /// 检查 VNDB ID 是否已存在
 #[tauri::command]
 pub async fn game_exists_by_vndb_id(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     vndb_id: String,
 ) -> Result<bool, String> {
+    let _timer = CommandTimer::start("game_exists_by_vndb_id");
+    let db = db.get();
     GamesRepository::exists_vndb_id(&db, &vndb_id)
         .await
         .map_err(|e| format!("检查 VNDB ID 是否存在失败: {}", e))
@@ -189,9 +269,9 @@ pub async fn game_exists_by_vndb_id(
 
 /// 获取所有游戏的 BGM ID（返回 {id, bgm_id} 对象数组）
 #[tauri::command]
-pub async fn get_all_bgm_ids(
-    db: State<'_, DatabaseConnection>,
-) -> Result<Vec<(i32, String)>, String> {
+pub async fn get_all_bgm_ids(db: State<'_, DbState>) -> Result<Vec<(i32, String)>, String> {
+    let _timer = CommandTimer::start("get_all_bgm_ids");
+    let db = db.get();
     GamesRepository::get_all_bgm_ids(&db)
         .await
         .map_err(|e| format!("获取 BGM ID 列表失败: {}", e))
@@ -199,9 +279,9 @@ pub async fn get_all_bgm_ids(
 
 /// 获取所有游戏的 VNDB ID（返回 {id, vndb_id} 对象数组）
 #[tauri::command]
-pub async fn get_all_vndb_ids(
-    db: State<'_, DatabaseConnection>,
-) -> Result<Vec<(i32, String)>, String> {
+pub async fn get_all_vndb_ids(db: State<'_, DbState>) -> Result<Vec<(i32, String)>, String> {
+    let _timer = CommandTimer::start("get_all_vndb_ids");
+    let db = db.get();
     GamesRepository::get_all_vndb_ids(&db)
         .await
         .map_err(|e| format!("获取 VNDB ID 列表失败: {}", e))
@@ -212,36 +292,104 @@ pub async fn get_all_vndb_ids(
 /// 使用单个事务处理所有更新操作，性能远优于逐个更新
 #[tauri::command]
 pub async fn update_games_batch(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     updates: Vec<(i32, UpdateGameData)>,
 ) -> Result<Vec<games::Model>, String> {
+    let _timer = CommandTimer::start("update_games_batch");
+    let db = db.get();
     GamesRepository::update_batch(&db, updates)
         .await
         .map_err(|e| format!("批量更新数据失败: {}", e))
 }
 
+/// 预览批量元数据更新，返回每个游戏逐字段的新旧值差异（不写入数据库）
+///
+/// 没有任何差异的游戏不会出现在结果中；不存在的游戏 ID 会被忽略。
+#[tauri::command]
+pub async fn preview_metadata_updates(
+    db: State<'_, DbState>,
+    updates: Vec<(i32, UpdateGameData)>,
+) -> Result<Vec<GameMetadataDiff>, String> {
+    let _timer = CommandTimer::start("preview_metadata_updates");
+    let db = db.get();
+    let mut diffs = Vec::new();
+    for (game_id, update) in updates {
+        let Some(game) = GamesRepository::find_by_id(&db, game_id)
+            .await
+            .map_err(|e| format!("获取游戏数据失败: {}", e))?
+        else {
+            continue;
+        };
+        let fields = diff_game(&game, &update.cleaned());
+        if !fields.is_empty() {
+            diffs.push(GameMetadataDiff { game_id, fields });
+        }
+    }
+    Ok(diffs)
+}
+
+/// 应用用户在预览中接受的字段变更，所有游戏在同一事务内提交
+#[tauri::command]
+pub async fn apply_metadata_diff(
+    db: State<'_, DbState>,
+    accepted: Vec<AcceptedFieldChange>,
+) -> Result<Vec<games::Model>, String> {
+    let _timer = CommandTimer::start("apply_metadata_diff");
+    let db = db.get();
+    let mut by_game: BTreeMap<i32, Vec<AcceptedFieldChange>> = BTreeMap::new();
+    for change in accepted {
+        by_game.entry(change.game_id).or_default().push(change);
+    }
+
+    let mut updates = Vec::with_capacity(by_game.len());
+    for (game_id, changes) in by_game {
+        let game = GamesRepository::find_by_id(&db, game_id)
+            .await
+            .map_err(|e| format!("获取游戏数据失败: {}", e))?
+            .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+        let update = build_update(&game, &changes)
+            .map_err(|e| format!("游戏 {} 的变更无效: {}", game_id, e))?;
+        updates.push((game_id, update));
+    }
+
+    GamesRepository::update_batch(&db, updates)
+        .await
+        .map_err(|e| format!("应用元数据变更失败: {}", e))
+}
+
 // ==================== 存档备份相关 ====================
 
 /// 保存存档备份记录
 #[tauri::command]
 pub async fn save_savedata_record(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
     file_name: String,
     backup_time: i32,
     file_size: i32,
+    auto: Option<bool>,
+    sha256: Option<String>,
 ) -> Result<i32, String> {
-    GamesRepository::save_savedata_record(&db, game_id, &file_name, backup_time, file_size)
-        .await
-        .map_err(|e| format!("保存存档备份记录失败: {}", e))
+    let _timer = CommandTimer::start("save_savedata_record");
+    let db = db.get();
+    GamesRepository::save_savedata_record(
+        &db,
+        game_id,
+        &file_name,
+        backup_time,
+        file_size,
+        auto.unwrap_or(false),
+        sha256,
+    )
+    .await
+    .map_err(|e| format!("保存存档备份记录失败: {}", e))
 }
 
 /// 获取指定游戏的备份数量
 #[tauri::command]
-pub async fn get_savedata_count(
-    db: State<'_, DatabaseConnection>,
-    game_id: i32,
-) -> Result<u64, String> {
+pub async fn get_savedata_count(db: State<'_, DbState>, game_id: i32) -> Result<u64, String> {
+    let _timer = CommandTimer::start("get_savedata_count");
+    let db = db.get();
     GamesRepository::get_savedata_count(&db, game_id)
         .await
         .map_err(|e| format!("获取备份数量失败: {}", e))
@@ -250,40 +398,135 @@ pub async fn get_savedata_count(
 /// 获取指定游戏的所有备份记录
 #[tauri::command]
 pub async fn get_savedata_records(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
 ) -> Result<Vec<savedata::Model>, String> {
+    let _timer = CommandTimer::start("get_savedata_records");
+    let db = db.get();
     GamesRepository::get_savedata_records(&db, game_id)
         .await
         .map_err(|e| format!("获取备份记录失败: {}", e))
 }
 
+// ==================== 启动记录相关 ====================
+
+/// 获取指定游戏最近的启动记录
+#[tauri::command]
+pub async fn get_launch_attempts(
+    db: State<'_, DbState>,
+    game_id: i32,
+    limit: u64,
+) -> Result<Vec<launch_attempts::Model>, String> {
+    let _timer = CommandTimer::start("get_launch_attempts");
+    let db = db.get();
+    LaunchAttemptsRepository::get_recent_attempts(&db, game_id, limit)
+        .await
+        .map_err(|e| format!("获取启动记录失败: {}", e))
+}
+
 // ==================== 游戏统计相关 ====================
 
 /// 记录游戏会话
 #[tauri::command]
 pub async fn record_game_session(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
     start_time: i32,
     end_time: i32,
     duration: i32,
     date: String,
+    process_stats: Option<ProcessStats>,
 ) -> Result<i32, String> {
-    GameStatsRepository::record_session(&db, game_id, start_time, end_time, duration, date)
+    let _timer = CommandTimer::start("record_game_session");
+    let db = db.get();
+    GameStatsRepository::record_session(
+        &db,
+        game_id,
+        start_time,
+        end_time,
+        duration,
+        date,
+        process_stats,
+    )
+    .await
+    .map_err(|e| format!("记录游戏会话失败: {}", e))
+}
+
+/// 读取游戏当前总时长（分钟），没有统计记录时为 0
+async fn current_total_time(db: &DatabaseConnection, game_id: i32) -> Result<i32, String> {
+    Ok(GameStatsRepository::get_statistics(db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏统计失败: {}", e))?
+        .and_then(|stats| stats.total_time)
+        .unwrap_or(0))
+}
+
+/// 手动补录一次游戏会话（如在其他设备或未开启监控时游玩），并重新计算统计
+///
+/// # Arguments
+/// * `start_time` / `end_time` - 会话起止时间（Unix 时间戳，秒）
+/// * `note` - 可选的备注
+#[tauri::command]
+pub async fn add_manual_session(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    game_id: i32,
+    start_time: i32,
+    end_time: i32,
+    note: Option<String>,
+) -> Result<crate::entity::game_sessions::Model, String> {
+    let _timer = CommandTimer::start("add_manual_session");
+    let db = db.get();
+    let previous_total = current_total_time(&db, game_id).await?;
+
+    let session = GameStatsRepository::add_manual_session(&db, game_id, start_time, end_time, note)
         .await
-        .map_err(|e| format!("记录游戏会话失败: {}", e))
+        .map_err(|e| format!("补录游戏会话失败: {}", e))?;
+
+    let total_time = current_total_time(&db, game_id).await?;
+    if let Err(e) = check_playtime_rule(&app, &db, game_id, previous_total, total_time).await {
+        log::warn!("检查自动标记规则失败: {}", e);
+    }
+    Ok(session)
 }
 
-/// 获取游戏会话历史
+/// 按分钟增减游戏总时长（正数增加，负数减少），返回调整后的总时长
+///
+/// 调整以一条手动会话的形式保存，不计入会话次数与每日统计。
+#[tauri::command]
+pub async fn adjust_game_total_time(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    game_id: i32,
+    delta_minutes: i32,
+    note: Option<String>,
+) -> Result<i32, String> {
+    let _timer = CommandTimer::start("adjust_game_total_time");
+    let db = db.get();
+    let previous_total = current_total_time(&db, game_id).await?;
+
+    let total_time = GameStatsRepository::adjust_total_time(&db, game_id, delta_minutes, note)
+        .await
+        .map_err(|e| format!("调整游戏时长失败: {}", e))?;
+
+    if let Err(e) = check_playtime_rule(&app, &db, game_id, previous_total, total_time).await {
+        log::warn!("检查自动标记规则失败: {}", e);
+    }
+    Ok(total_time)
+}
+
+/// 获取游戏会话历史，`tag` 不为空时只返回带该标签的会话
 #[tauri::command]
 pub async fn get_game_sessions(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
     limit: u64,
     offset: u64,
+    tag: Option<String>,
 ) -> Result<Vec<crate::entity::game_sessions::Model>, String> {
-    GameStatsRepository::get_sessions(&db, game_id, limit, offset)
+    let _timer = CommandTimer::start("get_game_sessions");
+    let db = db.get();
+    GameStatsRepository::get_sessions(&db, game_id, limit, offset, tag)
         .await
         .map_err(|e| format!("获取游戏会话历史失败: {}", e))
 }
@@ -291,21 +534,83 @@ pub async fn get_game_sessions(
 /// 获取指定游戏范围内的全局最近会话
 #[tauri::command]
 pub async fn get_recent_sessions_for_all(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_ids: Vec<i32>,
     limit: u64,
+    tag: Option<String>,
 ) -> Result<Vec<crate::entity::game_sessions::Model>, String> {
-    GameStatsRepository::get_recent_sessions_for_all(&db, game_ids, limit)
+    let _timer = CommandTimer::start("get_recent_sessions_for_all");
+    let db = db.get();
+    GameStatsRepository::get_recent_sessions_for_all(&db, game_ids, limit, tag)
         .await
         .map_err(|e| format!("获取最近会话失败: {}", e))
 }
 
-/// 删除游戏会话
+/// 设置会话标签（整体替换），传入空列表时清除标签
 #[tauri::command]
-pub async fn delete_game_session(
-    db: State<'_, DatabaseConnection>,
+pub async fn tag_session(
+    db: State<'_, DbState>,
     session_id: i32,
-) -> Result<u64, String> {
+    tags: Vec<String>,
+) -> Result<crate::entity::game_sessions::Model, String> {
+    let _timer = CommandTimer::start("tag_session");
+    let db = db.get();
+    GameStatsRepository::tag_session(&db, session_id, tags)
+        .await
+        .map_err(|e| format!("设置会话标签失败: {}", e))
+}
+
+/// 设置会话备注（如「打完 A 线」「直播录像」），传入空值时清除备注
+#[tauri::command]
+pub async fn update_session_note(
+    db: State<'_, DbState>,
+    session_id: i32,
+    note: Option<String>,
+) -> Result<crate::entity::game_sessions::Model, String> {
+    let _timer = CommandTimer::start("update_session_note");
+    let db = db.get();
+    GameStatsRepository::update_session_note(&db, session_id, note)
+        .await
+        .map_err(|e| format!("设置会话备注失败: {}", e))
+}
+
+/// 获取单个游戏按标签汇总的会话统计
+#[tauri::command]
+pub async fn get_session_tag_summary(
+    db: State<'_, DbState>,
+    game_id: i32,
+) -> Result<Vec<SessionTagSummary>, String> {
+    let _timer = CommandTimer::start("get_session_tag_summary");
+    let db = db.get();
+    GameStatsRepository::get_session_tag_summary(&db, game_id)
+        .await
+        .map_err(|e| format!("获取会话标签统计失败: {}", e))
+}
+
+/// 获取按星期与小时汇总的游玩习惯
+///
+/// # Arguments
+/// * `start_date` / `end_date` - 可选的 `YYYY-MM-DD` 日期范围（闭区间）
+/// * `game_id` - 可选的游戏 ID，为空时统计所有游戏
+#[tauri::command]
+pub async fn get_play_habits(
+    db: State<'_, DbState>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    game_id: Option<i32>,
+) -> Result<PlayHabits, String> {
+    let _timer = CommandTimer::start("get_play_habits");
+    let db = db.get();
+    GameStatsRepository::get_play_habits(&db, start_date, end_date, game_id)
+        .await
+        .map_err(|e| format!("获取游玩习惯统计失败: {}", e))
+}
+
+/// 删除游戏会话
+#[tauri::command]
+pub async fn delete_game_session(db: State<'_, DbState>, session_id: i32) -> Result<u64, String> {
+    let _timer = CommandTimer::start("delete_game_session");
+    let db = db.get();
     GameStatsRepository::delete_session(&db, session_id)
         .await
         .map(|result| result.rows_affected)
@@ -315,13 +620,22 @@ pub async fn delete_game_session(
 /// 更新游戏统计信息
 #[tauri::command]
 pub async fn update_game_statistics(
-    db: State<'_, DatabaseConnection>,
+    app: AppHandle,
+    db: State<'_, DbState>,
     game_id: i32,
     total_time: i32,
     session_count: i32,
     last_played: Option<i32>,
     daily_stats: Vec<DailyStats>,
 ) -> Result<(), String> {
+    let _timer = CommandTimer::start("update_game_statistics");
+    let db = db.get();
+    let previous_total = GameStatsRepository::get_statistics(&db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏统计失败: {}", e))?
+        .and_then(|stats| stats.total_time)
+        .unwrap_or(0);
+
     GameStatsRepository::update_statistics(
         &db,
         game_id,
@@ -331,15 +645,24 @@ pub async fn update_game_statistics(
         daily_stats,
     )
     .await
-    .map_err(|e| format!("更新游戏统计失败: {}", e))
+    .map_err(|e| format!("更新游戏统计失败: {}", e))?;
+
+    // 自动标记失败不影响统计写入
+    if let Err(e) = check_playtime_rule(&app, &db, game_id, previous_total, total_time).await {
+        log::warn!("检查自动标记规则失败: {}", e);
+    }
+    notify_playtime_milestone(&app, &db, game_id, previous_total, total_time).await;
+    Ok(())
 }
 
 /// 获取游戏统计信息
 #[tauri::command]
 pub async fn get_game_statistics(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
 ) -> Result<Option<crate::entity::game_statistics::Model>, String> {
+    let _timer = CommandTimer::start("get_game_statistics");
+    let db = db.get();
     GameStatsRepository::get_statistics(&db, game_id)
         .await
         .map_err(|e| format!("获取游戏统计失败: {}", e))
@@ -348,9 +671,11 @@ pub async fn get_game_statistics(
 /// 批量获取游戏统计信息
 #[tauri::command]
 pub async fn get_multiple_game_statistics(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_ids: Vec<i32>,
 ) -> Result<Vec<crate::entity::game_statistics::Model>, String> {
+    let _timer = CommandTimer::start("get_multiple_game_statistics");
+    let db = db.get();
     GameStatsRepository::get_statistics_batch(&db, game_ids)
         .await
         .map_err(|e| format!("批量获取游戏统计失败: {}", e))
@@ -359,8 +684,10 @@ pub async fn get_multiple_game_statistics(
 /// 获取所有游戏统计信息
 #[tauri::command]
 pub async fn get_all_game_statistics(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
 ) -> Result<Vec<crate::entity::game_statistics::Model>, String> {
+    let _timer = CommandTimer::start("get_all_game_statistics");
+    let db = db.get();
     GameStatsRepository::get_all_statistics(&db)
         .await
         .map_err(|e| format!("获取所有游戏统计失败: {}", e))
@@ -369,8 +696,10 @@ pub async fn get_all_game_statistics(
 /// 获取所有游戏的最近游玩时间
 #[tauri::command]
 pub async fn get_all_game_last_played(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
 ) -> Result<Vec<GameLastPlayed>, String> {
+    let _timer = CommandTimer::start("get_all_game_last_played");
+    let db = db.get();
     GameStatsRepository::get_all_last_played(&db)
         .await
         .map_err(|e| format!("获取所有游戏最近游玩时间失败: {}", e))
@@ -378,23 +707,64 @@ pub async fn get_all_game_last_played(
 
 /// 删除游戏统计信息
 #[tauri::command]
-pub async fn delete_game_statistics(
-    db: State<'_, DatabaseConnection>,
-    game_id: i32,
-) -> Result<u64, String> {
+pub async fn delete_game_statistics(db: State<'_, DbState>, game_id: i32) -> Result<u64, String> {
+    let _timer = CommandTimer::start("delete_game_statistics");
+    let db = db.get();
     GameStatsRepository::delete_statistics(&db, game_id)
         .await
         .map(|result| result.rows_affected)
         .map_err(|e| format!("删除游戏统计失败: {}", e))
 }
 
+/// 获取游戏库总览（总数、总时长、近 12 个月时长、排行、连续游玩天数与通关比例）
+#[tauri::command]
+pub async fn get_library_overview(db: State<'_, DbState>) -> Result<LibraryOverview, String> {
+    let _timer = CommandTimer::start("get_library_overview");
+    let db = db.get();
+    GameStatsRepository::get_library_overview(&db)
+        .await
+        .map_err(|e| format!("获取游戏库总览失败: {}", e))
+}
+
+/// 按日汇总所有游戏的游玩时长（分钟），用于日历热力图
+///
+/// # Arguments
+/// * `start_date` / `end_date` - `YYYY-MM-DD` 日期范围（闭区间）
+#[tauri::command]
+pub async fn get_daily_playtime_range(
+    db: State<'_, DbState>,
+    start_date: String,
+    end_date: String,
+) -> Result<BTreeMap<String, i32>, String> {
+    let _timer = CommandTimer::start("get_daily_playtime_range");
+    let db = db.get();
+    GameStatsRepository::get_daily_playtime_range(&db, &start_date, &end_date)
+        .await
+        .map_err(|e| format!("获取每日游玩时长失败: {}", e))
+}
+
+/// 生成年度报告
+///
+/// # Arguments
+/// * `year` - 公历年份，按本地时间统计
+#[tauri::command]
+pub async fn generate_year_report(db: State<'_, DbState>, year: i32) -> Result<YearReport, String> {
+    let _timer = CommandTimer::start("generate_year_report");
+    let db = db.get();
+    GameStatsRepository::generate_year_report(&db, year)
+        .await
+        .map_err(|e| format!("生成年度报告失败: {}", e))
+}
+
 /// 获取今天的游戏时间
 #[tauri::command]
 pub async fn get_today_playtime(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
     today: String,
 ) -> Result<i32, String> {
+    let _timer = CommandTimer::start("get_today_playtime");
+    let db = db.get();
     GameStatsRepository::get_today_playtime(&db, game_id, &today)
         .await
         .map_err(|e| format!("获取今天游戏时间失败: {}", e))
@@ -402,10 +772,9 @@ pub async fn get_today_playtime(
 
 /// 初始化游戏统计记录
 #[tauri::command]
-pub async fn init_game_statistics(
-    db: State<'_, DatabaseConnection>,
-    game_id: i32,
-) -> Result<(), String> {
+pub async fn init_game_statistics(db: State<'_, DbState>, game_id: i32) -> Result<(), String> {
+    let _timer = CommandTimer::start("init_game_statistics");
+    let db = db.get();
     GameStatsRepository::init_statistics_if_not_exists(&db, game_id)
         .await
         .map_err(|e| format!("初始化游戏统计失败: {}", e))
@@ -415,23 +784,56 @@ pub async fn init_game_statistics(
 
 /// 获取所有设置
 #[tauri::command]
-pub async fn get_all_settings(db: State<'_, DatabaseConnection>) -> Result<user::Model, String> {
+pub async fn get_all_settings(db: State<'_, DbState>) -> Result<user::Model, String> {
+    let _timer = CommandTimer::start("get_all_settings");
+    let db = db.get();
     SettingsRepository::get_all_settings(&db)
         .await
         .map_err(|e| format!("获取所有设置失败: {}", e))
 }
 
+/// 设置变更事件名
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// 设置变更事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsChangedPayload {
+    /// 变更的设置项（camelCase，与 UpdateSettingsData 字段一致）
+    pub keys: Vec<&'static str>,
+}
+
+/// 通知所有窗口设置已变更，使多个窗口无需轮询即可保持同步
+pub fn emit_settings_changed(app: &AppHandle, keys: Vec<&'static str>) {
+    if keys.is_empty() {
+        return;
+    }
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, SettingsChangedPayload { keys }) {
+        log::warn!("发送设置变更事件失败: {}", e);
+    }
+}
+
 /// 批量更新设置
+///
+/// 更新成功后广播 `settings-changed` 事件，负载为变更的设置项。
 #[tauri::command]
 pub async fn update_settings(
-    db: State<'_, DatabaseConnection>,
+    app: AppHandle,
+    db: State<'_, DbState>,
     data: UpdateSettingsData,
 ) -> Result<(), String> {
+    let _timer = CommandTimer::start("update_settings");
+    let db = db.get();
     let data = data.cleaned(); // 清洗空字符串
+    let keys = data.changed_keys();
 
     SettingsRepository::update_settings(&db, data)
         .await
-        .map_err(|e| format!("更新设置失败: {}", e))
+        .map_err(|e| format!("更新设置失败: {}", e))?;
+    if keys.contains(&"fileLockRetries") || keys.contains(&"fileLockRetryDelayMs") {
+        load_retry_policy(&db).await;
+    }
+    emit_settings_changed(&app, keys);
+    Ok(())
 }
 
 // ==================== 合集相关 ====================
@@ -439,12 +841,14 @@ pub async fn update_settings(
 /// 创建合集
 #[tauri::command]
 pub async fn create_collection(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     name: String,
     parent_id: Option<i32>,
     sort_order: i32,
     icon: Option<String>,
 ) -> Result<crate::entity::collections::Model, String> {
+    let _timer = CommandTimer::start("create_collection");
+    let db = db.get();
     let data = InsertCollectionData {
         name,
         parent_id,
@@ -461,23 +865,41 @@ pub async fn create_collection(
 /// 获取根合集
 #[tauri::command]
 pub async fn find_root_collections(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
 ) -> Result<Vec<crate::entity::collections::Model>, String> {
+    let _timer = CommandTimer::start("find_root_collections");
+    let db = db.get();
     CollectionsRepository::find_root_collections(&db)
         .await
         .map_err(|e| format!("获取根合集失败: {}", e))
 }
 
+/// 获取完整的合集树（任意层级），每个节点的游戏数包含所有子孙合集
+#[tauri::command]
+pub async fn get_collection_tree(
+    db: State<'_, DbState>,
+) -> Result<Vec<CollectionTreeNode>, String> {
+    let _timer = CommandTimer::start("get_collection_tree");
+    let db = db.get();
+    CollectionsRepository::get_collection_tree(&db)
+        .await
+        .map_err(|e| format!("获取合集树失败: {}", e))
+}
+
 /// 更新合集
+///
+/// 修改 `parent_id` 时拒绝把合集移动到自身或其子合集下，避免形成环
 #[tauri::command]
 pub async fn update_collection(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     id: i32,
     name: Option<String>,
     parent_id: Option<Option<i32>>,
     sort_order: Option<i32>,
     icon: Option<Option<String>>,
 ) -> Result<crate::entity::collections::Model, String> {
+    let _timer = CommandTimer::start("update_collection");
+    let db = db.get();
     let data = UpdateCollectionData {
         name,
         parent_id,
@@ -492,21 +914,84 @@ pub async fn update_collection(
 }
 
 /// 删除合集
+///
+/// # Arguments
+/// * `reassign_to` - 可选，把合集（及子合集）中的游戏移到指定合集或上级分组，未提供时直接删除关联
 #[tauri::command]
-pub async fn delete_collection(db: State<'_, DatabaseConnection>, id: i32) -> Result<u64, String> {
-    CollectionsRepository::delete(&db, id)
+pub async fn delete_collection(
+    db: State<'_, DbState>,
+    id: i32,
+    reassign_to: Option<CollectionReassignment>,
+) -> Result<DeleteCollectionResult, String> {
+    let _timer = CommandTimer::start("delete_collection");
+    let db = db.get();
+    CollectionsRepository::delete_with_reassignment(&db, id, reassign_to)
         .await
-        .map(|result| result.rows_affected)
         .map_err(|e| format!("删除合集失败: {}", e))
 }
 
+/// 把游戏从一个合集移动到另一个合集，在同一事务中完成加入与移除
+#[tauri::command]
+pub async fn move_games_between_collections(
+    db: State<'_, DbState>,
+    game_ids: Vec<i32>,
+    from_id: i32,
+    to_id: i32,
+) -> Result<CollectionTransferResult, String> {
+    let _timer = CommandTimer::start("move_games_between_collections");
+    let db = db.get();
+    CollectionsRepository::move_games_between_collections(&db, game_ids, from_id, to_id)
+        .await
+        .map_err(|e| format!("移动游戏失败: {}", e))
+}
+
+/// 把游戏复制到合集，追加到末尾，已在合集中的游戏跳过
+#[tauri::command]
+pub async fn copy_games_to_collection(
+    db: State<'_, DbState>,
+    game_ids: Vec<i32>,
+    to_id: i32,
+) -> Result<CollectionTransferResult, String> {
+    let _timer = CommandTimer::start("copy_games_to_collection");
+    let db = db.get();
+    CollectionsRepository::copy_games_to_collection(&db, game_ids, to_id)
+        .await
+        .map_err(|e| format!("复制游戏失败: {}", e))
+}
+
+/// 把元数据标签匹配的游戏批量加入合集，用于快速建立按类型划分的分类
+///
+/// # Arguments
+/// * `tags` - 要匹配的标签（忽略首尾空白与 ASCII 大小写）
+/// * `match_mode` - `any` 包含任一标签，`all` 包含全部标签，默认 `any`
+#[tauri::command]
+pub async fn populate_collection_from_tags(
+    db: State<'_, DbState>,
+    collection_id: i32,
+    tags: Vec<String>,
+    match_mode: Option<TagMatchMode>,
+) -> Result<PopulateCollectionResult, String> {
+    let _timer = CommandTimer::start("populate_collection_from_tags");
+    let db = db.get();
+    CollectionsRepository::populate_from_tags(
+        &db,
+        collection_id,
+        tags,
+        match_mode.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| format!("按标签填充合集失败: {}", e))
+}
+
 /// 从单个合集中批量移除游戏
 #[tauri::command]
 pub async fn remove_games_from_collection(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_ids: Vec<i32>,
     collection_id: i32,
 ) -> Result<u64, String> {
+    let _timer = CommandTimer::start("remove_games_from_collection");
+    let db = db.get();
     CollectionsRepository::remove_games_from_collection(&db, game_ids, collection_id)
         .await
         .map(|result| result.rows_affected)
@@ -516,9 +1001,11 @@ pub async fn remove_games_from_collection(
 /// 获取合集中的所有游戏 ID
 #[tauri::command]
 pub async fn get_games_in_collection(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     collection_id: i32,
 ) -> Result<Vec<i32>, String> {
+    let _timer = CommandTimer::start("get_games_in_collection");
+    let db = db.get();
     CollectionsRepository::get_games_in_collection(&db, collection_id)
         .await
         .map_err(|e| format!("获取合集中的游戏失败: {}", e))
@@ -527,9 +1014,11 @@ pub async fn get_games_in_collection(
 /// 获取游戏所在的所有合集 ID
 #[tauri::command]
 pub async fn get_game_collection_ids(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
 ) -> Result<Vec<i32>, String> {
+    let _timer = CommandTimer::start("get_game_collection_ids");
+    let db = db.get();
     CollectionsRepository::get_game_collection_ids(&db, game_id)
         .await
         .map_err(|e| format!("获取游戏所在合集失败: {}", e))
@@ -538,10 +1027,12 @@ pub async fn get_game_collection_ids(
 /// 批量将多个游戏添加到多个合集
 #[tauri::command]
 pub async fn add_games_to_collections(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_ids: Vec<i32>,
     collection_ids: Vec<i32>,
 ) -> Result<(), String> {
+    let _timer = CommandTimer::start("add_games_to_collections");
+    let db = db.get();
     CollectionsRepository::add_games_to_collections(&db, game_ids, collection_ids)
         .await
         .map_err(|e| format!("批量添加游戏到合集失败: {}", e))
@@ -550,10 +1041,12 @@ pub async fn add_games_to_collections(
 /// 设置单个游戏所在的合集列表
 #[tauri::command]
 pub async fn set_game_collections(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: i32,
     collection_ids: Vec<i32>,
 ) -> Result<(), String> {
+    let _timer = CommandTimer::start("set_game_collections");
+    let db = db.get();
     CollectionsRepository::set_game_collections(&db, game_id, collection_ids)
         .await
         .map_err(|e| format!("设置游戏合集失败: {}", e))
@@ -562,21 +1055,53 @@ pub async fn set_game_collections(
 /// 批量更新分类中的游戏列表
 #[tauri::command]
 pub async fn update_category_games(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_ids: Vec<i32>,
     collection_id: i32,
 ) -> Result<(), String> {
+    let _timer = CommandTimer::start("update_category_games");
+    let db = db.get();
     CollectionsRepository::update_category_games(&db, game_ids, collection_id)
         .await
         .map_err(|e| format!("批量更新分类游戏失败: {}", e))
 }
 
+/// 调整合集内游戏的顺序（拖拽排序），返回实际更新的记录数
+#[tauri::command]
+pub async fn reorder_collection(
+    db: State<'_, DbState>,
+    collection_id: i32,
+    ordered_ids: Vec<i32>,
+) -> Result<usize, String> {
+    let _timer = CommandTimer::start("reorder_collection");
+    let db = db.get();
+    CollectionsRepository::reorder_collection(&db, collection_id, ordered_ids)
+        .await
+        .map_err(|e| format!("调整合集游戏顺序失败: {}", e))
+}
+
+/// 调整同一父级下合集的顺序（拖拽排序），`parent_id` 为空时调整根分组，返回实际更新的记录数
+#[tauri::command]
+pub async fn reorder_collections(
+    db: State<'_, DbState>,
+    parent_id: Option<i32>,
+    ordered_ids: Vec<i32>,
+) -> Result<usize, String> {
+    let _timer = CommandTimer::start("reorder_collections");
+    let db = db.get();
+    CollectionsRepository::reorder_collections(&db, parent_id, ordered_ids)
+        .await
+        .map_err(|e| format!("调整合集顺序失败: {}", e))
+}
+
 /// 批量获取多个分组的游戏数量（优化版）
 #[tauri::command]
 pub async fn batch_count_games_in_groups(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     group_ids: Vec<i32>,
 ) -> Result<std::collections::HashMap<i32, u64>, String> {
+    let _timer = CommandTimer::start("batch_count_games_in_groups");
+    let db = db.get();
     CollectionsRepository::batch_count_games_in_groups(&db, group_ids)
         .await
         .map_err(|e| format!("批量获取分组游戏数量失败: {}", e))
@@ -584,10 +1109,9 @@ pub async fn batch_count_games_in_groups(
 
 /// 获取分组中的游戏总数
 #[tauri::command]
-pub async fn count_games_in_group(
-    db: State<'_, DatabaseConnection>,
-    group_id: i32,
-) -> Result<u64, String> {
+pub async fn count_games_in_group(db: State<'_, DbState>, group_id: i32) -> Result<u64, String> {
+    let _timer = CommandTimer::start("count_games_in_group");
+    let db = db.get();
     CollectionsRepository::count_games_in_group(&db, group_id)
         .await
         .map_err(|e| format!("获取分组游戏数量失败: {}", e))
@@ -596,10 +1120,67 @@ pub async fn count_games_in_group(
 /// 获取指定分组的分类列表（带游戏数量）
 #[tauri::command]
 pub async fn get_categories_with_count(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     group_id: i32,
 ) -> Result<Vec<CategoryWithCount>, String> {
+    let _timer = CommandTimer::start("get_categories_with_count");
+    let db = db.get();
     CollectionsRepository::get_categories_with_count(&db, group_id)
         .await
         .map_err(|e| format!("获取分类列表失败: {}", e))
 }
+
+// ==================== 启动数据 ====================
+
+/// 首屏默认加载的游戏数量
+const INITIAL_GAMES_PAGE_SIZE: usize = 50;
+
+/// 一次性获取启动所需的数据
+///
+/// 在启动画面期间调用，合并设置、合集、首页游戏、活跃会话与今日游戏时间，
+/// 代替启动阶段十余次独立调用。
+///
+/// # Arguments
+/// * `sort_option` / `sort_order` / `language` - 首页游戏的排序方式，与 `find_game_ids` 一致
+/// * `page_size` - 首页游戏数量，默认 50
+/// * `today` - 本地日期（YYYY-MM-DD），用于统计今日游戏时间
+#[tauri::command]
+pub async fn get_initial_app_state(
+    db: State<'_, DbState>,
+    sort_option: SortOption,
+    sort_order: SortOrder,
+    language: Option<String>,
+    page_size: Option<usize>,
+    today: String,
+) -> Result<InitialAppState, String> {
+    let _timer = CommandTimer::start("get_initial_app_state");
+    let db = db.get();
+    let settings = SettingsRepository::get_all_settings(&db)
+        .await
+        .map_err(|e| format!("获取所有设置失败: {}", e))?;
+    let collections = CollectionsRepository::find_all(&db)
+        .await
+        .map_err(|e| format!("获取合集失败: {}", e))?;
+
+    let ids =
+        GamesRepository::find_ids(&db, GameType::All, None, sort_option, sort_order, language)
+            .await
+            .map_err(|e| format!("获取游戏数据失败: {}", e))?;
+    let page_ids = &ids[..ids.len().min(page_size.unwrap_or(INITIAL_GAMES_PAGE_SIZE))];
+    let games = GamesRepository::find_by_ids(&db, page_ids)
+        .await
+        .map_err(|e| format!("获取游戏数据失败: {}", e))?;
+
+    let today_playtime = GameStatsRepository::get_total_playtime_on(&db, &today)
+        .await
+        .map_err(|e| format!("获取今天游戏时间失败: {}", e))?;
+
+    Ok(InitialAppState {
+        settings,
+        collections,
+        games,
+        total_games: ids.len() as u64,
+        active_sessions: active_sessions(),
+        today_playtime,
+    })
+}
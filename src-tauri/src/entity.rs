@@ -5,17 +5,44 @@
 pub mod prelude;
 
 // === JSON 数据结构（嵌入 games 表的 JSON 列）===
+pub mod backup_schedule;
 pub mod bgm_data;
 pub mod custom_data;
+pub mod game_runner;
+pub mod input_mapping;
 pub mod kun_data;
+pub mod launch_args;
+pub mod sandbox_config;
 pub mod vndb_data;
+pub mod window_placement;
 pub mod ymgal_data;
 
+// === JSON 数据结构（嵌入 game_sessions 表的 JSON 列）===
+pub mod process_stats;
+pub mod session_tags;
+
+// === JSON 数据结构（嵌入 user 表的 JSON 列）===
+pub mod auto_clear_rules;
+pub mod backup_compression;
+pub mod http_api_settings;
+pub mod log_settings;
+pub mod monitor_settings;
+pub mod notification_settings;
+pub mod presence_settings;
+pub mod savedata_quota;
+pub mod scan_config;
+pub mod setup_progress;
+pub mod tray_settings;
+
 // === SeaORM 实体（对应数据库表）===
 pub mod collections;
 pub mod game_collection_link;
 pub mod game_sessions;
 pub mod game_statistics;
 pub mod games;
+pub mod launch_attempts;
 pub mod savedata;
+pub mod screenshots;
+pub mod session_checkpoints;
+pub mod usage_stats;
 pub mod user;
@@ -0,0 +1,36 @@
+//! 自动标记游戏状态的规则 JSON 结构体
+//!
+//! 此文件定义了存储在 user.auto_clear_rules 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 缺省目标状态：玩过 / PLAYED
+pub const DEFAULT_TARGET_STATUS: i32 = 2;
+
+/// 自动标记规则
+///
+/// 规则命中时修改游戏状态并发出事件通知前端，不会静默修改。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct AutoClearRules {
+    /// 外部收藏同步（BGM / VNDB）报告为玩过时自动标记
+    pub on_external_finished: bool,
+
+    /// 累计游玩时长超过 VNDB 平均时长的倍数时自动标记，为空表示不启用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playtime_factor: Option<f64>,
+
+    /// 规则命中后设置的游戏状态
+    pub target_status: i32,
+}
+
+impl Default for AutoClearRules {
+    fn default() -> Self {
+        Self {
+            on_external_finished: false,
+            playtime_factor: None,
+            target_status: DEFAULT_TARGET_STATUS,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! 存档备份压缩设置 JSON 结构体
+//!
+//! 同一结构存储在 user.backup_compression（全局设置）中，也可在创建单个备份时传入以覆盖全局设置。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 压缩等级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionLevel {
+    /// 速度优先
+    Fast,
+    /// 速度与压缩率折中
+    #[default]
+    Normal,
+    /// 压缩率优先，适合体积很大的存档
+    Max,
+}
+
+/// 压缩算法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionCodec {
+    /// Zstandard，压缩与解压都较快
+    #[default]
+    Zstd,
+    /// LZMA2，7-Zip 的默认算法，压缩率更高但更慢
+    Lzma2,
+}
+
+/// 存档备份压缩设置，字段为 None 时沿用上一级设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct BackupCompression {
+    pub level: Option<CompressionLevel>,
+    pub codec: Option<CompressionCodec>,
+    /// 是否使用固实压缩（所有文件压缩为一个数据块），未设置时为是
+    pub solid: Option<bool>,
+}
+
+impl BackupCompression {
+    /// 用 `fallback` 补全未设置的字段
+    pub fn or(self, fallback: &BackupCompression) -> Self {
+        Self {
+            level: self.level.or(fallback.level),
+            codec: self.codec.or(fallback.codec),
+            solid: self.solid.or(fallback.solid),
+        }
+    }
+
+    /// 补全所有字段，未设置的字段取默认值
+    pub fn resolved(&self) -> Self {
+        Self {
+            level: Some(self.level.unwrap_or_default()),
+            codec: Some(self.codec.unwrap_or_default()),
+            solid: Some(self.solid.unwrap_or(true)),
+        }
+    }
+}
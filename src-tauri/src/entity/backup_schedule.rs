@@ -0,0 +1,30 @@
+//! 存档自动备份计划 JSON 结构体
+//!
+//! 同一结构存储在 user.backup_schedule（全局设置）和 games.backup_schedule（单个游戏的覆盖）中。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 自动备份计划，字段为 None 时沿用上一级设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct BackupSchedule {
+    /// 会话结束后是否自动备份
+    pub enabled: Option<bool>,
+    /// 两次自动备份的最短间隔（分钟），0 表示每次会话结束都备份
+    pub interval_minutes: Option<u32>,
+    /// 自动备份的保留数量，超出时删除最旧的自动备份，0 表示只受 maxbackups 限制
+    pub max_auto_backups: Option<u32>,
+    /// 会话进行中每累计游玩多少分钟自动备份一次，0 表示不在会话中备份
+    pub play_interval_minutes: Option<u32>,
+}
+
+impl BackupSchedule {
+    /// 所有字段都未设置
+    pub fn is_empty(&self) -> bool {
+        self.enabled.is_none()
+            && self.interval_minutes.is_none()
+            && self.max_auto_backups.is_none()
+            && self.play_interval_minutes.is_none()
+    }
+}
@@ -64,4 +64,27 @@ pub struct CustomData {
     /// 用户个人评价
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_review: Option<String>,
+
+    /// 在 Discord 等状态展示中代替真实名称显示的标题
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_title: Option<String>,
+}
+
+impl CustomData {
+    /// 用户评分的上限
+    pub const MAX_USER_RATING: f64 = 10.0;
+
+    /// 规范化用户评分与评价：评分限制在 0-10 并保留一位小数，0 或无效值视为未评分；空白评价视为未填写
+    pub fn with_normalized_review(mut self) -> Self {
+        self.user_rating = self
+            .user_rating
+            .filter(|rating| rating.is_finite())
+            .map(|rating| (rating.clamp(0.0, Self::MAX_USER_RATING) * 10.0).round() / 10.0)
+            .filter(|&rating| rating > 0.0);
+        self.user_review = self
+            .user_review
+            .map(|review| review.trim().to_string())
+            .filter(|review| !review.is_empty());
+        self
+    }
 }
@@ -0,0 +1,34 @@
+//! 游戏兼容层 JSON 结构体（Linux）
+//!
+//! 存储在 games.runner 列中，例如
+//! `{"kind": "proton", "path": "~/.steam/root/compatibilitytools.d/GE-Proton9-20", "prefix": "~/Games/foo", "env": {"DXVK_HUD": "fps"}}`。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 兼容层类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunnerKind {
+    /// 通过 wine 运行 Windows 可执行文件
+    #[default]
+    Wine,
+    /// 通过 Proton（`proton run`）运行 Windows 可执行文件
+    Proton,
+    /// 直接运行，不经过兼容层
+    Native,
+}
+
+/// 单个游戏的兼容层设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct GameRunner {
+    pub kind: RunnerKind,
+    /// wine 可执行文件路径或 Proton 安装目录；wine 未设置时使用全局的 linux_launch_command
+    pub path: Option<String>,
+    /// wine 前缀（WINEPREFIX）或 Proton 的 compatdata 目录（STEAM_COMPAT_DATA_PATH）
+    pub prefix: Option<String>,
+    /// 启动时额外设置的环境变量，如 DXVK_HUD、DXVK_ASYNC，优先于自动设置的变量
+    pub env: BTreeMap<String, String>,
+}
@@ -3,6 +3,9 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::process_stats::ProcessStats;
+use super::session_tags::SessionTags;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "game_sessions")]
 pub struct Model {
@@ -14,6 +17,18 @@ pub struct Model {
     pub duration: i32,
     #[sea_orm(column_type = "Text")]
     pub date: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub tags: Option<SessionTags>,
+    /// 手动补录或调整的会话
+    #[serde(default)]
+    pub manual: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub note: Option<String>,
+    /// 会话期间主进程的内存与 CPU 占用，仅由游戏监控记录的会话提供
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub process_stats: Option<ProcessStats>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -5,10 +5,16 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::backup_schedule::BackupSchedule;
 use super::bgm_data::BgmData;
 use super::custom_data::CustomData;
+use super::game_runner::GameRunner;
+use super::input_mapping::InputMapping;
 use super::kun_data::KunData;
+use super::launch_args::LaunchArgs;
+use super::sandbox_config::SandboxConfig;
 use super::vndb_data::VndbData;
+use super::window_placement::WindowPlacement;
 use super::ymgal_data::YmgalData;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
@@ -41,6 +47,38 @@ pub struct Model {
     pub clear: Option<i32>,
     pub le_launch: Option<i32>,
     pub magpie: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub launch_uri: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub launch_process_name: Option<String>,
+    /// 每次启动都附加在临时参数之前的参数，支持 `{gamedir}` 等占位符
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub launch_args: Option<LaunchArgs>,
+    /// Linux 上运行该游戏的兼容层，未设置时沿用全局的 linux_launch_command
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub runner: Option<GameRunner>,
+    /// Linux 上用 firejail / bubblewrap 限制游戏的文件系统与网络访问
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub sandbox: Option<SandboxConfig>,
+    /// 会话期间把手柄输入转换为键盘 / 鼠标操作（仅 Windows）
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub input_mapping: Option<InputMapping>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub screenshot_dir: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub window_placement: Option<WindowPlacement>,
+    /// 覆盖全局自动备份计划的字段，未设置的字段沿用 user.backup_schedule
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub backup_schedule: Option<BackupSchedule>,
 
     // === JSON 元数据列 ===
     #[sea_orm(column_type = "Text", nullable)]
@@ -0,0 +1,18 @@
+//! 局域网 HTTP API 设置 JSON 结构体
+//!
+//! 此文件定义了存储在 user.http_api 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 局域网 HTTP API 设置，默认关闭
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct HttpApiSettings {
+    /// 在局域网内提供游戏库浏览与启动接口
+    pub enabled: Option<bool>,
+    /// 监听端口，未设置时使用 17380
+    pub port: Option<u16>,
+    /// 访问令牌，开启时未设置会自动生成
+    pub token: Option<String>,
+}
@@ -0,0 +1,94 @@
+//! 手柄映射 JSON 结构体（Windows）
+//!
+//! 存储在 games.input_mapping 列中，例如
+//! `{"enabled": true, "buttons": {"a": {"type": "mouse_left"}, "start": {"type": "key", "code": 27}}}`。
+//! `buttons` 为空时使用适合鼠标操作的视觉小说的默认映射。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 手柄按键（XInput）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    LeftShoulder,
+    RightShoulder,
+    LeftTrigger,
+    RightTrigger,
+    Back,
+    Start,
+    LeftThumb,
+    RightThumb,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+}
+
+/// 按键映射到的键盘 / 鼠标操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MappedAction {
+    /// 键盘按键，`code` 为 Windows 虚拟键码（如 0x0D 回车、0x11 Ctrl）
+    Key {
+        code: u16,
+    },
+    MouseLeft,
+    MouseRight,
+    WheelUp,
+    WheelDown,
+}
+
+/// 单个游戏的手柄映射
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct InputMapping {
+    pub enabled: bool,
+    pub buttons: BTreeMap<GamepadButton, MappedAction>,
+    /// 用左摇杆移动鼠标
+    pub left_stick_mouse: bool,
+    /// 摇杆推到底时每秒移动的像素数
+    pub mouse_speed: u32,
+}
+
+impl Default for InputMapping {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            buttons: BTreeMap::new(),
+            left_stick_mouse: true,
+            mouse_speed: 1200,
+        }
+    }
+}
+
+impl InputMapping {
+    /// 生效的按键映射，未配置时使用默认映射：
+    /// A 左键、B 右键、X 空格、Y Ctrl（快进）、LB/RB 滚轮（回看）、Start Esc、Back 回车、十字键方向键
+    pub fn effective_buttons(&self) -> BTreeMap<GamepadButton, MappedAction> {
+        if !self.buttons.is_empty() {
+            return self.buttons.clone();
+        }
+        use GamepadButton::*;
+        use MappedAction::*;
+        BTreeMap::from([
+            (A, MouseLeft),
+            (B, MouseRight),
+            (X, Key { code: 0x20 }),
+            (Y, Key { code: 0x11 }),
+            (LeftShoulder, WheelUp),
+            (RightShoulder, WheelDown),
+            (Start, Key { code: 0x1B }),
+            (Back, Key { code: 0x0D }),
+            (DpadUp, Key { code: 0x26 }),
+            (DpadDown, Key { code: 0x28 }),
+            (DpadLeft, Key { code: 0x25 }),
+            (DpadRight, Key { code: 0x27 }),
+        ])
+    }
+}
@@ -0,0 +1,23 @@
+//! 游戏启动参数 JSON 结构体
+//!
+//! 存储在 games.launch_args 列中，序列化为字符串数组，例如 `["-windowed", "--save={savepath}"]`。
+//! 每一项是一个完整的参数，启动时展开占位符，不再按空格拆分。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 每次启动都会附加的参数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, FromJsonQueryResult)]
+pub struct LaunchArgs(pub Vec<String>);
+
+impl LaunchArgs {
+    /// 去除空参数，结果为空时返回 None
+    pub fn normalized(self) -> Option<Self> {
+        let args: Vec<String> = self
+            .0
+            .into_iter()
+            .filter(|arg| !arg.trim().is_empty())
+            .collect();
+        (!args.is_empty()).then_some(Self(args))
+    }
+}
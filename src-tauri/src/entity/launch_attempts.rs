@@ -0,0 +1,46 @@
+//! 启动记录实体
+//!
+//! 每次调用 `launch_game` 都会写入一条记录；开启输出捕获时，
+//! 进程退出后回写退出码和最后若干行输出，便于排查启动即退出的问题。
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "launch_attempts")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub game_id: i32,
+    pub launched_at: i32,
+    pub success: bool,
+    pub process_id: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub message: Option<String>,
+    pub exit_code: Option<i32>,
+    pub exited_at: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub log_path: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub output_tail: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::games::Entity",
+        from = "Column::GameId",
+        to = "super::games::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Games,
+}
+
+impl Related<super::games::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Games.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
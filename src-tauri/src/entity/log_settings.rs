@@ -0,0 +1,51 @@
+//! 日志设置 JSON 结构体
+//!
+//! 此文件定义了存储在 user.log_settings 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 单个日志文件的默认大小上限（KB），超过后轮转
+pub const DEFAULT_LOG_MAX_FILE_SIZE_KB: u32 = 1000;
+
+/// 默认保留的日志文件数量
+pub const DEFAULT_LOG_MAX_FILES: u32 = 5;
+
+/// 日志设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct LogSettings {
+    /// 日志级别（error / warn / info / debug），未设置时发布版为 info，开发版为 debug
+    pub level: Option<String>,
+    /// 单个日志文件大小上限（KB），未设置时为 1000，范围 100 ~ 102400
+    pub max_file_size_kb: Option<u32>,
+    /// 保留的日志文件数量（含正在写入的文件），未设置时为 5，范围 1 ~ 50
+    pub max_files: Option<u32>,
+}
+
+impl LogSettings {
+    /// 设置中的日志级别，未设置或无效时返回 None
+    pub fn level_filter(&self) -> Option<log::LevelFilter> {
+        match self.level.as_deref()?.trim().to_lowercase().as_str() {
+            "error" => Some(log::LevelFilter::Error),
+            "warn" => Some(log::LevelFilter::Warn),
+            "info" => Some(log::LevelFilter::Info),
+            "debug" => Some(log::LevelFilter::Debug),
+            _ => None,
+        }
+    }
+
+    /// 单个日志文件大小上限（字节）
+    pub fn max_file_size(&self) -> u128 {
+        let kb = self
+            .max_file_size_kb
+            .unwrap_or(DEFAULT_LOG_MAX_FILE_SIZE_KB)
+            .clamp(100, 100 * 1024);
+        u128::from(kb) * 1000
+    }
+
+    /// 保留的日志文件数量
+    pub fn max_files(&self) -> usize {
+        self.max_files.unwrap_or(DEFAULT_LOG_MAX_FILES).clamp(1, 50) as usize
+    }
+}
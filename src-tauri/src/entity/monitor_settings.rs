@@ -0,0 +1,26 @@
+//! 游戏监控设置 JSON 结构体
+//!
+//! 此文件定义了存储在 user.monitor_settings 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 游戏监控设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct MonitorSettings {
+    /// 无键鼠输入超过该秒数视为挂机，挂机期间不累计游戏时间；未设置表示不启用（仅 Windows）
+    pub idle_timeout_secs: Option<u64>,
+    /// Wayland 会话下无法判断焦点窗口时是否照常累计游戏时间；未设置时默认照常累计（仅 Linux）
+    pub wayland_assume_foreground: Option<bool>,
+    /// 查询焦点窗口 PID 的 shell 命令（如基于 swaymsg / hyprctl 的脚本），监控每秒执行一次（仅 Linux）
+    pub foreground_probe_command: Option<String>,
+    /// 截取前台游戏窗口的全局快捷键（如 `Ctrl+Shift+S`）；未设置表示不注册（仅 Windows）
+    pub screenshot_hotkey: Option<String>,
+    /// 停止最近启动的游戏的全局快捷键；未设置表示不注册
+    pub stop_game_hotkey: Option<String>,
+    /// 暂停 / 恢复最近启动的游戏计时的全局快捷键；未设置表示不注册
+    pub pause_timing_hotkey: Option<String>,
+    /// 是否记住游戏窗口的位置和大小，下次启动时在窗口出现后移回原处；未设置时默认关闭（仅 Windows）
+    pub remember_window_placement: Option<bool>,
+}
@@ -0,0 +1,20 @@
+//! 系统通知设置 JSON 结构体
+//!
+//! 此文件定义了存储在 user.notification_settings 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 各类系统通知的开关，未设置的类别默认开启
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// 存档备份完成
+    pub backup: Option<bool>,
+    /// 游戏累计时长达到里程碑
+    pub session_milestones: Option<bool>,
+    /// 有可用的应用更新
+    pub update_available: Option<bool>,
+    /// 后台任务出错（如游戏监控失败）
+    pub errors: Option<bool>,
+}
@@ -8,7 +8,11 @@ pub use super::game_collection_link::Entity as GameCollectionLink;
 pub use super::game_sessions::Entity as GameSessions;
 pub use super::game_statistics::Entity as GameStatistics;
 pub use super::games::Entity as Games;
+pub use super::launch_attempts::Entity as LaunchAttempts;
 pub use super::savedata::Entity as Savedata;
+pub use super::screenshots::Entity as Screenshots;
+pub use super::session_checkpoints::Entity as SessionCheckpoints;
+pub use super::usage_stats::Entity as UsageStats;
 pub use super::user::Entity as User;
 
 // === JSON 数据结构（嵌入 games 表）===
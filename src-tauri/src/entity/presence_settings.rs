@@ -0,0 +1,20 @@
+//! Discord Rich Presence 设置 JSON 结构体
+//!
+//! 此文件定义了存储在 user.presence_settings 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// Discord 状态展示设置，默认关闭
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct PresenceSettings {
+    /// 游戏运行时在 Discord 展示正在游玩的游戏
+    pub enabled: Option<bool>,
+    /// 是否展示封面（仅支持网络图片），默认展示
+    pub show_cover: Option<bool>,
+    /// 成人内容游戏不展示真实名称和封面（设置了 `custom_data.presence_title` 的除外），默认开启
+    pub hide_nsfw: Option<bool>,
+    /// Discord 应用的 Client ID，未设置时使用构建时的 `DISCORD_CLIENT_ID`
+    pub client_id: Option<String>,
+}
@@ -0,0 +1,20 @@
+//! 会话进程资源占用 JSON 结构体
+//!
+//! 存储在 game_sessions.process_stats 列中，例如
+//! `{"memory_min_bytes": 104857600, "memory_avg_bytes": 524288000, "memory_peak_bytes": 734003200, "cpu_min_percent": 2, "cpu_avg_percent": 11, "cpu_peak_percent": 37}`。
+//! CPU 占用按全部逻辑核心计算（100 表示占满所有核心）。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 会话期间主进程的内存（常驻内存 / 工作集）与 CPU 占用统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct ProcessStats {
+    pub memory_min_bytes: u64,
+    pub memory_avg_bytes: u64,
+    pub memory_peak_bytes: u64,
+    pub cpu_min_percent: u32,
+    pub cpu_avg_percent: u32,
+    pub cpu_peak_percent: u32,
+}
@@ -0,0 +1,32 @@
+//! 游戏沙盒 JSON 结构体（Linux）
+//!
+//! 存储在 games.sandbox 列中，例如
+//! `{"enabled": true, "tool": "bubblewrap", "allow_network": false, "writable_paths": ["~/Documents/MyGame"]}`。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 沙盒工具
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxTool {
+    #[default]
+    Firejail,
+    Bubblewrap,
+}
+
+/// 单个游戏的沙盒设置
+///
+/// 沙盒内主目录只保留游戏目录、存档目录、兼容层前缀和 `writable_paths`，其余内容不可见。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct SandboxConfig {
+    pub enabled: bool,
+    pub tool: SandboxTool,
+    /// 是否允许访问网络
+    pub allow_network: bool,
+    /// 额外允许读写的目录，支持 `~` 开头
+    pub writable_paths: Vec<String>,
+    /// firejail 的自定义 profile 文件，设置后替代内置的目录规则
+    pub firejail_profile: Option<String>,
+}
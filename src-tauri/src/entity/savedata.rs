@@ -13,6 +13,13 @@ pub struct Model {
     pub file: String,
     pub backup_time: i32,
     pub file_size: i32,
+    /// 是否为游戏结束时自动创建的检查点
+    #[serde(default)]
+    pub auto: bool,
+    /// 备份压缩包的 SHA-256，恢复前据此校验；旧记录可通过 `rehash_existing_backups` 补齐
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub sha256: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,32 @@
+//! 存档备份配额 JSON 结构体
+//!
+//! 此文件定义了存储在 user.savedata_quota 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 存档备份根目录的软配额
+///
+/// 超出配额不会阻止新的备份，只会发出警告事件，并按设置清理最旧的自动检查点。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct SavedataQuota {
+    /// 配额上限（字节）
+    pub limit_bytes: u64,
+
+    /// 用量达到上限的该比例时发出警告
+    pub warn_ratio: f64,
+
+    /// 超出配额时是否自动删除最旧的自动检查点（跨所有游戏）
+    pub auto_prune: bool,
+}
+
+impl Default for SavedataQuota {
+    fn default() -> Self {
+        Self {
+            limit_bytes: 50 * 1024 * 1024 * 1024,
+            warn_ratio: 0.9,
+            auto_prune: false,
+        }
+    }
+}
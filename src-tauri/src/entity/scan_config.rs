@@ -0,0 +1,20 @@
+//! 游戏库扫描规则 JSON 结构体
+//!
+//! 此文件定义了存储在 user.scan_config 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 批量导入时扫描游戏目录的规则，未设置的字段使用内置默认值
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct ScanConfig {
+    /// 除 exe / bat / cmd 外也视为启动程序的扩展名（不含点，如 `lnk`、`sh`）
+    pub extra_extensions: Option<Vec<String>>,
+    /// 额外忽略的目录名或文件名，不区分大小写，支持 `*` / `?` 通配符（如 `*_backup`、`config*.exe`）
+    pub ignore_patterns: Option<Vec<String>>,
+    /// 默认扫描深度，调用时传入的深度优先
+    pub max_depth: Option<u32>,
+    /// 是否跟随符号链接 / 目录联接，默认不跟随
+    pub follow_symlinks: Option<bool>,
+}
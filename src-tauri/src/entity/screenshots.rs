@@ -0,0 +1,43 @@
+//! 游戏截图实体
+//!
+//! `path` 为图片文件的绝对路径，`captured_at` 为截取时间（Unix 时间戳，秒）。
+//! 从游戏自带截图目录复制来的图片记录原文件路径 `source_path`，并按截取时间关联 `session_id`。
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "screenshots")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub game_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub path: String,
+    pub captured_at: i32,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub source_path: Option<String>,
+    pub session_id: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::games::Entity",
+        from = "Column::GameId",
+        to = "super::games::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Games,
+}
+
+impl Related<super::games::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Games.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,38 @@
+//! 进行中会话的检查点实体
+//!
+//! 监控循环每分钟写入一次，会话正常结束时删除；
+//! 启动时残留的记录说明上次运行中途崩溃，会被补记为游戏会话。
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "session_checkpoints")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub game_id: i32,
+    pub start_time: i32,
+    pub last_heartbeat: i32,
+    /// 已累计的前台时间（秒）
+    pub total_seconds: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::games::Entity",
+        from = "Column::GameId",
+        to = "super::games::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Games,
+}
+
+impl Related<super::games::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Games.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,24 @@
+//! 会话标签 JSON 结构体
+//!
+//! 存储在 game_sessions.tags 列中，序列化为字符串数组，例如 `["route: Ayane", "replay"]`。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 会话的游玩上下文标签
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, FromJsonQueryResult)]
+pub struct SessionTags(pub Vec<String>);
+
+impl SessionTags {
+    /// 去除首尾空白、空标签和重复标签（保留首次出现的顺序），结果为空时返回 None
+    pub fn normalized(tags: Vec<String>) -> Option<Self> {
+        let mut result: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !result.iter().any(|existing| existing == tag) {
+                result.push(tag.to_string());
+            }
+        }
+        (!result.is_empty()).then_some(Self(result))
+    }
+}
@@ -0,0 +1,72 @@
+//! 首次启动向导进度 JSON 结构体
+//!
+//! 此文件定义了存储在 user.setup_progress 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 首次启动向导的步骤，按向导中的顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    /// 选择数据目录（便携模式 / 安装模式）
+    DataDir,
+    /// 选择游戏库目录并开始扫描
+    Library,
+    /// 设置 Locale Emulator / Magpie 路径
+    Tools,
+    /// 验证 BGM 令牌
+    BgmToken,
+}
+
+impl SetupStep {
+    pub const ALL: [SetupStep; 4] = [
+        SetupStep::DataDir,
+        SetupStep::Library,
+        SetupStep::Tools,
+        SetupStep::BgmToken,
+    ];
+}
+
+/// 首次启动向导进度
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct SetupProgress {
+    /// 已完成的步骤
+    pub completed: Vec<SetupStep>,
+    /// 用户跳过的步骤
+    pub skipped: Vec<SetupStep>,
+    /// 向导中选择的游戏库目录
+    pub library_folders: Vec<String>,
+    /// 向导结束的时间（Unix 时间戳，秒），未结束时下次启动继续向导
+    pub finished_at: Option<i64>,
+}
+
+impl SetupProgress {
+    /// 步骤是否已完成或已跳过
+    pub fn is_done(&self, step: SetupStep) -> bool {
+        self.completed.contains(&step) || self.skipped.contains(&step)
+    }
+
+    /// 下一个待完成的步骤，向导已结束时为 None
+    pub fn next_step(&self) -> Option<SetupStep> {
+        if self.finished_at.is_some() {
+            return None;
+        }
+        SetupStep::ALL.into_iter().find(|step| !self.is_done(*step))
+    }
+
+    /// 记录步骤结果；重做已跳过的步骤会覆盖之前的结果，所有步骤结束时写入结束时间
+    pub fn mark(&mut self, step: SetupStep, skipped: bool, now: i64) {
+        self.completed.retain(|done| *done != step);
+        self.skipped.retain(|done| *done != step);
+        if skipped {
+            self.skipped.push(step);
+        } else {
+            self.completed.push(step);
+        }
+        if self.finished_at.is_none() && SetupStep::ALL.iter().all(|step| self.is_done(*step)) {
+            self.finished_at = Some(now);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! 系统托盘设置 JSON 结构体
+//!
+//! 此文件定义了存储在 user.tray_settings 列中的 JSON 数据结构。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 托盘菜单中默认显示的最近游玩游戏数量
+pub const DEFAULT_TRAY_RECENT_GAMES: u32 = 5;
+
+/// 系统托盘设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct TraySettings {
+    /// 启动时不显示主窗口，只保留托盘图标
+    pub start_minimized: Option<bool>,
+    /// 托盘菜单中显示的最近游玩游戏数量，未设置时为 5，为 0 时不显示
+    pub recent_games: Option<u32>,
+    /// 启动游戏后隐藏主窗口，会话结束时恢复
+    pub hide_on_launch: Option<bool>,
+}
+
+impl TraySettings {
+    pub fn start_minimized(&self) -> bool {
+        self.start_minimized.unwrap_or(false)
+    }
+
+    pub fn hide_on_launch(&self) -> bool {
+        self.hide_on_launch.unwrap_or(false)
+    }
+
+    pub fn recent_games(&self) -> usize {
+        self.recent_games.unwrap_or(DEFAULT_TRAY_RECENT_GAMES) as usize
+    }
+}
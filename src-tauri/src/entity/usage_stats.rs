@@ -0,0 +1,24 @@
+//! 本地功能使用统计实体
+//!
+//! 用户开启统计后按功能与本地日期累计使用次数，数据只保存在本地数据库，不会上传。
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "usage_stats")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub feature: String,
+    /// 本地日期 YYYY-MM-DD
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub date: String,
+    pub count: i32,
+    /// 最后一次使用的时间（Unix 时间戳，秒）
+    pub last_used: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,17 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
 
+use super::auto_clear_rules::AutoClearRules;
+use super::backup_compression::BackupCompression;
+use super::backup_schedule::BackupSchedule;
+use super::http_api_settings::HttpApiSettings;
+use super::log_settings::LogSettings;
+use super::monitor_settings::MonitorSettings;
+use super::notification_settings::NotificationSettings;
+use super::presence_settings::PresenceSettings;
+use super::savedata_quota::SavedataQuota;
+use super::scan_config::ScanConfig;
+use super::setup_progress::SetupProgress;
+use super::tray_settings::TraySettings;
 use sea_orm::FromJsonQueryResult;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -17,7 +29,7 @@ pub struct BgmAuth {
     pub nickname: Option<String>,
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "user")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -34,6 +46,58 @@ pub struct Model {
     pub le_path: Option<String>,
     #[sea_orm(column_type = "Text", nullable)]
     pub magpie_path: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub auto_clear_rules: Option<AutoClearRules>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub savedata_quota: Option<SavedataQuota>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub monitor_settings: Option<MonitorSettings>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub notification_settings: Option<NotificationSettings>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub presence_settings: Option<PresenceSettings>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub backup_schedule: Option<BackupSchedule>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub scan_config: Option<ScanConfig>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub http_api: Option<HttpApiSettings>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub tray_settings: Option<TraySettings>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub log_settings: Option<LogSettings>,
+    /// 是否在本地统计功能使用次数，未设置时不统计
+    #[serde(default)]
+    pub usage_stats_enabled: Option<bool>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub setup_progress: Option<SetupProgress>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub backup_compression: Option<BackupCompression>,
+    /// 删除文件、存档备份与自定义封面时是否移到回收站，未设置时为是
+    #[serde(default)]
+    pub use_trash: Option<bool>,
+    #[serde(default)]
+    pub max_db_backups: Option<i32>,
+    #[serde(default)]
+    pub max_backup_age_days: Option<i32>,
+    /// 文件被占用时的最大重试次数，未设置时为 5
+    #[serde(default)]
+    pub file_lock_retries: Option<i32>,
+    /// 文件被占用时首次重试前的等待时间（毫秒），之后每次翻倍，未设置时为 100
+    #[serde(default)]
+    pub file_lock_retry_delay_ms: Option<i32>,
 }
 
 impl Model {
@@ -45,6 +109,15 @@ impl Model {
         self.db_backup_path.as_deref()
     }
 
+    /// 数据库备份的保留策略（数量上限, 最长保留天数），未设置或不大于 0 的项不限制
+    pub fn db_backup_retention(&self) -> (Option<u32>, Option<u32>) {
+        let positive = |value: Option<i32>| value.and_then(|value| u32::try_from(value).ok());
+        (
+            positive(self.max_db_backups).filter(|max| *max > 0),
+            positive(self.max_backup_age_days).filter(|days| *days > 0),
+        )
+    }
+
     #[cfg(target_os = "windows")]
     pub fn le_path_value(&self) -> Option<&str> {
         self.le_path.as_deref()
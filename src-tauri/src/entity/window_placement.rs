@@ -0,0 +1,20 @@
+//! 游戏窗口位置 JSON 结构体
+//!
+//! 存储在 games.window_placement 列中，坐标为屏幕物理像素。
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 会话期间最后一次记录的游戏窗口位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct WindowPlacement {
+    /// 还原（非最大化）状态下窗口左上角的屏幕坐标
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// 记录时窗口是否最大化
+    pub maximized: bool,
+    /// 记录时窗口所在显示器的 DPI，用于缩放比例变化后按比例调整窗口大小
+    pub dpi: u32,
+}
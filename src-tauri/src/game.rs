@@ -1,4 +1,14 @@
+pub mod auto_clear;
 pub mod cover;
+pub mod cross_ids;
+pub mod desktop_shortcut;
 pub mod launch;
 pub mod monitor;
+pub mod presence;
+pub mod save_path;
 pub mod scan;
+pub mod screenshot;
+pub mod session_hotkeys;
+pub mod steam;
+pub mod uninstall;
+pub mod widget;
@@ -0,0 +1,185 @@
+//! 自动标记游戏状态的规则引擎
+//!
+//! 规则保存在用户设置的 `auto_clear_rules` 中，支持两种触发方式：
+//! - 外部收藏同步（BGM / VNDB）报告为玩过
+//! - 累计游玩时长首次超过 VNDB 平均时长 × 倍数
+//!
+//! 命中后更新游戏状态并发出 `game-auto-cleared` 事件，由前端提示用户。
+
+use crate::database::db::DbState;
+use crate::database::dto::UpdateGameData;
+use crate::database::repository::{
+    games_repository::GamesRepository, settings_repository::SettingsRepository,
+};
+use crate::entity::auto_clear_rules::AutoClearRules;
+use crate::utils::metrics::CommandTimer;
+use log::{info, warn};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+/// 自动标记事件名
+pub const AUTO_CLEAR_EVENT: &str = "game-auto-cleared";
+
+/// 游戏状态：玩过 / PLAYED
+const PLAYED_STATUS: i32 = 2;
+
+/// 规则命中原因
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoClearReason {
+    ExternalFinished,
+    PlaytimeThreshold,
+}
+
+/// `game-auto-cleared` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoClearEvent {
+    pub game_id: i32,
+    pub reason: AutoClearReason,
+    pub previous_status: Option<i32>,
+    pub new_status: i32,
+    /// 命中时的累计游玩时长（分钟），仅时长规则提供
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_minutes: Option<i32>,
+    /// VNDB 平均时长（小时），仅时长规则提供
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_hours: Option<f64>,
+}
+
+/// 读取当前规则，未配置时返回 None
+async fn load_rules(db: &DatabaseConnection) -> Result<Option<AutoClearRules>, String> {
+    SettingsRepository::get_all_settings(db)
+        .await
+        .map(|settings| settings.auto_clear_rules)
+        .map_err(|e| format!("获取自动标记规则失败: {}", e))
+}
+
+/// 计算时长阈值（分钟），规则未启用或缺少 VNDB 平均时长时返回 None
+fn playtime_threshold_minutes(factor: Option<f64>, average_hours: Option<f64>) -> Option<f64> {
+    let factor = factor.filter(|f| f.is_finite() && *f > 0.0)?;
+    let average_hours = average_hours.filter(|h| h.is_finite() && *h > 0.0)?;
+    Some(average_hours * 60.0 * factor)
+}
+
+/// 更新游戏状态并发出事件，已处于目标状态时不做任何事
+async fn mark_game<R: Runtime>(
+    app: &AppHandle<R>,
+    db: &DatabaseConnection,
+    mut event: AutoClearEvent,
+) -> Result<Option<AutoClearEvent>, String> {
+    let game = GamesRepository::find_by_id(db, event.game_id)
+        .await
+        .map_err(|e| format!("获取游戏数据失败: {}", e))?
+        .ok_or_else(|| format!("游戏不存在: {}", event.game_id))?;
+    if game.clear == Some(event.new_status) {
+        return Ok(None);
+    }
+    event.previous_status = game.clear;
+
+    GamesRepository::update(
+        db,
+        event.game_id,
+        UpdateGameData {
+            clear: Some(Some(event.new_status)),
+            ..Default::default()
+        },
+    )
+    .await
+    .map_err(|e| format!("更新游戏状态失败: {}", e))?;
+
+    info!(
+        "自动标记游戏状态: ID={}, {:?} -> {}, 原因={:?}",
+        event.game_id, event.previous_status, event.new_status, event.reason
+    );
+    if let Err(e) = app.emit(AUTO_CLEAR_EVENT, &event) {
+        warn!("无法发送 {} 事件: {}", AUTO_CLEAR_EVENT, e);
+    }
+    Ok(Some(event))
+}
+
+/// 游玩时长变化后检查时长规则
+///
+/// 只在累计时长从阈值以下跨越到阈值以上时触发，用户之后手动改回状态不会被反复覆盖。
+pub async fn check_playtime_rule<R: Runtime>(
+    app: &AppHandle<R>,
+    db: &DatabaseConnection,
+    game_id: i32,
+    previous_minutes: i32,
+    current_minutes: i32,
+) -> Result<Option<AutoClearEvent>, String> {
+    let Some(rules) = load_rules(db).await? else {
+        return Ok(None);
+    };
+    if rules.playtime_factor.is_none() || current_minutes <= previous_minutes {
+        return Ok(None);
+    }
+
+    let game = GamesRepository::find_by_id(db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏数据失败: {}", e))?
+        .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+    let average_hours = game.vndb_data.as_ref().and_then(|data| data.average_hours);
+    let Some(threshold) = playtime_threshold_minutes(rules.playtime_factor, average_hours) else {
+        return Ok(None);
+    };
+    if f64::from(previous_minutes) >= threshold || f64::from(current_minutes) < threshold {
+        return Ok(None);
+    }
+
+    mark_game(
+        app,
+        db,
+        AutoClearEvent {
+            game_id,
+            reason: AutoClearReason::PlaytimeThreshold,
+            previous_status: game.clear,
+            new_status: rules.target_status,
+            total_minutes: Some(current_minutes),
+            average_hours,
+        },
+    )
+    .await
+}
+
+/// 外部收藏同步回报游戏状态
+///
+/// 前端在同步 BGM / VNDB 收藏后调用；启用外部同步规则且外部状态为玩过时自动标记。
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+/// * `external_status` - 外部收藏映射到本地的游戏状态
+///
+/// # Returns
+/// * `Result<Option<AutoClearEvent>, String>` - 命中规则时返回事件内容
+#[tauri::command]
+pub async fn report_external_play_status(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    game_id: i32,
+    external_status: i32,
+) -> Result<Option<AutoClearEvent>, String> {
+    let _timer = CommandTimer::start("report_external_play_status");
+    let db = db.get();
+    let Some(rules) = load_rules(&db).await? else {
+        return Ok(None);
+    };
+    if !rules.on_external_finished || external_status != PLAYED_STATUS {
+        return Ok(None);
+    }
+
+    mark_game(
+        &app,
+        &db,
+        AutoClearEvent {
+            game_id,
+            reason: AutoClearReason::ExternalFinished,
+            previous_status: None,
+            new_status: rules.target_status,
+            total_minutes: None,
+            average_hours: None,
+        },
+    )
+    .await
+}
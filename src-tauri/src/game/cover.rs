@@ -1,6 +1,32 @@
+pub mod cache;
 pub mod cloud;
 pub mod custom;
+pub mod icon;
+pub mod repair;
+pub mod thumbnails;
 
 pub use cloud::{
     DownloadState, delete_cloud_cache, delete_game_cover_dir, register_game_cover_protocol,
 };
+
+use crate::entity::games;
+use std::path::PathBuf;
+
+/// 游戏当前使用的本地封面文件：自定义封面优先，其次是已缓存的云端封面，都没有时为 None
+pub async fn resolve_local_cover(game: &games::Model) -> Result<Option<PathBuf>, String> {
+    let Ok(game_id) = u32::try_from(game.id) else {
+        return Ok(None);
+    };
+    let cover_dir = cloud::get_game_cover_dir(game_id)?;
+    let custom_cover = game
+        .custom_data
+        .as_ref()
+        .and_then(|data| data.image.as_deref())
+        .filter(|image| !image.is_empty())
+        .map(|image| cover_dir.join(format!("cover_{}_{}", game_id, image)))
+        .filter(|path| path.is_file());
+    match custom_cover {
+        Some(path) => Ok(Some(path)),
+        None => Ok(cloud::get_cached_cloud_cover(&cover_dir, game_id).await),
+    }
+}
@@ -0,0 +1,352 @@
+//! 云端封面离线缓存
+//!
+//! `reina-cover` 协议在封面首次显示时才下载云端封面。`cache_cover` 提前把封面下载到同一个缓存位置，
+//! 之后即可离线浏览，也不会每次都请求 BGM / VNDB 的图床；内容相同的封面（同一作品的不同版本、
+//! 重复导入）用硬链接共享同一份文件。`purge_unused_covers` 清理已删除游戏留下的封面目录和
+//! 下载中断残留的临时文件。
+
+use super::cloud::{
+    CoverDownloadError, DEFAULT_CLOUD_COVER_FILE_NAME, DownloadState, fetch_and_cache_cover,
+    get_cached_cloud_cover, get_game_cover_dir,
+};
+use crate::backup::integrity::sha256_file;
+use crate::database::db::DbState;
+use crate::entity::{games, prelude::Games};
+use crate::utils::metrics::CommandTimer;
+use reina_path::get_base_data_dir;
+use sea_orm::{EntityTrait, QuerySelect};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::State;
+use walkdir::WalkDir;
+
+/// 超过该时间仍未完成的 `.part` 临时文件视为下载中断的残留
+const STALE_PART_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// `cache_cover` 的结果
+#[derive(Debug, Serialize)]
+pub struct CachedCover {
+    pub game_id: u32,
+    /// 缓存文件的本地路径
+    pub path: String,
+    pub size: u64,
+    /// 本次是否实际下载（已有缓存时为 false）
+    pub downloaded: bool,
+    /// 是否与其他游戏的封面内容相同，已改为共享同一份文件
+    pub deduplicated: bool,
+}
+
+/// `purge_unused_covers` 的结果
+#[derive(Debug, Default, Serialize)]
+pub struct PurgeCoversResult {
+    /// 删除的游戏封面目录（游戏已不存在）
+    pub removed_dirs: usize,
+    /// 删除的下载残留临时文件
+    pub removed_temp_files: usize,
+    /// 释放的空间（字节），共享的硬链接文件会被重复计算
+    pub freed_bytes: u64,
+}
+
+/// 从 `game_{id}` 目录名解析游戏 ID
+fn parse_cover_dir_game_id(dir_name: &str) -> Option<i32> {
+    dir_name.strip_prefix("game_")?.parse().ok()
+}
+
+/// 其他游戏的云端封面缓存文件
+fn other_cloud_covers(covers_root: &Path, exclude: &Path) -> Vec<PathBuf> {
+    WalkDir::new(covers_root)
+        .min_depth(2)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            name.starts_with(DEFAULT_CLOUD_COVER_FILE_NAME) && !name.contains(".part.")
+        })
+        .map(|entry| entry.into_path())
+        .filter(|path| path != exclude)
+        .collect()
+}
+
+/// 已有内容相同的云端封面时，把 `path` 换成指向它的硬链接，返回是否替换
+///
+/// 先比较文件大小，大小相同再比较 SHA-256。硬链接失败（如不同分区）时保留原文件。
+fn dedupe_cover(path: &Path) -> Result<bool, String> {
+    let Some(covers_root) = path.parent().and_then(Path::parent) else {
+        return Ok(false);
+    };
+    let size = fs::metadata(path)
+        .map_err(|e| format!("读取封面缓存失败: {}", e))?
+        .len();
+    let mut hash = None;
+
+    for candidate in other_cloud_covers(covers_root, path) {
+        if fs::metadata(&candidate).map(|meta| meta.len()).ok() != Some(size) {
+            continue;
+        }
+        if same_file(path, &candidate) {
+            return Ok(true);
+        }
+        if hash.is_none() {
+            hash = Some(sha256_file(path).map_err(|e| format!("计算封面哈希失败: {}", e))?);
+        }
+        if sha256_file(&candidate).ok() != hash {
+            continue;
+        }
+
+        let link_path = path.with_extension("link.part.0");
+        let _ = fs::remove_file(&link_path);
+        if let Err(e) = fs::hard_link(&candidate, &link_path) {
+            log::debug!("无法为重复封面创建硬链接 {}: {}", path.display(), e);
+            return Ok(false);
+        }
+        if let Err(e) = fs::rename(&link_path, path) {
+            let _ = fs::remove_file(&link_path);
+            log::debug!("替换重复封面失败 {}: {}", path.display(), e);
+            return Ok(false);
+        }
+        log::debug!(
+            "封面与 {} 内容相同，已共享同一份文件: {}",
+            candidate.display(),
+            path.display()
+        );
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// 两个路径是否已指向同一个文件
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn same_file(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
+/// 目录下所有文件的大小
+fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// 提前下载并缓存游戏的云端封面
+///
+/// 缓存位置与 `reina-cover` 协议相同，协议之后直接读取本地文件；已有缓存时不重新下载。
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+/// * `url` - 云端封面地址
+///
+/// # Returns
+/// * `Result<CachedCover, String>` - 缓存文件信息或错误消息
+#[tauri::command]
+pub async fn cache_cover(
+    db: State<'_, DbState>,
+    state: State<'_, DownloadState>,
+    game_id: u32,
+    url: String,
+) -> Result<CachedCover, String> {
+    let _timer = CommandTimer::start("cache_cover");
+    let db = db.get();
+    let game_cover_dir = get_game_cover_dir(game_id)?;
+    let mut downloaded = false;
+
+    let cache_path = match get_cached_cloud_cover(&game_cover_dir, game_id).await {
+        Some(path) => path,
+        None => {
+            let _permit = state
+                .semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|e| format!("获取封面下载许可失败: {}", e))?;
+            let generation = state.cache_generation(game_id).await;
+            fetch_and_cache_cover(game_id, generation, &url, &game_cover_dir, &db, &state)
+                .await
+                .map_err(|e| match e {
+                    CoverDownloadError::Retryable(e)
+                    | CoverDownloadError::GameDeleted(e)
+                    | CoverDownloadError::Stale(e)
+                    | CoverDownloadError::NonRetryable(e) => format!("缓存封面失败: {}", e),
+                })?;
+            downloaded = true;
+            get_cached_cloud_cover(&game_cover_dir, game_id)
+                .await
+                .ok_or_else(|| "缓存封面失败: 下载完成后找不到缓存文件".to_string())?
+        }
+    };
+    state.cached_ids.write().await.insert(game_id);
+
+    let path_for_dedupe = cache_path.clone();
+    let deduplicated = tokio::task::spawn_blocking(move || dedupe_cover(&path_for_dedupe))
+        .await
+        .map_err(|e| format!("封面去重失败: {}", e))?
+        .unwrap_or_else(|e| {
+            log::warn!("封面去重失败 game_id={}: {}", game_id, e);
+            false
+        });
+    let size = fs::metadata(&cache_path)
+        .map(|meta| meta.len())
+        .unwrap_or(0);
+
+    log::debug!(
+        "封面已缓存 game_id={} downloaded={} deduplicated={} path={}",
+        game_id,
+        downloaded,
+        deduplicated,
+        cache_path.display()
+    );
+    Ok(CachedCover {
+        game_id,
+        path: cache_path.to_string_lossy().to_string(),
+        size,
+        downloaded,
+        deduplicated,
+    })
+}
+
+/// 清理已删除游戏的封面目录（含自定义封面）与下载中断残留的临时文件
+///
+/// # Returns
+/// * `Result<PurgeCoversResult, String>` - 清理数量与释放的空间
+#[tauri::command]
+pub async fn purge_unused_covers(
+    db: State<'_, DbState>,
+    state: State<'_, DownloadState>,
+) -> Result<PurgeCoversResult, String> {
+    let _timer = CommandTimer::start("purge_unused_covers");
+    let db = db.get();
+    let covers_root = get_base_data_dir()?.join(reina_path::COVERS_SUBDIR);
+    if !covers_root.is_dir() {
+        return Ok(PurgeCoversResult::default());
+    }
+    let game_ids: HashSet<i32> = Games::find()
+        .select_only()
+        .column(games::Column::Id)
+        .into_tuple::<i32>()
+        .all(&db)
+        .await
+        .map_err(|e| format!("获取游戏列表失败: {}", e))?
+        .into_iter()
+        .collect();
+
+    let (result, removed_ids) = tokio::task::spawn_blocking(move || {
+        let mut result = PurgeCoversResult::default();
+        let mut removed_ids = Vec::new();
+        let entries = fs::read_dir(&covers_root).map_err(|e| format!("无法读取封面目录: {}", e))?;
+
+        for entry in entries.filter_map(Result::ok) {
+            let dir = entry.path();
+            if !dir.is_dir() {
+                continue;
+            }
+            let Some(game_id) = parse_cover_dir_game_id(&entry.file_name().to_string_lossy())
+            else {
+                continue;
+            };
+
+            if !game_ids.contains(&game_id) {
+                let size = dir_size(&dir);
+                match fs::remove_dir_all(&dir) {
+                    Ok(()) => {
+                        result.removed_dirs += 1;
+                        result.freed_bytes += size;
+                        removed_ids.push(game_id);
+                    }
+                    Err(e) => log::warn!("删除无用封面目录失败 {}: {}", dir.display(), e),
+                }
+                continue;
+            }
+
+            let Ok(files) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for file in files.filter_map(Result::ok) {
+                let path = file.path();
+                let Ok(meta) = file.metadata() else {
+                    continue;
+                };
+                let stale = meta
+                    .modified()
+                    .ok()
+                    .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                    .is_some_and(|age| age >= STALE_PART_FILE_AGE);
+                if meta.is_file()
+                    && file.file_name().to_string_lossy().contains(".part.")
+                    && stale
+                    && fs::remove_file(&path).is_ok()
+                {
+                    result.removed_temp_files += 1;
+                    result.freed_bytes += meta.len();
+                }
+            }
+        }
+        Ok::<_, String>((result, removed_ids))
+    })
+    .await
+    .map_err(|e| format!("清理封面失败: {}", e))??;
+
+    for game_id in removed_ids {
+        if let Ok(game_id) = u32::try_from(game_id) {
+            state.mark_game_deleted(game_id).await;
+        }
+    }
+    log::info!("无用封面清理完成: {:?}", result);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dedupe_cover, parse_cover_dir_game_id};
+    use std::fs;
+
+    #[test]
+    fn parses_game_cover_dir_names() {
+        assert_eq!(parse_cover_dir_game_id("game_42"), Some(42));
+        assert_eq!(parse_cover_dir_game_id("game_"), None);
+        assert_eq!(parse_cover_dir_game_id("backup_42"), None);
+    }
+
+    #[test]
+    fn identical_covers_share_one_file() {
+        let root = std::env::temp_dir().join(format!("reina_cover_dedupe_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let first = root.join("game_1").join("cloud_cover_1.jpg");
+        let second = root.join("game_2").join("cloud_cover_2.jpg");
+        let other = root.join("game_3").join("cloud_cover_3.jpg");
+        for (path, content) in [(&first, "same"), (&second, "same"), (&other, "diff")] {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        assert!(!dedupe_cover(&other).unwrap());
+        assert!(dedupe_cover(&second).unwrap());
+        assert_eq!(fs::read_to_string(&second).unwrap(), "same");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!(
+                fs::metadata(&first).unwrap().ino(),
+                fs::metadata(&second).unwrap().ino()
+            );
+        }
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -10,15 +10,17 @@ use tauri::command;
 use tauri::http::StatusCode;
 use tokio::sync::{RwLock, Semaphore, watch};
 
+use crate::database::db::DbState;
 use crate::entity::prelude::Games;
 use crate::utils::image::{
     content_type_for_extension, content_type_for_file, infer_image_extension, make_image_response,
     make_status_response,
 };
+use crate::utils::metrics::CommandTimer;
 use reina_path::get_base_data_dir;
 
 const DEFAULT_COVER_EXTENSION: &str = "jpg";
-const DEFAULT_CLOUD_COVER_FILE_NAME: &str = "cloud_cover";
+pub(super) const DEFAULT_CLOUD_COVER_FILE_NAME: &str = "cloud_cover";
 const MAX_CONCURRENT_COVER_DOWNLOADS: usize = 100;
 /// 最多重试次数（不含首次），退避延迟为 500ms * 2^attempt
 const COVER_MAX_RETRIES: u32 = 2;
@@ -34,9 +36,9 @@ struct DownloadKey {
 type DownloadingMap = Arc<Mutex<HashMap<DownloadKey, Arc<watch::Sender<bool>>>>>;
 
 pub struct DownloadState {
-    semaphore: Arc<Semaphore>,
+    pub(super) semaphore: Arc<Semaphore>,
     /// 内存中已确认缓存完毕的 game_id 集合，避免每次请求都扫描磁盘
-    cached_ids: Arc<RwLock<HashSet<u32>>>,
+    pub(super) cached_ids: Arc<RwLock<HashSet<u32>>>,
     /// 缓存代数：删缓存时递增，使旧下载任务失去写盘资格
     cache_generations: Arc<RwLock<HashMap<u32, u64>>>,
     /// 删除墓碑：记录已删除游戏，阻止下载任务继续写入封面
@@ -52,7 +54,7 @@ impl DownloadState {
         self.tombstoned_ids.write().await.insert(game_id);
     }
 
-    async fn cache_generation(&self, game_id: u32) -> u64 {
+    pub(super) async fn cache_generation(&self, game_id: u32) -> u64 {
         self.cache_generations
             .read()
             .await
@@ -72,6 +74,23 @@ impl DownloadState {
         self.cache_generation(game_id).await == generation
     }
 
+    /// 数据库被整体替换后调用：旧库的缓存标记与删除墓碑不再对应新库的 game_id，
+    /// 正在进行的下载也失去写盘资格
+    pub async fn reset_for_new_database(&self) {
+        self.cached_ids.write().await.clear();
+        self.tombstoned_ids.write().await.clear();
+        let in_flight: Vec<u32> = self
+            .downloading
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .map(|key| key.game_id)
+            .collect();
+        for game_id in in_flight {
+            self.bump_cache_generation(game_id).await;
+        }
+    }
+
     async fn clear_game_deleted(&self, game_id: u32) {
         self.tombstoned_ids.write().await.remove(&game_id);
     }
@@ -107,9 +126,9 @@ fn cloud_cover_file_stem(game_id: u32) -> String {
     format!("{DEFAULT_CLOUD_COVER_FILE_NAME}_{game_id}")
 }
 
-fn get_game_cover_dir(game_id: u32) -> Result<PathBuf, String> {
+pub(super) fn get_game_cover_dir(game_id: u32) -> Result<PathBuf, String> {
     Ok(get_base_data_dir()?
-        .join("covers")
+        .join(reina_path::COVERS_SUBDIR)
         .join(format!("game_{}", game_id)))
 }
 
@@ -129,7 +148,7 @@ fn build_temp_cache_path(game_cover_dir: &Path, game_id: u32, extension: &str) -
     ))
 }
 
-async fn get_cached_cloud_cover(game_cover_dir: &Path, game_id: u32) -> Option<PathBuf> {
+pub(super) async fn get_cached_cloud_cover(game_cover_dir: &Path, game_id: u32) -> Option<PathBuf> {
     let file_stem = cloud_cover_file_stem(game_id);
 
     // O(1) 快速路径：直接探测最常见的图片扩展名（stat 系统调用，无需遍历目录）
@@ -180,7 +199,7 @@ async fn remove_file_if_exists(path: &Path) {
 }
 
 #[derive(Debug)]
-enum CoverDownloadError {
+pub(super) enum CoverDownloadError {
     Retryable(String),
     GameDeleted(String),
     Stale(String),
@@ -231,6 +250,7 @@ pub async fn delete_cloud_cache(
     game_id: u32,
     state: tauri::State<'_, DownloadState>,
 ) -> Result<(), String> {
+    let _timer = CommandTimer::start("delete_cloud_cache");
     let game_cover_dir = get_game_cover_dir(game_id)?;
     let expected_prefix = format!("{}.", cloud_cover_file_stem(game_id));
 
@@ -348,7 +368,7 @@ async fn try_download_once(
 
 /// 带指数退避重试的封面下载（总尝试次数 = 1 + COVER_MAX_RETRIES）
 /// 成功时返回图片字节，并已写入磁盘缓存
-async fn fetch_and_cache_cover(
+pub(super) async fn fetch_and_cache_cover(
     game_id: u32,
     generation: u64,
     url: &str,
@@ -496,10 +516,10 @@ pub fn register_game_cover_protocol<R: tauri::Runtime>(
                 };
 
                 let state = app_handle.state::<DownloadState>();
-                let db = app_handle.state::<DatabaseConnection>();
+                let db = app_handle.state::<DbState>().get();
 
                 if state.is_game_deleted_marked(game_id).await {
-                    match ensure_game_cover_writable(&state, db.inner(), game_id).await {
+                    match ensure_game_cover_writable(&state, &db, game_id).await {
                         Ok(_) => {}
                         Err(CoverDownloadError::GameDeleted(_)) => {
                             responder.respond(make_status_response(StatusCode::NOT_FOUND));
@@ -624,15 +644,9 @@ pub fn register_game_cover_protocol<R: tauri::Runtime>(
                 };
 
                 // 执行下载（含指数退避重试）
-                let fetch_result = fetch_and_cache_cover(
-                    game_id,
-                    generation,
-                    &url,
-                    &game_cover_dir,
-                    db.inner(),
-                    &state,
-                )
-                .await;
+                let fetch_result =
+                    fetch_and_cache_cover(game_id, generation, &url, &game_cover_dir, &db, &state)
+                        .await;
                 match fetch_result {
                     Ok(bytes) => {
                         // 回填内存缓存集合
@@ -664,7 +678,7 @@ pub fn register_game_cover_protocol<R: tauri::Runtime>(
 /// 删除指定游戏的封面目录（包含云端缓存和自定义封面）
 pub async fn delete_game_cover_dir(game_id: i32) -> Result<(), String> {
     let game_cover_dir = reina_path::get_base_data_dir()?
-        .join("covers")
+        .join(reina_path::COVERS_SUBDIR)
         .join(format!("game_{}", game_id));
 
     if !game_cover_dir.exists() {
@@ -1,8 +1,11 @@
+use crate::database::db::DbState;
+use crate::utils::metrics::CommandTimer;
+use crate::utils::trash::{remove_path, trash_enabled};
 use image::{ColorType, ImageFormat};
 use std::fs;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::command;
+use tauri::{State, command};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
 /// 从剪贴板读取图片并写入临时 PNG 文件。
@@ -13,6 +16,7 @@ pub async fn import_clipboard_image_to_temp(
     app: tauri::AppHandle,
     game_id: u32,
 ) -> Result<String, String> {
+    let _timer = CommandTimer::start("import_clipboard_image_to_temp");
     let clipboard_image = app.clipboard().read_image().map_err(|e| {
         let message = e.to_string();
         let lower_message = message.to_lowercase();
@@ -53,9 +57,14 @@ pub async fn import_clipboard_image_to_temp(
     Ok(target_path.to_string_lossy().to_string())
 }
 
-/// 删除指定游戏的所有自定义封面文件，但保留封面目录
+/// 删除指定游戏的所有自定义封面文件，但保留封面目录；按 user.use_trash 设置移到回收站或永久删除
 #[command]
-pub async fn delete_game_covers(game_id: u32, covers_dir: String) -> Result<(), String> {
+pub async fn delete_game_covers(
+    db: State<'_, DbState>,
+    game_id: u32,
+    covers_dir: String,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("delete_game_covers");
     let dir_path = Path::new(&covers_dir);
 
     if !dir_path.exists() {
@@ -76,24 +85,32 @@ pub async fn delete_game_covers(game_id: u32, covers_dir: String) -> Result<(),
     }
 
     let expected_file_prefix = format!("cover_{}_", game_id);
-    let entries = fs::read_dir(dir_path).map_err(|e| format!("无法读取封面目录: {}", e))?;
+    let use_trash = trash_enabled(&db.get()).await;
+    let dir_path = dir_path.to_path_buf();
 
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
-        let path = entry.path();
+    // 文件被占用时会阻塞重试，放到阻塞线程中执行
+    tokio::task::spawn_blocking(move || {
+        let entries = fs::read_dir(&dir_path).map_err(|e| format!("无法读取封面目录: {}", e))?;
 
-        if !path.is_file() {
-            continue;
-        }
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
+            let path = entry.path();
 
-        let file_name = entry.file_name();
-        let file_name_str = file_name.to_string_lossy();
-        if !file_name_str.starts_with(&expected_file_prefix) {
-            continue;
-        }
+            if !path.is_file() {
+                continue;
+            }
 
-        fs::remove_file(&path).map_err(|e| format!("无法删除自定义封面文件: {}", e))?;
-    }
+            let file_name = entry.file_name();
+            let file_name_str = file_name.to_string_lossy();
+            if !file_name_str.starts_with(&expected_file_prefix) {
+                continue;
+            }
+
+            remove_path(&path, use_trash).map_err(|e| format!("无法删除自定义封面文件: {}", e))?;
+        }
 
-    Ok(())
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("无法删除自定义封面文件: {}", e))?
 }
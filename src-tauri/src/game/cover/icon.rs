@@ -0,0 +1,305 @@
+//! 从游戏可执行文件提取图标
+//!
+//! 没有元数据的游戏（同人作品、自制游戏）往往只有一个 .exe，这里读取其中的图标资源，
+//! 取分辨率最高的一张转换为 PNG，作为封面的兜底。提取依赖 Win32 资源 API，仅支持 Windows。
+
+use crate::utils::metrics::CommandTimer;
+use image::ImageFormat;
+use reina_path::get_base_data_dir;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// ICONDIR 头部长度
+const ICON_DIR_LEN: usize = 6;
+/// GRPICONDIRENTRY（资源中的图标组条目）长度
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const GROUP_ICON_ENTRY_LEN: usize = 14;
+/// ICONDIRENTRY（.ico 文件中的条目）长度
+const ICON_FILE_ENTRY_LEN: usize = 16;
+
+/// RT_GROUP_ICON 资源中的一个条目，描述同一图标的某个尺寸
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GroupIconEntry {
+    width: u8,
+    height: u8,
+    color_count: u8,
+    planes: u16,
+    bit_count: u16,
+    /// 对应 RT_ICON 资源的 ID
+    id: u16,
+}
+
+impl GroupIconEntry {
+    /// 实际像素尺寸，0 表示 256
+    fn dimensions(&self) -> (u32, u32) {
+        let size = |value: u8| if value == 0 { 256 } else { value as u32 };
+        (size(self.width), size(self.height))
+    }
+}
+
+/// 解析 RT_GROUP_ICON 资源（GRPICONDIR）
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_group_icon(data: &[u8]) -> Vec<GroupIconEntry> {
+    if data.len() < ICON_DIR_LEN {
+        return Vec::new();
+    }
+    let count = u16::from_le_bytes([data[4], data[5]]) as usize;
+    data[ICON_DIR_LEN..]
+        .chunks_exact(GROUP_ICON_ENTRY_LEN)
+        .take(count)
+        .map(|entry| GroupIconEntry {
+            width: entry[0],
+            height: entry[1],
+            color_count: entry[2],
+            planes: u16::from_le_bytes([entry[4], entry[5]]),
+            bit_count: u16::from_le_bytes([entry[6], entry[7]]),
+            id: u16::from_le_bytes([entry[12], entry[13]]),
+        })
+        .collect()
+}
+
+/// 分辨率最高的条目，尺寸相同时取色深更高的
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn pick_largest(entries: &[GroupIconEntry]) -> Option<GroupIconEntry> {
+    entries.iter().copied().max_by_key(|entry| {
+        let (width, height) = entry.dimensions();
+        (width * height, entry.bit_count)
+    })
+}
+
+/// 把单张 RT_ICON 数据（BMP 或 PNG）包装成只含一张图的 .ico 文件，交给 image 解码
+fn wrap_as_ico(entry: &GroupIconEntry, data: &[u8]) -> Vec<u8> {
+    let offset = (ICON_DIR_LEN + ICON_FILE_ENTRY_LEN) as u32;
+    let mut ico = Vec::with_capacity(offset as usize + data.len());
+    ico.extend_from_slice(&0u16.to_le_bytes());
+    ico.extend_from_slice(&1u16.to_le_bytes());
+    ico.extend_from_slice(&1u16.to_le_bytes());
+    ico.extend_from_slice(&[entry.width, entry.height, entry.color_count, 0]);
+    ico.extend_from_slice(&entry.planes.to_le_bytes());
+    ico.extend_from_slice(&entry.bit_count.to_le_bytes());
+    ico.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    ico.extend_from_slice(&offset.to_le_bytes());
+    ico.extend_from_slice(data);
+    ico
+}
+
+/// 读取可执行文件第一个图标组中分辨率最高的图标，返回条目与原始数据
+#[cfg(target_os = "windows")]
+fn read_largest_icon(exe_path: &Path) -> Result<(GroupIconEntry, Vec<u8>), String> {
+    use windows::Win32::Foundation::{FreeLibrary, HMODULE};
+    use windows::Win32::System::LibraryLoader::{
+        EnumResourceNamesW, FindResourceW, LOAD_LIBRARY_AS_DATAFILE,
+        LOAD_LIBRARY_AS_IMAGE_RESOURCE, LoadLibraryExW, LoadResource, LockResource, SizeofResource,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{RT_GROUP_ICON, RT_ICON};
+    use windows::core::{BOOL, HSTRING, PCWSTR};
+
+    /// 资源名可能是整数 ID，也可能是只在枚举回调期间有效的字符串
+    enum ResourceName {
+        Id(u16),
+        Name(Vec<u16>),
+    }
+
+    unsafe extern "system" fn first_name(
+        _module: HMODULE,
+        _kind: PCWSTR,
+        name: PCWSTR,
+        lparam: isize,
+    ) -> BOOL {
+        // SAFETY: lparam 指向 read_largest_icon 栈上的 Option<ResourceName>，枚举期间一直有效
+        let found = unsafe { &mut *(lparam as *mut Option<ResourceName>) };
+        *found = Some(if (name.0 as usize) >> 16 == 0 {
+            ResourceName::Id(name.0 as usize as u16)
+        } else {
+            // SAFETY: 非整数 ID 时 name 是以 NUL 结尾的字符串
+            let mut value = unsafe { name.as_wide() }.to_vec();
+            value.push(0);
+            ResourceName::Name(value)
+        });
+        // 只需要第一个图标组（资源管理器显示的就是它），返回 FALSE 停止枚举
+        BOOL(0)
+    }
+
+    /// SAFETY: module 必须是有效的模块句柄
+    unsafe fn resource_bytes(module: HMODULE, name: PCWSTR, kind: PCWSTR) -> Option<Vec<u8>> {
+        unsafe {
+            let info = FindResourceW(Some(module), name, kind);
+            if info.is_invalid() {
+                return None;
+            }
+            let handle = LoadResource(Some(module), info).ok()?;
+            let data = LockResource(handle) as *const u8;
+            let size = SizeofResource(Some(module), info) as usize;
+            if data.is_null() || size == 0 {
+                return None;
+            }
+            Some(std::slice::from_raw_parts(data, size).to_vec())
+        }
+    }
+
+    // SAFETY: 以数据文件方式加载，不会执行其中的代码；句柄在函数返回前释放
+    unsafe {
+        let module = LoadLibraryExW(
+            &HSTRING::from(exe_path.as_os_str()),
+            None,
+            LOAD_LIBRARY_AS_DATAFILE | LOAD_LIBRARY_AS_IMAGE_RESOURCE,
+        )
+        .map_err(|e| format!("无法读取可执行文件资源: {}", e))?;
+
+        let result = (|| {
+            let mut group_name: Option<ResourceName> = None;
+            let _ = EnumResourceNamesW(
+                Some(module),
+                RT_GROUP_ICON,
+                Some(first_name),
+                &mut group_name as *mut Option<ResourceName> as isize,
+            );
+            let group_name = group_name.ok_or_else(|| "可执行文件中没有图标".to_string())?;
+            let group_name = match &group_name {
+                ResourceName::Id(id) => PCWSTR(*id as usize as *const u16),
+                ResourceName::Name(name) => PCWSTR(name.as_ptr()),
+            };
+
+            let group = resource_bytes(module, group_name, RT_GROUP_ICON)
+                .ok_or_else(|| "读取图标组失败".to_string())?;
+            let entry =
+                pick_largest(&parse_group_icon(&group)).ok_or_else(|| "图标组为空".to_string())?;
+            let data = resource_bytes(module, PCWSTR(entry.id as usize as *const u16), RT_ICON)
+                .ok_or_else(|| format!("读取图标资源失败: id={}", entry.id))?;
+            Ok((entry, data))
+        })();
+
+        let _ = FreeLibrary(module);
+        result
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_largest_icon(_exe_path: &Path) -> Result<(GroupIconEntry, Vec<u8>), String> {
+    Err("当前平台暂不支持提取可执行文件图标".to_string())
+}
+
+/// 图标缓存路径：按可执行文件路径的哈希命名，同一路径重复提取时复用
+fn icon_cache_path(exe_path: &Path) -> Result<PathBuf, String> {
+    let digest = Sha256::digest(exe_path.to_string_lossy().as_bytes());
+    let hash = format!("{:x}", digest);
+    Ok(get_base_data_dir()?
+        .join(reina_path::COVERS_SUBDIR)
+        .join("exe_icons")
+        .join(format!("icon_{}.png", &hash[..16])))
+}
+
+/// 缓存是否比可执行文件新
+fn is_cache_fresh(cache: &Path, exe_path: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    matches!(
+        (modified(cache), modified(exe_path)),
+        (Some(cache), Some(exe)) if cache >= exe
+    )
+}
+
+fn extract_icon_to_png(exe_path: &Path) -> Result<PathBuf, String> {
+    if !exe_path.is_file() {
+        return Err(format!("可执行文件不存在: {}", exe_path.display()));
+    }
+    let output = icon_cache_path(exe_path)?;
+    if is_cache_fresh(&output, exe_path) {
+        return Ok(output);
+    }
+
+    let (entry, data) = read_largest_icon(exe_path)?;
+    let image = image::load_from_memory_with_format(&wrap_as_ico(&entry, &data), ImageFormat::Ico)
+        .map_err(|e| format!("解码图标失败: {}", e))?;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建图标目录失败: {}", e))?;
+    }
+    let temp_path = output.with_extension("png.part.0");
+    let saved = image
+        .save_with_format(&temp_path, ImageFormat::Png)
+        .map_err(|e| format!("保存图标失败: {}", e))
+        .and_then(|()| fs::rename(&temp_path, &output).map_err(|e| format!("保存图标失败: {}", e)));
+    if let Err(e) = saved {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    log::info!(
+        "已提取可执行文件图标 {}x{} {} -> {}",
+        entry.dimensions().0,
+        entry.dimensions().1,
+        exe_path.display(),
+        output.display()
+    );
+    Ok(output)
+}
+
+/// 提取游戏可执行文件中分辨率最高的图标，保存为 PNG（仅 Windows）
+///
+/// 结果按可执行文件路径缓存在封面目录的 `exe_icons` 中，可执行文件更新后重新提取。
+///
+/// # Arguments
+/// * `game_path` - 游戏可执行文件路径
+///
+/// # Returns
+/// * `Result<String, String>` - PNG 文件路径
+#[tauri::command]
+pub async fn extract_exe_icon(game_path: String) -> Result<String, String> {
+    let _timer = CommandTimer::start("extract_exe_icon");
+    tokio::task::spawn_blocking(move || extract_icon_to_png(Path::new(&game_path)))
+        .await
+        .map_err(|e| format!("提取图标失败: {}", e))?
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GroupIconEntry, parse_group_icon, pick_largest, wrap_as_ico};
+
+    fn group_entry(width: u8, bit_count: u16, id: u16) -> [u8; 14] {
+        let mut entry = [0u8; 14];
+        entry[0] = width;
+        entry[1] = width;
+        entry[4..6].copy_from_slice(&1u16.to_le_bytes());
+        entry[6..8].copy_from_slice(&bit_count.to_le_bytes());
+        entry[12..14].copy_from_slice(&id.to_le_bytes());
+        entry
+    }
+
+    #[test]
+    fn picks_largest_entry_and_treats_zero_as_256() {
+        let mut data = vec![0, 0, 1, 0, 3, 0];
+        data.extend_from_slice(&group_entry(48, 32, 1));
+        data.extend_from_slice(&group_entry(0, 32, 2));
+        data.extend_from_slice(&group_entry(32, 8, 3));
+
+        let entries = parse_group_icon(&data);
+        assert_eq!(entries.len(), 3);
+        let largest = pick_largest(&entries).unwrap();
+        assert_eq!((largest.id, largest.dimensions()), (2, (256, 256)));
+        assert!(parse_group_icon(&[0, 0]).is_empty());
+    }
+
+    #[test]
+    fn wrapped_png_icon_decodes() {
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgba8(16, 16)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let entry = GroupIconEntry {
+            width: 16,
+            height: 16,
+            color_count: 0,
+            planes: 1,
+            bit_count: 32,
+            id: 1,
+        };
+        let image = image::load_from_memory_with_format(
+            &wrap_as_ico(&entry, &png),
+            image::ImageFormat::Ico,
+        )
+        .unwrap();
+        assert_eq!((image.width(), image.height()), (16, 16));
+    }
+}
@@ -0,0 +1,430 @@
+//! 失效封面地址的巡检与修复
+//!
+//! BGM / VNDB 会不定期更换图片 CDN，导致 JSON 元数据中保存的封面地址失效。
+//! `audit_cover_urls` 分批 HEAD 检查已保存的图片地址；`repair_covers` 从数据源 API
+//! 重新获取封面地址，写回对应的 JSON 列并清除旧的云端封面缓存。
+
+use crate::database::db::DbState;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+use tauri_plugin_http::reqwest::header::{AUTHORIZATION, CONTENT_TYPE, RANGE};
+use tauri_plugin_http::reqwest::{Client, StatusCode};
+use tokio::task::JoinSet;
+
+use super::cloud::{DownloadState, delete_cloud_cache};
+use crate::database::dto::UpdateGameData;
+use crate::database::repository::games_repository::{
+    GameType, GamesRepository, SortOption, SortOrder,
+};
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::entity::games;
+use crate::utils::metrics::CommandTimer;
+
+const DEFAULT_AUDIT_BATCH_SIZE: usize = 16;
+const BGM_API_BASE_URL: &str = "https://api.bgm.tv/v0";
+const VNDB_API_BASE_URL: &str = "https://api.vndb.org/kana";
+
+/// 封面地址所在的 JSON 列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverSource {
+    Bgm,
+    Vndb,
+    Ymgal,
+    Kun,
+    Custom,
+}
+
+/// 检查失败的封面地址
+#[derive(Debug, Serialize)]
+pub struct BrokenCoverUrl {
+    pub game_id: i32,
+    pub source: CoverSource,
+    pub url: String,
+    /// HTTP 状态码，请求本身失败时为 None
+    pub status: Option<u16>,
+    pub error: String,
+}
+
+/// 封面巡检结果
+#[derive(Debug, Serialize)]
+pub struct CoverAuditResult {
+    /// 检查的地址数
+    pub checked: usize,
+    pub broken: Vec<BrokenCoverUrl>,
+}
+
+/// 修复成功的封面
+#[derive(Debug, Serialize)]
+pub struct RepairedCover {
+    pub game_id: i32,
+    pub source: CoverSource,
+    pub old_url: Option<String>,
+    pub new_url: String,
+}
+
+/// 修复失败的游戏
+#[derive(Debug, Serialize)]
+pub struct CoverRepairFailure {
+    pub game_id: i32,
+    pub message: String,
+}
+
+/// 封面修复结果
+#[derive(Debug, Serialize)]
+pub struct CoverRepairResult {
+    pub repaired: Vec<RepairedCover>,
+    pub failed: Vec<CoverRepairFailure>,
+}
+
+// ==================== 巡检 ====================
+
+/// 收集游戏各 JSON 列中保存的远程封面地址
+fn collect_cover_urls(game: &games::Model) -> Vec<(CoverSource, String)> {
+    let candidates = [
+        (
+            CoverSource::Bgm,
+            game.bgm_data.as_ref().and_then(|d| d.image.clone()),
+        ),
+        (
+            CoverSource::Vndb,
+            game.vndb_data.as_ref().and_then(|d| d.image.clone()),
+        ),
+        (
+            CoverSource::Ymgal,
+            game.ymgal_data.as_ref().and_then(|d| d.image.clone()),
+        ),
+        (
+            CoverSource::Kun,
+            game.kun_data.as_ref().and_then(|d| d.image.clone()),
+        ),
+        (
+            CoverSource::Custom,
+            game.custom_data.as_ref().and_then(|d| d.image.clone()),
+        ),
+    ];
+
+    candidates
+        .into_iter()
+        .filter_map(|(source, url)| url.map(|url| (source, url)))
+        .filter(|(_, url)| url.starts_with("http://") || url.starts_with("https://"))
+        .collect()
+}
+
+/// 检查单个地址是否可访问；部分 CDN 不支持 HEAD，此时退回只取首字节的 GET
+async fn check_cover_url(client: &Client, url: &str) -> Result<(), (Option<u16>, String)> {
+    let response = client
+        .head(url)
+        .send()
+        .await
+        .map_err(|e| (None, format!("请求失败: {}", e)))?;
+
+    let status = match response.status() {
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::FORBIDDEN | StatusCode::NOT_IMPLEMENTED => {
+            client
+                .get(url)
+                .header(RANGE, "bytes=0-0")
+                .send()
+                .await
+                .map_err(|e| (None, format!("请求失败: {}", e)))?
+                .status()
+        }
+        status => status,
+    };
+
+    // 被限流时无法判断地址是否有效，不计为失效
+    if status.is_success() || status == StatusCode::TOO_MANY_REQUESTS {
+        Ok(())
+    } else {
+        Err((
+            Some(status.as_u16()),
+            format!("HTTP 状态码异常: {}", status),
+        ))
+    }
+}
+
+/// 检查库中所有远程封面地址
+///
+/// # Arguments
+/// * `batch_size` - 每批并发检查的地址数，默认 16
+///
+/// # Returns
+/// * `Result<CoverAuditResult, String>` - 失效地址列表或错误消息
+#[tauri::command]
+pub async fn audit_cover_urls(
+    db: State<'_, DbState>,
+    batch_size: Option<usize>,
+) -> Result<CoverAuditResult, String> {
+    let _timer = CommandTimer::start("audit_cover_urls");
+    let db = db.get();
+    let games = GamesRepository::find_all(
+        &db,
+        GameType::All,
+        None,
+        SortOption::Addtime,
+        SortOrder::Asc,
+        None,
+    )
+    .await
+    .map_err(|e| format!("获取游戏列表失败: {}", e))?;
+
+    let targets: Vec<(i32, CoverSource, String)> = games
+        .iter()
+        .flat_map(|game| {
+            collect_cover_urls(game)
+                .into_iter()
+                .map(|(source, url)| (game.id, source, url))
+        })
+        .collect();
+    let batch_size = batch_size.unwrap_or(DEFAULT_AUDIT_BATCH_SIZE).max(1);
+    let client = crate::utils::http::get_client();
+    let mut broken = Vec::new();
+
+    for batch in targets.chunks(batch_size) {
+        let mut tasks = JoinSet::new();
+        for (game_id, source, url) in batch.iter().cloned() {
+            let client = client.clone();
+            tasks.spawn(async move {
+                let result = check_cover_url(&client, &url).await;
+                (game_id, source, url, result)
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let Ok((game_id, source, url, result)) = joined else {
+                continue;
+            };
+            if let Err((status, error)) = result {
+                broken.push(BrokenCoverUrl {
+                    game_id,
+                    source,
+                    url,
+                    status,
+                    error,
+                });
+            }
+        }
+    }
+
+    broken.sort_by_key(|item| item.game_id);
+    log::info!(
+        "封面地址巡检完成 checked={} broken={}",
+        targets.len(),
+        broken.len()
+    );
+
+    Ok(CoverAuditResult {
+        checked: targets.len(),
+        broken,
+    })
+}
+
+// ==================== 修复 ====================
+
+/// 从 BGM 获取条目的大尺寸封面地址
+async fn fetch_bgm_image(
+    client: &Client,
+    bgm_id: &str,
+    token: Option<&str>,
+) -> Result<Option<String>, String> {
+    let mut request = client.get(format!("{}/subjects/{}", BGM_API_BASE_URL, bgm_id));
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("请求 BGM 失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("BGM 返回异常状态码: {}", response.status()));
+    }
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("读取 BGM 响应失败: {}", e))?;
+    let body: Value =
+        serde_json::from_str(&text).map_err(|e| format!("解析 BGM 响应失败: {}", e))?;
+    Ok(body
+        .pointer("/images/large")
+        .and_then(Value::as_str)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string))
+}
+
+/// 从 VNDB 获取条目的封面地址
+async fn fetch_vndb_image(
+    client: &Client,
+    vndb_id: &str,
+    token: Option<&str>,
+) -> Result<Option<String>, String> {
+    let body = serde_json::json!({
+        "filters": ["id", "=", vndb_id],
+        "fields": "image.url",
+    });
+    let mut request = client
+        .post(format!("{}/vn", VNDB_API_BASE_URL))
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(&body).map_err(|e| format!("序列化请求体失败: {}", e))?);
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Token {}", token));
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("请求 VNDB 失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("VNDB 返回异常状态码: {}", response.status()));
+    }
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("读取 VNDB 响应失败: {}", e))?;
+    let body: Value =
+        serde_json::from_str(&text).map_err(|e| format!("解析 VNDB 响应失败: {}", e))?;
+    Ok(body
+        .pointer("/results/0/image/url")
+        .and_then(Value::as_str)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string))
+}
+
+/// 从数据源重新获取封面地址并写回 JSON 列
+///
+/// 目前支持 BGM 与 VNDB；只有 ymgal / kun / 自定义封面的游戏会记为失败。
+///
+/// # Arguments
+/// * `ids` - 需要修复的游戏 ID 列表
+///
+/// # Returns
+/// * `Result<CoverRepairResult, String>` - 修复结果或错误消息
+#[tauri::command]
+pub async fn repair_covers(
+    db: State<'_, DbState>,
+    download_state: State<'_, DownloadState>,
+    ids: Vec<i32>,
+) -> Result<CoverRepairResult, String> {
+    let _timer = CommandTimer::start("repair_covers");
+    let db = db.get();
+    let settings = SettingsRepository::get_all_settings(&db)
+        .await
+        .map_err(|e| format!("获取用户设置失败: {}", e))?;
+    let bgm_token = settings
+        .bgm_auth
+        .as_ref()
+        .map(|auth| auth.access_token.as_str());
+    let vndb_token = settings.vndb_token.as_deref().filter(|t| !t.is_empty());
+    let client = crate::utils::http::get_client();
+
+    let mut updates = Vec::new();
+    let mut repaired = Vec::new();
+    let mut failed = Vec::new();
+
+    for game_id in ids {
+        let game = match GamesRepository::find_by_id(&db, game_id).await {
+            Ok(Some(game)) => game,
+            Ok(None) => {
+                failed.push(CoverRepairFailure {
+                    game_id,
+                    message: "游戏不存在".to_string(),
+                });
+                continue;
+            }
+            Err(e) => {
+                failed.push(CoverRepairFailure {
+                    game_id,
+                    message: format!("查询游戏失败: {}", e),
+                });
+                continue;
+            }
+        };
+
+        let mut bgm_data = None;
+        let mut vndb_data = None;
+        let mut errors = Vec::new();
+
+        if let (Some(bgm_id), Some(data)) = (game.bgm_id.as_deref(), game.bgm_data.as_ref()) {
+            match fetch_bgm_image(&client, bgm_id, bgm_token).await {
+                Ok(Some(url)) if data.image.as_deref() != Some(url.as_str()) => {
+                    repaired.push(RepairedCover {
+                        game_id,
+                        source: CoverSource::Bgm,
+                        old_url: data.image.clone(),
+                        new_url: url.clone(),
+                    });
+                    let mut data = data.clone();
+                    data.image = Some(url);
+                    bgm_data = Some(Some(data));
+                }
+                Ok(_) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if let (Some(vndb_id), Some(data)) = (game.vndb_id.as_deref(), game.vndb_data.as_ref()) {
+            match fetch_vndb_image(&client, vndb_id, vndb_token).await {
+                Ok(Some(url)) if data.image.as_deref() != Some(url.as_str()) => {
+                    repaired.push(RepairedCover {
+                        game_id,
+                        source: CoverSource::Vndb,
+                        old_url: data.image.clone(),
+                        new_url: url.clone(),
+                    });
+                    let mut data = data.clone();
+                    data.image = Some(url);
+                    vndb_data = Some(Some(data));
+                }
+                Ok(_) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+
+        let has_supported_source = (game.bgm_id.is_some() && game.bgm_data.is_some())
+            || (game.vndb_id.is_some() && game.vndb_data.is_some());
+        if !has_supported_source {
+            errors.push("暂不支持从该游戏的数据源刷新封面".to_string());
+        }
+        if !errors.is_empty() {
+            failed.push(CoverRepairFailure {
+                game_id,
+                message: errors.join("; "),
+            });
+        }
+
+        if bgm_data.is_some() || vndb_data.is_some() {
+            updates.push((
+                game_id,
+                UpdateGameData {
+                    bgm_data,
+                    vndb_data,
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+
+    let updated_ids: Vec<i32> = updates.iter().map(|(id, _)| *id).collect();
+    GamesRepository::update_batch(&db, updates)
+        .await
+        .map_err(|e| format!("写入封面地址失败: {}", e))?;
+
+    // 旧地址对应的云端缓存已无意义，清除后前端按新地址重新下载
+    for game_id in &updated_ids {
+        if let Err(e) = delete_cloud_cache(*game_id as u32, download_state.clone()).await {
+            log::warn!("清除云端封面缓存失败 game_id={}: {}", game_id, e);
+        }
+    }
+
+    log::info!(
+        "封面修复完成 updated_games={} repaired_urls={} failed={}",
+        updated_ids.len(),
+        repaired.len(),
+        failed.len()
+    );
+
+    Ok(CoverRepairResult { repaired, failed })
+}
@@ -0,0 +1,242 @@
+//! 封面缩略图
+//!
+//! 网格视图同时显示上百张封面时，直接解码原图既慢又占内存。`generate_thumbnails` 为每个游戏
+//! 当前使用的封面（自定义封面优先，其次是已缓存的云端封面）生成指定宽度的有损 WebP 缩略图，
+//! 存放在封面目录的 `thumbnails` 子目录中。原图比缩略图新时重新生成，封面已不存在时删除缩略图。
+
+use super::cloud::get_game_cover_dir;
+use super::resolve_local_cover;
+use crate::database::db::DbState;
+use crate::entity::{games, prelude::Games};
+use crate::utils::metrics::CommandTimer;
+use image::{DynamicImage, ImageReader};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+const THUMBNAIL_DIR_NAME: &str = "thumbnails";
+const MIN_THUMBNAIL_WIDTH: u32 = 32;
+const MAX_THUMBNAIL_WIDTH: u32 = 1024;
+/// 单次最多生成的尺寸数
+const MAX_THUMBNAIL_SIZES: usize = 4;
+/// 有损 WebP 的质量（0 ~ 100）；image 自带的 WebP 编码器只支持无损，体积是有损的数倍
+const THUMBNAIL_QUALITY: f32 = 80.0;
+
+/// 单个尺寸的缩略图
+#[derive(Debug, Serialize)]
+pub struct Thumbnail {
+    /// 请求的宽度；原图更窄时实际宽度为原图宽度
+    pub size: u32,
+    pub path: String,
+}
+
+/// 单个游戏的缩略图
+#[derive(Debug, Serialize)]
+pub struct CoverThumbnails {
+    pub game_id: i32,
+    /// 生成缩略图所用的原图
+    pub source: String,
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+/// `generate_thumbnails` 的结果
+#[derive(Debug, Default, Serialize)]
+pub struct ThumbnailReport {
+    pub covers: Vec<CoverThumbnails>,
+    /// 本次新生成（或因原图更新而重新生成）的缩略图数量
+    pub generated: usize,
+    /// 没有本地封面（云端封面尚未缓存）的游戏
+    pub missing: Vec<i32>,
+    pub errors: Vec<String>,
+}
+
+/// 整理请求的尺寸：限制范围、去重并升序
+fn normalize_sizes(sizes: &[u32]) -> Vec<u32> {
+    let mut sizes: Vec<u32> = sizes
+        .iter()
+        .map(|&size| size.clamp(MIN_THUMBNAIL_WIDTH, MAX_THUMBNAIL_WIDTH))
+        .collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+    sizes.truncate(MAX_THUMBNAIL_SIZES);
+    sizes
+}
+
+/// 按宽度等比缩放，原图不超过该宽度时不放大
+fn resize_to_width(image: &DynamicImage, width: u32) -> DynamicImage {
+    if image.width() <= width {
+        image.clone()
+    } else {
+        image.thumbnail(width, u32::MAX)
+    }
+}
+
+fn thumbnail_path(thumbnail_dir: &Path, size: u32) -> PathBuf {
+    thumbnail_dir.join(format!("thumb_{}.webp", size))
+}
+
+/// 缩略图是否比原图旧（或不存在）
+fn is_outdated(thumbnail: &Path, source: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    match (modified(thumbnail), modified(source)) {
+        (Some(thumbnail), Some(source)) => thumbnail < source,
+        _ => true,
+    }
+}
+
+/// 为一张封面生成缺失或过期的缩略图，返回所有尺寸的路径与新生成的数量
+fn render_thumbnails(
+    source: &Path,
+    thumbnail_dir: &Path,
+    sizes: &[u32],
+) -> Result<(Vec<Thumbnail>, usize), String> {
+    let targets: Vec<(u32, PathBuf)> = sizes
+        .iter()
+        .map(|&size| (size, thumbnail_path(thumbnail_dir, size)))
+        .collect();
+    let outdated: Vec<&(u32, PathBuf)> = targets
+        .iter()
+        .filter(|(_, path)| is_outdated(path, source))
+        .collect();
+    let generated = outdated.len();
+
+    if !outdated.is_empty() {
+        let image = ImageReader::open(source)
+            .and_then(|reader| reader.with_guessed_format())
+            .map_err(|e| format!("读取封面失败 {}: {}", source.display(), e))?
+            .decode()
+            .map_err(|e| format!("解码封面失败 {}: {}", source.display(), e))?;
+        fs::create_dir_all(thumbnail_dir).map_err(|e| format!("创建缩略图目录失败: {}", e))?;
+
+        for (size, path) in &outdated {
+            let thumbnail = resize_to_width(&image, *size).to_rgba8();
+            let encoded =
+                webp::Encoder::from_rgba(thumbnail.as_raw(), thumbnail.width(), thumbnail.height())
+                    .encode_simple(false, THUMBNAIL_QUALITY)
+                    .map_err(|e| format!("编码缩略图失败: {:?}", e))?;
+            let temp_path = path.with_extension("webp.part.0");
+            let written = fs::write(&temp_path, &*encoded)
+                .map_err(|e| format!("写入缩略图失败: {}", e))
+                .and_then(|()| {
+                    fs::rename(&temp_path, path).map_err(|e| format!("保存缩略图失败: {}", e))
+                });
+            if let Err(e) = written {
+                let _ = fs::remove_file(&temp_path);
+                return Err(e);
+            }
+        }
+    }
+
+    Ok((
+        targets
+            .into_iter()
+            .map(|(size, path)| Thumbnail {
+                size,
+                path: path.to_string_lossy().to_string(),
+            })
+            .collect(),
+        generated,
+    ))
+}
+
+/// 为游戏当前使用的封面生成 WebP 缩略图
+///
+/// 云端封面只使用已缓存的文件，不会为此发起下载（可先调用 `cache_cover`）。
+///
+/// # Arguments
+/// * `sizes` - 缩略图宽度（像素），限制在 32 ~ 1024，最多 4 个
+/// * `game_ids` - 只处理这些游戏，未传入时处理全部游戏
+///
+/// # Returns
+/// * `Result<ThumbnailReport, String>` - 每个游戏的缩略图路径
+#[tauri::command]
+pub async fn generate_thumbnails(
+    db: State<'_, DbState>,
+    sizes: Vec<u32>,
+    game_ids: Option<Vec<i32>>,
+) -> Result<ThumbnailReport, String> {
+    let _timer = CommandTimer::start("generate_thumbnails");
+    let db = db.get();
+    let sizes = normalize_sizes(&sizes);
+    if sizes.is_empty() {
+        return Err("至少需要指定一个缩略图尺寸".to_string());
+    }
+
+    let mut query = Games::find();
+    if let Some(game_ids) = game_ids {
+        query = query.filter(games::Column::Id.is_in(game_ids));
+    }
+    let games = query
+        .all(&db)
+        .await
+        .map_err(|e| format!("获取游戏列表失败: {}", e))?;
+
+    let mut sources = Vec::with_capacity(games.len());
+    for game in games {
+        let Ok(game_id) = u32::try_from(game.id) else {
+            continue;
+        };
+        let thumbnail_dir = get_game_cover_dir(game_id)?.join(THUMBNAIL_DIR_NAME);
+        sources.push((game.id, thumbnail_dir, resolve_local_cover(&game).await?));
+    }
+
+    let report = tokio::task::spawn_blocking(move || {
+        let mut report = ThumbnailReport::default();
+        for (game_id, thumbnail_dir, source) in sources {
+            let Some(source) = source else {
+                if thumbnail_dir.is_dir() {
+                    let _ = fs::remove_dir_all(&thumbnail_dir);
+                }
+                report.missing.push(game_id);
+                continue;
+            };
+            match render_thumbnails(&source, &thumbnail_dir, &sizes) {
+                Ok((thumbnails, generated)) => {
+                    report.generated += generated;
+                    report.covers.push(CoverThumbnails {
+                        game_id,
+                        source: source.to_string_lossy().to_string(),
+                        thumbnails,
+                    });
+                }
+                Err(e) => report.errors.push(format!("game_id={}: {}", game_id, e)),
+            }
+        }
+        report
+    })
+    .await
+    .map_err(|e| format!("生成缩略图失败: {}", e))?;
+
+    log::info!(
+        "封面缩略图生成完成 covers={} generated={} missing={} errors={}",
+        report.covers.len(),
+        report.generated,
+        report.missing.len(),
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DynamicImage, normalize_sizes, resize_to_width};
+
+    #[test]
+    fn sizes_are_clamped_deduped_and_limited() {
+        assert_eq!(normalize_sizes(&[300, 8, 300, 4096]), vec![32, 300, 1024]);
+        assert_eq!(normalize_sizes(&[100, 200, 300, 400, 500]).len(), 4);
+        assert!(normalize_sizes(&[]).is_empty());
+    }
+
+    #[test]
+    fn resize_keeps_aspect_ratio_and_never_upscales() {
+        let image = DynamicImage::new_rgb8(600, 900);
+        let thumbnail = resize_to_width(&image, 200);
+        assert_eq!((thumbnail.width(), thumbnail.height()), (200, 300));
+
+        let small = resize_to_width(&image, 1024);
+        assert_eq!((small.width(), small.height()), (600, 900));
+    }
+}
@@ -0,0 +1,542 @@
+//! 跨数据源 ID 匹配
+//!
+//! 游戏只关联了部分数据源（如只有 vndb_id）时，用已有元数据中的标题到缺失的数据源搜索，
+//! 按标题相似度与发售日期为候选打分。`resolve_cross_ids` 只返回候选，不修改数据库；
+//! 用户确认后由 `apply_cross_ids` 写入 ID，对应的元数据由前端按新 ID 刷新。
+
+use crate::database::db::DbState;
+use serde::Serialize;
+use serde_json::{Value, json};
+use tauri::State;
+use tauri_plugin_http::reqwest::Client;
+use tauri_plugin_http::reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+
+use crate::database::dto::UpdateGameData;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::entity::games;
+use crate::utils::metrics::CommandTimer;
+
+const BGM_API_BASE_URL: &str = "https://api.bgm.tv/v0";
+const VNDB_API_BASE_URL: &str = "https://api.vndb.org/kana";
+const YMGAL_BASE_URL: &str = "https://www.ymgal.games";
+/// 月幕开放接口的公共客户端凭据（与前端 `src/metadata/api/ymgal.ts` 一致）
+const YMGAL_CLIENT_ID: &str = "ymgal";
+const YMGAL_CLIENT_SECRET: &str = "luna0327";
+
+/// 每个数据源返回的最大候选数
+const MAX_CANDIDATES: usize = 5;
+
+/// 候选所属的数据源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrossIdSource {
+    Bgm,
+    Vndb,
+    Ymgal,
+}
+
+/// 匹配候选
+#[derive(Debug, Clone, Serialize)]
+pub struct CrossIdCandidate {
+    pub source: CrossIdSource,
+    pub id: String,
+    pub title: String,
+    pub alt_title: Option<String>,
+    pub date: Option<String>,
+    /// 匹配度，0 ~ 1
+    pub score: f64,
+}
+
+/// 匹配结果，只包含游戏缺失的数据源
+#[derive(Debug, Serialize)]
+pub struct CrossIdResolution {
+    pub game_id: i32,
+    /// 用于搜索的标题
+    pub query: Option<String>,
+    pub bgm: Vec<CrossIdCandidate>,
+    pub vndb: Vec<CrossIdCandidate>,
+    pub ymgal: Vec<CrossIdCandidate>,
+    /// 各数据源的请求错误
+    pub errors: Vec<String>,
+}
+
+// ==================== 匹配打分 ====================
+
+/// 标题归一化：转小写，去掉空白与标点
+fn normalize_title(title: &str) -> Vec<char> {
+    title
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// 两个标题的相似度（归一化后相同为 1，否则为字符二元组的 Dice 系数）
+fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = normalize_title(a);
+    let b = normalize_title(b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+
+    let mut b_pairs: Vec<(char, char)> = b.windows(2).map(|w| (w[0], w[1])).collect();
+    let total = (a.len() - 1 + b_pairs.len()) as f64;
+    let mut common = 0usize;
+    for pair in a.windows(2).map(|w| (w[0], w[1])) {
+        if let Some(pos) = b_pairs.iter().position(|p| *p == pair) {
+            b_pairs.swap_remove(pos);
+            common += 1;
+        }
+    }
+    (2 * common) as f64 / total
+}
+
+/// 综合标题与发售日期给候选打分
+///
+/// 取本地标题与候选标题两两比较的最高相似度；发售日期完全一致时加分，年份不同时减分。
+fn score_candidate(
+    local_titles: &[String],
+    local_date: Option<&str>,
+    candidate_titles: &[&str],
+    candidate_date: Option<&str>,
+) -> f64 {
+    let similarity = local_titles
+        .iter()
+        .flat_map(|local| {
+            candidate_titles
+                .iter()
+                .map(move |candidate| title_similarity(local, candidate))
+        })
+        .fold(0.0, f64::max);
+
+    let date_adjust = match (local_date, candidate_date) {
+        (Some(local), Some(candidate)) if !local.is_empty() && !candidate.is_empty() => {
+            if local == candidate {
+                0.1
+            } else if local.get(..4) != candidate.get(..4) {
+                -0.2
+            } else {
+                0.0
+            }
+        }
+        _ => 0.0,
+    };
+    (similarity + date_adjust).clamp(0.0, 1.0)
+}
+
+/// 收集游戏已有元数据中的标题（原名优先），去重
+fn collect_titles(game: &games::Model) -> Vec<String> {
+    let mut titles = Vec::new();
+    macro_rules! push_titles {
+        ($source:expr) => {
+            if let Some(data) = $source.as_ref() {
+                titles.extend(data.name.clone());
+                titles.extend(data.name_cn.clone());
+                titles.extend(data.aliases.clone().unwrap_or_default());
+            }
+        };
+    }
+    push_titles!(game.vndb_data);
+    push_titles!(game.bgm_data);
+    push_titles!(game.ymgal_data);
+    if let Some(data) = game.vndb_data.as_ref() {
+        titles.extend(data.all_titles.clone().unwrap_or_default());
+    }
+    titles.extend(game.custom_data.as_ref().and_then(|d| d.name.clone()));
+
+    let mut seen = std::collections::HashSet::new();
+    titles.retain(|title| !title.trim().is_empty() && seen.insert(normalize_title(title)));
+    titles
+}
+
+/// 已有元数据中的发售日期
+fn collect_date(game: &games::Model) -> Option<String> {
+    game.vndb_data
+        .as_ref()
+        .and_then(|d| d.date.clone())
+        .or_else(|| game.bgm_data.as_ref().and_then(|d| d.date.clone()))
+        .or_else(|| game.ymgal_data.as_ref().and_then(|d| d.date.clone()))
+        .or_else(|| game.date.clone())
+        .filter(|date| !date.is_empty())
+}
+
+/// 按匹配度降序排列并截断
+fn rank(mut candidates: Vec<CrossIdCandidate>) -> Vec<CrossIdCandidate> {
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(MAX_CANDIDATES);
+    candidates
+}
+
+// ==================== 数据源搜索 ====================
+
+/// 读取响应并解析为 JSON
+async fn read_json(
+    response: tauri_plugin_http::reqwest::Response,
+    source: &str,
+) -> Result<Value, String> {
+    if !response.status().is_success() {
+        return Err(format!("{} 返回异常状态码: {}", source, response.status()));
+    }
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("读取 {} 响应失败: {}", source, e))?;
+    serde_json::from_str(&text).map_err(|e| format!("解析 {} 响应失败: {}", source, e))
+}
+
+fn value_str(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// 在 BGM 搜索游戏条目
+async fn search_bgm(
+    client: &Client,
+    keyword: &str,
+    token: Option<&str>,
+    titles: &[String],
+    date: Option<&str>,
+) -> Result<Vec<CrossIdCandidate>, String> {
+    let body = json!({ "keyword": keyword, "filter": { "type": [4] } });
+    let mut request = client
+        .post(format!("{}/search/subjects", BGM_API_BASE_URL))
+        .query(&[("limit", MAX_CANDIDATES * 2)])
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.to_string());
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("请求 BGM 失败: {}", e))?;
+    let body = read_json(response, "BGM").await?;
+
+    let items = body.get("data").and_then(Value::as_array);
+    Ok(rank(
+        items
+            .into_iter()
+            .flatten()
+            .filter_map(|item| {
+                let id = item.get("id").and_then(Value::as_i64)?.to_string();
+                let name = value_str(item, "name")?;
+                let name_cn = value_str(item, "name_cn");
+                let item_date = value_str(item, "date");
+                let mut candidate_titles = vec![name.as_str()];
+                candidate_titles.extend(name_cn.as_deref());
+                Some(CrossIdCandidate {
+                    source: CrossIdSource::Bgm,
+                    score: score_candidate(titles, date, &candidate_titles, item_date.as_deref()),
+                    id,
+                    title: name,
+                    alt_title: name_cn,
+                    date: item_date,
+                })
+            })
+            .collect(),
+    ))
+}
+
+/// 在 VNDB 搜索视觉小说
+async fn search_vndb(
+    client: &Client,
+    keyword: &str,
+    token: Option<&str>,
+    titles: &[String],
+    date: Option<&str>,
+) -> Result<Vec<CrossIdCandidate>, String> {
+    let body = json!({
+        "filters": ["search", "=", keyword],
+        "fields": "id, title, alttitle, titles.title, released",
+        "results": MAX_CANDIDATES * 2,
+        "sort": "searchrank",
+    });
+    let mut request = client
+        .post(format!("{}/vn", VNDB_API_BASE_URL))
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.to_string());
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Token {}", token));
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("请求 VNDB 失败: {}", e))?;
+    let body = read_json(response, "VNDB").await?;
+
+    let items = body.get("results").and_then(Value::as_array);
+    Ok(rank(
+        items
+            .into_iter()
+            .flatten()
+            .filter_map(|item| {
+                let id = value_str(item, "id")?;
+                let title = value_str(item, "title")?;
+                let alt_title = value_str(item, "alttitle");
+                let released = value_str(item, "released");
+                let mut candidate_titles: Vec<&str> = item
+                    .get("titles")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|t| t.get("title").and_then(Value::as_str))
+                    .collect();
+                candidate_titles.push(title.as_str());
+                candidate_titles.extend(alt_title.as_deref());
+                Some(CrossIdCandidate {
+                    source: CrossIdSource::Vndb,
+                    score: score_candidate(titles, date, &candidate_titles, released.as_deref()),
+                    id,
+                    title,
+                    alt_title,
+                    date: released,
+                })
+            })
+            .collect(),
+    ))
+}
+
+/// 只有标题时同时在 BGM 与 VNDB 搜索（批量导入扫描时使用），候选合并后按匹配度排序
+pub(crate) async fn search_by_title(
+    client: &Client,
+    title: &str,
+    bgm_token: Option<&str>,
+    vndb_token: Option<&str>,
+) -> (Vec<CrossIdCandidate>, Vec<String>) {
+    let titles = [title.to_string()];
+    let bgm = search_bgm(client, title, bgm_token, &titles, None).await;
+    let vndb = search_vndb(client, title, vndb_token, &titles, None).await;
+    let mut candidates = Vec::new();
+    let mut errors = Vec::new();
+    for result in [bgm, vndb] {
+        match result {
+            Ok(found) => candidates.extend(found),
+            Err(e) => errors.push(e),
+        }
+    }
+    (rank(candidates), errors)
+}
+
+/// 在月幕搜索游戏
+async fn search_ymgal(
+    client: &Client,
+    keyword: &str,
+    titles: &[String],
+    date: Option<&str>,
+) -> Result<Vec<CrossIdCandidate>, String> {
+    let response = client
+        .get(format!("{}/oauth/token", YMGAL_BASE_URL))
+        .query(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", YMGAL_CLIENT_ID),
+            ("client_secret", YMGAL_CLIENT_SECRET),
+            ("scope", "public"),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("获取月幕 Access Token 失败: {}", e))?;
+    let token = value_str(&read_json(response, "月幕").await?, "access_token")
+        .ok_or("月幕未返回 Access Token")?;
+
+    let page_size = (MAX_CANDIDATES * 2).to_string();
+    let response = client
+        .get(format!("{}/open/archive/search-game", YMGAL_BASE_URL))
+        .query(&[
+            ("mode", "list"),
+            ("keyword", keyword),
+            ("pageNum", "1"),
+            ("pageSize", page_size.as_str()),
+        ])
+        .header(ACCEPT, "application/json;charset=utf-8")
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .header("version", "1")
+        .send()
+        .await
+        .map_err(|e| format!("请求月幕失败: {}", e))?;
+    let body = read_json(response, "月幕").await?;
+    if body.get("code").and_then(Value::as_i64) != Some(0) {
+        return Err(format!("月幕返回错误: {}", body));
+    }
+
+    let items = body.pointer("/data/result").and_then(Value::as_array);
+    Ok(rank(
+        items
+            .into_iter()
+            .flatten()
+            .filter_map(|item| {
+                let id = item.get("id").and_then(Value::as_i64)?.to_string();
+                let name = value_str(item, "name")?;
+                let chinese_name = value_str(item, "chineseName");
+                let release_date = value_str(item, "releaseDate");
+                let mut candidate_titles = vec![name.as_str()];
+                candidate_titles.extend(chinese_name.as_deref());
+                Some(CrossIdCandidate {
+                    source: CrossIdSource::Ymgal,
+                    score: score_candidate(
+                        titles,
+                        date,
+                        &candidate_titles,
+                        release_date.as_deref(),
+                    ),
+                    id,
+                    title: name,
+                    alt_title: chinese_name,
+                    date: release_date,
+                })
+            })
+            .collect(),
+    ))
+}
+
+// ==================== Commands ====================
+
+/// 为游戏缺失的数据源查找对应条目
+///
+/// 只搜索，不写入数据库；没有可用标题时返回空候选。
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+///
+/// # Returns
+/// * `Result<CrossIdResolution, String>` - 各数据源的候选或错误消息
+#[tauri::command]
+pub async fn resolve_cross_ids(
+    db: State<'_, DbState>,
+    game_id: i32,
+) -> Result<CrossIdResolution, String> {
+    let _timer = CommandTimer::start("resolve_cross_ids");
+    let db = db.get();
+    let game = GamesRepository::find_by_id(&db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏数据失败: {}", e))?
+        .ok_or("游戏不存在")?;
+    let settings = SettingsRepository::get_all_settings(&db)
+        .await
+        .map_err(|e| format!("获取用户设置失败: {}", e))?;
+    let bgm_token = settings
+        .bgm_auth
+        .as_ref()
+        .map(|auth| auth.access_token.as_str());
+    let vndb_token = settings.vndb_token.as_deref().filter(|t| !t.is_empty());
+
+    let titles = collect_titles(&game);
+    let date = collect_date(&game);
+    let mut resolution = CrossIdResolution {
+        game_id,
+        query: titles.first().cloned(),
+        bgm: Vec::new(),
+        vndb: Vec::new(),
+        ymgal: Vec::new(),
+        errors: Vec::new(),
+    };
+    let Some(keyword) = resolution.query.clone() else {
+        return Ok(resolution);
+    };
+    let client = crate::utils::http::get_client();
+    let date = date.as_deref();
+
+    if game.bgm_id.is_none() {
+        match search_bgm(&client, &keyword, bgm_token, &titles, date).await {
+            Ok(candidates) => resolution.bgm = candidates,
+            Err(e) => resolution.errors.push(e),
+        }
+    }
+    if game.vndb_id.is_none() {
+        match search_vndb(&client, &keyword, vndb_token, &titles, date).await {
+            Ok(candidates) => resolution.vndb = candidates,
+            Err(e) => resolution.errors.push(e),
+        }
+    }
+    if game.ymgal_id.is_none() {
+        match search_ymgal(&client, &keyword, &titles, date).await {
+            Ok(candidates) => resolution.ymgal = candidates,
+            Err(e) => resolution.errors.push(e),
+        }
+    }
+
+    log::info!(
+        "跨数据源 ID 匹配完成 game_id={} bgm={} vndb={} ymgal={} errors={}",
+        game_id,
+        resolution.bgm.len(),
+        resolution.vndb.len(),
+        resolution.ymgal.len(),
+        resolution.errors.len()
+    );
+    Ok(resolution)
+}
+
+/// 写入用户确认的跨数据源 ID
+///
+/// 只更新传入的 ID，已有的 ID 和元数据保持不变。
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+/// * `bgm_id` / `vndb_id` / `ymgal_id` - 确认的 ID，不修改的传 None
+///
+/// # Returns
+/// * `Result<games::Model, String>` - 更新后的游戏数据或错误消息
+#[tauri::command]
+pub async fn apply_cross_ids(
+    db: State<'_, DbState>,
+    game_id: i32,
+    bgm_id: Option<String>,
+    vndb_id: Option<String>,
+    ymgal_id: Option<String>,
+) -> Result<games::Model, String> {
+    let _timer = CommandTimer::start("apply_cross_ids");
+    let db = db.get();
+    let updates = UpdateGameData {
+        bgm_id: bgm_id.map(Some),
+        vndb_id: vndb_id.map(Some),
+        ymgal_id: ymgal_id.map(Some),
+        ..Default::default()
+    };
+    GamesRepository::update(&db, game_id, updates)
+        .await
+        .map_err(|e| format!("更新游戏 ID 失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_ignores_case_spacing_and_punctuation() {
+        assert_eq!(title_similarity("Summer Pockets", "summer-pockets!"), 1.0);
+        assert_eq!(title_similarity("サクラノ詩", "サクラノ 詩"), 1.0);
+        assert!(title_similarity("Summer Pockets", "Summer Pockets REFLECTION BLUE") > 0.5);
+        assert!(title_similarity("Summer Pockets", "Rewrite") < 0.2);
+    }
+
+    #[test]
+    fn release_date_adjusts_score() {
+        let titles = vec!["Rewrite".to_string()];
+        let same = score_candidate(
+            &titles,
+            Some("2011-06-24"),
+            &["Rewrite+"],
+            Some("2011-06-24"),
+        );
+        let other_year = score_candidate(
+            &titles,
+            Some("2011-06-24"),
+            &["Rewrite+"],
+            Some("2018-01-26"),
+        );
+        assert!(same > other_year);
+        assert_eq!(
+            score_candidate(&titles, None, &["Rewrite"], Some("2011-06-24")),
+            1.0
+        );
+    }
+}
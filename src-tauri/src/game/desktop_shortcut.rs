@@ -0,0 +1,292 @@
+//! 桌面快捷方式
+//!
+//! 在桌面为游戏创建快捷方式（Windows 为 .lnk，Linux 为 .desktop），目标为 ReinaManager 本身并附带
+//! `reina://launch/<game_id>` 链接参数，从桌面启动时同样经过启动流程与游戏时间统计。
+//! 游戏有本地封面时把封面居中裁成正方形作为图标，写入封面目录下的 `shortcut_icon.*`。
+
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::game::cover::resolve_local_cover;
+use crate::utils::deep_link::DEEP_LINK_SCHEME;
+use crate::utils::metrics::CommandTimer;
+use image::ImageFormat;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// 图标边长
+const ICON_SIZE: u32 = 256;
+
+/// 图标文件名（不含扩展名）
+const ICON_FILE_STEM: &str = "shortcut_icon";
+
+/// 游戏对应的启动链接
+fn launch_link(game_id: u32) -> String {
+    format!("{}://launch/{}", DEEP_LINK_SCHEME, game_id)
+}
+
+/// 把游戏名称转换为合法的文件名
+fn sanitize_file_name(name: &str, game_id: u32) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    // Windows 不允许文件名以点或空格结尾
+    let trimmed = sanitized.trim().trim_end_matches('.').trim_end();
+    if trimmed.is_empty() {
+        format!("game_{}", game_id)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// 把封面居中裁成正方形并保存为图标，Windows 使用 .ico，Linux 使用 .png
+fn write_cover_icon(cover: &Path) -> Result<PathBuf, String> {
+    let (extension, format) = if cfg!(target_os = "windows") {
+        ("ico", ImageFormat::Ico)
+    } else {
+        ("png", ImageFormat::Png)
+    };
+    let output = cover.with_file_name(format!("{}.{}", ICON_FILE_STEM, extension));
+
+    let image = image::open(cover).map_err(|e| format!("读取封面失败: {}", e))?;
+    let side = image.width().min(image.height());
+    let icon = image
+        .crop_imm(
+            (image.width() - side) / 2,
+            (image.height() - side) / 2,
+            side,
+            side,
+        )
+        .thumbnail(ICON_SIZE, ICON_SIZE);
+    icon.save_with_format(&output, format)
+        .map_err(|e| format!("保存快捷方式图标失败: {}", e))?;
+    Ok(output)
+}
+
+/// ReinaManager 可执行文件路径，AppImage 中使用 AppImage 文件本身而非挂载目录中的路径
+fn manager_executable() -> Result<PathBuf, String> {
+    #[cfg(target_os = "linux")]
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    std::env::current_exe().map_err(|e| format!("获取程序路径失败: {}", e))
+}
+
+/// 按 Desktop Entry 规范转义 Exec 中的单个参数
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn quote_exec_arg(arg: &str) -> String {
+    let needs_quotes = arg.is_empty()
+        || arg.chars().any(|c| {
+            c.is_whitespace()
+                || matches!(
+                    c,
+                    '"' | '\''
+                        | '\\'
+                        | '>'
+                        | '<'
+                        | '~'
+                        | '|'
+                        | '&'
+                        | ';'
+                        | '$'
+                        | '*'
+                        | '?'
+                        | '#'
+                        | '('
+                        | ')'
+                        | '`'
+                )
+        });
+    let escaped = arg.replace('%', "%%");
+    if !needs_quotes {
+        return escaped;
+    }
+    let mut quoted = String::from("\"");
+    for c in escaped.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    // 引号内的反斜杠在字符串值层面还需要再转义一次
+    quoted.replace('\\', "\\\\")
+}
+
+/// 生成 .desktop 文件内容
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn desktop_entry(name: &str, exec: &Path, link: &str, icon: Option<&Path>) -> String {
+    let name = name.replace(['\n', '\r'], " ");
+    let mut entry = format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={} {}\nTerminal=false\nCategories=Game;\n",
+        name,
+        quote_exec_arg(&exec.to_string_lossy()),
+        quote_exec_arg(link)
+    );
+    if let Some(icon) = icon {
+        entry.push_str(&format!("Icon={}\n", icon.display()));
+    }
+    entry
+}
+
+#[cfg(target_os = "linux")]
+fn write_shortcut(
+    desktop: &Path,
+    file_name: &str,
+    name: &str,
+    link: &str,
+    icon: Option<&Path>,
+) -> Result<PathBuf, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = desktop.join(format!("{}.desktop", file_name));
+    std::fs::write(
+        &path,
+        desktop_entry(name, &manager_executable()?, link, icon),
+    )
+    .map_err(|e| format!("写入快捷方式失败: {}", e))?;
+    // 多数桌面环境只启动带可执行权限的 .desktop 文件
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("设置快捷方式权限失败: {}", e))?;
+    Ok(path)
+}
+
+#[cfg(target_os = "windows")]
+fn write_shortcut(
+    desktop: &Path,
+    file_name: &str,
+    name: &str,
+    link: &str,
+    icon: Option<&Path>,
+) -> Result<PathBuf, String> {
+    use windows::Win32::System::Com::{
+        CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx,
+        CoUninitialize, IPersistFile,
+    };
+    use windows::Win32::UI::Shell::{IShellLinkW, ShellLink};
+    use windows::core::{HSTRING, Interface};
+
+    let path = desktop.join(format!("{}.lnk", file_name));
+    let exe = manager_executable()?;
+
+    unsafe {
+        let initialized = CoInitializeEx(None, COINIT_APARTMENTTHREADED).is_ok();
+        let result = (|| -> windows::core::Result<()> {
+            let shell_link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            shell_link.SetPath(&HSTRING::from(exe.as_os_str()))?;
+            shell_link.SetArguments(&HSTRING::from(link))?;
+            shell_link.SetDescription(&HSTRING::from(name))?;
+            if let Some(dir) = exe.parent() {
+                shell_link.SetWorkingDirectory(&HSTRING::from(dir.as_os_str()))?;
+            }
+            if let Some(icon) = icon {
+                shell_link.SetIconLocation(&HSTRING::from(icon.as_os_str()), 0)?;
+            }
+            shell_link
+                .cast::<IPersistFile>()?
+                .Save(&HSTRING::from(path.as_os_str()), true)
+        })();
+        if initialized {
+            CoUninitialize();
+        }
+        result.map_err(|e| format!("写入快捷方式失败: {}", e))?;
+    }
+    Ok(path)
+}
+
+/// 在桌面创建游戏快捷方式，已存在同名快捷方式时覆盖
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+/// * `language` - 前端当前语言，用于选择快捷方式名称（与排序规则一致）
+///
+/// # Returns
+/// * `Result<String, String>` - 快捷方式文件路径或错误消息
+#[tauri::command]
+pub async fn create_desktop_shortcut<R: Runtime>(
+    app_handle: AppHandle<R>,
+    db: State<'_, DbState>,
+    game_id: u32,
+    language: Option<String>,
+) -> Result<String, String> {
+    let _timer = CommandTimer::start("create_desktop_shortcut");
+    let db = db.get();
+    let game = GamesRepository::find_by_id(&db, game_id as i32)
+        .await
+        .map_err(|e| format!("查询游戏失败: {}", e))?
+        .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+
+    let use_cn = language.as_deref() == Some("zh-CN");
+    let name = GamesRepository::get_display_name(&game, use_cn)
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| format!("game_{}", game_id));
+    let file_name = sanitize_file_name(&name, game_id);
+    let desktop = app_handle
+        .path()
+        .desktop_dir()
+        .map_err(|e| format!("获取桌面目录失败: {}", e))?;
+    let cover = resolve_local_cover(&game).await?;
+    // 没有封面时 Windows 使用游戏可执行文件的图标
+    let fallback_icon = game
+        .localpath
+        .as_deref()
+        .filter(|_| cfg!(target_os = "windows"))
+        .map(PathBuf::from)
+        .filter(|path| path.is_file());
+
+    tokio::task::spawn_blocking(move || {
+        let icon = match cover {
+            Some(cover) => match write_cover_icon(&cover) {
+                Ok(icon) => Some(icon),
+                Err(e) => {
+                    log::warn!("生成快捷方式图标失败，使用默认图标: {}", e);
+                    fallback_icon
+                }
+            },
+            None => fallback_icon,
+        };
+        let path = write_shortcut(
+            &desktop,
+            &file_name,
+            &name,
+            &launch_link(game_id),
+            icon.as_deref(),
+        )?;
+        log::info!("已创建游戏 {} 的桌面快捷方式: {}", game_id, path.display());
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("创建快捷方式失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizes_file_names() {
+        assert_eq!(sanitize_file_name("Fate/stay night", 1), "Fate_stay night");
+        assert_eq!(sanitize_file_name("What?. ", 1), "What_");
+        assert_eq!(sanitize_file_name(" ... ", 3), "game_3");
+    }
+
+    #[test]
+    fn desktop_entry_quotes_exec_arguments() {
+        let entry = desktop_entry(
+            "Game",
+            Path::new("/opt/Reina Manager/reina"),
+            &launch_link(5),
+            None,
+        );
+        assert!(entry.contains("Exec=\"/opt/Reina Manager/reina\" reina://launch/5\n"));
+        assert!(!entry.contains("Icon="));
+        assert_eq!(quote_exec_arg("100%"), "100%%");
+        assert_eq!(quote_exec_arg("a$b"), "\"a\\\\$b\"");
+    }
+}
@@ -1,11 +1,45 @@
+mod args;
+mod early_exit;
+mod error;
+mod failure;
+mod output;
+mod runner;
+mod uri;
+mod volume;
+
+pub use error::LaunchError;
+pub use runner::list_available_runners;
+
 #[cfg(target_os = "windows")]
 mod windows;
 
 #[cfg(target_os = "linux")]
 mod linux;
 
+#[cfg(target_os = "linux")]
+mod sandbox;
+
 #[cfg(target_os = "windows")]
 pub use windows::*;
 
 #[cfg(target_os = "linux")]
 pub use linux::*;
+
+use crate::database::db::DbState;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// 以默认参数启动游戏，供 HTTP API、`reina://` 链接等非前端入口使用
+pub async fn launch_game_by_id<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    game_id: u32,
+) -> Result<LaunchResult, LaunchError> {
+    let db = app_handle
+        .try_state::<DbState>()
+        .ok_or_else(|| LaunchError::from("数据库尚未初始化"))?;
+
+    #[cfg(target_os = "windows")]
+    return launch_game(app_handle.clone(), db, game_id, None, None, None, None).await;
+
+    #[cfg(target_os = "linux")]
+    return launch_game(app_handle.clone(), db, game_id, None, None, None).await;
+}
@@ -0,0 +1,121 @@
+//! 启动参数合并与占位符展开
+//!
+//! games.launch_args 中保存的参数在每次启动时展开占位符，放在前端临时传入的参数之前。
+//! 支持的占位符：
+//! - `{gamedir}`: 游戏可执行文件所在目录
+//! - `{gamepath}`: 游戏可执行文件路径
+//! - `{savepath}`: 存档目录
+//! - `{gameid}`: 游戏 ID
+//!
+//! 占位符对应的值未设置时启动失败，而不是把空路径传给游戏；未知的 `{...}` 原样保留。
+
+use crate::entity::games;
+use std::path::Path;
+
+/// 占位符名称与对应的值
+fn placeholder_value(game: &games::Model, name: &str) -> Option<Option<String>> {
+    let value = match name {
+        "gamedir" => game
+            .localpath
+            .as_deref()
+            .and_then(|path| Path::new(path).parent())
+            .map(|dir| dir.to_string_lossy().into_owned()),
+        "gamepath" => game.localpath.clone(),
+        "savepath" => game.savepath.clone(),
+        "gameid" => Some(game.id.to_string()),
+        _ => return None,
+    };
+    Some(value.filter(|value| !value.trim().is_empty()))
+}
+
+/// 展开单个参数中的占位符
+///
+/// `lookup` 对未知占位符返回 None（原样保留），对已知但未设置的占位符返回 `Some(None)`。
+fn expand_placeholders<F>(arg: &str, lookup: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<Option<String>>,
+{
+    let mut result = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            result.push_str(&rest[start..]);
+            return Ok(result);
+        };
+        let name = &after[..end];
+        match lookup(name) {
+            Some(Some(value)) => result.push_str(&value),
+            Some(None) => return Err(format!("启动参数中的 {{{}}} 未设置", name)),
+            None => result.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// 合并游戏保存的启动参数与本次临时传入的参数
+///
+/// 保存的参数展开占位符后在前，临时参数原样在后；两者都为空时返回 None。
+pub fn merge_launch_args(
+    game: &games::Model,
+    extra: Option<Vec<String>>,
+) -> Result<Option<Vec<String>>, String> {
+    let mut merged = Vec::new();
+    if let Some(saved) = &game.launch_args {
+        for arg in &saved.0 {
+            merged.push(expand_placeholders(arg, |name| {
+                placeholder_value(game, name)
+            })?);
+        }
+    }
+    merged.extend(extra.unwrap_or_default());
+    Ok((!merged.is_empty()).then_some(merged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<Option<String>> {
+        match name {
+            "gamedir" => Some(Some("D:\\Games\\Foo".to_string())),
+            "savepath" => Some(None),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_known_placeholders() {
+        assert_eq!(
+            expand_placeholders("--dir={gamedir}\\data", lookup).unwrap(),
+            "--dir=D:\\Games\\Foo\\data"
+        );
+        assert_eq!(
+            expand_placeholders("{gamedir}{gamedir}", lookup).unwrap(),
+            "D:\\Games\\FooD:\\Games\\Foo"
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_placeholders_and_unclosed_braces() {
+        assert_eq!(
+            expand_placeholders("{unknown} -x {gamedir", lookup).unwrap(),
+            "{unknown} -x {gamedir"
+        );
+        assert_eq!(
+            expand_placeholders("-windowed", lookup).unwrap(),
+            "-windowed"
+        );
+    }
+
+    #[test]
+    fn unset_placeholder_is_an_error() {
+        assert_eq!(
+            expand_placeholders("--save={savepath}", lookup).unwrap_err(),
+            "启动参数中的 {savepath} 未设置"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! 启动后立即退出检测
+//!
+//! 缺少 DLL、区域设置不对等问题通常表现为游戏进程启动后一两秒内以非零退出码退出。
+//! 启动后先观察 `EARLY_EXIT_WINDOW`，这种情况直接返回带退出码和 stderr 末尾的
+//! `LaunchError::ExitedEarly`，不再开始注定不会计时的监控会话。
+//! 以 0 退出的进程视为启动器已拉起真正的游戏，照常进入监控。
+
+use crate::game::launch::LaunchError;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// 启动后观察进程是否退出的时长
+pub const EARLY_EXIT_WINDOW: Duration = Duration::from_secs(2);
+
+/// 观察期内的轮询间隔
+pub const EARLY_EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 保留的 stderr 末尾字节数
+const STDERR_TAIL_BYTES: usize = 8 * 1024;
+
+/// 进程退出后等待 stderr 读取线程读完剩余输出的最长时间
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 格式化退出码：Windows 的 NTSTATUS（如 0xC0000135 找不到 DLL）按十六进制显示
+fn format_exit_code(exit_code: i32) -> String {
+    if exit_code < 0 {
+        format!("0x{:08X}", exit_code as u32)
+    } else {
+        exit_code.to_string()
+    }
+}
+
+/// 构造启动后立即退出的错误
+pub fn exited_early_error(exe_name: &str, exit_code: i32, stderr: Option<String>) -> LaunchError {
+    LaunchError::ExitedEarly {
+        message: format!(
+            "游戏 {} 启动后立即退出（退出码 {}），可能缺少运行库或需要转区启动",
+            exe_name,
+            format_exit_code(exit_code)
+        ),
+        exit_code,
+        detail: stderr,
+    }
+}
+
+/// 观察直接 spawn 的子进程，在观察期内以非零退出码退出时返回退出码
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub async fn wait_for_early_exit(child: &mut std::process::Child) -> Option<i32> {
+    let deadline = tokio::time::Instant::now() + EARLY_EXIT_WINDOW;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return status.code().filter(|code| *code != 0),
+            Ok(None) => {}
+            Err(e) => {
+                log::debug!("检查游戏进程状态失败: {}", e);
+                return None;
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return None;
+        }
+        tokio::time::sleep(EARLY_EXIT_POLL_INTERVAL).await;
+    }
+}
+
+/// 在后台线程持续读取子进程的 stderr，只保留末尾 `STDERR_TAIL_BYTES` 字节
+///
+/// 读取线程随进程结束（管道关闭）退出；不读取的话游戏写满管道缓冲区后会阻塞。
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub struct StderrTail {
+    buffer: Arc<Mutex<VecDeque<u8>>>,
+    finished: Arc<AtomicBool>,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+impl StderrTail {
+    pub fn collect<R: Read + Send + 'static>(mut reader: R) -> Self {
+        let buffer = Arc::new(Mutex::new(VecDeque::with_capacity(STDERR_TAIL_BYTES)));
+        let finished = Arc::new(AtomicBool::new(false));
+        let (thread_buffer, thread_finished) = (buffer.clone(), finished.clone());
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; 4096];
+            while let Ok(read) = reader.read(&mut chunk) {
+                if read == 0 {
+                    break;
+                }
+                push_tail(&mut thread_buffer.lock(), &chunk[..read]);
+            }
+            thread_finished.store(true, Ordering::Release);
+        });
+        Self { buffer, finished }
+    }
+
+    /// 等待读取线程读完剩余输出（最多 `STDERR_DRAIN_TIMEOUT`），返回 stderr 末尾，无输出时为 None
+    pub async fn finish(&self) -> Option<String> {
+        let deadline = tokio::time::Instant::now() + STDERR_DRAIN_TIMEOUT;
+        while !self.finished.load(Ordering::Acquire) && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let buffer = self.buffer.lock();
+        let (front, back) = buffer.as_slices();
+        let text = String::from_utf8_lossy(&[front, back].concat())
+            .trim()
+            .to_string();
+        (!text.is_empty()).then_some(text)
+    }
+}
+
+/// 追加输出并丢弃超出上限的最早部分
+fn push_tail(buffer: &mut VecDeque<u8>, data: &[u8]) {
+    let data = &data[data.len().saturating_sub(STDERR_TAIL_BYTES)..];
+    let overflow = (buffer.len() + data.len()).saturating_sub(STDERR_TAIL_BYTES);
+    buffer.drain(..overflow);
+    buffer.extend(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ntstatus_exit_codes_are_shown_in_hex() {
+        assert_eq!(format_exit_code(1), "1");
+        assert_eq!(format_exit_code(0xC000_0135_u32 as i32), "0xC0000135");
+    }
+
+    #[test]
+    fn push_tail_keeps_latest_bytes() {
+        let mut buffer = VecDeque::new();
+        push_tail(&mut buffer, &vec![b'a'; STDERR_TAIL_BYTES - 2]);
+        push_tail(&mut buffer, b"bcde");
+        assert_eq!(buffer.len(), STDERR_TAIL_BYTES);
+        assert!(buffer.iter().rev().take(4).eq(b"edcb".iter()));
+
+        push_tail(&mut buffer, &vec![b'z'; STDERR_TAIL_BYTES + 10]);
+        assert!(buffer.iter().all(|byte| *byte == b'z'));
+    }
+}
@@ -0,0 +1,49 @@
+use serde::Serialize;
+use std::fmt;
+
+/// 启动游戏的结构化错误
+///
+/// 序列化为 `{ code, message, ... }`，前端 `normalizeTauriError` 会读取 `code` 与 `detail`。
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum LaunchError {
+    /// 游戏所在的可移动/网络驱动器当前不可用
+    DriveUnavailable {
+        message: String,
+        /// 驱动器标识：Windows 盘符 / UNC 共享，Linux 挂载点名称（通常为卷标）
+        drive: String,
+        /// 游戏路径
+        detail: String,
+    },
+    /// 进程启动后立即以非零退出码退出（常见于缺少 DLL、区域设置不对）
+    ExitedEarly {
+        message: String,
+        exit_code: i32,
+        /// 进程 stderr 的末尾，无法获取时为 None
+        detail: Option<String>,
+    },
+    /// 其他启动错误
+    LaunchFailed { message: String },
+}
+
+impl From<String> for LaunchError {
+    fn from(message: String) -> Self {
+        Self::LaunchFailed { message }
+    }
+}
+
+impl From<&str> for LaunchError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DriveUnavailable { message, .. }
+            | Self::ExitedEarly { message, .. }
+            | Self::LaunchFailed { message } => f.write_str(message),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! 启动失败诊断事件
+//!
+//! 创建游戏进程失败时发送 `game-launch-failed` 事件，附带系统错误码、是否请求了 LE 转区 / Magpie
+//! 以及按错误码归类的提示，前端据此给出"以管理员身份运行""检查是否为 64 位游戏"等具体建议，
+//! 而不只是显示错误字符串。
+
+use log::warn;
+use serde::Serialize;
+use std::io;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// 启动失败的原因归类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchFailureHint {
+    /// 需要管理员权限（Windows 错误 740）
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    NeedsElevation,
+    /// 不是有效的可执行文件或架构不匹配（Windows 错误 193 / 216，Linux ENOEXEC）
+    BadExecutable,
+    /// 可执行文件或工作目录不存在
+    MissingFile,
+    /// 没有执行或访问权限
+    PermissionDenied,
+    /// 其他错误
+    Unknown,
+}
+
+/// `game-launch-failed` 事件的负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchFailure {
+    pub game_id: u32,
+    /// 系统错误码（Windows 为 GetLastError，Linux 为 errno），无法获取时为 None
+    pub os_error: Option<i32>,
+    pub le_requested: bool,
+    pub magpie_requested: bool,
+    pub hint: LaunchFailureHint,
+    pub message: String,
+}
+
+/// 按系统错误码与错误类型归类启动失败
+pub fn classify(os_error: Option<i32>, kind: Option<io::ErrorKind>) -> LaunchFailureHint {
+    #[cfg(target_os = "windows")]
+    match os_error {
+        Some(740) => return LaunchFailureHint::NeedsElevation,
+        Some(193) | Some(216) => return LaunchFailureHint::BadExecutable,
+        _ => {}
+    }
+    #[cfg(target_os = "linux")]
+    if os_error == Some(libc::ENOEXEC) {
+        return LaunchFailureHint::BadExecutable;
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let _ = os_error;
+
+    match kind {
+        Some(io::ErrorKind::NotFound) => LaunchFailureHint::MissingFile,
+        Some(io::ErrorKind::PermissionDenied) => LaunchFailureHint::PermissionDenied,
+        _ => LaunchFailureHint::Unknown,
+    }
+}
+
+impl LaunchFailure {
+    /// 由创建进程返回的 IO 错误构造
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn from_io(
+        game_id: u32,
+        error: &io::Error,
+        le_requested: bool,
+        magpie_requested: bool,
+        message: String,
+    ) -> Self {
+        let os_error = error.raw_os_error();
+        Self {
+            game_id,
+            os_error,
+            le_requested,
+            magpie_requested,
+            hint: classify(os_error, Some(error.kind())),
+            message,
+        }
+    }
+
+    /// 游戏可执行文件不存在
+    pub fn missing_file(
+        game_id: u32,
+        le_requested: bool,
+        magpie_requested: bool,
+        message: String,
+    ) -> Self {
+        Self {
+            game_id,
+            os_error: None,
+            le_requested,
+            magpie_requested,
+            hint: LaunchFailureHint::MissingFile,
+            message,
+        }
+    }
+
+    /// 发送 `game-launch-failed` 事件，失败只记录日志
+    pub fn emit<R: Runtime>(&self, app_handle: &AppHandle<R>) {
+        if let Err(e) = app_handle.emit("game-launch-failed", self) {
+            warn!("无法发送 game-launch-failed 事件: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_by_error_kind() {
+        assert_eq!(
+            classify(Some(2), Some(io::ErrorKind::NotFound)),
+            LaunchFailureHint::MissingFile
+        );
+        assert_eq!(
+            classify(None, Some(io::ErrorKind::PermissionDenied)),
+            LaunchFailureHint::PermissionDenied
+        );
+        assert_eq!(classify(None, None), LaunchFailureHint::Unknown);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn classifies_enoexec_as_bad_executable() {
+        let error = io::Error::from_raw_os_error(libc::ENOEXEC);
+        assert_eq!(
+            LaunchFailure::from_io(1, &error, false, false, String::new()).hint,
+            LaunchFailureHint::BadExecutable
+        );
+    }
+}
@@ -1,9 +1,30 @@
+use crate::database::db::DbState;
 use crate::database::repository::games_repository::GamesRepository;
-use crate::game::monitor::{get_connection, get_manager_proxy, monitor_game, stop_game_session};
-use log::{debug, info};
+use crate::entity::game_runner::RunnerKind;
+use crate::entity::games;
+use crate::entity::sandbox_config::SandboxConfig;
+use crate::game::launch::LaunchError;
+use crate::game::launch::args::merge_launch_args;
+use crate::game::launch::early_exit::{
+    EARLY_EXIT_POLL_INTERVAL, EARLY_EXIT_WINDOW, exited_early_error,
+};
+use crate::game::launch::failure::{LaunchFailure, LaunchFailureHint};
+use crate::game::launch::output::record_launch_attempt;
+use crate::game::launch::runner::{RunnerCommand, build_runner_command};
+use crate::game::launch::sandbox::{self, SandboxPaths};
+use crate::game::launch::uri;
+use crate::game::launch::volume::ensure_volume_available;
+use crate::game::monitor::{
+    ActiveSessionInfo, DEFAULT_STOP_GRACE_SECS, StopOutcome, StopStage, find_session,
+    get_connection, get_manager_proxy, is_game_unit_active, monitor_game, stop_game_session,
+};
+use crate::game::steam::find_steam_root;
+use crate::utils::metrics::CommandTimer;
+use log::{debug, info, warn};
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tauri::{AppHandle, Manager, Runtime, State, command};
 use tauri_plugin_store::StoreExt;
 
@@ -13,6 +34,28 @@ pub struct LaunchResult {
     message: String,
     process_id: Option<u32>,
     systemd_unit: Option<String>,
+    /// 游戏已在运行、未再次启动时为其现有会话（管理器未在监控时为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    running_session: Option<ActiveSessionInfo>,
+}
+
+/// 检查游戏是否已在运行：优先查找监控会话，其次检查游戏的 systemd 单元（如管理器重启前启动的游戏）
+async fn find_running_instance(game_id: u32) -> Option<LaunchResult> {
+    let running_session = find_session(game_id);
+    if running_session.is_none() && !is_game_unit_active(game_id).await {
+        return None;
+    }
+    let process_id = running_session.as_ref().map(|session| session.process_id);
+    Some(LaunchResult {
+        success: false,
+        message: match process_id {
+            Some(pid) => format!("游戏已在运行中 (PID {})", pid),
+            None => "游戏已在运行中".to_string(),
+        },
+        process_id,
+        systemd_unit: None,
+        running_session,
+    })
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,68 +63,130 @@ pub struct StopResult {
     success: bool,
     message: String,
     terminated_count: u32,
+    /// 游戏自行退出（graceful）还是被强制终止（forced）
+    stage: StopStage,
 }
 
+/// 启动游戏
+///
+/// 游戏设置了 `launch_uri` 时通过 `xdg-open` 打开该 URI（Steam 等商店），
+/// 并按可执行文件名查找商店拉起的进程，将其放入 systemd scope 后开始监控；
+/// 否则在 transient service 中直接启动 `localpath`。
+/// 单元在启动后约 2 秒内以非零退出码失败时返回 `LaunchError::ExitedEarly`（附 journald 中的输出），不开始监控。
+///
+/// 游戏已在运行时默认不再启动，返回 `success: false` 与现有会话；`force` 为 true 时跳过该检查，
+/// 但每个游戏只有一个同名 systemd 单元，单元仍在运行时再次启动会失败。
 #[command]
 pub async fn launch_game<R: Runtime>(
     app_handle: AppHandle<R>,
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: u32,
     args: Option<Vec<String>>,
-) -> Result<LaunchResult, String> {
-    let game = GamesRepository::find_by_id(db.inner(), game_id as i32)
+    wait_for_drive_secs: Option<u64>,
+    force: Option<bool>,
+) -> Result<LaunchResult, LaunchError> {
+    let _timer = CommandTimer::start("launch_game");
+    let db = db.get();
+    let game = GamesRepository::find_by_id(&db, game_id as i32)
         .await
         .map_err(|e| format!("查询游戏失败: {}", e))?
         .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+    if !force.unwrap_or(false)
+        && let Some(result) = find_running_instance(game_id).await
+    {
+        info!("游戏已在运行，跳过启动 game_id={}", game_id);
+        return Ok(result);
+    }
+    if let Some(launch_uri) = uri::launch_uri(&game) {
+        return launch_via_uri(app_handle, &db, &game, launch_uri).await;
+    }
+    let args = merge_launch_args(&game, args)?;
     let game_path = game.localpath.ok_or_else(|| "游戏路径未设置".to_string())?;
 
     if !Path::new(&game_path).exists() {
-        return Err(format!("游戏可执行文件不存在: {}", game_path));
+        // 先区分"所在驱动器未连接"与"文件不存在"
+        ensure_volume_available(Path::new(&game_path), wait_for_drive_secs).await?;
+        if !Path::new(&game_path).exists() {
+            let message = format!("游戏可执行文件不存在: {}", game_path);
+            LaunchFailure::missing_file(game_id, false, false, message.clone()).emit(&app_handle);
+            return Err(message.into());
+        }
     }
 
     let game_dir = match Path::new(&game_path).parent() {
         Some(dir) => dir,
-        None => return Err("无法获取游戏目录路径".to_string()),
+        None => return Err("无法获取游戏目录路径".into()),
     };
 
     let exe_name = match Path::new(&game_path).file_name() {
         Some(name) => name,
-        None => return Err("无法获取游戏可执行文件名".to_string()),
+        None => return Err("无法获取游戏可执行文件名".into()),
     };
 
     let systemd_unit_name = format!("reina_game_{}.service", game_id);
     let _ = check_unit_or_reset_failed(&systemd_unit_name).await;
 
-    let linux_launch_command = {
-        let cmd = app_handle
-            .store("settings.json")
-            .ok()
-            .and_then(|store| store.get("linux_launch_command"))
-            .and_then(|v| v.as_str().map(|s| s.to_string()))
-            .unwrap_or_else(|| "wine".to_string());
-        expand_path(&app_handle, &cmd)
+    let linux_launch_command = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("linux_launch_command"))
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "wine".to_string());
+
+    // 确定执行命令和参数：游戏设置了兼容层时按其构造，
+    // 否则 .exe 文件用 linux_launch_command 运行，其他文件直接运行
+    let home_dir = app_handle.path().home_dir().ok();
+    let steam_root = match game.runner.as_ref().map(|runner| runner.kind) {
+        Some(RunnerKind::Proton) => find_steam_root(&app_handle),
+        _ => None,
     };
-
-    // 确定执行命令和参数
-    // 对于 .exe 文件: 用 linux_launch_command 作为命令，游戏文件作为参数
-    // 对于其他文件: 直接用文件本身作为命令
-    let (exec_path, exec_args) = if exe_name.to_string_lossy().ends_with(".exe") {
-        // .exe 文件: wine game.exe [user_args...]
-        let mut cmd_args = vec![linux_launch_command.clone(), game_path.clone()];
-        if let Some(ref arguments) = args {
-            cmd_args.extend(arguments.iter().cloned());
+    let RunnerCommand {
+        exec_path,
+        argv: exec_args,
+        env: runner_env,
+    } = build_runner_command(
+        game.runner.as_ref(),
+        &linux_launch_command,
+        &game_path,
+        args.as_deref().unwrap_or_default(),
+        home_dir.as_deref(),
+        steam_root.as_deref(),
+    )?;
+    if let Some(prefix) = runner_env
+        .iter()
+        .find(|(key, _)| key == "WINEPREFIX" || key == "STEAM_COMPAT_DATA_PATH")
+        .map(|(_, prefix)| prefix)
+    {
+        // wine 不会创建多级目录，Proton 要求 compatdata 目录已存在
+        if let Err(e) = std::fs::create_dir_all(prefix) {
+            warn!("创建兼容层前缀目录失败 {}: {}", prefix, e);
         }
-        (linux_launch_command.clone(), cmd_args)
-    } else {
-        // 其他文件: ./game [user_args...]
-        let mut cmd_args = vec![game_path.clone()];
-        if let Some(ref arguments) = args {
-            cmd_args.extend(arguments.iter().cloned());
+    }
+
+    // 开启沙盒时用 firejail / bubblewrap 包裹上面的命令
+    let (exec_path, exec_args) = match game.sandbox.as_ref().filter(|sandbox| sandbox.enabled) {
+        Some(sandbox_config) => {
+            let tool_path = sandbox::find_tool(sandbox_config.tool)?;
+            let paths = collect_sandbox_paths(
+                sandbox_config,
+                game_dir,
+                game.savepath.as_deref(),
+                (exec_path != game_path).then_some(exec_path.as_str()),
+                &runner_env,
+                home_dir.as_deref(),
+            );
+            debug!("沙盒放行路径 game_id={} paths={:?}", game_id, paths);
+            let argv = sandbox::wrap_command(&tool_path, sandbox_config, &paths, exec_args);
+            (tool_path.to_string_lossy().into_owned(), argv)
         }
-        (game_path.clone(), cmd_args)
+        None => (exec_path, exec_args),
     };
-    // 从当前进程导入环境变量
-    let env_vars: Vec<String> = std::env::vars()
+
+    // 从当前进程导入环境变量，再叠加兼容层的变量
+    let mut env_map: std::collections::BTreeMap<String, String> = std::env::vars().collect();
+    env_map.extend(runner_env);
+    let env_vars: Vec<String> = env_map
+        .into_iter()
         .map(|(k, v)| format!("{}={}", k, v))
         .collect();
 
@@ -152,6 +257,7 @@ pub async fn launch_game<R: Runtime>(
     ];
     // aux 参数（空）
     let aux: Vec<(String, Vec<(String, OwnedValue)>)> = Vec::new();
+    let launched_at = chrono::Utc::now().timestamp();
 
     match manager
         .start_transient_unit(
@@ -168,8 +274,17 @@ pub async fn launch_game<R: Runtime>(
                 game_id, systemd_unit_name, job_path
             );
 
-            // 等待一小段时间让进程启动
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            // 启动后立即失败（缺少运行库、wine 前缀损坏等）时不再开始监控
+            if let Some(exit_code) = wait_for_unit_early_exit(&systemd_unit_name).await {
+                let stderr = read_unit_journal(&systemd_unit_name, launched_at).await;
+                let error = exited_early_error(&exe_name.to_string_lossy(), exit_code, stderr);
+                warn!(
+                    "游戏启动后立即退出 game_id={} unit={} exit_code={}",
+                    game_id, systemd_unit_name, exit_code
+                );
+                record_launch_attempt(&db, game_id, false, None, &error.to_string(), None).await;
+                return Err(error);
+            }
 
             // 获取 service 的主进程 PID
             let process_id = get_service_main_pid(&systemd_unit_name).await.unwrap_or(0);
@@ -184,21 +299,272 @@ pub async fn launch_game<R: Runtime>(
             )
             .await;
 
+            let message = format!(
+                "成功启动游戏: {}，工作目录: {:?}",
+                exe_name.to_string_lossy(),
+                game_dir
+            );
+            record_launch_attempt(&db, game_id, true, Some(process_id), &message, None).await;
+
             Ok(LaunchResult {
                 success: true,
-                message: format!(
-                    "成功启动游戏: {}，工作目录: {:?}",
-                    exe_name.to_string_lossy(),
-                    game_dir
-                ),
+                message,
                 process_id: Some(process_id),
                 systemd_unit: Some(systemd_unit_name),
+                running_session: None,
             })
         }
-        Err(e) => Err(format!("启动游戏失败: {}，目录: {:?}", e, game_dir)),
+        Err(e) => {
+            let message = format!("启动游戏失败: {}，目录: {:?}", e, game_dir);
+            // systemd 通过 D-Bus 返回错误，没有可用的系统错误码
+            LaunchFailure {
+                game_id,
+                os_error: None,
+                le_requested: false,
+                magpie_requested: false,
+                hint: LaunchFailureHint::Unknown,
+                message: message.clone(),
+            }
+            .emit(&app_handle);
+            record_launch_attempt(&db, game_id, false, None, &message, None).await;
+            Err(message.into())
+        }
     }
 }
 
+/// 收集沙盒需要放行的路径
+///
+/// # Arguments
+/// * `compat_exec` - 通过 wine / Proton 运行时为兼容层的可执行文件，直接运行时为 None
+fn collect_sandbox_paths(
+    config: &SandboxConfig,
+    game_dir: &Path,
+    savepath: Option<&str>,
+    compat_exec: Option<&str>,
+    runner_env: &[(String, String)],
+    home: Option<&Path>,
+) -> SandboxPaths {
+    let expand = |path: &str| match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches('/')),
+        _ => PathBuf::from(path),
+    };
+    let env_value = |name: &str| {
+        runner_env
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| PathBuf::from(value))
+    };
+
+    let mut writable = vec![game_dir.to_path_buf()];
+    writable.extend(
+        savepath
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(expand),
+    );
+    let prefix = env_value("WINEPREFIX").or_else(|| env_value("STEAM_COMPAT_DATA_PATH"));
+    match (prefix, compat_exec, home) {
+        (Some(prefix), _, _) => writable.push(prefix),
+        // 未设置前缀的 wine 使用默认的 ~/.wine
+        (None, Some(_), Some(home)) => writable.push(home.join(".wine")),
+        _ => {}
+    }
+    writable.extend(
+        config
+            .writable_paths
+            .iter()
+            .map(|path| path.trim())
+            .filter(|path| !path.is_empty())
+            .map(expand),
+    );
+
+    let mut readonly = Vec::new();
+    // 兼容层可能安装在主目录中（Lutris、compatibilitytools.d），放行其安装目录
+    if let Some(exec) = compat_exec.map(Path::new).filter(|exec| exec.is_absolute()) {
+        let dir = exec.parent().map(|dir| match dir.file_name() {
+            Some(name) if name == "bin" => dir.parent().unwrap_or(dir),
+            _ => dir,
+        });
+        readonly.extend(dir.map(Path::to_path_buf));
+    }
+    readonly.extend(env_value("STEAM_COMPAT_CLIENT_INSTALL_PATH"));
+    readonly.extend(
+        std::env::var_os("XAUTHORITY")
+            .map(PathBuf::from)
+            .or_else(|| home.map(|home| home.join(".Xauthority"))),
+    );
+
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    let runtime_sockets = runtime_dir
+        .as_deref()
+        .map(|dir| {
+            let wayland_display = std::env::var("WAYLAND_DISPLAY").ok();
+            sandbox::runtime_sockets(dir, wayland_display.as_deref())
+        })
+        .unwrap_or_default();
+
+    SandboxPaths {
+        home: home.map(Path::to_path_buf),
+        writable,
+        readonly,
+        runtime_dir,
+        runtime_sockets,
+    }
+}
+
+/// 通过商店 URI 启动游戏，并在后台等待游戏进程出现后开始监控
+async fn launch_via_uri<R: Runtime>(
+    app_handle: AppHandle<R>,
+    db: &DatabaseConnection,
+    game: &games::Model,
+    launch_uri: &str,
+) -> Result<LaunchResult, LaunchError> {
+    let game_id = game.id as u32;
+    let process_name = uri::process_name(game)
+        .ok_or_else(|| "通过 URI 启动需要设置游戏进程名或游戏路径，以便查找游戏进程".to_string())?;
+
+    if let Err(message) = uri::open_uri(launch_uri) {
+        record_launch_attempt(db, game_id, false, None, &message, None).await;
+        return Err(message.into());
+    }
+    info!(
+        "已通过 URI 启动游戏 game_id={} uri={} process_name={}",
+        game_id, launch_uri, process_name
+    );
+
+    uri::spawn_process_watch(
+        app_handle,
+        game_id,
+        process_name.clone(),
+        find_process_id_by_name,
+        move |app_handle, pid| async move {
+            // 商店拉起的进程不在我们的 service 中，放入独立的 scope 以便按 unit 监控和停止
+            match start_game_scope(game_id, pid).await {
+                Ok(scope_name) => monitor_game(app_handle, game_id, pid, scope_name).await,
+                Err(e) => warn!("无法为游戏进程创建 systemd scope，跳过监控: {}", e),
+            }
+        },
+    );
+
+    let message = format!(
+        "已通过 URI 启动游戏，等待进程 {} 出现后开始计时",
+        process_name
+    );
+    record_launch_attempt(db, game_id, true, None, &message, None).await;
+
+    Ok(LaunchResult {
+        success: true,
+        message,
+        process_id: None,
+        systemd_unit: None,
+        running_session: None,
+    })
+}
+
+/// 按可执行文件名查找正在运行的进程 PID（不区分大小写）
+///
+/// 同时匹配 `/proc/<pid>/exe` 和命令行参数，wine 运行的游戏 exe 只出现在参数中。
+fn find_process_id_by_name(process_name: &str) -> Option<u32> {
+    let own_pid = std::process::id();
+    let matches = |value: &str| {
+        value
+            .rsplit(['/', '\\'])
+            .next()
+            .is_some_and(|file_name| file_name.eq_ignore_ascii_case(process_name))
+    };
+
+    std::fs::read_dir("/proc")
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| *pid != own_pid)
+        .find(|pid| {
+            let exe_matches = std::fs::read_link(format!("/proc/{}/exe", pid))
+                .is_ok_and(|exe| matches(&exe.to_string_lossy()));
+            exe_matches
+                || std::fs::read(format!("/proc/{}/cmdline", pid)).is_ok_and(|cmdline| {
+                    cmdline
+                        .split(|byte| *byte == 0)
+                        .filter(|arg| !arg.is_empty())
+                        .any(|arg| matches(&String::from_utf8_lossy(arg)))
+                })
+        })
+}
+
+/// 将已运行的游戏进程放入 transient scope，返回 scope 名称
+async fn start_game_scope(game_id: u32, pid: u32) -> Result<String, String> {
+    use zbus::zvariant::{OwnedValue, Value};
+
+    let scope_name = format!("reina_game_{}.scope", game_id);
+    let _ = check_unit_or_reset_failed(&scope_name).await;
+
+    let manager = get_manager_proxy()
+        .await
+        .map_err(|e| format!("连接到 systemd 失败: {}", e))?;
+    let pids_prop = (
+        "PIDs".to_string(),
+        OwnedValue::try_from(Value::from(vec![pid]))
+            .map_err(|e| format!("构建 PIDs 属性失败: {}", e))?,
+    );
+    let aux: Vec<(String, Vec<(String, OwnedValue)>)> = Vec::new();
+
+    manager
+        .start_transient_unit(scope_name.clone(), "fail".to_string(), vec![pids_prop], aux)
+        .await
+        .map_err(|e| format!("创建 scope {} 失败: {}", scope_name, e))?;
+
+    Ok(scope_name)
+}
+
+/// 观察刚启动的 transient service，在观察期内进入 failed 状态时返回主进程的退出码
+///
+/// 以 0 退出的 transient service 会被 systemd 立即回收（查不到单元），视为启动器已拉起游戏。
+async fn wait_for_unit_early_exit(unit_name: &str) -> Option<i32> {
+    let manager = get_manager_proxy().await.ok()?;
+    let conn = get_connection().await.ok()?;
+    let deadline = tokio::time::Instant::now() + EARLY_EXIT_WINDOW;
+    loop {
+        tokio::time::sleep(EARLY_EXIT_POLL_INTERVAL).await;
+        let Ok(unit_path) = manager.get_unit(unit_name.to_string()).await else {
+            return None;
+        };
+        let unit = zbus_systemd::systemd1::UnitProxy::new(conn, unit_path.clone())
+            .await
+            .ok()?;
+        match unit.active_state().await.ok()?.as_str() {
+            "failed" => {
+                let service = zbus_systemd::systemd1::ServiceProxy::new(conn, unit_path)
+                    .await
+                    .ok()?;
+                return service
+                    .exec_main_status()
+                    .await
+                    .ok()
+                    .filter(|status| *status != 0);
+            }
+            "inactive" => return None,
+            _ => {}
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return None;
+        }
+    }
+}
+
+/// 读取 transient service 自 `since`（秒级时间戳）起写入 journald 的最后若干行输出
+async fn read_unit_journal(unit_name: &str, since: i64) -> Option<String> {
+    let output = tokio::process::Command::new("journalctl")
+        .args(["--user", "--no-pager", "-o", "cat", "-n", "50", "-u"])
+        .arg(unit_name)
+        .arg(format!("--since=@{}", since))
+        .output()
+        .await
+        .map_err(|e| debug!("读取 journald 输出失败: {}", e))
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
 /// 获取 systemd service 的主进程 PID
 async fn get_service_main_pid(unit_name: &str) -> Result<u32, String> {
     let manager = get_manager_proxy()
@@ -228,30 +594,37 @@ async fn get_service_main_pid(unit_name: &str) -> Result<u32, String> {
     Ok(main_pid)
 }
 
+/// 停止游戏
+///
+/// 先停止 systemd 单元（SIGTERM），`grace_secs`（默认 5 秒）内未停止时发送 SIGKILL。
 #[command]
-pub async fn stop_game(game_id: u32) -> Result<StopResult, String> {
-    match stop_game_session(game_id).await {
-        Ok(terminated_count) => Ok(StopResult {
+pub async fn stop_game(game_id: u32, grace_secs: Option<u64>) -> Result<StopResult, String> {
+    let _timer = CommandTimer::start("stop_game");
+    let grace = Duration::from_secs(grace_secs.unwrap_or(DEFAULT_STOP_GRACE_SECS));
+    match stop_game_session(game_id, grace).await {
+        Ok(StopOutcome {
+            stage,
+            terminated_count,
+        }) => Ok(StopResult {
             success: true,
-            message: format!("成功停止游戏 {}，终止进程数: {}", game_id, terminated_count),
+            message: match stage {
+                StopStage::Graceful => {
+                    format!("成功停止游戏 {}，终止进程数: {}", game_id, terminated_count)
+                }
+                StopStage::Forced => format!(
+                    "游戏 {} 未在 {} 秒内退出，已强制终止，进程数: {}",
+                    game_id,
+                    grace.as_secs(),
+                    terminated_count
+                ),
+            },
             terminated_count,
+            stage,
         }),
         Err(e) => Err(format!("停止游戏 {} 失败: {}", game_id, e)),
     }
 }
 
-fn expand_path<R: Runtime>(app_handle: &AppHandle<R>, path: &str) -> String {
-    if path.starts_with('~') {
-        if let Ok(home_dir) = app_handle.path().home_dir() {
-            path.replacen('~', &home_dir.to_string_lossy(), 1)
-        } else {
-            path.to_string()
-        }
-    } else {
-        path.to_string()
-    }
-}
-
 /// 检查 systemd unit 的状态，如果是 failed 则重置它
 /// 返回 bool 值表示 unit 是否已经存在
 /// # Arguments
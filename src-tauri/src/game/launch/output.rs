@@ -0,0 +1,139 @@
+//! 启动输出捕获与启动记录
+//!
+//! 直接 spawn 的游戏进程（目前仅 Windows）可选择把 stdout/stderr 重定向到单次启动的日志文件，
+//! 进程退出后把退出码和最后若干行输出写回 `launch_attempts` 记录。
+//! Linux 通过 systemd 临时单元启动，输出由 journald 记录，这里只写入启动记录。
+
+use crate::database::repository::launch_attempts_repository::LaunchAttemptsRepository;
+use crate::utils::usage_stats::{UsageFeature, record_usage};
+use sea_orm::DatabaseConnection;
+use std::path::Path;
+#[cfg(target_os = "windows")]
+use {
+    chrono::Local,
+    std::fs::{self, File},
+    std::path::PathBuf,
+};
+
+/// 写回启动记录的输出行数
+#[cfg(target_os = "windows")]
+const OUTPUT_TAIL_LINES: usize = 50;
+
+/// 单次启动的输出日志文件
+#[cfg(target_os = "windows")]
+pub struct OutputCapture {
+    pub log_path: PathBuf,
+    file: File,
+}
+
+#[cfg(target_os = "windows")]
+impl OutputCapture {
+    /// 创建 `<base>/launch_logs/game_{id}_{时间}.log`
+    pub fn create(game_id: u32) -> Result<Self, String> {
+        let log_dir = reina_path::get_base_data_dir()?.join(reina_path::LAUNCH_LOG_SUBDIR);
+        fs::create_dir_all(&log_dir).map_err(|e| format!("创建启动日志目录失败: {}", e))?;
+
+        let log_path = log_dir.join(format!(
+            "game_{}_{}.log",
+            game_id,
+            Local::now().format("%Y%m%d_%H%M%S")
+        ));
+        let file = File::create(&log_path).map_err(|e| format!("创建启动日志文件失败: {}", e))?;
+
+        Ok(Self { log_path, file })
+    }
+
+    /// 返回可分别交给 stdout/stderr 的文件句柄
+    pub fn stdio_handles(&self) -> Result<(File, File), String> {
+        let stdout = self
+            .file
+            .try_clone()
+            .map_err(|e| format!("复制启动日志句柄失败: {}", e))?;
+        let stderr = self
+            .file
+            .try_clone()
+            .map_err(|e| format!("复制启动日志句柄失败: {}", e))?;
+        Ok((stdout, stderr))
+    }
+}
+
+/// 读取日志文件的最后若干行，文件为空或读取失败时返回 None
+#[cfg(target_os = "windows")]
+pub fn read_output_tail(path: &Path) -> Option<String> {
+    let content = fs::read(path).ok()?;
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.lines().collect();
+    let tail = lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n");
+
+    (!tail.trim().is_empty()).then_some(tail)
+}
+
+/// 写入启动记录；记录失败不影响启动流程，只输出警告
+pub async fn record_launch_attempt(
+    db: &DatabaseConnection,
+    game_id: u32,
+    success: bool,
+    process_id: Option<u32>,
+    message: &str,
+    log_path: Option<&Path>,
+) -> Option<i32> {
+    if success {
+        record_usage(db, UsageFeature::GameLaunch).await;
+    }
+    match LaunchAttemptsRepository::record_attempt(
+        db,
+        game_id as i32,
+        success,
+        process_id,
+        Some(message.to_string()),
+        log_path.map(|path| path.to_string_lossy().to_string()),
+    )
+    .await
+    {
+        Ok(attempt_id) => Some(attempt_id),
+        Err(e) => {
+            log::warn!("写入启动记录失败 game_id={}: {}", game_id, e);
+            None
+        }
+    }
+}
+
+/// 等待捕获输出的进程退出，并回写启动记录
+#[cfg(target_os = "windows")]
+pub fn watch_captured_process(
+    db: DatabaseConnection,
+    attempt_id: i32,
+    mut child: std::process::Child,
+    log_path: PathBuf,
+) {
+    tokio::spawn(async move {
+        let exit_code = tokio::task::spawn_blocking(move || child.wait())
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .and_then(|status| status.code());
+
+        log::debug!(
+            "捕获输出的启动进程已退出 attempt_id={} exit_code={:?}",
+            attempt_id,
+            exit_code
+        );
+        complete_launch_attempt(&db, attempt_id, exit_code, &log_path).await;
+    });
+}
+
+/// 进程退出后回写退出码与输出末尾
+#[cfg(target_os = "windows")]
+async fn complete_launch_attempt(
+    db: &DatabaseConnection,
+    attempt_id: i32,
+    exit_code: Option<i32>,
+    log_path: &Path,
+) {
+    let output_tail = read_output_tail(log_path);
+    if let Err(e) =
+        LaunchAttemptsRepository::complete_attempt(db, attempt_id, exit_code, output_tail).await
+    {
+        log::warn!("回写启动记录失败 attempt_id={}: {}", attempt_id, e);
+    }
+}
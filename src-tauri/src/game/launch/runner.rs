@@ -0,0 +1,309 @@
+//! Linux 兼容层（wine / Proton / 原生）
+//!
+//! 游戏设置了 `runner` 时按其中的类型、路径与前缀构造启动命令和环境变量；
+//! 未设置时保持原来的行为：`.exe` 交给全局的 `linux_launch_command`，其他文件直接运行。
+//! `list_available_runners` 扫描系统 PATH、Lutris / Bottles / Heroic 与 Steam 的常见安装位置，
+//! 供前端选择已安装的 wine 与 Proton 版本。
+
+use crate::entity::game_runner::{GameRunner, RunnerKind};
+use crate::utils::metrics::CommandTimer;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// 交给 systemd 启动的命令
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, PartialEq, Eq)]
+pub struct RunnerCommand {
+    pub exec_path: String,
+    /// 参数列表（含 argv[0]）
+    pub argv: Vec<String>,
+    /// 在当前进程环境变量之上追加或覆盖的变量
+    pub env: Vec<(String, String)>,
+}
+
+/// 展开以 `~` 开头的路径
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn expand_home(path: &str, home: Option<&Path>) -> String {
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) => format!("{}{}", home.to_string_lossy(), rest),
+        _ => path.to_string(),
+    }
+}
+
+/// 按游戏的兼容层设置构造启动命令
+///
+/// # Arguments
+/// * `runner` - 游戏的兼容层设置，None 时沿用旧行为
+/// * `default_wine` - 全局的 linux_launch_command
+/// * `steam_root` - Steam 安装目录，Proton 需要通过 STEAM_COMPAT_CLIENT_INSTALL_PATH 找到它
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn build_runner_command(
+    runner: Option<&GameRunner>,
+    default_wine: &str,
+    game_path: &str,
+    args: &[String],
+    home: Option<&Path>,
+    steam_root: Option<&Path>,
+) -> Result<RunnerCommand, String> {
+    let is_exe = game_path.to_lowercase().ends_with(".exe");
+    let kind = match runner {
+        Some(runner) => runner.kind,
+        None if is_exe => RunnerKind::Wine,
+        None => RunnerKind::Native,
+    };
+    let path = runner
+        .and_then(|runner| runner.path.as_deref())
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(|path| expand_home(path, home));
+    let prefix = runner
+        .and_then(|runner| runner.prefix.as_deref())
+        .map(str::trim)
+        .filter(|prefix| !prefix.is_empty())
+        .map(|prefix| expand_home(prefix, home));
+
+    let mut env = Vec::new();
+    let (exec_path, mut argv) = match kind {
+        RunnerKind::Native => (game_path.to_string(), vec![game_path.to_string()]),
+        RunnerKind::Wine => {
+            let wine = path.unwrap_or_else(|| expand_home(default_wine, home));
+            if let Some(prefix) = prefix {
+                env.push(("WINEPREFIX".to_string(), prefix));
+            }
+            (wine.clone(), vec![wine, game_path.to_string()])
+        }
+        RunnerKind::Proton => {
+            let path =
+                path.ok_or_else(|| "使用 Proton 启动需要设置 Proton 安装目录".to_string())?;
+            let prefix = prefix.ok_or_else(|| "使用 Proton 启动需要设置前缀目录".to_string())?;
+            // 允许直接填写 proton 脚本或其所在目录
+            let script = if path.ends_with("/proton") {
+                path
+            } else {
+                format!("{}/proton", path.trim_end_matches('/'))
+            };
+            env.push(("STEAM_COMPAT_DATA_PATH".to_string(), prefix));
+            if let Some(steam_root) = steam_root {
+                env.push((
+                    "STEAM_COMPAT_CLIENT_INSTALL_PATH".to_string(),
+                    steam_root.to_string_lossy().into_owned(),
+                ));
+            }
+            (
+                script.clone(),
+                vec![script, "run".to_string(), game_path.to_string()],
+            )
+        }
+    };
+    argv.extend(args.iter().cloned());
+
+    // 用户填写的变量放在最后，覆盖自动设置的同名变量
+    if let Some(runner) = runner {
+        env.retain(|(key, _)| !runner.env.contains_key(key));
+        env.extend(
+            runner
+                .env
+                .iter()
+                .filter(|(key, _)| !key.trim().is_empty())
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+    }
+
+    Ok(RunnerCommand {
+        exec_path,
+        argv,
+        env,
+    })
+}
+
+/// 检测到的兼容层
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailableRunner {
+    pub kind: RunnerKind,
+    /// 显示名称（版本目录名）
+    pub name: String,
+    /// 可填入 `GameRunner.path` 的路径
+    pub path: String,
+}
+
+/// 在 `dir` 的每个子目录下查找 `relative` 指向的文件
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn scan_versions(dir: &Path, relative: &str) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut found: Vec<(String, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().join(relative).is_file())
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().into_owned(),
+                entry.path(),
+            )
+        })
+        .collect();
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    found
+}
+
+#[cfg(target_os = "linux")]
+fn detect_runners(home: Option<PathBuf>) -> Vec<AvailableRunner> {
+    let mut runners = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut push = |kind, name: String, path: PathBuf| {
+        let key = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if seen.insert(key) {
+            runners.push(AvailableRunner {
+                kind,
+                name,
+                path: path.to_string_lossy().into_owned(),
+            });
+        }
+    };
+
+    // 系统 PATH 中的 wine
+    if let Some(paths) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&paths) {
+            for binary in ["wine", "wine64"] {
+                let path = dir.join(binary);
+                if path.is_file() {
+                    push(RunnerKind::Wine, binary.to_string(), path);
+                }
+            }
+        }
+    }
+
+    let Some(home) = home else {
+        return runners;
+    };
+
+    // Lutris / Bottles / Heroic 管理的 wine 版本
+    for dir in [
+        ".local/share/lutris/runners/wine",
+        ".var/app/net.lutris.Lutris/data/lutris/runners/wine",
+        ".local/share/bottles/runners",
+        ".var/app/com.usebottles.bottles/data/bottles/runners",
+        ".config/heroic/tools/wine",
+    ] {
+        for (name, version_dir) in scan_versions(&home.join(dir), "bin/wine") {
+            push(RunnerKind::Wine, name, version_dir.join("bin/wine"));
+        }
+    }
+
+    // Steam 自带的 Proton 与 compatibilitytools.d 中的第三方版本（GE-Proton 等）
+    for dir in [
+        ".steam/steam/steamapps/common",
+        ".local/share/Steam/steamapps/common",
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam/steamapps/common",
+        ".steam/root/compatibilitytools.d",
+        ".local/share/Steam/compatibilitytools.d",
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam/compatibilitytools.d",
+        ".config/heroic/tools/proton",
+    ] {
+        for (name, version_dir) in scan_versions(&home.join(dir), "proton") {
+            push(RunnerKind::Proton, name, version_dir);
+        }
+    }
+
+    runners
+}
+
+/// 列出检测到的 wine 与 Proton 安装（仅 Linux，其他平台返回空列表）
+///
+/// # Returns
+/// * `Result<Vec<AvailableRunner>, String>` - 检测到的兼容层，按位置分组、版本名排序
+#[tauri::command]
+pub async fn list_available_runners(app: AppHandle) -> Result<Vec<AvailableRunner>, String> {
+    let _timer = CommandTimer::start("list_available_runners");
+    #[cfg(target_os = "linux")]
+    {
+        use tauri::Manager;
+
+        let home = app.path().home_dir().ok();
+        tokio::task::spawn_blocking(move || detect_runners(home))
+            .await
+            .map_err(|e| format!("检测兼容层失败: {}", e))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = app;
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runner(kind: RunnerKind, path: Option<&str>, prefix: Option<&str>) -> GameRunner {
+        GameRunner {
+            kind,
+            path: path.map(str::to_string),
+            prefix: prefix.map(str::to_string),
+            env: Default::default(),
+        }
+    }
+
+    #[test]
+    fn without_runner_keeps_global_wine_for_exe() {
+        let args = vec!["-windowed".to_string()];
+        let command =
+            build_runner_command(None, "wine", "/games/a.exe", &args, None, None).unwrap();
+        assert_eq!(command.exec_path, "wine");
+        assert_eq!(command.argv, ["wine", "/games/a.exe", "-windowed"]);
+        assert!(command.env.is_empty());
+
+        let command = build_runner_command(None, "wine", "/games/a.sh", &[], None, None).unwrap();
+        assert_eq!(command.argv, ["/games/a.sh"]);
+    }
+
+    #[test]
+    fn wine_runner_sets_prefix() {
+        let home = Path::new("/home/user");
+        let wine = runner(RunnerKind::Wine, Some("~/wine-ge/bin/wine"), Some("~/pfx"));
+        let command =
+            build_runner_command(Some(&wine), "wine", "/g/a.exe", &[], Some(home), None).unwrap();
+        assert_eq!(command.exec_path, "/home/user/wine-ge/bin/wine");
+        assert_eq!(
+            command.env,
+            [("WINEPREFIX".to_string(), "/home/user/pfx".to_string())]
+        );
+    }
+
+    #[test]
+    fn proton_runner_uses_run_verb_and_user_env_wins() {
+        let mut proton = runner(RunnerKind::Proton, Some("/proton/GE-9/"), Some("/pfx"));
+        proton
+            .env
+            .insert("STEAM_COMPAT_DATA_PATH".to_string(), "/other".to_string());
+        proton.env.insert("DXVK_HUD".to_string(), "fps".to_string());
+        let command = build_runner_command(
+            Some(&proton),
+            "wine",
+            "/g/a.exe",
+            &[],
+            None,
+            Some(Path::new("/steam")),
+        )
+        .unwrap();
+        assert_eq!(command.argv, ["/proton/GE-9/proton", "run", "/g/a.exe"]);
+        assert_eq!(
+            command.env,
+            [
+                (
+                    "STEAM_COMPAT_CLIENT_INSTALL_PATH".to_string(),
+                    "/steam".to_string()
+                ),
+                ("DXVK_HUD".to_string(), "fps".to_string()),
+                ("STEAM_COMPAT_DATA_PATH".to_string(), "/other".to_string()),
+            ]
+        );
+
+        let missing_prefix = runner(RunnerKind::Proton, Some("/proton"), None);
+        assert!(
+            build_runner_command(Some(&missing_prefix), "wine", "/g/a.exe", &[], None, None)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! 在 firejail / bubblewrap 沙盒中启动游戏（仅 Linux）
+//!
+//! 用于运行来源不明的游戏：沙盒内主目录被替换为空目录，只挂载游戏目录、存档目录、
+//! 兼容层前缀和用户指定的目录，关闭网络时游戏无法联网。
+//! 两种工具都以游戏命令作为子进程运行，systemd 单元的主进程变为沙盒工具本身，
+//! 监控与停止仍按单元进行，不受影响。
+
+use crate::entity::sandbox_config::{SandboxConfig, SandboxTool};
+use std::path::{Path, PathBuf};
+
+/// 沙盒需要放行的路径
+#[derive(Debug, Default)]
+pub struct SandboxPaths {
+    pub home: Option<PathBuf>,
+    /// 可读写的目录（游戏目录、存档目录、前缀等）
+    pub writable: Vec<PathBuf>,
+    /// 只读放行的路径（位于主目录中的兼容层、X11 授权文件等）
+    pub readonly: Vec<PathBuf>,
+    /// XDG_RUNTIME_DIR，沙盒内替换为空目录
+    pub runtime_dir: Option<PathBuf>,
+    /// 只读放行的运行时套接字（Wayland、PulseAudio、PipeWire）
+    ///
+    /// 不放行整个运行时目录：其中的 D-Bus 会话总线与 systemd 用户套接字
+    /// 可以让游戏在沙盒外启动进程。
+    pub runtime_sockets: Vec<PathBuf>,
+}
+
+/// 游戏需要的运行时套接字
+///
+/// `wayland_display` 为 WAYLAND_DISPLAY 的值，可以是绝对路径或相对于运行时目录的名称。
+pub fn runtime_sockets(runtime_dir: &Path, wayland_display: Option<&str>) -> Vec<PathBuf> {
+    let mut sockets: Vec<PathBuf> = wayland_display
+        .map(str::trim)
+        .filter(|display| !display.is_empty())
+        .map(|display| runtime_dir.join(display))
+        .into_iter()
+        .collect();
+    sockets.push(runtime_dir.join("pulse").join("native"));
+    sockets.push(runtime_dir.join("pipewire-0"));
+    sockets
+}
+
+/// 在 PATH 中查找沙盒工具
+pub fn find_tool(tool: SandboxTool) -> Result<PathBuf, String> {
+    let binary = match tool {
+        SandboxTool::Firejail => "firejail",
+        SandboxTool::Bubblewrap => "bwrap",
+    };
+    std::env::var_os("PATH")
+        .and_then(|paths| {
+            std::env::split_paths(&paths)
+                .map(|dir| dir.join(binary))
+                .find(|path| path.is_file())
+        })
+        .ok_or_else(|| format!("未找到沙盒工具 {}，请先安装", binary))
+}
+
+fn arg(prefix: &str, path: &Path) -> String {
+    format!("{}{}", prefix, path.to_string_lossy())
+}
+
+/// 用沙盒工具包裹游戏命令，返回新的参数列表（含 argv[0]）
+pub fn wrap_command(
+    tool_path: &Path,
+    config: &SandboxConfig,
+    paths: &SandboxPaths,
+    argv: Vec<String>,
+) -> Vec<String> {
+    let mut wrapped = vec![tool_path.to_string_lossy().into_owned()];
+    match config.tool {
+        SandboxTool::Firejail => {
+            wrapped.push("--quiet".to_string());
+            if let Some(profile) = config
+                .firejail_profile
+                .as_deref()
+                .map(str::trim)
+                .filter(|profile| !profile.is_empty())
+            {
+                wrapped.push(format!("--profile={}", profile));
+            } else {
+                // firejail 的 whitelist 只对主目录生效，主目录外的路径本来就可见
+                let in_home = |path: &&PathBuf| {
+                    paths
+                        .home
+                        .as_deref()
+                        .is_some_and(|home| path.starts_with(home))
+                };
+                for path in paths.writable.iter().filter(in_home) {
+                    wrapped.push(arg("--whitelist=", path));
+                }
+                for path in paths.readonly.iter().filter(in_home) {
+                    wrapped.push(arg("--whitelist=", path));
+                    wrapped.push(arg("--read-only=", path));
+                }
+                wrapped.push("--caps.drop=all".to_string());
+                wrapped.push("--nonewprivs".to_string());
+            }
+            if !config.allow_network {
+                wrapped.push("--net=none".to_string());
+            }
+        }
+        SandboxTool::Bubblewrap => {
+            wrapped.extend(
+                [
+                    "--die-with-parent",
+                    "--unshare-user-try",
+                    "--unshare-pid",
+                    "--unshare-uts",
+                    "--unshare-cgroup-try",
+                    "--ro-bind",
+                    "/",
+                    "/",
+                    // 游戏需要 GPU、声卡和手柄设备
+                    "--dev-bind",
+                    "/dev",
+                    "/dev",
+                    "--proc",
+                    "/proc",
+                    "--tmpfs",
+                    "/tmp",
+                    "--ro-bind-try",
+                    "/tmp/.X11-unix",
+                    "/tmp/.X11-unix",
+                ]
+                .map(str::to_string),
+            );
+            if !config.allow_network {
+                wrapped.push("--unshare-net".to_string());
+            }
+            if let Some(home) = &paths.home {
+                wrapped.extend(["--tmpfs".to_string(), home.to_string_lossy().into_owned()]);
+            }
+            if let Some(runtime_dir) = &paths.runtime_dir {
+                wrapped.extend([
+                    "--tmpfs".to_string(),
+                    runtime_dir.to_string_lossy().into_owned(),
+                ]);
+            }
+            for (flag, list) in [
+                ("--ro-bind-try", &paths.runtime_sockets),
+                ("--ro-bind-try", &paths.readonly),
+                ("--bind-try", &paths.writable),
+            ] {
+                for path in list {
+                    let path = path.to_string_lossy().into_owned();
+                    wrapped.extend([flag.to_string(), path.clone(), path]);
+                }
+            }
+        }
+    }
+    wrapped.push("--".to_string());
+    wrapped.extend(argv);
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths() -> SandboxPaths {
+        SandboxPaths {
+            home: Some(PathBuf::from("/home/user")),
+            writable: vec![
+                PathBuf::from("/home/user/Games/foo"),
+                PathBuf::from("/mnt/games/bar"),
+            ],
+            readonly: vec![PathBuf::from("/home/user/.Xauthority")],
+            runtime_dir: None,
+            runtime_sockets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn firejail_whitelists_only_home_paths() {
+        let config = SandboxConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let argv = wrap_command(
+            Path::new("/usr/bin/firejail"),
+            &config,
+            &paths(),
+            vec!["wine".to_string(), "a.exe".to_string()],
+        );
+        assert!(argv.contains(&"--whitelist=/home/user/Games/foo".to_string()));
+        assert!(!argv.iter().any(|arg| arg.contains("/mnt/games")));
+        assert!(argv.contains(&"--read-only=/home/user/.Xauthority".to_string()));
+        assert!(argv.contains(&"--net=none".to_string()));
+        assert!(argv.ends_with(&["--".to_string(), "wine".to_string(), "a.exe".to_string()]));
+    }
+
+    #[test]
+    fn bubblewrap_hides_home_and_binds_writable_paths() {
+        let config = SandboxConfig {
+            enabled: true,
+            tool: SandboxTool::Bubblewrap,
+            allow_network: true,
+            ..Default::default()
+        };
+        let argv = wrap_command(
+            Path::new("/usr/bin/bwrap"),
+            &config,
+            &paths(),
+            vec!["./game".to_string()],
+        );
+        let tmpfs_home = argv
+            .windows(2)
+            .position(|pair| pair == ["--tmpfs", "/home/user"])
+            .unwrap();
+        let bind_game = argv
+            .windows(3)
+            .position(|w| w == ["--bind-try", "/home/user/Games/foo", "/home/user/Games/foo"])
+            .unwrap();
+        assert!(tmpfs_home < bind_game);
+        assert!(!argv.contains(&"--unshare-net".to_string()));
+    }
+    #[test]
+    fn bubblewrap_binds_only_runtime_sockets() {
+        let config = SandboxConfig {
+            enabled: true,
+            tool: SandboxTool::Bubblewrap,
+            ..Default::default()
+        };
+        let runtime_dir = PathBuf::from("/run/user/1000");
+        let paths = SandboxPaths {
+            runtime_sockets: runtime_sockets(&runtime_dir, Some("wayland-0")),
+            runtime_dir: Some(runtime_dir),
+            ..paths()
+        };
+        let argv = wrap_command(
+            Path::new("/usr/bin/bwrap"),
+            &config,
+            &paths,
+            vec!["./game".to_string()],
+        );
+        let tmpfs_runtime = argv
+            .windows(2)
+            .position(|pair| pair == ["--tmpfs", "/run/user/1000"])
+            .unwrap();
+        for socket in ["wayland-0", "pulse/native", "pipewire-0"] {
+            let socket = format!("/run/user/1000/{}", socket);
+            let bind = argv
+                .windows(3)
+                .position(|w| w == ["--ro-bind-try", socket.as_str(), socket.as_str()])
+                .unwrap();
+            assert!(tmpfs_runtime < bind);
+        }
+        assert!(!argv.iter().any(|arg| arg == "/run/user/1000/bus"));
+        assert!(
+            !argv
+                .windows(2)
+                .any(|pair| pair[0] == "--bind-try" && pair[1] == "/run/user/1000")
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! 通过商店 URI 启动游戏
+//!
+//! Steam、DMM 等商店管理的游戏没有可直接启动的 exe，
+//! 这里交给系统打开 `steam://rungameid/...`、`dmmgameplayer://...` 等 URI，
+//! 再按可执行文件名轮询查找商店拉起的游戏进程，找到后交给平台实现开始监控。
+
+use crate::entity::games;
+use log::{info, warn};
+use serde_json::json;
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// 等待商店拉起游戏进程的最长时间（首次启动可能需要商店先完成更新检查）
+const PROCESS_WAIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 查找游戏进程的轮询间隔
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 获取游戏配置的启动 URI，未设置时返回 None
+pub fn launch_uri(game: &games::Model) -> Option<&str> {
+    game.launch_uri
+        .as_deref()
+        .map(str::trim)
+        .filter(|uri| !uri.is_empty())
+}
+
+/// 获取用于查找游戏进程的可执行文件名
+///
+/// 优先使用 `launch_process_name`，未设置时取 `localpath` 的文件名
+pub fn process_name(game: &games::Model) -> Option<String> {
+    game.launch_process_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .or_else(|| {
+            game.localpath
+                .as_deref()
+                .and_then(|path| Path::new(path).file_name())
+                .map(|name| name.to_string_lossy().to_string())
+        })
+}
+
+/// 使用系统关联的程序打开 URI
+#[cfg(target_os = "windows")]
+pub fn open_uri(uri: &str) -> Result<(), String> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+    use windows::core::{PCWSTR, w};
+
+    let wide: Vec<u16> = OsStr::new(uri).encode_wide().chain(Some(0)).collect();
+    // ShellExecuteW 返回值大于 32 表示成功
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            w!("open"),
+            PCWSTR(wide.as_ptr()),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    if result.0 as isize > 32 {
+        Ok(())
+    } else {
+        Err(format!(
+            "无法打开启动 URI {}（错误码 {}），请确认已安装对应的商店客户端",
+            uri, result.0 as isize
+        ))
+    }
+}
+
+/// 使用系统关联的程序打开 URI
+#[cfg(target_os = "linux")]
+pub fn open_uri(uri: &str) -> Result<(), String> {
+    std::process::Command::new("xdg-open")
+        .arg(uri)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("无法打开启动 URI {}: {}", uri, e))
+}
+
+/// 在后台等待商店拉起游戏进程，找到后开始监控
+///
+/// 超时仍未找到时发送一次时长为 0 的 `game-session-ended` 事件，让前端退出运行状态。
+///
+/// # Arguments
+/// * `app_handle` - Tauri 应用句柄
+/// * `game_id` - 游戏 ID
+/// * `process_name` - 要查找的可执行文件名（不区分大小写）
+/// * `find_process` - 平台相关的按名称查找进程函数
+/// * `start_monitor` - 找到进程后启动监控
+pub fn spawn_process_watch<R, F, M, Fut>(
+    app_handle: AppHandle<R>,
+    game_id: u32,
+    process_name: String,
+    find_process: F,
+    start_monitor: M,
+) where
+    R: Runtime,
+    F: Fn(&str) -> Option<u32> + Send + 'static,
+    M: FnOnce(AppHandle<R>, u32) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tauri::async_runtime::spawn(async move {
+        let start_time = get_timestamp();
+        let deadline = tokio::time::Instant::now() + PROCESS_WAIT_TIMEOUT;
+
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(PROCESS_POLL_INTERVAL).await;
+            if let Some(pid) = find_process(&process_name) {
+                info!(
+                    "已找到商店拉起的游戏进程 game_id={} name={} pid={}",
+                    game_id, process_name, pid
+                );
+                start_monitor(app_handle, pid).await;
+                return;
+            }
+        }
+
+        warn!(
+            "等待游戏进程超时，本次不记录游戏时间 game_id={} name={}",
+            game_id, process_name
+        );
+        if let Err(e) = app_handle.emit(
+            "game-session-ended",
+            json!({
+                "gameId": game_id,
+                "startTime": start_time,
+                "endTime": get_timestamp(),
+                "totalMinutes": 0,
+                "totalSeconds": 0,
+                "processId": 0
+            }),
+        ) {
+            warn!("无法发送 game-session-ended 事件: {}", e);
+        }
+    });
+}
+
+fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,121 @@
+//! 游戏所在卷的可用性检测
+//!
+//! 游戏放在移动硬盘、U 盘或网络共享上时，卷未连接会导致启动报出笼统的"文件不存在"。
+//! 这里区分"卷不可用"与"文件被删除"，并支持等待卷重新挂载。
+
+use super::error::LaunchError;
+use log::info;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 等待卷挂载时的轮询间隔
+const VOLUME_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 当前不可用的卷
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnavailableVolume {
+    /// 卷根目录（Linux 为挂载点）
+    pub root: PathBuf,
+    /// 展示给用户的驱动器标识
+    pub label: String,
+}
+
+/// 检查路径所在的卷是否不可用，卷存在或无法判断时返回 None
+#[cfg(target_os = "windows")]
+pub fn find_unavailable_volume(path: &Path) -> Option<UnavailableVolume> {
+    use std::path::{Component, Prefix};
+
+    let Some(Component::Prefix(prefix)) = path.components().next() else {
+        return None;
+    };
+    let (root, label) = match prefix.kind() {
+        Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+            let letter = char::from(letter).to_ascii_uppercase();
+            (
+                PathBuf::from(format!("{}:\\", letter)),
+                format!("{}:", letter),
+            )
+        }
+        Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+            let label = format!(
+                "\\\\{}\\{}",
+                server.to_string_lossy(),
+                share.to_string_lossy()
+            );
+            (PathBuf::from(format!("{}\\", label)), label)
+        }
+        _ => return None,
+    };
+
+    (!root.exists()).then_some(UnavailableVolume { root, label })
+}
+
+/// 检查路径所在的卷是否不可用，卷存在或无法判断时返回 None
+///
+/// Linux 下可移动设备挂载在 `/media/<user>/<label>`、`/run/media/<user>/<label>` 或 `/mnt/<label>`，
+/// 卸载后挂载点目录会被删除，因此以"最近存在的祖先目录是挂载基目录"作为判断依据。
+#[cfg(target_os = "linux")]
+pub fn find_unavailable_volume(path: &Path) -> Option<UnavailableVolume> {
+    let existing = path
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| ancestor.exists())?;
+    // `/run/media` 下没有任何挂载时连用户目录也会被删除，需要跳过用户名这一级
+    let skip = if existing == Path::new("/run/media") {
+        1
+    } else if existing == Path::new("/mnt")
+        || existing == Path::new("/media")
+        || existing.parent() == Some(Path::new("/media"))
+        || existing.parent() == Some(Path::new("/run/media"))
+    {
+        0
+    } else {
+        return None;
+    };
+
+    let remainder = path.strip_prefix(existing).ok()?;
+    let root = existing.join(remainder.components().take(skip + 1).collect::<PathBuf>());
+    let label = root.file_name()?.to_string_lossy().to_string();
+    Some(UnavailableVolume { root, label })
+}
+
+/// 游戏路径不存在时检查所在卷，卷不可用则按需等待其重新挂载
+///
+/// # Arguments
+/// * `game_path` - 游戏可执行文件路径
+/// * `wait_secs` - 最长等待秒数，None 或 0 表示不等待
+///
+/// # Returns
+/// 卷可用（或无法判断）时返回 `Ok(())`，由调用方继续检查文件本身；
+/// 超时后卷仍不可用返回 `LaunchError::DriveUnavailable`
+pub async fn ensure_volume_available(
+    game_path: &Path,
+    wait_secs: Option<u64>,
+) -> Result<(), LaunchError> {
+    let Some(volume) = find_unavailable_volume(game_path) else {
+        return Ok(());
+    };
+
+    let wait = Duration::from_secs(wait_secs.unwrap_or(0));
+    if !wait.is_zero() {
+        info!(
+            "游戏所在驱动器不可用，等待挂载 drive={} timeout={}s",
+            volume.label,
+            wait.as_secs()
+        );
+        let deadline = tokio::time::Instant::now() + wait;
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(VOLUME_POLL_INTERVAL).await;
+            if volume.root.exists() {
+                info!("驱动器已挂载 drive={}", volume.label);
+                return Ok(());
+            }
+        }
+    }
+
+    Err(LaunchError::DriveUnavailable {
+        message: format!("游戏所在的驱动器 {} 当前不可用，请连接后重试", volume.label),
+        drive: volume.label,
+        detail: game_path.to_string_lossy().to_string(),
+    })
+}
@@ -1,12 +1,28 @@
+use crate::database::db::DbState;
 use crate::database::dto::UpdateSettingsData;
 use crate::database::repository::games_repository::GamesRepository;
 use crate::database::repository::settings_repository::{DbSettingsExt, SettingsRepository};
-use crate::game::monitor::{monitor_game, stop_game_session};
+use crate::entity::games;
+use crate::game::launch::LaunchError;
+use crate::game::launch::args::merge_launch_args;
+use crate::game::launch::early_exit::{StderrTail, exited_early_error, wait_for_early_exit};
+use crate::game::launch::failure::LaunchFailure;
+use crate::game::launch::output::{
+    OutputCapture, read_output_tail, record_launch_attempt, watch_captured_process,
+};
+use crate::game::launch::uri;
+use crate::game::launch::volume::ensure_volume_available;
+use crate::game::monitor::{
+    ActiveSessionInfo, DEFAULT_STOP_GRACE_SECS, StopOutcome, StopStage, find_session,
+    get_all_candidate_pids, get_process_executable_path, monitor_game, stop_game_session,
+};
 use crate::utils::command_ext::CommandGuiExt;
+use crate::utils::metrics::CommandTimer;
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::time::Duration;
 use tauri::{AppHandle, Runtime, State, command};
 use {
     log::{debug, info, warn},
@@ -19,6 +35,34 @@ pub struct LaunchResult {
     message: String,
 
     process_id: Option<u32>, // 添加进程ID字段
+    /// 游戏已在运行、未再次启动时为其现有会话（管理器未在监控时为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    running_session: Option<ActiveSessionInfo>,
+}
+
+impl LaunchResult {
+    fn already_running(process_id: u32, running_session: Option<ActiveSessionInfo>) -> Self {
+        Self {
+            success: false,
+            message: format!("游戏已在运行中 (PID {})", process_id),
+            process_id: Some(process_id),
+            running_session,
+        }
+    }
+}
+
+/// 检查游戏是否已在运行：优先查找监控会话，其次扫描游戏目录下的进程（如管理器重启前启动的游戏）
+fn find_running_instance(game_id: u32, game_path: Option<&str>) -> Option<LaunchResult> {
+    if let Some(session) = find_session(game_id) {
+        return Some(LaunchResult::already_running(
+            session.process_id,
+            Some(session),
+        ));
+    }
+    let pid = game_path
+        .filter(|path| Path::new(path).is_file())
+        .and_then(|path| get_all_candidate_pids(path).into_iter().next())?;
+    Some(LaunchResult::already_running(pid, None))
 }
 
 #[derive(Clone, Copy)]
@@ -55,6 +99,8 @@ pub struct StopResult {
     success: bool,
     message: String,
     terminated_count: u32,
+    /// 游戏自行退出（graceful）还是被强制终止（forced）
+    stage: StopStage,
 }
 
 // ================= Windows键盘模拟支持 =================
@@ -225,46 +271,130 @@ async fn resolve_tool_path(
     Ok(path.to_string())
 }
 
+/// 通过商店 URI 启动游戏，并在后台等待游戏进程出现后开始监控
+async fn launch_via_uri<R: Runtime>(
+    app_handle: AppHandle<R>,
+    db: &DatabaseConnection,
+    game: &games::Model,
+    launch_uri: &str,
+) -> Result<LaunchResult, LaunchError> {
+    let game_id = game.id as u32;
+    let process_name = uri::process_name(game)
+        .ok_or_else(|| "通过 URI 启动需要设置游戏进程名或游戏路径，以便查找游戏进程".to_string())?;
+
+    if let Err(message) = uri::open_uri(launch_uri) {
+        record_launch_attempt(db, game_id, false, None, &message, None).await;
+        return Err(message.into());
+    }
+    info!(
+        "已通过 URI 启动游戏 game_id={} uri={} process_name={}",
+        game_id, launch_uri, process_name
+    );
+
+    let localpath = game.localpath.clone();
+    uri::spawn_process_watch(
+        app_handle,
+        game_id,
+        process_name.clone(),
+        find_process_id_by_name,
+        move |app_handle, pid| async move {
+            // 监控需要游戏目录扫描相关进程，优先使用进程的实际路径
+            let Some(executable_path) = get_process_executable_path(pid)
+                .map(|path| path.to_string_lossy().to_string())
+                .or(localpath)
+            else {
+                warn!(
+                    "无法获取游戏进程路径，跳过监控 game_id={} pid={}",
+                    game_id, pid
+                );
+                return;
+            };
+            monitor_game(app_handle, game_id, pid, executable_path).await;
+        },
+    );
+
+    let message = format!(
+        "已通过 URI 启动游戏，等待进程 {} 出现后开始计时",
+        process_name
+    );
+    record_launch_attempt(db, game_id, true, None, &message, None).await;
+
+    Ok(LaunchResult {
+        success: true,
+        message,
+        process_id: None,
+        running_session: None,
+    })
+}
+
 /// 启动游戏
 ///
+/// 游戏设置了 `launch_uri` 时通过系统打开该 URI（Steam、DMM 等商店），
+/// 并按可执行文件名查找商店拉起的进程开始监控；否则直接启动 `localpath`。
+/// 直接启动的进程会先观察约 2 秒，期间以非零退出码退出时返回 `LaunchError::ExitedEarly`，不开始监控。
+///
 /// # Arguments
 ///
 /// * `app_handle` - Tauri应用句柄
 /// * `game_id` - 游戏ID (数据库记录ID)
-/// * `args` - 可选的游戏启动参数
+/// * `args` - 可选的本次启动参数，附加在游戏保存的 `launch_args` 之后
+/// * `capture_output` - 是否把进程 stdout/stderr 写入单次启动日志（提权启动时无法捕获）
+/// * `wait_for_drive_secs` - 游戏所在驱动器未连接时最长等待挂载的秒数，默认不等待
+/// * `force` - 游戏已在运行时仍然再启动一个实例，默认返回现有会话而不启动
 ///
 /// # Returns
 ///
-/// 启动结果，包含成功标志、消息和进程ID
+/// 启动结果，包含成功标志、消息和进程ID；游戏已在运行时 `success` 为 false 并附带现有会话
 #[command]
 pub async fn launch_game<R: Runtime>(
     app_handle: AppHandle<R>,
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     game_id: u32,
     args: Option<Vec<String>>,
-) -> Result<LaunchResult, String> {
-    let game = GamesRepository::find_by_id(db.inner(), game_id as i32)
+    capture_output: Option<bool>,
+    wait_for_drive_secs: Option<u64>,
+    force: Option<bool>,
+) -> Result<LaunchResult, LaunchError> {
+    let _timer = CommandTimer::start("launch_game");
+    let db = db.get();
+    let game = GamesRepository::find_by_id(&db, game_id as i32)
         .await
         .map_err(|e| format!("查询游戏失败: {}", e))?
         .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+    if !force.unwrap_or(false)
+        && let Some(result) = find_running_instance(game_id, game.localpath.as_deref())
+    {
+        info!("游戏已在运行，跳过启动 game_id={}", game_id);
+        return Ok(result);
+    }
+    if let Some(launch_uri) = uri::launch_uri(&game) {
+        return launch_via_uri(app_handle, &db, &game, launch_uri).await;
+    }
+    let args = merge_launch_args(&game, args)?;
+    let use_le = game.le_launch.unwrap_or(0) == 1;
+    let use_magpie = game.magpie.unwrap_or(0) == 1;
     let game_path = game.localpath.ok_or_else(|| "游戏路径未设置".to_string())?;
 
     if !Path::new(&game_path).exists() {
-        return Err(format!("游戏可执行文件不存在: {}", game_path));
+        // 先区分"所在驱动器未连接"与"文件不存在"
+        ensure_volume_available(Path::new(&game_path), wait_for_drive_secs).await?;
+        if !Path::new(&game_path).exists() {
+            let message = format!("游戏可执行文件不存在: {}", game_path);
+            LaunchFailure::missing_file(game_id, use_le, use_magpie, message.clone())
+                .emit(&app_handle);
+            return Err(message.into());
+        }
     }
 
-    let use_le = game.le_launch.unwrap_or(0) == 1;
-    let use_magpie = game.magpie.unwrap_or(0) == 1;
-
     let settings = if use_le || use_magpie {
-        Some(db.inner().get_settings().await?)
+        Some(db.get_settings().await?)
     } else {
         None
     };
     let le_path = if use_le {
         Some(
             resolve_tool_path(
-                db.inner(),
+                &db,
                 settings.as_ref().and_then(|s| s.le_path_value()),
                 ToolPathKind::Le,
             )
@@ -277,7 +407,7 @@ pub async fn launch_game<R: Runtime>(
     let magpie_path = if use_magpie {
         Some(
             resolve_tool_path(
-                db.inner(),
+                &db,
                 settings.as_ref().and_then(|s| s.magpie_path_value()),
                 ToolPathKind::Magpie,
             )
@@ -290,13 +420,13 @@ pub async fn launch_game<R: Runtime>(
     // 获取游戏可执行文件的目录
     let game_dir = match Path::new(&game_path).parent() {
         Some(dir) => dir,
-        None => return Err("无法获取游戏目录路径".to_string()),
+        None => return Err("无法获取游戏目录路径".into()),
     };
 
     // 获取游戏可执行文件名
     let exe_name = match Path::new(&game_path).file_name() {
         Some(name) => name,
-        None => return Err("无法获取游戏可执行文件名".to_string()),
+        None => return Err("无法获取游戏可执行文件名".into()),
     };
 
     // 根据启动选项决定启动方式
@@ -330,10 +460,71 @@ pub async fn launch_game<R: Runtime>(
         game_dir.display()
     );
 
-    let spawn_result = command.gui_safe().spawn();
+    // 可选：捕获直接启动进程的输出，便于排查"打开后立即退出"的问题
+    let output_capture = if capture_output.unwrap_or(false) {
+        match OutputCapture::create(game_id) {
+            Ok(capture) => Some(capture),
+            Err(e) => {
+                warn!(
+                    "创建启动日志失败，本次不捕获输出 game_id={}: {}",
+                    game_id, e
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    command.gui_safe();
+    let output_capture = output_capture.and_then(|capture| match capture.stdio_handles() {
+        Ok((stdout, stderr)) => {
+            command.stdout(stdout).stderr(stderr);
+            Some(capture)
+        }
+        Err(e) => {
+            warn!("无法重定向输出，本次不捕获输出 game_id={}: {}", game_id, e);
+            None
+        }
+    });
+    if output_capture.is_none() {
+        // 不捕获输出时也读取 stderr 末尾，进程立即退出时随错误返回
+        command.stderr(Stdio::piped());
+    }
+
+    let spawn_result = command.spawn();
     match spawn_result {
-        Ok(child) => {
+        Ok(mut child) => {
             let process_id = child.id();
+            let stderr_tail = child.stderr.take().map(StderrTail::collect);
+
+            // 启动后立即以非零退出码退出（缺少 DLL、需要转区等），不再开始监控
+            if let Some(exit_code) = wait_for_early_exit(&mut child).await {
+                let stderr = match (&output_capture, &stderr_tail) {
+                    (Some(capture), _) => read_output_tail(&capture.log_path),
+                    (None, Some(tail)) => tail.finish().await,
+                    (None, None) => None,
+                };
+                let error = exited_early_error(&exe_name.to_string_lossy(), exit_code, stderr);
+                warn!(
+                    "游戏启动后立即退出 game_id={} pid={} exit_code={}",
+                    game_id, process_id, exit_code
+                );
+                let log_path = output_capture.map(|capture| capture.log_path);
+                let attempt_id = record_launch_attempt(
+                    &db,
+                    game_id,
+                    false,
+                    Some(process_id),
+                    &error.to_string(),
+                    log_path.as_deref(),
+                )
+                .await;
+                if let (Some(attempt_id), Some(log_path)) = (attempt_id, log_path) {
+                    watch_captured_process(db.clone(), attempt_id, child, log_path);
+                }
+                return Err(error);
+            }
             info!(
                 "游戏启动成功 game_id={} pid={} mode={} magpie={}",
                 game_id,
@@ -355,15 +546,31 @@ pub async fn launch_game<R: Runtime>(
                 });
             }
 
+            let message = format!(
+                "成功启动游戏: {}，工作目录: {:?}{}",
+                exe_name.to_string_lossy(),
+                game_dir,
+                if use_le { " (LE转区)" } else { "" }
+            );
+            let log_path = output_capture.map(|capture| capture.log_path);
+            let attempt_id = record_launch_attempt(
+                &db,
+                game_id,
+                true,
+                Some(process_id),
+                &message,
+                log_path.as_deref(),
+            )
+            .await;
+            if let (Some(attempt_id), Some(log_path)) = (attempt_id, log_path) {
+                watch_captured_process(db.clone(), attempt_id, child, log_path);
+            }
+
             Ok(LaunchResult {
                 success: true,
-                message: format!(
-                    "成功启动游戏: {}，工作目录: {:?}{}",
-                    exe_name.to_string_lossy(),
-                    game_dir,
-                    if use_le { " (LE转区)" } else { "" }
-                ),
+                message,
                 process_id: Some(process_id),
+                running_session: None,
             })
         }
         Err(e) => {
@@ -416,21 +623,38 @@ pub async fn launch_game<R: Runtime>(
                             });
                         }
 
+                        let message = format!(
+                            "已使用管理员权限启动游戏: {}{}，工作目录: {:?}",
+                            exe_name.to_string_lossy(),
+                            if use_le { " (LE转区)" } else { "" },
+                            game_dir
+                        );
+                        if output_capture.is_some() {
+                            debug!("提权启动无法捕获进程输出 game_id={}", game_id);
+                        }
+                        record_launch_attempt(&db, game_id, true, Some(pid), &message, None).await;
+
                         Ok(LaunchResult {
                             success: true,
-                            message: format!(
-                                "已使用管理员权限启动游戏: {}{}，工作目录: {:?}",
-                                exe_name.to_string_lossy(),
-                                if use_le { " (LE转区)" } else { "" },
-                                game_dir
-                            ),
+                            message,
                             process_id: Some(pid),
+                            running_session: None,
                         })
                     }
-                    Err(err2) => Err(format!("普通启动失败且提权启动失败: {} | {}", e, err2)),
+                    Err(err2) => {
+                        let message = format!("普通启动失败且提权启动失败: {} | {}", e, err2);
+                        LaunchFailure::from_io(game_id, &e, use_le, use_magpie, message.clone())
+                            .emit(&app_handle);
+                        record_launch_attempt(&db, game_id, false, None, &message, None).await;
+                        Err(message.into())
+                    }
                 }
             } else {
-                Err(format!("启动游戏失败: {}，目录: {:?}", e, game_dir))
+                let message = format!("启动游戏失败: {}，目录: {:?}", e, game_dir);
+                LaunchFailure::from_io(game_id, &e, use_le, use_magpie, message.clone())
+                    .emit(&app_handle);
+                record_launch_attempt(&db, game_id, false, None, &message, None).await;
+                Err(message.into())
             }
         }
     }
@@ -441,20 +665,35 @@ pub async fn launch_game<R: Runtime>(
 /// # Arguments
 ///
 /// * `game_id` - 游戏ID (bgm_id 或 vndb_id)
+/// * `grace_secs` - 发送 WM_CLOSE 后等待游戏自行退出的秒数，超时后强制终止，默认 5 秒
 ///
 /// # Returns
 ///
-/// 停止结果，包含成功标志、消息和终止的进程数量
+/// 停止结果，包含成功标志、消息、终止的进程数量和起作用的阶段
 #[command]
-pub async fn stop_game(game_id: u32) -> Result<StopResult, String> {
-    match stop_game_session(game_id).await {
-        Ok(terminated_count) => Ok(StopResult {
+pub async fn stop_game(game_id: u32, grace_secs: Option<u64>) -> Result<StopResult, String> {
+    let _timer = CommandTimer::start("stop_game");
+    let grace = Duration::from_secs(grace_secs.unwrap_or(DEFAULT_STOP_GRACE_SECS));
+    match stop_game_session(game_id, grace).await {
+        Ok(StopOutcome {
+            stage,
+            terminated_count,
+        }) => Ok(StopResult {
             success: true,
-            message: format!(
-                "已成功停止游戏 {}, 终止了 {} 个进程",
-                game_id, terminated_count
-            ),
+            message: match stage {
+                StopStage::Graceful => format!(
+                    "游戏 {} 已自行退出，共 {} 个进程",
+                    game_id, terminated_count
+                ),
+                StopStage::Forced => format!(
+                    "游戏 {} 未在 {} 秒内退出，已强制终止 {} 个进程",
+                    game_id,
+                    grace.as_secs(),
+                    terminated_count
+                ),
+            },
             terminated_count,
+            stage,
         }),
         Err(e) => Err(format!("停止游戏失败: {}", e)),
     }
@@ -463,7 +702,7 @@ pub async fn stop_game(game_id: u32) -> Result<StopResult, String> {
 /// 为游戏启动Magpie放大
 async fn start_magpie_for_game(magpie_path: &str) -> Result<(), String> {
     // 检查Magpie是否已经在运行
-    let magpie_was_running = is_process_running("Magpie.exe");
+    let magpie_was_running = find_process_id_by_name("Magpie.exe").is_some();
 
     if !magpie_was_running {
         // Magpie没有运行，启动它
@@ -507,8 +746,8 @@ async fn start_magpie_for_game(magpie_path: &str) -> Result<(), String> {
     }
 }
 
-/// 检查指定名称的进程是否在运行（使用 Windows ToolHelp API）
-fn is_process_running(process_name: &str) -> bool {
+/// 按进程名查找正在运行的进程 PID（使用 Windows ToolHelp API，不区分大小写）
+fn find_process_id_by_name(process_name: &str) -> Option<u32> {
     use std::mem;
     use windows::Win32::{
         Foundation::CloseHandle,
@@ -524,7 +763,7 @@ fn is_process_running(process_name: &str) -> bool {
             0,
         ) {
             Ok(h) if !h.is_invalid() => h,
-            _ => return false,
+            _ => return None,
         };
 
         let mut entry = PROCESSENTRY32W {
@@ -532,7 +771,7 @@ fn is_process_running(process_name: &str) -> bool {
             ..Default::default()
         };
 
-        let mut found = false;
+        let mut found = None;
         if Process32FirstW(snapshot, &mut entry).is_ok() {
             loop {
                 // th32ExeFile 是以 null 结尾的 UTF-16 进程名（不含路径）
@@ -543,7 +782,7 @@ fn is_process_running(process_name: &str) -> bool {
                     .unwrap_or(entry.szExeFile.len());
                 let name = String::from_utf16_lossy(&entry.szExeFile[..name_end]);
                 if name.eq_ignore_ascii_case(process_name) {
-                    found = true;
+                    found = Some(entry.th32ProcessID);
                     break;
                 }
                 if Process32NextW(snapshot, &mut entry).is_err() {
@@ -1,9 +1,24 @@
+mod sessions;
+
+#[cfg(target_os = "windows")]
+mod gamepad;
+#[cfg(target_os = "windows")]
+mod placement;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+mod power;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+mod usage;
 #[cfg(target_os = "windows")]
 mod windows;
 
 #[cfg(target_os = "linux")]
 mod linux;
 
+pub use sessions::{
+    ActiveSessionInfo, ActiveSessionSummary, DEFAULT_STOP_GRACE_SECS, StopOutcome, StopStage,
+    active_sessions, find_session, get_active_sessions, get_monitor_health, toggle_timing_pause,
+};
+
 #[cfg(target_os = "windows")]
 pub use windows::*;
 
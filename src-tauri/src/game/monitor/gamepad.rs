@@ -0,0 +1,302 @@
+//! 手柄映射（仅 Windows）
+//!
+//! 很多视觉小说只支持鼠标和键盘。游戏设置了 `input_mapping` 时，会话期间在后台线程轮询 XInput 手柄，
+//! 把按键转换为 SendInput 的键盘 / 鼠标事件，左摇杆移动鼠标。只有游戏窗口在前台时才发送输入，
+//! 会话结束（停止信号置位）时松开所有仍按住的按键并退出。
+
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::entity::input_mapping::{GamepadButton, InputMapping, MappedAction};
+use log::{debug, info, warn};
+use parking_lot::RwLock;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBD_EVENT_FLAGS, KEYBDINPUT, KEYEVENTF_KEYUP,
+    MOUSE_EVENT_FLAGS, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MOVE,
+    MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_WHEEL, MOUSEINPUT, SendInput,
+    VIRTUAL_KEY,
+};
+use windows::Win32::UI::Input::XboxController::{XINPUT_STATE, XInputGetState};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+/// 轮询手柄的间隔（约 60Hz）
+const POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// 没有手柄连接时的轮询间隔
+const DISCONNECTED_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// XInput 支持的手柄数量
+const MAX_CONTROLLERS: u32 = 4;
+
+/// 左摇杆死区（XINPUT_GAMEPAD_LEFT_THUMB_DEADZONE）
+const STICK_DEADZONE: i16 = 7849;
+
+/// 扳机视为按下的阈值（XINPUT_GAMEPAD_TRIGGER_THRESHOLD）
+const TRIGGER_THRESHOLD: u8 = 30;
+
+/// 滚轮一格
+const WHEEL_DELTA: i32 = 120;
+
+/// XInput 的按键位
+fn button_mask(button: GamepadButton) -> u16 {
+    match button {
+        GamepadButton::DpadUp => 0x0001,
+        GamepadButton::DpadDown => 0x0002,
+        GamepadButton::DpadLeft => 0x0004,
+        GamepadButton::DpadRight => 0x0008,
+        GamepadButton::Start => 0x0010,
+        GamepadButton::Back => 0x0020,
+        GamepadButton::LeftThumb => 0x0040,
+        GamepadButton::RightThumb => 0x0080,
+        GamepadButton::LeftShoulder => 0x0100,
+        GamepadButton::RightShoulder => 0x0200,
+        GamepadButton::A => 0x1000,
+        GamepadButton::B => 0x2000,
+        GamepadButton::X => 0x4000,
+        GamepadButton::Y => 0x8000,
+        // 扳机不是按键位，由 pressed_buttons 单独换算
+        GamepadButton::LeftTrigger | GamepadButton::RightTrigger => 0,
+    }
+}
+
+/// 当前按下的按键集合
+fn pressed_buttons(buttons: u16, left_trigger: u8, right_trigger: u8) -> HashSet<GamepadButton> {
+    use GamepadButton::*;
+    let mut pressed: HashSet<GamepadButton> = [
+        A,
+        B,
+        X,
+        Y,
+        LeftShoulder,
+        RightShoulder,
+        Back,
+        Start,
+        LeftThumb,
+        RightThumb,
+        DpadUp,
+        DpadDown,
+        DpadLeft,
+        DpadRight,
+    ]
+    .into_iter()
+    .filter(|button| buttons & button_mask(*button) != 0)
+    .collect();
+    if left_trigger > TRIGGER_THRESHOLD {
+        pressed.insert(LeftTrigger);
+    }
+    if right_trigger > TRIGGER_THRESHOLD {
+        pressed.insert(RightTrigger);
+    }
+    pressed
+}
+
+/// 摇杆偏移量换算为本次轮询的鼠标位移，死区内为 0
+fn stick_to_delta(value: i16, speed: u32, interval: Duration) -> i32 {
+    let magnitude = (value as i32).abs();
+    if magnitude <= STICK_DEADZONE as i32 {
+        return 0;
+    }
+    let ratio = (magnitude - STICK_DEADZONE as i32) as f64 / (32767 - STICK_DEADZONE as i32) as f64;
+    // 平方曲线：小幅推动时便于精确点选
+    let delta = ratio.min(1.0).powi(2) * speed as f64 * interval.as_secs_f64();
+    (delta.round() as i32).max(1) * value.signum() as i32
+}
+
+fn keyboard_input(code: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(code),
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+fn mouse_input(dx: i32, dy: i32, data: i32, flags: MOUSE_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_MOUSE,
+        Anonymous: INPUT_0 {
+            mi: MOUSEINPUT {
+                dx,
+                dy,
+                mouseData: data as u32,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+/// 按键按下 / 松开时对应的输入事件；滚轮只在按下时滚动一格
+fn action_input(action: MappedAction, pressed: bool) -> Option<INPUT> {
+    let input = match (action, pressed) {
+        (MappedAction::Key { code }, true) => keyboard_input(code, KEYBD_EVENT_FLAGS(0)),
+        (MappedAction::Key { code }, false) => keyboard_input(code, KEYEVENTF_KEYUP),
+        (MappedAction::MouseLeft, true) => mouse_input(0, 0, 0, MOUSEEVENTF_LEFTDOWN),
+        (MappedAction::MouseLeft, false) => mouse_input(0, 0, 0, MOUSEEVENTF_LEFTUP),
+        (MappedAction::MouseRight, true) => mouse_input(0, 0, 0, MOUSEEVENTF_RIGHTDOWN),
+        (MappedAction::MouseRight, false) => mouse_input(0, 0, 0, MOUSEEVENTF_RIGHTUP),
+        (MappedAction::WheelUp, true) => mouse_input(0, 0, WHEEL_DELTA, MOUSEEVENTF_WHEEL),
+        (MappedAction::WheelDown, true) => mouse_input(0, 0, -WHEEL_DELTA, MOUSEEVENTF_WHEEL),
+        (MappedAction::WheelUp | MappedAction::WheelDown, false) => return None,
+    };
+    Some(input)
+}
+
+fn send(inputs: &[INPUT]) {
+    if inputs.is_empty() {
+        return;
+    }
+    // SAFETY: inputs 在调用期间有效，cbsize 为 INPUT 的大小
+    let sent = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        debug!("手柄映射输入只发送了 {}/{} 个事件", sent, inputs.len());
+    }
+}
+
+/// 松开所有仍按住的按键
+fn release_all(buttons: &BTreeMap<GamepadButton, MappedAction>, held: &mut HashSet<GamepadButton>) {
+    let releases: Vec<INPUT> = held
+        .drain()
+        .filter_map(|button| buttons.get(&button))
+        .filter_map(|action| action_input(*action, false))
+        .collect();
+    send(&releases);
+}
+
+/// 读取第一个已连接手柄的状态
+fn read_first_controller() -> Option<XINPUT_STATE> {
+    (0..MAX_CONTROLLERS).find_map(|index| {
+        let mut state = XINPUT_STATE::default();
+        // SAFETY: state 在调用期间有效
+        (unsafe { XInputGetState(index, &mut state) } == 0).then_some(state)
+    })
+}
+
+/// 前台窗口是否属于游戏进程
+fn game_in_foreground(candidate_pids: &RwLock<HashSet<u32>>) -> bool {
+    // SAFETY: 只读取前台窗口句柄及其所属进程 ID
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return false;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        candidate_pids.read().contains(&pid)
+    }
+}
+
+/// 读取该游戏启用的手柄映射，未设置或未启用时返回 None
+pub async fn load<R: Runtime>(app_handle: &AppHandle<R>, game_id: u32) -> Option<InputMapping> {
+    let db = app_handle.try_state::<DbState>().map(|state| state.get())?;
+    match GamesRepository::find_by_id(&db, game_id as i32).await {
+        Ok(game) => game
+            .and_then(|game| game.input_mapping)
+            .filter(|mapping| mapping.enabled),
+        Err(e) => {
+            warn!("读取手柄映射失败 (game_id: {}): {}", game_id, e);
+            None
+        }
+    }
+}
+
+/// 在后台线程运行手柄映射，直到会话结束
+pub fn spawn_mapper(
+    game_id: u32,
+    mapping: InputMapping,
+    candidate_pids: Arc<RwLock<HashSet<u32>>>,
+    stop_signal: Arc<AtomicBool>,
+) {
+    tokio::task::spawn_blocking(move || {
+        let buttons: BTreeMap<GamepadButton, MappedAction> = mapping.effective_buttons();
+        let mut held: HashSet<GamepadButton> = HashSet::new();
+        info!("手柄映射已启动 (game_id: {})", game_id);
+
+        while !stop_signal.load(Ordering::Acquire) {
+            let Some(state) = read_first_controller() else {
+                // 手柄断开时松开仍按住的按键
+                release_all(&buttons, &mut held);
+                std::thread::sleep(DISCONNECTED_POLL_INTERVAL);
+                continue;
+            };
+            let pad = state.Gamepad;
+            // 游戏不在前台时视为所有按键都已松开，避免按键卡在其他窗口
+            let foreground = game_in_foreground(&candidate_pids);
+            let pressed = if foreground {
+                pressed_buttons(pad.wButtons.0, pad.bLeftTrigger, pad.bRightTrigger)
+            } else {
+                HashSet::new()
+            };
+
+            let mut inputs: Vec<INPUT> = Vec::new();
+            for button in pressed.difference(&held) {
+                inputs.extend(
+                    buttons
+                        .get(button)
+                        .and_then(|action| action_input(*action, true)),
+                );
+            }
+            for button in held.difference(&pressed) {
+                inputs.extend(
+                    buttons
+                        .get(button)
+                        .and_then(|action| action_input(*action, false)),
+                );
+            }
+            if mapping.left_stick_mouse && foreground {
+                let dx = stick_to_delta(pad.sThumbLX, mapping.mouse_speed, POLL_INTERVAL);
+                // 摇杆向上为正，屏幕坐标向下为正
+                let dy = -stick_to_delta(pad.sThumbLY, mapping.mouse_speed, POLL_INTERVAL);
+                if dx != 0 || dy != 0 {
+                    inputs.push(mouse_input(dx, dy, 0, MOUSEEVENTF_MOVE));
+                }
+            }
+            send(&inputs);
+            held = pressed;
+
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        // 会话结束时松开仍按住的按键
+        release_all(&buttons, &mut held);
+        info!("手柄映射已停止 (game_id: {})", game_id);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triggers_and_buttons_are_decoded() {
+        let pressed = pressed_buttons(0x1000 | 0x0001, 0, 200);
+        assert_eq!(
+            pressed,
+            HashSet::from([
+                GamepadButton::A,
+                GamepadButton::DpadUp,
+                GamepadButton::RightTrigger
+            ])
+        );
+    }
+
+    #[test]
+    fn stick_deadzone_and_direction() {
+        let interval = Duration::from_millis(16);
+        assert_eq!(stick_to_delta(STICK_DEADZONE, 1200, interval), 0);
+        assert_eq!(stick_to_delta(32767, 1000, Duration::from_secs(1)), 1000);
+        assert_eq!(stick_to_delta(-32768, 1000, Duration::from_secs(1)), -1000);
+        assert_eq!(stick_to_delta(STICK_DEADZONE + 10, 1200, interval), 1);
+    }
+}
@@ -6,13 +6,29 @@
 // ============================================================================
 // 外部依赖导入
 // ============================================================================
+use crate::backup::schedule::PlayBackupTimer;
+use crate::database::db::DbState;
 use log::{debug, error, info, warn};
+use parking_lot::Mutex;
 use serde_json::json;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Emitter, Runtime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 use tokio::sync::OnceCell;
 use tokio::time::{MissedTickBehavior, interval};
 
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::process_stats::ProcessStats;
+
+use crate::utils::notification::{NotificationCategory, notify};
+use crate::utils::tray::{hide_for_session, restore_after_session};
+
+use super::power::{self, SuspendState, SuspendTracker};
+use super::sessions::{
+    ActiveSessionInfo, CHECKPOINT_INTERVAL_SECS, StopOutcome, StopStage, clear_checkpoint,
+    is_timing_paused, publish_session, remove_session, save_checkpoint, update_health,
+};
+use super::usage::UsageSampler;
+
 // ============================================================================
 // 常量定义
 // ============================================================================
@@ -26,6 +42,12 @@ const TIME_UPDATE_INTERVAL_SECS: u64 = 1;
 /// 监控循环检查间隔（秒）
 const MONITOR_CHECK_INTERVAL_SECS: u64 = 1;
 
+/// 停止游戏时检查单元是否已停止的间隔
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 自定义焦点查询命令的超时时间（毫秒），需小于监控循环间隔
+const FOREGROUND_PROBE_TIMEOUT_MS: u64 = 500;
+
 // ============================================================================
 // systemd 会话连接缓存
 // ============================================================================
@@ -41,14 +63,31 @@ pub async fn monitor_game<R: Runtime>(
     process_id: u32,
     systemd_scope: String,
 ) {
+    hide_for_session(&app_handle, game_id).await;
     let app_handle_clone = app_handle.clone();
     tauri::async_runtime::spawn(async move {
-        use tauri::Manager;
         if let Err(e) =
             run_game_monitor(app_handle_clone.app_handle(), game_id, &systemd_scope).await
         {
             error!("游戏监控任务 (game_id: {}) 出错: {}", game_id, e);
-            if let Err(e) = finalize_session(&app_handle, game_id, process_id, get_timestamp(), 0) {
+            notify(
+                &app_handle,
+                NotificationCategory::Error,
+                "游戏监控失败",
+                &format!("本次游戏时间可能未被记录：{}", e),
+            )
+            .await;
+            remove_session(game_id);
+            clear_checkpoint(&app_handle, game_id).await;
+            if let Err(e) = finalize_session(
+                &app_handle,
+                game_id,
+                process_id,
+                get_timestamp(),
+                0,
+                0,
+                None,
+            ) {
                 error!("无法完成游戏会话结束: {}", e);
             }
         }
@@ -59,18 +98,76 @@ pub async fn monitor_game<R: Runtime>(
 // 公共 API
 // ============================================================================
 
-/// 停止指定游戏的监控并终止所有相关进程
+/// 停止指定游戏的 systemd 单元并结束其中的所有进程
 ///
-/// # Arguments
-/// * `game_id` - 游戏 ID
+/// 先让 systemd 停止单元（向单元内所有进程发送 SIGTERM），`grace` 内单元仍未停止时
+/// 再向单元内所有进程发送 SIGKILL。
 ///
 /// # Returns
-/// 成功返回终止的进程数量，失败返回错误信息
-pub async fn stop_game_session(game_id: u32) -> Result<u32, String> {
-    stop_game_unit(game_id).await.map(|_| 1)
+/// 成功返回起作用的阶段与停止的进程数量，失败返回错误信息
+pub async fn stop_game_session(game_id: u32, grace: Duration) -> Result<StopOutcome, String> {
+    let (proxy, unit_name, terminated_count) = stop_game_unit(game_id).await?;
+
+    let deadline = tokio::time::Instant::now() + grace;
+    while tokio::time::Instant::now() < deadline {
+        if !is_unit_active(&unit_name).await {
+            info!("游戏 {} 的单元 {} 已停止", game_id, unit_name);
+            return Ok(StopOutcome {
+                stage: StopStage::Graceful,
+                terminated_count,
+            });
+        }
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+    }
+
+    proxy
+        .kill_unit(unit_name.clone(), "all".to_string(), libc::SIGKILL)
+        .await
+        .map_err(|e| format!("强制终止游戏 {} 的单元 {} 失败: {}", game_id, unit_name, e))?;
+    info!(
+        "游戏 {} 的单元 {} 未在 {:?} 内停止，已发送 SIGKILL",
+        game_id, unit_name, grace
+    );
+    Ok(StopOutcome {
+        stage: StopStage::Forced,
+        terminated_count,
+    })
+}
+
+/// 游戏的 systemd 单元（直接启动的 service 或商店启动的 scope）是否仍在运行
+pub async fn is_game_unit_active(game_id: u32) -> bool {
+    is_unit_active(&format!("reina_game_{}.service", game_id)).await
+        || is_unit_active(&format!("reina_game_{}.scope", game_id)).await
+}
+
+/// 单元是否仍存在且未进入 inactive / failed 状态
+async fn is_unit_active(unit_name: &str) -> bool {
+    let (Ok(manager), Ok(conn)) = (get_manager_proxy().await, get_connection().await) else {
+        return false;
+    };
+    let Ok(unit_path) = manager.get_unit(unit_name.to_string()).await else {
+        return false;
+    };
+    match zbus_systemd::systemd1::UnitProxy::new(conn, unit_path).await {
+        Ok(unit) => unit
+            .active_state()
+            .await
+            .is_ok_and(|state| state != "inactive" && state != "failed"),
+        Err(_) => false,
+    }
 }
 
-async fn stop_game_unit(game_id: u32) -> Result<(), String> {
+/// 请求 systemd 停止游戏所在的单元，返回管理器代理、单元名称与停止前单元内的进程数量
+async fn stop_game_unit(
+    game_id: u32,
+) -> Result<
+    (
+        &'static zbus_systemd::systemd1::ManagerProxy<'static>,
+        String,
+        u32,
+    ),
+    String,
+> {
     // 1. 连接到 Session Bus (对应 --user)
     let proxy = get_manager_proxy().await.map_err(|e| {
         format!(
@@ -79,8 +176,17 @@ async fn stop_game_unit(game_id: u32) -> Result<(), String> {
         )
     })?;
 
-    // 2. 构造单元名称
-    let unit_name = format!("reina_game_{}.service", game_id);
+    // 2. 构造单元名称：通过商店 URI 启动的游戏位于 scope 中
+    let scope_name = format!("reina_game_{}.scope", game_id);
+    let unit_name = if proxy.get_unit(scope_name.clone()).await.is_ok() {
+        scope_name
+    } else {
+        format!("reina_game_{}.service", game_id)
+    };
+
+    let process_count = get_process_id_by_unit(&unit_name)
+        .await
+        .map_or(0, |pids| pids.len() as u32);
 
     // 3. 调用停止方法
     match proxy
@@ -97,7 +203,7 @@ async fn stop_game_unit(game_id: u32) -> Result<(), String> {
         }
     }
 
-    Ok(())
+    Ok((proxy, unit_name, process_count))
 }
 
 pub async fn get_connection() -> Result<&'static zbus::Connection, zbus::Error> {
@@ -221,7 +327,7 @@ async fn is_game_running(unit_name: &str) -> bool {
 }
 
 fn select_best_from_candidates(candidate_pids: &[u32]) -> Option<u32> {
-    if let Some(p) = check_any_foreground(candidate_pids) {
+    if let Foreground::Candidate(p) = query_foreground_x11(candidate_pids) {
         debug!("从候选列表中找到聚焦进程 PID: {}", p);
         Some(p)
     } else if let Some(p) = check_any_has_window(candidate_pids) {
@@ -236,88 +342,235 @@ fn select_best_from_candidates(candidate_pids: &[u32]) -> Option<u32> {
     }
 }
 
-/// TODO: 未来可考虑集成其他 wayland 合成器特定功能实现。
-fn check_any_foreground(_candidate_pids: &[u32]) -> Option<u32> {
-    check_any_foreground_x11(_candidate_pids)
+/// 前台窗口判定结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Foreground {
+    /// 候选进程之一持有焦点
+    Candidate(u32),
+    /// 焦点在其他进程的窗口上
+    Other,
+    /// 无法判断：没有可用的 X 连接、窗口管理器不支持 EWMH，
+    /// 或 Wayland 原生窗口持有焦点（XWayland 中没有活动窗口）
+    Unknown,
 }
 
-/// TODO: 未来可考虑集成 x11 或 wayland 合成器特定功能实现。
-fn check_any_has_window(_candidate_pids: &[u32]) -> Option<u32> {
-    check_any_has_window_x11(_candidate_pids)
+/// 缓存的 X 连接与所需的 Atom，避免每秒重新连接 X Server
+struct X11Probe {
+    conn: xcb::Connection,
+    root: xcb::x::Window,
+    atom_active_window: xcb::x::Atom,
+    atom_net_wm_pid: xcb::x::Atom,
 }
 
-fn check_any_foreground_x11(candidate_pids: &[u32]) -> Option<u32> {
-    // 1. 连接到 X Server
-    let (conn, screen_num) = xcb::Connection::connect(None).ok()?;
-    let setup = conn.get_setup();
-    // 获取当前屏幕的根窗口 (Root Window)
-    let screen = setup.roots().nth(screen_num as usize)?;
-    let root_window = screen.root();
+static X11_PROBE: Mutex<Option<X11Probe>> = Mutex::new(None);
 
-    // 2. 获取 Atom 标识符
-    // 我们需要 "_NET_ACTIVE_WINDOW" 来找当前活动窗口
-    // 我们需要 "_NET_WM_PID" 来找窗口对应的 PID
-    let cookie_active = conn.send_request(&xcb::x::InternAtom {
-        only_if_exists: true,
-        name: b"_NET_ACTIVE_WINDOW",
-    });
-    let cookie_pid = conn.send_request(&xcb::x::InternAtom {
-        only_if_exists: true,
-        name: b"_NET_WM_PID",
-    });
+impl X11Probe {
+    fn connect() -> Option<Self> {
+        let (conn, screen_num) = xcb::Connection::connect(None).ok()?;
+        // 获取当前屏幕的根窗口 (Root Window)
+        let root = conn.get_setup().roots().nth(screen_num as usize)?.root();
 
-    let atom_active_window = conn.wait_for_reply(cookie_active).ok()?.atom();
-    let atom_net_wm_pid = conn.wait_for_reply(cookie_pid).ok()?.atom();
+        // "_NET_ACTIVE_WINDOW" 用于找当前活动窗口，"_NET_WM_PID" 用于找窗口对应的 PID
+        let cookie_active = conn.send_request(&xcb::x::InternAtom {
+            only_if_exists: true,
+            name: b"_NET_ACTIVE_WINDOW",
+        });
+        let cookie_pid = conn.send_request(&xcb::x::InternAtom {
+            only_if_exists: true,
+            name: b"_NET_WM_PID",
+        });
+        let atom_active_window = conn.wait_for_reply(cookie_active).ok()?.atom();
+        let atom_net_wm_pid = conn.wait_for_reply(cookie_pid).ok()?.atom();
 
-    // 3. 获取当前活动窗口的 ID
-    // 向 Root Window 请求 _NET_ACTIVE_WINDOW 属性
-    let active_win_cookie = conn.send_request(&xcb::x::GetProperty {
-        delete: false,
-        window: root_window,
-        property: atom_active_window,
-        long_offset: 0,
-        long_length: 1, // 我们只需要读 1 个值
-        r#type: xcb::x::ATOM_WINDOW,
-    });
+        // 窗口管理器不支持 EWMH 时无法判断前台窗口
+        if atom_active_window == xcb::x::ATOM_NONE || atom_net_wm_pid == xcb::x::ATOM_NONE {
+            return None;
+        }
 
-    let active_win_reply = conn.wait_for_reply(active_win_cookie).ok()?;
+        Some(Self {
+            conn,
+            root,
+            atom_active_window,
+            atom_net_wm_pid,
+        })
+    }
 
-    // 如果没有值，说明没有活动窗口或不支持 EWMH
-    if active_win_reply.value::<xcb::x::Window>().is_empty() {
-        return None;
+    /// 读取窗口上的单个 32 位属性值
+    fn read_u32_property(
+        &self,
+        window: xcb::x::Window,
+        property: xcb::x::Atom,
+        r#type: xcb::x::Atom,
+    ) -> Result<Option<u32>, xcb::Error> {
+        let cookie = self.conn.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window,
+            property,
+            r#type,
+            long_offset: 0,
+            long_length: 1,
+        });
+        let reply = self.conn.wait_for_reply(cookie)?;
+        // 属性不存在或类型不匹配时格式为 0，此时不能按 u32 读取
+        if reply.format() != 32 {
+            return Ok(None);
+        }
+        Ok(reply.value::<u32>().first().copied())
     }
 
-    // 提取 Window ID
-    let active_window = active_win_reply
-        .value::<xcb::x::Window>()
-        .first()
-        .copied()?;
+    /// 查询活动窗口所属的 PID，没有活动窗口或窗口未设置 `_NET_WM_PID` 时返回 None
+    fn active_window_pid(&self) -> Result<Option<u32>, xcb::Error> {
+        let active_window = self
+            .read_u32_property(self.root, self.atom_active_window, xcb::x::ATOM_WINDOW)?
+            .filter(|window| *window != 0);
+        let Some(active_window) = active_window else {
+            return Ok(None);
+        };
+
+        // 属性值即 X Server 返回的窗口 ID
+        let window = xcb::XidNew::new(active_window);
+        self.read_u32_property(window, self.atom_net_wm_pid, xcb::x::ATOM_CARDINAL)
+    }
+}
 
-    // 4. 获取该窗口的 PID
-    // 向活动窗口请求 _NET_WM_PID 属性
-    let pid_cookie = conn.send_request(&xcb::x::GetProperty {
-        delete: false,
-        window: active_window,
-        property: atom_net_wm_pid,
-        r#type: xcb::x::ATOM_CARDINAL, // PID 通常是 Cardinal 类型
-        long_offset: 0,
-        long_length: 1,
-    });
+/// 通过 X11 的 `_NET_ACTIVE_WINDOW` 判断候选进程是否持有焦点
+///
+/// Wayland 会话下 XWayland 只能看到 X 客户端的焦点，
+/// 焦点在 Wayland 原生窗口上时 `_NET_ACTIVE_WINDOW` 为空，返回 `Foreground::Unknown`。
+fn query_foreground_x11(candidate_pids: &[u32]) -> Foreground {
+    let mut probe = X11_PROBE.lock();
+    if probe.is_none() {
+        *probe = X11Probe::connect();
+    }
+    let Some(x11) = probe.as_ref() else {
+        return Foreground::Unknown;
+    };
 
-    let pid_reply = conn.wait_for_reply(pid_cookie).ok()?;
+    match x11.active_window_pid() {
+        Ok(Some(pid)) if candidate_pids.contains(&pid) => Foreground::Candidate(pid),
+        Ok(Some(_)) => Foreground::Other,
+        Ok(None) => Foreground::Unknown,
+        Err(e) => {
+            // 连接断开（如 X Server 重启）时丢弃缓存，下次重新连接
+            debug!("查询 X11 活动窗口失败，将重新连接: {}", e);
+            *probe = None;
+            Foreground::Unknown
+        }
+    }
+}
 
-    if pid_reply.value::<xcb::x::Window>().is_empty() {
-        return None;
+/// 当前是否为 Wayland 会话
+fn is_wayland_session() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session == "wayland")
+}
+
+/// 前台判定选项，会话开始时从监控设置中读取
+#[derive(Debug, Clone)]
+struct ForegroundOptions {
+    /// 用户提供的焦点窗口查询命令
+    probe_command: Option<String>,
+    /// Wayland 会话下无法判断焦点时是否照常计时
+    assume_foreground: bool,
+}
+
+/// 执行用户提供的焦点查询命令，输出的第一个数字即焦点窗口的 PID
+///
+/// 命令通过 `sh -c` 执行，可以是 swaymsg / hyprctl 等合成器工具的管道组合，例如
+/// `swaymsg -t get_tree | jq '.. | select(.focused? == true) | .pid'`。
+/// 命令失败、超时或输出中没有 PID 时返回 `Foreground::Unknown`，由 X11 检测继续判断。
+async fn query_foreground_probe(command: &str, candidate_pids: &[u32]) -> Foreground {
+    let output = tokio::time::timeout(
+        Duration::from_millis(FOREGROUND_PROBE_TIMEOUT_MS),
+        tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+
+    let stdout = match output {
+        Ok(Ok(output)) if output.status.success() => output.stdout,
+        Ok(Ok(output)) => {
+            debug!("焦点查询命令退出码异常: {}", output.status);
+            return Foreground::Unknown;
+        }
+        Ok(Err(e)) => {
+            debug!("无法执行焦点查询命令: {}", e);
+            return Foreground::Unknown;
+        }
+        Err(_) => {
+            debug!("焦点查询命令超时（{}ms）", FOREGROUND_PROBE_TIMEOUT_MS);
+            return Foreground::Unknown;
+        }
+    };
+
+    match String::from_utf8_lossy(&stdout)
+        .split_whitespace()
+        .find_map(|token| token.parse::<u32>().ok())
+    {
+        Some(pid) if candidate_pids.contains(&pid) => Foreground::Candidate(pid),
+        Some(_) => Foreground::Other,
+        None => Foreground::Unknown,
+    }
+}
+
+/// 判断候选进程中是否有进程持有焦点，返回持有焦点的 PID
+///
+/// 配置了焦点查询命令时优先使用其输出；否则（或命令无结果时）按 X11 活动窗口判断。
+/// X11 会话下严格按活动窗口判断；Wayland 会话下仍无法判断焦点时，
+/// `assume_foreground` 为 true 则视为游戏在前台（返回 `fallback_pid`），否则不计时。
+async fn check_any_foreground(
+    candidate_pids: &[u32],
+    fallback_pid: u32,
+    options: &ForegroundOptions,
+) -> Option<u32> {
+    let mut foreground = Foreground::Unknown;
+    if let Some(command) = options.probe_command.as_deref() {
+        foreground = query_foreground_probe(command, candidate_pids).await;
+    }
+    if foreground == Foreground::Unknown {
+        foreground = query_foreground_x11(candidate_pids);
     }
 
-    // 提取 PID
-    let active_pid = pid_reply.value::<u32>().first().copied()?;
+    match foreground {
+        Foreground::Candidate(pid) => Some(pid),
+        Foreground::Other => None,
+        Foreground::Unknown => {
+            (options.assume_foreground && is_wayland_session()).then_some(fallback_pid)
+        }
+    }
+}
 
-    // 5. 检查是否匹配
-    if candidate_pids.contains(&active_pid) {
-        Some(active_pid)
-    } else {
-        None
+/// TODO: 未来可考虑集成 x11 或 wayland 合成器特定功能实现。
+fn check_any_has_window(_candidate_pids: &[u32]) -> Option<u32> {
+    check_any_has_window_x11(_candidate_pids)
+}
+
+/// 读取前台判定设置
+///
+/// Wayland 会话下无法判断焦点时是否照常计时，未设置或读取失败时默认照常计时
+async fn load_foreground_options<R: Runtime>(app_handle: &AppHandle<R>) -> ForegroundOptions {
+    let settings = match app_handle.try_state::<DbState>().map(|state| state.get()) {
+        Some(db) => match db.get_settings().await {
+            Ok(settings) => settings.monitor_settings.unwrap_or_default(),
+            Err(e) => {
+                warn!("读取前台判定设置失败: {}", e);
+                Default::default()
+            }
+        },
+        None => Default::default(),
+    };
+
+    ForegroundOptions {
+        probe_command: settings
+            .foreground_probe_command
+            .map(|command| command.trim().to_string())
+            .filter(|command| !command.is_empty()),
+        assume_foreground: settings.wayland_assume_foreground.unwrap_or(true),
     }
 }
 
@@ -393,6 +646,7 @@ async fn run_game_monitor(
     // {
     let mut accumulated_seconds = 0u64;
     let start_time = get_timestamp();
+    power::ensure_registered();
     tokio::time::sleep(Duration::from_secs(MONITOR_CHECK_INTERVAL_SECS * 3)).await;
 
     // 初始扫描：获取所有候选 PID
@@ -418,7 +672,29 @@ async fn run_game_monitor(
             json!({ "gameId": game_id, "processId": best_pid, "startTime": start_time }),
         )
         .map_err(|e| format!("无法发送 game-session-started 事件: {}", e))?;
+    publish_session(ActiveSessionInfo {
+        game_id,
+        process_id: best_pid,
+        start_time,
+        total_seconds: 0,
+    });
     let mut consecutive_failures = 0u32;
+    let mut last_checkpoint = start_time;
+    let mut play_backup = PlayBackupTimer::load(app_handle, game_id).await;
+    let foreground_options = load_foreground_options(app_handle).await;
+    if let Some(command) = &foreground_options.probe_command {
+        info!("使用自定义焦点查询命令判断前台: {}", command);
+    }
+    if is_wayland_session() {
+        info!(
+            "当前为 Wayland 会话，无法判断焦点时{}累计游戏时间",
+            if foreground_options.assume_foreground {
+                "照常"
+            } else {
+                "不"
+            }
+        );
+    }
 
     // 等待 9 秒让游戏进程充分启动（例如 Launcher -> Game 的切换）
     debug!(
@@ -442,13 +718,38 @@ async fn run_game_monitor(
     // 创建精确的 1 秒间隔定时器
     let mut tick_interval = interval(Duration::from_secs(MONITOR_CHECK_INTERVAL_SECS));
     tick_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut suspend_tracker = SuspendTracker::new(get_timestamp());
+    let mut usage_sampler = UsageSampler::default();
 
     loop {
         tick_interval.tick().await;
 
+        // 系统睡眠期间暂停累计；唤醒后睡眠前的候选 PID 可能已失效，重新扫描
+        match suspend_tracker.tick(app_handle, game_id, get_timestamp()) {
+            SuspendState::Suspended => continue,
+            SuspendState::Resumed => {
+                candidate_pids = get_all_candidate_pids(unit_name).await;
+                if let Some(new_best) = select_best_from_candidates(&candidate_pids) {
+                    best_pid = new_best;
+                }
+                info!(
+                    "唤醒后重新校验候选进程: ID={}, 最佳 PID={}, 候选进程组={:?}",
+                    game_id, best_pid, candidate_pids
+                );
+                usage_sampler.reset_baseline();
+                consecutive_failures = 0;
+                update_health(game_id, |health| health.consecutive_failures = 0);
+                continue;
+            }
+            SuspendState::Running => {}
+        }
+
         let game_running = is_game_running(unit_name).await;
         if !game_running {
             consecutive_failures += 1;
+            update_health(game_id, |health| {
+                health.consecutive_failures = consecutive_failures
+            });
             debug!(
                 "最佳进程 {} 检查失败次数: {}/{}",
                 best_pid, consecutive_failures, MAX_CONSECUTIVE_FAILURES
@@ -464,16 +765,29 @@ async fn run_game_monitor(
         } else {
             // 最佳 PID 仍在运行，重置失败计数
             consecutive_failures = 0;
+            let now = get_timestamp();
+            update_health(game_id, |health| {
+                health.consecutive_failures = 0;
+                health.last_liveness_check = Some(now);
+            });
 
             // 2. 清理候选列表中已失活的 PID（轻量级维护）
 
             // 3. 前台判定：检查候选列表中是否有任何进程在前台
             //    这是关键优化点 - 即使最佳 PID 不在前台，其他候选 PID 在前台也算数
-            if let Some(foreground_pid) = check_any_foreground(&candidate_pids) {
-                accumulated_seconds += 1;
+            if let Some(foreground_pid) =
+                check_any_foreground(&candidate_pids, best_pid, &foreground_options).await
+            {
+                let switched = foreground_pid != best_pid;
+                update_health(game_id, |health| {
+                    health.last_foreground_at = Some(now);
+                    if switched {
+                        health.pid_switches += 1;
+                    }
+                });
 
                 // 如果前台进程不是当前的最佳 PID，考虑切换
-                if foreground_pid != best_pid {
+                if switched {
                     debug!(
                         "前台进程 {} 不是最佳 PID {}，考虑调整",
                         foreground_pid, best_pid
@@ -481,41 +795,76 @@ async fn run_game_monitor(
                     best_pid = foreground_pid;
                 }
 
-                // 发送时间更新
-                if accumulated_seconds > 0
-                    && accumulated_seconds.is_multiple_of(TIME_UPDATE_INTERVAL_SECS)
-                {
-                    let minutes = accumulated_seconds / 60;
-                    // debug!(
-                    //     "发送时间更新事件: {} 分钟 ({} 秒)",
-                    //     minutes, accumulated_seconds
-                    // );
-                    app_handle
-                        .emit(
-                            "game-time-update",
-                            json!({
-                                "gameId": game_id,
-                                "totalMinutes": minutes,
-                                "totalSeconds": accumulated_seconds,
-                                "startTime": start_time,
-                                "currentTime": get_timestamp(),
-                                "processId": best_pid
-                            }),
-                        )
-                        .map_err(|e| format!("无法发送 game-time-update 事件: {}", e))?;
+                // 用户暂停计时期间不累计
+                if !is_timing_paused(game_id) {
+                    accumulated_seconds += 1;
+
+                    // 发送时间更新
+                    if accumulated_seconds > 0
+                        && accumulated_seconds.is_multiple_of(TIME_UPDATE_INTERVAL_SECS)
+                    {
+                        publish_session(ActiveSessionInfo {
+                            game_id,
+                            process_id: best_pid,
+                            start_time,
+                            total_seconds: accumulated_seconds,
+                        });
+                        let minutes = accumulated_seconds / 60;
+                        // debug!(
+                        //     "发送时间更新事件: {} 分钟 ({} 秒)",
+                        //     minutes, accumulated_seconds
+                        // );
+                        app_handle
+                            .emit(
+                                "game-time-update",
+                                json!({
+                                    "gameId": game_id,
+                                    "totalMinutes": minutes,
+                                    "totalSeconds": accumulated_seconds,
+                                    "startTime": start_time,
+                                    "currentTime": get_timestamp(),
+                                    "processId": best_pid
+                                }),
+                            )
+                            .map_err(|e| format!("无法发送 game-time-update 事件: {}", e))?;
+                    }
                 }
             } else {
                 candidate_pids = get_all_candidate_pids(unit_name).await;
             }
+
+            usage_sampler.sample(best_pid);
+
+            // 定期持久化检查点，应用崩溃时下次启动据此补记会话
+            if get_timestamp().saturating_sub(last_checkpoint) >= CHECKPOINT_INTERVAL_SECS {
+                last_checkpoint = get_timestamp();
+                save_checkpoint(
+                    app_handle,
+                    &ActiveSessionInfo {
+                        game_id,
+                        process_id: best_pid,
+                        start_time,
+                        total_seconds: accumulated_seconds,
+                    },
+                )
+                .await;
+            }
+
+            // 会话中按累计游玩时间定时备份存档
+            play_backup.tick(app_handle, game_id, accumulated_seconds);
         }
     }
 
+    remove_session(game_id);
+    clear_checkpoint(app_handle, game_id).await;
     finalize_session(
         app_handle,
         game_id,
         best_pid,
         start_time,
         accumulated_seconds,
+        suspend_tracker.suspended_seconds,
+        usage_sampler.finish(),
     )
 }
 
@@ -527,6 +876,8 @@ async fn run_game_monitor(
 /// * `process_id` - 最终的进程 PID
 /// * `start_time` - 会话开始时间戳
 /// * `accumulated_seconds` - 累计的活动时间（秒）
+/// * `suspended_seconds` - 会话期间系统睡眠的时间（秒），不计入游戏时间
+/// * `process_stats` - 会话期间主进程的内存与 CPU 占用，没有采样时为 None
 ///
 /// # 返回值
 /// 成功返回 `Ok(())`，失败返回包含错误信息的 `Err(String)`
@@ -536,6 +887,8 @@ fn finalize_session<R: Runtime>(
     process_id: u32,
     start_time: u64,
     accumulated_seconds: u64,
+    suspended_seconds: u64,
+    process_stats: Option<ProcessStats>,
 ) -> Result<(), String> {
     let end_time = get_timestamp();
     let total_minutes = accumulated_seconds / 60;
@@ -549,8 +902,8 @@ fn finalize_session<R: Runtime>(
     };
 
     info!(
-        "游戏会话结束: ID={}, 最终 PID={}, 总活动时间={}秒 (计为 {} 分钟)",
-        game_id, process_id, accumulated_seconds, final_minutes
+        "游戏会话结束: ID={}, 最终 PID={}, 总活动时间={}秒 (计为 {} 分钟), 睡眠时间={}秒",
+        game_id, process_id, accumulated_seconds, final_minutes, suspended_seconds
     );
 
     // 发送会话结束事件到前端
@@ -563,10 +916,15 @@ fn finalize_session<R: Runtime>(
                 "endTime": end_time,
                 "totalMinutes": final_minutes,
                 "totalSeconds": accumulated_seconds,
-                "processId": process_id
+                "suspendedSeconds": suspended_seconds,
+                "processId": process_id,
+                "processStats": process_stats
             }),
         )
-        .map_err(|e| format!("无法发送 game-session-ended 事件: {}", e))
+        .map_err(|e| format!("无法发送 game-session-ended 事件: {}", e))?;
+
+    restore_after_session(app_handle, game_id);
+    Ok(())
 }
 
 // ============================================================================
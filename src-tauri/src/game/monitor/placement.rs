@@ -0,0 +1,233 @@
+//! 游戏窗口位置记忆（仅 Windows）
+//!
+//! 不少老引擎不会保存窗口位置，多显示器环境下每次启动都回到主屏。开启 `remember_window_placement` 后，
+//! 监控循环定期记录游戏主窗口（候选进程中面积最大的可见窗口）的位置，会话结束时写入 games 表；
+//! 下次启动时等待窗口出现，再移回原处。
+//!
+//! 本进程为 Per-Monitor DPI 感知，读写的都是屏幕物理像素。目标显示器已不存在时不移动窗口，
+//! 显示器缩放比例变化时按 DPI 比例调整窗口大小。
+
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::window_placement::WindowPlacement;
+use log::{debug, info, warn};
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use windows::Win32::Foundation::{HWND, LPARAM, RECT};
+use windows::Win32::Graphics::Gdi::{MONITOR_DEFAULTTONULL, MonitorFromRect};
+use windows::Win32::UI::HiDpi::{GetDpiForMonitor, GetDpiForWindow, MDT_EFFECTIVE_DPI};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindowRect, GetWindowThreadProcessId, IsIconic, IsWindowVisible, IsZoomed,
+    SW_MAXIMIZE, SWP_NOACTIVATE, SWP_NOZORDER, SetWindowPos, ShowWindow,
+};
+use windows::core::BOOL;
+
+/// 监控循环记录窗口位置的间隔（秒）
+pub const CAPTURE_INTERVAL_SECS: u64 = 5;
+
+/// 启动后等待游戏窗口出现的最长时间
+const RESTORE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 等待窗口出现时的轮询间隔
+const RESTORE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 窗口出现后再等待一会儿，避免引擎初始化时自己把窗口挪回默认位置
+const RESTORE_SETTLE_DELAY: Duration = Duration::from_secs(1);
+
+/// 小于该尺寸的窗口视为启动画面或隐藏的消息窗口
+const MIN_WINDOW_SIZE: i32 = 160;
+
+/// Windows 的标准 DPI（100% 缩放）
+const DEFAULT_DPI: u32 = 96;
+
+struct WindowSearch<'a> {
+    pids: &'a HashSet<u32>,
+    best: Option<(HWND, i64)>,
+}
+
+unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    // SAFETY: lparam 指向 find_main_window 栈上的 WindowSearch，EnumWindows 返回前一直有效
+    let search = unsafe { &mut *(lparam.0 as *mut WindowSearch) };
+    unsafe {
+        if !IsWindowVisible(hwnd).as_bool() || IsIconic(hwnd).as_bool() {
+            return BOOL(1);
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if !search.pids.contains(&pid) {
+            return BOOL(1);
+        }
+        let mut rect = RECT::default();
+        if GetWindowRect(hwnd, &mut rect).is_ok() {
+            let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+            let area = width as i64 * height as i64;
+            if width >= MIN_WINDOW_SIZE
+                && height >= MIN_WINDOW_SIZE
+                && search.best.is_none_or(|(_, best)| area > best)
+            {
+                search.best = Some((hwnd, area));
+            }
+        }
+    }
+    BOOL(1)
+}
+
+/// 候选进程中面积最大的可见、未最小化的顶层窗口
+fn find_main_window(pids: &HashSet<u32>) -> Option<HWND> {
+    let mut search = WindowSearch { pids, best: None };
+    // SAFETY: 回调只在 EnumWindows 调用期间访问 search
+    let _ = unsafe {
+        EnumWindows(
+            Some(collect_window),
+            LPARAM(&mut search as *mut WindowSearch as isize),
+        )
+    };
+    search.best.map(|(hwnd, _)| hwnd)
+}
+
+/// 记录游戏主窗口的当前位置
+///
+/// 最大化时系统报告的是最大化后的矩形，此时沿用 `previous` 中的还原位置，只更新最大化标记；
+/// 找不到窗口（已最小化或已退出）时返回 None。
+pub fn capture(pids: &HashSet<u32>, previous: Option<WindowPlacement>) -> Option<WindowPlacement> {
+    let hwnd = find_main_window(pids)?;
+    // SAFETY: hwnd 来自 EnumWindows，窗口已销毁时下面的调用只会失败
+    unsafe {
+        let dpi = GetDpiForWindow(hwnd);
+        if IsZoomed(hwnd).as_bool() {
+            return previous.map(|placement| WindowPlacement {
+                maximized: true,
+                ..placement
+            });
+        }
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+        Some(WindowPlacement {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right - rect.left,
+            height: rect.bottom - rect.top,
+            maximized: false,
+            dpi: if dpi == 0 { DEFAULT_DPI } else { dpi },
+        })
+    }
+}
+
+/// 把窗口移回记录的位置，目标显示器不存在时不移动
+fn apply(hwnd: HWND, placement: &WindowPlacement) -> Result<(), String> {
+    let target = RECT {
+        left: placement.x,
+        top: placement.y,
+        right: placement.x + placement.width,
+        bottom: placement.y + placement.height,
+    };
+    // SAFETY: target 在调用期间有效，hwnd 来自 EnumWindows
+    unsafe {
+        let monitor = MonitorFromRect(&target, MONITOR_DEFAULTTONULL);
+        if monitor.is_invalid() {
+            return Err("记录的窗口位置不在任何显示器上，可能已断开该显示器".to_string());
+        }
+
+        let (mut dpi_x, mut dpi_y) = (0u32, 0u32);
+        let monitor_dpi = GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y)
+            .map(|_| dpi_x)
+            .unwrap_or(placement.dpi);
+        let scale = |value: i32| {
+            if placement.dpi == 0 || monitor_dpi == placement.dpi {
+                value
+            } else {
+                (value as i64 * monitor_dpi as i64 / placement.dpi as i64) as i32
+            }
+        };
+
+        SetWindowPos(
+            hwnd,
+            None,
+            placement.x,
+            placement.y,
+            scale(placement.width),
+            scale(placement.height),
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        )
+        .map_err(|e| format!("移动窗口失败: {}", e))?;
+        if placement.maximized {
+            let _ = ShowWindow(hwnd, SW_MAXIMIZE);
+        }
+    }
+    Ok(())
+}
+
+/// 读取设置与该游戏记录的窗口位置；未开启时返回 `None`，开启但没有记录时返回 `Some(None)`
+pub async fn load<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    game_id: u32,
+) -> Option<Option<WindowPlacement>> {
+    let db = app_handle.try_state::<DbState>().map(|state| state.get())?;
+    let enabled = match db.get_settings().await {
+        Ok(settings) => settings
+            .monitor_settings
+            .and_then(|settings| settings.remember_window_placement)
+            .unwrap_or(false),
+        Err(e) => {
+            warn!("读取窗口位置设置失败: {}", e);
+            false
+        }
+    };
+    if !enabled {
+        return None;
+    }
+
+    match GamesRepository::find_by_id(&db, game_id as i32).await {
+        Ok(game) => Some(game.and_then(|game| game.window_placement)),
+        Err(e) => {
+            warn!("读取游戏窗口位置失败 (game_id: {}): {}", game_id, e);
+            Some(None)
+        }
+    }
+}
+
+/// 会话结束时保存最后记录的窗口位置
+pub async fn save<R: Runtime>(app_handle: &AppHandle<R>, game_id: u32, placement: WindowPlacement) {
+    let Some(db) = app_handle.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    match GamesRepository::set_window_placement(&db, game_id as i32, placement).await {
+        Ok(()) => debug!("已保存游戏窗口位置 (game_id: {}): {:?}", game_id, placement),
+        Err(e) => warn!("保存游戏窗口位置失败 (game_id: {}): {}", game_id, e),
+    }
+}
+
+/// 在后台等待游戏窗口出现后恢复位置，超时或会话结束时放弃
+pub fn spawn_restore(
+    game_id: u32,
+    placement: WindowPlacement,
+    candidate_pids: Arc<RwLock<HashSet<u32>>>,
+    stop_signal: Arc<AtomicBool>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let deadline = tokio::time::Instant::now() + RESTORE_TIMEOUT;
+        while tokio::time::Instant::now() < deadline {
+            if stop_signal.load(Ordering::Acquire) {
+                return;
+            }
+            if find_main_window(&candidate_pids.read()).is_some() {
+                tokio::time::sleep(RESTORE_SETTLE_DELAY).await;
+                let Some(hwnd) = find_main_window(&candidate_pids.read()) else {
+                    continue;
+                };
+                match apply(hwnd, &placement) {
+                    Ok(()) => info!("已恢复游戏窗口位置 (game_id: {}): {:?}", game_id, placement),
+                    Err(e) => warn!("恢复游戏窗口位置失败 (game_id: {}): {}", game_id, e),
+                }
+                return;
+            }
+            tokio::time::sleep(RESTORE_POLL_INTERVAL).await;
+        }
+        debug!("等待游戏窗口超时，未恢复窗口位置 (game_id: {})", game_id);
+    });
+}
@@ -0,0 +1,221 @@
+//! 系统睡眠 / 唤醒通知
+//!
+//! - Windows：通过 `PowerRegisterSuspendResumeNotification` 的回调方式订阅睡眠事件，不需要窗口消息循环。
+//! - Linux：在独立线程中监听 systemd-logind 的 `PrepareForSleep` 信号（system bus）。
+//!
+//! 通知只更新原子状态，监控循环每秒读取，睡眠期间暂停累计游戏时间；
+//! 唤醒后监控循环会重新扫描候选进程，避免沿用睡眠前已经失效的 PID 和前台状态。
+
+use log::{info, warn};
+use serde_json::json;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// 相邻两次循环的墙钟间隔超过该秒数视为系统曾经睡眠（未收到睡眠通知时的兜底判断）
+pub const SUSPEND_GAP_SECS: u64 = 30;
+
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+static REGISTER: Once = Once::new();
+
+fn mark_suspended() {
+    info!("系统即将进入睡眠");
+    SUSPENDED.store(true, Ordering::Release);
+}
+
+fn mark_resumed() {
+    if SUSPENDED.swap(false, Ordering::AcqRel) {
+        info!("系统已从睡眠中唤醒");
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use log::warn;
+    use std::ffi::c_void;
+    use windows::Win32::Foundation::{ERROR_SUCCESS, HANDLE};
+    use windows::Win32::System::Power::{
+        DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS, PowerRegisterSuspendResumeNotification,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DEVICE_NOTIFY_CALLBACK, PBT_APMRESUMEAUTOMATIC, PBT_APMRESUMESUSPEND, PBT_APMSUSPEND,
+    };
+
+    unsafe extern "system" fn on_power_event(
+        _context: *const c_void,
+        event: u32,
+        _setting: *const c_void,
+    ) -> u32 {
+        match event {
+            PBT_APMSUSPEND => super::mark_suspended(),
+            PBT_APMRESUMEAUTOMATIC | PBT_APMRESUMESUSPEND => super::mark_resumed(),
+            _ => {}
+        }
+        ERROR_SUCCESS.0
+    }
+
+    pub fn register() {
+        // 订阅参数在注册期间必须保持有效，应用生命周期内不注销，直接泄漏为 'static
+        let params: &'static mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS =
+            Box::leak(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+                Callback: Some(on_power_event),
+                Context: std::ptr::null_mut(),
+            }));
+        let mut registration: *mut c_void = std::ptr::null_mut();
+        let result = unsafe {
+            PowerRegisterSuspendResumeNotification(
+                DEVICE_NOTIFY_CALLBACK,
+                HANDLE(params as *mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS as *mut c_void),
+                &mut registration,
+            )
+        };
+        if result != ERROR_SUCCESS {
+            warn!("订阅系统睡眠通知失败，错误码 {}", result.0);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use log::warn;
+
+    /// 阻塞监听 `PrepareForSleep(bool)`：true 为即将睡眠，false 为已唤醒
+    fn listen() -> zbus::Result<()> {
+        let connection = zbus::blocking::Connection::system()?;
+        let proxy = zbus::blocking::Proxy::new(
+            &connection,
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager",
+        )?;
+        for message in proxy.receive_signal("PrepareForSleep")? {
+            match message.body().deserialize::<bool>() {
+                Ok(true) => super::mark_suspended(),
+                Ok(false) => super::mark_resumed(),
+                Err(e) => warn!("解析 PrepareForSleep 信号失败: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn register() {
+        let spawned = std::thread::Builder::new()
+            .name("reina-power-listener".into())
+            .spawn(|| {
+                if let Err(e) = listen() {
+                    warn!("订阅系统睡眠通知失败: {}", e);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("无法启动系统睡眠监听线程: {}", e);
+        }
+    }
+}
+
+/// 订阅睡眠 / 唤醒通知，整个进程只注册一次，注册失败时仅依赖监控循环的时间间隔检测
+pub fn ensure_registered() {
+    REGISTER.call_once(platform::register);
+}
+
+/// 系统当前是否处于睡眠流程中（已收到睡眠通知、尚未收到唤醒通知）
+pub fn is_suspended() -> bool {
+    SUSPENDED.load(Ordering::Acquire)
+}
+
+/// 发送 `game-session-paused` 事件（系统进入睡眠）
+pub fn emit_session_paused<R: Runtime>(app_handle: &AppHandle<R>, game_id: u32, paused_at: u64) {
+    info!("系统睡眠，暂停累计游戏时间: ID={}", game_id);
+    if let Err(e) = app_handle.emit(
+        "game-session-paused",
+        json!({ "gameId": game_id, "reason": "suspend", "pausedAt": paused_at }),
+    ) {
+        warn!("无法发送 game-session-paused 事件: {}", e);
+    }
+}
+
+/// 发送 `game-session-resumed` 事件（系统从睡眠中唤醒）
+pub fn emit_session_resumed<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    game_id: u32,
+    paused_at: u64,
+    resumed_at: u64,
+) {
+    info!(
+        "系统唤醒，恢复累计游戏时间: ID={}, 暂停 {} 秒",
+        game_id,
+        resumed_at.saturating_sub(paused_at)
+    );
+    if let Err(e) = app_handle.emit(
+        "game-session-resumed",
+        json!({
+            "gameId": game_id,
+            "reason": "suspend",
+            "pausedAt": paused_at,
+            "resumedAt": resumed_at,
+            "pausedSeconds": resumed_at.saturating_sub(paused_at)
+        }),
+    ) {
+        warn!("无法发送 game-session-resumed 事件: {}", e);
+    }
+}
+
+/// 监控循环中的睡眠状态跟踪
+///
+/// 每次循环调用 [`SuspendTracker::tick`]：睡眠期间返回 [`SuspendState::Suspended`]，
+/// 调用方应跳过本次累计；唤醒后的第一次循环返回 [`SuspendState::Resumed`]，
+/// 调用方应重新校验候选进程。睡眠时长累计到 `suspended_seconds`，随会话结束事件上报。
+pub struct SuspendTracker {
+    last_tick: u64,
+    /// 进入睡眠的时间，None 表示当前未暂停
+    paused_at: Option<u64>,
+    /// 本次会话中睡眠的总秒数
+    pub suspended_seconds: u64,
+}
+
+/// [`SuspendTracker::tick`] 的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspendState {
+    Running,
+    Suspended,
+    Resumed,
+}
+
+impl SuspendTracker {
+    pub fn new(now: u64) -> Self {
+        Self {
+            last_tick: now,
+            paused_at: None,
+            suspended_seconds: 0,
+        }
+    }
+
+    /// 收到睡眠通知时立即暂停，唤醒后恢复；未收到通知但墙钟跳变时按跳变前的时间补发暂停事件
+    pub fn tick<R: Runtime>(
+        &mut self,
+        app_handle: &AppHandle<R>,
+        game_id: u32,
+        now: u64,
+    ) -> SuspendState {
+        let previous_tick = std::mem::replace(&mut self.last_tick, now);
+        if is_suspended() {
+            if self.paused_at.is_none() {
+                self.paused_at = Some(now);
+                emit_session_paused(app_handle, game_id, now);
+            }
+            return SuspendState::Suspended;
+        }
+
+        let gap_paused_at = (now.saturating_sub(previous_tick) > SUSPEND_GAP_SECS).then(|| {
+            emit_session_paused(app_handle, game_id, previous_tick);
+            previous_tick
+        });
+        match self.paused_at.take().or(gap_paused_at) {
+            Some(paused_at) => {
+                self.suspended_seconds += now.saturating_sub(paused_at);
+                emit_session_resumed(app_handle, game_id, paused_at, now);
+                SuspendState::Resumed
+            }
+            None => SuspendState::Running,
+        }
+    }
+}
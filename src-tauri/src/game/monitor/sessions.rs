@@ -0,0 +1,326 @@
+//! 跨平台的活跃会话快照
+//!
+//! 多个游戏可以同时运行，每个游戏各有一个监控循环，`SessionManager` 按游戏 ID 统一记录所有会话。
+//! 监控循环在会话开始和每次时间更新时写入快照，会话结束时移除。
+//! 前端刷新或冷启动时据此恢复正在运行的游戏状态，无需等待下一次 `game-time-update` 事件。
+//!
+//! 同时每隔一段时间把快照持久化到 `session_checkpoints` 表，
+//! 应用中途崩溃时由下次启动的 `SessionCheckpointsRepository::reconcile_orphans` 补记会话。
+//!
+//! 会话可以暂停计时（如离开座位但不想关闭游戏），暂停期间监控照常检查进程存活，但不累计游戏时间。
+//!
+//! 监控循环还会记录每个会话的健康状况（最近一次存活检查、前台检测、PID 切换次数等），
+//! 用户反馈"时间不累计"时可以通过 `get_monitor_health` 复制诊断信息。
+
+use crate::database::db::DbState;
+use crate::database::repository::session_checkpoints_repository::SessionCheckpointsRepository;
+use crate::utils::metrics::CommandTimer;
+use log::warn;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// 会话检查点写入间隔（秒）
+pub(super) const CHECKPOINT_INTERVAL_SECS: u64 = 60;
+
+/// 停止游戏时默认等待其自行退出的秒数，超时后强制终止
+pub const DEFAULT_STOP_GRACE_SECS: u64 = 5;
+
+/// 停止游戏时最终起作用的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopStage {
+    /// 游戏在请求关闭（Windows 为 WM_CLOSE，Linux 为 SIGTERM）后自行退出
+    Graceful,
+    /// 超时后强制终止（Windows 为 TerminateProcess，Linux 为 SIGKILL）
+    Forced,
+}
+
+/// 停止游戏的结果
+#[derive(Debug, Clone, Copy)]
+pub struct StopOutcome {
+    pub stage: StopStage,
+    /// 停止时仍在运行的进程数量
+    pub terminated_count: u32,
+}
+
+/// 正在监控的游戏会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSessionInfo {
+    pub game_id: u32,
+    pub process_id: u32,
+    pub start_time: u64,
+    /// 已累计的前台时间（秒）
+    pub total_seconds: u64,
+}
+
+/// 监控循环记录的健康状况
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct MonitorHealth {
+    /// 最近一次确认游戏进程存活的时间戳
+    pub last_liveness_check: Option<u64>,
+    /// 最近一次检测到游戏窗口在前台的时间戳
+    pub last_foreground_at: Option<u64>,
+    /// 会话期间切换监控 PID 的次数
+    pub pid_switches: u32,
+    /// 当前连续存活检查失败的次数
+    pub consecutive_failures: u32,
+}
+
+/// `get_monitor_health` 返回的单个会话诊断信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHealth {
+    pub game_id: u32,
+    pub process_id: u32,
+    pub start_time: u64,
+    pub total_seconds: u64,
+    pub last_liveness_check: Option<u64>,
+    pub last_foreground_at: Option<u64>,
+    pub pid_switches: u32,
+    pub consecutive_failures: u32,
+}
+
+/// `get_active_sessions` 返回的单个会话
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSessionSummary {
+    pub game_id: u32,
+    pub process_id: u32,
+    pub start_time: u64,
+    /// 自会话开始经过的墙钟时间（秒）
+    pub elapsed_seconds: u64,
+    /// 已累计的前台时间（秒）
+    pub total_seconds: u64,
+    /// 是否已暂停计时
+    pub paused: bool,
+}
+
+/// 跟踪所有正在监控的游戏会话，以游戏 ID 为键
+#[derive(Default)]
+pub struct SessionManager {
+    snapshots: RwLock<HashMap<u32, ActiveSessionInfo>>,
+    health: RwLock<HashMap<u32, MonitorHealth>>,
+    /// 已暂停计时的游戏
+    paused: RwLock<HashSet<u32>>,
+}
+
+impl SessionManager {
+    /// 写入或更新会话快照
+    fn publish(&self, info: ActiveSessionInfo) {
+        self.snapshots.write().insert(info.game_id, info);
+    }
+
+    /// 移除会话快照、健康状况与暂停状态
+    fn remove(&self, game_id: u32) {
+        self.snapshots.write().remove(&game_id);
+        self.health.write().remove(&game_id);
+        self.paused.write().remove(&game_id);
+    }
+
+    /// 切换会话的暂停计时状态，返回切换后是否暂停；没有该游戏的会话时返回 None
+    fn toggle_paused(&self, game_id: u32) -> Option<bool> {
+        if !self.snapshots.read().contains_key(&game_id) {
+            return None;
+        }
+        let mut paused = self.paused.write();
+        if paused.remove(&game_id) {
+            Some(false)
+        } else {
+            paused.insert(game_id);
+            Some(true)
+        }
+    }
+
+    fn is_paused(&self, game_id: u32) -> bool {
+        self.paused.read().contains(&game_id)
+    }
+
+    fn update_health(&self, game_id: u32, update: impl FnOnce(&mut MonitorHealth)) {
+        update(self.health.write().entry(game_id).or_default());
+    }
+
+    /// 指定游戏正在监控的会话
+    pub fn get(&self, game_id: u32) -> Option<ActiveSessionInfo> {
+        self.snapshots.read().get(&game_id).cloned()
+    }
+
+    /// 所有会话，按开始时间排序
+    pub fn sessions(&self) -> Vec<ActiveSessionInfo> {
+        let mut sessions: Vec<ActiveSessionInfo> =
+            self.snapshots.read().values().cloned().collect();
+        sessions.sort_by_key(|session| session.start_time);
+        sessions
+    }
+
+    /// 所有会话及其经过时间，按开始时间排序
+    pub fn summaries(&self, now: u64) -> Vec<ActiveSessionSummary> {
+        self.sessions()
+            .into_iter()
+            .map(|session| ActiveSessionSummary {
+                game_id: session.game_id,
+                process_id: session.process_id,
+                start_time: session.start_time,
+                elapsed_seconds: now.saturating_sub(session.start_time),
+                total_seconds: session.total_seconds,
+                paused: self.is_paused(session.game_id),
+            })
+            .collect()
+    }
+
+    /// 所有会话的健康状况，按开始时间排序
+    pub fn health(&self) -> Vec<SessionHealth> {
+        let sessions = self.sessions();
+        let health = self.health.read();
+        sessions
+            .into_iter()
+            .map(|session| {
+                let health = health.get(&session.game_id).copied().unwrap_or_default();
+                SessionHealth {
+                    game_id: session.game_id,
+                    process_id: session.process_id,
+                    start_time: session.start_time,
+                    total_seconds: session.total_seconds,
+                    last_liveness_check: health.last_liveness_check,
+                    last_foreground_at: health.last_foreground_at,
+                    pid_switches: health.pid_switches,
+                    consecutive_failures: health.consecutive_failures,
+                }
+            })
+            .collect()
+    }
+}
+
+/// 全局的会话管理器
+static SESSION_MANAGER: LazyLock<SessionManager> = LazyLock::new(SessionManager::default);
+
+/// 写入或更新会话快照
+pub(super) fn publish_session(info: ActiveSessionInfo) {
+    SESSION_MANAGER.publish(info);
+}
+
+/// 移除会话快照
+pub(super) fn remove_session(game_id: u32) {
+    SESSION_MANAGER.remove(game_id);
+}
+
+/// 更新会话的健康状况
+pub(super) fn update_health(game_id: u32, update: impl FnOnce(&mut MonitorHealth)) {
+    SESSION_MANAGER.update_health(game_id, update);
+}
+
+/// 会话是否已暂停计时
+pub(super) fn is_timing_paused(game_id: u32) -> bool {
+    SESSION_MANAGER.is_paused(game_id)
+}
+
+/// 切换指定游戏的暂停计时状态，返回切换后是否暂停；游戏没有正在监控的会话时返回 None
+pub fn toggle_timing_pause(game_id: u32) -> Option<bool> {
+    SESSION_MANAGER.toggle_paused(game_id)
+}
+
+/// 获取所有正在监控的会话的健康状况，按开始时间排序
+pub fn monitor_health() -> Vec<SessionHealth> {
+    SESSION_MANAGER.health()
+}
+
+/// 获取监控子系统的诊断信息：每个活跃会话的最近存活检查、前台检测时间、PID 切换次数与连续失败次数
+#[tauri::command]
+pub fn get_monitor_health() -> Vec<SessionHealth> {
+    let _timer = CommandTimer::start("get_monitor_health");
+    monitor_health()
+}
+
+/// 获取所有正在监控的会话，按开始时间排序
+pub fn active_sessions() -> Vec<ActiveSessionInfo> {
+    SESSION_MANAGER.sessions()
+}
+
+/// 获取指定游戏正在监控的会话
+pub fn find_session(game_id: u32) -> Option<ActiveSessionInfo> {
+    SESSION_MANAGER.get(game_id)
+}
+
+/// 获取所有正在运行的游戏会话：游戏 ID、主进程 PID、开始时间、经过时间与已累计的前台时间
+#[tauri::command]
+pub fn get_active_sessions() -> Vec<ActiveSessionSummary> {
+    let _timer = CommandTimer::start("get_active_sessions");
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    SESSION_MANAGER.summaries(now)
+}
+
+/// 持久化会话检查点，失败只记录日志，不影响监控
+pub(super) async fn save_checkpoint<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    info: &ActiveSessionInfo,
+) {
+    let Some(db) = app_handle.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    if let Err(e) = SessionCheckpointsRepository::save_checkpoint(
+        &db,
+        info.game_id as i32,
+        info.start_time as i32,
+        info.total_seconds as i32,
+    )
+    .await
+    {
+        warn!("写入会话检查点失败 (game_id: {}): {}", info.game_id, e);
+    }
+}
+
+/// 删除会话检查点（会话正常结束，交由前端记录）
+pub(super) async fn clear_checkpoint<R: Runtime>(app_handle: &AppHandle<R>, game_id: u32) {
+    let Some(db) = app_handle.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    if let Err(e) = SessionCheckpointsRepository::delete_checkpoint(&db, game_id as i32).await {
+        warn!("删除会话检查点失败 (game_id: {}): {}", game_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_concurrent_sessions_independently() {
+        let manager = SessionManager::default();
+        for (game_id, start_time) in [(2, 200), (1, 100)] {
+            manager.publish(ActiveSessionInfo {
+                game_id,
+                process_id: game_id * 10,
+                start_time,
+                total_seconds: 0,
+            });
+        }
+        manager.update_health(1, |health| health.pid_switches = 3);
+        assert_eq!(manager.toggle_paused(2), Some(true));
+        assert_eq!(manager.toggle_paused(3), None);
+
+        let summaries = manager.summaries(260);
+        assert_eq!(
+            summaries
+                .iter()
+                .map(|s| (s.game_id, s.elapsed_seconds))
+                .collect::<Vec<_>>(),
+            [(1, 160), (2, 60)]
+        );
+
+        assert!(summaries[1].paused && !summaries[0].paused);
+
+        manager.remove(1);
+        let health = manager.health();
+        assert_eq!(health.len(), 1);
+        assert_eq!((health[0].game_id, health[0].pid_switches), (2, 0));
+
+        manager.remove(2);
+        assert!(!manager.is_paused(2));
+    }
+}
@@ -0,0 +1,239 @@
+//! 会话期间主进程的资源占用采样
+//!
+//! 监控循环每秒对最佳 PID 采样一次常驻内存（Windows 为工作集）与累计 CPU 时间，
+//! 会话结束时汇总为最小值、平均值和峰值，随 `game-session-ended` 事件发送并写入会话记录。
+//!
+//! - Windows：`GetProcessMemoryInfo` + `GetProcessTimes`
+//! - Linux：读取 `/proc/<pid>/statm` 与 `/proc/<pid>/stat`
+
+use crate::entity::process_stats::ProcessStats;
+use std::time::{Duration, Instant};
+
+/// 按两次采样之间的 CPU 时间与墙钟时间计算占用百分比（相对全部逻辑核心，最大 100）
+fn cpu_percent(cpu_delta: Duration, wall_delta: Duration, cores: usize) -> u32 {
+    let capacity = wall_delta.as_secs_f64() * cores.max(1) as f64;
+    if capacity <= 0.0 {
+        return 0;
+    }
+    (cpu_delta.as_secs_f64() / capacity * 100.0)
+        .round()
+        .clamp(0.0, 100.0) as u32
+}
+
+#[derive(Default)]
+struct Series {
+    min: u64,
+    max: u64,
+    sum: u128,
+    count: u64,
+}
+
+impl Series {
+    fn push(&mut self, value: u64) {
+        if self.count == 0 || value < self.min {
+            self.min = value;
+        }
+        self.max = self.max.max(value);
+        self.sum += value as u128;
+        self.count += 1;
+    }
+
+    fn avg(&self) -> u64 {
+        if self.count == 0 {
+            0
+        } else {
+            (self.sum / self.count as u128) as u64
+        }
+    }
+}
+
+/// 单个会话的资源占用采样器
+pub struct UsageSampler {
+    cores: usize,
+    /// 上一次采样的 PID、累计 CPU 时间与采样时刻，PID 变化后重新建立基准
+    baseline: Option<(u32, Duration, Instant)>,
+    memory: Series,
+    cpu: Series,
+}
+
+impl Default for UsageSampler {
+    fn default() -> Self {
+        Self {
+            cores: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            baseline: None,
+            memory: Series::default(),
+            cpu: Series::default(),
+        }
+    }
+}
+
+impl UsageSampler {
+    /// 对指定 PID 采样一次，进程已退出或无权访问时忽略
+    pub fn sample(&mut self, pid: u32) {
+        if let Some((memory, cpu_time)) = platform::read_usage(pid) {
+            self.record(pid, memory, cpu_time, Instant::now());
+        }
+    }
+
+    /// 丢弃 CPU 基准，用于系统唤醒后，避免把睡眠时间算进 CPU 占用的分母
+    pub fn reset_baseline(&mut self) {
+        self.baseline = None;
+    }
+
+    fn record(&mut self, pid: u32, memory: u64, cpu_time: Duration, now: Instant) {
+        self.memory.push(memory);
+        if let Some((last_pid, last_cpu, last_at)) = self.baseline
+            && last_pid == pid
+        {
+            self.cpu.push(cpu_percent(
+                cpu_time.saturating_sub(last_cpu),
+                now.duration_since(last_at),
+                self.cores,
+            ) as u64);
+        }
+        self.baseline = Some((pid, cpu_time, now));
+    }
+
+    /// 汇总采样结果，没有任何内存采样时返回 None
+    pub fn finish(&self) -> Option<ProcessStats> {
+        (self.memory.count > 0).then(|| ProcessStats {
+            memory_min_bytes: self.memory.min,
+            memory_avg_bytes: self.memory.avg(),
+            memory_peak_bytes: self.memory.max,
+            cpu_min_percent: self.cpu.min as u32,
+            cpu_avg_percent: self.cpu.avg() as u32,
+            cpu_peak_percent: self.cpu.max as u32,
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::time::Duration;
+    use windows::Win32::{
+        Foundation::{CloseHandle, FILETIME},
+        System::{
+            ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+            Threading::{GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
+        },
+    };
+
+    /// FILETIME 以 100 纳秒为单位
+    fn filetime_duration(time: FILETIME) -> Duration {
+        let ticks = ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+        Duration::from_nanos(ticks.saturating_mul(100))
+    }
+
+    /// 读取进程的工作集大小（字节）与累计 CPU 时间（内核态 + 用户态）
+    pub fn read_usage(pid: u32) -> Option<(u64, Duration)> {
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+            let mut counters = PROCESS_MEMORY_COUNTERS::default();
+            let memory = GetProcessMemoryInfo(
+                handle,
+                &mut counters,
+                std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+            )
+            .ok()
+            .map(|_| counters.WorkingSetSize as u64);
+
+            let mut creation = FILETIME::default();
+            let mut exit = FILETIME::default();
+            let mut kernel = FILETIME::default();
+            let mut user = FILETIME::default();
+            let cpu_time =
+                GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user)
+                    .ok()
+                    .map(|_| filetime_duration(kernel) + filetime_duration(user));
+            CloseHandle(handle).ok();
+
+            Some((memory?, cpu_time?))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    /// 页大小与每秒时钟滴答数
+    fn system_units() -> (u64, u64) {
+        static UNITS: OnceLock<(u64, u64)> = OnceLock::new();
+        *UNITS.get_or_init(|| {
+            let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+            let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+            (
+                if page_size > 0 {
+                    page_size as u64
+                } else {
+                    4096
+                },
+                if ticks > 0 { ticks as u64 } else { 100 },
+            )
+        })
+    }
+
+    /// 读取进程的常驻内存（字节）与累计 CPU 时间（utime + stime）
+    pub fn read_usage(pid: u32) -> Option<(u64, Duration)> {
+        let (page_size, ticks_per_sec) = system_units();
+
+        let statm = std::fs::read_to_string(format!("/proc/{}/statm", pid)).ok()?;
+        let resident_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+
+        // 进程名可能包含空格和括号，从最后一个 ')' 之后开始按字段解析：
+        // 第 0 个字段为 state，utime / stime 分别为第 11、12 个
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let mut fields = stat
+            .get(stat.rfind(')')? + 1..)?
+            .split_whitespace()
+            .skip(11);
+        let utime: u64 = fields.next()?.parse().ok()?;
+        let stime: u64 = fields.next()?.parse().ok()?;
+        let ticks = utime + stime;
+
+        Some((
+            resident_pages * page_size,
+            Duration::from_secs(ticks / ticks_per_sec)
+                + Duration::from_nanos((ticks % ticks_per_sec) * 1_000_000_000 / ticks_per_sec),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_percent_is_relative_to_all_cores() {
+        let second = Duration::from_secs(1);
+        assert_eq!(cpu_percent(second, second, 4), 25);
+        assert_eq!(cpu_percent(second * 8, second, 4), 100);
+        assert_eq!(cpu_percent(second, Duration::ZERO, 4), 0);
+    }
+
+    #[test]
+    fn pid_switch_resets_cpu_baseline() {
+        let mut sampler = UsageSampler {
+            cores: 1,
+            baseline: None,
+            memory: Series::default(),
+            cpu: Series::default(),
+        };
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        sampler.record(1, 100, Duration::from_secs(10), at(0));
+        sampler.record(1, 300, Duration::from_millis(10_500), at(1));
+        // 新进程的累计 CPU 时间与旧进程无关，不能计算差值
+        sampler.record(2, 200, Duration::from_secs(50), at(2));
+        sampler.record(2, 200, Duration::from_millis(50_100), at(3));
+
+        let stats = sampler.finish().unwrap();
+        assert_eq!(stats.memory_min_bytes, 100);
+        assert_eq!(stats.memory_avg_bytes, 200);
+        assert_eq!(stats.memory_peak_bytes, 300);
+        assert_eq!(stats.cpu_min_percent, 10);
+        assert_eq!(stats.cpu_avg_percent, 30);
+        assert_eq!(stats.cpu_peak_percent, 50);
+    }
+}
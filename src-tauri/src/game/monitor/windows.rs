@@ -15,9 +15,26 @@ use std::sync::{
 };
 use std::time::SystemTime;
 use std::time::{Duration, UNIX_EPOCH};
-use tauri::{AppHandle, Emitter, Runtime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 use tokio::time::{MissedTickBehavior, interval};
 
+use crate::backup::schedule::PlayBackupTimer;
+use crate::database::db::DbState;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::process_stats::ProcessStats;
+
+use crate::utils::notification::{NotificationCategory, notify};
+use crate::utils::tray::{hide_for_session, restore_after_session};
+
+use super::gamepad;
+use super::placement;
+use super::power::{self, SuspendState, SuspendTracker};
+use super::sessions::{
+    ActiveSessionInfo, CHECKPOINT_INTERVAL_SECS, StopOutcome, StopStage, clear_checkpoint,
+    is_timing_paused, publish_session, remove_session, save_checkpoint, update_health,
+};
+use super::usage::UsageSampler;
+
 use {
     log::warn, parking_lot::RwLock, std::collections::HashSet, std::path::Path, std::sync::OnceLock,
 };
@@ -29,11 +46,13 @@ use windows::Win32::{
             CREATE_TOOLHELP_SNAPSHOT_FLAGS, CreateToolhelp32Snapshot, PROCESSENTRY32W,
             Process32FirstW, Process32NextW,
         },
+        SystemInformation::GetTickCount,
         Threading::{
             GetExitCodeProcess, OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
             PROCESS_TERMINATE, QueryFullProcessImageNameW, TerminateProcess,
         },
     },
+    UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
     UI::WindowsAndMessaging::GetWindowThreadProcessId,
 };
 
@@ -50,6 +69,9 @@ const TIME_UPDATE_INTERVAL_SECS: u64 = 1;
 /// 监控循环检查间隔（秒）
 const MONITOR_CHECK_INTERVAL_SECS: u64 = 1;
 
+/// 停止游戏时检查进程是否已退出的间隔
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
 // ============================================================================
 // 数据结构定义
 // ============================================================================
@@ -132,50 +154,163 @@ fn unregister_session(game_id: u32) {
 // 公共 API
 // ============================================================================
 
-/// 停止指定游戏的监控并终止所有相关进程
+/// 停止指定游戏的监控并结束所有相关进程
+///
+/// 先向游戏的顶层窗口发送 WM_CLOSE，让游戏有机会保存设置后自行退出；
+/// `grace` 内仍未退出的进程再用 TerminateProcess 强制终止。
 ///
 /// # Arguments
 /// * `game_id` - 游戏 ID
+/// * `grace` - 等待游戏自行退出的时长
 ///
 /// # Returns
-/// 成功返回终止的进程数量，失败返回错误信息
-pub async fn stop_game_session(game_id: u32) -> Result<u32, String> {
-    // 获取会话信息
-    let sessions = get_sessions().read();
-    let session = sessions
-        .get(&game_id)
-        .ok_or_else(|| format!("未找到游戏 {} 的监控会话", game_id))?;
+/// 成功返回起作用的阶段与停止的进程数量，失败返回错误信息
+pub async fn stop_game_session(game_id: u32, grace: Duration) -> Result<StopOutcome, String> {
+    // 读锁只在这个块内持有，之后的等待会跨越 await
+    let pids: Vec<u32> = {
+        let sessions = get_sessions().read();
+        let session = sessions
+            .get(&game_id)
+            .ok_or_else(|| format!("未找到游戏 {} 的监控会话", game_id))?;
+
+        // 发送停止信号
+        session.stop_signal.store(true, Ordering::Release);
+
+        // 复制候选 PID 列表；同时运行的其他游戏可能共用启动器等进程，属于其他会话的 PID 不结束
+        let other_pids: HashSet<u32> = sessions
+            .iter()
+            .filter(|(id, _)| **id != game_id)
+            .flat_map(|(_, other)| {
+                other
+                    .candidate_pids
+                    .read()
+                    .iter()
+                    .copied()
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        session
+            .candidate_pids
+            .read()
+            .iter()
+            .copied()
+            .filter(|pid| !other_pids.contains(pid))
+            .collect()
+    };
 
-    // 发送停止信号
-    session.stop_signal.store(true, Ordering::Release);
+    let running: Vec<u32> = pids
+        .into_iter()
+        .filter(|pid| is_process_running(*pid))
+        .collect();
+    let terminated_count = running.len() as u32;
 
-    // 复制候选 PID 列表
-    let pids: Vec<u32> = session.candidate_pids.read().iter().copied().collect();
+    // 1. 请求关闭游戏窗口，等待进程自行退出
+    let closed_windows = request_close_windows(&running.iter().copied().collect());
+    if closed_windows > 0 {
+        debug!(
+            "已向游戏 {} 的 {} 个窗口发送 WM_CLOSE，最多等待 {:?}",
+            game_id, closed_windows, grace
+        );
+        let deadline = tokio::time::Instant::now() + grace;
+        while tokio::time::Instant::now() < deadline
+            && running.iter().any(|pid| is_process_running(*pid))
+        {
+            tokio::time::sleep(STOP_POLL_INTERVAL).await;
+        }
+    }
 
-    // 释放读锁
-    drop(sessions);
+    let remaining: Vec<u32> = running
+        .iter()
+        .copied()
+        .filter(|pid| is_process_running(*pid))
+        .collect();
+    if remaining.is_empty() {
+        info!(
+            "游戏 {} 已自行退出，共 {} 个进程",
+            game_id, terminated_count
+        );
+        return Ok(StopOutcome {
+            stage: StopStage::Graceful,
+            terminated_count,
+        });
+    }
 
-    // 终止所有候选进程
-    let mut terminated_count = 0u32;
-    for pid in pids {
-        if is_process_running(pid) {
-            match terminate_process(pid) {
-                Ok(_) => {
-                    info!("成功终止进程 PID: {}", pid);
-                    terminated_count += 1;
-                }
-                Err(e) => {
-                    warn!("终止进程 {} 失败: {}", pid, e);
-                }
-            }
+    // 2. 强制终止仍在运行的进程
+    for pid in remaining {
+        match terminate_process(pid) {
+            Ok(_) => info!("成功终止进程 PID: {}", pid),
+            Err(e) => warn!("终止进程 {} 失败: {}", pid, e),
         }
     }
 
     info!(
-        "游戏 {} 停止完成，共终止 {} 个进程",
-        game_id, terminated_count
+        "游戏 {} 未在 {:?} 内退出，已强制终止，共 {} 个进程",
+        game_id, grace, terminated_count
     );
-    Ok(terminated_count)
+    Ok(StopOutcome {
+        stage: StopStage::Forced,
+        terminated_count,
+    })
+}
+
+/// 向候选进程的所有可见顶层窗口发送 WM_CLOSE，返回发送的窗口数量
+fn request_close_windows(pids: &HashSet<u32>) -> usize {
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, IsWindowVisible, PostMessageW, WM_CLOSE,
+    };
+    use windows::core::BOOL;
+
+    struct CloseSearch<'a> {
+        pids: &'a HashSet<u32>,
+        windows: Vec<HWND>,
+    }
+
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        // SAFETY: lparam 指向 request_close_windows 栈上的 CloseSearch，EnumWindows 返回前一直有效
+        let search = unsafe { &mut *(lparam.0 as *mut CloseSearch) };
+        unsafe {
+            if IsWindowVisible(hwnd).as_bool() {
+                let mut pid = 0u32;
+                GetWindowThreadProcessId(hwnd, Some(&mut pid));
+                if search.pids.contains(&pid) {
+                    search.windows.push(hwnd);
+                }
+            }
+        }
+        BOOL(1)
+    }
+
+    if pids.is_empty() {
+        return 0;
+    }
+    let mut search = CloseSearch {
+        pids,
+        windows: Vec::new(),
+    };
+    // SAFETY: 回调只在 EnumWindows 调用期间访问 search
+    let _ = unsafe {
+        EnumWindows(
+            Some(collect),
+            LPARAM(&mut search as *mut CloseSearch as isize),
+        )
+    };
+    search
+        .windows
+        .iter()
+        // SAFETY: hwnd 来自 EnumWindows，窗口已销毁时发送只会失败
+        .filter(|hwnd| {
+            unsafe { PostMessageW(Some(**hwnd), WM_CLOSE, WPARAM(0), LPARAM(0)) }.is_ok()
+        })
+        .count()
+}
+
+/// 获取指定游戏当前监控的候选进程 PID，游戏未在监控中时返回 None
+pub fn get_game_process_ids(game_id: u32) -> Option<HashSet<u32>> {
+    get_sessions()
+        .read()
+        .get(&game_id)
+        .map(|session| session.candidate_pids.read().clone())
 }
 
 /// 启动指定游戏进程的监控
@@ -198,6 +333,7 @@ pub async fn monitor_game<R: Runtime>(
     process_id: u32,
     executable_path: String,
 ) {
+    hide_for_session(&app_handle, game_id).await;
     let app_handle_clone = app_handle.clone();
 
     tauri::async_runtime::spawn(async move {
@@ -205,6 +341,16 @@ pub async fn monitor_game<R: Runtime>(
             run_game_monitor(app_handle_clone, game_id, process_id, executable_path).await
         {
             error!("游戏监控任务 (game_id: {}) 出错: {}", game_id, e);
+            notify(
+                &app_handle,
+                NotificationCategory::Error,
+                "游戏监控失败",
+                &format!("本次游戏时间可能未被记录：{}", e),
+            )
+            .await;
+            remove_session(game_id);
+            clear_checkpoint(&app_handle, game_id).await;
+            restore_after_session(&app_handle, game_id);
         }
     });
 }
@@ -234,7 +380,8 @@ pub async fn monitor_game<R: Runtime>(
 /// 2. 扫描游戏目录获取所有候选进程
 /// 3. 创建共享状态和停止信号
 /// 4. 启动 Hook 线程监听前台窗口变化
-/// 5. 主循环每秒检查状态并累计时间
+/// 5. 主循环每秒检查状态并累计时间（启用挂机判定时，挂机期间计入挂机时间；系统睡眠期间暂停并发送
+///    `game-session-paused` / `game-session-resumed` 事件，唤醒后重新扫描候选进程）
 /// 6. 进程失活时触发重新扫描
 /// 7. 会话结束时发送结束事件
 async fn run_game_monitor<R: Runtime>(
@@ -244,7 +391,11 @@ async fn run_game_monitor<R: Runtime>(
     executable_path: String,
 ) -> Result<(), String> {
     let mut accumulated_seconds = 0u64;
+    let mut idle_seconds = 0u64;
+    let mut is_idle = false;
     let start_time = get_timestamp();
+    let idle_timeout = load_idle_timeout(&app_handle).await;
+    power::ensure_registered();
 
     // 等待游戏进程充分启动（例如 Launcher -> Game 的切换）
     debug!("等待 3 秒以便游戏进程充分启动...");
@@ -298,6 +449,30 @@ async fn run_game_monitor<R: Runtime>(
         stop_signal.clone(),
     );
 
+    // 开启窗口位置记忆时，等待窗口出现后移回上次的位置
+    let stored_placement = placement::load(&app_handle, game_id).await;
+    let remember_placement = stored_placement.is_some();
+    let mut window_placement = stored_placement.flatten();
+    let mut placement_captured = false;
+    if let Some(saved) = window_placement {
+        placement::spawn_restore(
+            game_id,
+            saved,
+            shared_candidate_pids.clone(),
+            stop_signal.clone(),
+        );
+    }
+
+    // 游戏设置了手柄映射时，会话期间把手柄输入转换为键盘 / 鼠标操作
+    if let Some(mapping) = gamepad::load(&app_handle, game_id).await {
+        gamepad::spawn_mapper(
+            game_id,
+            mapping,
+            shared_candidate_pids.clone(),
+            stop_signal.clone(),
+        );
+    }
+
     // 获取当前最佳 PID
     let best_pid = monitor_state.read().best_pid;
 
@@ -308,9 +483,20 @@ async fn run_game_monitor<R: Runtime>(
             json!({ "gameId": game_id, "processId": best_pid, "startTime": start_time }),
         )
         .map_err(|e| format!("无法发送 game-session-started 事件: {}", e))?;
+    publish_session(ActiveSessionInfo {
+        game_id,
+        process_id: best_pid,
+        start_time,
+        total_seconds: 0,
+    });
 
     let mut consecutive_failures = 0u32;
     let mut last_best_pid = best_pid;
+    let mut last_checkpoint = start_time;
+    let mut play_backup = PlayBackupTimer::load(&app_handle, game_id).await;
+    let mut last_placement_capture = start_time;
+    let mut suspend_tracker = SuspendTracker::new(get_timestamp());
+    let mut usage_sampler = UsageSampler::default();
 
     // 创建精确的 1 秒间隔定时器
     let mut tick_interval = interval(Duration::from_secs(MONITOR_CHECK_INTERVAL_SECS));
@@ -326,6 +512,42 @@ async fn run_game_monitor<R: Runtime>(
             break;
         }
 
+        // 系统睡眠期间暂停累计；唤醒后睡眠前的候选 PID 和前台状态可能已失效，重新扫描
+        match suspend_tracker.tick(&app_handle, game_id, get_timestamp()) {
+            SuspendState::Suspended => continue,
+            SuspendState::Resumed => {
+                let mut pids: HashSet<u32> = get_all_candidate_pids(&executable_path)
+                    .into_iter()
+                    .collect();
+                let previous_best = monitor_state.read().best_pid;
+                if is_process_running(previous_best) {
+                    pids.insert(previous_best);
+                }
+                info!(
+                    "唤醒后重新校验候选进程: ID={}, 候选进程组={:?}",
+                    game_id, pids
+                );
+                {
+                    let mut state = monitor_state.write();
+                    if !pids.contains(&state.best_pid)
+                        && let Some(pid) = pids.iter().next()
+                    {
+                        state.best_pid = *pid;
+                    }
+                    // 前台状态由 Hook 线程在下一次检查时重新写入
+                    state.is_foreground = false;
+                }
+                if !pids.is_empty() {
+                    *shared_candidate_pids.write() = pids;
+                }
+                usage_sampler.reset_baseline();
+                consecutive_failures = 0;
+                update_health(game_id, |health| health.consecutive_failures = 0);
+                continue;
+            }
+            SuspendState::Running => {}
+        }
+
         // 读取共享状态（使用 RwLock 读锁，不会阻塞 Hook 线程的写操作太久）
         let (is_foreground, current_best_pid) = {
             let state = monitor_state.read();
@@ -337,6 +559,9 @@ async fn run_game_monitor<R: Runtime>(
 
         if !best_pid_running {
             consecutive_failures += 1;
+            update_health(game_id, |health| {
+                health.consecutive_failures = consecutive_failures
+            });
             debug!(
                 "最佳进程 {} 检查失败次数: {}/{}",
                 current_best_pid, consecutive_failures, MAX_CONSECUTIVE_FAILURES
@@ -373,27 +598,69 @@ async fn run_game_monitor<R: Runtime>(
 
                 debug!("成功切换到新的最佳进程 PID: {}", new_best_pid);
                 consecutive_failures = 0;
+                update_health(game_id, |health| {
+                    health.consecutive_failures = 0;
+                    if new_best_pid != last_best_pid {
+                        health.pid_switches += 1;
+                    }
+                });
                 last_best_pid = new_best_pid;
                 continue;
             }
         } else {
             // 最佳 PID 仍在运行，重置失败计数
             consecutive_failures = 0;
+            let now = get_timestamp();
+            let switched = current_best_pid != last_best_pid;
+            update_health(game_id, |health| {
+                health.consecutive_failures = 0;
+                health.last_liveness_check = Some(now);
+                if is_foreground {
+                    health.last_foreground_at = Some(now);
+                }
+                if switched {
+                    health.pid_switches += 1;
+                }
+            });
 
             // 如果 best_pid 变化了，记录日志
-            if current_best_pid != last_best_pid {
+            if switched {
                 debug!("检测到进程切换: {} -> {}", last_best_pid, current_best_pid);
                 last_best_pid = current_best_pid;
             }
 
-            // 前台判定：仅检查共享状态（性能优化的关键）
-            if is_foreground {
+            usage_sampler.sample(current_best_pid);
+
+            // 挂机判定：超过阈值没有键鼠输入时不累计游戏时间
+            let idle_now = is_foreground
+                && idle_timeout.is_some_and(|timeout| {
+                    get_input_idle_seconds().is_some_and(|idle| idle >= timeout)
+                });
+            if idle_now != is_idle {
+                is_idle = idle_now;
+                if is_idle {
+                    info!("检测到挂机，暂停累计游戏时间: ID={}", game_id);
+                } else {
+                    info!("挂机结束，恢复累计游戏时间: ID={}", game_id);
+                }
+            }
+
+            // 前台判定：仅检查共享状态（性能优化的关键）；用户暂停计时期间不累计
+            if is_idle {
+                idle_seconds += 1;
+            } else if is_foreground && !is_timing_paused(game_id) {
                 accumulated_seconds += 1;
 
                 // 发送时间更新
                 if accumulated_seconds > 0
                     && accumulated_seconds.is_multiple_of(TIME_UPDATE_INTERVAL_SECS)
                 {
+                    publish_session(ActiveSessionInfo {
+                        game_id,
+                        process_id: current_best_pid,
+                        start_time,
+                        total_seconds: accumulated_seconds,
+                    });
                     let minutes = accumulated_seconds / 60;
                     app_handle
                         .emit(
@@ -410,11 +677,47 @@ async fn run_game_monitor<R: Runtime>(
                         .map_err(|e| format!("无法发送 game-time-update 事件: {}", e))?;
                 }
             }
+
+            // 定期记录窗口位置：进程退出后窗口已不存在，会话结束时保存最后一次的记录
+            if remember_placement
+                && get_timestamp().saturating_sub(last_placement_capture)
+                    >= placement::CAPTURE_INTERVAL_SECS
+            {
+                last_placement_capture = get_timestamp();
+                let captured = placement::capture(&shared_candidate_pids.read(), window_placement);
+                if captured.is_some() {
+                    window_placement = captured;
+                    placement_captured = true;
+                }
+            }
+
+            // 定期持久化检查点，应用崩溃时下次启动据此补记会话
+            if get_timestamp().saturating_sub(last_checkpoint) >= CHECKPOINT_INTERVAL_SECS {
+                last_checkpoint = get_timestamp();
+                save_checkpoint(
+                    &app_handle,
+                    &ActiveSessionInfo {
+                        game_id,
+                        process_id: current_best_pid,
+                        start_time,
+                        total_seconds: accumulated_seconds,
+                    },
+                )
+                .await;
+            }
+
+            // 会话中按累计游玩时间定时备份存档
+            play_backup.tick(&app_handle, game_id, accumulated_seconds);
         }
     }
 
     // 清理会话注册
     unregister_session(game_id);
+    remove_session(game_id);
+    clear_checkpoint(&app_handle, game_id).await;
+    if placement_captured && let Some(placement) = window_placement {
+        placement::save(&app_handle, game_id, placement).await;
+    }
 
     finalize_session(
         &app_handle,
@@ -422,6 +725,9 @@ async fn run_game_monitor<R: Runtime>(
         last_best_pid,
         start_time,
         accumulated_seconds,
+        idle_seconds,
+        suspend_tracker.suspended_seconds,
+        usage_sampler.finish(),
     )
 }
 
@@ -433,15 +739,22 @@ async fn run_game_monitor<R: Runtime>(
 /// * `process_id` - 最终的进程 PID
 /// * `start_time` - 会话开始时间戳
 /// * `accumulated_seconds` - 累计的活动时间（秒）
+/// * `idle_seconds` - 游戏在前台但判定为挂机的时间（秒）
+/// * `suspended_seconds` - 会话期间系统睡眠的时间（秒），不计入游戏时间
+/// * `process_stats` - 会话期间主进程的内存与 CPU 占用，没有采样时为 None
 ///
 /// # 返回值
 /// 成功返回 `Ok(())`，失败返回包含错误信息的 `Err(String)`
+#[allow(clippy::too_many_arguments)]
 fn finalize_session<R: Runtime>(
     app_handle: &AppHandle<R>,
     game_id: u32,
     process_id: u32,
     start_time: u64,
     accumulated_seconds: u64,
+    idle_seconds: u64,
+    suspended_seconds: u64,
+    process_stats: Option<ProcessStats>,
 ) -> Result<(), String> {
     let end_time = get_timestamp();
     let total_minutes = accumulated_seconds / 60;
@@ -455,8 +768,8 @@ fn finalize_session<R: Runtime>(
     };
 
     info!(
-        "游戏会话结束: ID={}, 最终 PID={}, 总活动时间={}秒 (计为 {} 分钟)",
-        game_id, process_id, accumulated_seconds, final_minutes
+        "游戏会话结束: ID={}, 最终 PID={}, 总活动时间={}秒 (计为 {} 分钟), 挂机时间={}秒, 睡眠时间={}秒",
+        game_id, process_id, accumulated_seconds, final_minutes, idle_seconds, suspended_seconds
     );
 
     // 发送会话结束事件到前端
@@ -469,10 +782,48 @@ fn finalize_session<R: Runtime>(
                 "endTime": end_time,
                 "totalMinutes": final_minutes,
                 "totalSeconds": accumulated_seconds,
-                "processId": process_id
+                "idleSeconds": idle_seconds,
+                "suspendedSeconds": suspended_seconds,
+                "processId": process_id,
+                "processStats": process_stats
             }),
         )
-        .map_err(|e| format!("无法发送 game-session-ended 事件: {}", e))
+        .map_err(|e| format!("无法发送 game-session-ended 事件: {}", e))?;
+
+    restore_after_session(app_handle, game_id);
+    Ok(())
+}
+
+/// 读取挂机判定阈值（秒），未启用或读取失败时返回 None
+async fn load_idle_timeout<R: Runtime>(app_handle: &AppHandle<R>) -> Option<u64> {
+    let db = app_handle.try_state::<DbState>().map(|state| state.get())?;
+    match db.get_settings().await {
+        Ok(settings) => settings
+            .monitor_settings
+            .and_then(|settings| settings.idle_timeout_secs)
+            .filter(|timeout| *timeout > 0),
+        Err(e) => {
+            warn!("读取挂机判定设置失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 获取距离最后一次键鼠输入的秒数
+///
+/// 注意：手柄（XInput）输入不会更新系统的最后输入时间
+fn get_input_idle_seconds() -> Option<u64> {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // GetTickCount 约 49.7 天回绕一次，使用回绕减法
+        Some(u64::from(GetTickCount().wrapping_sub(info.dwTime) / 1000))
+    }
 }
 
 // ============================================================================
@@ -643,7 +994,7 @@ fn is_sub_path_ignore_case(path: &str, base_dir: &str) -> bool {
 ///
 /// # Returns
 /// 返回所有候选 PID 的列表，如果没有找到则返回空列表
-fn get_all_candidate_pids(executable_path: &str) -> Vec<u32> {
+pub fn get_all_candidate_pids(executable_path: &str) -> Vec<u32> {
     let manager_pid = std::process::id();
 
     let candidate_pids: Vec<u32> = get_processes_in_directory(executable_path)
@@ -806,7 +1157,7 @@ pub fn terminate_process(pid: u32) -> Result<(), String> {
 ///
 /// # Returns
 /// 如果成功，返回进程的可执行文件完整路径
-fn get_process_executable_path(pid: u32) -> Option<std::path::PathBuf> {
+pub(crate) fn get_process_executable_path(pid: u32) -> Option<std::path::PathBuf> {
     use windows::core::PWSTR;
 
     unsafe {
@@ -0,0 +1,348 @@
+//! Discord Rich Presence
+//!
+//! 有游戏会话正在监控时，通过 Discord 客户端的本地 IPC 展示游戏名称、封面和已游玩时间。
+//! 默认关闭，在设置的 `presence_settings` 中开启；成人内容默认不展示真实名称和封面，
+//! 也可以用 `custom_data.presence_title` 为单个游戏指定替代标题。
+//!
+//! Discord 限制约 15 秒更新一次状态，这里按同样的间隔轮询活跃会话，只在展示内容变化时更新。
+
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::games;
+use crate::entity::presence_settings::PresenceSettings;
+use crate::game::monitor::active_sessions;
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+use sea_orm::DatabaseConnection;
+use serde_json::{Value, json};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// 轮询活跃会话的间隔（Discord 的状态更新频率上限）
+const PRESENCE_UPDATE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// 隐藏名称时展示的标题
+const HIDDEN_TITLE: &str = "Visual Novel";
+
+/// Discord 对文本字段的长度限制
+const MAX_TEXT_LEN: usize = 128;
+
+/// 构建时注入的 Discord 应用 Client ID
+const BUILTIN_CLIENT_ID: Option<&str> = option_env!("DISCORD_CLIENT_ID");
+
+/// 当前的 IPC 连接，连接失败或 Discord 关闭后置空，下次更新时重连
+static CLIENT: Mutex<Option<ipc::IpcClient>> = Mutex::new(None);
+
+/// Discord 本地 IPC 协议（帧格式：操作码 u32 LE + 长度 u32 LE + JSON）
+mod ipc {
+    use serde_json::{Value, json};
+    use std::io::{self, Read, Write};
+
+    const OP_HANDSHAKE: u32 = 0;
+    const OP_FRAME: u32 = 1;
+    const OP_CLOSE: u32 = 2;
+
+    #[cfg(unix)]
+    type Stream = std::os::unix::net::UnixStream;
+    #[cfg(windows)]
+    type Stream = std::fs::File;
+
+    pub struct IpcClient {
+        stream: Stream,
+        pub client_id: String,
+        nonce: u64,
+    }
+
+    /// 可能存放 Discord IPC 套接字的目录（含 Flatpak / Snap 版）
+    #[cfg(unix)]
+    fn socket_dirs() -> Vec<std::path::PathBuf> {
+        let mut bases: Vec<std::path::PathBuf> = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(Into::into)
+            .collect();
+        bases.push("/tmp".into());
+
+        bases
+            .into_iter()
+            .flat_map(|base| {
+                [
+                    base.clone(),
+                    base.join("app/com.discordapp.Discord"),
+                    base.join("snap.discord"),
+                ]
+            })
+            .collect()
+    }
+
+    #[cfg(unix)]
+    fn open_stream() -> io::Result<Stream> {
+        for dir in socket_dirs() {
+            for index in 0..10 {
+                if let Ok(stream) = Stream::connect(dir.join(format!("discord-ipc-{}", index))) {
+                    stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+                    return Ok(stream);
+                }
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "未找到 Discord IPC 套接字",
+        ))
+    }
+
+    #[cfg(windows)]
+    fn open_stream() -> io::Result<Stream> {
+        for index in 0..10 {
+            let pipe = format!(r"\\.\pipe\discord-ipc-{}", index);
+            if let Ok(stream) = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(pipe)
+            {
+                return Ok(stream);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "未找到 Discord IPC 管道",
+        ))
+    }
+
+    impl IpcClient {
+        /// 连接本机的 Discord 客户端并完成握手
+        pub fn connect(client_id: &str) -> io::Result<Self> {
+            let mut client = Self {
+                stream: open_stream()?,
+                client_id: client_id.to_string(),
+                nonce: 0,
+            };
+            client.send(OP_HANDSHAKE, &json!({ "v": 1, "client_id": client_id }))?;
+            client.recv()?;
+            Ok(client)
+        }
+
+        /// 设置或清除（`None`）当前状态
+        pub fn set_activity(&mut self, activity: Option<Value>) -> io::Result<()> {
+            self.nonce += 1;
+            let payload = json!({
+                "cmd": "SET_ACTIVITY",
+                "args": { "pid": std::process::id(), "activity": activity },
+                "nonce": self.nonce.to_string(),
+            });
+            self.send(OP_FRAME, &payload)?;
+            let response = self.recv()?;
+            if response.get("evt").and_then(Value::as_str) == Some("ERROR") {
+                return Err(io::Error::other(response.to_string()));
+            }
+            Ok(())
+        }
+
+        fn send(&mut self, opcode: u32, payload: &Value) -> io::Result<()> {
+            let body = payload.to_string();
+            let mut frame = Vec::with_capacity(8 + body.len());
+            frame.extend_from_slice(&opcode.to_le_bytes());
+            frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            frame.extend_from_slice(body.as_bytes());
+            self.stream.write_all(&frame)?;
+            self.stream.flush()
+        }
+
+        fn recv(&mut self) -> io::Result<Value> {
+            let mut header = [0u8; 8];
+            self.stream.read_exact(&mut header)?;
+            let opcode = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+            let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+            let mut body = vec![0u8; length as usize];
+            self.stream.read_exact(&mut body)?;
+
+            let value: Value = serde_json::from_slice(&body)?;
+            if opcode == OP_CLOSE {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    value.to_string(),
+                ));
+            }
+            Ok(value)
+        }
+    }
+}
+
+/// 按字符截断到 Discord 的长度限制
+fn truncate_text(text: &str) -> String {
+    text.chars().take(MAX_TEXT_LEN).collect()
+}
+
+/// 游戏是否被任一数据源标记为成人内容
+fn is_nsfw(game: &games::Model) -> bool {
+    [
+        game.custom_data.as_ref().and_then(|d| d.nsfw),
+        game.bgm_data.as_ref().and_then(|d| d.nsfw),
+        game.vndb_data.as_ref().and_then(|d| d.nsfw),
+        game.ymgal_data.as_ref().and_then(|d| d.nsfw),
+        game.kun_data.as_ref().and_then(|d| d.nsfw),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|nsfw| nsfw)
+}
+
+/// 第一个可供 Discord 加载的网络封面
+fn cover_url(game: &games::Model) -> Option<String> {
+    [
+        game.custom_data.as_ref().and_then(|d| d.image.clone()),
+        game.bgm_data.as_ref().and_then(|d| d.image.clone()),
+        game.vndb_data.as_ref().and_then(|d| d.image.clone()),
+        game.ymgal_data.as_ref().and_then(|d| d.image.clone()),
+        game.kun_data.as_ref().and_then(|d| d.image.clone()),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|url| url.starts_with("https://") || url.starts_with("http://"))
+}
+
+/// 构建 SET_ACTIVITY 的 activity 内容
+fn build_activity(game: &games::Model, start_time: u64, settings: &PresenceSettings) -> Value {
+    let override_title = game
+        .custom_data
+        .as_ref()
+        .and_then(|d| d.presence_title.as_deref())
+        .map(str::trim)
+        .filter(|title| !title.is_empty());
+    let hidden = override_title.is_none() && settings.hide_nsfw.unwrap_or(true) && is_nsfw(game);
+
+    let title = match override_title {
+        Some(title) => title.to_string(),
+        None if hidden => HIDDEN_TITLE.to_string(),
+        None => GamesRepository::get_display_name(game, true)
+            .unwrap_or(HIDDEN_TITLE)
+            .to_string(),
+    };
+    // Discord 要求文本至少 2 个字符
+    let title = if title.chars().count() < 2 {
+        format!("{} ", title)
+    } else {
+        truncate_text(&title)
+    };
+
+    let mut activity = json!({
+        "details": title,
+        "timestamps": { "start": start_time },
+    });
+    if settings.show_cover.unwrap_or(true)
+        && override_title.is_none()
+        && !hidden
+        && let Some(url) = cover_url(game)
+    {
+        activity["assets"] = json!({ "large_image": url, "large_text": title });
+    }
+    activity
+}
+
+/// 读取设置，返回启用时使用的 Client ID；未启用或没有可用的 Client ID 时为 None
+async fn load_settings(db: &DatabaseConnection) -> Option<(PresenceSettings, String)> {
+    let settings = match db.get_settings().await {
+        Ok(settings) => settings.presence_settings.unwrap_or_default(),
+        Err(e) => {
+            warn!("读取 Discord 状态设置失败: {}", e);
+            return None;
+        }
+    };
+    if !settings.enabled.unwrap_or(false) {
+        return None;
+    }
+    let client_id = settings
+        .client_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .or(BUILTIN_CLIENT_ID)?
+        .to_string();
+    Some((settings, client_id))
+}
+
+/// 在阻塞线程中设置状态，必要时（重新）连接
+async fn push_activity(client_id: String, activity: Option<Value>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let mut guard = CLIENT.lock();
+        if guard
+            .as_ref()
+            .is_some_and(|client| client.client_id != client_id)
+        {
+            *guard = None;
+        }
+        if guard.is_none() {
+            if activity.is_none() {
+                return Ok(());
+            }
+            *guard = Some(ipc::IpcClient::connect(&client_id).map_err(|e| e.to_string())?);
+        }
+
+        let result = guard
+            .as_mut()
+            .map_or(Ok(()), |client| client.set_activity(activity));
+        if result.is_err() {
+            *guard = None;
+        }
+        result.map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 启动后台任务：有会话时展示最近开始的游戏，会话全部结束或关闭设置后清除状态
+pub fn spawn_presence_loop<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        // 当前展示的 (game_id, start_time)
+        let mut shown: Option<(u32, u64)> = None;
+
+        loop {
+            tokio::time::sleep(PRESENCE_UPDATE_INTERVAL).await;
+            let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+                continue;
+            };
+
+            let enabled = load_settings(&db).await;
+            let session = enabled
+                .as_ref()
+                .and_then(|_| active_sessions().into_iter().last());
+            let target = session
+                .as_ref()
+                .map(|session| (session.game_id, session.start_time));
+            if target == shown {
+                continue;
+            }
+
+            let result = match (enabled, session) {
+                (Some((settings, client_id)), Some(session)) => {
+                    match GamesRepository::find_by_id(&db, session.game_id as i32).await {
+                        Ok(Some(game)) => {
+                            let activity = build_activity(&game, session.start_time, &settings);
+                            push_activity(client_id, Some(activity)).await
+                        }
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(format!("获取游戏数据失败: {}", e)),
+                    }
+                }
+                (Some((_, client_id)), None) => push_activity(client_id, None).await,
+                (None, _) => {
+                    // 关闭设置后断开连接，Discord 会自动清除状态
+                    CLIENT.lock().take();
+                    Ok(())
+                }
+            };
+
+            match result {
+                Ok(()) => {
+                    if let Some((game_id, _)) = target {
+                        info!("已更新 Discord 状态 game_id={}", game_id);
+                    }
+                    shown = target;
+                }
+                // Discord 未运行是常态，只记录调试日志，下一轮重试
+                Err(e) => debug!("更新 Discord 状态失败: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,462 @@
+//! 存档路径自动检测
+//!
+//! 依据游戏目录结构、常见用户目录（AppData / Documents / Saved Games）与 Windows 注册表线索，
+//! 返回按置信度排序的候选存档目录，免去用户手动查找。
+
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::entity::games;
+use crate::utils::metrics::CommandTimer;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, State};
+use walkdir::WalkDir;
+
+/// 常见的存档目录名（不区分大小写）
+const SAVE_DIR_NAMES: &[&str] = &[
+    "save",
+    "saves",
+    "savedata",
+    "savedat",
+    "save_data",
+    "saved",
+    "userdata",
+    "セーブ",
+    "セーブデータ",
+    "存档",
+];
+
+/// 常见的存档文件扩展名
+const SAVE_FILE_EXTENSIONS: &[&str] = &["sav", "save", "dat", "ksd", "kdt", "bin", "rvdata2"];
+
+/// 过于通用、不能用来匹配目录名的可执行文件名
+const GENERIC_NAMES: &[&str] = &[
+    "game", "games", "start", "launcher", "setup", "config", "main",
+];
+
+/// 游戏目录内的最大扫描深度
+const GAME_DIR_SCAN_DEPTH: usize = 3;
+
+/// 用户目录下匹配到游戏后继续向下查找的深度
+const USER_DIR_SCAN_DEPTH: usize = 2;
+
+/// 候选来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveCandidateSource {
+    GameDir,
+    UserDir,
+    /// 注册表 `HKCU\Software` 下记录的目录（仅 Windows）
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Registry,
+}
+
+/// 候选存档目录
+#[derive(Debug, Clone, Serialize)]
+pub struct SaveCandidate {
+    pub path: String,
+    /// 0.0 - 1.0，越高越可能是存档目录
+    pub confidence: f32,
+    pub source: SaveCandidateSource,
+    pub reason: String,
+    /// 目录中（两层以内）看起来像存档的文件数量
+    pub save_file_count: usize,
+}
+
+/// 名称归一化：小写并移除空白与标点，用于模糊匹配目录名
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 目录名是否与任一关键词匹配（相等，或较长关键词互相包含）
+fn matches_keyword(dir_name: &str, keywords: &[String]) -> bool {
+    let dir_name = normalize(dir_name);
+    if dir_name.is_empty() {
+        return false;
+    }
+    keywords.iter().any(|keyword| {
+        dir_name == *keyword
+            || (keyword.chars().count() >= 4 && dir_name.contains(keyword.as_str()))
+            || (dir_name.chars().count() >= 4 && keyword.contains(dir_name.as_str()))
+    })
+}
+
+fn is_save_dir_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SAVE_DIR_NAMES.contains(&name.as_str())
+}
+
+/// 统计目录中两层以内的存档样式文件
+fn count_save_files(dir: &Path) -> usize {
+    WalkDir::new(dir)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| SAVE_FILE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .count()
+}
+
+/// 存档文件数量带来的置信度加成
+fn file_bonus(save_file_count: usize) -> f32 {
+    match save_file_count {
+        0 => 0.0,
+        1..=2 => 0.1,
+        _ => 0.2,
+    }
+}
+
+/// 收集用于匹配目录名的关键词：游戏名称、可执行文件名、游戏目录名
+fn collect_name_keywords(game: &games::Model, game_dir: Option<&Path>) -> Vec<String> {
+    let mut names: Vec<&str> = Vec::new();
+    names.extend(GamesRepository::get_display_name(game, true));
+    names.extend(GamesRepository::get_display_name(game, false));
+    names.extend(game.bgm_data.as_ref().and_then(|d| d.name.as_deref()));
+    names.extend(game.vndb_data.as_ref().and_then(|d| d.name.as_deref()));
+
+    let exe_stem = game
+        .localpath
+        .as_deref()
+        .and_then(|path| Path::new(path).file_stem())
+        .and_then(|stem| stem.to_str());
+    let dir_name = game_dir
+        .and_then(|dir| dir.file_name())
+        .and_then(|name| name.to_str());
+    names.extend(exe_stem);
+    names.extend(dir_name);
+
+    dedup_keywords(names)
+}
+
+/// 收集开发商关键词，用于匹配 `AppData/<开发商>/<游戏>` 这类目录结构
+fn collect_developer_keywords(game: &games::Model) -> Vec<String> {
+    let mut names: Vec<&str> = Vec::new();
+    names.extend(
+        game.custom_data
+            .as_ref()
+            .and_then(|d| d.developer.as_deref()),
+    );
+    names.extend(game.vndb_data.as_ref().and_then(|d| d.developer.as_deref()));
+    names.extend(game.bgm_data.as_ref().and_then(|d| d.developer.as_deref()));
+    dedup_keywords(names)
+}
+
+fn dedup_keywords(names: Vec<&str>) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for keyword in names.into_iter().map(normalize) {
+        if !keyword.is_empty()
+            && !GENERIC_NAMES.contains(&keyword.as_str())
+            && !keywords.contains(&keyword)
+        {
+            keywords.push(keyword);
+        }
+    }
+    keywords
+}
+
+/// 在游戏目录内查找存档目录
+fn scan_game_dir(game_dir: &Path, candidates: &mut Vec<SaveCandidate>) {
+    for entry in WalkDir::new(game_dir)
+        .min_depth(1)
+        .max_depth(GAME_DIR_SCAN_DEPTH)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_dir())
+    {
+        let name = entry.file_name().to_string_lossy();
+        let save_file_count = count_save_files(entry.path());
+        let (confidence, reason) = if is_save_dir_name(&name) {
+            (0.75, format!("游戏目录内的存档目录名: {}", name))
+        } else if save_file_count >= 3 {
+            (0.4, "游戏目录内包含多个存档样式文件".to_string())
+        } else {
+            continue;
+        };
+
+        candidates.push(SaveCandidate {
+            path: entry.path().to_string_lossy().to_string(),
+            confidence: confidence + file_bonus(save_file_count),
+            source: SaveCandidateSource::GameDir,
+            reason,
+            save_file_count,
+        });
+    }
+}
+
+/// 常见的用户存档根目录
+fn user_save_roots<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    let resolver = app.path();
+    let mut roots = Vec::new();
+    roots.extend(resolver.document_dir().ok());
+    roots.extend(resolver.document_dir().ok().map(|dir| dir.join("My Games")));
+    roots.extend(resolver.data_dir().ok());
+    roots.extend(resolver.local_data_dir().ok());
+    if let Ok(home) = resolver.home_dir() {
+        roots.push(home.join("Saved Games"));
+        roots.push(home.join("AppData").join("LocalLow"));
+    }
+    roots.sort();
+    roots.dedup();
+    roots.into_iter().filter(|root| root.is_dir()).collect()
+}
+
+/// 在用户目录中按游戏名 / 开发商名查找存档目录
+fn scan_user_dirs(
+    roots: &[PathBuf],
+    name_keywords: &[String],
+    developer_keywords: &[String],
+    candidates: &mut Vec<SaveCandidate>,
+) {
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if matches_keyword(&name, name_keywords) {
+                push_user_dir_candidate(
+                    &path,
+                    0.6,
+                    format!("用户目录中与游戏同名: {}", name),
+                    candidates,
+                );
+            } else if matches_keyword(&name, developer_keywords) {
+                // 开发商目录下再找与游戏同名的子目录
+                for sub in WalkDir::new(&path)
+                    .min_depth(1)
+                    .max_depth(USER_DIR_SCAN_DEPTH)
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .filter(|sub| sub.file_type().is_dir())
+                {
+                    let sub_name = sub.file_name().to_string_lossy();
+                    if matches_keyword(&sub_name, name_keywords) {
+                        push_user_dir_candidate(
+                            sub.path(),
+                            0.65,
+                            format!("开发商目录 {} 下与游戏同名: {}", name, sub_name),
+                            candidates,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn push_user_dir_candidate(
+    path: &Path,
+    base_confidence: f32,
+    reason: String,
+    candidates: &mut Vec<SaveCandidate>,
+) {
+    let save_file_count = count_save_files(path);
+    candidates.push(SaveCandidate {
+        path: path.to_string_lossy().to_string(),
+        confidence: base_confidence + file_bonus(save_file_count),
+        source: SaveCandidateSource::UserDir,
+        reason,
+        save_file_count,
+    });
+}
+
+/// 读取 `HKCU\Software\<关键词>` 下指向已存在目录的字符串值
+#[cfg(target_os = "windows")]
+fn scan_registry(keywords: &[&str], candidates: &mut Vec<SaveCandidate>) {
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{
+        HKEY, HKEY_CURRENT_USER, KEY_READ, REG_EXPAND_SZ, REG_SZ, REG_VALUE_TYPE, RegCloseKey,
+        RegEnumValueW, RegOpenKeyExW,
+    };
+    use windows::core::HSTRING;
+
+    for keyword in keywords {
+        let subkey = HSTRING::from(format!("Software\\{}", keyword));
+        let mut key = HKEY::default();
+        let status =
+            unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, &subkey, None, KEY_READ, &raw mut key) };
+        if status != ERROR_SUCCESS {
+            continue;
+        }
+
+        for index in 0.. {
+            let mut name = [0u16; 256];
+            let mut name_len = name.len() as u32;
+            let mut data = [0u16; 1024];
+            let mut data_len = (data.len() * std::mem::size_of::<u16>()) as u32;
+            let mut value_type = REG_VALUE_TYPE::default();
+            let status = unsafe {
+                RegEnumValueW(
+                    key,
+                    index,
+                    Some(windows::core::PWSTR(name.as_mut_ptr())),
+                    &raw mut name_len,
+                    None,
+                    Some(&raw mut value_type.0),
+                    Some(data.as_mut_ptr().cast()),
+                    Some(&raw mut data_len),
+                )
+            };
+            if status != ERROR_SUCCESS {
+                break;
+            }
+            if value_type != REG_SZ && value_type != REG_EXPAND_SZ {
+                continue;
+            }
+
+            let len = data.iter().position(|&c| c == 0).unwrap_or(data.len());
+            let value = String::from_utf16_lossy(&data[..len]);
+            let path = PathBuf::from(value.trim());
+            if path.is_absolute() && path.is_dir() {
+                let value_name = String::from_utf16_lossy(&name[..name_len as usize]);
+                let save_file_count = count_save_files(&path);
+                candidates.push(SaveCandidate {
+                    path: path.to_string_lossy().to_string(),
+                    confidence: 0.55 + file_bonus(save_file_count),
+                    source: SaveCandidateSource::Registry,
+                    reason: format!("注册表 HKCU\\Software\\{} 的值 {}", keyword, value_name),
+                    save_file_count,
+                });
+            }
+        }
+
+        unsafe {
+            let _ = RegCloseKey(key);
+        }
+    }
+}
+
+/// 同一路径只保留置信度最高的候选，并按置信度降序排列
+fn rank_candidates(candidates: Vec<SaveCandidate>) -> Vec<SaveCandidate> {
+    let mut best: HashMap<String, SaveCandidate> = HashMap::new();
+    for mut candidate in candidates {
+        candidate.confidence = candidate.confidence.min(1.0);
+        let key = candidate.path.to_lowercase();
+        match best.get(&key) {
+            Some(existing) if existing.confidence >= candidate.confidence => {}
+            _ => {
+                best.insert(key, candidate);
+            }
+        }
+    }
+
+    let mut ranked: Vec<SaveCandidate> = best.into_values().collect();
+    ranked.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| b.save_file_count.cmp(&a.save_file_count))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    ranked
+}
+
+/// 自动检测游戏的存档目录
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+///
+/// # Returns
+/// * `Result<Vec<SaveCandidate>, String>` - 按置信度降序排列的候选目录
+#[tauri::command]
+pub async fn detect_save_path(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    game_id: i32,
+) -> Result<Vec<SaveCandidate>, String> {
+    let _timer = CommandTimer::start("detect_save_path");
+    let db = db.get();
+    let game = GamesRepository::find_by_id(&db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏数据失败: {}", e))?
+        .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+
+    let game_dir = game.localpath.as_deref().map(Path::new).and_then(|path| {
+        if path.is_dir() {
+            Some(path.to_path_buf())
+        } else {
+            path.parent()
+                .filter(|dir| dir.is_dir())
+                .map(Path::to_path_buf)
+        }
+    });
+    let name_keywords = collect_name_keywords(&game, game_dir.as_deref());
+    let developer_keywords = collect_developer_keywords(&game);
+    let roots = user_save_roots(&app);
+
+    #[cfg(target_os = "windows")]
+    let registry_keywords: Vec<String> = [
+        game.custom_data.as_ref().and_then(|d| d.developer.clone()),
+        game.vndb_data.as_ref().and_then(|d| d.developer.clone()),
+        game.bgm_data.as_ref().and_then(|d| d.developer.clone()),
+        GamesRepository::get_display_name(&game, false).map(str::to_string),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let candidates = tokio::task::spawn_blocking(move || {
+        let mut candidates = Vec::new();
+        if let Some(game_dir) = &game_dir {
+            scan_game_dir(game_dir, &mut candidates);
+        }
+        scan_user_dirs(&roots, &name_keywords, &developer_keywords, &mut candidates);
+        #[cfg(target_os = "windows")]
+        {
+            let keywords: Vec<&str> = registry_keywords.iter().map(String::as_str).collect();
+            scan_registry(&keywords, &mut candidates);
+        }
+        rank_candidates(candidates)
+    })
+    .await
+    .map_err(|e| format!("检测存档路径失败: {}", e))?;
+
+    log::info!(
+        "存档路径检测完成 game_id={} candidates={}",
+        game_id,
+        candidates.len()
+    );
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dedup_keywords, matches_keyword, normalize};
+
+    #[test]
+    fn normalize_strips_punctuation_and_case() {
+        assert_eq!(
+            normalize("Summer Pockets REFLECTION BLUE"),
+            "summerpocketsreflectionblue"
+        );
+        assert_eq!(
+            normalize("サクラノ詩 -櫻の森の上を舞う-"),
+            "サクラノ詩櫻の森の上を舞う"
+        );
+    }
+
+    #[test]
+    fn keyword_matching_requires_meaningful_overlap() {
+        let keywords = dedup_keywords(vec!["Summer Pockets", "key", "Game"]);
+        assert!(matches_keyword("SummerPockets", &keywords));
+        assert!(matches_keyword("Summer Pockets RB", &keywords));
+        assert!(matches_keyword("KEY", &keywords));
+        assert!(!matches_keyword("keyboard", &keywords));
+        assert!(!matches_keyword("", &keywords));
+        assert!(!matches_keyword("My Games", &keywords));
+    }
+}
@@ -1,25 +1,68 @@
+use crate::database::db::DbState;
 use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::scan_config::ScanConfig;
+use crate::game::cross_ids::{CrossIdCandidate, search_by_title};
+use crate::utils::metrics::CommandTimer;
+use crate::utils::usage_stats::{UsageFeature, record_usage};
+use parking_lot::Mutex;
 use sea_orm::DatabaseConnection;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
 use std::time::Instant;
-use tauri::{State, command};
+use tauri::{AppHandle, Emitter, State, command};
 use walkdir::WalkDir;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ScanResult {
-    /// 文件夹名=游戏名
+/// 扫描识别出的游戏目录
+#[derive(Debug, Serialize)]
+pub struct ScannedGame {
+    /// 由文件夹名清洗得到的游戏名，用作元数据搜索关键字
     pub name: String,
-    /// 完整路径
+    /// 游戏目录完整路径
     pub path: String,
-    /// exe文件列表
+    /// exe文件列表（相对游戏目录），按可能性排序
     pub executables: Vec<String>,
+    /// 推测的主程序，即 executables 的第一项
+    pub main_executable: Option<String>,
+    /// 游戏目录的估算大小（字节）
+    pub size: u64,
+    /// 该目录已作为游戏导入，executables 中只有已记录的程序
+    pub already_imported: bool,
+    /// 按游戏名在 BGM / VNDB 搜索到的候选，未请求元数据匹配时为空
+    pub candidates: Vec<CrossIdCandidate>,
+}
+
+/// 可执行文件已不存在的游戏及建议的新路径
+#[derive(Debug, Serialize)]
+pub struct MissingGamePath {
+    pub game_id: i32,
+    pub localpath: String,
+    /// 最可能的新路径，可作为 localpath 通过 `update_games_batch` 应用
+    pub suggested_path: Option<String>,
+    /// 所有候选路径，按可能性排序
+    pub candidates: Vec<String>,
+}
+
+/// `validate_game_paths` 的结果
+#[derive(Debug, Serialize)]
+pub struct GamePathReport {
+    /// 检查的游戏数
+    pub checked: usize,
+    /// 为寻找新路径而扫描的游戏库目录
+    pub library_roots: Vec<String>,
+    pub missing: Vec<MissingGamePath>,
 }
 
 const VALID_EXE_EXTENSIONS: &[&str] = &["exe", "bat", "cmd"];
 const MIN_SCAN_MAX_DEPTH: usize = 2;
 const MAX_SCAN_MAX_DEPTH: usize = 5;
+/// 调用方和扫描规则都未指定深度时使用
+const DEFAULT_SCAN_MAX_DEPTH: usize = 3;
+/// 寻找新路径时扫描游戏库目录的深度（游戏目录改名或多套了一层文件夹时仍能找到）
+const RELINK_SCAN_MAX_DEPTH: usize = 3;
 
 /// 扫描时跳过的目录名（不区分大小写）
 const EXCLUDED_DIRS: &[&str] = &[
@@ -102,52 +145,190 @@ fn trim_dirname_to_search_name(dir_name: &str) -> String {
     }
 }
 
-fn is_excluded_dir(name: &str) -> bool {
-    let lower = name.to_lowercase();
-    EXCLUDED_DIRS.iter().any(|&d| lower == d)
+/// 不区分大小写的通配符匹配，`*` 匹配任意个字符，`?` 匹配单个字符
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // 最近一个 `*` 的位置及其当时对应的文本位置，失配时回溯到这里
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&ch| ch == '*')
 }
 
-fn is_excluded_exe(path: &Path) -> bool {
-    path.file_stem().is_some_and(|stem| {
-        let lower = stem.to_string_lossy().to_lowercase();
-        EXCLUDED_EXE_PATTERNS.iter().any(|&p| lower.contains(p))
-    })
+/// 合并内置规则与用户 [`ScanConfig`] 后实际使用的扫描规则
+#[derive(Debug, Clone)]
+struct ScanRules {
+    /// 视为启动程序的扩展名（小写，不含点）
+    extensions: Vec<String>,
+    ignore_patterns: Vec<String>,
+    max_depth: usize,
+    follow_symlinks: bool,
 }
 
+impl ScanRules {
+    /// `max_depth` 为调用时传入的深度，优先于扫描规则中的默认深度
+    fn resolve(config: &ScanConfig, max_depth: Option<usize>) -> Self {
+        let mut extensions: Vec<String> = VALID_EXE_EXTENSIONS
+            .iter()
+            .map(|ext| ext.to_string())
+            .collect();
+        for ext in config.extra_extensions.iter().flatten() {
+            let ext = ext.trim().trim_start_matches('.').to_lowercase();
+            if !ext.is_empty() && !extensions.contains(&ext) {
+                extensions.push(ext);
+            }
+        }
+
+        Self {
+            extensions,
+            ignore_patterns: config
+                .ignore_patterns
+                .iter()
+                .flatten()
+                .map(|pattern| pattern.trim().to_string())
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+            max_depth: max_depth
+                .or(config.max_depth.map(|depth| depth as usize))
+                .unwrap_or(DEFAULT_SCAN_MAX_DEPTH)
+                .clamp(MIN_SCAN_MAX_DEPTH, MAX_SCAN_MAX_DEPTH),
+            follow_symlinks: config.follow_symlinks.unwrap_or(false),
+        }
+    }
+
+    fn is_ignored(&self, name: &str) -> bool {
+        self.ignore_patterns
+            .iter()
+            .any(|pattern| wildcard_match(pattern, name))
+    }
+
+    fn is_excluded_dir(&self, name: &str) -> bool {
+        let lower = name.to_lowercase();
+        EXCLUDED_DIRS.iter().any(|&d| lower == d) || self.is_ignored(name)
+    }
+
+    fn is_excluded_exe(&self, path: &Path) -> bool {
+        path.file_stem().is_some_and(|stem| {
+            let lower = stem.to_string_lossy().to_lowercase();
+            EXCLUDED_EXE_PATTERNS.iter().any(|&p| lower.contains(p))
+        }) || path
+            .file_name()
+            .is_some_and(|name| self.is_ignored(&name.to_string_lossy()))
+    }
+
+    fn is_executable(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|ext| {
+            self.extensions
+                .iter()
+                .any(|valid| ext.eq_ignore_ascii_case(valid.as_str()))
+        }) && !self.is_excluded_exe(path)
+    }
+}
+
+/// 统计目录下所有文件的大小，无法读取的文件忽略
+fn dir_size(dir: &Path, follow_symlinks: bool) -> u64 {
+    WalkDir::new(dir)
+        .follow_links(follow_symlinks)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// 已导入游戏的目录 → 记录的可执行文件
+fn existing_game_dirs(localpaths: impl IntoIterator<Item = String>) -> HashMap<PathBuf, PathBuf> {
+    localpaths
+        .into_iter()
+        .filter_map(|localpath| {
+            let localpath = PathBuf::from(localpath);
+            Some((localpath.parent()?.to_path_buf(), localpath))
+        })
+        .collect()
+}
+
+/// 读取用户保存的扫描规则，读取失败时使用内置默认值
+async fn load_scan_config(db: &DatabaseConnection) -> ScanConfig {
+    match db.get_settings().await {
+        Ok(settings) => settings.scan_config.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("读取扫描规则失败，使用默认规则: {}", e);
+            ScanConfig::default()
+        }
+    }
+}
+
+/// 扫描目录下的游戏文件夹
+///
+/// # Arguments
+/// * `path` - 游戏库目录
+/// * `max_depth` - 扫描深度，未传入时使用扫描规则中的默认深度
+/// * `config` - 本次扫描使用的规则，未传入时使用用户设置中保存的规则
+/// * `match_metadata` - 是否按游戏名在 BGM / VNDB 搜索候选（每个未导入的游戏各请求一次），默认否
+///
+/// # Returns
+/// * `Result<Vec<ScannedGame>, String>` - 识别出的游戏目录，已导入的目录也会返回并标记
 #[command]
 pub async fn scan_directory_for_games(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     path: String,
-    max_depth: usize,
-) -> Result<Vec<ScanResult>, String> {
+    max_depth: Option<usize>,
+    config: Option<ScanConfig>,
+    match_metadata: Option<bool>,
+) -> Result<Vec<ScannedGame>, String> {
+    let _timer = CommandTimer::start("scan_directory_for_games");
+    let db = db.get();
     // 先做路径预检查（一次 syscall，可在 async 上下文进行）
     if !Path::new(&path).is_dir() {
         return Err(format!("目录不存在或不是文件夹: {}", path));
     }
 
     // 异步查询 DB，获取已导入目录集合
-    let existing_dirs: HashSet<PathBuf> = GamesRepository::get_all_localpaths(&db)
-        .await
-        .map_err(|e| format!("查询已有路径失败: {}", e))?
-        .into_iter()
-        .filter_map(|lp| PathBuf::from(lp).parent().map(Path::to_path_buf))
-        .collect();
+    let existing_dirs = existing_game_dirs(
+        GamesRepository::get_all_localpaths(&db)
+            .await
+            .map_err(|e| format!("查询已有路径失败: {}", e))?,
+    );
 
-    let max_depth = max_depth.clamp(MIN_SCAN_MAX_DEPTH, MAX_SCAN_MAX_DEPTH);
+    let config = match config {
+        Some(config) => config,
+        None => load_scan_config(&db).await,
+    };
+    let rules = ScanRules::resolve(&config, max_depth);
+    let max_depth = rules.max_depth;
+    record_usage(&db, UsageFeature::LibraryScan).await;
     let existing_dirs_count = existing_dirs.len();
     let started_at = Instant::now();
     let path_for_log = path.clone();
     log::debug!(
-        "开始扫描游戏目录 path={} max_depth={} existing_dirs={}",
+        "开始扫描游戏目录 path={} max_depth={} existing_dirs={} rules={:?}",
         path_for_log,
         max_depth,
-        existing_dirs_count
+        existing_dirs_count,
+        rules
     );
 
     // WalkDir 大量文件系统 I/O 属于阻塞操作，
     // 放入 Tokio 革层阻塞线程池，避免占用异步运行时线程。
-    let results =
-        tokio::task::spawn_blocking(move || scan_games_blocking(path, existing_dirs, max_depth))
+    let mut results =
+        tokio::task::spawn_blocking(move || scan_games_blocking(path, existing_dirs, &rules))
             .await
             .map_err(|e| {
                 log::error!(
@@ -159,6 +340,28 @@ pub async fn scan_directory_for_games(
                 format!("扫描任务异常: {}", e)
             })??;
 
+    if match_metadata.unwrap_or(false) {
+        let settings = db.get_settings().await?;
+        let bgm_token = settings
+            .bgm_auth
+            .as_ref()
+            .map(|auth| auth.access_token.as_str());
+        let vndb_token = settings.vndb_token.as_deref().filter(|t| !t.is_empty());
+        let client = crate::utils::http::get_client();
+        for game in results.iter_mut().filter(|game| !game.already_imported) {
+            let (candidates, errors) =
+                search_by_title(&client, &game.name, bgm_token, vndb_token).await;
+            if !errors.is_empty() {
+                log::warn!(
+                    "搜索游戏元数据失败 name={}: {}",
+                    game.name,
+                    errors.join("; ")
+                );
+            }
+            game.candidates = candidates;
+        }
+    }
+
     log::info!(
         "游戏目录扫描完成 max_depth={} result_count={} elapsed_ms={}",
         max_depth,
@@ -169,30 +372,291 @@ pub async fn scan_directory_for_games(
     Ok(results)
 }
 
+/// 每扫描完一个一级子目录发出的进度事件
+pub const LIBRARY_SCAN_PROGRESS_EVENT: &str = "library-scan-progress";
+/// 识别出游戏时发出的事件，每个游戏一次
+pub const LIBRARY_SCAN_FOUND_EVENT: &str = "library-scan-found";
+/// 扫描结束（完成、取消或出错）时发出的事件
+pub const LIBRARY_SCAN_FINISHED_EVENT: &str = "library-scan-finished";
+
+/// 进行中的扫描会话 → 取消标记
+static LIBRARY_SCANS: LazyLock<Mutex<HashMap<u64, Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_SCAN_ID: AtomicU64 = AtomicU64::new(1);
+
+/// `library-scan-progress` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryScanProgress {
+    pub scan_id: u64,
+    #[serde(flatten)]
+    pub step: ScanStep,
+    /// 目前识别出的游戏数
+    pub found: usize,
+}
+
+/// `library-scan-found` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryScanFound<'a> {
+    pub scan_id: u64,
+    pub game: &'a ScannedGame,
+}
+
+/// `library-scan-finished` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryScanFinished {
+    pub scan_id: u64,
+    pub found: usize,
+    pub cancelled: bool,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// 在后台扫描游戏库目录，逐个发送识别出的游戏，立即返回扫描会话 ID
+///
+/// 已导入的目录同样会发送并带有 `already_imported` 标记。扫描结果通过
+/// `library-scan-progress` / `library-scan-found` / `library-scan-finished` 事件推送，
+/// 可用 [`cancel_library_scan`] 中途取消。
+///
+/// # Arguments
+/// * `path` - 游戏库目录
+/// * `max_depth` - 扫描深度，未传入时使用扫描规则中的默认深度
+/// * `config` - 本次扫描使用的规则，未传入时使用用户设置中保存的规则
+///
+/// # Returns
+/// * `Result<u64, String>` - 扫描会话 ID
+#[command]
+pub async fn start_library_scan(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    path: String,
+    max_depth: Option<usize>,
+    config: Option<ScanConfig>,
+) -> Result<u64, String> {
+    let _timer = CommandTimer::start("start_library_scan");
+    spawn_library_scan(app, &db.get(), path, max_depth, config).await
+}
+
+/// 开始后台扫描，供 [`start_library_scan`] 与首次启动向导使用
+pub async fn spawn_library_scan(
+    app: AppHandle,
+    db: &DatabaseConnection,
+    path: String,
+    max_depth: Option<usize>,
+    config: Option<ScanConfig>,
+) -> Result<u64, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("目录不存在或不是文件夹: {}", path));
+    }
+    let existing_dirs = existing_game_dirs(
+        GamesRepository::get_all_localpaths(db)
+            .await
+            .map_err(|e| format!("查询已有路径失败: {}", e))?,
+    );
+    let config = match config {
+        Some(config) => config,
+        None => load_scan_config(db).await,
+    };
+    let rules = ScanRules::resolve(&config, max_depth);
+    record_usage(db, UsageFeature::LibraryScan).await;
+
+    let scan_id = NEXT_SCAN_ID.fetch_add(1, Ordering::Relaxed);
+    let cancel = Arc::new(AtomicBool::new(false));
+    LIBRARY_SCANS.lock().insert(scan_id, cancel.clone());
+    log::info!(
+        "开始后台扫描游戏目录 scan_id={} path={} rules={:?}",
+        scan_id,
+        path,
+        rules
+    );
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let started_at = Instant::now();
+        let mut found = 0;
+        let result = scan_games_streaming(
+            Path::new(&path),
+            &existing_dirs,
+            &rules,
+            &cancel,
+            |step, games| {
+                for game in &games {
+                    if let Err(e) =
+                        app.emit(LIBRARY_SCAN_FOUND_EVENT, LibraryScanFound { scan_id, game })
+                    {
+                        log::warn!("无法发送 {} 事件: {}", LIBRARY_SCAN_FOUND_EVENT, e);
+                    }
+                }
+                found += games.len();
+                let progress = LibraryScanProgress {
+                    scan_id,
+                    step,
+                    found,
+                };
+                if let Err(e) = app.emit(LIBRARY_SCAN_PROGRESS_EVENT, &progress) {
+                    log::warn!("无法发送 {} 事件: {}", LIBRARY_SCAN_PROGRESS_EVENT, e);
+                }
+            },
+        );
+        LIBRARY_SCANS.lock().remove(&scan_id);
+
+        let finished = LibraryScanFinished {
+            scan_id,
+            found,
+            cancelled: matches!(result, Ok(false)),
+            error: result.err(),
+            elapsed_ms: started_at.elapsed().as_millis() as u64,
+        };
+        log::info!("后台扫描游戏目录结束 {:?}", finished);
+        if let Err(e) = app.emit(LIBRARY_SCAN_FINISHED_EVENT, &finished) {
+            log::warn!("无法发送 {} 事件: {}", LIBRARY_SCAN_FINISHED_EVENT, e);
+        }
+    });
+
+    Ok(scan_id)
+}
+
+/// 取消后台扫描，已发送的结果不受影响
+///
+/// # Arguments
+/// * `scan_id` - `start_library_scan` 返回的扫描会话 ID
+///
+/// # Returns
+/// * `Result<bool, String>` - 扫描是否仍在进行（已结束的扫描返回 false）
+#[command]
+pub async fn cancel_library_scan(scan_id: u64) -> Result<bool, String> {
+    let _timer = CommandTimer::start("cancel_library_scan");
+    let Some(cancel) = LIBRARY_SCANS.lock().get(&scan_id).cloned() else {
+        return Ok(false);
+    };
+    cancel.store(true, Ordering::Relaxed);
+    log::info!("已请求取消后台扫描 scan_id={}", scan_id);
+    Ok(true)
+}
+
 /// 包含所有阻塞 I/O 和 CPU 密集计算的同步扫描逻辑
 ///
 /// 由 [`scan_directory_for_games`] 通过 `tokio::task::spawn_blocking` 调用，
 /// 运行在顶层阻塞线程池中而非异步运行时。
 fn scan_games_blocking(
     path: String,
-    existing_dirs: HashSet<PathBuf>,
-    max_depth: usize,
-) -> Result<Vec<ScanResult>, String> {
-    let dir_path = PathBuf::from(&path);
+    existing_dirs: HashMap<PathBuf, PathBuf>,
+    rules: &ScanRules,
+) -> Result<Vec<ScannedGame>, String> {
+    let mut results = Vec::new();
+    scan_games_streaming(
+        Path::new(&path),
+        &existing_dirs,
+        rules,
+        &AtomicBool::new(false),
+        |_, found| results.extend(found),
+    )?;
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(results)
+}
+
+/// 扫描进度：扫描根目录下的每个一级子目录算一步
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanStep {
+    /// 已扫描的一级子目录数
+    pub scanned: usize,
+    /// 一级子目录总数
+    pub total: usize,
+    /// 刚扫描完的目录
+    pub current_dir: String,
+}
+
+/// 逐个扫描根目录下的一级子目录，每扫描完一个就回调一次，回调中带上该子目录中识别出的游戏
+///
+/// 忽略直接位于扫描根目录下的文件（它们不属于任何子游戏文件夹）。`cancel` 置位后在下一个条目处停止，
+/// 返回 `Ok(false)`；已回调的结果不受影响。
+fn scan_games_streaming(
+    root: &Path,
+    existing_dirs: &HashMap<PathBuf, PathBuf>,
+    rules: &ScanRules,
+    cancel: &AtomicBool,
+    mut on_step: impl FnMut(ScanStep, Vec<ScannedGame>),
+) -> Result<bool, String> {
+    let children: Vec<PathBuf> = WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(1)
+        .follow_links(rules.follow_symlinks)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_dir())
+        .map(|entry| entry.into_path())
+        .collect();
+
+    let total = children.len();
+    for (index, child) in children.into_iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
 
+        let found = if let Some(localpath) = existing_dirs.get(&child) {
+            imported_game(&child, localpath, rules)
+                .into_iter()
+                .collect()
+        } else if rules.is_excluded_dir(&child.file_name().unwrap_or_default().to_string_lossy()) {
+            Vec::new()
+        } else {
+            scan_subtree(&child, existing_dirs, rules, cancel)
+        };
+
+        on_step(
+            ScanStep {
+                scanned: index + 1,
+                total,
+                current_dir: child.to_string_lossy().to_string(),
+            },
+            found,
+        );
+    }
+    Ok(!cancel.load(Ordering::Relaxed))
+}
+
+/// 已导入游戏目录的扫描结果，只列出已记录的程序
+fn imported_game(game_dir: &Path, localpath: &Path, rules: &ScanRules) -> Option<ScannedGame> {
+    let raw_name = game_dir.file_name()?.to_string_lossy().to_string();
+    let executable = localpath
+        .strip_prefix(game_dir)
+        .ok()
+        .map(|rel| rel.to_string_lossy().to_string());
+    Some(ScannedGame {
+        name: trim_dirname_to_search_name(&raw_name),
+        path: game_dir.to_string_lossy().to_string(),
+        executables: executable.iter().cloned().collect(),
+        main_executable: executable,
+        size: dir_size(game_dir, rules.follow_symlinks),
+        already_imported: true,
+        candidates: Vec::new(),
+    })
+}
+
+/// 扫描一个一级子目录（含其自身的直属文件），深度为扫描深度减一
+fn scan_subtree(
+    subtree_root: &Path,
+    existing_dirs: &HashMap<PathBuf, PathBuf>,
+    rules: &ScanRules,
+    cancel: &AtomicBool,
+) -> Vec<ScannedGame> {
     // Phase 1: DFS 遍历，收集所有有效 exe，按其所在目录分组
     // 使用手动迭代器控制代替 filter_entry，以便调用 skip_current_dir() 实现真正的短路：
-    //   - 遇到排除目录 / 已导入目录 → skip_current_dir，剪掉整棵子树
-    //   - 当某目录已确认含有直属 exe 时，之后遇到其子目录立即 skip_current_dir，
-    //   - 忽略直接位于扫描根目录下的文件（它们不属于任何子游戏文件夹）
+    //   - 遇到排除目录 / 已导入目录 → skip_current_dir，剪掉整棵子树（已导入目录单独记录）
+    //   - 当某目录已确认含有直属 exe 时，之后遇到其子目录立即 skip_current_dir
     let mut exe_by_dir: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
     // 已发现直属 exe 的目录集合，用于对其子目录执行 skip_current_dir
     let mut dirs_with_exe: HashSet<PathBuf> = HashSet::new();
+    // 扫描中遇到的已导入游戏目录
+    let mut imported_dirs: Vec<PathBuf> = Vec::new();
 
-    let mut walker = WalkDir::new(&dir_path)
+    let mut walker = WalkDir::new(subtree_root)
         .min_depth(1)
-        .max_depth(max_depth)
-        .follow_links(false)
+        .max_depth(rules.max_depth - 1)
+        .follow_links(rules.follow_symlinks)
         .sort_by(|a, b| {
             let a_is_dir = a.file_type().is_dir();
             let b_is_dir = b.file_type().is_dir();
@@ -202,6 +666,9 @@ fn scan_games_blocking(
         .into_iter();
 
     while let Some(entry) = walker.next() {
+        if cancel.load(Ordering::Relaxed) {
+            return Vec::new();
+        }
         let entry = match entry {
             Ok(e) => e,
             Err(_) => continue,
@@ -212,13 +679,16 @@ fn scan_games_blocking(
         if entry.file_type().is_dir() {
             // walkdir 在 yield 目录时已将其 ReadDir 压栈，skip_current_dir() 将其弹出，
             // 从而跳过该目录的所有内容，但不影响同级其他条目。
-            let should_skip = is_excluded_dir(&entry.file_name().to_string_lossy())
-                || existing_dirs.contains(entry_path)
-                // 父目录已有直属 exe → 该子目录无需遍历（祖先优先短路）
-                || entry_path
-                    .parent()
-                    .is_some_and(|p| dirs_with_exe.contains(p));
-            if should_skip {
+            // 父目录已有直属 exe → 该子目录无需遍历（祖先优先短路）
+            if entry_path
+                .parent()
+                .is_some_and(|p| dirs_with_exe.contains(p))
+            {
+                walker.skip_current_dir();
+            } else if existing_dirs.contains_key(entry_path) {
+                imported_dirs.push(entry_path.to_path_buf());
+                walker.skip_current_dir();
+            } else if rules.is_excluded_dir(&entry.file_name().to_string_lossy()) {
                 walker.skip_current_dir();
             }
             continue; // 目录条目本身无需记录
@@ -228,16 +698,8 @@ fn scan_games_blocking(
             let Some(parent) = entry_path.parent() else {
                 continue;
             };
-            if parent == dir_path.as_path() {
-                continue; // 忽略根目录直属文件
-            }
             // 收集有效可执行文件，并标记该目录已有 exe
-            if let Some(ext) = entry_path.extension()
-                && VALID_EXE_EXTENSIONS
-                    .iter()
-                    .any(|&e| ext.eq_ignore_ascii_case(e))
-                && !is_excluded_exe(entry_path)
-            {
+            if rules.is_executable(entry_path) {
                 dirs_with_exe.insert(parent.to_path_buf());
                 exe_by_dir
                     .entry(parent.to_path_buf())
@@ -267,7 +729,7 @@ fn scan_games_blocking(
     }
 
     // Phase 3: 构建结果。层级已由用户控制，这里只要求目录含有效启动程序。
-    let mut results: Vec<ScanResult> = selected
+    let mut results: Vec<ScannedGame> = selected
         .into_iter()
         .filter_map(|game_dir| {
             let exes = exe_by_dir.get(&game_dir)?;
@@ -307,21 +769,167 @@ fn scan_games_blocking(
                 }
             });
 
-            Some(ScanResult {
+            Some(ScannedGame {
                 name,
                 path: game_dir.to_string_lossy().to_string(),
+                main_executable: executables.first().cloned(),
                 executables,
+                size: dir_size(&game_dir, rules.follow_symlinks),
+                already_imported: false,
+                candidates: Vec::new(),
             })
         })
         .collect();
 
-    results.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(results)
+    results.extend(
+        imported_dirs
+            .iter()
+            .filter_map(|game_dir| imported_game(game_dir, existing_dirs.get(game_dir)?, rules)),
+    );
+    results
+}
+
+/// 在扫描结果中寻找与失效路径同名的可执行文件，按与原游戏目录名的相似程度排序
+fn relink_candidates(localpath: &Path, scanned: &[ScannedGame]) -> Vec<String> {
+    let Some(exe_name) = localpath.file_name() else {
+        return Vec::new();
+    };
+    let exe_name = exe_name.to_string_lossy().to_lowercase();
+    let old_dir_name = localpath
+        .parent()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let old_search_name = trim_dirname_to_search_name(&old_dir_name);
+
+    let mut candidates: Vec<(u8, String)> = scanned
+        .iter()
+        .filter(|result| !result.already_imported)
+        .flat_map(|result| {
+            let dir_name = Path::new(&result.path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let score = if dir_name == old_dir_name {
+                2
+            } else if trim_dirname_to_search_name(&dir_name) == old_search_name {
+                1
+            } else {
+                0
+            };
+            result
+                .executables
+                .iter()
+                .filter(|exe| {
+                    Path::new(exe)
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().to_lowercase() == exe_name)
+                })
+                .map(move |exe| {
+                    let path = Path::new(&result.path).join(exe);
+                    (score, path.to_string_lossy().to_string())
+                })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.len().cmp(&b.1.len())));
+    candidates.into_iter().map(|(_, path)| path).collect()
+}
+
+/// 检查所有游戏的可执行文件是否存在；对已失效的路径扫描同级游戏库目录，给出建议的新路径
+///
+/// 游戏库目录取所有游戏所在目录的上一级（如 `D:/Games/某游戏/game.exe` 的 `D:/Games`），
+/// 仍有效的游戏目录在扫描时跳过。只返回建议，不修改数据库。
+///
+/// # Returns
+/// * `Result<GamePathReport, String>` - 失效的路径与候选新路径
+#[command]
+pub async fn validate_game_paths(db: State<'_, DbState>) -> Result<GamePathReport, String> {
+    let _timer = CommandTimer::start("validate_game_paths");
+    let db = db.get();
+    let localpaths = GamesRepository::get_localpaths_by_id(&db)
+        .await
+        .map_err(|e| format!("查询已有路径失败: {}", e))?;
+    let rules = ScanRules::resolve(&load_scan_config(&db).await, Some(RELINK_SCAN_MAX_DEPTH));
+
+    tokio::task::spawn_blocking(move || {
+        let checked = localpaths.len();
+        let (existing, missing): (Vec<_>, Vec<_>) = localpaths
+            .into_iter()
+            .partition(|(_, localpath)| Path::new(localpath).exists());
+        let mut report = GamePathReport {
+            checked,
+            library_roots: Vec::new(),
+            missing: Vec::new(),
+        };
+        if missing.is_empty() {
+            return Ok(report);
+        }
+
+        let existing_dirs =
+            existing_game_dirs(existing.iter().map(|(_, localpath)| localpath.clone()));
+        let mut library_roots: Vec<PathBuf> = existing
+            .iter()
+            .chain(missing.iter())
+            .filter_map(|(_, localpath)| Path::new(localpath).parent()?.parent())
+            .filter(|root| root.is_dir())
+            .map(Path::to_path_buf)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        library_roots.sort();
+
+        let mut scanned = Vec::new();
+        for root in &library_roots {
+            match scan_games_blocking(
+                root.to_string_lossy().to_string(),
+                existing_dirs.clone(),
+                &rules,
+            ) {
+                Ok(results) => scanned.extend(results),
+                Err(e) => log::warn!("扫描游戏库目录失败 {}: {}", root.display(), e),
+            }
+        }
+
+        report.library_roots = library_roots
+            .iter()
+            .map(|root| root.to_string_lossy().to_string())
+            .collect();
+        report.missing = missing
+            .into_iter()
+            .map(|(game_id, localpath)| {
+                let candidates = relink_candidates(Path::new(&localpath), &scanned);
+                MissingGamePath {
+                    game_id,
+                    localpath,
+                    suggested_path: candidates.first().cloned(),
+                    candidates,
+                }
+            })
+            .collect();
+
+        log::info!(
+            "游戏路径检查完成 checked={} missing={} library_roots={}",
+            report.checked,
+            report.missing.len(),
+            report.library_roots.len()
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("检查游戏路径失败: {}", e))?
 }
 
 #[cfg(test)]
 mod tests {
-    use super::trim_dirname_to_search_name;
+    use super::{
+        ScanConfig, ScanRules, ScannedGame, relink_candidates, scan_games_streaming,
+        trim_dirname_to_search_name, wildcard_match,
+    };
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
 
     #[test]
     fn trim_dirname_removes_common_tags() {
@@ -339,4 +947,137 @@ mod tests {
     fn trim_dirname_falls_back_when_everything_is_removed() {
         assert_eq!(trim_dirname_to_search_name("[社团名]"), "[社团名]");
     }
+
+    #[test]
+    fn wildcard_match_is_case_insensitive() {
+        assert!(wildcard_match("*_backup", "Game_BACKUP"));
+        assert!(wildcard_match("config*.exe", "Config_Tool.exe"));
+        assert!(wildcard_match("save??", "save01"));
+        assert!(!wildcard_match("save??", "save1"));
+        assert!(!wildcard_match("*.exe", "game.exe.bak"));
+        assert!(wildcard_match("*a*b*", "xxaxxbxx"));
+    }
+
+    #[test]
+    fn scan_rules_merge_config_with_builtin_defaults() {
+        let config = ScanConfig {
+            extra_extensions: Some(vec![".SH".to_string(), "exe".to_string(), " ".to_string()]),
+            ignore_patterns: Some(vec!["*_old".to_string(), "launcher.exe".to_string()]),
+            max_depth: Some(9),
+            follow_symlinks: Some(true),
+        };
+        let rules = ScanRules::resolve(&config, None);
+        assert_eq!(rules.extensions, vec!["exe", "bat", "cmd", "sh"]);
+        assert_eq!(rules.max_depth, 5);
+        assert!(rules.follow_symlinks);
+        assert!(rules.is_executable(Path::new("/games/a/start.sh")));
+        assert!(!rules.is_executable(Path::new("/games/a/Launcher.exe")));
+        assert!(!rules.is_executable(Path::new("/games/a/uninstall.exe")));
+        assert!(rules.is_excluded_dir("Game_OLD"));
+        assert!(rules.is_excluded_dir("Redist"));
+
+        assert_eq!(ScanRules::resolve(&config, Some(2)).max_depth, 2);
+        let defaults = ScanRules::resolve(&ScanConfig::default(), None);
+        assert_eq!(defaults.max_depth, 3);
+        assert!(!defaults.follow_symlinks);
+        assert!(!defaults.is_executable(Path::new("/games/a/start.sh")));
+    }
+
+    fn scanned_game(path: &str, executables: &[&str], already_imported: bool) -> ScannedGame {
+        let executables: Vec<String> = executables.iter().map(|exe| exe.to_string()).collect();
+        ScannedGame {
+            name: trim_dirname_to_search_name(path.rsplit('/').next().unwrap_or(path)),
+            path: path.to_string(),
+            main_executable: executables.first().cloned(),
+            executables,
+            size: 0,
+            already_imported,
+            candidates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn relink_prefers_folders_with_the_original_name() {
+        let scanned = vec![
+            scanned_game("/games/Other", &["Game.exe"], false),
+            scanned_game(
+                "/games/[社团名] 游戏名 (v1.1)",
+                &["bin/game.EXE", "config.exe"],
+                false,
+            ),
+            scanned_game("/games/[社团名] 游戏名", &["Game.exe"], false),
+            scanned_game("/games/Imported", &["Game.exe"], true),
+        ];
+
+        let candidates = relink_candidates(Path::new("/old/[社团名] 游戏名/Game.exe"), &scanned);
+        assert_eq!(
+            candidates,
+            vec![
+                Path::new("/games/[社团名] 游戏名").join("Game.exe"),
+                Path::new("/games/[社团名] 游戏名 (v1.1)").join("bin/game.EXE"),
+                Path::new("/games/Other").join("Game.exe"),
+            ]
+            .into_iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn streaming_scan_reports_each_top_level_dir_and_stops_on_cancel() {
+        let root = std::env::temp_dir().join(format!("reina_scan_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for file in [
+            "A Game/game.exe",
+            "A Game/sub/tool.exe",
+            "B Library/Nested/start.bat",
+            "C Imported/play.exe",
+            "root.exe",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"1234").unwrap();
+        }
+        let existing = HashMap::from([(root.join("C Imported"), root.join("C Imported/play.exe"))]);
+        let rules = ScanRules::resolve(&ScanConfig::default(), Some(3));
+
+        let mut steps = Vec::new();
+        let completed = scan_games_streaming(
+            &root,
+            &existing,
+            &rules,
+            &AtomicBool::new(false),
+            |step, games| steps.push((step, games)),
+        )
+        .unwrap();
+        assert!(completed);
+        assert_eq!(steps.len(), 3);
+        assert!(steps.iter().all(|(step, _)| step.total == 3));
+        let names: Vec<_> = steps
+            .iter()
+            .flat_map(|(_, games)| {
+                games
+                    .iter()
+                    .map(|game| (game.name.as_str(), game.already_imported))
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![("A Game", false), ("Nested", false), ("C Imported", true)]
+        );
+        assert_eq!(steps[0].1[0].main_executable.as_deref(), Some("game.exe"));
+        assert_eq!(steps[0].1[0].size, 8);
+
+        let cancel = AtomicBool::new(false);
+        let mut seen = 0;
+        let completed = scan_games_streaming(&root, &existing, &rules, &cancel, |_, _| {
+            seen += 1;
+            cancel.store(true, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert!(!completed);
+        assert_eq!(seen, 1);
+
+        let _ = fs::remove_dir_all(&root);
+    }
 }
@@ -0,0 +1,562 @@
+//! 游玩截图
+//!
+//! 截取处于前台的游戏窗口（仅 Windows），保存为 PNG 到 `screenshots/game_<id>` 目录，
+//! 并在 `screenshots` 表中记录路径和尺寸。可选注册全局快捷键，游戏在前台时按下即截图。
+//! 也可以把已有的截图文件夹导入图库（原地索引，不复制文件），恢复数据库备份后重新导入即可找回图库。
+//! 游戏引擎自行保存截图时，可为游戏设置 `screenshot_dir`，由 `import_engine_screenshots`
+//! 把其中的新图片复制到图库，并按文件时间关联到对应的游戏会话。
+
+use crate::database::db::DbState;
+use crate::database::repository::game_stats_repository::GameStatsRepository;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::screenshots_repository::ScreenshotsRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::screenshots;
+use crate::game::monitor::active_sessions;
+use crate::utils::metrics::CommandTimer;
+use log::{info, warn};
+use parking_lot::Mutex;
+use sea_orm::{DatabaseConnection, Set};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use walkdir::WalkDir;
+
+/// 截图成功后发送的事件
+const SCREENSHOT_CAPTURED_EVENT: &str = "screenshot-captured";
+
+/// 未指定分页大小时每页返回的截图数量
+const DEFAULT_PAGE_SIZE: u64 = 50;
+
+/// 导入时识别为截图的文件扩展名
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "bmp", "webp"];
+
+/// 当前注册的截图快捷键
+static SCREENSHOT_HOTKEY: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+/// 截图文件夹导入结果
+#[derive(Debug, Serialize)]
+pub struct ScreenshotImportResult {
+    /// 新建索引的截图数量
+    pub imported: usize,
+    /// 已在图库中而跳过的数量
+    pub skipped: usize,
+}
+
+/// 获取指定游戏的截图目录，不存在时创建
+fn game_screenshot_dir(game_id: i32) -> Result<PathBuf, String> {
+    let dir = reina_path::get_screenshots_dir()?.join(format!("game_{}", game_id));
+    fs::create_dir_all(&dir).map_err(|e| format!("创建截图目录失败: {}", e))?;
+    Ok(dir)
+}
+
+/// 截取前台的游戏窗口（客户区），返回 RGBA 图像
+///
+/// 前台窗口不属于该游戏的监控进程时返回错误，避免截到其他程序。
+#[cfg(target_os = "windows")]
+fn capture_foreground_game_window(game_id: u32) -> Result<image::RgbaImage, String> {
+    use crate::game::monitor::get_game_process_ids;
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Gdi::{
+        BI_RGB, BITMAPINFO, BITMAPINFOHEADER, BitBlt, CreateCompatibleBitmap, CreateCompatibleDC,
+        DIB_RGB_COLORS, DeleteDC, DeleteObject, GetDC, GetDIBits, ReleaseDC, SRCCOPY, SelectObject,
+    };
+    use windows::Win32::Storage::Xps::{PRINT_WINDOW_FLAGS, PrintWindow};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetClientRect, GetForegroundWindow, GetWindowThreadProcessId,
+    };
+
+    /// PW_CLIENTONLY | PW_RENDERFULLCONTENT，后者让 DirectX 渲染的窗口也能正确截取
+    const PRINT_FLAGS: PRINT_WINDOW_FLAGS = PRINT_WINDOW_FLAGS(0x1 | 0x2);
+
+    let pids = get_game_process_ids(game_id).ok_or_else(|| "游戏未在运行".to_string())?;
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return Err("没有处于前台的窗口".to_string());
+        }
+        let mut pid: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if !pids.contains(&pid) {
+            return Err("游戏窗口不在前台，请切换到游戏后再截图".to_string());
+        }
+
+        let mut rect = RECT::default();
+        GetClientRect(hwnd, &mut rect).map_err(|e| format!("获取窗口尺寸失败: {}", e))?;
+        let width = rect.right - rect.left;
+        let height = rect.bottom - rect.top;
+        if width <= 0 || height <= 0 {
+            return Err("游戏窗口尺寸无效（可能已最小化）".to_string());
+        }
+
+        let window_dc = GetDC(Some(hwnd));
+        let mem_dc = CreateCompatibleDC(Some(window_dc));
+        let bitmap = CreateCompatibleBitmap(window_dc, width, height);
+        let previous = SelectObject(mem_dc, bitmap.into());
+
+        // 部分窗口不响应 PrintWindow，退回直接从窗口 DC 复制
+        if !PrintWindow(hwnd, mem_dc, PRINT_FLAGS).as_bool() {
+            let _ = BitBlt(mem_dc, 0, 0, width, height, Some(window_dc), 0, 0, SRCCOPY);
+        }
+
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // 负值表示自上而下的行顺序
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut buffer = vec![0u8; width as usize * height as usize * 4];
+        let lines = GetDIBits(
+            mem_dc,
+            bitmap,
+            0,
+            height as u32,
+            Some(buffer.as_mut_ptr().cast()),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+
+        SelectObject(mem_dc, previous);
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(mem_dc);
+        ReleaseDC(Some(hwnd), window_dc);
+
+        if lines == 0 {
+            return Err("读取窗口图像失败".to_string());
+        }
+
+        // GDI 输出为 BGRA，且 alpha 通道无意义
+        for pixel in buffer.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
+        }
+        image::RgbaImage::from_raw(width as u32, height as u32, buffer)
+            .ok_or_else(|| "窗口图像数据无效".to_string())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn capture_foreground_game_window(_game_id: u32) -> Result<image::RgbaImage, String> {
+    Err("当前平台暂不支持截取游戏窗口".to_string())
+}
+
+/// 截取游戏窗口并保存、记录
+async fn capture_and_record(
+    db: &DatabaseConnection,
+    game_id: i32,
+) -> Result<screenshots::Model, String> {
+    let image = tokio::task::spawn_blocking(move || capture_foreground_game_window(game_id as u32))
+        .await
+        .map_err(|e| format!("截图任务失败: {}", e))??;
+
+    let now = chrono::Local::now();
+    let file_name = format!("{}.png", now.format("%Y%m%d_%H%M%S_%3f"));
+    let path = game_screenshot_dir(game_id)?.join(file_name);
+    let (width, height) = image.dimensions();
+
+    let save_path = path.clone();
+    tokio::task::spawn_blocking(move || image.save(&save_path))
+        .await
+        .map_err(|e| format!("截图任务失败: {}", e))?
+        .map_err(|e| format!("保存截图失败: {}", e))?;
+
+    let record = ScreenshotsRepository::insert_screenshot(
+        db,
+        game_id,
+        path.to_string_lossy().to_string(),
+        now.timestamp() as i32,
+        Some(width as i32),
+        Some(height as i32),
+    )
+    .await
+    .map_err(|e| format!("保存截图记录失败: {}", e))?;
+
+    info!("已保存游戏截图 game_id={} path={}", game_id, path.display());
+    Ok(record)
+}
+
+/// 截取指定游戏的窗口
+///
+/// 游戏需正在运行且窗口处于前台。
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+///
+/// # Returns
+/// * `Result<screenshots::Model, String>` - 新的截图记录或错误消息
+#[tauri::command]
+pub async fn capture_game_screenshot(
+    db: State<'_, DbState>,
+    game_id: i32,
+) -> Result<screenshots::Model, String> {
+    let _timer = CommandTimer::start("capture_game_screenshot");
+    let db = db.get();
+    capture_and_record(&db, game_id).await
+}
+
+/// 分页获取指定游戏的截图（按截取时间倒序）
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+/// * `limit` - 每页数量，默认 50
+/// * `offset` - 跳过的数量，默认 0
+#[tauri::command]
+pub async fn get_screenshots(
+    db: State<'_, DbState>,
+    game_id: i32,
+    limit: Option<u64>,
+    offset: Option<u64>,
+) -> Result<Vec<screenshots::Model>, String> {
+    let _timer = CommandTimer::start("get_screenshots");
+    let db = db.get();
+    ScreenshotsRepository::get_screenshots(
+        &db,
+        game_id,
+        limit.unwrap_or(DEFAULT_PAGE_SIZE),
+        offset.unwrap_or(0),
+    )
+    .await
+    .map_err(|e| format!("获取截图失败: {}", e))
+}
+
+/// 判断文件是否为可导入的图片
+fn is_image_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// 扫描文件夹（含子目录）中的图片，返回路径和修改时间（Unix 时间戳，秒）
+fn scan_image_files(folder: &Path) -> Vec<(PathBuf, i32)> {
+    WalkDir::new(folder)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && is_image_file(entry.path()))
+        .map(|entry| {
+            let modified = entry
+                .metadata()
+                .ok()
+                .and_then(|meta| meta.modified().ok())
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_secs() as i32);
+            (entry.into_path(), modified)
+        })
+        .collect()
+}
+
+/// 把已有文件夹中的截图导入到游戏图库
+///
+/// 图片原地索引，不复制文件；截取时间取文件修改时间，已在图库中的路径会被跳过。
+/// 之后从图库删除这些截图时只移除记录，不会删除原文件。
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+/// * `path` - 截图文件夹路径
+///
+/// # Returns
+/// * `Result<ScreenshotImportResult, String>` - 导入结果或错误消息
+#[tauri::command]
+pub async fn import_screenshots_from_folder(
+    db: State<'_, DbState>,
+    game_id: i32,
+    path: String,
+) -> Result<ScreenshotImportResult, String> {
+    let _timer = CommandTimer::start("import_screenshots_from_folder");
+    let db = db.get();
+    let folder = PathBuf::from(&path);
+    if !folder.is_dir() {
+        return Err(format!("截图文件夹不存在: {}", path));
+    }
+
+    let existing = ScreenshotsRepository::get_paths_by_game(&db, game_id)
+        .await
+        .map_err(|e| format!("获取截图失败: {}", e))?;
+    let files = tokio::task::spawn_blocking(move || scan_image_files(&folder))
+        .await
+        .map_err(|e| format!("扫描截图文件夹失败: {}", e))?;
+
+    let mut skipped = 0;
+    let mut records = Vec::new();
+    for (file, captured_at) in files {
+        let file_path = file.to_string_lossy().to_string();
+        if existing.contains(&file_path) {
+            skipped += 1;
+            continue;
+        }
+        // 只能读出已启用解码器的格式（PNG）的尺寸，其他格式留空
+        let dimensions = image::image_dimensions(&file).ok();
+        records.push(screenshots::ActiveModel {
+            game_id: Set(game_id),
+            path: Set(file_path),
+            captured_at: Set(captured_at),
+            width: Set(dimensions.map(|(width, _)| width as i32)),
+            height: Set(dimensions.map(|(_, height)| height as i32)),
+            ..Default::default()
+        });
+    }
+
+    let imported = ScreenshotsRepository::insert_screenshots(&db, records)
+        .await
+        .map_err(|e| format!("保存截图记录失败: {}", e))?;
+
+    info!(
+        "截图文件夹导入完成 game_id={} path={} imported={} skipped={}",
+        game_id, path, imported, skipped
+    );
+    Ok(ScreenshotImportResult { imported, skipped })
+}
+
+/// 解析游戏的自带截图目录，相对路径基于游戏可执行文件所在目录
+fn resolve_engine_screenshot_dir(screenshot_dir: &str, localpath: Option<&str>) -> Option<PathBuf> {
+    let dir = Path::new(screenshot_dir);
+    if dir.is_absolute() {
+        return Some(dir.to_path_buf());
+    }
+    localpath
+        .and_then(|path| Path::new(path).parent())
+        .map(|game_dir| game_dir.join(dir))
+}
+
+/// 按截取时间找到所在的会话（包含该时间点，或会话结束后一分钟内保存的文件）
+fn find_session_at(ranges: &[(i32, i32, i32)], captured_at: i32) -> Option<i32> {
+    const SAVE_GRACE_SECS: i32 = 60;
+    ranges
+        .iter()
+        .find(|(_, start, end)| *start <= captured_at && captured_at <= end + SAVE_GRACE_SECS)
+        .map(|(session_id, _, _)| *session_id)
+}
+
+/// 把游戏自带截图目录中的新图片复制到图库
+///
+/// 已复制过的原文件会被跳过；截取时间取原文件修改时间，并关联到对应的游戏会话。
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID，需已设置 `screenshot_dir`
+///
+/// # Returns
+/// * `Result<ScreenshotImportResult, String>` - 导入结果或错误消息
+#[tauri::command]
+pub async fn import_engine_screenshots(
+    db: State<'_, DbState>,
+    game_id: i32,
+) -> Result<ScreenshotImportResult, String> {
+    let _timer = CommandTimer::start("import_engine_screenshots");
+    let db = db.get();
+    let game = GamesRepository::find_by_id(&db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏数据失败: {}", e))?
+        .ok_or("游戏不存在")?;
+    let Some(screenshot_dir) = game.screenshot_dir.as_deref() else {
+        return Err("未设置游戏截图目录".to_string());
+    };
+    let source_dir = resolve_engine_screenshot_dir(screenshot_dir, game.localpath.as_deref())
+        .ok_or("截图目录为相对路径，但游戏未设置本地路径")?;
+    if !source_dir.is_dir() {
+        return Err(format!("截图目录不存在: {}", source_dir.display()));
+    }
+
+    let copied = ScreenshotsRepository::get_source_paths_by_game(&db, game_id)
+        .await
+        .map_err(|e| format!("获取截图失败: {}", e))?;
+    let ranges = GameStatsRepository::get_session_ranges(&db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏会话失败: {}", e))?;
+    let target_dir = game_screenshot_dir(game_id)?;
+
+    let (records, skipped) = tokio::task::spawn_blocking(move || {
+        let mut records = Vec::new();
+        let mut skipped = 0;
+        for (file, captured_at) in scan_image_files(&source_dir) {
+            let source_path = file.to_string_lossy().to_string();
+            if copied.contains(&source_path) {
+                skipped += 1;
+                continue;
+            }
+            let Some(file_name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            // 加上时间前缀避免不同子目录中的同名文件互相覆盖
+            let mut target = target_dir.join(format!("{}_{}", captured_at, file_name));
+            let mut index = 1;
+            while target.exists() {
+                target = target_dir.join(format!("{}_{}_{}", captured_at, index, file_name));
+                index += 1;
+            }
+            if let Err(e) = fs::copy(&file, &target) {
+                warn!("复制截图失败 {}: {}", file.display(), e);
+                continue;
+            }
+
+            let dimensions = image::image_dimensions(&target).ok();
+            records.push(screenshots::ActiveModel {
+                game_id: Set(game_id),
+                path: Set(target.to_string_lossy().to_string()),
+                captured_at: Set(captured_at),
+                width: Set(dimensions.map(|(width, _)| width as i32)),
+                height: Set(dimensions.map(|(_, height)| height as i32)),
+                source_path: Set(Some(source_path)),
+                session_id: Set(find_session_at(&ranges, captured_at)),
+                ..Default::default()
+            });
+        }
+        (records, skipped)
+    })
+    .await
+    .map_err(|e| format!("复制截图失败: {}", e))?;
+
+    let imported = ScreenshotsRepository::insert_screenshots(&db, records)
+        .await
+        .map_err(|e| format!("保存截图记录失败: {}", e))?;
+
+    info!(
+        "游戏自带截图导入完成 game_id={} imported={} skipped={}",
+        game_id, imported, skipped
+    );
+    Ok(ScreenshotImportResult { imported, skipped })
+}
+
+/// 图片是否位于应用管理的截图目录中；从外部文件夹原地导入的图片属于用户，不在其中
+fn is_managed_screenshot(path: &Path) -> bool {
+    let Ok(root) = reina_path::get_screenshots_dir() else {
+        return false;
+    };
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    canonical(path).starts_with(canonical(&root))
+}
+
+/// 删除截图记录，图片位于应用的截图目录中时一并删除文件
+///
+/// 从外部文件夹导入的图片只从图库中移除，不删除原文件；图片文件已不存在时只删除记录。
+#[tauri::command]
+pub async fn delete_screenshot(db: State<'_, DbState>, screenshot_id: i32) -> Result<(), String> {
+    let _timer = CommandTimer::start("delete_screenshot");
+    let db = db.get();
+    let Some(record) = ScreenshotsRepository::find_by_id(&db, screenshot_id)
+        .await
+        .map_err(|e| format!("获取截图失败: {}", e))?
+    else {
+        return Ok(());
+    };
+
+    let path = Path::new(&record.path);
+    if is_managed_screenshot(path) {
+        match fs::remove_file(path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("删除截图文件失败: {}", e)),
+        }
+    }
+    ScreenshotsRepository::delete(&db, screenshot_id)
+        .await
+        .map_err(|e| format!("删除截图记录失败: {}", e))?;
+    Ok(())
+}
+
+/// 快捷键触发时截取当前处于前台的游戏
+async fn capture_active_game<R: Runtime>(app: AppHandle<R>) {
+    let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    let sessions = active_sessions();
+    if sessions.is_empty() {
+        return;
+    }
+
+    // 只有前台的游戏能截取成功，按开始时间依次尝试
+    let mut last_error = None;
+    for session in sessions {
+        match capture_and_record(&db, session.game_id as i32).await {
+            Ok(record) => {
+                if let Err(e) = app.emit(SCREENSHOT_CAPTURED_EVENT, &record) {
+                    warn!("无法发送 {} 事件: {}", SCREENSHOT_CAPTURED_EVENT, e);
+                }
+                return;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    if let Some(e) = last_error {
+        warn!("快捷键截图失败: {}", e);
+    }
+}
+
+/// 注册截图快捷键，替换之前注册的快捷键
+///
+/// 传入 None 或空字符串时只注销。
+fn apply_screenshot_hotkey<R: Runtime>(
+    app: &AppHandle<R>,
+    shortcut: Option<&str>,
+) -> Result<(), String> {
+    let mut current = SCREENSHOT_HOTKEY.lock();
+    if let Some(previous) = current.take()
+        && let Err(e) = app.global_shortcut().unregister(previous)
+    {
+        warn!("注销截图快捷键失败: {}", e);
+    }
+
+    let Some(shortcut) = shortcut.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok(());
+    };
+    let parsed: Shortcut = shortcut
+        .parse()
+        .map_err(|e| format!("无效的快捷键 {}: {}", shortcut, e))?;
+    app.global_shortcut()
+        .on_shortcut(parsed, |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                tauri::async_runtime::spawn(capture_active_game(app.clone()));
+            }
+        })
+        .map_err(|e| format!("注册截图快捷键失败（可能已被其他程序占用）: {}", e))?;
+    *current = Some(parsed);
+
+    info!("已注册截图快捷键: {}", shortcut);
+    Ok(())
+}
+
+/// 启动时按监控设置注册截图快捷键
+pub async fn init_screenshot_hotkey<R: Runtime>(app: AppHandle<R>) {
+    let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    let hotkey = match db.get_settings().await {
+        Ok(settings) => settings
+            .monitor_settings
+            .and_then(|monitor| monitor.screenshot_hotkey),
+        Err(e) => {
+            warn!("读取截图快捷键设置失败: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = apply_screenshot_hotkey(&app, hotkey.as_deref()) {
+        warn!("{}", e);
+    }
+}
+
+/// 重新注册截图快捷键（保存监控设置中的 `screenshot_hotkey` 后调用）
+///
+/// # Arguments
+/// * `shortcut` - 快捷键（如 `Ctrl+Shift+S`），None 或空字符串表示关闭
+#[tauri::command]
+pub async fn register_screenshot_hotkey(
+    app: AppHandle,
+    shortcut: Option<String>,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("register_screenshot_hotkey");
+    apply_screenshot_hotkey(&app, shortcut.as_deref())
+}
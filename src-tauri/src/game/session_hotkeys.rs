@@ -0,0 +1,183 @@
+//! 游戏会话全局快捷键
+//!
+//! 与截图快捷键相同，通过 global-shortcut 插件注册，快捷键保存在监控设置中：
+//! - `stop_game_hotkey`：停止最近启动的游戏（先请求关闭，超时后强制终止）
+//! - `pause_timing_hotkey`：暂停 / 恢复最近启动的游戏的计时，离开座位时不必关闭游戏
+//!
+//! 同时运行多个游戏时作用于最近启动的会话。
+
+use crate::database::db::DbState;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::game::monitor::{
+    DEFAULT_STOP_GRACE_SECS, active_sessions, stop_game_session, toggle_timing_pause,
+};
+use crate::utils::metrics::CommandTimer;
+use log::{info, warn};
+use parking_lot::Mutex;
+use serde_json::json;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// 暂停状态切换后发送的事件
+const TIMING_PAUSED_EVENT: &str = "game-timing-paused";
+
+/// 当前注册的停止游戏快捷键
+static STOP_HOTKEY: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+/// 当前注册的暂停计时快捷键
+static PAUSE_HOTKEY: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+#[derive(Clone, Copy)]
+enum HotkeyAction {
+    Stop,
+    Pause,
+}
+
+impl HotkeyAction {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Stop => "停止游戏",
+            Self::Pause => "暂停计时",
+        }
+    }
+
+    fn slot(self) -> &'static Mutex<Option<Shortcut>> {
+        match self {
+            Self::Stop => &STOP_HOTKEY,
+            Self::Pause => &PAUSE_HOTKEY,
+        }
+    }
+}
+
+/// 最近启动的会话对应的游戏 ID
+fn latest_game_id() -> Option<u32> {
+    active_sessions().last().map(|session| session.game_id)
+}
+
+/// 切换指定游戏的暂停计时状态并通知前端，返回切换后是否暂停
+fn toggle_pause_and_notify<R: Runtime>(app: &AppHandle<R>, game_id: u32) -> Option<bool> {
+    let paused = toggle_timing_pause(game_id)?;
+    info!(
+        "游戏 {} 已{}计时",
+        game_id,
+        if paused { "暂停" } else { "恢复" }
+    );
+    if let Err(e) = app.emit(
+        TIMING_PAUSED_EVENT,
+        json!({ "gameId": game_id, "paused": paused }),
+    ) {
+        warn!("无法发送 {} 事件: {}", TIMING_PAUSED_EVENT, e);
+    }
+    Some(paused)
+}
+
+/// 快捷键触发时作用于最近启动的会话
+async fn run_action<R: Runtime>(app: AppHandle<R>, action: HotkeyAction) {
+    let Some(game_id) = latest_game_id() else {
+        return;
+    };
+    match action {
+        HotkeyAction::Stop => {
+            let grace = Duration::from_secs(DEFAULT_STOP_GRACE_SECS);
+            match stop_game_session(game_id, grace).await {
+                Ok(outcome) => info!(
+                    "快捷键已停止游戏 {}，共 {} 个进程",
+                    game_id, outcome.terminated_count
+                ),
+                Err(e) => warn!("快捷键停止游戏 {} 失败: {}", game_id, e),
+            }
+        }
+        HotkeyAction::Pause => {
+            toggle_pause_and_notify(&app, game_id);
+        }
+    }
+}
+
+/// 注册快捷键，替换该操作之前注册的快捷键
+///
+/// 传入 None 或空字符串时只注销。
+fn apply_hotkey<R: Runtime>(
+    app: &AppHandle<R>,
+    action: HotkeyAction,
+    shortcut: Option<&str>,
+) -> Result<(), String> {
+    let mut current = action.slot().lock();
+    if let Some(previous) = current.take()
+        && let Err(e) = app.global_shortcut().unregister(previous)
+    {
+        warn!("注销{}快捷键失败: {}", action.label(), e);
+    }
+
+    let Some(shortcut) = shortcut.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok(());
+    };
+    let parsed: Shortcut = shortcut
+        .parse()
+        .map_err(|e| format!("无效的快捷键 {}: {}", shortcut, e))?;
+    app.global_shortcut()
+        .on_shortcut(parsed, move |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                tauri::async_runtime::spawn(run_action(app.clone(), action));
+            }
+        })
+        .map_err(|e| {
+            format!(
+                "注册{}快捷键失败（可能已被其他程序占用）: {}",
+                action.label(),
+                e
+            )
+        })?;
+    *current = Some(parsed);
+
+    info!("已注册{}快捷键: {}", action.label(), shortcut);
+    Ok(())
+}
+
+/// 启动时按监控设置注册会话快捷键
+pub async fn init_session_hotkeys<R: Runtime>(app: AppHandle<R>) {
+    let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    let monitor = match db.get_settings().await {
+        Ok(settings) => settings.monitor_settings.unwrap_or_default(),
+        Err(e) => {
+            warn!("读取会话快捷键设置失败: {}", e);
+            return;
+        }
+    };
+    for (action, shortcut) in [
+        (HotkeyAction::Stop, monitor.stop_game_hotkey),
+        (HotkeyAction::Pause, monitor.pause_timing_hotkey),
+    ] {
+        if let Err(e) = apply_hotkey(&app, action, shortcut.as_deref()) {
+            warn!("{}", e);
+        }
+    }
+}
+
+/// 重新注册会话快捷键（保存监控设置中的 `stop_game_hotkey` / `pause_timing_hotkey` 后调用）
+///
+/// # Arguments
+/// * `stop_shortcut` - 停止游戏的快捷键，None 或空字符串表示关闭
+/// * `pause_shortcut` - 暂停 / 恢复计时的快捷键，None 或空字符串表示关闭
+#[tauri::command]
+pub async fn register_session_hotkeys(
+    app: AppHandle,
+    stop_shortcut: Option<String>,
+    pause_shortcut: Option<String>,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("register_session_hotkeys");
+    apply_hotkey(&app, HotkeyAction::Stop, stop_shortcut.as_deref())?;
+    apply_hotkey(&app, HotkeyAction::Pause, pause_shortcut.as_deref())
+}
+
+/// 暂停或恢复指定游戏的计时，返回切换后是否暂停
+///
+/// 暂停期间监控照常检查游戏进程，但不累计游戏时间。
+#[tauri::command]
+pub async fn toggle_session_pause(app: AppHandle, game_id: u32) -> Result<bool, String> {
+    let _timer = CommandTimer::start("toggle_session_pause");
+    toggle_pause_and_notify(&app, game_id)
+        .ok_or_else(|| format!("未找到游戏 {} 的监控会话", game_id))
+}
@@ -0,0 +1,534 @@
+//! Steam 快捷方式导出
+//!
+//! 将库中的游戏以"非 Steam 游戏"形式写入 `userdata/<id>/config/shortcuts.vdf`，
+//! 便于在 Big Picture / Steam Deck 中直接启动。
+//!
+//! shortcuts.vdf 为二进制 VDF 格式，Steam 运行时会在退出时覆盖该文件，
+//! 因此导出前需要关闭 Steam。
+
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::utils::metrics::CommandTimer;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, State};
+
+const VDF_TYPE_MAP: u8 = 0x00;
+const VDF_TYPE_STRING: u8 = 0x01;
+const VDF_TYPE_INT32: u8 = 0x02;
+const VDF_TYPE_END: u8 = 0x08;
+
+const SHORTCUTS_ROOT_KEY: &str = "shortcuts";
+
+/// 二进制 VDF 节点值
+#[derive(Debug, Clone, PartialEq)]
+enum VdfValue {
+    Map(Vec<(String, VdfValue)>),
+    Str(String),
+    Int(u32),
+}
+
+/// Steam 快捷方式导出结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SteamExportResult {
+    /// 写入的 shortcuts.vdf 路径
+    pub written_files: Vec<String>,
+    /// 新增的快捷方式数量（按单个 Steam 用户计）
+    pub added: usize,
+    /// 已存在并被更新的快捷方式数量（按单个 Steam 用户计）
+    pub updated: usize,
+    /// 因缺少本地路径或可执行文件不存在而跳过的游戏 ID
+    pub skipped: Vec<i32>,
+}
+
+/// 需要写入 Steam 的单条快捷方式
+#[derive(Debug, Clone)]
+struct ShortcutEntry {
+    app_id: u32,
+    name: String,
+    exe: String,
+    start_dir: String,
+    icon: String,
+}
+
+// ==================== 二进制 VDF 读写 ====================
+
+fn read_cstring(data: &[u8], pos: &mut usize) -> Result<String, String> {
+    let start = *pos;
+    let end = data[start..]
+        .iter()
+        .position(|&b| b == 0)
+        .map(|offset| start + offset)
+        .ok_or_else(|| "VDF 字符串缺少结束符".to_string())?;
+    *pos = end + 1;
+    Ok(String::from_utf8_lossy(&data[start..end]).into_owned())
+}
+
+fn read_map(data: &[u8], pos: &mut usize) -> Result<Vec<(String, VdfValue)>, String> {
+    let mut entries = Vec::new();
+    loop {
+        let value_type = *data
+            .get(*pos)
+            .ok_or_else(|| "VDF 数据意外结束".to_string())?;
+        *pos += 1;
+
+        if value_type == VDF_TYPE_END {
+            return Ok(entries);
+        }
+
+        let key = read_cstring(data, pos)?;
+        let value = match value_type {
+            VDF_TYPE_MAP => VdfValue::Map(read_map(data, pos)?),
+            VDF_TYPE_STRING => VdfValue::Str(read_cstring(data, pos)?),
+            VDF_TYPE_INT32 => {
+                let bytes = data
+                    .get(*pos..*pos + 4)
+                    .ok_or_else(|| "VDF 整数字段长度不足".to_string())?;
+                *pos += 4;
+                VdfValue::Int(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            other => return Err(format!("不支持的 VDF 字段类型: 0x{:02x}", other)),
+        };
+        entries.push((key, value));
+    }
+}
+
+fn write_map(out: &mut Vec<u8>, entries: &[(String, VdfValue)]) {
+    for (key, value) in entries {
+        match value {
+            VdfValue::Map(children) => {
+                out.push(VDF_TYPE_MAP);
+                write_cstring(out, key);
+                write_map(out, children);
+            }
+            VdfValue::Str(text) => {
+                out.push(VDF_TYPE_STRING);
+                write_cstring(out, key);
+                write_cstring(out, text);
+            }
+            VdfValue::Int(number) => {
+                out.push(VDF_TYPE_INT32);
+                write_cstring(out, key);
+                out.extend_from_slice(&number.to_le_bytes());
+            }
+        }
+    }
+    out.push(VDF_TYPE_END);
+}
+
+fn write_cstring(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(value.as_bytes());
+    out.push(0);
+}
+
+fn parse_shortcuts(data: &[u8]) -> Result<Vec<(String, VdfValue)>, String> {
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut pos = 0;
+    let root = read_map(data, &mut pos)?;
+    let shortcuts = root
+        .into_iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(SHORTCUTS_ROOT_KEY))
+        .map(|(_, value)| value);
+
+    match shortcuts {
+        Some(VdfValue::Map(entries)) => Ok(entries),
+        Some(_) => Err("shortcuts.vdf 格式错误：shortcuts 节点不是对象".to_string()),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn serialize_shortcuts(entries: Vec<VdfValue>) -> Vec<u8> {
+    // Steam 要求数组节点按 "0"、"1"、... 连续编号
+    let indexed = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| (index.to_string(), entry))
+        .collect();
+    let root = vec![(SHORTCUTS_ROOT_KEY.to_string(), VdfValue::Map(indexed))];
+
+    let mut out = Vec::new();
+    write_map(&mut out, &root);
+    out
+}
+
+// ==================== 快捷方式合并 ====================
+
+/// Steam 非 Steam 游戏 ID 的计算方式：crc32(exe + name) | 0x80000000
+fn shortcut_app_id(exe: &str, name: &str) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for byte in exe.bytes().chain(name.bytes()) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc | 0x8000_0000
+}
+
+fn quote_path(path: &str) -> String {
+    format!("\"{}\"", path.trim_matches('"'))
+}
+
+fn get_field<'a>(entry: &'a [(String, VdfValue)], field: &str) -> Option<&'a VdfValue> {
+    entry
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(field))
+        .map(|(_, value)| value)
+}
+
+fn set_field(entry: &mut Vec<(String, VdfValue)>, field: &str, value: VdfValue) {
+    match entry
+        .iter_mut()
+        .find(|(key, _)| key.eq_ignore_ascii_case(field))
+    {
+        Some((_, existing)) => *existing = value,
+        None => entry.push((field.to_string(), value)),
+    }
+}
+
+fn new_shortcut_fields() -> Vec<(String, VdfValue)> {
+    let text = |key: &str| (key.to_string(), VdfValue::Str(String::new()));
+    let int = |key: &str, value: u32| (key.to_string(), VdfValue::Int(value));
+    vec![
+        int("appid", 0),
+        text("AppName"),
+        text("Exe"),
+        text("StartDir"),
+        text("icon"),
+        text("ShortcutPath"),
+        text("LaunchOptions"),
+        int("IsHidden", 0),
+        int("AllowDesktopConfig", 1),
+        int("AllowOverlay", 1),
+        int("OpenVR", 0),
+        int("Devkit", 0),
+        text("DevkitGameID"),
+        int("DevkitOverrideAppID", 0),
+        int("LastPlayTime", 0),
+        text("FlatpakAppID"),
+        ("tags".to_string(), VdfValue::Map(Vec::new())),
+    ]
+}
+
+/// 将待导出的快捷方式合并进现有条目
+///
+/// 按 appid 或可执行文件路径匹配已有条目，命中时只覆盖名称/路径/图标，
+/// 保留用户在 Steam 中设置的启动参数与标签。返回 (新增数, 更新数)。
+fn merge_shortcuts(existing: &mut Vec<VdfValue>, shortcuts: &[ShortcutEntry]) -> (usize, usize) {
+    let mut added = 0;
+    let mut updated = 0;
+
+    for shortcut in shortcuts {
+        let position = existing.iter().position(|entry| {
+            let VdfValue::Map(fields) = entry else {
+                return false;
+            };
+            let same_id =
+                matches!(get_field(fields, "appid"), Some(VdfValue::Int(id)) if *id == shortcut.app_id);
+            let same_exe = matches!(
+                get_field(fields, "Exe"),
+                Some(VdfValue::Str(exe)) if exe.eq_ignore_ascii_case(&shortcut.exe)
+            );
+            same_id || same_exe
+        });
+
+        let fields = match position {
+            Some(index) => {
+                updated += 1;
+                match &mut existing[index] {
+                    VdfValue::Map(fields) => fields,
+                    _ => unreachable!(),
+                }
+            }
+            None => {
+                added += 1;
+                existing.push(VdfValue::Map(new_shortcut_fields()));
+                match existing.last_mut() {
+                    Some(VdfValue::Map(fields)) => fields,
+                    _ => unreachable!(),
+                }
+            }
+        };
+
+        set_field(fields, "appid", VdfValue::Int(shortcut.app_id));
+        set_field(fields, "AppName", VdfValue::Str(shortcut.name.clone()));
+        set_field(fields, "Exe", VdfValue::Str(shortcut.exe.clone()));
+        set_field(
+            fields,
+            "StartDir",
+            VdfValue::Str(shortcut.start_dir.clone()),
+        );
+        set_field(fields, "icon", VdfValue::Str(shortcut.icon.clone()));
+    }
+
+    (added, updated)
+}
+
+// ==================== Steam 目录定位 ====================
+
+#[cfg(target_os = "windows")]
+pub(crate) fn find_steam_root<R: Runtime>(_app_handle: &AppHandle<R>) -> Option<PathBuf> {
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{HKEY_CURRENT_USER, RRF_RT_REG_SZ, RegGetValueW};
+    use windows::core::w;
+
+    let mut buffer = [0u16; 512];
+    let mut size = (buffer.len() * std::mem::size_of::<u16>()) as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Valve\\Steam"),
+            w!("SteamPath"),
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr().cast()),
+            Some(&raw mut size),
+        )
+    };
+
+    if status == ERROR_SUCCESS {
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        let path = PathBuf::from(String::from_utf16_lossy(&buffer[..len]));
+        if path.is_dir() {
+            return Some(path);
+        }
+    }
+
+    let fallback = PathBuf::from(r"C:\Program Files (x86)\Steam");
+    fallback.is_dir().then_some(fallback)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn find_steam_root<R: Runtime>(app_handle: &AppHandle<R>) -> Option<PathBuf> {
+    use tauri::Manager;
+
+    let home = app_handle.path().home_dir().ok()?;
+    [
+        home.join(".steam/steam"),
+        home.join(".local/share/Steam"),
+        home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+    ]
+    .into_iter()
+    .find(|path| path.join("userdata").is_dir())
+}
+
+/// 查找需要写入的 shortcuts.vdf 路径
+///
+/// 指定了 `steam_user_id` 时只写入该用户，否则写入 userdata 下的所有用户。
+fn resolve_shortcut_files(
+    steam_root: &Path,
+    steam_user_id: Option<&str>,
+) -> Result<Vec<PathBuf>, String> {
+    let userdata_dir = steam_root.join("userdata");
+
+    let user_dirs = match steam_user_id {
+        Some(user_id) => {
+            let dir = userdata_dir.join(user_id);
+            if !dir.is_dir() {
+                return Err(format!("未找到 Steam 用户目录: {}", dir.display()));
+            }
+            vec![dir]
+        }
+        None => fs::read_dir(&userdata_dir)
+            .map_err(|e| format!("读取 Steam userdata 目录失败: {}", e))?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_dir()
+                    && path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name != "0" && name.chars().all(|c| c.is_ascii_digit()))
+            })
+            .collect(),
+    };
+
+    if user_dirs.is_empty() {
+        return Err("未找到任何 Steam 用户，请先登录一次 Steam".to_string());
+    }
+
+    Ok(user_dirs
+        .into_iter()
+        .map(|dir| dir.join("config").join("shortcuts.vdf"))
+        .collect())
+}
+
+fn write_shortcut_file(path: &Path, shortcuts: &[ShortcutEntry]) -> Result<(usize, usize), String> {
+    let mut existing: Vec<VdfValue> = if path.exists() {
+        let data = fs::read(path).map_err(|e| format!("读取 shortcuts.vdf 失败: {}", e))?;
+        let entries = parse_shortcuts(&data)?;
+
+        // 写入前保留一份原文件，避免格式异常时丢失用户已有的快捷方式
+        let backup_path = path.with_extension("vdf.bak");
+        fs::copy(path, &backup_path).map_err(|e| format!("备份 shortcuts.vdf 失败: {}", e))?;
+
+        entries.into_iter().map(|(_, value)| value).collect()
+    } else {
+        Vec::new()
+    };
+
+    let counts = merge_shortcuts(&mut existing, shortcuts);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建 Steam 配置目录失败: {}", e))?;
+    }
+    fs::write(path, serialize_shortcuts(existing))
+        .map_err(|e| format!("写入 shortcuts.vdf 失败: {}", e))?;
+
+    Ok(counts)
+}
+
+/// 导出游戏到 Steam 快捷方式
+///
+/// # Arguments
+/// * `game_ids` - 需要导出的游戏 ID 列表
+/// * `steam_user_id` - 可选的 Steam 用户（userdata 下的数字目录名），为空时写入所有用户
+/// * `language` - 前端当前语言，用于选择显示名称（与排序规则一致）
+///
+/// # Returns
+/// * `Result<SteamExportResult, String>` - 导出结果或错误消息
+#[tauri::command]
+pub async fn export_steam_shortcuts<R: Runtime>(
+    app_handle: AppHandle<R>,
+    db: State<'_, DbState>,
+    game_ids: Vec<i32>,
+    steam_user_id: Option<String>,
+    language: Option<String>,
+) -> Result<SteamExportResult, String> {
+    let _timer = CommandTimer::start("export_steam_shortcuts");
+    let db = db.get();
+    let steam_root =
+        find_steam_root(&app_handle).ok_or_else(|| "未找到 Steam 安装目录".to_string())?;
+    let shortcut_files = resolve_shortcut_files(&steam_root, steam_user_id.as_deref())?;
+    let use_cn = language.as_deref() == Some("zh-CN");
+
+    let mut shortcuts = Vec::with_capacity(game_ids.len());
+    let mut skipped = Vec::new();
+
+    for game_id in game_ids {
+        let game = GamesRepository::find_by_id(&db, game_id)
+            .await
+            .map_err(|e| format!("查询游戏失败: {}", e))?
+            .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+
+        let Some(localpath) = game.localpath.as_deref().filter(|p| Path::new(p).is_file()) else {
+            skipped.push(game_id);
+            continue;
+        };
+
+        let exe_path = Path::new(localpath);
+        let name = GamesRepository::get_display_name(&game, use_cn)
+            .map(ToOwned::to_owned)
+            .or_else(|| {
+                exe_path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| format!("game_{}", game_id));
+        let exe = quote_path(localpath);
+        let start_dir = exe_path
+            .parent()
+            .map(|dir| quote_path(&dir.to_string_lossy()))
+            .unwrap_or_default();
+
+        shortcuts.push(ShortcutEntry {
+            app_id: shortcut_app_id(&exe, &name),
+            name,
+            icon: localpath.to_string(),
+            exe,
+            start_dir,
+        });
+    }
+
+    let mut result = SteamExportResult {
+        written_files: Vec::with_capacity(shortcut_files.len()),
+        added: 0,
+        updated: 0,
+        skipped,
+    };
+
+    if shortcuts.is_empty() {
+        return Ok(result);
+    }
+
+    for file in shortcut_files {
+        let (added, updated) = write_shortcut_file(&file, &shortcuts)?;
+        result.added = added;
+        result.updated = updated;
+        result
+            .written_files
+            .push(file.to_string_lossy().to_string());
+    }
+
+    log::info!(
+        "Steam 快捷方式导出完成 files={} added={} updated={} skipped={}",
+        result.written_files.len(),
+        result.added,
+        result.updated,
+        result.skipped.len()
+    );
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcuts_roundtrip_keeps_user_fields() {
+        let mut entries = Vec::new();
+        let first = ShortcutEntry {
+            app_id: shortcut_app_id("\"C:\\game.exe\"", "Game"),
+            name: "Game".to_string(),
+            exe: "\"C:\\game.exe\"".to_string(),
+            start_dir: "\"C:\\\"".to_string(),
+            icon: "C:\\game.exe".to_string(),
+        };
+        assert_eq!(
+            merge_shortcuts(&mut entries, std::slice::from_ref(&first)),
+            (1, 0)
+        );
+
+        if let VdfValue::Map(fields) = &mut entries[0] {
+            set_field(fields, "LaunchOptions", VdfValue::Str("-debug".to_string()));
+        }
+
+        let data = serialize_shortcuts(entries);
+        let mut parsed: Vec<VdfValue> = parse_shortcuts(&data)
+            .unwrap()
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+
+        let renamed = ShortcutEntry {
+            name: "Renamed".to_string(),
+            ..first
+        };
+        assert_eq!(merge_shortcuts(&mut parsed, &[renamed]), (0, 1));
+
+        let VdfValue::Map(fields) = &parsed[0] else {
+            panic!("shortcut entry should be a map");
+        };
+        assert_eq!(
+            get_field(fields, "AppName"),
+            Some(&VdfValue::Str("Renamed".to_string()))
+        );
+        assert_eq!(
+            get_field(fields, "LaunchOptions"),
+            Some(&VdfValue::Str("-debug".to_string()))
+        );
+    }
+
+    #[test]
+    fn app_id_sets_high_bit() {
+        // crc32("123456789") = 0xCBF43926
+        assert_eq!(shortcut_app_id("1234", "56789"), 0xCBF4_3926);
+        assert!(shortcut_app_id("\"a.exe\"", "a") & 0x8000_0000 != 0);
+    }
+}
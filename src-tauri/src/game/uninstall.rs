@@ -0,0 +1,553 @@
+//! 删除游戏文件
+//!
+//! 与只删除库中记录的 `delete_game` 不同，这里连同磁盘上的游戏目录、封面一起删除，可选删除存档目录
+//! 与存档备份。先由 `preview_game_removal` 列出将删除的内容与占用空间，用户确认后再调用
+//! `remove_game_files` 执行；执行时重新生成清单，与确认时不一致则拒绝删除。
+//!
+//! 先删除游戏目录，失败时游戏记录保持不变；成功后在一个短事务内删除游戏记录，再删除其余文件，
+//! 失败只记入结果。删除是否经过回收站取决于 user.use_trash。
+
+use crate::backup::savedata::resolve_savedata_backup_root;
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::entity::prelude::*;
+use crate::entity::{game_sessions, games, savedata, user};
+use crate::game::cover::DownloadState;
+use crate::game::monitor::active_sessions;
+use crate::utils::metrics::CommandTimer;
+use crate::utils::trash::{DeleteOutcome, remove_path, trash_enabled};
+use sea_orm::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, State};
+use walkdir::WalkDir;
+
+/// 删除项的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalKind {
+    /// 游戏安装目录
+    GameFolder,
+    /// 游戏的存档目录（games.savepath）
+    SaveFolder,
+    /// 应用保存的存档备份
+    SavedataBackups,
+    /// 云端缓存与自定义封面
+    Covers,
+    /// 游戏截图
+    Screenshots,
+}
+
+/// 清单中的一个目录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemovalItem {
+    pub kind: RemovalKind,
+    pub path: String,
+    /// 占用空间（字节）
+    pub size: u64,
+    pub file_count: u64,
+}
+
+/// 删除清单
+#[derive(Debug, Clone, Serialize)]
+pub struct GameRemovalManifest {
+    pub game_id: i32,
+    pub name: Option<String>,
+    pub also_saves: bool,
+    /// 将被删除的目录
+    pub items: Vec<RemovalItem>,
+    /// 保留在磁盘上的目录（截图，未选择删除存档时的存档目录与备份）
+    pub kept: Vec<RemovalItem>,
+    /// 随游戏记录一起删除的游玩记录数量
+    pub session_count: u64,
+    /// 随游戏记录一起删除的存档备份记录数量
+    pub savedata_record_count: u64,
+    /// 将释放的空间（字节）
+    pub total_size: u64,
+    /// 是否移到回收站
+    pub use_trash: bool,
+}
+
+/// 删除结果
+#[derive(Debug, Clone, Serialize)]
+pub struct GameRemovalResult {
+    pub manifest: GameRemovalManifest,
+    /// 实际删除的空间（字节），移到回收站的文件也计入
+    pub freed_bytes: u64,
+    /// 是否有文件移到了回收站
+    pub trashed: bool,
+    /// 游戏记录删除后，清理其余目录时的错误
+    pub errors: Vec<String>,
+}
+
+/// 解析为绝对路径用于比较，尽量消除符号链接与大小写差异
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize()
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 游戏的安装目录：localpath 是目录时为其本身，否则为可执行文件所在目录
+fn game_folder(localpath: &str) -> Option<PathBuf> {
+    let path = Path::new(localpath);
+    if path.is_dir() {
+        Some(path.to_path_buf())
+    } else {
+        path.parent()
+            .filter(|dir| !dir.as_os_str().is_empty() && dir.is_dir())
+            .map(Path::to_path_buf)
+    }
+}
+
+/// 检查目录能否随游戏删除
+///
+/// `protected` 中的目录（用户目录、应用数据目录、游戏库目录等）不能被删除或包含在删除的目录中；
+/// `others` 是其他游戏的目录，与要删除的目录互相包含时拒绝，以免误删别的游戏；
+/// 系统目录按 [`check_system_dirs`] 检查。所有路径都应已经过 [`normalize`]。
+fn check_removable(dir: &Path, protected: &[PathBuf], others: &[PathBuf]) -> Result<(), String> {
+    if dir.parent().is_none() {
+        return Err(format!("不能删除磁盘根目录: {}", dir.display()));
+    }
+    check_system_dirs(dir)?;
+    if let Some(path) = protected.iter().find(|path| path.starts_with(dir)) {
+        return Err(format!(
+            "{} 包含受保护的目录 {}，不能删除",
+            dir.display(),
+            path.display()
+        ));
+    }
+    if let Some(path) = others
+        .iter()
+        .find(|path| path.starts_with(dir) || dir.starts_with(path))
+    {
+        return Err(format!(
+            "{} 与其他游戏的目录 {} 重叠，不能删除",
+            dir.display(),
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// 统计目录占用的空间与文件数
+fn dir_usage(path: &Path) -> (u64, u64) {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .fold((0, 0), |(size, count), entry| {
+            let len = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+            (size + len, count + 1)
+        })
+}
+
+fn removal_item(kind: RemovalKind, path: &Path) -> RemovalItem {
+    let (size, file_count) = dir_usage(path);
+    RemovalItem {
+        kind,
+        path: path.to_string_lossy().to_string(),
+        size,
+        file_count,
+    }
+}
+
+/// 系统目录：Windows 目录，或 /usr、/bin 等，目录本身与其中的任何目录都不能删除
+fn system_dirs() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        ["WINDIR", "SystemRoot"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .map(PathBuf::from)
+            .collect()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        [
+            "/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc", "/var", "/boot",
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect()
+    }
+}
+
+/// 软件安装目录：Program Files 或 /opt，只有直接位于其下的游戏目录可以删除
+fn install_dirs() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .map(PathBuf::from)
+            .collect()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        vec![PathBuf::from("/opt")]
+    }
+}
+
+/// 检查目录是否与系统目录重叠
+///
+/// 系统目录、软件安装目录本身，包含它们的目录以及位于它们之中的目录都不能删除；
+/// 只有软件安装目录的下一级（每个游戏自己的安装目录）例外。`dir` 应已经过 [`normalize`]。
+fn check_system_dirs(dir: &Path) -> Result<(), String> {
+    let install: Vec<PathBuf> = install_dirs().iter().map(|path| normalize(path)).collect();
+    if install
+        .iter()
+        .any(|path| dir.parent() == Some(path.as_path()))
+    {
+        return Ok(());
+    }
+    let system: Vec<PathBuf> = system_dirs().iter().map(|path| normalize(path)).collect();
+    if let Some(path) = system
+        .iter()
+        .chain(&install)
+        .find(|path| dir.starts_with(path) || path.starts_with(dir))
+    {
+        return Err(format!(
+            "{} 与系统目录 {} 重叠，不能删除",
+            dir.display(),
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// 不能随游戏删除的目录：用户目录、应用数据目录与程序所在目录、首次启动向导选择的游戏库目录
+fn protected_dirs<R: Runtime>(app: &AppHandle<R>, settings: &user::Model) -> Vec<PathBuf> {
+    let resolver = app.path();
+    let mut dirs = Vec::new();
+    dirs.extend(resolver.home_dir().ok());
+    dirs.extend(resolver.document_dir().ok());
+    dirs.extend(resolver.desktop_dir().ok());
+    dirs.extend(resolver.download_dir().ok());
+    dirs.extend(resolver.data_dir().ok());
+    dirs.extend(resolver.local_data_dir().ok());
+    dirs.extend(reina_path::get_base_data_dir().ok());
+    dirs.extend(
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf)),
+    );
+    if let Some(progress) = &settings.setup_progress {
+        dirs.extend(progress.library_folders.iter().map(PathBuf::from));
+    }
+    dirs.into_iter().map(|dir| normalize(&dir)).collect()
+}
+
+/// 生成删除清单
+async fn build_manifest<R: Runtime>(
+    app: &AppHandle<R>,
+    db: &DatabaseConnection,
+    game_id: i32,
+    also_saves: bool,
+) -> Result<GameRemovalManifest, String> {
+    let game = GamesRepository::find_by_id(db, game_id)
+        .await
+        .map_err(|e| format!("获取游戏数据失败: {}", e))?
+        .ok_or_else(|| format!("游戏不存在: {}", game_id))?;
+    let settings = SettingsRepository::get_stored_settings(db)
+        .await
+        .map_err(|e| format!("读取用户设置失败: {}", e))?;
+
+    let other_games: Vec<(i32, Option<String>, Option<String>)> = Games::find()
+        .select_only()
+        .column(games::Column::Id)
+        .column(games::Column::Localpath)
+        .column(games::Column::Savepath)
+        .filter(games::Column::Id.ne(game_id))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("获取游戏路径失败: {}", e))?;
+    let session_count = GameSessions::find()
+        .filter(game_sessions::Column::GameId.eq(game_id))
+        .count(db)
+        .await
+        .map_err(|e| format!("统计游玩记录失败: {}", e))?;
+    let savedata_record_count = Savedata::find()
+        .filter(savedata::Column::GameId.eq(game_id))
+        .count(db)
+        .await
+        .map_err(|e| format!("统计存档备份记录失败: {}", e))?;
+
+    let game_dir = game.localpath.as_deref().and_then(game_folder);
+    let save_dir = game
+        .savepath
+        .as_deref()
+        .map(PathBuf::from)
+        .filter(|path| path.is_dir());
+    let backup_dir = resolve_savedata_backup_root(db)
+        .await?
+        .join(format!("game_{}", game_id));
+    let cover_dir = reina_path::get_base_data_dir()?
+        .join(reina_path::COVERS_SUBDIR)
+        .join(format!("game_{}", game_id));
+    let screenshot_dir = reina_path::get_screenshots_dir()?.join(format!("game_{}", game_id));
+    let protected = protected_dirs(app, &settings);
+    let use_trash = trash_enabled(db).await;
+    let name = GamesRepository::get_display_name(&game, true).map(str::to_string);
+
+    tokio::task::spawn_blocking(move || {
+        let others: Vec<PathBuf> = other_games
+            .iter()
+            .flat_map(|(_, localpath, savepath)| {
+                let localpath = localpath.as_deref().and_then(game_folder);
+                let savepath = savepath
+                    .as_deref()
+                    .map(PathBuf::from)
+                    .filter(|p| p.is_dir());
+                localpath.into_iter().chain(savepath)
+            })
+            .map(|path| normalize(&path))
+            .collect();
+
+        let mut items = Vec::new();
+        let mut kept = Vec::new();
+        let game_dir_normalized = game_dir.as_deref().map(normalize);
+        if let (Some(dir), Some(normalized)) = (&game_dir, &game_dir_normalized) {
+            check_removable(normalized, &protected, &others)?;
+            items.push(removal_item(RemovalKind::GameFolder, dir));
+        }
+        if let Some(dir) = &save_dir {
+            let normalized = normalize(dir);
+            // 存档在游戏目录内时会随游戏目录删除
+            let inside_game_dir = game_dir_normalized
+                .as_deref()
+                .is_some_and(|game_dir| normalized.starts_with(game_dir));
+            if inside_game_dir && !also_saves {
+                return Err(format!(
+                    "存档目录 {} 位于游戏目录内，会随游戏目录删除，请先备份存档或选择同时删除存档",
+                    dir.display()
+                ));
+            }
+            if !inside_game_dir {
+                if also_saves {
+                    check_removable(&normalized, &protected, &others)?;
+                    items.push(removal_item(RemovalKind::SaveFolder, dir));
+                } else {
+                    kept.push(removal_item(RemovalKind::SaveFolder, dir));
+                }
+            }
+        }
+        for (kind, dir, remove) in [
+            (RemovalKind::SavedataBackups, &backup_dir, also_saves),
+            (RemovalKind::Covers, &cover_dir, true),
+            (RemovalKind::Screenshots, &screenshot_dir, false),
+        ] {
+            if dir.is_dir() {
+                let item = removal_item(kind, dir);
+                if remove {
+                    items.push(item)
+                } else {
+                    kept.push(item)
+                }
+            }
+        }
+
+        Ok(GameRemovalManifest {
+            game_id,
+            name,
+            also_saves,
+            total_size: items.iter().map(|item| item.size).sum(),
+            items,
+            kept,
+            session_count,
+            savedata_record_count,
+            use_trash,
+        })
+    })
+    .await
+    .map_err(|e| format!("生成删除清单失败: {}", e))?
+}
+
+async fn remove_item(item: &RemovalItem, use_trash: bool) -> Result<DeleteOutcome, String> {
+    let path = PathBuf::from(&item.path);
+    tokio::task::spawn_blocking(move || remove_path(&path, use_trash))
+        .await
+        .map_err(|e| format!("删除 {} 失败: {}", item.path, e))?
+        .map_err(|e| format!("删除 {} 失败: {}", item.path, e))
+}
+
+/// 预览删除游戏文件时会删除的内容
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+/// * `also_saves` - 是否同时删除存档目录与存档备份
+///
+/// # Returns
+/// * `Result<GameRemovalManifest, String>` - 删除清单，确认后把 `items` 的路径传给 `remove_game_files`
+#[tauri::command]
+pub async fn preview_game_removal(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    game_id: i32,
+    also_saves: bool,
+) -> Result<GameRemovalManifest, String> {
+    let _timer = CommandTimer::start("preview_game_removal");
+    build_manifest(&app, &db.get(), game_id, also_saves).await
+}
+
+/// 删除游戏记录及其在磁盘上的文件
+///
+/// 游戏正在运行或清单与确认时不一致时拒绝删除。游戏目录删除失败时游戏记录保持不变；
+/// 删除目录可能耗时较长，不在数据库事务内进行。
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID
+/// * `also_saves` - 是否同时删除存档目录与存档备份
+/// * `confirmed_paths` - 用户确认过的清单路径（`preview_game_removal` 返回的 `items`）
+///
+/// # Returns
+/// * `Result<GameRemovalResult, String>` - 删除结果与释放的空间
+#[tauri::command]
+pub async fn remove_game_files(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    cover_state: State<'_, DownloadState>,
+    game_id: i32,
+    also_saves: bool,
+    confirmed_paths: Vec<String>,
+) -> Result<GameRemovalResult, String> {
+    let _timer = CommandTimer::start("remove_game_files");
+    if active_sessions()
+        .iter()
+        .any(|session| session.game_id as i32 == game_id)
+    {
+        return Err("游戏正在运行，请先退出游戏".to_string());
+    }
+
+    let db = db.get();
+    let manifest = build_manifest(&app, &db, game_id, also_saves).await?;
+    let paths: Vec<&str> = manifest
+        .items
+        .iter()
+        .map(|item| item.path.as_str())
+        .collect();
+    if paths
+        != confirmed_paths
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+    {
+        return Err("待删除的内容已变化，请重新确认".to_string());
+    }
+
+    let use_trash = manifest.use_trash;
+    let (game_dirs, rest): (Vec<_>, Vec<_>) = manifest
+        .items
+        .iter()
+        .partition(|item| item.kind == RemovalKind::GameFolder);
+    let mut freed_bytes = 0;
+    let mut trashed = false;
+
+    for item in game_dirs {
+        let outcome = remove_item(item, use_trash).await?;
+        freed_bytes += item.size;
+        trashed |= outcome == DeleteOutcome::Trashed;
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    Games::delete_by_id(game_id)
+        .exec(&txn)
+        .await
+        .map_err(|e| format!("删除游戏失败: {}", e))?;
+    txn.commit()
+        .await
+        .map_err(|e| format!("提交事务失败: {}", e))?;
+    cover_state.mark_game_deleted(game_id as u32).await;
+
+    let mut errors = Vec::new();
+    for item in rest {
+        match remove_item(item, use_trash).await {
+            Ok(outcome) => {
+                freed_bytes += item.size;
+                trashed |= outcome == DeleteOutcome::Trashed;
+            }
+            Err(e) => {
+                log::warn!("{}", e);
+                errors.push(e);
+            }
+        }
+    }
+
+    log::info!(
+        "游戏文件删除完成 game_id={} freed_bytes={} trashed={} errors={}",
+        game_id,
+        freed_bytes,
+        trashed,
+        errors.len()
+    );
+
+    Ok(GameRemovalResult {
+        manifest,
+        freed_bytes,
+        trashed,
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn refuses_to_remove_shared_or_protected_dirs() {
+        let root =
+            std::env::temp_dir().join(format!("reina_uninstall_test_{}", std::process::id()));
+        let library = root.join("Games");
+        let game = library.join("Game A");
+        let other = library.join("Game B");
+        let data_dir = root.join("ReinaManager");
+        let protected = vec![library.clone(), data_dir.clone()];
+        let others = vec![other.clone()];
+
+        assert!(check_removable(&game, &protected, &others).is_ok());
+        assert!(check_removable(&game.join("data"), &protected, &others).is_ok());
+        // 游戏库目录本身与包含数据目录的目录
+        assert!(check_removable(&library, &protected, &others).is_err());
+        assert!(check_removable(&root, &protected, &others).is_err());
+        // 与其他游戏的目录互相包含
+        assert!(check_removable(&other, &[], &others).is_err());
+        assert!(check_removable(&other.join("DLC"), &[], &others).is_err());
+        assert!(check_removable(Path::new("/"), &[], &[]).is_err());
+        // 系统目录、软件安装目录本身与其中的目录不能删除，软件安装目录下的游戏目录除外
+        for dir in system_dirs().iter().chain(&install_dirs()) {
+            assert!(check_removable(&normalize(dir), &[], &[]).is_err());
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            assert!(check_removable(Path::new("/usr/bin"), &[], &[]).is_err());
+            let opt = normalize(Path::new("/opt"));
+            assert!(check_removable(&opt.join("Game A"), &[], &[]).is_ok());
+            assert!(check_removable(&opt.join("Game A").join("bin"), &[], &[]).is_err());
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let windir = PathBuf::from(std::env::var_os("WINDIR").unwrap());
+            assert!(check_removable(&normalize(&windir.join("System32")), &[], &[]).is_err());
+            let program_files =
+                normalize(&PathBuf::from(std::env::var_os("ProgramFiles").unwrap()));
+            assert!(check_removable(&program_files.join("Game A"), &[], &[]).is_ok());
+            assert!(check_removable(&program_files.join("Game A").join("bin"), &[], &[]).is_err());
+        }
+
+        fs::create_dir_all(game.join("data")).unwrap();
+        fs::write(game.join("game.exe"), "exe").unwrap();
+        fs::write(game.join("data").join("pack.dat"), "12345").unwrap();
+        assert_eq!(
+            game_folder(&game.join("game.exe").to_string_lossy()),
+            Some(game.clone())
+        );
+        assert_eq!(dir_usage(&game), (8, 2));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+//! 游戏时间小组件窗口
+//!
+//! 一个置顶、无边框的小窗口，显示今天和本周所有游戏的累计游玩时间，方便对照每日 / 每周目标。
+//! 数据库中的每日统计只在会话结束时写入，正在运行的会话由监控的活跃会话快照补上，
+//! 窗口存在期间后台任务定时向它推送 `playtime-widget-update` 事件。
+
+use crate::database::db::DbState;
+use crate::database::repository::game_stats_repository::GameStatsRepository;
+use crate::game::monitor::active_sessions;
+use crate::utils::metrics::CommandTimer;
+use chrono::{Datelike, Duration as ChronoDuration, Local};
+use log::{info, warn};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+/// 小组件窗口标签
+pub const WIDGET_WINDOW_LABEL: &str = "playtime-widget";
+
+/// 小组件数据推送事件
+const WIDGET_UPDATE_EVENT: &str = "playtime-widget-update";
+
+/// 数据推送间隔
+const WIDGET_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 小组件显示的数据
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaytimeWidgetData {
+    /// 今天所有游戏的游戏时间（分钟），含正在运行的会话
+    pub today_minutes: i32,
+    /// 本周（周一起）所有游戏的游戏时间（分钟），含正在运行的会话
+    pub week_minutes: i32,
+    /// 正在运行的游戏数量
+    pub active_games: usize,
+}
+
+/// 汇总数据库中的每日统计与正在运行的会话
+async fn collect_widget_data(db: &DatabaseConnection) -> Result<PlaytimeWidgetData, String> {
+    let today = Local::now().date_naive();
+    let week_start = today - ChronoDuration::days(today.weekday().num_days_from_monday() as i64);
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let week_start_str = week_start.format("%Y-%m-%d").to_string();
+
+    let today_minutes = GameStatsRepository::get_total_playtime_on(db, &today_str)
+        .await
+        .map_err(|e| format!("获取今天游戏时间失败: {}", e))?;
+    let week_minutes =
+        GameStatsRepository::get_total_playtime_between(db, &week_start_str, &today_str)
+            .await
+            .map_err(|e| format!("获取本周游戏时间失败: {}", e))?;
+
+    let sessions = active_sessions();
+    let live_minutes = (sessions
+        .iter()
+        .map(|session| session.total_seconds)
+        .sum::<u64>()
+        / 60) as i32;
+
+    Ok(PlaytimeWidgetData {
+        today_minutes: today_minutes + live_minutes,
+        week_minutes: week_minutes + live_minutes,
+        active_games: sessions.len(),
+    })
+}
+
+/// 窗口存在期间定时推送数据，窗口关闭后自动退出
+fn spawn_update_loop<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(WIDGET_UPDATE_INTERVAL).await;
+            if app.get_webview_window(WIDGET_WINDOW_LABEL).is_none() {
+                break;
+            }
+            let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+                break;
+            };
+            match collect_widget_data(&db).await {
+                Ok(data) => {
+                    if let Err(e) = app.emit_to(WIDGET_WINDOW_LABEL, WIDGET_UPDATE_EVENT, data) {
+                        warn!("无法发送 {} 事件: {}", WIDGET_UPDATE_EVENT, e);
+                    }
+                }
+                Err(e) => warn!("{}", e),
+            }
+        }
+        info!("游戏时间小组件已关闭，停止推送");
+    });
+}
+
+/// 打开游戏时间小组件窗口，已打开时将其显示到前台
+#[tauri::command]
+pub async fn open_playtime_widget(app: AppHandle) -> Result<(), String> {
+    let _timer = CommandTimer::start("open_playtime_widget");
+    if let Some(window) = app.get_webview_window(WIDGET_WINDOW_LABEL) {
+        window
+            .show()
+            .and_then(|_| window.set_focus())
+            .map_err(|e| format!("显示小组件窗口失败: {}", e))?;
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(&app, WIDGET_WINDOW_LABEL, WebviewUrl::App("widget".into()))
+        .title("ReinaManager")
+        .inner_size(220.0, 96.0)
+        .resizable(false)
+        .maximizable(false)
+        .minimizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .build()
+        .map_err(|e| format!("创建小组件窗口失败: {}", e))?;
+
+    spawn_update_loop(app);
+    Ok(())
+}
+
+/// 关闭游戏时间小组件窗口，未打开时不做任何操作
+#[tauri::command]
+pub async fn close_playtime_widget(app: AppHandle) -> Result<(), String> {
+    let _timer = CommandTimer::start("close_playtime_widget");
+    if let Some(window) = app.get_webview_window(WIDGET_WINDOW_LABEL) {
+        window
+            .destroy()
+            .map_err(|e| format!("关闭小组件窗口失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 获取小组件当前数据，供窗口加载完成时立即显示
+#[tauri::command]
+pub async fn get_playtime_widget_data(
+    db: tauri::State<'_, DbState>,
+) -> Result<PlaytimeWidgetData, String> {
+    let _timer = CommandTimer::start("get_playtime_widget_data");
+    let db = db.get();
+    collect_widget_data(&db).await
+}
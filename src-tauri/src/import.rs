@@ -0,0 +1,2 @@
+pub mod external;
+pub mod playtime;
@@ -0,0 +1,367 @@
+//! 外部游戏库导入
+//!
+//! 解析 Playnite（Library Exporter 扩展导出的 JSON）与 Whitecloud 导出文件，
+//! 映射为 `InsertGameData`（元数据写入 `custom_data`），按 BGM/VNDB ID 去重后
+//! 通过 `GamesRepository::insert_batch` 在单个事务内批量插入。
+
+use crate::database::db::DbState;
+use crate::database::dto::{BatchOperationResult, InsertGameData};
+use crate::database::repository::games_repository::GamesRepository;
+use crate::entity::custom_data::CustomData;
+use crate::utils::metrics::CommandTimer;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::State;
+
+/// 支持的外部库格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalLibraryFormat {
+    Playnite,
+    Whitecloud,
+}
+
+impl ExternalLibraryFormat {
+    /// 导入后写入 games.id_type 的值，与前端 IdType 保持一致
+    fn id_type(self) -> &'static str {
+        match self {
+            Self::Playnite => "custom",
+            Self::Whitecloud => "Whitecloud",
+        }
+    }
+}
+
+/// 外部库导入结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExternalImportResult {
+    /// 文件中解析到的条目数
+    pub total_entries: usize,
+    /// 因 BGM/VNDB ID 重复而跳过的条目名称
+    pub skipped_duplicates: Vec<String>,
+    /// 因缺少名称等必要信息而无法解析的条目数
+    pub invalid_entries: usize,
+    /// 批量插入结果
+    pub batch: BatchOperationResult,
+}
+
+// ==================== JSON 字段提取 ====================
+
+/// 按候选键名（不区分大小写）取字段
+fn field<'a>(entry: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    let object = entry.as_object()?;
+    keys.iter().find_map(|key| {
+        object
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    })
+}
+
+fn string_field(entry: &Value, keys: &[&str]) -> Option<String> {
+    field(entry, keys)
+        .and_then(|value| match value {
+            Value::String(text) => Some(text.trim().to_string()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        })
+        .filter(|text| !text.is_empty())
+}
+
+/// 提取字符串数组，兼容 `["a"]` 与 Playnite 的 `[{"Name": "a"}]` 两种写法
+fn string_list_field(entry: &Value, keys: &[&str]) -> Option<Vec<String>> {
+    let items = match field(entry, keys)? {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(text) => Some(text.trim().to_string()),
+                Value::Object(_) => string_field(item, &["name"]),
+                _ => None,
+            })
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>(),
+        Value::String(text) => text
+            .split([',', '，'])
+            .map(|part| part.trim().to_string())
+            .filter(|part| !part.is_empty())
+            .collect(),
+        _ => return None,
+    };
+
+    (!items.is_empty()).then_some(items)
+}
+
+/// 从链接中解析 BGM / VNDB ID
+fn parse_source_ids(urls: &[String]) -> (Option<String>, Option<String>) {
+    let mut bgm_id = None;
+    let mut vndb_id = None;
+
+    for url in urls {
+        let lower = url.to_lowercase();
+        if bgm_id.is_none()
+            && (lower.contains("bgm.tv/subject/") || lower.contains("bangumi.tv/subject/"))
+        {
+            bgm_id = lower
+                .split("/subject/")
+                .nth(1)
+                .map(|rest| rest.chars().take_while(char::is_ascii_digit).collect())
+                .filter(|id: &String| !id.is_empty());
+        }
+        if vndb_id.is_none() && lower.contains("vndb.org/v") {
+            vndb_id = lower
+                .split("vndb.org/")
+                .nth(1)
+                .map(|rest| {
+                    rest.chars()
+                        .take_while(|c| c.is_ascii_alphanumeric())
+                        .collect()
+                })
+                .filter(|id: &String| id.len() > 1 && id.starts_with('v'));
+        }
+    }
+
+    (bgm_id, vndb_id)
+}
+
+/// 导出文件可能是数组，也可能是 `{ "games": [...] }` 包装
+fn extract_entries(root: Value) -> Result<Vec<Value>, String> {
+    match root {
+        Value::Array(items) => Ok(items),
+        Value::Object(mut object) => {
+            let key = object
+                .keys()
+                .find(|key| {
+                    ["games", "items", "library", "data"]
+                        .iter()
+                        .any(|name| key.eq_ignore_ascii_case(name))
+                })
+                .cloned()
+                .ok_or_else(|| "导入文件中未找到游戏列表".to_string())?;
+            match object.remove(&key) {
+                Some(Value::Array(items)) => Ok(items),
+                _ => Err("导入文件中的游戏列表格式错误".to_string()),
+            }
+        }
+        _ => Err("导入文件格式错误：根节点必须是数组或对象".to_string()),
+    }
+}
+
+// ==================== 格式映射 ====================
+
+/// Playnite 的启动动作路径可能包含 `{InstallDir}` 占位符
+fn resolve_playnite_exe(entry: &Value) -> Option<String> {
+    let install_dir = string_field(entry, &["InstallDirectory"]);
+    let actions = field(entry, &["GameActions", "PlayAction"])?;
+    let action = match actions {
+        Value::Array(items) => items
+            .iter()
+            .find(|action| {
+                field(action, &["IsPlayAction"])
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false)
+            })
+            .or_else(|| items.first())?,
+        other => other,
+    };
+
+    let path = string_field(action, &["Path"])?;
+    let path = match &install_dir {
+        Some(dir) => path.replace("{InstallDir}", dir),
+        None => path,
+    };
+
+    if path.contains('{') || path.contains("://") {
+        return None;
+    }
+
+    let mut resolved = Path::new(&path).to_path_buf();
+    if resolved.is_relative()
+        && let Some(dir) = &install_dir
+    {
+        resolved = Path::new(dir).join(resolved);
+    }
+    Some(resolved.to_string_lossy().to_string())
+}
+
+fn map_playnite_entry(entry: &Value) -> Option<InsertGameData> {
+    let name = string_field(entry, &["Name"])?;
+    let links: Vec<String> = match field(entry, &["Links"]) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|link| string_field(link, &["Url"]))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let (bgm_id, vndb_id) = parse_source_ids(&links);
+
+    let custom_data = CustomData {
+        image: string_field(entry, &["CoverImage"]).filter(|cover| cover.contains("://")),
+        name: Some(name),
+        summary: string_field(entry, &["Description", "Notes"]),
+        tags: string_list_field(entry, &["Tags", "Genres"]),
+        developer: string_list_field(entry, &["Developers"]).map(|list| list.join(", ")),
+        ..Default::default()
+    };
+
+    Some(build_insert_data(
+        ExternalLibraryFormat::Playnite,
+        bgm_id,
+        vndb_id,
+        string_field(entry, &["ReleaseDate"]),
+        resolve_playnite_exe(entry),
+        custom_data,
+    ))
+}
+
+fn map_whitecloud_entry(entry: &Value) -> Option<InsertGameData> {
+    let name = string_field(entry, &["name", "title", "game_name"])?;
+    let links: Vec<String> = string_list_field(entry, &["links", "urls"]).unwrap_or_default();
+    let (link_bgm_id, link_vndb_id) = parse_source_ids(&links);
+
+    let custom_data = CustomData {
+        image: string_field(entry, &["image", "cover", "cover_url"]),
+        name: Some(name),
+        aliases: string_list_field(entry, &["aliases", "alias"]),
+        summary: string_field(entry, &["summary", "brief", "description", "intro"]),
+        tags: string_list_field(entry, &["tags", "tag"]),
+        developer: string_field(entry, &["developer", "company", "brand"]),
+        ..Default::default()
+    };
+
+    Some(build_insert_data(
+        ExternalLibraryFormat::Whitecloud,
+        string_field(entry, &["bgm_id", "bgmId", "bangumi_id"]).or(link_bgm_id),
+        string_field(entry, &["vndb_id", "vndbId"]).or(link_vndb_id),
+        string_field(entry, &["date", "release_date", "releaseDate"]),
+        string_field(entry, &["localpath", "exe_path", "exePath", "path"]),
+        custom_data,
+    ))
+}
+
+fn build_insert_data(
+    format: ExternalLibraryFormat,
+    bgm_id: Option<String>,
+    vndb_id: Option<String>,
+    date: Option<String>,
+    localpath: Option<String>,
+    custom_data: CustomData,
+) -> InsertGameData {
+    InsertGameData {
+        bgm_id,
+        vndb_id,
+        ymgal_id: None,
+        kun_id: None,
+        id_type: format.id_type().to_string(),
+        date,
+        localpath,
+        savepath: None,
+        autosave: None,
+        maxbackups: None,
+        clear: None,
+        le_launch: None,
+        magpie: None,
+        vndb_data: None,
+        bgm_data: None,
+        ymgal_data: None,
+        kun_data: None,
+        custom_data: Some(custom_data),
+    }
+}
+
+/// 从外部游戏库导出文件导入游戏
+///
+/// # Arguments
+/// * `path` - 导出文件路径（JSON）
+/// * `format` - 文件格式：`playnite` 或 `whitecloud`
+///
+/// # Returns
+/// * `Result<ExternalImportResult, String>` - 导入结果或错误消息
+#[tauri::command]
+pub async fn import_external_library(
+    db: State<'_, DbState>,
+    path: String,
+    format: ExternalLibraryFormat,
+) -> Result<ExternalImportResult, String> {
+    let _timer = CommandTimer::start("import_external_library");
+    let db = db.get();
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let root: Value = serde_json::from_str(content.trim_start_matches('\u{feff}'))
+        .map_err(|e| format!("解析导入文件失败: {}", e))?;
+    let entries = extract_entries(root)?;
+    let total_entries = entries.len();
+
+    let mut known_bgm_ids: HashSet<String> = GamesRepository::get_all_bgm_ids(&db)
+        .await
+        .map_err(|e| format!("获取 BGM ID 列表失败: {}", e))?
+        .into_iter()
+        .map(|(_, id)| id)
+        .collect();
+    let mut known_vndb_ids: HashSet<String> = GamesRepository::get_all_vndb_ids(&db)
+        .await
+        .map_err(|e| format!("获取 VNDB ID 列表失败: {}", e))?
+        .into_iter()
+        .map(|(_, id)| id)
+        .collect();
+
+    let mut games = Vec::with_capacity(total_entries);
+    let mut skipped_duplicates = Vec::new();
+    let mut invalid_entries = 0;
+
+    for entry in &entries {
+        let mapped = match format {
+            ExternalLibraryFormat::Playnite => map_playnite_entry(entry),
+            ExternalLibraryFormat::Whitecloud => map_whitecloud_entry(entry),
+        };
+        let Some(game) = mapped else {
+            invalid_entries += 1;
+            continue;
+        };
+
+        // 同时与数据库及本次文件中已接受的条目去重
+        let duplicate = game
+            .bgm_id
+            .as_ref()
+            .is_some_and(|id| known_bgm_ids.contains(id))
+            || game
+                .vndb_id
+                .as_ref()
+                .is_some_and(|id| known_vndb_ids.contains(id));
+        if duplicate {
+            skipped_duplicates.push(
+                game.custom_data
+                    .as_ref()
+                    .and_then(|data| data.name.clone())
+                    .unwrap_or_default(),
+            );
+            continue;
+        }
+
+        if let Some(id) = &game.bgm_id {
+            known_bgm_ids.insert(id.clone());
+        }
+        if let Some(id) = &game.vndb_id {
+            known_vndb_ids.insert(id.clone());
+        }
+        games.push(game);
+    }
+
+    let batch = GamesRepository::insert_batch(&db, games).await;
+
+    log::info!(
+        "外部游戏库导入完成 format={:?} total={} inserted={} duplicates={} invalid={} failed={}",
+        format,
+        total_entries,
+        batch.success,
+        skipped_duplicates.len(),
+        invalid_entries,
+        batch.failed
+    );
+
+    Ok(ExternalImportResult {
+        total_entries,
+        skipped_duplicates,
+        invalid_entries,
+        batch,
+    })
+}
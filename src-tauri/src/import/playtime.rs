@@ -0,0 +1,411 @@
+//! 外部游玩时长导入
+//!
+//! 解析 ManicTime（按时间段导出的 CSV）与 Playnite（库导出的 CSV，仅含累计时长）记录，
+//! 按名称匹配库中游戏后写入 `game_sessions`，并在同一事务内重新计算 `game_statistics`。
+
+use crate::database::db::DbState;
+use crate::database::repository::game_stats_repository::GameStatsRepository;
+use crate::database::repository::games_repository::{
+    GameType, GamesRepository, SortOption, SortOrder,
+};
+use crate::entity::games;
+use crate::game::auto_clear::check_playtime_rule;
+use crate::utils::metrics::CommandTimer;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use sea_orm::TransactionTrait;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+/// 支持的游玩记录格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaytimeImportFormat {
+    /// 每行一个时间段：Name, Start, End[, Duration]
+    Manictime,
+    /// 每行一个游戏：Name, Playtime（秒）, LastActivity
+    Playnite,
+}
+
+/// 游玩记录导入结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaytimeImportResult {
+    /// 文件中的记录行数
+    pub total_records: usize,
+    /// 写入的会话数
+    pub imported_sessions: usize,
+    /// 与已有会话开始时间相同而跳过的记录数
+    pub skipped_duplicates: usize,
+    /// 时间或时长无法解析的记录数
+    pub invalid_records: usize,
+    /// 未能匹配到库中游戏的名称
+    pub unmatched_names: Vec<String>,
+    /// 重新计算了统计的游戏 ID
+    pub affected_games: Vec<i32>,
+}
+
+/// 解析后的单条游玩记录（时间为秒级时间戳）
+struct PlayRecord {
+    name: String,
+    start_time: i64,
+    end_time: i64,
+    duration_seconds: i64,
+}
+
+// ==================== CSV 解析 ====================
+
+/// 根据表头猜测分隔符（ManicTime 在部分区域设置下导出分号分隔）
+fn detect_delimiter(header: &str) -> char {
+    [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|delimiter| header.matches(*delimiter).count())
+        .unwrap_or(',')
+}
+
+/// 解析 CSV 文本，支持引号包裹字段与转义的双引号
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let content = content.trim_start_matches('\u{feff}');
+    let delimiter = detect_delimiter(content.lines().next().unwrap_or_default());
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+/// 按候选表头（不区分大小写）定位列
+fn column_index(header: &[String], names: &[&str]) -> Option<usize> {
+    names.iter().find_map(|name| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    })
+}
+
+fn cell(row: &[String], index: Option<usize>) -> Option<&str> {
+    index
+        .and_then(|i| row.get(i))
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+/// 解析本地时间字符串，兼容常见的导出格式
+fn parse_datetime(value: &str) -> Option<i64> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.timestamp());
+    }
+
+    const FORMATS: &[&str] = &[
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y/%m/%d %H:%M",
+        "%m/%d/%Y %I:%M:%S %p",
+        "%m/%d/%Y %H:%M:%S",
+        "%d.%m.%Y %H:%M:%S",
+    ];
+    FORMATS.iter().find_map(|format| {
+        NaiveDateTime::parse_from_str(value, format)
+            .ok()
+            .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+            .map(|dt| dt.timestamp())
+    })
+}
+
+/// 解析时长：`hh:mm:ss`、`mm:ss` 或秒数
+fn parse_duration_seconds(value: &str) -> Option<i64> {
+    if value.contains(':') {
+        let parts: Vec<i64> = value
+            .split(':')
+            .map(|part| part.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        return match parts.as_slice() {
+            [h, m, s] => Some(h * 3600 + m * 60 + s),
+            [m, s] => Some(m * 60 + s),
+            _ => None,
+        };
+    }
+    value.parse::<f64>().ok().map(|seconds| seconds as i64)
+}
+
+fn parse_manictime(rows: &[Vec<String>]) -> (Vec<PlayRecord>, usize) {
+    let Some((header, body)) = rows.split_first() else {
+        return (Vec::new(), 0);
+    };
+    let name_col = column_index(header, &["Name", "Application", "Title"]);
+    let start_col = column_index(header, &["Start", "StartTime", "Start time"]);
+    let end_col = column_index(header, &["End", "EndTime", "End time"]);
+    let duration_col = column_index(header, &["Duration"]);
+
+    let parse_row = |row: &[String]| {
+        let name = cell(row, name_col)?.to_string();
+        let start_time = parse_datetime(cell(row, start_col)?)?;
+        let end_time = cell(row, end_col).and_then(parse_datetime);
+        let duration = cell(row, duration_col).and_then(parse_duration_seconds);
+        let (end_time, duration_seconds) = match (end_time, duration) {
+            (Some(end), Some(duration)) => (end, duration),
+            (Some(end), None) => (end, end - start_time),
+            (None, Some(duration)) => (start_time + duration, duration),
+            (None, None) => return None,
+        };
+        (duration_seconds > 0 && end_time > start_time).then_some(PlayRecord {
+            name,
+            start_time,
+            end_time,
+            duration_seconds,
+        })
+    };
+
+    let mut records = Vec::with_capacity(body.len());
+    let mut invalid = 0;
+    for row in body {
+        match parse_row(row) {
+            Some(record) => records.push(record),
+            None => invalid += 1,
+        }
+    }
+    (records, invalid)
+}
+
+/// Playnite 只导出累计时长，导入为一条以最后游玩时间结束的汇总会话
+fn parse_playnite(rows: &[Vec<String>]) -> (Vec<PlayRecord>, usize) {
+    let Some((header, body)) = rows.split_first() else {
+        return (Vec::new(), 0);
+    };
+    let name_col = column_index(header, &["Name"]);
+    let playtime_col = column_index(header, &["Playtime", "Play time", "TimePlayed"]);
+    let last_col = column_index(header, &["LastActivity", "Last Played", "LastPlayed"]);
+    let now = Local::now().timestamp();
+
+    let mut records = Vec::with_capacity(body.len());
+    let mut invalid = 0;
+    for row in body {
+        let name = cell(row, name_col);
+        let duration_seconds = cell(row, playtime_col).and_then(parse_duration_seconds);
+        match (name, duration_seconds) {
+            // 从未游玩的游戏时长为 0，不计为无效记录
+            (Some(_), Some(0)) => {}
+            (Some(name), Some(duration_seconds)) if duration_seconds > 0 => {
+                let end_time = cell(row, last_col).and_then(parse_datetime).unwrap_or(now);
+                records.push(PlayRecord {
+                    name: name.to_string(),
+                    start_time: end_time - duration_seconds,
+                    end_time,
+                    duration_seconds,
+                });
+            }
+            _ => invalid += 1,
+        }
+    }
+    (records, invalid)
+}
+
+// ==================== 游戏匹配 ====================
+
+fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// 建立 名称 → 游戏 ID 的索引：显示名称（中 / 原文）与启动程序文件名均可匹配
+fn build_name_index(games: &[games::Model]) -> HashMap<String, i32> {
+    let mut index = HashMap::new();
+    for game in games {
+        let names = [
+            GamesRepository::get_display_name(game, true),
+            GamesRepository::get_display_name(game, false),
+        ];
+        for name in names.into_iter().flatten() {
+            index.entry(normalize_name(name)).or_insert(game.id);
+        }
+
+        if let Some(stem) = game
+            .localpath
+            .as_deref()
+            .and_then(|path| Path::new(path).file_stem())
+            .and_then(|stem| stem.to_str())
+        {
+            index.entry(normalize_name(stem)).or_insert(game.id);
+        }
+    }
+    index
+}
+
+/// 时长换算为分钟，与监控器一致：不足一分钟的余数满 30 秒进一
+fn to_minutes(seconds: i64) -> i32 {
+    let minutes = seconds / 60;
+    let minutes = if seconds % 60 >= 30 {
+        minutes + 1
+    } else {
+        minutes
+    };
+    minutes as i32
+}
+
+/// 从外部时长记录工具导入游玩历史
+///
+/// # Arguments
+/// * `path` - 导出文件路径（CSV）
+/// * `format` - 文件格式：`manictime` 或 `playnite`
+///
+/// # Returns
+/// * `Result<PlaytimeImportResult, String>` - 导入结果或错误消息
+#[tauri::command]
+pub async fn import_playtime(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    path: String,
+    format: PlaytimeImportFormat,
+) -> Result<PlaytimeImportResult, String> {
+    let _timer = CommandTimer::start("import_playtime");
+    let db = db.get();
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let rows = parse_csv(&String::from_utf8_lossy(&bytes));
+    let total_records = rows.len().saturating_sub(1);
+
+    let (records, mut invalid_records) = match format {
+        PlaytimeImportFormat::Manictime => parse_manictime(&rows),
+        PlaytimeImportFormat::Playnite => parse_playnite(&rows),
+    };
+
+    let games = GamesRepository::find_all(
+        &db,
+        GameType::All,
+        None,
+        SortOption::Addtime,
+        SortOrder::Asc,
+        None,
+    )
+    .await
+    .map_err(|e| format!("获取游戏列表失败: {}", e))?;
+    let name_index = build_name_index(&games);
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+
+    let mut known_starts: HashMap<i32, HashSet<i32>> = HashMap::new();
+    let mut unmatched_names = BTreeSet::new();
+    let mut affected_games = BTreeSet::new();
+    let mut imported_sessions = 0;
+    let mut skipped_duplicates = 0;
+
+    for record in records {
+        let Some(&game_id) = name_index.get(&normalize_name(&record.name)) else {
+            unmatched_names.insert(record.name);
+            continue;
+        };
+
+        let duration = to_minutes(record.duration_seconds);
+        let (Ok(start_time), Ok(end_time)) = (
+            i32::try_from(record.start_time),
+            i32::try_from(record.end_time),
+        ) else {
+            invalid_records += 1;
+            continue;
+        };
+        if duration <= 0 {
+            invalid_records += 1;
+            continue;
+        }
+
+        if let Entry::Vacant(entry) = known_starts.entry(game_id) {
+            let starts = GameStatsRepository::get_session_start_times(&txn, game_id)
+                .await
+                .map_err(|e| format!("获取已有会话失败: {}", e))?;
+            entry.insert(starts.into_iter().collect());
+        }
+        if !known_starts
+            .get_mut(&game_id)
+            .is_some_and(|starts| starts.insert(start_time))
+        {
+            skipped_duplicates += 1;
+            continue;
+        }
+
+        let date = Local
+            .timestamp_opt(record.start_time, 0)
+            .single()
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        GameStatsRepository::record_session(
+            &txn, game_id, start_time, end_time, duration, date, None,
+        )
+        .await
+        .map_err(|e| format!("写入游戏会话失败: {}", e))?;
+
+        imported_sessions += 1;
+        affected_games.insert(game_id);
+    }
+
+    let mut total_changes = Vec::with_capacity(affected_games.len());
+    for &game_id in &affected_games {
+        let previous_total = GameStatsRepository::get_statistics(&txn, game_id)
+            .await
+            .map_err(|e| format!("获取游戏统计失败: {}", e))?
+            .and_then(|stats| stats.total_time)
+            .unwrap_or(0);
+        let current_total = GameStatsRepository::recompute_statistics(&txn, game_id)
+            .await
+            .map_err(|e| format!("重新计算游戏统计失败: {}", e))?;
+        total_changes.push((game_id, previous_total, current_total));
+    }
+
+    txn.commit()
+        .await
+        .map_err(|e| format!("提交事务失败: {}", e))?;
+
+    for (game_id, previous_total, current_total) in total_changes {
+        if let Err(e) = check_playtime_rule(&app, &db, game_id, previous_total, current_total).await
+        {
+            log::warn!("检查自动标记规则失败: {}", e);
+        }
+    }
+
+    log::info!(
+        "游玩记录导入完成 format={:?} records={} imported={} duplicates={} unmatched={} invalid={}",
+        format,
+        total_records,
+        imported_sessions,
+        skipped_duplicates,
+        unmatched_names.len(),
+        invalid_records
+    );
+
+    Ok(PlaytimeImportResult {
+        total_records,
+        imported_sessions,
+        skipped_duplicates,
+        invalid_records,
+        unmatched_names: unmatched_names.into_iter().collect(),
+        affected_games: affected_games.into_iter().collect(),
+    })
+}
@@ -1,49 +1,115 @@
 mod backup;
+mod cli;
 mod database;
 mod entity;
 mod game;
+mod import;
+mod setup;
 mod utils;
 
 use backup::covers::backup_custom_covers;
 use backup::database::{backup_database, import_database};
+use backup::integrity::rehash_existing_backups;
+use backup::library::{export_library, import_library};
+use backup::quota::get_savedata_usage;
+use backup::restore_preview::list_savedata_backup_contents;
 use backup::savedata::{
-    create_savedata_backup, delete_savedata_backup, move_backup_folder, restore_savedata_backup,
+    create_savedata_backup, delete_savedata_backup, is_backup_encrypted, move_backup_folder,
+    restore_savedata_backup,
 };
+use backup::schedule::get_backup_settings;
+use backup::self_test::self_test_backup_pipeline;
+use backup::settings::{export_settings, import_settings};
+use backup::statistics_csv::export_statistics_csv;
+use database::integrity::{fix_integrity_issues, run_integrity_check};
 use database::*;
+use game::auto_clear::report_external_play_status;
+use game::cover::cache::{cache_cover, purge_unused_covers};
 use game::cover::custom::{delete_game_covers, import_clipboard_image_to_temp};
+use game::cover::icon::extract_exe_icon;
+use game::cover::repair::{audit_cover_urls, repair_covers};
+use game::cover::thumbnails::generate_thumbnails;
 use game::cover::{delete_cloud_cache, register_game_cover_protocol};
-use game::launch::{launch_game, stop_game};
-use game::scan::scan_directory_for_games;
-use migration::MigratorTrait;
+use game::cross_ids::{apply_cross_ids, resolve_cross_ids};
+use game::desktop_shortcut::create_desktop_shortcut;
+use game::launch::{launch_game, list_available_runners, stop_game};
+use game::monitor::{get_active_sessions, get_monitor_health};
+use game::save_path::detect_save_path;
+use game::scan::{
+    cancel_library_scan, scan_directory_for_games, start_library_scan, validate_game_paths,
+};
+use game::screenshot::{
+    capture_game_screenshot, delete_screenshot, get_screenshots, import_engine_screenshots,
+    import_screenshots_from_folder, register_screenshot_hotkey,
+};
+use game::session_hotkeys::{register_session_hotkeys, toggle_session_pause};
+use game::steam::export_steam_shortcuts;
+use game::uninstall::{preview_game_removal, remove_game_files};
+use game::widget::{close_playtime_widget, get_playtime_widget_data, open_playtime_widget};
+use import::external::import_external_library;
+use import::playtime::import_playtime;
+use setup::{complete_setup_step, get_setup_state};
 use tauri::Manager;
 use tauri_plugin_log::{RotationStrategy, Target, TargetKind, TimezoneStrategy};
 use utils::{
     bgm_auth::{bgm_oauth_exchange_code, bgm_oauth_refresh_token, bgm_oauth_start_login},
+    chart::{render_chart, render_chart_png},
+    crash::{delete_crash_report, list_crash_reports},
+    file_lock::get_file_lock_diagnostics,
     fs::{copy_file, delete_file, is_portable_mode, open_directory},
     http::update_proxy_config,
+    http_api::{apply_http_api_settings, get_http_api_status},
     image::register_image_proxy_protocol,
     legacy_migration::run_startup_migrations,
-    logs::{get_reina_log_level, set_reina_log_level},
+    logs::{
+        clear_old_logs, get_recent_logs, get_reina_log_level, open_log_directory,
+        set_reina_log_level,
+    },
+    metrics::{get_performance_metrics, reset_performance_metrics},
+    notification::send_notification,
+    portable::{get_data_root, preview_portable_migration, set_data_root},
+    tray::set_tray_labels,
+    usage_stats::{clear_usage_stats, get_usage_summary},
 };
 
-const LOG_MAX_FILE_SIZE: u128 = 1_000_000;
-const LOG_KEEP_FILE_COUNT: usize = 5;
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 发生 panic 时写入崩溃报告，下次启动时由前端展示
+    utils::crash::install_panic_hook();
+
+    // 命令行操作在创建窗口前处理，无界面的操作执行完直接退出
+    let cli_command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => std::process::exit(cli::report_usage_error(&e)),
+    };
+    if let Some(command) = cli_command.clone().filter(cli::CliCommand::is_headless) {
+        std::process::exit(cli::run_headless(command));
+    }
+    let launch_on_start = match cli_command {
+        Some(cli::CliCommand::Launch(game_id)) => Some(game_id),
+        _ => None,
+    };
+
     register_image_proxy_protocol(register_game_cover_protocol(
         tauri::Builder::default().plugin(tauri_plugin_os::init()),
     ))
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_window_state::Builder::new().build())
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-                let _ = window.unminimize();
-                let _ = window.set_focus();
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            // 已在运行时再次以 --launch 调用，由当前实例启动游戏
+            if let Ok(Some(cli::CliCommand::Launch(game_id))) =
+                cli::parse_args(args.into_iter().skip(1))
+            {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    utils::deep_link::launch_and_report(&app, game_id).await;
+                });
+                return;
             }
+            utils::tray::show_main_window(app);
         }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             Some(vec!["--flag1", "--flag2"]), /* arbitrary number of args to pass to your app */
@@ -53,25 +119,63 @@ pub fn run() {
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .invoke_handler(tauri::generate_handler![
             // 工具类 commands
             launch_game,
             stop_game,
+            list_available_runners,
+            get_monitor_health,
+            get_active_sessions,
             open_directory,
             is_portable_mode,
+            preview_portable_migration,
+            get_data_root,
+            set_data_root,
+            set_tray_labels,
+            apply_http_api_settings,
+            get_http_api_status,
             scan_directory_for_games,
+            start_library_scan,
+            cancel_library_scan,
+            validate_game_paths,
+            detect_save_path,
             move_backup_folder,
             copy_file,
             create_savedata_backup,
             delete_savedata_backup,
             restore_savedata_backup,
+            rehash_existing_backups,
+            run_integrity_check,
+            fix_integrity_issues,
+            list_savedata_backup_contents,
+            is_backup_encrypted,
+            get_savedata_usage,
+            get_backup_settings,
+            self_test_backup_pipeline,
             delete_file,
             import_clipboard_image_to_temp,
             delete_game_covers,
             delete_cloud_cache,
+            audit_cover_urls,
+            repair_covers,
+            cache_cover,
+            purge_unused_covers,
+            generate_thumbnails,
+            extract_exe_icon,
             backup_database,
             backup_custom_covers,
             import_database,
+            export_library,
+            import_library,
+            export_settings,
+            import_settings,
+            export_statistics_csv,
+            export_steam_shortcuts,
+            preview_game_removal,
+            remove_game_files,
+            create_desktop_shortcut,
             // 游戏数据相关 commands
             insert_game,
             insert_games_batch,
@@ -79,6 +183,8 @@ pub fn run() {
             find_all_games,
             find_game_ids,
             update_game,
+            set_play_status,
+            count_by_status,
             delete_game,
             delete_games_batch,
             count_games,
@@ -87,14 +193,29 @@ pub fn run() {
             get_all_bgm_ids,
             get_all_vndb_ids,
             update_games_batch,
+            preview_metadata_updates,
+            apply_metadata_diff,
+            resolve_cross_ids,
+            apply_cross_ids,
+            import_external_library,
+            import_playtime,
+            report_external_play_status,
             // 存档备份相关 commands
             save_savedata_record,
             get_savedata_count,
             get_savedata_records,
+            // 启动记录相关 commands
+            get_launch_attempts,
             // 游戏统计相关 commands
             record_game_session,
+            add_manual_session,
+            adjust_game_total_time,
             get_game_sessions,
             get_recent_sessions_for_all,
+            tag_session,
+            update_session_note,
+            get_session_tag_summary,
+            get_play_habits,
             delete_game_session,
             update_game_statistics,
             get_game_statistics,
@@ -103,6 +224,11 @@ pub fn run() {
             get_all_game_last_played,
             delete_game_statistics,
             get_today_playtime,
+            get_library_overview,
+            get_daily_playtime_range,
+            generate_year_report,
+            render_chart,
+            render_chart_png,
             init_game_statistics,
             // 用户设置相关 commands
             get_all_settings,
@@ -115,26 +241,61 @@ pub fn run() {
             // 日志相关 commands（运行时动态调整）
             set_reina_log_level,
             get_reina_log_level,
+            get_recent_logs,
+            open_log_directory,
+            clear_old_logs,
+            list_crash_reports,
+            delete_crash_report,
+            get_usage_summary,
+            clear_usage_stats,
+            // 性能统计相关 commands
+            get_performance_metrics,
+            reset_performance_metrics,
+            // 文件占用诊断 commands
+            get_file_lock_diagnostics,
+            // 系统通知 commands
+            send_notification,
+            // 游戏时间小组件 commands
+            open_playtime_widget,
+            close_playtime_widget,
+            get_playtime_widget_data,
+            // 游戏截图 commands
+            capture_game_screenshot,
+            get_screenshots,
+            delete_screenshot,
+            import_screenshots_from_folder,
+            import_engine_screenshots,
+            register_screenshot_hotkey,
+            // 会话快捷键 commands
+            register_session_hotkeys,
+            toggle_session_pause,
             // 合集相关 commands
             create_collection,
             find_root_collections,
+            get_collection_tree,
             update_collection,
             delete_collection,
             remove_games_from_collection,
+            populate_collection_from_tags,
+            move_games_between_collections,
+            copy_games_to_collection,
             get_games_in_collection,
             get_game_collection_ids,
             add_games_to_collections,
             set_game_collections,
             update_category_games,
+            reorder_collection,
+            reorder_collections,
             batch_count_games_in_groups,
             count_games_in_group,
             get_categories_with_count,
+            // 启动数据
+            get_initial_app_state,
+            // 首次启动向导
+            get_setup_state,
+            complete_setup_step,
         ])
-        .setup(|app| {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-            }
-
+        .setup(move |app| {
             // 仅在调试模式下自动打开开发者工具
             #[cfg(debug_assertions)]
             {
@@ -143,6 +304,8 @@ pub fn run() {
                 window.open_devtools();
             }
 
+            // 日志级别与轮转设置保存在数据库中，需在初始化日志插件前读取
+            let log_settings = utils::logs::load_log_settings();
             if cfg!(debug_assertions) {
                 app.handle().plugin(
                     tauri_plugin_log::Builder::default()
@@ -152,8 +315,8 @@ pub fn run() {
                         .level_for("hyper", log::LevelFilter::Warn)
                         .level_for("hyper_util", log::LevelFilter::Warn)
                         .level_for("h2", log::LevelFilter::Warn)
-                        .max_file_size(LOG_MAX_FILE_SIZE)
-                        .rotation_strategy(RotationStrategy::KeepSome(LOG_KEEP_FILE_COUNT))
+                        .max_file_size(log_settings.max_file_size())
+                        .rotation_strategy(RotationStrategy::KeepSome(log_settings.max_files()))
                         .targets([
                             Target::new(TargetKind::LogDir {
                                 // set custom log file name for debug
@@ -173,13 +336,22 @@ pub fn run() {
                         .level_for("hyper", log::LevelFilter::Warn)
                         .level_for("hyper_util", log::LevelFilter::Warn)
                         .level_for("h2", log::LevelFilter::Warn)
-                        .max_file_size(LOG_MAX_FILE_SIZE)
-                        .rotation_strategy(RotationStrategy::KeepSome(LOG_KEEP_FILE_COUNT))
+                        .max_file_size(log_settings.max_file_size())
+                        .rotation_strategy(RotationStrategy::KeepSome(log_settings.max_files()))
                         .build(),
                 )?;
-                // 发布版默认保持 Info，但保留本会话临时升到 Debug 的能力。
-                log::set_max_level(log::LevelFilter::Info);
             }
+            if let Ok(dir) = utils::logs::log_dir(app.handle()) {
+                utils::crash::set_log_dir(dir);
+            }
+            // 使用保存的日志级别；未设置时发布版为 Info，开发版为 Debug。
+            // 插件本身按 Debug 过滤，保留运行时升到 Debug 的能力。
+            let default_level = if cfg!(debug_assertions) {
+                log::LevelFilter::Debug
+            } else {
+                log::LevelFilter::Info
+            };
+            log::set_max_level(log_settings.level_filter().unwrap_or(default_level));
 
             match run_startup_migrations() {
                 Ok(result) if result.executed == 0 => {
@@ -207,15 +379,33 @@ pub fn run() {
                     Ok(conn) => {
                         log::debug!("数据库连接建立成功");
 
-                        // 执行数据库迁移
-                        log::debug!("开始执行数据库迁移...");
-                        match migration::Migrator::up(&conn, None).await {
-                            Ok(_) => log::info!("数据库迁移完成"),
-                            Err(e) => log::error!("数据库迁移失败: {}", e),
-                        }
+                        // 执行数据库迁移，补记未正常结束的游戏会话
+                        db::prepare_connection(&conn).await;
+                        utils::file_lock::load_retry_policy(&conn).await;
+
+                        // 将数据库连接注册到 Tauri 状态管理，导入数据库后会原地替换
+                        app_handle.manage(db::DbState::new(conn));
 
-                        // 将数据库连接注册到 Tauri 状态管理
-                        app_handle.manage(conn.clone());
+                        game::screenshot::init_screenshot_hotkey(app_handle.clone()).await;
+                        game::session_hotkeys::init_session_hotkeys(app_handle.clone()).await;
+                        game::presence::spawn_presence_loop(app_handle.clone());
+                        utils::http_api::init_http_api(app_handle.clone()).await;
+                        utils::deep_link::init_deep_link(&app_handle);
+                        if let Err(e) = utils::tray::init_tray(&app_handle) {
+                            log::error!("创建托盘图标失败: {}", e);
+                        }
+                        // 开启"启动时最小化到托盘"且托盘可用时不显示主窗口
+                        if app_handle.tray_by_id("main").is_none()
+                            || !utils::tray::start_minimized(&app_handle).await
+                        {
+                            utils::tray::show_main_window(&app_handle);
+                        }
+                        if let Some(game_id) = launch_on_start {
+                            let app_handle = app_handle.clone();
+                            tauri::async_runtime::spawn(async move {
+                                utils::deep_link::launch_and_report(&app_handle, game_id).await;
+                            });
+                        }
                     }
                     Err(e) => {
                         log::error!("无法建立数据库连接: {}", e);
@@ -231,8 +421,8 @@ pub fn run() {
             // 监听应用退出事件
             if let tauri::RunEvent::Exit = event {
                 // 同步获取并关闭数据库连接
-                if let Some(conn_state) = app_handle.try_state::<sea_orm::DatabaseConnection>() {
-                    let conn = conn_state.inner().clone();
+                if let Some(conn_state) = app_handle.try_state::<db::DbState>() {
+                    let conn = conn_state.get();
 
                     // 使用 block_on 确保数据库连接在应用退出前完全关闭
                     tauri::async_runtime::block_on(async {
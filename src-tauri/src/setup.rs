@@ -0,0 +1,305 @@
+//! 首次启动向导
+//!
+//! 由后端编排首次启动时的各项任务：选择数据目录（便携 / 安装模式）、选择游戏库目录并开始扫描、
+//! 设置 Locale Emulator / Magpie 路径、验证 BGM 令牌。每完成或跳过一步就写入 user.setup_progress，
+//! 向导中途关闭后下次启动从未完成的步骤继续。
+
+use crate::database::db::DbState;
+use crate::database::dto::UpdateSettingsData;
+use crate::database::repository::settings_repository::{DbSettingsExt, SettingsRepository};
+use crate::database::service::emit_settings_changed;
+use crate::entity::setup_progress::{SetupProgress, SetupStep};
+use crate::entity::user::{self, BgmAuth};
+use crate::game::scan::spawn_library_scan;
+use crate::utils::bgm_auth::{BgmUser, store_bgm_auth, verify_bgm_token};
+use crate::utils::metrics::CommandTimer;
+use crate::utils::portable::{MigrationResult, migrate_data_files};
+use reina_path::{get_base_data_dir, is_portable_mode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use tauri::{AppHandle, State, command};
+
+/// 向导状态
+#[derive(Debug, Serialize)]
+pub struct SetupState {
+    /// 向导是否已结束
+    pub finished: bool,
+    /// 下一个待完成的步骤，向导已结束时为 None
+    pub current_step: Option<SetupStep>,
+    pub completed: Vec<SetupStep>,
+    pub skipped: Vec<SetupStep>,
+    pub is_portable: bool,
+    /// 当前的基础数据目录
+    pub data_dir: String,
+    pub library_folders: Vec<String>,
+    pub le_path: Option<String>,
+    pub magpie_path: Option<String>,
+    /// 已保存的 BGM 授权对应的用户名
+    pub bgm_username: Option<String>,
+}
+
+/// 完成单个步骤的结果
+#[derive(Debug, Serialize)]
+pub struct SetupStepResult {
+    pub state: SetupState,
+    /// 选择游戏库目录后开始的扫描会话 ID，结果通过 `library-scan-*` 事件推送
+    pub scan_ids: Vec<u64>,
+    /// 切换数据目录的迁移结果，未切换时为 None
+    pub migration: Option<MigrationResult>,
+    /// 验证通过的 BGM 用户
+    pub bgm_user: Option<BgmUser>,
+}
+
+/// `data_dir` 步骤参数
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DataDirPayload {
+    /// 是否使用便携模式，与当前模式不同时迁移数据目录
+    portable: Option<bool>,
+}
+
+/// `library` 步骤参数
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct LibraryPayload {
+    folders: Vec<String>,
+    /// 是否立即扫描选择的目录，默认是
+    scan: bool,
+}
+
+impl Default for LibraryPayload {
+    fn default() -> Self {
+        Self {
+            folders: Vec::new(),
+            scan: true,
+        }
+    }
+}
+
+/// `tools` 步骤参数，未传入的路径保持不变
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ToolsPayload {
+    le_path: Option<String>,
+    magpie_path: Option<String>,
+}
+
+/// `bgm_token` 步骤参数
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BgmTokenPayload {
+    /// 手动填写的令牌，未传入时验证已保存的授权（如已通过 OAuth 登录）
+    access_token: Option<String>,
+}
+
+fn parse_payload<T: DeserializeOwned + Default>(payload: Option<Value>) -> Result<T, String> {
+    match payload {
+        None | Some(Value::Null) => Ok(T::default()),
+        Some(payload) => {
+            serde_json::from_value(payload).map_err(|e| format!("步骤参数无效: {}", e))
+        }
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn build_state(settings: &user::Model) -> Result<SetupState, String> {
+    let progress = settings.setup_progress.clone().unwrap_or_default();
+    Ok(SetupState {
+        finished: progress.finished_at.is_some(),
+        current_step: progress.next_step(),
+        is_portable: is_portable_mode(),
+        data_dir: get_base_data_dir()?.to_string_lossy().to_string(),
+        le_path: settings.le_path.clone(),
+        magpie_path: settings.magpie_path.clone(),
+        bgm_username: settings
+            .bgm_auth
+            .as_ref()
+            .and_then(|auth| auth.username.clone()),
+        completed: progress.completed,
+        skipped: progress.skipped,
+        library_folders: progress.library_folders,
+    })
+}
+
+/// 获取首次启动向导的状态
+///
+/// # Returns
+/// * `Result<SetupState, String>` - 向导状态，`finished` 为 false 时前端应从 `current_step` 继续
+#[command]
+pub async fn get_setup_state(db: State<'_, DbState>) -> Result<SetupState, String> {
+    let _timer = CommandTimer::start("get_setup_state");
+    build_state(&db.get().get_settings().await?)
+}
+
+/// 完成或跳过首次启动向导的一个步骤，并保存进度
+///
+/// 参数 `{"skip": true}` 表示跳过该步骤。各步骤的其他参数：
+/// - `data_dir`：`{"portable": bool}`，与当前模式不同时迁移数据目录
+/// - `library`：`{"folders": [..], "scan": bool}`，默认立即在后台扫描
+/// - `tools`：`{"le_path": "..", "magpie_path": ".."}`
+/// - `bgm_token`：`{"access_token": ".."}`，不传时验证已保存的授权
+///
+/// 步骤失败时不记录进度，可修改参数后重试。
+///
+/// # Arguments
+/// * `step` - 步骤名
+/// * `payload` - 步骤参数
+///
+/// # Returns
+/// * `Result<SetupStepResult, String>` - 步骤结果与最新的向导状态
+#[command]
+pub async fn complete_setup_step(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    step: SetupStep,
+    payload: Option<Value>,
+) -> Result<SetupStepResult, String> {
+    let _timer = CommandTimer::start("complete_setup_step");
+    let skip = payload
+        .as_ref()
+        .and_then(|payload| payload.get("skip"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut result = SetupStepResult {
+        state: build_state(&db.get().get_settings().await?)?,
+        scan_ids: Vec::new(),
+        migration: None,
+        bgm_user: None,
+    };
+    let mut update = UpdateSettingsData::default();
+    let mut library_folders = None;
+
+    if !skip {
+        match step {
+            SetupStep::DataDir => {
+                let payload: DataDirPayload = parse_payload(payload)?;
+                if let Some(portable) = payload.portable
+                    && portable != is_portable_mode()
+                {
+                    // 迁移后连接指向新位置的数据库，进度随数据库一起移动
+                    result.migration = Some(migrate_data_files(&db, portable).await?);
+                }
+            }
+            SetupStep::Library => {
+                let payload: LibraryPayload = parse_payload(payload)?;
+                let folders: Vec<String> = payload
+                    .folders
+                    .into_iter()
+                    .filter_map(|folder| non_empty(Some(folder)))
+                    .collect();
+                if folders.is_empty() {
+                    return Err("请至少选择一个游戏库目录".to_string());
+                }
+                if let Some(missing) = folders.iter().find(|folder| !Path::new(folder).is_dir()) {
+                    return Err(format!("目录不存在或不是文件夹: {}", missing));
+                }
+                if payload.scan {
+                    let conn = db.get();
+                    for folder in &folders {
+                        result.scan_ids.push(
+                            spawn_library_scan(app.clone(), &conn, folder.clone(), None, None)
+                                .await?,
+                        );
+                    }
+                }
+                library_folders = Some(folders);
+            }
+            SetupStep::Tools => {
+                let payload: ToolsPayload = parse_payload(payload)?;
+                for (label, path) in [
+                    ("Locale Emulator", &payload.le_path),
+                    ("Magpie", &payload.magpie_path),
+                ] {
+                    if let Some(path) = non_empty(path.clone())
+                        && !Path::new(&path).is_file()
+                    {
+                        return Err(format!("{} 程序不存在: {}", label, path));
+                    }
+                }
+                update.le_path = payload.le_path.map(|path| non_empty(Some(path)));
+                update.magpie_path = payload.magpie_path.map(|path| non_empty(Some(path)));
+            }
+            SetupStep::BgmToken => {
+                let payload: BgmTokenPayload = parse_payload(payload)?;
+                let conn = db.get();
+                let mut auth = match non_empty(payload.access_token) {
+                    Some(access_token) => BgmAuth {
+                        access_token,
+                        ..Default::default()
+                    },
+                    None => conn
+                        .get_settings()
+                        .await?
+                        .bgm_auth
+                        .ok_or_else(|| "尚未登录 BGM 或填写令牌".to_string())?,
+                };
+                let user = verify_bgm_token(&auth.access_token).await?;
+                auth.username = Some(user.username.clone());
+                auth.nickname = Some(user.nickname.clone());
+                store_bgm_auth(&conn, &auth).await?;
+                emit_settings_changed(&app, vec!["bgmAuth"]);
+                result.bgm_user = Some(user);
+            }
+        }
+    }
+
+    // 数据目录迁移后连接已替换，重新读取设置
+    let conn = db.get();
+    let mut progress: SetupProgress = conn
+        .get_settings()
+        .await?
+        .setup_progress
+        .unwrap_or_default();
+    if let Some(folders) = library_folders {
+        progress.library_folders = folders;
+    }
+    progress.mark(step, skip, chrono::Utc::now().timestamp());
+    update.setup_progress = Some(Some(progress));
+
+    let keys = update.changed_keys();
+    SettingsRepository::update_settings(&conn, update)
+        .await
+        .map_err(|e| format!("保存向导进度失败: {}", e))?;
+    emit_settings_changed(&app, keys);
+    log::info!("首次启动向导步骤完成 step={:?} skip={}", step, skip);
+
+    result.state = build_state(&conn.get_settings().await?)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_from_first_unfinished_step() {
+        let mut progress = SetupProgress::default();
+        assert_eq!(progress.next_step(), Some(SetupStep::DataDir));
+
+        progress.mark(SetupStep::Library, true, 1);
+        progress.mark(SetupStep::DataDir, false, 2);
+        assert_eq!(progress.next_step(), Some(SetupStep::Tools));
+        assert_eq!(progress.finished_at, None);
+
+        // 重做跳过的步骤会覆盖之前的结果
+        progress.mark(SetupStep::Library, false, 3);
+        assert_eq!(progress.skipped, Vec::new());
+        assert_eq!(
+            progress.completed,
+            vec![SetupStep::DataDir, SetupStep::Library]
+        );
+
+        progress.mark(SetupStep::Tools, false, 4);
+        progress.mark(SetupStep::BgmToken, true, 5);
+        assert_eq!(progress.finished_at, Some(5));
+        assert_eq!(progress.next_step(), None);
+    }
+}
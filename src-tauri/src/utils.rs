@@ -2,8 +2,20 @@
 pub mod command_ext;
 
 pub mod bgm_auth;
+pub mod chart;
+pub mod crash;
+pub mod deep_link;
+pub mod file_lock;
 pub mod fs;
 pub mod http;
+pub mod http_api;
 pub mod image;
 pub mod legacy_migration;
 pub mod logs;
+pub mod metrics;
+pub mod notification;
+pub mod portable;
+pub mod secrets;
+pub mod trash;
+pub mod tray;
+pub mod usage_stats;
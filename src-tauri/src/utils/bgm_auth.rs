@@ -1,6 +1,7 @@
 //! BGM OAuth 授权模块。
 //!
-//! 仅放需要 `BGM_APP_SECRET` 的流程：授权 URL、code 换 token、refresh。
+//! 主要放需要 `BGM_APP_SECRET` 的流程：授权 URL、code 换 token、refresh；
+//! 另提供验证令牌的 `verify_bgm_token`，供首次启动向导使用。
 
 use std::fmt::Write as _;
 use std::io::{BufRead, BufReader, Write};
@@ -8,12 +9,15 @@ use std::net::TcpListener;
 use std::time::Duration;
 
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
-use serde::Deserialize;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, State};
 
+use crate::database::db::DbState;
+use crate::database::dto::UpdateSettingsData;
 use crate::database::repository::settings_repository::SettingsRepository;
 use crate::entity::user::BgmAuth;
+use crate::utils::metrics::CommandTimer;
 
 const BGM_APP_ID: &str = "bgm606669f8b19c14e6e";
 const BGM_REDIRECT_URI: &str = "http://127.0.0.1:23380/callback";
@@ -29,6 +33,7 @@ struct BgmTokenResponse {
 
 #[tauri::command]
 pub async fn bgm_oauth_start_login(app: AppHandle) -> Result<String, String> {
+    let _timer = CommandTimer::start("bgm_oauth_start_login");
     let state = generate_oauth_state()?;
 
     let listener = TcpListener::bind(("127.0.0.1", BGM_CALLBACK_PORT)).map_err(|e| {
@@ -86,9 +91,11 @@ fn generate_oauth_state() -> Result<String, String> {
 
 #[tauri::command]
 pub async fn bgm_oauth_exchange_code(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     code: String,
 ) -> Result<BgmAuth, String> {
+    let _timer = CommandTimer::start("bgm_oauth_exchange_code");
+    let db = db.get();
     let app_secret = read_bgm_app_secret()?;
 
     let token_resp = request_token(&serde_json::json!({
@@ -115,9 +122,11 @@ pub async fn bgm_oauth_exchange_code(
 
 #[tauri::command]
 pub async fn bgm_oauth_refresh_token(
-    db: State<'_, DatabaseConnection>,
+    db: State<'_, DbState>,
     refresh_token: String,
 ) -> Result<BgmAuth, String> {
+    let _timer = CommandTimer::start("bgm_oauth_refresh_token");
+    let db = db.get();
     let app_secret = read_bgm_app_secret()?;
 
     let token_resp = request_token(&serde_json::json!({
@@ -258,17 +267,44 @@ async fn request_token(body: &serde_json::Value) -> Result<BgmTokenResponse, Str
     serde_json::from_str(&text).map_err(|e| format!("解析 BGM OAuth 响应失败: {} - {}", e, text))
 }
 
-async fn store_bgm_auth(db: &DatabaseConnection, auth: &BgmAuth) -> Result<(), String> {
-    let settings = SettingsRepository::get_all_settings(db)
+/// BGM 当前登录用户（`/v0/me`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BgmUser {
+    pub username: String,
+    pub nickname: String,
+}
+
+/// 用令牌请求当前用户信息，验证令牌是否有效
+pub async fn verify_bgm_token(access_token: &str) -> Result<BgmUser, String> {
+    let response = crate::utils::http::get_client()
+        .get("https://api.bgm.tv/v0/me")
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
         .await
-        .map_err(|e| format!("获取用户记录失败: {}", e))?;
+        .map_err(|e| format!("请求 BGM 失败: {}", e))?;
 
-    let mut active: crate::entity::user::ActiveModel = settings.into();
-    active.bgm_auth = Set(Some(auth.clone()));
-    active
-        .update(db)
+    let status = response.status();
+    if status.as_u16() == 401 {
+        return Err("BGM 令牌无效或已过期".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("BGM 返回异常状态码: {}", status));
+    }
+    let text = response
+        .text()
         .await
-        .map_err(|e| format!("保存 BGM 授权信息失败: {}", e))?;
+        .map_err(|e| format!("读取 BGM 响应失败: {}", e))?;
+    serde_json::from_str(&text).map_err(|e| format!("解析 BGM 用户信息失败: {}", e))
+}
 
-    Ok(())
+pub(crate) async fn store_bgm_auth(db: &DatabaseConnection, auth: &BgmAuth) -> Result<(), String> {
+    SettingsRepository::update_settings(
+        db,
+        UpdateSettingsData {
+            bgm_auth: Some(Some(auth.clone())),
+            ..Default::default()
+        },
+    )
+    .await
+    .map_err(|e| format!("保存 BGM 授权信息失败: {}", e))
 }
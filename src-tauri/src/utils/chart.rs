@@ -0,0 +1,549 @@
+//! 统计图表渲染
+//!
+//! 分享卡片和报告导出需要在不同平台的 WebView 中得到一致的图片，这里在后端用 plotters 直接生成
+//! SVG 或 PNG：游玩时长随时间变化的柱状图与标签分布的环形图。SVG 的文字使用系统字体栈，
+//! PNG 由系统字体栅格化，优先选用各平台自带的中文字体。
+
+use crate::database::db::DbState;
+use crate::database::repository::game_stats_repository::GameStatsRepository;
+use crate::utils::metrics::CommandTimer;
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate};
+use image::{ImageFormat, RgbImage};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use std::io::Cursor;
+use tauri::State;
+use tauri::ipc::Response;
+
+const DEFAULT_WIDTH: u32 = 800;
+const DEFAULT_HEIGHT: u32 = 400;
+const MIN_SIZE: u32 = 200;
+const MAX_SIZE: u32 = 4096;
+/// 按天统计时允许的最大天数
+const MAX_RANGE_DAYS: i64 = 3660;
+const DEFAULT_TAG_LIMIT: usize = 8;
+/// X 轴最多显示的标签数
+const MAX_X_LABELS: usize = 12;
+/// SVG 的字体栈，由查看图片的环境选择字体
+const FONT_FAMILY: &str = "system-ui, -apple-system, 'Segoe UI', 'Microsoft YaHei', 'PingFang SC', 'Noto Sans CJK SC', sans-serif";
+/// PNG 栅格化使用的字体，找不到时 plotters 退回系统默认无衬线字体
+#[cfg(target_os = "windows")]
+const RASTER_FONT_FAMILY: &str = "Microsoft YaHei";
+#[cfg(target_os = "macos")]
+const RASTER_FONT_FAMILY: &str = "PingFang SC";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const RASTER_FONT_FAMILY: &str = "Noto Sans CJK SC";
+
+/// 环形图配色
+const PALETTE: [RGBColor; 10] = [
+    RGBColor(0x19, 0x76, 0xd2),
+    RGBColor(0xef, 0x6c, 0x00),
+    RGBColor(0x2e, 0x7d, 0x32),
+    RGBColor(0xc6, 0x28, 0x28),
+    RGBColor(0x6a, 0x1b, 0x9a),
+    RGBColor(0x00, 0x83, 0x8f),
+    RGBColor(0xf9, 0xa8, 0x25),
+    RGBColor(0x5d, 0x40, 0x37),
+    RGBColor(0xad, 0x14, 0x57),
+    RGBColor(0x54, 0x6e, 0x7a),
+];
+
+/// 时间维度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+/// 图表类型及其数据范围
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChartKind {
+    /// 所有游戏的游玩时长随时间变化（柱状图，单位小时）
+    PlaytimeOverTime {
+        /// `YYYY-MM-DD`（含）
+        start_date: String,
+        /// `YYYY-MM-DD`（含）
+        end_date: String,
+        #[serde(default)]
+        granularity: Granularity,
+    },
+    /// 按元数据标签汇总的总时长分布（环形图）
+    GenreDistribution {
+        /// 展示的标签数，其余合并为「其他」，默认 8
+        limit: Option<usize>,
+    },
+}
+
+/// 配色主题
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartTheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+struct ThemeColors {
+    background: RGBColor,
+    text: RGBColor,
+    muted: RGBColor,
+    grid: RGBColor,
+    bar: RGBColor,
+}
+
+impl ChartTheme {
+    fn colors(self) -> ThemeColors {
+        match self {
+            Self::Light => ThemeColors {
+                background: RGBColor(0xff, 0xff, 0xff),
+                text: RGBColor(0x21, 0x21, 0x21),
+                muted: RGBColor(0x75, 0x75, 0x75),
+                grid: RGBColor(0xe0, 0xe0, 0xe0),
+                bar: RGBColor(0x19, 0x76, 0xd2),
+            },
+            Self::Dark => ThemeColors {
+                background: RGBColor(0x12, 0x12, 0x12),
+                text: RGBColor(0xee, 0xee, 0xee),
+                muted: RGBColor(0x9e, 0x9e, 0x9e),
+                grid: RGBColor(0x33, 0x33, 0x33),
+                bar: RGBColor(0x90, 0xca, 0xf9),
+            },
+        }
+    }
+}
+
+/// 图表描述
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChartSpec {
+    #[serde(flatten)]
+    pub kind: ChartKind,
+    pub title: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub theme: ChartTheme,
+}
+
+/// 查询得到的图表数据
+#[derive(Debug, Clone)]
+enum ChartData {
+    /// 柱状图，(标签, 数值) 与 Y 轴单位
+    Bars {
+        bars: Vec<(String, f64)>,
+        unit: &'static str,
+    },
+    /// 环形图，(名称, 数值)
+    Donut { slices: Vec<(String, f64)> },
+}
+
+/// 绘图时使用的标题、配色与字体
+struct ChartStyle<'a> {
+    title: Option<&'a str>,
+    colors: ThemeColors,
+    font: &'a str,
+}
+
+type DrawResult<DB> = Result<(), DrawingAreaErrorKind<<DB as DrawingBackend>::ErrorType>>;
+
+/// 绘制背景与标题，返回标题以下的绘图区域
+fn draw_frame<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    style: &ChartStyle,
+) -> Result<DrawingArea<DB, Shift>, DrawingAreaErrorKind<DB::ErrorType>> {
+    root.fill(&style.colors.background)?;
+    match style.title.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(title) => root.titled(
+            title,
+            (style.font, 22)
+                .into_font()
+                .style(FontStyle::Bold)
+                .color(&style.colors.text),
+        ),
+        None => Ok(root.clone()),
+    }
+}
+
+/// 绘制柱状图，`bars` 为 (标签, 数值)
+fn draw_bar_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    bars: &[(String, f64)],
+    unit: &str,
+    style: &ChartStyle,
+) -> DrawResult<DB> {
+    let area = draw_frame(root, style)?;
+    let colors = &style.colors;
+    let max_value = bars.iter().map(|(_, v)| *v).fold(0.0, f64::max);
+    let y_max = if max_value > 0.0 {
+        max_value * 1.1
+    } else {
+        1.0
+    };
+
+    let mut chart = ChartBuilder::on(&area)
+        .margin(16)
+        .x_label_area_size(24)
+        .y_label_area_size(48)
+        .build_cartesian_2d((0..bars.len().max(1)).into_segmented(), 0.0..y_max)?;
+    let label_font = (style.font, 13).into_font().color(&colors.muted);
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .bold_line_style(colors.grid)
+        .light_line_style(TRANSPARENT)
+        .axis_style(colors.grid)
+        .x_labels(bars.len().clamp(1, MAX_X_LABELS))
+        .x_label_formatter(&|segment| match segment {
+            SegmentValue::CenterOf(index) => bars
+                .get(*index)
+                .map(|(label, _)| label.clone())
+                .unwrap_or_default(),
+            _ => String::new(),
+        })
+        .y_labels(5)
+        .y_desc(unit)
+        .label_style(label_font.clone())
+        .axis_desc_style(label_font)
+        .draw()?;
+    chart.draw_series(bars.iter().enumerate().filter(|(_, (_, v))| *v > 0.0).map(
+        |(index, (_, value))| {
+            let mut bar = Rectangle::new(
+                [
+                    (SegmentValue::Exact(index), 0.0),
+                    (SegmentValue::Exact(index + 1), *value),
+                ],
+                colors.bar.filled(),
+            );
+            bar.set_margin(0, 0, 2, 2);
+            bar
+        },
+    ))?;
+    Ok(())
+}
+
+/// 绘制环形图与图例，`slices` 为 (名称, 数值)
+fn draw_donut_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    slices: &[(String, f64)],
+    style: &ChartStyle,
+) -> DrawResult<DB> {
+    let area = draw_frame(root, style)?;
+    let colors = &style.colors;
+    let (width, height) = area.dim_in_pixel();
+    let radius = (f64::from(height) / 2.0 - 16.0)
+        .min(f64::from(width) / 4.0)
+        .max(10.0);
+    let center = ((16.0 + radius) as i32, (height / 2) as i32);
+    let total: f64 = slices.iter().map(|(_, v)| v.max(0.0)).sum();
+
+    // 没有数据时画一个灰色圆环
+    let (sizes, slice_colors): (Vec<f64>, Vec<RGBColor>) = if total > 0.0 {
+        slices
+            .iter()
+            .enumerate()
+            .map(|(index, (_, value))| (value.max(0.0), PALETTE[index % PALETTE.len()]))
+            .unzip()
+    } else {
+        (vec![1.0], vec![colors.grid])
+    };
+    let labels = vec![""; sizes.len()];
+    // Pie 直接使用后端坐标，需要加上标题下方绘图区的偏移
+    let (offset_x, offset_y) = area.get_base_pixel();
+    let pie_center = (center.0 + offset_x, center.1 + offset_y);
+    let mut pie = Pie::new(&pie_center, &radius, &sizes, &slice_colors, &labels);
+    pie.start_angle(-90.0);
+    pie.donut_hole(radius * 0.6);
+    area.draw(&pie)?;
+
+    // 图例
+    let legend_x = center.0 + radius as i32 + 32;
+    let row_height = 22;
+    let legend_top = center.1 - row_height * slices.len() as i32 / 2;
+    let name_font = (style.font, 16).into_font().color(&colors.text);
+    let percent_font = (style.font, 16).into_font().color(&colors.muted);
+    for (index, (name, value)) in slices.iter().enumerate() {
+        let y = legend_top + row_height * index as i32;
+        let percent = if total > 0.0 {
+            value / total * 100.0
+        } else {
+            0.0
+        };
+        area.draw(&Rectangle::new(
+            [(legend_x, y + 4), (legend_x + 12, y + 16)],
+            PALETTE[index % PALETTE.len()].filled(),
+        ))?;
+        area.draw(&Text::new(name.clone(), (legend_x + 20, y + 3), &name_font))?;
+        let (name_width, _) = area.estimate_text_size(name, &name_font)?;
+        area.draw(&Text::new(
+            format!("{:.1}%", percent),
+            (legend_x + 26 + name_width as i32, y + 3),
+            &percent_font,
+        ))?;
+    }
+    Ok(())
+}
+
+impl ChartData {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        style: &ChartStyle,
+    ) -> DrawResult<DB> {
+        match self {
+            Self::Bars { bars, unit } => draw_bar_chart(root, bars, unit, style),
+            Self::Donut { slices } => draw_donut_chart(root, slices, style),
+        }
+    }
+
+    /// 渲染为 SVG 文本
+    fn to_svg(
+        &self,
+        width: u32,
+        height: u32,
+        title: Option<&str>,
+        theme: ChartTheme,
+    ) -> Result<String, String> {
+        let style = ChartStyle {
+            title,
+            colors: theme.colors(),
+            font: FONT_FAMILY,
+        };
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (width, height)).into_drawing_area();
+            self.draw(&root, &style)
+                .and_then(|_| root.present())
+                .map_err(|e| format!("渲染图表失败: {}", e))?;
+        }
+        Ok(svg)
+    }
+
+    /// 渲染为 PNG 图片
+    fn to_png(
+        &self,
+        width: u32,
+        height: u32,
+        title: Option<&str>,
+        theme: ChartTheme,
+    ) -> Result<Vec<u8>, String> {
+        let style = ChartStyle {
+            title,
+            colors: theme.colors(),
+            font: RASTER_FONT_FAMILY,
+        };
+        let mut pixels = vec![0; width as usize * height as usize * 3];
+        {
+            let root = BitMapBackend::with_buffer(&mut pixels, (width, height)).into_drawing_area();
+            self.draw(&root, &style)
+                .and_then(|_| root.present())
+                .map_err(|e| format!("渲染图表失败: {}", e))?;
+        }
+        let image = RgbImage::from_raw(width, height, pixels)
+            .ok_or_else(|| "渲染图表失败: 像素缓冲区大小不匹配".to_string())?;
+        let mut png = Cursor::new(Vec::new());
+        image
+            .write_to(&mut png, ImageFormat::Png)
+            .map_err(|e| format!("编码 PNG 失败: {}", e))?;
+        Ok(png.into_inner())
+    }
+}
+
+/// 把每日时长（分钟）按时间维度汇总为小时
+fn bucket_daily_playtime(
+    daily: &std::collections::BTreeMap<String, i32>,
+    start: NaiveDate,
+    end: NaiveDate,
+    granularity: Granularity,
+) -> Vec<(String, f64)> {
+    let mut buckets: Vec<(String, f64)> = Vec::new();
+    let mut date = start;
+    while date <= end {
+        let label = match granularity {
+            Granularity::Day => date.format("%m-%d").to_string(),
+            Granularity::Week => {
+                let monday =
+                    date - ChronoDuration::days(date.weekday().num_days_from_monday() as i64);
+                monday.format("%m-%d").to_string()
+            }
+            Granularity::Month => date.format("%Y-%m").to_string(),
+        };
+        let hours = daily
+            .get(&date.format("%Y-%m-%d").to_string())
+            .copied()
+            .unwrap_or(0) as f64
+            / 60.0;
+        match buckets.last_mut() {
+            Some((last, total)) if *last == label => *total += hours,
+            _ => buckets.push((label, hours)),
+        }
+        let Some(next) = date.succ_opt() else {
+            break;
+        };
+        date = next;
+    }
+    buckets
+}
+
+/// 按图表描述查询数据
+async fn load_chart_data(db: &DatabaseConnection, kind: &ChartKind) -> Result<ChartData, String> {
+    match kind {
+        ChartKind::PlaytimeOverTime {
+            start_date,
+            end_date,
+            granularity,
+        } => {
+            let parse = |date: &str| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map_err(|e| format!("日期格式无效 {}: {}", date, e))
+            };
+            let (start, end) = (parse(start_date)?, parse(end_date)?);
+            if start > end {
+                return Err("开始日期不能晚于结束日期".to_string());
+            }
+            if (end - start).num_days() > MAX_RANGE_DAYS {
+                return Err(format!("日期范围不能超过 {} 天", MAX_RANGE_DAYS));
+            }
+
+            let daily = GameStatsRepository::get_daily_playtime_range(db, start_date, end_date)
+                .await
+                .map_err(|e| format!("获取每日游玩时长失败: {}", e))?;
+            Ok(ChartData::Bars {
+                bars: bucket_daily_playtime(&daily, start, end, *granularity),
+                unit: "h",
+            })
+        }
+        ChartKind::GenreDistribution { limit } => {
+            let limit = limit
+                .unwrap_or(DEFAULT_TAG_LIMIT)
+                .clamp(1, PALETTE.len() - 1);
+            // 同一游戏可能有多个标签，比例按各标签时长之和计算
+            let tags = GameStatsRepository::get_tag_playtime(db, usize::MAX)
+                .await
+                .map_err(|e| format!("获取标签分布失败: {}", e))?;
+            let mut slices: Vec<(String, f64)> = tags
+                .iter()
+                .take(limit)
+                .map(|tag| (tag.name.clone(), tag.playtime as f64))
+                .collect();
+            let rest: f64 = tags.iter().skip(limit).map(|tag| tag.playtime as f64).sum();
+            if rest > 0.0 {
+                slices.push(("其他".to_string(), rest));
+            }
+            Ok(ChartData::Donut { slices })
+        }
+    }
+}
+
+/// 限制在允许范围内的图片尺寸
+fn chart_size(spec: &ChartSpec) -> (u32, u32) {
+    (
+        spec.width
+            .unwrap_or(DEFAULT_WIDTH)
+            .clamp(MIN_SIZE, MAX_SIZE),
+        spec.height
+            .unwrap_or(DEFAULT_HEIGHT)
+            .clamp(MIN_SIZE, MAX_SIZE),
+    )
+}
+
+/// 渲染统计图表，返回 SVG 文本
+///
+/// # Arguments
+/// * `spec` - 图表类型、数据范围、尺寸与主题
+///
+/// # Returns
+/// * `Result<String, String>` - SVG 文本或错误消息
+#[tauri::command]
+pub async fn render_chart(db: State<'_, DbState>, spec: ChartSpec) -> Result<String, String> {
+    let _timer = CommandTimer::start("render_chart");
+    let data = load_chart_data(&db.get(), &spec.kind).await?;
+    let (width, height) = chart_size(&spec);
+    tokio::task::spawn_blocking(move || {
+        data.to_svg(width, height, spec.title.as_deref(), spec.theme)
+    })
+    .await
+    .map_err(|e| format!("渲染图表失败: {}", e))?
+}
+
+/// 渲染统计图表，返回 PNG 图片
+///
+/// # Arguments
+/// * `spec` - 图表类型、数据范围、尺寸与主题
+///
+/// # Returns
+/// * `Result<Response, String>` - PNG 字节（前端收到 ArrayBuffer）或错误消息
+#[tauri::command]
+pub async fn render_chart_png(db: State<'_, DbState>, spec: ChartSpec) -> Result<Response, String> {
+    let _timer = CommandTimer::start("render_chart_png");
+    let data = load_chart_data(&db.get(), &spec.kind).await?;
+    let (width, height) = chart_size(&spec);
+    let png = tokio::task::spawn_blocking(move || {
+        data.to_png(width, height, spec.title.as_deref(), spec.theme)
+    })
+    .await
+    .map_err(|e| format!("渲染图表失败: {}", e))??;
+    Ok(Response::new(png))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_charts_escape_labels() {
+        let bars = ChartData::Bars {
+            bars: vec![("<a&b>".to_string(), 2.0), ("10-02".to_string(), 0.0)],
+            unit: "h",
+        };
+        let svg = bars
+            .to_svg(400, 300, Some("\"t\""), ChartTheme::Light)
+            .unwrap();
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("&lt;a&amp;b&gt;"));
+        assert!(svg.contains("&quot;t&quot;"));
+
+        let slices = ChartData::Donut {
+            slices: vec![("RPG".to_string(), 3.0), ("ADV".to_string(), 1.0)],
+        };
+        let svg = slices.to_svg(400, 300, None, ChartTheme::Dark).unwrap();
+        assert!(svg.contains("75.0%"));
+        assert!(svg.contains("25.0%"));
+    }
+
+    #[test]
+    fn empty_charts_still_render() {
+        let bars = ChartData::Bars {
+            bars: Vec::new(),
+            unit: "h",
+        };
+        assert!(bars.to_svg(200, 200, None, ChartTheme::Light).is_ok());
+        let slices = ChartData::Donut { slices: Vec::new() };
+        assert!(slices.to_svg(200, 200, None, ChartTheme::Light).is_ok());
+    }
+
+    #[test]
+    fn week_buckets_start_on_monday() {
+        let daily = [
+            ("2026-10-12".to_string(), 60),
+            ("2026-10-18".to_string(), 30),
+        ]
+        .into_iter()
+        .collect();
+        let start = NaiveDate::from_ymd_opt(2026, 10, 11).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let buckets = bucket_daily_playtime(&daily, start, end, Granularity::Week);
+        assert_eq!(
+            buckets,
+            vec![
+                ("10-05".to_string(), 0.0),
+                ("10-12".to_string(), 1.5),
+                ("10-19".to_string(), 0.0)
+            ]
+        );
+    }
+}
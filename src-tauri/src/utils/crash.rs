@@ -0,0 +1,240 @@
+//! 崩溃报告
+//!
+//! 在 `run()` 开头安装 panic hook，发生 panic 时把调用栈、应用版本、正在运行的游戏会话与最近 200 行
+//! 日志写入数据目录下的 `crashes/`，下次启动时前端可通过 `list_crash_reports` 展示并提示用户反馈。
+
+use crate::game::monitor::get_active_sessions;
+use crate::utils::logs::tail_log_lines;
+use crate::utils::metrics::CommandTimer;
+use serde::Serialize;
+use std::backtrace::Backtrace;
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+use std::fs;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::UNIX_EPOCH;
+
+/// 报告中附带的日志行数
+const CRASH_LOG_LINES: usize = 200;
+
+/// 报告文件名前缀与扩展名
+const CRASH_FILE_PREFIX: &str = "crash_";
+const CRASH_FILE_EXTENSION: &str = ".txt";
+
+/// 报告中 panic 信息所在行的前缀
+const MESSAGE_PREFIX: &str = "信息: ";
+
+/// 日志目录，日志插件初始化后设置
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// 崩溃报告列表项
+#[derive(Debug, Serialize)]
+pub struct CrashReportInfo {
+    pub file_name: String,
+    pub path: String,
+    /// 写入时间（Unix 时间戳，秒）
+    pub created_at: i64,
+    pub size: u64,
+    /// panic 信息
+    pub message: Option<String>,
+}
+
+/// 设置日志目录，崩溃报告从中读取最近的日志
+pub fn set_log_dir(dir: PathBuf) {
+    let _ = LOG_DIR.set(dir);
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<非字符串 panic 信息>".to_string())
+}
+
+/// 生成报告内容
+fn build_report(
+    message: &str,
+    location: Option<String>,
+    thread: &str,
+    backtrace: &Backtrace,
+    log_lines: &[String],
+) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "ReinaManager 崩溃报告");
+    let _ = writeln!(report, "版本: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "时间: {}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    let _ = writeln!(
+        report,
+        "系统: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "线程: {}", thread);
+    let _ = writeln!(report, "位置: {}", location.as_deref().unwrap_or("<未知>"));
+    let _ = writeln!(report, "{}{}", MESSAGE_PREFIX, message.replace('\n', " "));
+
+    let _ = writeln!(report, "\n== 正在运行的游戏会话 ==");
+    let sessions = get_active_sessions();
+    if sessions.is_empty() {
+        let _ = writeln!(report, "无");
+    }
+    for session in sessions {
+        let _ = writeln!(
+            report,
+            "game_id={} pid={} elapsed={}s total={}s paused={}",
+            session.game_id,
+            session.process_id,
+            session.elapsed_seconds,
+            session.total_seconds,
+            session.paused
+        );
+    }
+
+    let _ = writeln!(report, "\n== 调用栈 ==\n{}", backtrace);
+
+    let _ = writeln!(report, "\n== 最近 {} 行日志 ==", CRASH_LOG_LINES);
+    for line in log_lines {
+        let _ = writeln!(report, "{}", line);
+    }
+    report
+}
+
+fn write_report(info: &PanicHookInfo<'_>) -> Result<PathBuf, String> {
+    let dir = reina_path::get_crashes_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建崩溃报告目录失败: {}", e))?;
+
+    let thread = std::thread::current();
+    let log_lines = LOG_DIR
+        .get()
+        .map(|dir| tail_log_lines(dir, CRASH_LOG_LINES))
+        .unwrap_or_default();
+    let report = build_report(
+        &panic_message(info),
+        info.location().map(|location| location.to_string()),
+        thread.name().unwrap_or("<unnamed>"),
+        &Backtrace::force_capture(),
+        &log_lines,
+    );
+
+    let path = dir.join(format!(
+        "{}{}{}",
+        CRASH_FILE_PREFIX,
+        chrono::Local::now().format("%Y%m%d_%H%M%S_%3f"),
+        CRASH_FILE_EXTENSION
+    ));
+    fs::write(&path, report).map_err(|e| format!("写入崩溃报告失败: {}", e))?;
+    Ok(path)
+}
+
+/// 安装 panic hook，写入报告后交给原有的 hook 继续处理
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(info) {
+            Ok(path) => log::error!("程序发生 panic，已写入崩溃报告: {}", path.display()),
+            Err(e) => eprintln!("{}", e),
+        }
+        previous(info);
+    }));
+}
+
+/// 是否为崩溃报告文件名（不含路径分隔符，防止删除目录外的文件）
+fn is_crash_report_name(file_name: &str) -> bool {
+    file_name.starts_with(CRASH_FILE_PREFIX)
+        && file_name.ends_with(CRASH_FILE_EXTENSION)
+        && !file_name.contains(['/', '\\'])
+        && !file_name.contains("..")
+}
+
+/// 从报告内容中取出 panic 信息
+fn report_message(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(MESSAGE_PREFIX))
+        .map(str::to_string)
+}
+
+fn report_info(path: &Path) -> Option<CrashReportInfo> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    if !is_crash_report_name(&file_name) {
+        return None;
+    }
+    let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+    let created_at = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs() as i64);
+    let message = fs::read(path)
+        .ok()
+        .and_then(|bytes| report_message(&String::from_utf8_lossy(&bytes)));
+    Some(CrashReportInfo {
+        file_name,
+        path: path.to_string_lossy().to_string(),
+        created_at,
+        size: metadata.len(),
+        message,
+    })
+}
+
+/// 列出崩溃报告，按时间从新到旧排列
+#[tauri::command]
+pub async fn list_crash_reports() -> Result<Vec<CrashReportInfo>, String> {
+    let _timer = CommandTimer::start("list_crash_reports");
+    let dir = reina_path::get_crashes_dir()?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut reports: Vec<CrashReportInfo> = fs::read_dir(&dir)
+        .map_err(|e| format!("读取崩溃报告目录失败: {}", e))?
+        .filter_map(Result::ok)
+        .filter_map(|entry| report_info(&entry.path()))
+        .collect();
+    reports.sort_by_key(|report| Reverse(report.created_at));
+    Ok(reports)
+}
+
+/// 删除崩溃报告
+///
+/// # Arguments
+/// * `file_name` - `list_crash_reports` 返回的文件名
+#[tauri::command]
+pub async fn delete_crash_report(file_name: String) -> Result<(), String> {
+    let _timer = CommandTimer::start("delete_crash_report");
+    if !is_crash_report_name(&file_name) {
+        return Err(format!("无效的崩溃报告文件名: {}", file_name));
+    }
+    let path = reina_path::get_crashes_dir()?.join(&file_name);
+    fs::remove_file(&path).map_err(|e| format!("删除崩溃报告失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_report_names() {
+        assert!(is_crash_report_name("crash_20261014_180617_123.txt"));
+        assert!(!is_crash_report_name("../crash_1.txt"));
+        assert!(!is_crash_report_name("crash_a/b.txt"));
+        assert!(!is_crash_report_name("debug.log"));
+    }
+
+    #[test]
+    fn extracts_panic_message() {
+        let content = "ReinaManager 崩溃报告\n版本: 1.0\n信息: index out of bounds\n";
+        assert_eq!(
+            report_message(content).as_deref(),
+            Some("index out of bounds")
+        );
+        assert_eq!(report_message("empty"), None);
+    }
+}
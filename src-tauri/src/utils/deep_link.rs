@@ -0,0 +1,124 @@
+//! `reina://` 自定义协议
+//!
+//! 注册 `reina://launch/<game_id>` 链接，桌面快捷方式、浏览器书签或其他启动器打开链接时
+//! 直接启动对应游戏。应用未运行时链接随启动参数传入；已在运行时由 single-instance 插件
+//! 转发给当前实例。
+
+use crate::game::launch::launch_game_by_id;
+use crate::utils::notification::{NotificationCategory, notify};
+use log::{info, warn};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+/// 注册的协议名
+pub const DEEP_LINK_SCHEME: &str = "reina";
+
+/// 链接对应的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeepLinkAction {
+    /// `reina://launch/<game_id>`
+    Launch(u32),
+}
+
+/// 解析 `reina://` 链接
+pub fn parse_deep_link(link: &str) -> Result<DeepLinkAction, String> {
+    let url = Url::parse(link).map_err(|e| format!("无效的链接 {}: {}", link, e))?;
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(format!("不支持的协议: {}", url.scheme()));
+    }
+    match url.host_str() {
+        Some("launch") => {
+            let id = url.path().trim_matches('/');
+            id.parse::<u32>()
+                .ok()
+                .filter(|id| *id > 0)
+                .map(DeepLinkAction::Launch)
+                .ok_or_else(|| format!("无效的游戏 ID: {}", id))
+        }
+        other => Err(format!("不支持的操作: {}", other.unwrap_or_default())),
+    }
+}
+
+/// 处理单个链接，失败时发送错误通知
+async fn handle_link<R: Runtime>(app: AppHandle<R>, link: String) {
+    let action = match parse_deep_link(&link) {
+        Ok(action) => action,
+        Err(e) => {
+            warn!("忽略链接 {}: {}", link, e);
+            return;
+        }
+    };
+    match action {
+        DeepLinkAction::Launch(game_id) => {
+            info!("通过链接启动游戏 game_id={}", game_id);
+            launch_and_report(&app, game_id).await;
+        }
+    }
+}
+
+/// 启动游戏，失败时发送错误通知；供链接与命令行 `--launch` 使用
+pub async fn launch_and_report<R: Runtime>(app: &AppHandle<R>, game_id: u32) {
+    if let Err(e) = launch_game_by_id(app, game_id).await {
+        warn!("启动游戏 {} 失败: {}", game_id, e);
+        notify(
+            app,
+            NotificationCategory::Error,
+            "启动游戏失败",
+            &e.to_string(),
+        )
+        .await;
+    }
+}
+
+fn dispatch<R: Runtime>(app: &AppHandle<R>, urls: Vec<Url>) {
+    for url in urls {
+        tauri::async_runtime::spawn(handle_link(app.clone(), url.to_string()));
+    }
+}
+
+/// 注册协议并开始处理链接，需在数据库状态注册之后调用
+pub fn init_deep_link<R: Runtime>(app: &AppHandle<R>) {
+    // 安装版由安装程序写入协议关联；便携版与 AppImage 在运行时注册
+    if let Err(e) = app.deep_link().register_all() {
+        warn!("注册 {}:// 协议失败: {}", DEEP_LINK_SCHEME, e);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        dispatch(&handle, event.urls());
+    });
+
+    // 应用由链接启动时，链接在启动参数中
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => dispatch(app, urls),
+        Ok(None) => {}
+        Err(e) => warn!("读取启动链接失败: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_launch_links() {
+        assert_eq!(
+            parse_deep_link("reina://launch/42"),
+            Ok(DeepLinkAction::Launch(42))
+        );
+        assert_eq!(
+            parse_deep_link("reina://launch/7/"),
+            Ok(DeepLinkAction::Launch(7))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_links() {
+        assert!(parse_deep_link("reina://launch/").is_err());
+        assert!(parse_deep_link("reina://launch/0").is_err());
+        assert!(parse_deep_link("reina://launch/abc").is_err());
+        assert!(parse_deep_link("reina://delete/1").is_err());
+        assert!(parse_deep_link("https://launch/1").is_err());
+    }
+}
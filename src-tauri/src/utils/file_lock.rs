@@ -0,0 +1,236 @@
+//! 文件被占用时的重试与诊断
+//!
+//! 杀毒软件扫描新写入的 7z 备份或数据库文件时会短暂独占打开它们，
+//! 导致备份、恢复和便携模式迁移偶发失败（Windows 上为 ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION）。
+//! 这里对这类瞬时错误按退避策略重试，并记录最近的占用事件，供诊断命令建议添加杀毒软件排除项。
+//! 重试次数与首次等待时间可通过 user.file_lock_retries / user.file_lock_retry_delay_ms 配置。
+
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::utils::metrics::CommandTimer;
+use parking_lot::Mutex;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::collections::{BTreeSet, VecDeque};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// 默认最大重试次数，配合默认等待时间总计约 3.1 秒
+const DEFAULT_RETRIES: u32 = 5;
+
+/// 默认首次重试前的等待时间（毫秒），之后每次翻倍
+const DEFAULT_RETRY_DELAY_MS: u64 = 100;
+
+/// 允许配置的最大重试次数
+const MAX_RETRIES: u32 = 10;
+
+/// 单次等待时间上限
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// 当前生效的重试策略（最大重试次数, 首次等待时间）
+static RETRY_POLICY: Mutex<(u32, Duration)> = Mutex::new((
+    DEFAULT_RETRIES,
+    Duration::from_millis(DEFAULT_RETRY_DELAY_MS),
+));
+
+/// 保留的占用事件数量
+const EVENT_CAPACITY: usize = 100;
+
+/// 统计窗口（秒）
+const DIAGNOSTIC_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+/// 统计窗口内超过该次数的占用即建议添加排除项
+const SUGGEST_EXCLUSION_THRESHOLD: usize = 3;
+
+#[cfg(target_os = "windows")]
+const ERROR_SHARING_VIOLATION: i32 = 32;
+#[cfg(target_os = "windows")]
+const ERROR_LOCK_VIOLATION: i32 = 33;
+#[cfg(not(target_os = "windows"))]
+const EBUSY: i32 = 16;
+
+static LOCK_EVENTS: Mutex<VecDeque<FileLockEvent>> = Mutex::new(VecDeque::new());
+
+/// 一次文件被占用事件
+#[derive(Debug, Clone, Serialize)]
+pub struct FileLockEvent {
+    pub path: String,
+    /// 发生时间（Unix 时间戳，秒）
+    pub occurred_at: i64,
+    /// 实际尝试次数（含首次）
+    pub attempts: usize,
+    /// 重试后是否成功
+    pub recovered: bool,
+}
+
+/// 文件占用诊断结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FileLockDiagnostics {
+    /// 最近的占用事件，按时间倒序
+    pub events: Vec<FileLockEvent>,
+    /// 最近 24 小时内的占用次数
+    pub recent_count: usize,
+    /// 最近 24 小时内重试后仍失败的次数
+    pub unrecovered_count: usize,
+    /// 是否建议将相关目录加入杀毒软件排除项
+    pub suggest_exclusion: bool,
+    /// 建议排除的目录
+    pub suggested_paths: Vec<String>,
+    /// Windows Defender 添加排除项的 PowerShell 命令（需管理员权限），其他平台为 None
+    pub exclusion_command: Option<String>,
+}
+
+/// 判断 IO 错误是否为文件被其他进程占用导致的瞬时错误
+pub fn is_sharing_violation(error: &io::Error) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        matches!(
+            error.raw_os_error(),
+            Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+        )
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        error.raw_os_error() == Some(EBUSY)
+    }
+}
+
+/// 按用户设置更新重试策略，未设置或超出范围的项使用默认值
+///
+/// 启动时以及重试设置变更、导入数据库后调用。
+pub async fn load_retry_policy(db: &DatabaseConnection) {
+    let settings = match SettingsRepository::get_stored_settings(db).await {
+        Ok(settings) => settings,
+        Err(e) => {
+            log::warn!("读取文件占用重试设置失败，使用默认值: {}", e);
+            return;
+        }
+    };
+
+    let retries = settings
+        .file_lock_retries
+        .and_then(|retries| u32::try_from(retries).ok())
+        .map_or(DEFAULT_RETRIES, |retries| retries.min(MAX_RETRIES));
+    let delay = settings
+        .file_lock_retry_delay_ms
+        .and_then(|delay| u64::try_from(delay).ok())
+        .filter(|delay| *delay > 0)
+        .map_or(Duration::from_millis(DEFAULT_RETRY_DELAY_MS), |delay| {
+            Duration::from_millis(delay).min(MAX_RETRY_DELAY)
+        });
+    *RETRY_POLICY.lock() = (retries, delay);
+}
+
+/// 执行文件操作，遇到文件被占用时按退避策略重试
+///
+/// 非占用类错误立即返回；占用事件无论最终是否成功都会被记录。
+/// 重试期间会阻塞当前线程，在异步命令中须放到 `spawn_blocking` 内调用。
+///
+/// # Arguments
+/// * `path` - 操作涉及的文件路径（用于诊断记录）
+/// * `op` - 文件操作
+pub fn retry_on_lock<T>(path: &Path, mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let (retries, mut delay) = *RETRY_POLICY.lock();
+    let mut attempts = 1;
+    let mut result = op();
+
+    for _ in 0..retries {
+        match &result {
+            Err(e) if is_sharing_violation(e) => {
+                log::debug!(
+                    "文件被占用，{}ms 后重试 ({}/{}): {}",
+                    delay.as_millis(),
+                    attempts,
+                    retries,
+                    path.display()
+                );
+                std::thread::sleep(delay);
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+                attempts += 1;
+                result = op();
+            }
+            _ => break,
+        }
+    }
+
+    if attempts > 1 {
+        let recovered = result.is_ok();
+        if recovered {
+            log::info!("文件占用已解除（尝试 {} 次）: {}", attempts, path.display());
+        } else {
+            log::warn!("文件持续被占用（尝试 {} 次）: {}", attempts, path.display());
+        }
+        record_event(path, attempts, recovered);
+    }
+
+    result
+}
+
+fn record_event(path: &Path, attempts: usize, recovered: bool) {
+    let mut events = LOCK_EVENTS.lock();
+    if events.len() == EVENT_CAPACITY {
+        events.pop_front();
+    }
+    events.push_back(FileLockEvent {
+        path: path.to_string_lossy().to_string(),
+        occurred_at: chrono::Utc::now().timestamp(),
+        attempts,
+        recovered,
+    });
+}
+
+/// 生成 Windows Defender 添加排除项的命令
+fn build_exclusion_command(paths: &[String]) -> Option<String> {
+    if !cfg!(target_os = "windows") || paths.is_empty() {
+        return None;
+    }
+    let quoted: Vec<String> = paths
+        .iter()
+        .map(|path| format!("\"{}\"", path.replace('"', "`\"")))
+        .collect();
+    Some(format!(
+        "Add-MpPreference -ExclusionPath {}",
+        quoted.join(",")
+    ))
+}
+
+/// 获取文件占用诊断信息
+///
+/// 最近 24 小时内多次出现文件被占用（或重试后仍失败）时，
+/// 建议将相关目录加入杀毒软件排除项，并给出 Windows Defender 的配置命令。
+///
+/// # Returns
+/// * `FileLockDiagnostics` - 诊断结果
+#[tauri::command]
+pub fn get_file_lock_diagnostics() -> FileLockDiagnostics {
+    let _timer = CommandTimer::start("get_file_lock_diagnostics");
+    let events: Vec<FileLockEvent> = LOCK_EVENTS.lock().iter().rev().cloned().collect();
+    let since = chrono::Utc::now().timestamp() - DIAGNOSTIC_WINDOW_SECS;
+    let recent: Vec<&FileLockEvent> = events
+        .iter()
+        .filter(|event| event.occurred_at >= since)
+        .collect();
+    let unrecovered_count = recent.iter().filter(|event| !event.recovered).count();
+    let suggest_exclusion = recent.len() >= SUGGEST_EXCLUSION_THRESHOLD || unrecovered_count > 0;
+
+    let suggested_paths: Vec<String> = if suggest_exclusion {
+        recent
+            .iter()
+            .filter_map(|event| Path::new(&event.path).parent())
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    FileLockDiagnostics {
+        recent_count: recent.len(),
+        unrecovered_count,
+        suggest_exclusion,
+        exclusion_command: build_exclusion_command(&suggested_paths),
+        suggested_paths,
+        events,
+    }
+}
@@ -1,10 +1,14 @@
 #[cfg(target_os = "windows")]
 use crate::utils::command_ext::CommandGuiExt;
 
+use crate::database::db::DbState;
+use crate::utils::file_lock::retry_on_lock;
+use crate::utils::metrics::CommandTimer;
+use crate::utils::trash::{remove_path, trash_enabled};
 use std::fs;
 use std::path::Path;
 use std::process::Command;
-use tauri::command;
+use tauri::{State, command};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct PortableModeResult {
@@ -22,6 +26,7 @@ pub struct PortableModeResult {
 /// 操作结果
 #[command]
 pub async fn open_directory(dir_path: String) -> Result<(), String> {
+    let _timer = CommandTimer::start("open_directory");
     // 首先检查路径是否存在
     if !Path::new(&dir_path).exists() {
         return Err(format!("路径不存在: {}", dir_path));
@@ -71,6 +76,7 @@ pub async fn open_directory(dir_path: String) -> Result<(), String> {
 /// 判断当前是否为便携模式
 #[command]
 pub fn is_portable_mode() -> PortableModeResult {
+    let _timer = CommandTimer::start("is_portable_mode");
     PortableModeResult {
         is_portable: reina_path::is_portable_mode(),
     }
@@ -82,6 +88,7 @@ pub fn is_portable_mode() -> PortableModeResult {
 ///
 /// 优先使用 fs::rename，失败则使用 copy + remove
 /// 如果目标文件已存在，会先尝试删除后重试
+/// 文件被杀毒软件等占用时会自动退避重试
 ///
 /// **跨盘策略**：对于跨盘符场景，先完整复制文件，成功后再删除源文件
 pub fn move_file(from: &Path, to: &Path) -> Result<(), String> {
@@ -91,7 +98,7 @@ pub fn move_file(from: &Path, to: &Path) -> Result<(), String> {
     }
 
     // 尝试使用 rename（性能最好，适用于同盘符）
-    match fs::rename(from, to) {
+    match retry_on_lock(from, || fs::rename(from, to)) {
         Ok(_) => {
             log::debug!("已移动文件(rename): {} -> {}", from.display(), to.display());
             Ok(())
@@ -103,15 +110,17 @@ pub fn move_file(from: &Path, to: &Path) -> Result<(), String> {
             // 如果目标文件已存在，先尝试删除
             if to.exists() {
                 log::warn!("目标文件已存在，尝试删除: {}", to.display());
-                fs::remove_file(to)
+                retry_on_lock(to, || fs::remove_file(to))
                     .map_err(|e| format!("无法删除已存在的目标文件 {}: {}", to.display(), e))?;
             }
 
             // 复制文件
-            fs::copy(from, to).map_err(|e| format!("复制文件失败: {}", e))?;
+            retry_on_lock(from, || fs::copy(from, to))
+                .map_err(|e| format!("复制文件失败: {}", e))?;
 
             // 复制成功，删除源文件
-            fs::remove_file(from).map_err(|e| format!("删除源文件失败: {}", e))?;
+            retry_on_lock(from, || fs::remove_file(from))
+                .map_err(|e| format!("删除源文件失败: {}", e))?;
 
             log::debug!(
                 "已移动文件(copy+remove): {} -> {}",
@@ -141,7 +150,6 @@ pub fn move_file(from: &Path, to: &Path) -> Result<(), String> {
 ///
 /// # Returns
 /// * `Result<usize, String>` - 成功移动的文件数量或错误消息
-#[allow(dead_code)]
 pub fn move_dir_recursive(from: &Path, to: &Path) -> Result<usize, String> {
     // 尝试使用 rename（同盘符时性能最好）
     match fs::rename(from, to) {
@@ -188,7 +196,7 @@ pub fn move_dir_recursive(from: &Path, to: &Path) -> Result<usize, String> {
             }
 
             // 第二阶段：所有文件复制成功，删除源目录
-            fs::remove_dir_all(from).map_err(|e| {
+            retry_on_lock(from, || fs::remove_dir_all(from)).map_err(|e| {
                 format!(
                     "所有文件已复制到目标位置，但删除源目录失败: {}\n源目录: {}\n目标目录: {}\n请手动删除源目录",
                     e,
@@ -216,7 +224,6 @@ pub fn move_dir_recursive(from: &Path, to: &Path) -> Result<usize, String> {
 ///
 /// # Returns
 /// * `Result<usize, String>` - 成功复制的文件数量或致命错误
-#[allow(dead_code)]
 fn copy_dir_with_error_collection(
     from: &Path,
     to: &Path,
@@ -259,7 +266,7 @@ fn copy_dir_with_error_collection(
             }
         } else {
             // 复制文件
-            match fs::copy(&entry_path, &target_path) {
+            match retry_on_lock(&entry_path, || fs::copy(&entry_path, &target_path)) {
                 Ok(_) => {
                     copied_count += 1;
                     log::debug!(
@@ -283,7 +290,6 @@ fn copy_dir_with_error_collection(
 }
 
 /// 统计目录中的文件数量（递归）
-#[allow(dead_code)]
 fn count_files_in_dir(dir: &Path) -> Result<usize, String> {
     let mut count = 0;
 
@@ -307,30 +313,48 @@ fn count_files_in_dir(dir: &Path) -> Result<usize, String> {
 
 #[command]
 pub async fn copy_file(src: String, dst: String) -> Result<(), String> {
-    let src_path = Path::new(&src);
-    let dst_path = Path::new(&dst);
-
-    if !src_path.exists() {
-        return Err(format!("源文件不存在: {}", src));
-    }
+    let _timer = CommandTimer::start("copy_file");
+    // 文件被占用时会阻塞重试，放到阻塞线程中执行
+    tokio::task::spawn_blocking(move || {
+        let src_path = Path::new(&src);
+        let dst_path = Path::new(&dst);
+
+        if !src_path.exists() {
+            return Err(format!("源文件不存在: {}", src));
+        }
 
-    if let Some(parent) = dst_path.parent()
-        && !parent.exists()
-    {
-        fs::create_dir_all(parent).map_err(|e| format!("无法创建目标目录的父目录: {}", e))?;
-    }
-    fs::copy(src_path, dst_path).map_err(|e| format!("无法复制文件: {}", e))?;
-    Ok(())
+        if let Some(parent) = dst_path.parent()
+            && !parent.exists()
+        {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建目标目录的父目录: {}", e))?;
+        }
+        retry_on_lock(src_path, || fs::copy(src_path, dst_path))
+            .map_err(|e| format!("无法复制文件: {}", e))?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("无法复制文件: {}", e))?
 }
 
-/// 删除文件
+/// 删除文件，按 user.use_trash 设置移到回收站或永久删除
+///
+/// 系统临时目录中的文件（如粘贴封面时生成的临时图片）由应用自己创建，总是永久删除，
+/// 不在回收站中留下无用文件。
 #[command]
-pub async fn delete_file(file_path: String) -> Result<(), String> {
+pub async fn delete_file(db: State<'_, DbState>, file_path: String) -> Result<(), String> {
+    let _timer = CommandTimer::start("delete_file");
     let path = Path::new(&file_path);
     if !path.exists() {
         return Ok(()); // 文件不存在，视为成功
     }
+    if !path.is_file() {
+        return Err(format!("不是文件: {}", file_path));
+    }
 
-    fs::remove_file(path).map_err(|e| format!("无法删除文件: {}", e))?;
+    let use_trash = !path.starts_with(std::env::temp_dir()) && trash_enabled(&db.get()).await;
+    tokio::task::spawn_blocking(move || remove_path(Path::new(&file_path), use_trash))
+        .await
+        .map_err(|e| format!("无法删除文件: {}", e))?
+        .map_err(|e| format!("无法删除文件: {}", e))?;
     Ok(())
 }
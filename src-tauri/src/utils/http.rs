@@ -1,3 +1,4 @@
+use crate::utils::metrics::CommandTimer;
 use serde::Deserialize;
 use std::sync::{OnceLock, RwLock};
 use std::time::Duration;
@@ -22,6 +23,7 @@ static GLOBAL_HTTP_CLIENT: OnceLock<RwLock<Client>> = OnceLock::new();
 
 #[tauri::command]
 pub fn update_proxy_config(config: ProxyConfig) -> Result<(), String> {
+    let _timer = CommandTimer::start("update_proxy_config");
     let client = build_client(config.url.trim())?;
     let mut guard = http_client()
         .write()
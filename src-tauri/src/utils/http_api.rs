@@ -0,0 +1,487 @@
+//! 局域网 HTTP API
+//!
+//! 开启设置中的 `http_api` 后，在本机端口上提供一组简单的 JSON 接口，方便在同一局域网的手机上
+//! 浏览游戏库、查看统计并远程启动游戏。所有接口都要求携带设置中的访问令牌
+//! `Authorization: Bearer <token>`；只读的封面接口另外接受查询参数 `?token=`，供 `<img>` 直接加载。
+//!
+//! - `GET /api/games` 游戏列表（名称、状态、时长与封面地址），`?lang=zh-CN` 时优先显示中文名
+//! - `GET /api/games/{id}` 单个游戏的完整记录与统计
+//! - `GET /api/games/{id}/cover` 本地封面（自定义封面或已缓存的云端封面）
+//! - `GET /api/stats` 游戏库总览
+//! - `POST /api/games/{id}/launch` 启动游戏
+//!
+//! 除启动游戏外，接口都不会修改数据。
+
+use crate::database::db::DbState;
+use crate::database::dto::UpdateSettingsData;
+use crate::database::repository::game_stats_repository::GameStatsRepository;
+use crate::database::repository::games_repository::{
+    GameType, GamesRepository, SortOption, SortOrder,
+};
+use crate::database::repository::settings_repository::{DbSettingsExt, SettingsRepository};
+use crate::database::service::emit_settings_changed;
+use crate::entity::http_api_settings::HttpApiSettings;
+use crate::game::cover::resolve_local_cover;
+use crate::game::launch::launch_game_by_id;
+use crate::utils::image::content_type_for_file;
+use crate::utils::metrics::CommandTimer;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use hyper::server::conn::http1;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use parking_lot::Mutex;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tower_http::cors::{Any, CorsLayer};
+
+/// 未设置端口时的默认监听端口
+pub const DEFAULT_HTTP_API_PORT: u16 = 17380;
+
+struct RunningServer {
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+/// 当前运行的服务；重新应用设置时先停止旧服务
+static SERVER: Mutex<Option<RunningServer>> = Mutex::new(None);
+
+type ApiResponse = Response;
+
+/// 接口处理函数共享的状态
+#[derive(Clone)]
+struct ApiState {
+    app: AppHandle,
+}
+
+/// `get_http_api_status` / `apply_http_api_settings` 的返回结果
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpApiStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+}
+
+/// 游戏列表中的一项
+#[derive(Debug, Serialize)]
+struct GameSummary {
+    id: i32,
+    name: Option<String>,
+    /// 游玩状态（games.clear，1-5）
+    play_status: Option<i32>,
+    /// 总游戏时长（分钟）
+    total_time: i32,
+    last_played: Option<i32>,
+    /// 封面接口地址（相对路径，需要同样携带令牌）
+    cover: String,
+}
+
+/// `GET /api/games` 的查询参数
+#[derive(Debug, Deserialize)]
+struct GamesQuery {
+    lang: Option<String>,
+}
+
+/// 请求携带的令牌：`Authorization: Bearer`，`allow_query` 时也接受查询参数 `token`
+fn request_token(
+    authorization: Option<&str>,
+    query: Option<&str>,
+    allow_query: bool,
+) -> Option<String> {
+    let bearer = authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    if bearer.is_some() || !allow_query {
+        return bearer;
+    }
+    query.and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.into_owned())
+    })
+}
+
+/// 比较令牌，耗时与令牌内容无关
+fn token_matches(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 24];
+    getrandom::fill(&mut bytes).map_err(|e| format!("生成访问令牌失败: {}", e))?;
+
+    let mut token = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(&mut token, "{byte:02x}").map_err(|e| format!("生成访问令牌失败: {}", e))?;
+    }
+    Ok(token)
+}
+
+fn build_response(status: StatusCode, content_type: &str, body: Vec<u8>) -> ApiResponse {
+    (status, [(CONTENT_TYPE, content_type)], body).into_response()
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> ApiResponse {
+    (status, Json(value)).into_response()
+}
+
+fn error_response(status: StatusCode, message: String) -> ApiResponse {
+    json_response(status, &serde_json::json!({ "error": message }))
+}
+
+/// 校验访问令牌，通过后交给接口处理
+async fn check_token(
+    expected: &str,
+    allow_query: bool,
+    request: Request,
+    next: Next,
+) -> ApiResponse {
+    let authorization = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    let authorized = request_token(authorization, request.uri().query(), allow_query)
+        .is_some_and(|provided| token_matches(expected, &provided));
+    if !authorized {
+        return error_response(StatusCode::UNAUTHORIZED, "访问令牌无效".to_string());
+    }
+    next.run(request).await
+}
+
+/// 只接受 `Authorization: Bearer` 令牌
+async fn require_bearer(
+    State(token): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> ApiResponse {
+    check_token(&token, false, request, next).await
+}
+
+/// 同时接受查询参数中的令牌，仅用于只读的封面接口
+async fn require_token_or_query(
+    State(token): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> ApiResponse {
+    check_token(&token, true, request, next).await
+}
+
+/// 取得数据库连接，应用启动阶段数据库可能尚未就绪
+fn database(app: &AppHandle) -> Option<DatabaseConnection> {
+    app.try_state::<DbState>().map(|state| state.get())
+}
+
+fn database_unavailable() -> ApiResponse {
+    error_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "数据库尚未就绪".to_string(),
+    )
+}
+
+async fn load_games(db: &DatabaseConnection, use_cn: bool) -> Result<Vec<GameSummary>, String> {
+    let games = GamesRepository::find_all(
+        db,
+        GameType::All,
+        None,
+        SortOption::Addtime,
+        SortOrder::Desc,
+        None,
+    )
+    .await
+    .map_err(|e| format!("获取游戏列表失败: {}", e))?;
+    let statistics: HashMap<i32, _> = GameStatsRepository::get_all_statistics(db)
+        .await
+        .map_err(|e| format!("获取游戏统计失败: {}", e))?
+        .into_iter()
+        .map(|stats| (stats.game_id, stats))
+        .collect();
+
+    Ok(games
+        .iter()
+        .map(|game| {
+            let stats = statistics.get(&game.id);
+            GameSummary {
+                id: game.id,
+                name: GamesRepository::get_display_name(game, use_cn).map(str::to_string),
+                play_status: game.clear,
+                total_time: stats.and_then(|stats| stats.total_time).unwrap_or(0),
+                last_played: stats.and_then(|stats| stats.last_played),
+                cover: format!("/api/games/{}/cover", game.id),
+            }
+        })
+        .collect())
+}
+
+async fn list_games(State(state): State<ApiState>, Query(query): Query<GamesQuery>) -> ApiResponse {
+    let Some(db) = database(&state.app) else {
+        return database_unavailable();
+    };
+    let use_cn = query.lang.as_deref() == Some("zh-CN");
+    match load_games(&db, use_cn).await {
+        Ok(games) => json_response(StatusCode::OK, &games),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+async fn library_stats(State(state): State<ApiState>) -> ApiResponse {
+    let Some(db) = database(&state.app) else {
+        return database_unavailable();
+    };
+    match GameStatsRepository::get_library_overview(&db).await {
+        Ok(overview) => json_response(StatusCode::OK, &overview),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("获取游戏库总览失败: {}", e),
+        ),
+    }
+}
+
+async fn game_detail(State(state): State<ApiState>, Path(game_id): Path<i32>) -> ApiResponse {
+    let Some(db) = database(&state.app) else {
+        return database_unavailable();
+    };
+    let game = match GamesRepository::find_by_id(&db, game_id).await {
+        Ok(Some(game)) => game,
+        Ok(None) => {
+            return error_response(StatusCode::NOT_FOUND, format!("游戏不存在: {}", game_id));
+        }
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("查询游戏失败: {}", e),
+            );
+        }
+    };
+    match GameStatsRepository::get_statistics(&db, game_id).await {
+        Ok(statistics) => json_response(
+            StatusCode::OK,
+            &serde_json::json!({ "game": game, "statistics": statistics }),
+        ),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("获取游戏统计失败: {}", e),
+        ),
+    }
+}
+
+async fn game_cover(State(state): State<ApiState>, Path(game_id): Path<i32>) -> ApiResponse {
+    let Some(db) = database(&state.app) else {
+        return database_unavailable();
+    };
+    let game = match GamesRepository::find_by_id(&db, game_id).await {
+        Ok(Some(game)) => game,
+        Ok(None) => {
+            return error_response(StatusCode::NOT_FOUND, format!("游戏不存在: {}", game_id));
+        }
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("查询游戏失败: {}", e),
+            );
+        }
+    };
+    let path = match resolve_local_cover(&game).await {
+        Ok(Some(path)) => path,
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, "游戏没有本地封面".to_string()),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => build_response(StatusCode::OK, content_type_for_file(&path), bytes),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("读取封面失败: {}", e),
+        ),
+    }
+}
+
+async fn launch(State(state): State<ApiState>, Path(game_id): Path<i32>) -> ApiResponse {
+    let Ok(game_id) = u32::try_from(game_id) else {
+        return error_response(StatusCode::NOT_FOUND, format!("游戏不存在: {}", game_id));
+    };
+    log::info!("通过 HTTP API 启动游戏 game_id={}", game_id);
+
+    match launch_game_by_id(&state.app, game_id).await {
+        Ok(result) => json_response(StatusCode::OK, &result),
+        Err(e) => json_response(StatusCode::INTERNAL_SERVER_ERROR, &e),
+    }
+}
+
+fn router(app: AppHandle, token: Arc<str>) -> Router {
+    // 启动游戏等接口只接受请求头中的令牌，避免令牌随链接出现在日志或浏览记录中
+    let api = Router::new()
+        .route("/api/games", get(list_games))
+        .route("/api/games/{id}", get(game_detail))
+        .route("/api/stats", get(library_stats))
+        .route("/api/games/{id}/launch", post(launch))
+        .route_layer(middleware::from_fn_with_state(
+            token.clone(),
+            require_bearer,
+        ));
+    let cover = Router::new()
+        .route("/api/games/{id}/cover", get(game_cover))
+        .route_layer(middleware::from_fn_with_state(
+            token,
+            require_token_or_query,
+        ));
+
+    api.merge(cover)
+        .fallback(|| async { error_response(StatusCode::NOT_FOUND, "接口不存在".to_string()) })
+        // 浏览器跨域预检不携带令牌，由 CORS 层直接应答
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods([Method::GET, Method::POST])
+                .allow_headers([AUTHORIZATION, CONTENT_TYPE]),
+        )
+        .with_state(ApiState { app })
+}
+
+async fn serve(app: AppHandle, listener: tokio::net::TcpListener, token: Arc<str>) {
+    let router = router(app, token);
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                log::warn!("HTTP API 接受连接失败: {}", e);
+                continue;
+            }
+        };
+        let service = TowerToHyperService::new(router.clone());
+        tauri::async_runtime::spawn(async move {
+            // 不保持长连接，停止服务后不会有连接继续占用
+            if let Err(e) = http1::Builder::new()
+                .keep_alive(false)
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                log::debug!("HTTP API 连接异常结束 peer={}: {}", peer, e);
+            }
+        });
+    }
+}
+
+fn stop_server() {
+    if let Some(server) = SERVER.lock().take() {
+        server.task.abort();
+        log::info!("HTTP API 已停止 port={}", server.port);
+    }
+}
+
+fn current_status() -> HttpApiStatus {
+    let server = SERVER.lock();
+    HttpApiStatus {
+        running: server.is_some(),
+        port: server.as_ref().map(|server| server.port),
+    }
+}
+
+/// 按当前设置启动或停止 HTTP API
+///
+/// 开启时没有访问令牌会先生成一个并写入设置。
+pub async fn apply_settings(app: &AppHandle) -> Result<HttpApiStatus, String> {
+    let db = app
+        .try_state::<DbState>()
+        .map(|state| state.get())
+        .ok_or_else(|| "数据库尚未就绪".to_string())?;
+    let settings = db.get_settings().await?.http_api.unwrap_or_default();
+
+    stop_server();
+    if settings.enabled != Some(true) {
+        return Ok(current_status());
+    }
+
+    let token = match settings.token.as_deref().map(str::trim) {
+        Some(token) if !token.is_empty() => token.to_string(),
+        _ => {
+            let token = generate_token()?;
+            SettingsRepository::update_settings(
+                &db,
+                UpdateSettingsData {
+                    http_api: Some(Some(HttpApiSettings {
+                        token: Some(token.clone()),
+                        ..settings.clone()
+                    })),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| format!("保存访问令牌失败: {}", e))?;
+            emit_settings_changed(app, vec!["httpApi"]);
+            token
+        }
+    };
+
+    let port = settings.port.unwrap_or(DEFAULT_HTTP_API_PORT);
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)))
+        .await
+        .map_err(|e| format!("无法监听端口 {}: {}", port, e))?;
+    let task = tauri::async_runtime::spawn(serve(app.clone(), listener, Arc::from(token)));
+    *SERVER.lock() = Some(RunningServer { port, task });
+
+    log::info!("HTTP API 已启动 port={}", port);
+    Ok(current_status())
+}
+
+/// 启动时按设置开启 HTTP API，失败只记录日志
+pub async fn init_http_api(app: AppHandle) {
+    if let Err(e) = apply_settings(&app).await {
+        log::warn!("启动 HTTP API 失败: {}", e);
+    }
+}
+
+/// 按当前设置重新启动或停止 HTTP API，修改 `http_api` 设置后调用
+///
+/// # Returns
+/// * `Result<HttpApiStatus, String>` - 应用后的运行状态
+#[tauri::command]
+pub async fn apply_http_api_settings(app: AppHandle) -> Result<HttpApiStatus, String> {
+    let _timer = CommandTimer::start("apply_http_api_settings");
+    apply_settings(&app).await
+}
+
+/// 获取 HTTP API 的运行状态
+#[tauri::command]
+pub fn get_http_api_status() -> HttpApiStatus {
+    let _timer = CommandTimer::start("get_http_api_status");
+    current_status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{request_token, token_matches};
+
+    #[test]
+    fn query_token_only_where_allowed() {
+        let query = Some("token=abc%20d&lang=zh-CN");
+        assert_eq!(request_token(None, query, true).as_deref(), Some("abc d"));
+        assert_eq!(request_token(None, query, false), None);
+        assert_eq!(
+            request_token(Some("Bearer xyz"), query, true).as_deref(),
+            Some("xyz")
+        );
+        assert_eq!(
+            request_token(Some("Bearer xyz"), None, false).as_deref(),
+            Some("xyz")
+        );
+        assert_eq!(request_token(Some("Basic xyz"), None, true), None);
+
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secret", "secreT"));
+        assert!(!token_matches("secret", "secret2"));
+    }
+}
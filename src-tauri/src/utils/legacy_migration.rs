@@ -4,6 +4,7 @@ use std::time::SystemTime;
 
 use reina_path::{get_base_data_dir, get_base_data_dir_for_mode};
 
+use crate::utils::file_lock::retry_on_lock;
 use crate::utils::fs::move_file;
 
 #[derive(Debug, Default)]
@@ -39,8 +40,8 @@ fn run_startup_migration(
 }
 
 fn m20260326_000001_migrate_legacy_covers() -> Result<StartupMigrationResult, String> {
-    let legacy_covers_dir = get_base_data_dir_for_mode(true)?.join("covers");
-    let current_covers_dir = get_base_data_dir()?.join("covers");
+    let legacy_covers_dir = get_base_data_dir_for_mode(true)?.join(reina_path::COVERS_SUBDIR);
+    let current_covers_dir = get_base_data_dir()?.join(reina_path::COVERS_SUBDIR);
 
     if current_covers_dir == legacy_covers_dir || !legacy_covers_dir.exists() {
         return Ok(StartupMigrationResult {
@@ -127,13 +128,13 @@ fn merge_covers_dir(
         let to_modified = file_modified_time(&to_path)?;
 
         if from_modified > to_modified {
-            fs::remove_file(&to_path)
+            retry_on_lock(&to_path, || fs::remove_file(&to_path))
                 .map_err(|e| format!("删除旧目标文件失败 {}: {}", to_path.display(), e))?;
             move_file(&from_path, &to_path)?;
             result.migrated_files += 1;
             result.replaced_files += 1;
         } else {
-            fs::remove_file(&from_path)
+            retry_on_lock(&from_path, || fs::remove_file(&from_path))
                 .map_err(|e| format!("删除 legacy 重复文件失败 {}: {}", from_path.display(), e))?;
             result.removed_legacy_files += 1;
         }
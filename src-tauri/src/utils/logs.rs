@@ -1,6 +1,25 @@
+use crate::database::db::{DbState, close_connection, establish_connection};
+use crate::database::dto::UpdateSettingsData;
+use crate::database::repository::settings_repository::{DbSettingsExt, SettingsRepository};
+use crate::entity::log_settings::LogSettings;
+use crate::entity::prelude::User;
+use crate::utils::fs::open_directory;
+use crate::utils::metrics::CommandTimer;
+use sea_orm::EntityTrait;
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, State};
 
-#[derive(Serialize, Deserialize, Debug)]
+/// 单次最多返回的日志条数
+const MAX_LOG_ENTRIES: usize = 2000;
+
+/// 未指定数量时返回的日志条数
+const DEFAULT_LOG_ENTRIES: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Error,
@@ -11,23 +30,52 @@ pub enum LogLevel {
     Off,
 }
 
-/// 动态设置日志输出级别（不持久化）
+/// 设置日志输出级别，立即生效并保存到设置中，下次启动时沿用
 #[tauri::command]
-pub fn set_reina_log_level(level: String) -> Result<(), String> {
-    let lf = match level.to_lowercase().as_str() {
-        "error" => log::LevelFilter::Error,
-        "warn" => log::LevelFilter::Warn,
-        "info" => log::LevelFilter::Info,
-        "debug" => log::LevelFilter::Debug,
-        other => return Err(format!("无效的日志级别: {}", other)),
-    };
+pub async fn set_reina_log_level(db: State<'_, DbState>, level: String) -> Result<(), String> {
+    let _timer = CommandTimer::start("set_reina_log_level");
+    let level = level.to_lowercase();
+    let lf = LogSettings {
+        level: Some(level.clone()),
+        ..Default::default()
+    }
+    .level_filter()
+    .ok_or_else(|| format!("无效的日志级别: {}", level))?;
     log::set_max_level(lf);
-    Ok(())
+
+    let db = db.get();
+    let current = db.get_settings().await?.log_settings.unwrap_or_default();
+    SettingsRepository::update_settings(
+        &db,
+        UpdateSettingsData {
+            log_settings: Some(Some(LogSettings {
+                level: Some(level),
+                ..current
+            })),
+            ..Default::default()
+        },
+    )
+    .await
+    .map_err(|e| format!("保存日志级别失败: {}", e))
+}
+
+/// 启动时读取日志设置，需在初始化日志插件之前调用
+///
+/// 此时数据库迁移尚未执行，首次启动或新增的列还不存在时读取失败，使用默认设置。
+pub fn load_log_settings() -> LogSettings {
+    tauri::async_runtime::block_on(async {
+        let conn = establish_connection().await.ok()?;
+        let settings = User::find_by_id(1).one(&conn).await;
+        let _ = close_connection(conn).await;
+        settings.ok().flatten()?.log_settings
+    })
+    .unwrap_or_default()
 }
 
 /// 获取当前日志级别
 #[tauri::command]
 pub fn get_reina_log_level() -> LogLevel {
+    let _timer = CommandTimer::start("get_reina_log_level");
     let level = log::max_level();
     match level {
         log::LevelFilter::Error => LogLevel::Error,
@@ -38,3 +86,266 @@ pub fn get_reina_log_level() -> LogLevel {
         log::LevelFilter::Off => LogLevel::Off,
     }
 }
+
+impl LogLevel {
+    fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warn" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            "off" => Some(Self::Off),
+            _ => None,
+        }
+    }
+}
+
+/// 日志文件中的一条记录
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// 本地时间，格式为 `YYYY-MM-DD HH:MM:SS`
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+}
+
+/// 去掉行首 `[...]` 中的内容，返回内容与剩余部分
+fn take_bracket(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    Some((&rest[..end], &rest[end + 1..]))
+}
+
+/// 解析 tauri-plugin-log 写入的一行：`[日期][时间][级别][模块] 消息`
+fn parse_log_line(line: &str) -> Option<LogEntry> {
+    let (date, rest) = take_bracket(line)?;
+    let (time, rest) = take_bracket(rest)?;
+    let (level, rest) = take_bracket(rest)?;
+    let (target, message) = take_bracket(rest)?;
+    let level = LogLevel::parse(level).filter(|level| *level != LogLevel::Off)?;
+    if date.len() != 10 || time.len() != 8 {
+        return None;
+    }
+    Some(LogEntry {
+        timestamp: format!("{} {}", date, time),
+        level,
+        target: target.to_string(),
+        message: message.strip_prefix(' ').unwrap_or(message).to_string(),
+    })
+}
+
+/// 解析日志文件内容，不符合格式的行视为上一条记录的续行（多行消息）
+fn parse_log_content(content: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for line in content.lines() {
+        match parse_log_line(line) {
+            Some(entry) => entries.push(entry),
+            None => {
+                if let Some(last) = entries.last_mut() {
+                    last.message.push('\n');
+                    last.message.push_str(line);
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// 是否为轮转后的旧日志文件（`<名称>_YYYY-MM-DD_HH-MM-SS.log`，或同名冲突时追加的 `.bak`）
+fn is_rotated_log(file_name: &str) -> bool {
+    let Some(stem) = file_name
+        .strip_suffix(".log.bak")
+        .or_else(|| file_name.strip_suffix(".log"))
+    else {
+        return false;
+    };
+    // 后缀形如 `_2026-10-14_18-06-17`
+    let Some(suffix) = stem.len().checked_sub(20).and_then(|at| stem.get(at..)) else {
+        return false;
+    };
+    suffix.bytes().enumerate().all(|(i, b)| match i {
+        0 | 11 => b == b'_',
+        5 | 8 | 14 | 17 => b == b'-',
+        _ => b.is_ascii_digit(),
+    })
+}
+
+pub fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_log_dir()
+        .map_err(|e| format!("获取日志目录失败: {}", e))
+}
+
+/// 日志目录中的日志文件，按修改时间从新到旧排列
+fn log_files(dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<(PathBuf, SystemTime)> = fs::read_dir(dir)
+        .map_err(|e| format!("读取日志目录失败: {}", e))?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.ends_with(".log") || name.ends_with(".log.bak")
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((
+                entry.path(),
+                metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            ))
+        })
+        .collect();
+    files.sort_by_key(|(_, modified)| Reverse(*modified));
+    Ok(files)
+}
+
+/// 最新日志文件的最后 `count` 行，供崩溃报告使用
+pub fn tail_log_lines(dir: &Path, count: usize) -> Vec<String> {
+    let Some((path, _)) = log_files(dir)
+        .ok()
+        .and_then(|files| files.into_iter().next())
+    else {
+        return Vec::new();
+    };
+    let Ok(bytes) = fs::read(path) else {
+        return Vec::new();
+    };
+    let content = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// 读取最近的日志记录，按时间从新到旧返回
+///
+/// # Arguments
+/// * `level` - 最低级别（如 `warn` 返回 warn 与 error），未指定时返回全部
+/// * `limit` - 返回条数，默认 200，最多 2000
+#[tauri::command]
+pub async fn get_recent_logs(
+    app: AppHandle,
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    let _timer = CommandTimer::start("get_recent_logs");
+    let min_level = match level.as_deref() {
+        Some(level) => Some(
+            LogLevel::parse(level)
+                .filter(|level| *level != LogLevel::Off)
+                .ok_or_else(|| format!("无效的日志级别: {}", level))?,
+        ),
+        None => None,
+    };
+    let limit = limit
+        .unwrap_or(DEFAULT_LOG_ENTRIES)
+        .clamp(1, MAX_LOG_ENTRIES);
+    let dir = log_dir(&app)?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut entries = Vec::with_capacity(limit);
+        for (path, _) in log_files(&dir)? {
+            // 日志文件不一定是合法 UTF-8（如被截断的多字节字符），按有损方式读取
+            let content = match fs::read(&path) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) => {
+                    log::debug!("读取日志文件 {} 失败: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let remaining = limit - entries.len();
+            entries.extend(
+                parse_log_content(&content)
+                    .into_iter()
+                    .rev()
+                    .filter(|entry| min_level.is_none_or(|min| entry.level <= min))
+                    .take(remaining),
+            );
+            if entries.len() >= limit {
+                break;
+            }
+        }
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| format!("读取日志失败: {}", e))?
+}
+
+/// 在文件管理器中打开日志目录
+#[tauri::command]
+pub async fn open_log_directory(app: AppHandle) -> Result<(), String> {
+    let _timer = CommandTimer::start("open_log_directory");
+    let dir = log_dir(&app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建日志目录失败: {}", e))?;
+    open_directory(dir.to_string_lossy().to_string()).await
+}
+
+/// 删除修改时间早于指定天数的轮转日志，正在写入的日志文件不受影响
+///
+/// # Returns
+/// * `Result<usize, String>` - 删除的文件数量
+#[tauri::command]
+pub async fn clear_old_logs(app: AppHandle, days: u32) -> Result<usize, String> {
+    let _timer = CommandTimer::start("clear_old_logs");
+    let dir = log_dir(&app)?;
+    let cutoff = SystemTime::now()
+        .checked_sub(Duration::from_secs(u64::from(days) * 24 * 60 * 60))
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut removed = 0;
+    for (path, modified) in log_files(&dir)? {
+        let rotated = path
+            .file_name()
+            .is_some_and(|name| is_rotated_log(&name.to_string_lossy()));
+        if !rotated || modified > cutoff {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("删除日志文件 {} 失败: {}", path.display(), e),
+        }
+    }
+    log::info!("已清理 {} 个旧日志文件", removed);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_log_lines_with_continuations() {
+        let content = "[2026-10-14][18:06:17][INFO][reina_manager::db] 数据库迁移完成\n\
+                       [2026-10-14][18:06:18][ERROR][reina::x] 失败: a\n\
+                       第二行\n";
+        let entries = parse_log_content(content);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].timestamp, "2026-10-14 18:06:17");
+        assert_eq!(entries[0].level, LogLevel::Info);
+        assert_eq!(entries[0].target, "reina_manager::db");
+        assert_eq!(entries[0].message, "数据库迁移完成");
+        assert_eq!(entries[1].level, LogLevel::Error);
+        assert_eq!(entries[1].message, "失败: a\n第二行");
+        assert!(parse_log_line("plain text").is_none());
+    }
+
+    #[test]
+    fn detects_rotated_log_files() {
+        assert!(is_rotated_log("ReinaManager_2026-10-14_18-06-17.log"));
+        assert!(is_rotated_log("debug_2026-10-14_18-06-17.log.bak"));
+        assert!(!is_rotated_log("ReinaManager.log"));
+        assert!(!is_rotated_log("debug.log"));
+        assert!(!is_rotated_log("other_2026-10-14.log"));
+    }
+
+    #[test]
+    fn orders_levels_by_severity() {
+        assert!(LogLevel::Error < LogLevel::Warn);
+        assert!(LogLevel::Debug < LogLevel::Trace);
+    }
+}
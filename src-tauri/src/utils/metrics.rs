@@ -0,0 +1,233 @@
+//! 命令调用与数据库查询的耗时统计
+//!
+//! 每个命令保留最近若干次调用的耗时（环形缓冲），数据库查询只保留最近的慢查询，
+//! 用于区分"库很卡"到底是数据库延迟、IPC 开销还是前端渲染。
+//!
+//! Tauri 在派生的任务中执行 async 命令，`invoke_handler` 返回时命令尚未完成，无法在分发处计时。
+//! 每个命令在函数体开头创建 [`CommandTimer`]，由它在命令的 future 中计时；
+//! 与前端记录的往返耗时相减即为 IPC 与序列化开销。
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// 每个命令保留的最近调用数量
+const COMMAND_SAMPLE_CAPACITY: usize = 64;
+
+/// 保留的慢查询数量
+const SLOW_QUERY_CAPACITY: usize = 64;
+
+/// 超过该耗时的查询才会被记录
+const SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(20);
+
+/// 记录的 SQL 最大长度（字符）
+const MAX_STATEMENT_CHARS: usize = 500;
+
+#[derive(Default)]
+struct CommandStats {
+    count: u64,
+    total: Duration,
+    max: Duration,
+    recent: VecDeque<Duration>,
+}
+
+static COMMAND_STATS: LazyLock<Mutex<HashMap<String, CommandStats>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static SLOW_QUERIES: Mutex<VecDeque<SlowQuery>> = Mutex::new(VecDeque::new());
+
+/// 单个命令的耗时统计
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandMetrics {
+    pub command: String,
+    pub count: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    /// 最近调用中最慢的若干次，按耗时降序
+    pub slowest_recent_ms: Vec<f64>,
+}
+
+/// 一次慢查询
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowQuery {
+    pub statement: String,
+    pub elapsed_ms: f64,
+    pub failed: bool,
+    /// 记录时间（Unix 时间戳，毫秒）
+    pub recorded_at: i64,
+}
+
+/// 性能统计快照
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceMetrics {
+    /// 按最大耗时降序
+    pub commands: Vec<CommandMetrics>,
+    /// 按耗时降序
+    pub slow_queries: Vec<SlowQuery>,
+}
+
+fn to_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// 命令耗时计时器
+///
+/// 在命令函数体开头创建并绑定到变量（`let _timer = CommandTimer::start("命令名");`），
+/// 命令返回、计时器随 future 释放时记录耗时。
+pub struct CommandTimer {
+    command: &'static str,
+    started: Instant,
+}
+
+impl CommandTimer {
+    pub fn start(command: &'static str) -> Self {
+        Self {
+            command,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        record_command(self.command, self.started.elapsed());
+    }
+}
+
+/// 记录一次命令调用耗时
+pub fn record_command(command: &str, elapsed: Duration) {
+    let mut stats = COMMAND_STATS.lock();
+    let entry = stats.entry(command.to_string()).or_default();
+    entry.count += 1;
+    entry.total += elapsed;
+    entry.max = entry.max.max(elapsed);
+    if entry.recent.len() == COMMAND_SAMPLE_CAPACITY {
+        entry.recent.pop_front();
+    }
+    entry.recent.push_back(elapsed);
+}
+
+/// 记录一次数据库查询，未超过阈值且成功的查询直接忽略
+pub fn record_query(statement: impl Display, elapsed: Duration, failed: bool) {
+    if elapsed < SLOW_QUERY_THRESHOLD && !failed {
+        return;
+    }
+
+    let statement = statement.to_string();
+    let statement = if statement.chars().count() > MAX_STATEMENT_CHARS {
+        let truncated: String = statement.chars().take(MAX_STATEMENT_CHARS).collect();
+        format!("{}…", truncated)
+    } else {
+        statement
+    };
+
+    let mut queries = SLOW_QUERIES.lock();
+    if queries.len() == SLOW_QUERY_CAPACITY {
+        queries.pop_front();
+    }
+    queries.push_back(SlowQuery {
+        statement,
+        elapsed_ms: to_ms(elapsed),
+        failed,
+        recorded_at: chrono::Utc::now().timestamp_millis(),
+    });
+}
+
+/// 获取命令与数据库查询的耗时统计
+///
+/// # Arguments
+/// * `limit` - 每个命令返回的最慢调用数量，以及慢查询数量，默认 5
+///
+/// # Returns
+/// * `PerformanceMetrics` - 统计快照
+#[tauri::command]
+pub fn get_performance_metrics(limit: Option<usize>) -> PerformanceMetrics {
+    let _timer = CommandTimer::start("get_performance_metrics");
+    let limit = limit.unwrap_or(5);
+
+    let mut commands: Vec<CommandMetrics> = COMMAND_STATS
+        .lock()
+        .iter()
+        .map(|(command, stats)| {
+            let mut recent: Vec<Duration> = stats.recent.iter().copied().collect();
+            recent.sort_unstable_by(|a, b| b.cmp(a));
+            CommandMetrics {
+                command: command.clone(),
+                count: stats.count,
+                avg_ms: to_ms(stats.total) / stats.count.max(1) as f64,
+                max_ms: to_ms(stats.max),
+                slowest_recent_ms: recent.into_iter().take(limit).map(to_ms).collect(),
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| b.max_ms.total_cmp(&a.max_ms));
+
+    let mut slow_queries: Vec<SlowQuery> = SLOW_QUERIES.lock().iter().cloned().collect();
+    slow_queries.sort_by(|a, b| b.elapsed_ms.total_cmp(&a.elapsed_ms));
+    slow_queries.truncate(limit);
+
+    PerformanceMetrics {
+        commands,
+        slow_queries,
+    }
+}
+
+/// 清空耗时统计
+#[tauri::command]
+pub fn reset_performance_metrics() {
+    let _timer = CommandTimer::start("reset_performance_metrics");
+    COMMAND_STATS.lock().clear();
+    SLOW_QUERIES.lock().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use walkdir::WalkDir;
+
+    /// 读取 lib.rs 中 `generate_handler!` 注册的命令名
+    fn registered_commands(lib: &str) -> Vec<&str> {
+        let start = lib
+            .find("generate_handler![")
+            .expect("lib.rs 中没有 generate_handler!")
+            + "generate_handler![".len();
+        let end = start + lib[start..].find("])").expect("generate_handler! 未闭合");
+        lib[start..end]
+            .lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| !line.is_empty() && !line.starts_with("//"))
+            .map(|line| line.rsplit("::").next().unwrap_or(line))
+            .collect()
+    }
+
+    #[test]
+    fn every_registered_command_starts_a_timer() {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let lib = fs::read_to_string(src.join("lib.rs")).unwrap();
+        let sources: Vec<String> = WalkDir::new(&src)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rs"))
+            .map(|entry| fs::read_to_string(entry.path()).unwrap())
+            .collect();
+
+        let commands = registered_commands(&lib);
+        assert!(!commands.is_empty());
+        let missing: Vec<&str> = commands
+            .into_iter()
+            .filter(|command| {
+                let timer = format!("CommandTimer::start(\"{}\")", command);
+                !sources.iter().any(|source| source.contains(&timer))
+            })
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "以下命令没有创建 CommandTimer: {:?}",
+            missing
+        );
+    }
+}
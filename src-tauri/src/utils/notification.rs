@@ -0,0 +1,142 @@
+//! 系统通知
+//!
+//! 备份完成、时长里程碑、可用更新和后台错误等反馈原本只在前端界面内提示，
+//! 窗口最小化或被遮挡时用户无法看到。这里通过系统通知（Windows 上为 Toast）发送这些消息，
+//! 每类通知可在设置的 `notification_settings` 中单独关闭；主窗口可见且聚焦时不重复发送。
+
+use crate::database::db::DbState;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::notification_settings::NotificationSettings;
+use crate::utils::metrics::CommandTimer;
+use log::{debug, warn};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_notification::NotificationExt;
+
+/// 累计时长每跨过该分钟数发送一次里程碑通知
+const MILESTONE_INTERVAL_MINUTES: i32 = 10 * 60;
+
+/// 通知类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    Backup,
+    SessionMilestone,
+    UpdateAvailable,
+    Error,
+}
+
+impl NotificationCategory {
+    fn is_enabled(self, settings: &NotificationSettings) -> bool {
+        match self {
+            Self::Backup => settings.backup,
+            Self::SessionMilestone => settings.session_milestones,
+            Self::UpdateAvailable => settings.update_available,
+            Self::Error => settings.errors,
+        }
+        .unwrap_or(true)
+    }
+}
+
+/// 主窗口是否可见且聚焦，此时界面内已有提示
+fn is_main_window_active<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.get_webview_window("main").is_some_and(|window| {
+        window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false)
+    })
+}
+
+/// 读取通知开关，读取失败时按默认（全部开启）处理
+async fn load_settings<R: Runtime>(app: &AppHandle<R>) -> NotificationSettings {
+    let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+        return NotificationSettings::default();
+    };
+    match db.get_settings().await {
+        Ok(settings) => settings.notification_settings.unwrap_or_default(),
+        Err(e) => {
+            warn!("读取通知设置失败: {}", e);
+            NotificationSettings::default()
+        }
+    }
+}
+
+/// 发送一条系统通知
+///
+/// 类别被关闭或主窗口处于前台时跳过；发送失败只记录日志，不影响调用方。
+pub async fn notify<R: Runtime>(
+    app: &AppHandle<R>,
+    category: NotificationCategory,
+    title: &str,
+    body: &str,
+) {
+    if !category.is_enabled(&load_settings(app).await) {
+        debug!("通知类别 {:?} 已关闭，跳过: {}", category, title);
+        return;
+    }
+    if is_main_window_active(app) {
+        debug!("主窗口处于前台，跳过系统通知: {}", title);
+        return;
+    }
+
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        warn!("发送系统通知失败: {}", e);
+    }
+}
+
+/// 累计时长跨过整十小时时发送里程碑通知
+///
+/// # Arguments
+/// * `game_id` - 游戏 ID，用于查询通知中显示的游戏名称
+/// * `previous_minutes` / `current_minutes` - 本次更新前后的累计时长（分钟）
+pub async fn notify_playtime_milestone<R: Runtime>(
+    app: &AppHandle<R>,
+    db: &DatabaseConnection,
+    game_id: i32,
+    previous_minutes: i32,
+    current_minutes: i32,
+) {
+    let reached = current_minutes / MILESTONE_INTERVAL_MINUTES;
+    if current_minutes <= previous_minutes
+        || reached <= previous_minutes / MILESTONE_INTERVAL_MINUTES
+    {
+        return;
+    }
+
+    let game_name = match GamesRepository::find_by_id(db, game_id).await {
+        Ok(Some(game)) => GamesRepository::get_display_name(&game, true)
+            .unwrap_or_default()
+            .to_string(),
+        Ok(None) => return,
+        Err(e) => {
+            warn!("获取游戏数据失败: {}", e);
+            return;
+        }
+    };
+    let hours = reached * MILESTONE_INTERVAL_MINUTES / 60;
+    notify(
+        app,
+        NotificationCategory::SessionMilestone,
+        "游玩里程碑",
+        &format!("《{}》累计游玩时间已达到 {} 小时", game_name, hours),
+    )
+    .await;
+}
+
+/// 由前端发送系统通知（如检查到应用更新时），同样遵循通知开关
+///
+/// # Arguments
+/// * `category` - 通知类别
+/// * `title` - 通知标题
+/// * `body` - 通知正文
+#[tauri::command]
+pub async fn send_notification(
+    app: AppHandle,
+    category: NotificationCategory,
+    title: String,
+    body: String,
+) -> Result<(), String> {
+    let _timer = CommandTimer::start("send_notification");
+    notify(&app, category, &title, &body).await;
+    Ok(())
+}
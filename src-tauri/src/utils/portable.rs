@@ -0,0 +1,839 @@
+//! 便携模式切换预览与数据迁移
+//!
+//! 切换便携 / 安装模式需要把整个基础数据目录（数据库、备份、截图等）搬到另一处，
+//! 中途失败会留下两份不完整的数据。`preview_switch` 只读地计算一次迁移会移动哪些文件、需要多少空间，
+//! 并提前检查目标目录是否可写、磁盘空间是否足够，让用户在真正迁移前决定是否继续；
+//! `migrate_data_files` 在预览没有问题时执行迁移。安装模式下还可以用 `set_data_root`
+//! 把数据目录放到其他磁盘，新位置记录在程序目录下的指针文件中。
+
+use crate::database::db::{DbState, close_connection, reconnect};
+use crate::database::repository::launch_attempts_repository::LaunchAttemptsRepository;
+use crate::database::repository::screenshots_repository::ScreenshotsRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::entity::user;
+use crate::game::monitor::active_sessions;
+use crate::utils::fs::move_dir_recursive;
+use crate::utils::metrics::CommandTimer;
+use reina_path::{
+    ASSET_SUBDIRS, BACKUP_SUBDIR, COVERS_SUBDIR, CRASH_SUBDIR, DB_DATA_DIR, DB_FILE_NAME,
+    LAUNCH_LOG_SUBDIR, SCREENSHOT_SUBDIR, get_base_data_dir, get_base_data_dir_for_mode,
+    get_custom_data_root, get_data_root_pointer_path, get_system_data_dir, is_portable_mode,
+    set_custom_data_root,
+};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use walkdir::WalkDir;
+
+/// 有游戏会话时拒绝迁移的提示
+const GAME_RUNNING_MESSAGE: &str = "有游戏正在运行，请先退出游戏再迁移数据目录";
+
+/// 预览中单个待移动的文件
+#[derive(Debug, Serialize)]
+pub struct PortableSwitchFile {
+    pub source: String,
+    pub destination: String,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 目标位置已存在同名文件，迁移时会被覆盖
+    pub conflict: bool,
+}
+
+/// 阻止切换的问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortableSwitchBlockerKind {
+    /// 已经处于目标模式
+    AlreadyInMode,
+    /// 目标目录（便携模式下为程序目录）不可写
+    ReadOnlyDestination,
+    /// 目标磁盘剩余空间不足
+    InsufficientSpace,
+    /// 自定义备份目录位于数据库目录中，移动数据库目录后设置会失效
+    CustomDirInDatabaseDir,
+    /// 有游戏正在运行，迁移期间数据库连接会关闭，会话记录无法写入
+    GameRunning,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortableSwitchBlocker {
+    pub kind: PortableSwitchBlockerKind,
+    pub message: String,
+}
+
+/// 因用户自定义路径而保留在原处、不随切换移动的目录
+#[derive(Debug, Serialize)]
+pub struct ExcludedPath {
+    pub path: String,
+    pub reason: String,
+}
+
+/// 便携模式切换预览结果
+#[derive(Debug, Serialize)]
+pub struct PortableSwitchPreview {
+    /// 切换后是否为便携模式
+    pub enabled: bool,
+    pub source_dir: String,
+    pub destination_dir: String,
+    pub files: Vec<PortableSwitchFile>,
+    /// 待移动文件的总大小（字节）
+    pub total_size: u64,
+    /// 迁移需要的目标磁盘空间（字节）；同一分区内只需重命名，为 0
+    pub required_space: u64,
+    /// 目标磁盘剩余空间（字节），无法获取时为 None
+    pub available_space: Option<u64>,
+    pub excluded: Vec<ExcludedPath>,
+    /// 为空时才可以安全切换
+    pub blockers: Vec<PortableSwitchBlocker>,
+}
+
+/// 数据迁移结果
+#[derive(Debug, Default, Serialize)]
+pub struct MigrationResult {
+    /// 迁移后是否为便携模式
+    pub enabled: bool,
+    pub source_dir: String,
+    pub destination_dir: String,
+    /// 移动的数据库目录文件数（含默认位置的数据库备份）
+    pub database_files: usize,
+    /// 移动的存档备份文件数
+    pub backup_files: usize,
+    /// 移动的封面文件数（含云端封面缓存、自定义封面与缩略图）
+    pub cover_files: usize,
+    pub screenshot_files: usize,
+    pub crash_report_files: usize,
+    pub launch_log_files: usize,
+    /// 因被自定义路径引用而留在原处的资源目录
+    pub skipped_dirs: Vec<String>,
+}
+
+impl MigrationResult {
+    fn add_files(&mut self, subdir: &str, count: usize) {
+        match subdir {
+            DB_DATA_DIR => self.database_files += count,
+            BACKUP_SUBDIR => self.backup_files += count,
+            COVERS_SUBDIR => self.cover_files += count,
+            SCREENSHOT_SUBDIR => self.screenshot_files += count,
+            CRASH_SUBDIR => self.crash_report_files += count,
+            LAUNCH_LOG_SUBDIR => self.launch_log_files += count,
+            _ => {}
+        }
+    }
+}
+
+/// 向上找到第一个已存在的目录，用于检查尚未创建的目标目录
+fn nearest_existing_dir(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|dir| dir.is_dir())
+}
+
+/// 在目录中创建并删除一个临时文件，判断是否可写
+fn is_dir_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".reina_write_test_{}", std::process::id()));
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// 两个已存在的路径是否位于同一分区（同分区时迁移只需重命名）
+#[cfg(unix)]
+fn same_volume(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(windows)]
+fn same_volume(a: &Path, b: &Path) -> bool {
+    use std::path::Component;
+    let prefix = |path: &Path| match path.components().next() {
+        Some(Component::Prefix(prefix)) => {
+            Some(prefix.as_os_str().to_string_lossy().to_ascii_lowercase())
+        }
+        _ => None,
+    };
+    matches!((prefix(a), prefix(b)), (Some(a), Some(b)) if a == b)
+}
+
+/// 获取路径所在磁盘对当前用户可用的剩余空间
+#[cfg(target_os = "linux")]
+fn available_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: path 是以 NUL 结尾的有效字符串，stat 由 statvfs 填充
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(target_os = "windows")]
+fn available_space(path: &Path) -> Option<u64> {
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+    use windows::core::HSTRING;
+
+    let mut available = 0u64;
+    // SAFETY: 传入有效的路径字符串与输出指针
+    unsafe {
+        GetDiskFreeSpaceExW(
+            &HSTRING::from(path.as_os_str()),
+            Some(&mut available as *mut u64),
+            None,
+            None,
+        )
+    }
+    .ok()?;
+    Some(available)
+}
+
+/// 计算把数据从 `source_dir` 迁移到 `destination_dir` 的预览，不修改任何文件
+///
+/// `custom_dirs` 为用户自定义的存档备份 / 数据库备份目录：位于基础数据目录之外的不受切换影响，
+/// 位于其中的由设置直接引用，迁移时必须留在原处，两种情况都记入 `excluded`。
+/// 是否已处于目标模式由调用方检查。
+pub fn preview_switch(
+    enabled: bool,
+    source_dir: &Path,
+    destination_dir: &Path,
+    custom_dirs: &[(&str, PathBuf)],
+) -> PortableSwitchPreview {
+    let mut preview = PortableSwitchPreview {
+        enabled,
+        source_dir: source_dir.to_string_lossy().to_string(),
+        destination_dir: destination_dir.to_string_lossy().to_string(),
+        files: Vec::new(),
+        total_size: 0,
+        required_space: 0,
+        available_space: None,
+        excluded: Vec::new(),
+        blockers: Vec::new(),
+    };
+
+    let source_db_dir = source_dir.join(DB_DATA_DIR);
+    for (label, dir) in custom_dirs {
+        let reason = if dir.starts_with(&source_db_dir) {
+            preview.blockers.push(PortableSwitchBlocker {
+                kind: PortableSwitchBlockerKind::CustomDirInDatabaseDir,
+                message: format!(
+                    "{}位于数据库目录中，请先在设置中移到其他位置: {}",
+                    label,
+                    dir.display()
+                ),
+            });
+            format!("{}位于数据库目录中", label)
+        } else if dir.starts_with(source_dir) {
+            format!("{}由设置直接引用，需保留在原位置", label)
+        } else {
+            format!("{}位于数据目录之外，不受切换影响", label)
+        };
+        preview.excluded.push(ExcludedPath {
+            path: dir.to_string_lossy().to_string(),
+            reason,
+        });
+    }
+
+    // 只统计迁移时实际移动的目录，日志等其他文件留在原处
+    let custom: Vec<&Path> = custom_dirs.iter().map(|(_, dir)| dir.as_path()).collect();
+    let (assets, _) = plan_subdirs(source_dir, &custom);
+    for subdir in assets.into_iter().chain([DB_DATA_DIR]) {
+        let dir = source_dir.join(subdir);
+        if !dir.is_dir() {
+            continue;
+        }
+        let entries = WalkDir::new(&dir)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file());
+        for entry in entries {
+            let Ok(relative) = entry.path().strip_prefix(source_dir) else {
+                continue;
+            };
+            let destination = destination_dir.join(relative);
+            let size = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+            preview.total_size += size;
+            preview.files.push(PortableSwitchFile {
+                source: entry.path().to_string_lossy().to_string(),
+                destination: destination.to_string_lossy().to_string(),
+                size,
+                conflict: destination.exists(),
+            });
+        }
+    }
+
+    let Some(existing) = nearest_existing_dir(destination_dir) else {
+        preview.blockers.push(PortableSwitchBlocker {
+            kind: PortableSwitchBlockerKind::ReadOnlyDestination,
+            message: format!("目标目录不可访问: {}", destination_dir.display()),
+        });
+        return preview;
+    };
+
+    if !is_dir_writable(existing) {
+        preview.blockers.push(PortableSwitchBlocker {
+            kind: PortableSwitchBlockerKind::ReadOnlyDestination,
+            message: if enabled {
+                format!(
+                    "程序目录不可写，无法使用便携模式（可能安装在系统目录中）: {}",
+                    existing.display()
+                )
+            } else {
+                format!("目标目录不可写: {}", existing.display())
+            },
+        });
+    }
+
+    if !source_dir.is_dir() || !same_volume(source_dir, existing) {
+        preview.required_space = preview.total_size;
+    }
+    preview.available_space = available_space(existing);
+    if let Some(available) = preview.available_space
+        && available < preview.required_space
+    {
+        preview.blockers.push(PortableSwitchBlocker {
+            kind: PortableSwitchBlockerKind::InsufficientSpace,
+            message: format!(
+                "目标磁盘剩余空间不足：需要 {} MB，可用 {} MB",
+                preview.required_space.div_ceil(1024 * 1024),
+                available / (1024 * 1024)
+            ),
+        });
+    }
+
+    preview
+}
+
+/// 设置中直接引用的自定义目录
+fn custom_dirs(settings: &user::Model) -> Vec<(&'static str, PathBuf)> {
+    [
+        (
+            "自定义存档备份目录",
+            settings
+                .save_root_path_value()
+                .map(|path| PathBuf::from(path).join(BACKUP_SUBDIR)),
+        ),
+        (
+            "自定义数据库备份目录",
+            settings.db_backup_path_value().map(PathBuf::from),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(label, path)| path.map(|path| (label, path)))
+    .collect()
+}
+
+/// 按当前设置计算切换预览
+async fn switch_preview(
+    db: &DatabaseConnection,
+    enabled: bool,
+) -> Result<PortableSwitchPreview, String> {
+    let custom_dirs = custom_dirs(&db.get_settings().await?);
+    let source_dir = get_base_data_dir_for_mode(!enabled)?;
+    let destination_dir = get_base_data_dir_for_mode(enabled)?;
+
+    let mut preview = tokio::task::spawn_blocking(move || {
+        preview_switch(enabled, &source_dir, &destination_dir, &custom_dirs)
+    })
+    .await
+    .map_err(|e| format!("计算便携模式切换预览失败: {}", e))?;
+
+    if is_portable_mode() == enabled {
+        preview.blockers.insert(
+            0,
+            PortableSwitchBlocker {
+                kind: PortableSwitchBlockerKind::AlreadyInMode,
+                message: if enabled {
+                    "当前已是便携模式".to_string()
+                } else {
+                    "当前已是安装模式".to_string()
+                },
+            },
+        );
+    }
+    if !active_sessions().is_empty() {
+        preview.blockers.push(PortableSwitchBlocker {
+            kind: PortableSwitchBlockerKind::GameRunning,
+            message: GAME_RUNNING_MESSAGE.to_string(),
+        });
+    }
+    Ok(preview)
+}
+
+/// 划分资源目录：返回（迁移时移动的资源目录, 被自定义路径引用而留在原处的资源目录）
+///
+/// 数据库目录总是移动，不在返回值中。
+fn plan_subdirs(
+    source_dir: &Path,
+    custom_dirs: &[&Path],
+) -> (Vec<&'static str>, Vec<&'static str>) {
+    ASSET_SUBDIRS.iter().partition(|subdir| {
+        let dir = source_dir.join(subdir);
+        !custom_dirs.iter().any(|custom| custom.starts_with(&dir))
+    })
+}
+
+/// 把已移动的资源目录移回原处，尽量恢复迁移前的状态
+fn roll_back_moved_dirs(source_dir: &Path, destination_dir: &Path, moved: &[&str]) {
+    for subdir in moved.iter().rev() {
+        if let Err(e) = move_dir_recursive(&destination_dir.join(subdir), &source_dir.join(subdir))
+        {
+            log::warn!("回滚资源目录 {} 失败: {}", subdir, e);
+        }
+    }
+}
+
+/// 移动数据库目录与各资源目录，任一目录失败时把已移动的目录移回原处
+///
+/// 资源目录先于数据库目录移动：目标位置出现数据库目录即意味着切换完成（便携模式由
+/// `<exe>/resources/data` 是否存在判断），数据库目录失败时同样回滚资源目录。
+/// 被 `custom_dirs` 引用的资源目录留在原处。
+fn move_data_dirs(
+    source_dir: &Path,
+    destination_dir: &Path,
+    custom_dirs: &[PathBuf],
+) -> Result<MigrationResult, String> {
+    let source_db_dir = source_dir.join(DB_DATA_DIR);
+    if let Some(dir) = custom_dirs
+        .iter()
+        .find(|dir| dir.starts_with(&source_db_dir))
+    {
+        return Err(format!(
+            "自定义目录位于数据库目录中，请先在设置中移到其他位置: {}",
+            dir.display()
+        ));
+    }
+    fs::create_dir_all(destination_dir).map_err(|e| format!("创建目标目录失败: {}", e))?;
+
+    let custom: Vec<&Path> = custom_dirs.iter().map(PathBuf::as_path).collect();
+    let (assets, skipped) = plan_subdirs(source_dir, &custom);
+    let mut result = MigrationResult {
+        skipped_dirs: skipped
+            .into_iter()
+            .map(|subdir| source_dir.join(subdir))
+            .filter(|dir| dir.is_dir())
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect(),
+        ..Default::default()
+    };
+    let mut moved = Vec::new();
+    for subdir in assets {
+        let source = source_dir.join(subdir);
+        if !source.is_dir() {
+            continue;
+        }
+        match move_dir_recursive(&source, &destination_dir.join(subdir)) {
+            Ok(count) => {
+                result.add_files(subdir, count);
+                moved.push(subdir);
+            }
+            Err(e) => {
+                roll_back_moved_dirs(source_dir, destination_dir, &moved);
+                return Err(format!("移动 {} 目录失败: {}", subdir, e));
+            }
+        }
+    }
+
+    let destination_db_dir = destination_dir.join(DB_DATA_DIR);
+    match move_dir_recursive(&source_db_dir, &destination_db_dir) {
+        Ok(count) => result.add_files(DB_DATA_DIR, count),
+        Err(e) => {
+            // 源数据库还在时删除不完整的副本，避免重新连接时打开目标位置的半份数据
+            if source_db_dir.join(DB_FILE_NAME).is_file() {
+                let _ = fs::remove_dir_all(&destination_db_dir);
+                roll_back_moved_dirs(source_dir, destination_dir, &moved);
+            }
+            return Err(format!("移动数据库目录失败: {}", e));
+        }
+    }
+
+    Ok(result)
+}
+
+/// 把数据库中指向已移动目录的绝对路径（截图、启动日志）改写到目标目录
+///
+/// 留在原处的目录（`skipped_dirs`）中的路径保持不变。
+async fn rebase_stored_paths(
+    db: &DatabaseConnection,
+    source_dir: &Path,
+    destination_dir: &Path,
+    skipped_dirs: &[String],
+) -> Result<(), String> {
+    let moved = |subdir: &str| {
+        let dir = source_dir.join(subdir);
+        (!skipped_dirs.iter().any(|skipped| Path::new(skipped) == dir))
+            .then(|| (dir, destination_dir.join(subdir)))
+    };
+    if let Some((from, to)) = moved(SCREENSHOT_SUBDIR) {
+        ScreenshotsRepository::rebase_paths(db, &from, &to)
+            .await
+            .map_err(|e| format!("更新截图路径失败: {}", e))?;
+    }
+    if let Some((from, to)) = moved(LAUNCH_LOG_SUBDIR) {
+        LaunchAttemptsRepository::rebase_log_paths(db, &from, &to)
+            .await
+            .map_err(|e| format!("更新启动日志路径失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 按预览执行迁移：关闭数据库连接，移动目录后执行 `commit`，再重新连接数据库
+///
+/// `commit` 用于迁移后修改数据目录的解析结果（如写入指针文件），失败时把目录移回原处。
+/// 无论成功与否都会重新连接（迁移失败时连回原位置的数据库），成功时再把截图与启动日志记录中的
+/// 绝对路径改写到新位置。
+async fn run_migration(
+    db_state: &DbState,
+    preview: PortableSwitchPreview,
+    custom_dirs: Vec<PathBuf>,
+    commit: impl FnOnce() -> Result<(), String> + Send + 'static,
+) -> Result<MigrationResult, String> {
+    if let Some(blocker) = preview.blockers.first() {
+        return Err(blocker.message.clone());
+    }
+    if !active_sessions().is_empty() {
+        return Err(GAME_RUNNING_MESSAGE.to_string());
+    }
+    let source_dir = PathBuf::from(&preview.source_dir);
+    let destination_dir = PathBuf::from(&preview.destination_dir);
+
+    close_connection(db_state.get())
+        .await
+        .map_err(|e| format!("关闭数据库连接失败: {}", e))?;
+    log::info!(
+        "数据库连接已关闭，开始迁移数据目录: {} -> {}",
+        source_dir.display(),
+        destination_dir.display()
+    );
+
+    let moved = tokio::task::spawn_blocking(move || {
+        let result = move_data_dirs(&source_dir, &destination_dir, &custom_dirs)?;
+        if let Err(e) = commit() {
+            if let Err(rollback) = move_data_dirs(&destination_dir, &source_dir, &[]) {
+                log::error!("迁移回滚失败: {}", rollback);
+            }
+            return Err(e);
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("迁移数据目录失败: {}", e))
+    .and_then(|result| result);
+    reconnect(db_state).await?;
+    let mut result = moved?;
+    rebase_stored_paths(
+        &db_state.get(),
+        Path::new(&preview.source_dir),
+        Path::new(&preview.destination_dir),
+        &result.skipped_dirs,
+    )
+    .await
+    .map_err(|e| format!("数据目录已迁移，但{}", e))?;
+    result.enabled = preview.enabled;
+    result.source_dir = preview.source_dir;
+    result.destination_dir = preview.destination_dir;
+
+    log::info!("数据目录迁移完成 {:?}", result);
+    Ok(result)
+}
+
+/// 设置中自定义目录的路径列表
+async fn custom_dir_paths(db: &DatabaseConnection) -> Result<Vec<PathBuf>, String> {
+    Ok(custom_dirs(&db.get_settings().await?)
+        .into_iter()
+        .map(|(_, dir)| dir)
+        .collect())
+}
+
+/// 切换便携 / 安装模式，把数据库目录与封面、截图、备份等资源目录移动到目标模式的数据目录
+///
+/// 预览中有阻止切换的问题时不做任何改动。
+pub async fn migrate_data_files(
+    db_state: &DbState,
+    enabled: bool,
+) -> Result<MigrationResult, String> {
+    let db = db_state.get();
+    let preview = switch_preview(&db, enabled).await?;
+    let custom_dirs = custom_dir_paths(&db).await?;
+    run_migration(db_state, preview, custom_dirs, || Ok(())).await
+}
+
+/// 当前数据目录信息
+#[derive(Debug, Serialize)]
+pub struct DataRootInfo {
+    /// 当前使用的基础数据目录
+    pub path: String,
+    /// 系统默认的数据目录
+    pub default_path: String,
+    /// 是否使用了自定义数据目录（便携模式下不生效）
+    pub custom: bool,
+    pub is_portable: bool,
+}
+
+/// 获取当前数据目录信息
+#[tauri::command]
+pub fn get_data_root() -> Result<DataRootInfo, String> {
+    let _timer = CommandTimer::start("get_data_root");
+    Ok(DataRootInfo {
+        path: get_base_data_dir()?.to_string_lossy().to_string(),
+        default_path: get_system_data_dir()?.to_string_lossy().to_string(),
+        custom: get_custom_data_root().is_some(),
+        is_portable: is_portable_mode(),
+    })
+}
+
+/// 把安装模式的数据目录迁移到自定义位置（如其他磁盘上的文件夹）
+///
+/// 移动数据库与资源目录后，把新位置写入程序目录下的指针文件，之后启动时由 reina-path 解析。
+/// 便携模式下数据始终保存在程序目录中，需先切换到安装模式。
+///
+/// # Arguments
+/// * `path` - 新的数据目录（绝对路径），为空时恢复为系统默认目录
+///
+/// # Returns
+/// * `Result<MigrationResult, String>` - 迁移结果或错误消息
+#[tauri::command]
+pub async fn set_data_root(
+    db: State<'_, DbState>,
+    path: Option<String>,
+) -> Result<MigrationResult, String> {
+    let _timer = CommandTimer::start("set_data_root");
+    if is_portable_mode() {
+        return Err("便携模式下数据保存在程序目录中，请先切换到安装模式".to_string());
+    }
+    let system_dir = get_system_data_dir()?;
+    let target = match path.map(|path| path.trim().to_string()) {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => system_dir.clone(),
+    };
+    if !target.is_absolute() {
+        return Err(format!("数据目录必须是绝对路径: {}", target.display()));
+    }
+    let current = get_base_data_dir_for_mode(false)?;
+    if target == current {
+        return Err("新目录与当前数据目录相同".to_string());
+    }
+    if target.starts_with(&current) || current.starts_with(&target) {
+        return Err("新数据目录与当前数据目录不能互相包含".to_string());
+    }
+    if target.join(DB_DATA_DIR).join(DB_FILE_NAME).exists() {
+        return Err(format!(
+            "目标目录中已有数据库，请选择空目录: {}",
+            target.display()
+        ));
+    }
+    let pointer = get_data_root_pointer_path()?;
+    if let Some(exe_dir) = pointer.parent()
+        && !is_dir_writable(exe_dir)
+    {
+        return Err(format!(
+            "程序目录不可写，无法保存数据目录设置: {}",
+            exe_dir.display()
+        ));
+    }
+
+    let db_conn = db.get();
+    let custom_dirs = custom_dirs(&db_conn.get_settings().await?);
+    let custom_paths: Vec<PathBuf> = custom_dirs.iter().map(|(_, dir)| dir.clone()).collect();
+    let preview = {
+        let (current, target) = (current.clone(), target.clone());
+        tokio::task::spawn_blocking(move || preview_switch(false, &current, &target, &custom_dirs))
+            .await
+            .map_err(|e| format!("计算数据目录迁移预览失败: {}", e))?
+    };
+
+    let root = (target != system_dir).then_some(target);
+    run_migration(&db, preview, custom_paths, move || {
+        set_custom_data_root(root.as_deref())
+    })
+    .await
+}
+
+/// 预览 `migrate_data_files` 切换便携模式时会移动的文件、占用空间与阻止切换的问题，不修改任何文件
+///
+/// 前端在执行迁移前展示确认摘要，并提前发现目标磁盘空间不足等问题。
+///
+/// # Arguments
+/// * `enabled` - 切换后是否为便携模式
+///
+/// # Returns
+/// * `Result<PortableSwitchPreview, String>` - 预览结果或错误消息
+#[tauri::command]
+pub async fn preview_portable_migration(
+    db: State<'_, DbState>,
+    enabled: bool,
+) -> Result<PortableSwitchPreview, String> {
+    let _timer = CommandTimer::start("preview_portable_migration");
+    switch_preview(&db.get(), enabled).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"x").unwrap();
+    }
+
+    #[test]
+    fn moves_database_and_asset_dirs() {
+        let root = std::env::temp_dir().join(format!("reina_portable_move_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (source, destination) = (root.join("system"), root.join("portable"));
+        write(&source.join(DB_DATA_DIR).join(DB_FILE_NAME));
+        write(&source.join(COVERS_SUBDIR).join("game_1").join("cover.jpg"));
+        write(&source.join(COVERS_SUBDIR).join("thumbnails").join("1.webp"));
+        write(&source.join(SCREENSHOT_SUBDIR).join("shot.png"));
+        write(&source.join(BACKUP_SUBDIR).join("game_1").join("save.7z"));
+
+        let custom = [source.join(BACKUP_SUBDIR)];
+        let result = move_data_dirs(&source, &destination, &custom).unwrap();
+        assert_eq!(result.database_files, 1);
+        assert_eq!(result.cover_files, 2);
+        assert_eq!(result.screenshot_files, 1);
+        assert_eq!(result.backup_files, 0);
+        assert_eq!(result.skipped_dirs.len(), 1);
+        assert!(destination.join(DB_DATA_DIR).join(DB_FILE_NAME).is_file());
+        assert!(
+            destination
+                .join(SCREENSHOT_SUBDIR)
+                .join("shot.png")
+                .is_file()
+        );
+        assert!(!source.join(COVERS_SUBDIR).exists());
+        // 被自定义路径引用的目录留在原处
+        assert!(
+            source
+                .join(BACKUP_SUBDIR)
+                .join("game_1")
+                .join("save.7z")
+                .is_file()
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn rejects_custom_dir_inside_database_dir() {
+        let root =
+            std::env::temp_dir().join(format!("reina_portable_reject_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (source, destination) = (root.join("system"), root.join("portable"));
+        write(&source.join(DB_DATA_DIR).join(DB_FILE_NAME));
+
+        let custom = [source.join(DB_DATA_DIR).join("my_backups")];
+        assert!(move_data_dirs(&source, &destination, &custom).is_err());
+        assert!(source.join(DB_DATA_DIR).join(DB_FILE_NAME).is_file());
+        assert!(!destination.exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn preview_counts_only_migrated_dirs() {
+        let root =
+            std::env::temp_dir().join(format!("reina_portable_preview_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (source, destination) = (root.join("system"), root.join("portable"));
+        write(&source.join(DB_DATA_DIR).join(DB_FILE_NAME));
+        write(&source.join(COVERS_SUBDIR).join("game_1").join("cover.jpg"));
+        write(&source.join("logs").join("app.log"));
+
+        let custom = [(
+            "自定义数据库备份目录",
+            source.join(DB_DATA_DIR).join("my_backups"),
+        )];
+        let preview = preview_switch(true, &source, &destination, &custom);
+        assert_eq!(preview.files.len(), 2);
+        assert_eq!(preview.total_size, 2);
+        assert!(
+            preview
+                .blockers
+                .iter()
+                .any(|blocker| blocker.kind == PortableSwitchBlockerKind::CustomDirInDatabaseDir)
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn rebases_screenshot_records_after_move() {
+        use crate::entity::prelude::{Games, LaunchAttempts, Screenshots};
+        use sea_orm::{ConnectionTrait, Database, EntityTrait, Schema};
+
+        let root =
+            std::env::temp_dir().join(format!("reina_portable_rebase_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (source, destination) = (root.join("system"), root.join("portable"));
+        let shot = source.join(SCREENSHOT_SUBDIR).join("1").join("shot.png");
+        write(&source.join(DB_DATA_DIR).join(DB_FILE_NAME));
+        write(&shot);
+
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let db = Database::connect("sqlite::memory:").await.unwrap();
+            let backend = db.get_database_backend();
+            let schema = Schema::new(backend);
+            db.execute(backend.build(&schema.create_table_from_entity(Games)))
+                .await
+                .unwrap();
+            db.execute(backend.build(&schema.create_table_from_entity(Screenshots)))
+                .await
+                .unwrap();
+            db.execute(backend.build(&schema.create_table_from_entity(LaunchAttempts)))
+                .await
+                .unwrap();
+            db.execute_unprepared("INSERT INTO games (id, id_type) VALUES (1, 'custom')")
+                .await
+                .unwrap();
+            let external = "/mnt/pictures/outside.png".to_string();
+            let managed = ScreenshotsRepository::insert_screenshot(
+                &db,
+                1,
+                shot.to_string_lossy().to_string(),
+                0,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            let outside =
+                ScreenshotsRepository::insert_screenshot(&db, 1, external.clone(), 0, None, None)
+                    .await
+                    .unwrap();
+
+            let result = move_data_dirs(&source, &destination, &[]).unwrap();
+            rebase_stored_paths(&db, &source, &destination, &result.skipped_dirs)
+                .await
+                .unwrap();
+
+            let moved = Screenshots::find_by_id(managed.id)
+                .one(&db)
+                .await
+                .unwrap()
+                .unwrap();
+            let expected = destination
+                .join(SCREENSHOT_SUBDIR)
+                .join("1")
+                .join("shot.png");
+            assert_eq!(Path::new(&moved.path), expected);
+            assert!(expected.is_file());
+            let untouched = Screenshots::find_by_id(outside.id)
+                .one(&db)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(untouched.path, external);
+        });
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,152 @@
+//! 系统凭据存储
+//!
+//! BGM 授权信息与 VNDB 令牌保存在系统凭据管理器（Windows 凭据管理器 / Linux Secret Service）中，
+//! 不再明文写入数据库。凭据管理器不可用时（如没有运行 Secret Service 的 Linux 桌面、用户拒绝解锁
+//! 密钥环）由调用方回退到数据库保存，下次启动时再尝试迁移。
+//!
+//! 凭据管理器通过 keyring 访问，调用是阻塞的，放到阻塞线程中执行；读取失败后在
+//! [`UNAVAILABLE_RETRY`] 内不再访问凭据管理器，避免每次读取设置都重试 D-Bus 并输出警告。
+
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// 凭据的服务名
+const SERVICE: &str = "ReinaManager";
+
+/// 保存在凭据管理器中的项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecretKey {
+    /// BGM 授权信息（JSON）
+    BgmAuth,
+    VndbToken,
+}
+
+impl SecretKey {
+    fn account(self) -> &'static str {
+        match self {
+            Self::BgmAuth => "bgm_auth",
+            Self::VndbToken => "vndb_token",
+        }
+    }
+}
+
+/// 已读取的凭据，避免每次读取设置都访问凭据管理器
+static CACHE: LazyLock<RwLock<HashMap<SecretKey, Option<String>>>> =
+    LazyLock::new(Default::default);
+
+/// 读取失败后多久再尝试访问凭据管理器（如用户稍后解锁了密钥环）
+const UNAVAILABLE_RETRY: Duration = Duration::from_secs(5 * 60);
+
+/// 最近一次读取失败的时间，成功读写后清除
+static UNAVAILABLE_SINCE: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// 凭据管理器是否在最近读取失败过
+fn recently_unavailable() -> bool {
+    UNAVAILABLE_SINCE
+        .lock()
+        .is_some_and(|since| since.elapsed() < UNAVAILABLE_RETRY)
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+mod platform {
+    use keyring::{Entry, Error};
+
+    fn entry(account: &str) -> Result<Entry, String> {
+        Entry::new(super::SERVICE, account).map_err(|e| e.to_string())
+    }
+
+    pub fn read(account: &str) -> Result<Option<String>, String> {
+        match entry(account)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn write(account: &str, value: &str) -> Result<(), String> {
+        entry(account)?
+            .set_password(value)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn delete(account: &str) -> Result<(), String> {
+        match entry(account)?.delete_credential() {
+            Ok(()) | Err(Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    const UNSUPPORTED: &str = "当前平台不支持系统凭据管理器";
+
+    pub fn read(_account: &str) -> Result<Option<String>, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn write(_account: &str, _value: &str) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn delete(_account: &str) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+/// 读取凭据，凭据管理器不可用时返回 None
+///
+/// 读取失败不缓存凭据本身，但在 [`UNAVAILABLE_RETRY`] 内直接返回 None，不再重试。
+pub async fn get_secret(key: SecretKey) -> Option<String> {
+    if let Some(value) = CACHE.read().get(&key) {
+        return value.clone();
+    }
+    if recently_unavailable() {
+        return None;
+    }
+    let result = tokio::task::spawn_blocking(move || platform::read(key.account()))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    match result {
+        Ok(value) => {
+            *UNAVAILABLE_SINCE.lock() = None;
+            CACHE.write().insert(key, value.clone());
+            value
+        }
+        Err(e) => {
+            log::warn!(
+                "读取系统凭据 {} 失败，{} 分钟内不再重试: {}",
+                key.account(),
+                UNAVAILABLE_RETRY.as_secs() / 60,
+                e
+            );
+            *UNAVAILABLE_SINCE.lock() = Some(Instant::now());
+            None
+        }
+    }
+}
+
+/// 写入凭据，`value` 为 None 时删除
+pub async fn set_secret(key: SecretKey, value: Option<&str>) -> Result<(), String> {
+    let owned = value.map(str::to_string);
+    let result = tokio::task::spawn_blocking(move || match owned.as_deref() {
+        Some(value) => {
+            platform::write(key.account(), value).map_err(|e| format!("写入系统凭据失败: {}", e))
+        }
+        None => platform::delete(key.account()).map_err(|e| format!("删除系统凭据失败: {}", e)),
+    })
+    .await
+    .unwrap_or_else(|e| Err(format!("写入系统凭据失败: {}", e)));
+    // 写入失败时调用方改存数据库，缓存置空以免读到凭据管理器中的旧值
+    let cached = match result {
+        Ok(()) => {
+            *UNAVAILABLE_SINCE.lock() = None;
+            value.map(str::to_string)
+        }
+        Err(_) => None,
+    };
+    CACHE.write().insert(key, cached);
+    result
+}
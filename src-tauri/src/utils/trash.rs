@@ -0,0 +1,64 @@
+//! 删除到回收站
+//!
+//! 删除单个文件、存档备份与自定义封面时默认通过 `trash` crate 移到系统回收站，
+//! 误删后可以找回。user.use_trash 为 false 时直接永久删除；移到回收站失败（如文件与废纸篓不在同一分区）
+//! 时回退为永久删除。自动清理旧备份、配额清理等批量删除不经过回收站。
+
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::utils::file_lock::retry_on_lock;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 删除的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteOutcome {
+    /// 已移到回收站
+    Trashed,
+    /// 已永久删除
+    Deleted,
+}
+
+/// 删除时是否移到回收站，未设置或读取失败时为是
+pub async fn trash_enabled(db: &DatabaseConnection) -> bool {
+    match SettingsRepository::get_stored_settings(db).await {
+        Ok(settings) => settings.use_trash.unwrap_or(true),
+        Err(e) => {
+            log::warn!("读取回收站设置失败，默认移到回收站: {}", e);
+            true
+        }
+    }
+}
+
+/// 删除文件或目录
+///
+/// `use_trash` 为 true 时先尝试移到回收站，失败时记录日志并改为永久删除。
+pub fn remove_path(path: &Path, use_trash: bool) -> io::Result<DeleteOutcome> {
+    if use_trash {
+        match move_to_trash(path) {
+            Ok(()) => {
+                log::debug!("已移到回收站: {}", path.display());
+                return Ok(DeleteOutcome::Trashed);
+            }
+            Err(e) => log::warn!("移到回收站失败，改为永久删除 {}: {}", path.display(), e),
+        }
+    }
+
+    if path.is_dir() {
+        retry_on_lock(path, || fs::remove_dir_all(path))?;
+    } else {
+        retry_on_lock(path, || fs::remove_file(path))?;
+    }
+    Ok(DeleteOutcome::Deleted)
+}
+
+/// 通过系统接口移到回收站（Windows 回收站 / macOS 废纸篓 / FreeDesktop 废纸篓）
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path).is_err() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "文件不存在"));
+    }
+    trash::delete(path).map_err(io::Error::other)
+}
@@ -0,0 +1,495 @@
+//! 系统托盘
+//!
+//! 托盘图标由后端创建并维护（id 为 `main`），菜单包含：
+//! - 正在运行的游戏及本次已累计的游戏时间，每 5 秒刷新
+//! - 最近游玩的游戏，点击即启动
+//! - 立即备份数据库、打开游戏库数据目录
+//! - 打开主窗口、退出
+//!
+//! 菜单文字由前端按当前语言通过 `set_tray_labels` 传入。点击"退出"时发送 `tray-exit-requested` 事件，
+//! 由前端确认是否仍有游戏在运行后再退出；左键单击托盘图标显示主窗口。
+//!
+//! 开启 `hide_on_launch` 后，游戏开始监控时隐藏主窗口（发送 `main-window-hidden`），
+//! 会话结束、`game-session-ended` 发出之后恢复（发送 `main-window-restored`），前端据此展示本次会话。
+//! 同时运行多个游戏时，最后一个结束后才恢复；期间用户手动打开主窗口后不再自动恢复。
+
+use crate::backup::database::backup_database_file;
+use crate::database::db::DbState;
+use crate::database::repository::game_stats_repository::GameStatsRepository;
+use crate::database::repository::games_repository::GamesRepository;
+use crate::database::repository::settings_repository::DbSettingsExt;
+use crate::game::monitor::{ActiveSessionSummary, get_active_sessions};
+use crate::utils::deep_link::launch_and_report;
+use crate::utils::fs::open_directory;
+use crate::utils::metrics::CommandTimer;
+use crate::utils::notification::{NotificationCategory, notify};
+use log::warn;
+use parking_lot::{Mutex, RwLock};
+use reina_path::get_base_data_dir;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tauri::menu::{Menu, MenuBuilder, MenuItem, MenuItemBuilder, SubmenuBuilder};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::Notify;
+
+const TRAY_ID: &str = "main";
+
+/// 点击"退出"后发送给前端的事件
+const EXIT_REQUESTED_EVENT: &str = "tray-exit-requested";
+
+/// 菜单刷新间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 每隔多少次刷新重新读取最近游玩列表
+const RECENT_REFRESH_TICKS: u32 = 6;
+
+const MENU_OPEN: &str = "open";
+const MENU_EXIT: &str = "exit";
+const MENU_BACKUP: &str = "backup";
+const MENU_OPEN_LIBRARY: &str = "open_library";
+const MENU_LAUNCH_PREFIX: &str = "launch:";
+
+/// 托盘菜单文字，由前端按当前语言传入
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrayLabels {
+    /// 前端当前语言，用于选择游戏显示名称
+    pub language: Option<String>,
+    pub open: String,
+    pub exit: String,
+    pub backup: String,
+    pub open_library: String,
+    pub recent: String,
+    pub no_running_game: String,
+    pub paused: String,
+}
+
+impl Default for TrayLabels {
+    fn default() -> Self {
+        Self {
+            language: None,
+            open: "打开主窗口".to_string(),
+            exit: "退出".to_string(),
+            backup: "立即备份".to_string(),
+            open_library: "打开游戏库文件夹".to_string(),
+            recent: "最近游玩".to_string(),
+            no_running_game: "没有正在运行的游戏".to_string(),
+            paused: "已暂停".to_string(),
+        }
+    }
+}
+
+static LABELS: LazyLock<RwLock<TrayLabels>> = LazyLock::new(Default::default);
+
+/// 菜单文字变化后立即刷新，不必等到下一轮
+static REFRESH: Notify = Notify::const_new();
+
+/// 因启动而隐藏了主窗口、尚未结束的游戏
+static HIDDEN_FOR_GAMES: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// 决定是否需要重建菜单；只有计时变化时原地更新菜单项文字
+#[derive(PartialEq)]
+struct MenuKey {
+    labels: TrayLabels,
+    running: Vec<(u32, bool)>,
+    recent: Vec<(u32, String)>,
+}
+
+struct TrayMenuState<R: Runtime> {
+    key: Option<MenuKey>,
+    session_items: Vec<MenuItem<R>>,
+    recent: Vec<(u32, String)>,
+    names: HashMap<u32, String>,
+}
+
+/// 格式化为 `H:MM:SS`
+fn format_duration(seconds: u64) -> String {
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Windows 菜单把 `&` 当作助记符前缀
+fn escape_menu_text(text: &str) -> String {
+    text.replace('&', "&&")
+}
+
+fn session_text(session: &ActiveSessionSummary, name: &str, labels: &TrayLabels) -> String {
+    let mut text = format!(
+        "{}  {}",
+        escape_menu_text(name),
+        format_duration(session.total_seconds)
+    );
+    if session.paused {
+        text.push_str(&format!("（{}）", labels.paused));
+    }
+    text
+}
+
+/// 显示并聚焦主窗口；用户手动打开后，会话结束时不再自动恢复
+pub fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
+    HIDDEN_FOR_GAMES.lock().clear();
+    raise_main_window(app);
+}
+
+fn raise_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+async fn game_name(db: &DatabaseConnection, game_id: u32, use_cn: bool) -> String {
+    GamesRepository::find_by_id(db, game_id as i32)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|game| GamesRepository::get_display_name(&game, use_cn).map(ToOwned::to_owned))
+        .unwrap_or_else(|| format!("game_{}", game_id))
+}
+
+/// 按最近游玩时间倒序取前 `limit` 个游戏
+async fn load_recent_games(
+    db: &DatabaseConnection,
+    limit: usize,
+    use_cn: bool,
+) -> Vec<(u32, String)> {
+    if limit == 0 {
+        return Vec::new();
+    }
+    let mut played = match GameStatsRepository::get_all_last_played(db).await {
+        Ok(played) => played,
+        Err(e) => {
+            warn!("读取最近游玩记录失败: {}", e);
+            return Vec::new();
+        }
+    };
+    played.retain(|entry| entry.last_played.is_some());
+    played.sort_by_key(|entry| std::cmp::Reverse(entry.last_played));
+
+    let mut recent = Vec::with_capacity(limit);
+    for entry in played.into_iter().take(limit) {
+        let Ok(game_id) = u32::try_from(entry.game_id) else {
+            continue;
+        };
+        recent.push((game_id, game_name(db, game_id, use_cn).await));
+    }
+    recent
+}
+
+fn build_menu<R: Runtime>(
+    app: &AppHandle<R>,
+    labels: &TrayLabels,
+    sessions: &[ActiveSessionSummary],
+    names: &HashMap<u32, String>,
+    recent: &[(u32, String)],
+) -> tauri::Result<(Menu<R>, Vec<MenuItem<R>>)> {
+    let mut builder = MenuBuilder::new(app);
+    let mut session_items = Vec::with_capacity(sessions.len());
+    if sessions.is_empty() {
+        let item = MenuItemBuilder::new(escape_menu_text(&labels.no_running_game))
+            .enabled(false)
+            .build(app)?;
+        builder = builder.item(&item);
+    }
+    for session in sessions {
+        let name = names.get(&session.game_id).map_or("", String::as_str);
+        let item = MenuItemBuilder::new(session_text(session, name, labels))
+            .enabled(false)
+            .build(app)?;
+        builder = builder.item(&item);
+        session_items.push(item);
+    }
+    builder = builder.separator();
+
+    if !recent.is_empty() {
+        let mut submenu = SubmenuBuilder::new(app, escape_menu_text(&labels.recent));
+        for (game_id, name) in recent {
+            submenu = submenu.text(
+                format!("{}{}", MENU_LAUNCH_PREFIX, game_id),
+                escape_menu_text(name),
+            );
+        }
+        builder = builder.item(&submenu.build()?).separator();
+    }
+
+    let menu = builder
+        .text(MENU_BACKUP, escape_menu_text(&labels.backup))
+        .text(MENU_OPEN_LIBRARY, escape_menu_text(&labels.open_library))
+        .separator()
+        .text(MENU_OPEN, escape_menu_text(&labels.open))
+        .text(MENU_EXIT, escape_menu_text(&labels.exit))
+        .build()?;
+    Ok((menu, session_items))
+}
+
+async fn refresh<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &mut TrayMenuState<R>,
+    reload_recent: bool,
+) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    let labels = LABELS.read().clone();
+    let use_cn = labels.language.as_deref() == Some("zh-CN");
+    let sessions = get_active_sessions();
+    let running: Vec<(u32, bool)> = sessions
+        .iter()
+        .map(|session| (session.game_id, session.paused))
+        .collect();
+
+    let running_changed = state.key.as_ref().is_none_or(|key| {
+        key.running
+            .iter()
+            .map(|(id, _)| id)
+            .ne(running.iter().map(|(id, _)| id))
+    });
+    let labels_changed = state.key.as_ref().is_none_or(|key| key.labels != labels);
+    // 会话结束会更新最近游玩时间，语言变化会影响显示名称
+    if reload_recent || running_changed || labels_changed {
+        let limit = match db.get_settings().await {
+            Ok(settings) => settings.tray_settings.unwrap_or_default().recent_games(),
+            Err(_) => 0,
+        };
+        state.recent = load_recent_games(&db, limit, use_cn).await;
+        state.names.clear();
+    }
+    for session in &sessions {
+        if let Entry::Vacant(entry) = state.names.entry(session.game_id) {
+            entry.insert(game_name(&db, session.game_id, use_cn).await);
+        }
+    }
+
+    let key = MenuKey {
+        labels,
+        running,
+        recent: state.recent.clone(),
+    };
+    if state.key.as_ref() != Some(&key) {
+        match build_menu(app, &key.labels, &sessions, &state.names, &state.recent) {
+            Ok((menu, session_items)) => {
+                if let Err(e) = tray.set_menu(Some(menu)) {
+                    warn!("更新托盘菜单失败: {}", e);
+                }
+                state.session_items = session_items;
+                state.key = Some(key);
+            }
+            Err(e) => warn!("创建托盘菜单失败: {}", e),
+        }
+    } else {
+        for (session, item) in sessions.iter().zip(&state.session_items) {
+            let name = state.names.get(&session.game_id).map_or("", String::as_str);
+            let _ = item.set_text(session_text(session, name, &key.labels));
+        }
+    }
+
+    let mut tooltip = format!("ReinaManager v{}", app.package_info().version);
+    for session in &sessions {
+        let name = state.names.get(&session.game_id).map_or("", String::as_str);
+        tooltip.push_str(&format!(
+            "\n{}  {}",
+            name,
+            format_duration(session.total_seconds)
+        ));
+    }
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+async fn refresh_loop<R: Runtime>(app: AppHandle<R>) {
+    let mut state = TrayMenuState {
+        key: None,
+        session_items: Vec::new(),
+        recent: Vec::new(),
+        names: HashMap::new(),
+    };
+    let mut tick = 0u32;
+    loop {
+        refresh(&app, &mut state, tick.is_multiple_of(RECENT_REFRESH_TICKS)).await;
+        tick = tick.wrapping_add(1);
+        let _ = tokio::time::timeout(REFRESH_INTERVAL, REFRESH.notified()).await;
+    }
+}
+
+async fn backup_now<R: Runtime>(app: AppHandle<R>) {
+    let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    match backup_database_file(&db, false).await {
+        Ok(result) => {
+            notify(
+                &app,
+                NotificationCategory::Backup,
+                "数据库备份完成",
+                result.path.as_deref().unwrap_or_default(),
+            )
+            .await
+        }
+        Err(e) => {
+            warn!("托盘备份数据库失败: {}", e);
+            notify(&app, NotificationCategory::Error, "数据库备份失败", &e).await;
+        }
+    }
+}
+
+async fn open_library_folder() {
+    let result = match get_base_data_dir() {
+        Ok(dir) => open_directory(dir.to_string_lossy().to_string()).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        warn!("打开游戏库文件夹失败: {}", e);
+    }
+}
+
+fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, id: &str) {
+    match id {
+        MENU_OPEN => show_main_window(app),
+        MENU_EXIT => {
+            // 主窗口负责退出前的确认与窗口状态保存；主窗口不存在时直接退出
+            if app.get_webview_window("main").is_some() {
+                if let Err(e) = app.emit(EXIT_REQUESTED_EVENT, ()) {
+                    warn!("无法发送 {} 事件: {}", EXIT_REQUESTED_EVENT, e);
+                }
+            } else {
+                app.exit(0);
+            }
+        }
+        MENU_BACKUP => {
+            tauri::async_runtime::spawn(backup_now(app.clone()));
+        }
+        MENU_OPEN_LIBRARY => {
+            tauri::async_runtime::spawn(open_library_folder());
+        }
+        _ => {
+            if let Some(game_id) = id
+                .strip_prefix(MENU_LAUNCH_PREFIX)
+                .and_then(|id| id.parse::<u32>().ok())
+            {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    launch_and_report(&app, game_id).await;
+                });
+            }
+        }
+    }
+}
+
+/// 创建托盘图标并开始定时刷新菜单，需在数据库状态注册之后调用
+pub fn init_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(format!("ReinaManager v{}", app.package_info().version))
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    tauri::async_runtime::spawn(refresh_loop(app.clone()));
+    Ok(())
+}
+
+/// 是否按托盘设置在启动时不显示主窗口
+pub async fn start_minimized<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+        return false;
+    };
+    match db.get_settings().await {
+        Ok(settings) => settings.tray_settings.unwrap_or_default().start_minimized(),
+        Err(e) => {
+            warn!("读取托盘设置失败: {}", e);
+            false
+        }
+    }
+}
+
+/// 游戏开始监控时按 `hide_on_launch` 设置隐藏主窗口
+pub async fn hide_for_session<R: Runtime>(app: &AppHandle<R>, game_id: u32) {
+    let Some(db) = app.try_state::<DbState>().map(|state| state.get()) else {
+        return;
+    };
+    let enabled = db
+        .get_settings()
+        .await
+        .is_ok_and(|settings| settings.tray_settings.unwrap_or_default().hide_on_launch());
+    // 没有托盘图标时隐藏后无法找回窗口
+    if !enabled || app.tray_by_id(TRAY_ID).is_none() {
+        return;
+    }
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    let mut hidden = HIDDEN_FOR_GAMES.lock();
+    // 用户自己隐藏的窗口不由会话恢复；已因其他游戏隐藏时一并等待本游戏结束
+    if !hidden.is_empty() || window.is_visible().unwrap_or(false) {
+        if let Err(e) = window.hide() {
+            warn!("隐藏主窗口失败: {}", e);
+            return;
+        }
+        if !hidden.contains(&game_id) {
+            hidden.push(game_id);
+        }
+        drop(hidden);
+        let _ = app.emit("main-window-hidden", json!({ "gameId": game_id }));
+    }
+}
+
+/// 会话结束后恢复因启动游戏而隐藏的主窗口
+pub fn restore_after_session<R: Runtime>(app: &AppHandle<R>, game_id: u32) {
+    let mut hidden = HIDDEN_FOR_GAMES.lock();
+    let Some(index) = hidden.iter().position(|id| *id == game_id) else {
+        return;
+    };
+    hidden.remove(index);
+    if !hidden.is_empty() {
+        return;
+    }
+    drop(hidden);
+    raise_main_window(app);
+    let _ = app.emit("main-window-restored", json!({ "gameId": game_id }));
+}
+
+/// 更新托盘菜单文字（前端语言切换后调用）
+#[tauri::command]
+pub fn set_tray_labels(labels: TrayLabels) {
+    let _timer = CommandTimer::start("set_tray_labels");
+    *LABELS.write() = labels;
+    REFRESH.notify_one();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_session_duration() {
+        assert_eq!(format_duration(0), "0:00:00");
+        assert_eq!(format_duration(3 * 3600 + 25 * 60 + 7), "3:25:07");
+        assert_eq!(escape_menu_text("A&B"), "A&&B");
+    }
+}
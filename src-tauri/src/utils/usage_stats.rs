@@ -0,0 +1,173 @@
+//! 本地功能使用统计
+//!
+//! 默认关闭。用户在设置中开启后，按功能与本地日期累计启动游戏、备份、扫描等操作的次数，
+//! 写入 usage_stats 表，只用于在应用内查看自己的使用情况，不会通过网络发送。
+
+use crate::database::db::DbState;
+use crate::database::repository::settings_repository::SettingsRepository;
+use crate::database::repository::usage_stats_repository::UsageStatsRepository;
+use crate::entity::usage_stats;
+use crate::utils::metrics::CommandTimer;
+use chrono::{Local, NaiveDate};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::State;
+
+/// 统计近期使用次数的天数
+const RECENT_DAYS: i64 = 30;
+
+/// 统计的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageFeature {
+    GameLaunch,
+    DatabaseBackup,
+    SavedataBackup,
+    LibraryScan,
+}
+
+impl UsageFeature {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::GameLaunch => "game_launch",
+            Self::DatabaseBackup => "database_backup",
+            Self::SavedataBackup => "savedata_backup",
+            Self::LibraryScan => "library_scan",
+        }
+    }
+}
+
+/// 单个功能的使用情况
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct FeatureUsage {
+    pub feature: String,
+    pub total: i64,
+    /// 最近 30 天（含今天）的使用次数
+    pub recent: i64,
+    /// 有使用记录的天数
+    pub days_used: usize,
+    /// 最后一次使用的时间（Unix 时间戳，秒）
+    pub last_used: i32,
+}
+
+/// 使用统计汇总
+#[derive(Debug, Serialize)]
+pub struct UsageSummary {
+    pub enabled: bool,
+    /// 最早的记录日期
+    pub since: Option<String>,
+    /// 按总次数从多到少排列
+    pub features: Vec<FeatureUsage>,
+}
+
+/// 记录一次功能使用；未开启统计时不记录，写入失败只输出调试日志
+pub async fn record_usage(db: &DatabaseConnection, feature: UsageFeature) {
+    // 只读取数据库中的设置，不必访问系统凭据存储
+    match SettingsRepository::get_stored_settings(db).await {
+        Ok(settings) if settings.usage_stats_enabled == Some(true) => {}
+        _ => return,
+    }
+    let now = Local::now();
+    if let Err(e) = UsageStatsRepository::increment(
+        db,
+        feature.as_str(),
+        &now.format("%Y-%m-%d").to_string(),
+        now.timestamp() as i32,
+    )
+    .await
+    {
+        log::debug!("记录使用统计失败 {}: {}", feature.as_str(), e);
+    }
+}
+
+/// 按功能汇总统计记录
+fn summarize(records: &[usage_stats::Model], today: NaiveDate) -> Vec<FeatureUsage> {
+    let recent_start = today - chrono::Duration::days(RECENT_DAYS - 1);
+    let mut features: BTreeMap<&str, FeatureUsage> = BTreeMap::new();
+    for record in records {
+        let usage = features
+            .entry(record.feature.as_str())
+            .or_insert_with(|| FeatureUsage {
+                feature: record.feature.clone(),
+                total: 0,
+                recent: 0,
+                days_used: 0,
+                last_used: 0,
+            });
+        let count = i64::from(record.count);
+        usage.total += count;
+        usage.days_used += 1;
+        usage.last_used = usage.last_used.max(record.last_used);
+        let recent = NaiveDate::parse_from_str(&record.date, "%Y-%m-%d")
+            .is_ok_and(|date| date >= recent_start && date <= today);
+        if recent {
+            usage.recent += count;
+        }
+    }
+    let mut features: Vec<FeatureUsage> = features.into_values().collect();
+    features.sort_by_key(|usage| std::cmp::Reverse(usage.total));
+    features
+}
+
+/// 获取本地使用统计汇总
+#[tauri::command]
+pub async fn get_usage_summary(db: State<'_, DbState>) -> Result<UsageSummary, String> {
+    let _timer = CommandTimer::start("get_usage_summary");
+    let db = db.get();
+    let enabled = SettingsRepository::get_stored_settings(&db)
+        .await
+        .map_err(|e| format!("读取用户设置失败: {}", e))?
+        .usage_stats_enabled
+        == Some(true);
+    let records = UsageStatsRepository::find_all(&db)
+        .await
+        .map_err(|e| format!("读取使用统计失败: {}", e))?;
+    Ok(UsageSummary {
+        enabled,
+        since: records.iter().map(|record| record.date.clone()).min(),
+        features: summarize(&records, Local::now().date_naive()),
+    })
+}
+
+/// 清空本地使用统计，返回删除的记录数
+#[tauri::command]
+pub async fn clear_usage_stats(db: State<'_, DbState>) -> Result<u64, String> {
+    let _timer = CommandTimer::start("clear_usage_stats");
+    UsageStatsRepository::clear(&db.get())
+        .await
+        .map_err(|e| format!("清空使用统计失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(feature: &str, date: &str, count: i32, last_used: i32) -> usage_stats::Model {
+        usage_stats::Model {
+            feature: feature.to_string(),
+            date: date.to_string(),
+            count,
+            last_used,
+        }
+    }
+
+    #[test]
+    fn summarizes_by_feature() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let records = [
+            record("game_launch", "2026-08-01", 4, 100),
+            record("game_launch", "2026-10-14", 2, 300),
+            record("library_scan", "2026-09-15", 1, 200),
+            record("library_scan", "2026-09-14", 1, 150),
+        ];
+        let summary = summarize(&records, today);
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].feature, "game_launch");
+        assert_eq!(summary[0].total, 6);
+        assert_eq!(summary[0].recent, 2);
+        assert_eq!(summary[0].days_used, 2);
+        assert_eq!(summary[0].last_used, 300);
+        // 2026-09-15 是 30 天窗口的第一天
+        assert_eq!(summary[1].recent, 1);
+    }
+}
@@ -13,9 +13,9 @@ import type { TFunction } from "i18next";
 import { useTranslation } from "react-i18next";
 import { Virtuoso } from "react-virtuoso";
 import { getRuntimeSourceAdapter, REGISTERED_SOURCE_KEYS } from "@/metadata";
-import type { GameCandidateData, ScanResult } from "@/types";
+import type { GameCandidateData, ScannedGame } from "@/types";
 
-export interface BulkImportItem extends ScanResult {
+export interface BulkImportItem extends ScannedGame {
 	status: "pending" | "matched" | "imported" | "error" | "not found";
 	matchedData?: GameCandidateData;
 	selectedExe?: string;
@@ -186,12 +186,13 @@ const BulkImportTab = ({ hidden, onClose }: BulkImportTabProps) => {
 					maxDepth,
 				);
 				setItems(
-					subdirs.map((dir) => ({
-						...dir,
-						status: "pending",
-						selectedExe:
-							dir.executables.length > 0 ? dir.executables[0] : undefined,
-					})),
+					subdirs
+						.filter((dir) => !dir.already_imported)
+						.map((dir) => ({
+							...dir,
+							status: "pending",
+							selectedExe: dir.main_executable ?? undefined,
+						})),
 				);
 			} catch (error) {
 				snackbar.error(getUserErrorMessage(error, t));
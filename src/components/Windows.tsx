@@ -26,7 +26,7 @@ import { useShallow } from "zustand/react/shallow";
 import { useProxyImageUrlResolver } from "@/hooks/common/useProxyImageUrlResolver";
 import { snackbar } from "@/providers/snackBar";
 import { destroyCurrentWindow, getRunningGameCount } from "@/services/appExit";
-import { fileService } from "@/services/invoke";
+import { fileService, settingsService } from "@/services/invoke";
 import {
 	checkForUpdates,
 	downloadAndInstallUpdate,
@@ -385,6 +385,16 @@ const WindowsHandler: React.FC = () => {
 					onUpdateFound: (update) => {
 						useStore.getState().setPendingUpdate(update);
 						useStore.getState().setShowUpdateModal(true);
+						// 窗口最小化或在托盘时通过系统通知提醒
+						void settingsService.sendNotification(
+							"update_available",
+							t("components.Window.UpdateModal.title", "发现新版本"),
+							t(
+								"components.Window.UpdateModal.notificationBody",
+								"ReinaManager {{version}} 可以更新了",
+								{ version: update.version },
+							),
+						);
 					},
 					onError: (error) => {
 						snackbar.warning(
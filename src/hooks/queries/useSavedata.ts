@@ -34,6 +34,7 @@ export const saveDataKeys = {
 interface CreateBackupParams {
 	gameId: number;
 	savePath: string;
+	auto?: boolean;
 }
 
 interface DeleteBackupParams {
@@ -84,9 +85,9 @@ export function useSaveDataBackupCount(gameId: number) {
  */
 export async function createBackupAndSync(
 	queryClient: QueryClient,
-	{ gameId, savePath }: CreateBackupParams,
+	{ gameId, savePath, auto }: CreateBackupParams,
 ) {
-	const backupInfo = await createGameSavedataBackup(gameId, savePath);
+	const backupInfo = await createGameSavedataBackup(gameId, savePath, auto);
 
 	await queryClient.invalidateQueries({
 		queryKey: saveDataKeys.backups(gameId),
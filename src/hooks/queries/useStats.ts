@@ -7,6 +7,7 @@ import {
 	getRecentSessionsForGames,
 } from "@/services/game/gameStats";
 import { statsService } from "@/services/invoke";
+import type { ActiveSessionInfo } from "@/services/invoke/gameService";
 import { getLocalDateString } from "@/utils/dateTime";
 
 export const statsKeys = {
@@ -21,6 +22,7 @@ export const statsKeys = {
 	totalPlayTime: () => [...statsKeys.all, "totalPlayTime"] as const,
 	weekPlayTime: () => [...statsKeys.all, "weekPlayTime"] as const,
 	todayPlayTime: () => [...statsKeys.all, "todayPlayTime"] as const,
+	activeSessions: () => [...statsKeys.all, "activeSessions"] as const,
 };
 
 interface PlayTimeSummary {
@@ -164,17 +166,27 @@ function useWeekPlayTime() {
 }
 
 function useTodayPlayTime() {
-	const playTimeSummaryQuery = usePlayTimeSummaryQuery();
+	const todayPlayTimeQuery = useQuery({
+		queryKey: statsKeys.todayPlayTime(),
+		queryFn: async () => (await getPlayTimeSummary()).todayPlayTime,
+	});
 
 	return useMemo(
 		() => ({
-			...playTimeSummaryQuery,
-			data: playTimeSummaryQuery.data?.todayPlayTime ?? 0,
+			...todayPlayTimeQuery,
+			data: todayPlayTimeQuery.data ?? 0,
 		}),
-		[playTimeSummaryQuery],
+		[todayPlayTimeQuery],
 	);
 }
 
+function useActiveSessions() {
+	return useQuery<ActiveSessionInfo[]>({
+		queryKey: statsKeys.activeSessions(),
+		queryFn: () => statsService.getActiveSessions(),
+	});
+}
+
 function usePlayTimeSummary() {
 	const playTimeSummaryQuery = usePlayTimeSummaryQuery();
 
@@ -193,6 +205,7 @@ function usePlayTimeSummary() {
 }
 
 export {
+	useActiveSessions,
 	useAllGameLastPlayedMap,
 	useGameSessions,
 	useGameStats,
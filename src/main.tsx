@@ -21,6 +21,7 @@ import { ReactQueryDevtools } from "@tanstack/react-query-devtools";
 import { isTauri } from "@tauri-apps/api/core";
 import { queryClient } from "@/providers/queryClient";
 import { initPathCache } from "@/services/fs/pathCache";
+import { primeInitialAppState } from "@/services/initialAppState";
 import { initTray } from "@/services/plugins/trayService";
 import { initializeStores } from "./store/appStore";
 
@@ -50,31 +51,52 @@ document.addEventListener("keydown", (e) => {
 	}
 });
 
-// 初始化全局状态后，挂载 React 应用
-initializeStores().then(async () => {
-	const trayReady = isTauri()
-		? initTray().catch((error) => {
-				console.error("托盘初始化失败:", error);
-			})
-		: Promise.resolve(null);
+// 游戏时间小组件窗口只渲染小组件本身，不初始化托盘和主窗口的全局状态
+if (window.location.pathname === "/widget") {
+	void Promise.all([
+		import("@/pages/PlaytimeWidget"),
+		import("@/providers/i18n"),
+	]).then(([{ PlaytimeWidget }]) => {
+		createRoot(document.getElementById("root") as HTMLElement).render(
+			<CacheProvider value={emotionCache}>
+				<PlaytimeWidget />
+			</CacheProvider>,
+		);
+	});
+} else {
+	// 初始化全局状态后，挂载 React 应用
+	initializeStores().then(async () => {
+		const trayReady = isTauri()
+			? initTray().catch((error) => {
+					console.error("托盘初始化失败:", error);
+				})
+			: Promise.resolve(null);
 
-	// 封面路径依赖路径缓存，仍需在首屏挂载前完成
-	if (isTauri()) {
-		try {
-			await initPathCache();
-		} catch (error) {
-			console.error("路径缓存初始化失败:", error);
+		// 封面路径依赖路径缓存，仍需在首屏挂载前完成
+		if (isTauri()) {
+			try {
+				await initPathCache();
+			} catch (error) {
+				console.error("路径缓存初始化失败:", error);
+			}
+
+			// 一次性预取首屏数据，失败时各查询按需单独加载
+			try {
+				await primeInitialAppState(queryClient);
+			} catch (error) {
+				console.error("启动数据预取失败:", error);
+			}
 		}
-	}
 
-	createRoot(document.getElementById("root") as HTMLElement).render(
-		<CacheProvider value={emotionCache}>
-			<QueryClientProvider client={queryClient}>
-				<ReactQueryDevtools initialIsOpen={false} />
-				<RouterProvider router={routers} />
-			</QueryClientProvider>
-		</CacheProvider>,
-	);
+		createRoot(document.getElementById("root") as HTMLElement).render(
+			<CacheProvider value={emotionCache}>
+				<QueryClientProvider client={queryClient}>
+					<ReactQueryDevtools initialIsOpen={false} />
+					<RouterProvider router={routers} />
+				</QueryClientProvider>
+			</CacheProvider>,
+		);
 
-	void trayReady;
-});
+		void trayReady;
+	});
+}
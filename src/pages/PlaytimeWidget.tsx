@@ -0,0 +1,70 @@
+/**
+ * @file 游戏时间小组件
+ * @description 置顶小窗口，显示今天和本周所有游戏的累计游玩时间；由后端创建，数据通过 playtime-widget-update 事件推送
+ * @module src/pages/PlaytimeWidget
+ * @author ReinaManager
+ * @copyright AGPL-3.0
+ */
+
+import Box from "@mui/material/Box";
+import Typography from "@mui/material/Typography";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import {
+	type PlaytimeWidgetData,
+	statsService,
+} from "@/services/invoke/statsService";
+import { formatPlayTime } from "@/utils/dateTime";
+
+export const PlaytimeWidget: React.FC = () => {
+	const { t } = useTranslation();
+	const [data, setData] = useState<PlaytimeWidgetData | null>(null);
+
+	useEffect(() => {
+		statsService
+			.getPlaytimeWidgetData()
+			.then(setData)
+			.catch((error) => console.error("获取小组件数据失败:", error));
+
+		const unlisten = listen<PlaytimeWidgetData>(
+			"playtime-widget-update",
+			(event) => setData(event.payload),
+		);
+		return () => {
+			void unlisten.then((fn) => fn());
+		};
+	}, []);
+
+	return (
+		<Box
+			data-tauri-drag-region
+			sx={{
+				height: "100vh",
+				px: 2,
+				py: 1,
+				display: "flex",
+				flexDirection: "column",
+				justifyContent: "center",
+				userSelect: "none",
+				cursor: "move",
+			}}
+		>
+			<Typography variant="body2" data-tauri-drag-region>
+				{t("pages.Widget.today")}: {formatPlayTime(data?.todayMinutes ?? 0)}
+			</Typography>
+			<Typography variant="body2" data-tauri-drag-region>
+				{t("pages.Widget.week")}: {formatPlayTime(data?.weekMinutes ?? 0)}
+			</Typography>
+			{!!data?.activeGames && (
+				<Typography
+					variant="caption"
+					color="text.secondary"
+					data-tauri-drag-region
+				>
+					{t("pages.Widget.playing", { count: data.activeGames })}
+				</Typography>
+			)}
+		</Box>
+	);
+};
@@ -7,7 +7,6 @@ import Box from "@mui/material/Box";
 import Button from "@mui/material/Button";
 import Stack from "@mui/material/Stack";
 import { useQueryClient } from "@tanstack/react-query";
-import { relaunch } from "@tauri-apps/plugin-process";
 import { type ChangeEvent, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { useShallow } from "zustand/react/shallow";
@@ -202,12 +201,12 @@ export const DatabaseBackupSettings = () => {
 					snackbar.success(
 						t(
 							"pages.Settings.databaseBackup.importSuccess",
-							"数据库导入成功，已备份自定义封面并清空封面缓存，应用将自动重启",
+							"数据库导入成功，已备份自定义封面并清空封面缓存，即将重新加载",
 						),
 					);
-					// 延迟重启应用，让用户看到成功提示
-					setTimeout(async () => {
-						await relaunch();
+					// 后端已切换到新数据库，延迟重新加载页面以刷新全部数据，让用户看到成功提示
+					setTimeout(() => {
+						window.location.reload();
 					}, 3000);
 				} else {
 					snackbar.error(
@@ -244,7 +243,7 @@ export const DatabaseBackupSettings = () => {
 			title={t("pages.Settings.databaseBackup.title", "数据备份与恢复")}
 			description={t(
 				"pages.Settings.databaseBackup.restoreWarning",
-				"恢复数据库将覆盖现有数据，并会先备份自定义封面、清空封面缓存以避免封面错配。导入后应用将自动重启。",
+				"恢复数据库将覆盖现有数据，并会先备份自定义封面、清空封面缓存以避免封面错配。导入后将自动重新加载。",
 			)}
 		>
 			<Stack
@@ -83,6 +83,7 @@ export async function createAutoBackup(
  * 导入数据库文件（覆盖现有数据库）
  *
  * 流程：
+ * 0. 解压 .db.7z 压缩备份，并校验待导入数据库的完整性、表结构与迁移版本，不通过时直接报错
  * 1. 读取备份目录配置
  * 2. 备份当前自定义封面
  * 3. 关闭当前数据库连接
@@ -92,14 +93,14 @@ export async function createAutoBackup(
  *
  * 备份路径从数据库的 user 表中读取配置
  *
- * 注意：由于需要关闭并重新打开数据库连接，导入后需要重启应用以确保数据正确加载
+ * 导入后后端会原地重新连接数据库，无需重启应用；前端重新加载页面即可刷新数据
  *
  * @returns Promise<ImportResult | null> 导入成功返回结果对象，取消返回 null
  */
 export async function importDatabase(): Promise<ImportResult | null> {
 	// 打开文件选择对话框
 	const filePath = await open({
-		filters: [{ name: "SQLite Database", extensions: ["db"] }],
+		filters: [{ name: "SQLite Database", extensions: ["db", "7z"] }],
 		multiple: false,
 		directory: false,
 	});
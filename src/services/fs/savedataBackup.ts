@@ -10,15 +10,23 @@ import { toError } from "@/utils/errors";
 export async function createGameSavedataBackup(
 	gameId: number,
 	saveDataPath: string,
+	auto = false,
 ): Promise<{ folder_name: string; backup_time: number; file_size: number }> {
 	try {
-		const backupInfo = await savedataService.createBackup(gameId, saveDataPath);
+		const backupInfo = await savedataService.createBackup(
+			gameId,
+			saveDataPath,
+			undefined,
+			auto,
+		);
 
 		await savedataService.saveSavedataRecord(
 			gameId,
 			backupInfo.folder_name,
 			backupInfo.backup_time,
 			backupInfo.file_size,
+			auto,
+			backupInfo.sha256,
 		);
 
 		return backupInfo;
@@ -1,10 +1,15 @@
 import { listen } from "@tauri-apps/api/event";
 import { createBackupAndSync } from "@/hooks/queries/useSavedata";
 import { queryClient } from "@/providers/queryClient";
-import { gameService, statsService } from "@/services/invoke";
+import { gameService, savedataService, statsService } from "@/services/invoke";
 import type { DailyStats } from "@/services/invoke/types";
 import { useStore } from "@/store/appStore";
-import type { GameSession, GameStatistics, GameTimeStats } from "@/types";
+import type {
+	GameSession,
+	GameStatistics,
+	GameTimeStats,
+	ProcessStats,
+} from "@/types";
 import { formatPlayTime, getLocalDateString } from "@/utils/dateTime";
 
 // 类型定义
@@ -21,6 +26,7 @@ export async function recordGameSession(
 	minutes: number,
 	startTime: number,
 	endTime: number,
+	processStats?: ProcessStats,
 ): Promise<number> {
 	// 当前日期，格式YYYY-MM-DD
 	const date = getLocalDateString(endTime);
@@ -33,6 +39,7 @@ export async function recordGameSession(
 			endTime,
 			minutes,
 			date,
+			processStats,
 		);
 
 		// 更新统计信息
@@ -378,10 +385,23 @@ export function initGameTimeTracking(
 		totalSeconds: number;
 		startTime: number;
 		endTime: number;
+		/** 游戏在前台但判定为挂机的时间（秒），仅 Windows 启用挂机判定时提供 */
+		idleSeconds?: number;
+		/** 会话期间系统睡眠的时间（秒） */
+		suspendedSeconds?: number;
 		processId: number;
+		/** 会话期间主进程的内存与 CPU 占用，没有采样时为 null */
+		processStats?: ProcessStats | null;
 	}>("game-session-ended", async (event) => {
-		const { gameId, totalMinutes, totalSeconds, startTime, endTime } =
-			event.payload;
+		const {
+			gameId,
+			totalMinutes,
+			totalSeconds,
+			startTime,
+			endTime,
+			suspendedSeconds = 0,
+			processStats,
+		} = event.payload;
 
 		try {
 			console.log("收到游戏会话结束事件:", event.payload);
@@ -394,8 +414,8 @@ export function initGameTimeTracking(
 			let effectiveMinutes: number;
 
 			if (timeTrackingMode === "elapsed") {
-				// 游戏启动时间模式：使用 endTime - startTime
-				effectiveSeconds = endTime - startTime;
+				// 游戏启动时间模式：使用 endTime - startTime，扣除系统睡眠的时间
+				effectiveSeconds = Math.max(0, endTime - startTime - suspendedSeconds);
 				effectiveMinutes = Math.round(effectiveSeconds / 60);
 				console.log(
 					`使用游戏启动时间模式: ${effectiveSeconds}秒 (${effectiveMinutes}分钟)`,
@@ -427,7 +447,13 @@ export function initGameTimeTracking(
 			const minutesToRecord = effectiveMinutes;
 
 			// 记录游戏会话
-			await recordGameSession(gameId, minutesToRecord, startTime, endTime);
+			await recordGameSession(
+				gameId,
+				minutesToRecord,
+				startTime,
+				endTime,
+				processStats ?? undefined,
+			);
 
 			// 先通知前端更新会话结束状态，避免被自动备份耗时阻塞 UI
 			if (onSessionEnd) {
@@ -442,13 +468,15 @@ export function initGameTimeTracking(
 						console.error("游戏数据未找到，无法进行自动备份");
 						return;
 					}
-					if (fullgame.autosave === 1 && fullgame.savepath) {
+					const backupSettings = await savedataService.getBackupSettings(gameId);
+					if (backupSettings.due && fullgame.savepath) {
 						console.log(
 							`开始自动备份游戏 ${gameId}，存档路径: ${fullgame.savepath}`,
 						);
 						await createBackupAndSync(queryClient, {
 							gameId,
 							savePath: fullgame.savepath,
+							auto: true,
 						});
 						console.log(`游戏 ${gameId} 自动备份完成`);
 					}
@@ -0,0 +1,42 @@
+/**
+ * @file 启动数据预取
+ * @description 启动画面期间通过 get_initial_app_state 一次性获取首屏数据并写入 React Query 缓存
+ */
+
+import type { QueryClient } from "@tanstack/react-query";
+import { collectionKeys } from "@/hooks/queries/useCollections";
+import { gameKeys } from "@/hooks/queries/useGames";
+import { settingsKeys } from "@/hooks/queries/useSettings";
+import { statsKeys } from "@/hooks/queries/useStats";
+import { gameService, type InitialAppState } from "@/services/invoke/gameService";
+import { useStore } from "@/store/appStore";
+import { getLocalDateString } from "@/utils/dateTime";
+
+/**
+ * 预取启动数据并填充设置、分组、游戏列表、运行中会话与今日游戏时间缓存
+ * @returns 完整的启动数据，供首屏渲染使用
+ */
+export async function primeInitialAppState(
+	queryClient: QueryClient,
+): Promise<InitialAppState> {
+	const { sortOption, sortOrder } = useStore.getState();
+	const state = await gameService.getInitialAppState(
+		sortOption,
+		sortOrder,
+		getLocalDateString(),
+	);
+
+	queryClient.setQueryData(settingsKeys.allSettings(), state.settings);
+	queryClient.setQueryData(
+		collectionKeys.groups(),
+		state.collections.filter((collection) => collection.parent_id === null),
+	);
+	// 启动数据只含首页游戏，不完整时标记为过期，挂载后再拉取完整列表
+	queryClient.setQueryData(gameKeys.all, state.games, {
+		updatedAt: state.games.length < state.total_games ? 0 : undefined,
+	});
+	queryClient.setQueryData(statsKeys.activeSessions(), state.active_sessions);
+	queryClient.setQueryData(statsKeys.todayPlayTime(), state.today_playtime);
+
+	return state;
+}
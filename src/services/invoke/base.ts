@@ -1,11 +1,34 @@
 /**
  * @file Service 基础类
- * @description 提供统一的错误归一化能力
+ * @description 提供统一的错误归一化能力与命令往返耗时统计
  */
 
 import { invoke, isTauri } from "@tauri-apps/api/core";
 import { AppError, normalizeTauriError } from "@/utils/errors";
 
+/** 每个命令保留的最近往返耗时数量 */
+const INVOKE_TIMING_CAPACITY = 64;
+
+const invokeTimings = new Map<string, number[]>();
+
+function recordInvokeTiming(command: string, elapsedMs: number) {
+	const samples = invokeTimings.get(command) ?? [];
+	if (samples.length >= INVOKE_TIMING_CAPACITY) {
+		samples.shift();
+	}
+	samples.push(elapsedMs);
+	invokeTimings.set(command, samples);
+}
+
+/**
+ * 获取前端记录的命令往返耗时（毫秒），按命令名分组
+ */
+export function getInvokeTimings(): Record<string, number[]> {
+	return Object.fromEntries(
+		Array.from(invokeTimings, ([command, samples]) => [command, [...samples]]),
+	);
+}
+
 /**
  * 基础 Service 类
  */
@@ -27,10 +50,13 @@ export class BaseService {
 			});
 		}
 
+		const started = performance.now();
 		try {
 			return await invoke<T>(command, args);
 		} catch (error) {
 			throw normalizeTauriError(error, { command, args });
+		} finally {
+			recordInvokeTiming(command, performance.now() - started);
 		}
 	}
 }
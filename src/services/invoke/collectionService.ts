@@ -6,6 +6,40 @@
 import type { CollectionCategory, CollectionGroup } from "@/types/collection";
 import { BaseService } from "./base";
 
+/** 删除合集时游戏关联的去向 */
+export type CollectionReassignment =
+	| { type: "collection"; id: number }
+	| { type: "parent" };
+
+/** 任意层级的合集树节点，game_count 包含所有子孙合集（去重） */
+export interface CollectionTreeNode {
+	id: number;
+	name: string;
+	parent_id: number | null;
+	sort_order: number;
+	icon: string | null;
+	game_count: number;
+	children: CollectionTreeNode[];
+}
+
+export interface CollectionTransferResult {
+	added: number;
+	skipped: number;
+	removed: number;
+}
+
+export interface PopulateCollectionResult {
+	matched: number;
+	added: number;
+	skipped: number;
+}
+
+export interface DeleteCollectionResult {
+	deleted: number;
+	moved_links: number;
+	skipped_links: number;
+}
+
 class CollectionService extends BaseService {
 	/**
 	 * 创建合集
@@ -23,7 +57,14 @@ class CollectionService extends BaseService {
 	}
 
 	/**
-	 * 更新合集
+	 * 获取完整的合集树（任意层级）
+	 */
+	async getCollectionTree(): Promise<CollectionTreeNode[]> {
+		return this.invoke<CollectionTreeNode[]>("get_collection_tree");
+	}
+
+	/**
+	 * 更新合集（不能把合集移动到自身或其子合集下）
 	 */
 	async updateCollection(
 		id: number,
@@ -41,9 +82,58 @@ class CollectionService extends BaseService {
 
 	/**
 	 * 删除合集
+	 * @param reassignTo 可选，把合集（及子合集）中的游戏移到指定合集或上级分组
+	 */
+	async deleteCollection(
+		id: number,
+		reassignTo?: CollectionReassignment,
+	): Promise<DeleteCollectionResult> {
+		return this.invoke<DeleteCollectionResult>("delete_collection", {
+			id,
+			reassignTo: reassignTo ?? null,
+		});
+	}
+
+	/**
+	 * 把游戏从一个合集移动到另一个合集（单个事务）
+	 */
+	async moveGamesBetweenCollections(
+		gameIds: number[],
+		fromId: number,
+		toId: number,
+	): Promise<CollectionTransferResult> {
+		return this.invoke<CollectionTransferResult>(
+			"move_games_between_collections",
+			{ gameIds, fromId, toId },
+		);
+	}
+
+	/**
+	 * 把游戏复制到合集，已在合集中的游戏跳过
+	 */
+	async copyGamesToCollection(
+		gameIds: number[],
+		toId: number,
+	): Promise<CollectionTransferResult> {
+		return this.invoke<CollectionTransferResult>("copy_games_to_collection", {
+			gameIds,
+			toId,
+		});
+	}
+
+	/**
+	 * 把元数据标签匹配的游戏批量加入合集
+	 * @param matchMode any: 包含任一标签；all: 包含全部标签
 	 */
-	async deleteCollection(id: number): Promise<number> {
-		return this.invoke<number>("delete_collection", { id });
+	async populateCollectionFromTags(
+		collectionId: number,
+		tags: string[],
+		matchMode: "any" | "all" = "any",
+	): Promise<PopulateCollectionResult> {
+		return this.invoke<PopulateCollectionResult>(
+			"populate_collection_from_tags",
+			{ collectionId, tags, matchMode },
+		);
 	}
 
 	/**
@@ -113,6 +203,35 @@ class CollectionService extends BaseService {
 		});
 	}
 
+	/**
+	 * 调整合集内游戏的顺序（拖拽排序）
+	 * 后端采用稀疏排序，通常只更新被移动的游戏；未列出的游戏保持原顺序排在末尾
+	 * @returns 实际更新的记录数
+	 */
+	async reorderCollection(
+		collectionId: number,
+		orderedIds: number[],
+	): Promise<number> {
+		return this.invoke<number>("reorder_collection", {
+			collectionId,
+			orderedIds,
+		});
+	}
+
+	/**
+	 * 调整同一父级下合集的顺序（拖拽排序），parentId 为 null 时调整根分组
+	 * @returns 实际更新的记录数
+	 */
+	async reorderCollections(
+		parentId: number | null,
+		orderedIds: number[],
+	): Promise<number> {
+		return this.invoke<number>("reorder_collections", {
+			parentId,
+			orderedIds,
+		});
+	}
+
 	// ==================== 前端友好的组合 API ====================
 
 	/**
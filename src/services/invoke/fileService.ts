@@ -3,18 +3,31 @@
  * @description 封装文件系统、目录打开与数据库备份/导入相关后端调用
  */
 
-import type { ScanResult } from "@/types";
+import type { ScanConfig, ScannedGame } from "@/types";
 import { BaseService } from "./base";
 
 export interface BackupResult {
 	success: boolean;
 	path: string | null;
 	message: string;
+	/** 按数据库备份保留策略删除的旧备份数量 */
+	pruned_backups?: number;
+}
+
+export interface SettingsImportResult {
+	/** 写入的设置项（camelCase） */
+	keys: string[];
+	/** 是否使用了导入文件中的令牌 */
+	secrets_restored: boolean;
+	/** 导入文件中的焦点窗口探测命令是否因未获同意而被忽略 */
+	probe_command_skipped: boolean;
 }
 
 export interface BackupOptions {
 	auto?: boolean;
 	maxAutoBackups?: number;
+	/** 压缩为 reina_manager_*.db.7z */
+	compress?: boolean;
 }
 
 export interface ImportResult {
@@ -32,20 +45,236 @@ export interface PortableModeResult {
 	is_portable: boolean;
 }
 
+export interface PortableSwitchPreview {
+	enabled: boolean;
+	source_dir: string;
+	destination_dir: string;
+	files: {
+		source: string;
+		destination: string;
+		size: number;
+		conflict: boolean;
+	}[];
+	total_size: number;
+	required_space: number;
+	available_space: number | null;
+	excluded: { path: string; reason: string }[];
+	blockers: {
+		kind:
+			| "already_in_mode"
+			| "read_only_destination"
+			| "insufficient_space"
+			| "custom_dir_in_database_dir"
+			| "game_running";
+		message: string;
+	}[];
+}
+
+/** 数据目录迁移结果 */
+export interface DataMigrationResult {
+	/** 迁移后是否为便携模式 */
+	enabled: boolean;
+	source_dir: string;
+	destination_dir: string;
+	database_files: number;
+	backup_files: number;
+	/** 含云端封面缓存、自定义封面与缩略图 */
+	cover_files: number;
+	screenshot_files: number;
+	crash_report_files: number;
+	launch_log_files: number;
+	/** 因被自定义路径引用而留在原处的资源目录 */
+	skipped_dirs: string[];
+}
+
+export interface DataRootInfo {
+	/** 当前使用的基础数据目录 */
+	path: string;
+	/** 系统默认的数据目录 */
+	default_path: string;
+	/** 是否使用了自定义数据目录（便携模式下不生效） */
+	custom: boolean;
+	is_portable: boolean;
+}
+
+export interface FileLockEvent {
+	path: string;
+	occurred_at: number;
+	attempts: number;
+	recovered: boolean;
+}
+
+export interface FileLockDiagnostics {
+	events: FileLockEvent[];
+	recent_count: number;
+	unrecovered_count: number;
+	suggest_exclusion: boolean;
+	suggested_paths: string[];
+	exclusion_command: string | null;
+}
+
+/** 数据完整性检查报告 */
+export interface IntegrityReport {
+	missing_savedata_files: { id: number; game_id: number; file: string }[];
+	/** 游戏已删除但仍存在的统计行（game_id） */
+	orphaned_statistics: number[];
+	orphaned_collection_links: {
+		id: number;
+		game_id: number;
+		collection_id: number;
+	}[];
+	/** 可执行文件不存在的游戏，游戏位于未连接的移动硬盘上时也会出现 */
+	missing_local_paths: { game_id: number; localpath: string }[];
+}
+
+/** 要修复的完整性问题 */
+export interface IntegritySelection {
+	savedata_record_ids?: number[];
+	statistics_game_ids?: number[];
+	collection_link_ids?: number[];
+	localpath_game_ids?: number[];
+}
+
+export interface IntegrityFixResult {
+	removed_savedata_records: number;
+	removed_statistics: number;
+	removed_collection_links: number;
+	cleared_localpaths: number;
+}
+
+/** 可执行文件已不存在的游戏及建议的新路径 */
+export interface MissingGamePath {
+	game_id: number;
+	localpath: string;
+	/** 最可能的新路径，可通过 gameService.updateBatch 写回 localpath */
+	suggested_path: string | null;
+	candidates: string[];
+}
+
+export interface GamePathReport {
+	checked: number;
+	library_roots: string[];
+	missing: MissingGamePath[];
+}
+
+/** cacheCover 的结果 */
+export interface CachedCover {
+	game_id: number;
+	/** 缓存文件的本地路径 */
+	path: string;
+	size: number;
+	/** 本次是否实际下载（已有缓存时为 false） */
+	downloaded: boolean;
+	/** 是否与其他游戏的封面内容相同，已共享同一份文件 */
+	deduplicated: boolean;
+}
+
+export interface PurgeCoversResult {
+	removed_dirs: number;
+	removed_temp_files: number;
+	/** 释放的空间（字节） */
+	freed_bytes: number;
+}
+
+export interface CoverThumbnail {
+	/** 请求的宽度；原图更窄时实际宽度为原图宽度 */
+	size: number;
+	path: string;
+}
+
+export interface CoverThumbnails {
+	game_id: number;
+	/** 生成缩略图所用的原图 */
+	source: string;
+	thumbnails: CoverThumbnail[];
+}
+
+export interface ThumbnailReport {
+	covers: CoverThumbnails[];
+	/** 本次新生成的缩略图数量 */
+	generated: number;
+	/** 没有本地封面的游戏 */
+	missing: number[];
+	errors: string[];
+}
+
+/** library-scan-progress 事件负载，每扫描完一个一级子目录发出一次 */
+export interface LibraryScanProgress {
+	scanId: number;
+	scanned: number;
+	total: number;
+	currentDir: string;
+	/** 目前识别出的游戏数 */
+	found: number;
+}
+
+/** library-scan-found 事件负载 */
+export interface LibraryScanFound {
+	scanId: number;
+	game: ScannedGame;
+}
+
+/** library-scan-finished 事件负载 */
+export interface LibraryScanFinished {
+	scanId: number;
+	found: number;
+	cancelled: boolean;
+	error: string | null;
+	elapsedMs: number;
+}
+
 class FileService extends BaseService {
 	/**
 	 * 扫描目录下的游戏文件夹
+	 * @param maxDepth 扫描深度，不传时使用扫描规则中的默认深度
+	 * @param config 本次扫描使用的规则，不传时使用用户设置中保存的规则
+	 * @param matchMetadata 是否按游戏名在 BGM / VNDB 搜索候选
 	 */
 	async scanDirectoryForGames(
 		path: string,
-		maxDepth: number,
-	): Promise<ScanResult[]> {
-		return this.invoke<ScanResult[]>("scan_directory_for_games", {
+		maxDepth?: number,
+		config?: ScanConfig,
+		matchMetadata?: boolean,
+	): Promise<ScannedGame[]> {
+		return this.invoke<ScannedGame[]>("scan_directory_for_games", {
+			path,
+			maxDepth,
+			config,
+			matchMetadata,
+		});
+	}
+
+	/**
+	 * 在后台扫描游戏库目录，结果通过 library-scan-* 事件推送
+	 * @returns 扫描会话 ID
+	 */
+	async startLibraryScan(
+		path: string,
+		maxDepth?: number,
+		config?: ScanConfig,
+	): Promise<number> {
+		return this.invoke<number>("start_library_scan", {
 			path,
 			maxDepth,
+			config,
 		});
 	}
 
+	/**
+	 * 取消后台扫描
+	 * @returns 扫描是否仍在进行
+	 */
+	async cancelLibraryScan(scanId: number): Promise<boolean> {
+		return this.invoke<boolean>("cancel_library_scan", { scanId });
+	}
+
+	/**
+	 * 检查所有游戏的可执行文件，并为失效路径在同级游戏库目录中寻找新路径
+	 */
+	async validateGamePaths(): Promise<GamePathReport> {
+		return this.invoke<GamePathReport>("validate_game_paths");
+	}
+
 	/**
 	 * 打开目录
 	 */
@@ -60,6 +289,33 @@ class FileService extends BaseService {
 		return this.invoke<PortableModeResult>("is_portable_mode");
 	}
 
+	/**
+	 * 预览切换便携模式时迁移会移动的文件、所需空间与阻止切换的问题，不修改任何文件
+	 * @param enabled 切换后是否为便携模式
+	 */
+	async previewPortableMigration(
+		enabled: boolean,
+	): Promise<PortableSwitchPreview> {
+		return this.invoke<PortableSwitchPreview>("preview_portable_migration", {
+			enabled,
+		});
+	}
+
+	/**
+	 * 获取当前数据目录信息
+	 */
+	async getDataRoot(): Promise<DataRootInfo> {
+		return this.invoke<DataRootInfo>("get_data_root");
+	}
+
+	/**
+	 * 把安装模式的数据目录迁移到自定义位置，迁移期间会短暂关闭数据库连接
+	 * @param path 新的数据目录（绝对路径），不传时恢复为系统默认目录
+	 */
+	async setDataRoot(path?: string): Promise<DataMigrationResult> {
+		return this.invoke<DataMigrationResult>("set_data_root", { path });
+	}
+
 	/**
 	 * 复制文件
 	 */
@@ -68,12 +324,19 @@ class FileService extends BaseService {
 	}
 
 	/**
-	 * 删除文件
+	 * 删除文件，按设置移到回收站或永久删除
 	 */
 	async deleteFile(filePath: string): Promise<void> {
 		return this.invoke<void>("delete_file", { filePath });
 	}
 
+	/**
+	 * 获取文件占用诊断（杀毒软件锁定文件导致的备份/迁移失败）
+	 */
+	async getFileLockDiagnostics(): Promise<FileLockDiagnostics> {
+		return this.invoke<FileLockDiagnostics>("get_file_lock_diagnostics");
+	}
+
 	/**
 	 * 从剪贴板导入图片到临时文件
 	 */
@@ -95,6 +358,44 @@ class FileService extends BaseService {
 		return this.invoke<void>("delete_cloud_cache", { gameId });
 	}
 
+	/**
+	 * 提前下载并缓存游戏的云端封面，之后可离线显示
+	 */
+	async cacheCover(gameId: number, url: string): Promise<CachedCover> {
+		return this.invoke<CachedCover>("cache_cover", { gameId, url });
+	}
+
+	/**
+	 * 清理已删除游戏的封面目录与下载残留的临时文件
+	 */
+	async purgeUnusedCovers(): Promise<PurgeCoversResult> {
+		return this.invoke<PurgeCoversResult>("purge_unused_covers");
+	}
+
+	/**
+	 * 提取游戏可执行文件中分辨率最高的图标，保存为 PNG（仅 Windows）
+	 * @param gamePath 游戏可执行文件路径
+	 * @returns PNG 文件路径，可作为没有元数据的游戏的兜底封面
+	 */
+	async extractExeIcon(gamePath: string): Promise<string> {
+		return this.invoke<string>("extract_exe_icon", { gamePath });
+	}
+
+	/**
+	 * 为游戏当前使用的封面生成 WebP 缩略图，供网格视图使用
+	 * @param sizes 缩略图宽度（像素），限制在 32 ~ 1024，最多 4 个
+	 * @param gameIds 只处理这些游戏，未传入时处理全部游戏
+	 */
+	async generateThumbnails(
+		sizes: number[],
+		gameIds: number[] | null = null,
+	): Promise<ThumbnailReport> {
+		return this.invoke<ThumbnailReport>("generate_thumbnails", {
+			sizes,
+			gameIds,
+		});
+	}
+
 	/**
 	 * 备份数据库
 	 */
@@ -113,6 +414,69 @@ class FileService extends BaseService {
 		return this.invoke<BackupResult>("backup_custom_covers", { options });
 	}
 
+	/**
+	 * 导出设置为 JSON（LE / Magpie 路径、扫描配置、备份策略等）
+	 * @param includeSecrets 为 true 时包含 BGM / VNDB 令牌与 HTTP API 访问令牌
+	 */
+	async exportSettings(
+		path: string,
+		includeSecrets = false,
+	): Promise<BackupResult> {
+		return this.invoke<BackupResult>("export_settings", {
+			path,
+			includeSecrets,
+		});
+	}
+
+	/**
+	 * 从 JSON 导入设置，覆盖当前设置；文件不含令牌时保留当前令牌
+	 * @param allowProbeCommand 为 true 时使用导入文件中的焦点窗口探测命令（会作为 shell 命令执行）
+	 */
+	async importSettings(
+		path: string,
+		allowProbeCommand = false,
+	): Promise<SettingsImportResult> {
+		return this.invoke<SettingsImportResult>("import_settings", {
+			path,
+			allowProbeCommand,
+		});
+	}
+
+	/**
+	 * 导出游戏统计为 CSV
+	 * @param scope sessions 为会话明细，daily 为按游戏和日期汇总
+	 * @param language 为 zh-CN 时优先使用中文游戏名
+	 */
+	async exportStatisticsCsv(
+		path: string,
+		scope: "sessions" | "daily",
+		language?: string,
+	): Promise<BackupResult> {
+		return this.invoke<BackupResult>("export_statistics_csv", {
+			path,
+			scope,
+			language,
+		});
+	}
+
+	/**
+	 * 检查数据库记录与磁盘文件的一致性（只读）
+	 */
+	async runIntegrityCheck(): Promise<IntegrityReport> {
+		return this.invoke<IntegrityReport>("run_integrity_check");
+	}
+
+	/**
+	 * 清理选中的完整性问题
+	 */
+	async fixIntegrityIssues(
+		selection: IntegritySelection,
+	): Promise<IntegrityFixResult> {
+		return this.invoke<IntegrityFixResult>("fix_integrity_issues", {
+			selection,
+		});
+	}
+
 	/**
 	 * 导入数据库
 	 */
@@ -12,13 +12,135 @@
 
 import type {
 	BatchOperationResult,
+	CollectionGroup,
+	CrossIdCandidate,
 	FullGameData,
 	InsertGameParams,
+	PlayStatus,
+	Screenshot,
 	UpdateGameParams,
 } from "@/types";
 import { BaseService } from "./base";
+import type { UserSettings } from "./settingsService";
 import type { GameType, SortOption, SortOrder } from "./types";
 
+/** 单个游戏状态的游戏数量 */
+export interface PlayStatusCount {
+	status: PlayStatus;
+	count: number;
+}
+
+/** 跨数据源 ID 匹配结果，只包含游戏缺失的数据源 */
+export interface CrossIdResolution {
+	game_id: number;
+	query: string | null;
+	bgm: CrossIdCandidate[];
+	vndb: CrossIdCandidate[];
+	ymgal: CrossIdCandidate[];
+	errors: string[];
+}
+
+/** 截图文件夹导入结果 */
+export interface ScreenshotImportResult {
+	imported: number;
+	skipped: number;
+}
+
+export type MetadataColumn =
+	| "bgm_data"
+	| "vndb_data"
+	| "ymgal_data"
+	| "kun_data"
+	| "custom_data"
+	| "date";
+
+export interface FieldDiff {
+	column: MetadataColumn;
+	field: string;
+	old_value: unknown;
+	new_value: unknown;
+}
+
+export interface GameMetadataDiff {
+	game_id: number;
+	fields: FieldDiff[];
+}
+
+export interface AutoClearEvent {
+	gameId: number;
+	reason: "external_finished" | "playtime_threshold";
+	previousStatus: number | null;
+	newStatus: number;
+	totalMinutes?: number;
+	averageHours?: number;
+}
+
+export interface AcceptedFieldChange {
+	game_id: number;
+	column: MetadataColumn;
+	field: string;
+	value: unknown;
+}
+
+export interface ActiveSessionInfo {
+	gameId: number;
+	processId: number;
+	startTime: number;
+	totalSeconds: number;
+}
+
+/** 删除游戏文件时清单中的一个目录 */
+export interface RemovalItem {
+	kind:
+		| "game_folder"
+		| "save_folder"
+		| "savedata_backups"
+		| "covers"
+		| "screenshots";
+	path: string;
+	/** 占用空间（字节） */
+	size: number;
+	file_count: number;
+}
+
+/** 删除游戏文件的清单 */
+export interface GameRemovalManifest {
+	game_id: number;
+	name: string | null;
+	also_saves: boolean;
+	/** 将被删除的目录 */
+	items: RemovalItem[];
+	/** 保留在磁盘上的目录 */
+	kept: RemovalItem[];
+	session_count: number;
+	savedata_record_count: number;
+	/** 将释放的空间（字节） */
+	total_size: number;
+	/** 是否移到回收站 */
+	use_trash: boolean;
+}
+
+export interface GameRemovalResult {
+	manifest: GameRemovalManifest;
+	/** 实际删除的空间（字节） */
+	freed_bytes: number;
+	trashed: boolean;
+	/** 游戏记录删除后，清理其余目录时的错误 */
+	errors: string[];
+}
+
+/** 启动时一次性获取的初始数据 */
+export interface InitialAppState {
+	settings: UserSettings;
+	/** 所有合集的扁平列表，parent_id 为 null 的是分组 */
+	collections: (CollectionGroup & { parent_id: number | null })[];
+	games: FullGameData[];
+	total_games: number;
+	active_sessions: ActiveSessionInfo[];
+	/** 今天所有游戏的游戏时间（分钟） */
+	today_playtime: number;
+}
+
 class GameService extends BaseService {
 	/**
 	 * 插入游戏数据（单表架构）
@@ -53,15 +175,37 @@ class GameService extends BaseService {
 		sortOption: SortOption = "addtime",
 		sortOrder: SortOrder = "asc",
 		language?: string,
+		playStatus?: PlayStatus,
 	): Promise<FullGameData[]> {
 		return this.invoke<FullGameData[]>("find_all_games", {
 			gameType,
+			playStatus: playStatus ?? null,
 			sortOption,
 			sortOrder,
 			language: language ?? null,
 		});
 	}
 
+	/**
+	 * 一次性获取启动所需的数据（设置、合集、首页游戏、活跃会话、今日游戏时间）
+	 * 在启动阶段调用，代替多次独立调用
+	 */
+	async getInitialAppState(
+		sortOption: SortOption,
+		sortOrder: SortOrder,
+		today: string,
+		language?: string,
+		pageSize?: number,
+	): Promise<InitialAppState> {
+		return this.invoke<InitialAppState>("get_initial_app_state", {
+			sortOption,
+			sortOrder,
+			language: language ?? null,
+			pageSize,
+			today,
+		});
+	}
+
 	/**
 	 * 只返回排序/筛选后的游戏 ID 列表
 	 *
@@ -73,15 +217,34 @@ class GameService extends BaseService {
 		sortOption: SortOption = "addtime",
 		sortOrder: SortOrder = "asc",
 		language?: string,
+		playStatus?: PlayStatus,
 	): Promise<number[]> {
 		return this.invoke<number[]>("find_game_ids", {
 			gameType,
+			playStatus: playStatus ?? null,
 			sortOption,
 			sortOrder,
 			language: language ?? null,
 		});
 	}
 
+	/**
+	 * 批量设置游戏状态，返回更新的游戏数量
+	 */
+	async setPlayStatus(
+		gameIds: number[],
+		status: PlayStatus,
+	): Promise<number> {
+		return this.invoke<number>("set_play_status", { gameIds, status });
+	}
+
+	/**
+	 * 按游戏状态统计游戏数量（包含数量为 0 的状态）
+	 */
+	async countByStatus(): Promise<PlayStatusCount[]> {
+		return this.invoke<PlayStatusCount[]>("count_by_status");
+	}
+
 	/**
 	 * 更新游戏数据（单表架构）
 	 *
@@ -110,6 +273,36 @@ class GameService extends BaseService {
 		return this.invoke<number>("delete_game", { id });
 	}
 
+	/**
+	 * 预览删除游戏文件时会删除的内容
+	 * @param alsoSaves 是否同时删除存档目录与存档备份
+	 */
+	async previewGameRemoval(
+		gameId: number,
+		alsoSaves: boolean,
+	): Promise<GameRemovalManifest> {
+		return this.invoke<GameRemovalManifest>("preview_game_removal", {
+			gameId,
+			alsoSaves,
+		});
+	}
+
+	/**
+	 * 删除游戏记录及其在磁盘上的文件
+	 * @param confirmedPaths 用户确认过的清单路径，与当前清单不一致时拒绝删除
+	 */
+	async removeGameFiles(
+		gameId: number,
+		alsoSaves: boolean,
+		confirmedPaths: string[],
+	): Promise<GameRemovalResult> {
+		return this.invoke<GameRemovalResult>("remove_game_files", {
+			gameId,
+			alsoSaves,
+			confirmedPaths,
+		});
+	}
+
 	/**
 	 * 批量删除游戏
 	 */
@@ -167,6 +360,159 @@ class GameService extends BaseService {
 	): Promise<FullGameData[]> {
 		return this.invoke<FullGameData[]>("update_games_batch", { updates });
 	}
+
+	/**
+	 * 预览批量元数据更新，返回逐字段的新旧值差异（不写入数据库）
+	 *
+	 * @param updates 更新列表 [[gameId, updates], ...]
+	 */
+	async previewMetadataUpdates(
+		updates: Array<[number, UpdateGameParams]>,
+	): Promise<GameMetadataDiff[]> {
+		return this.invoke<GameMetadataDiff[]>("preview_metadata_updates", {
+			updates,
+		});
+	}
+
+	/**
+	 * 应用用户选择接受的字段变更，在单个事务内提交
+	 *
+	 * @param accepted 被接受的字段列表，value 为 null 表示删除该字段
+	 */
+	async applyMetadataDiff(
+		accepted: AcceptedFieldChange[],
+	): Promise<FullGameData[]> {
+		return this.invoke<FullGameData[]>("apply_metadata_diff", { accepted });
+	}
+
+	/**
+	 * 回报外部收藏同步得到的游戏状态，由后端按自动标记规则决定是否更新
+	 *
+	 * @returns 命中规则时返回 `game-auto-cleared` 事件内容，否则为 null
+	 */
+	async reportExternalPlayStatus(
+		gameId: number,
+		externalStatus: number,
+	): Promise<AutoClearEvent | null> {
+		return this.invoke<AutoClearEvent | null>("report_external_play_status", {
+			gameId,
+			externalStatus,
+		});
+	}
+
+	/**
+	 * 截取正在前台运行的游戏窗口（仅 Windows）
+	 */
+	async captureGameScreenshot(gameId: number): Promise<Screenshot> {
+		return this.invoke<Screenshot>("capture_game_screenshot", { gameId });
+	}
+
+	/**
+	 * 分页获取游戏的截图（按截取时间倒序）
+	 * @param limit 每页数量，默认 50
+	 */
+	async getScreenshots(
+		gameId: number,
+		limit?: number,
+		offset?: number,
+	): Promise<Screenshot[]> {
+		return this.invoke<Screenshot[]>("get_screenshots", {
+			gameId,
+			limit,
+			offset,
+		});
+	}
+
+	/**
+	 * 删除截图记录；应用截图目录中的图片一并删除，外部导入的图片只移出图库
+	 */
+	async deleteScreenshot(screenshotId: number): Promise<void> {
+		return this.invoke<void>("delete_screenshot", { screenshotId });
+	}
+
+	/**
+	 * 把已有文件夹中的截图原地导入到游戏图库，已导入的文件会被跳过
+	 */
+	async importScreenshotsFromFolder(
+		gameId: number,
+		path: string,
+	): Promise<ScreenshotImportResult> {
+		return this.invoke<ScreenshotImportResult>(
+			"import_screenshots_from_folder",
+			{ gameId, path },
+		);
+	}
+
+	/**
+	 * 把游戏自带截图目录（screenshot_dir）中的新图片复制到图库，并按时间关联游戏会话
+	 */
+	async importEngineScreenshots(
+		gameId: number,
+	): Promise<ScreenshotImportResult> {
+		return this.invoke<ScreenshotImportResult>("import_engine_screenshots", {
+			gameId,
+		});
+	}
+
+	/**
+	 * 重新注册截图快捷键，保存监控设置的 screenshot_hotkey 后调用
+	 * @param shortcut 如 "Ctrl+Shift+S"，null 表示关闭
+	 */
+	async registerScreenshotHotkey(shortcut: string | null): Promise<void> {
+		return this.invoke<void>("register_screenshot_hotkey", { shortcut });
+	}
+
+	/**
+	 * 重新注册停止游戏 / 暂停计时快捷键，保存监控设置的 stop_game_hotkey、pause_timing_hotkey 后调用
+	 * @param stopShortcut 停止最近启动的游戏，null 表示关闭
+	 * @param pauseShortcut 暂停 / 恢复最近启动的游戏的计时，null 表示关闭
+	 */
+	async registerSessionHotkeys(
+		stopShortcut: string | null,
+		pauseShortcut: string | null,
+	): Promise<void> {
+		return this.invoke<void>("register_session_hotkeys", {
+			stopShortcut,
+			pauseShortcut,
+		});
+	}
+
+	/**
+	 * 暂停或恢复游戏计时，返回切换后是否暂停；切换后会发送 game-timing-paused 事件
+	 */
+	async toggleSessionPause(gameId: number): Promise<boolean> {
+		return this.invoke<boolean>("toggle_session_pause", { gameId });
+	}
+
+	/**
+	 * 在桌面创建游戏快捷方式，返回快捷方式文件路径
+	 */
+	async createDesktopShortcut(
+		gameId: number,
+		language?: string,
+	): Promise<string> {
+		return this.invoke<string>("create_desktop_shortcut", {
+			gameId,
+			language,
+		});
+	}
+
+	/**
+	 * 按已有元数据的标题为缺失的数据源查找候选条目（不写入数据库）
+	 */
+	async resolveCrossIds(gameId: number): Promise<CrossIdResolution> {
+		return this.invoke<CrossIdResolution>("resolve_cross_ids", { gameId });
+	}
+
+	/**
+	 * 写入用户确认的跨数据源 ID，未传入的 ID 保持不变
+	 */
+	async applyCrossIds(
+		gameId: number,
+		ids: { bgmId?: string; vndbId?: string; ymgalId?: string },
+	): Promise<FullGameData> {
+		return this.invoke<FullGameData>("apply_cross_ids", { gameId, ...ids });
+	}
 }
 
 // 导出单例
@@ -3,10 +3,12 @@
  * @description 提供所有 service 的统一访问入口
  */
 
+export { getInvokeTimings } from "./base";
 export { collectionService } from "./collectionService";
 export type {
 	BackupOptions,
 	BackupResult,
+	FileLockDiagnostics,
 	ImportResult,
 	MoveBackupFolderResult,
 } from "./fileService";
@@ -14,8 +16,14 @@ export { fileService } from "./fileService";
 // 导出所有服务
 export { gameService } from "./gameService";
 export { savedataService } from "./savedataService";
-export type { ProxyConfig, UserSettings } from "./settingsService";
+export type {
+	PerformanceMetrics,
+	ProxyConfig,
+	UserSettings,
+} from "./settingsService";
 export { settingsService } from "./settingsService";
+export type { SetupState, SetupStep } from "./setupService";
+export { setupService } from "./setupService";
 export { statsService } from "./statsService";
 // 导出类型
 export type { DailyStats, GameType, SortOption, SortOrder } from "./types";
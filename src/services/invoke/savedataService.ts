@@ -3,7 +3,11 @@
  * @description 封装所有存档备份相关的后端调用
  */
 
-import type { SavedataRecord } from "@/types";
+import type {
+	BackupCompression,
+	BackupSchedule,
+	SavedataRecord,
+} from "@/types";
 import { BaseService } from "./base";
 
 /** 备份信息 */
@@ -12,6 +16,98 @@ export interface BackupInfo {
 	backup_time: number;
 	file_size: number;
 	backup_path: string;
+	encrypted: boolean;
+	/** 压缩包的 SHA-256 */
+	sha256: string;
+	/** 实际使用的压缩设置 */
+	compression: BackupCompression;
+}
+
+/** 旧备份哈希补算结果 */
+export interface RehashResult {
+	hashed: number;
+	missing: number[];
+	failed: number[];
+}
+
+/** 游戏生效的自动备份计划 */
+export interface GameBackupSettings {
+	game_id: number;
+	enabled: boolean;
+	interval_minutes: number;
+	/** null 表示不单独限制自动备份数量 */
+	max_auto_backups: number | null;
+	/** 会话中自动备份的间隔（累计游玩分钟），0 表示不在会话中备份 */
+	play_interval_minutes: number;
+	max_backups: number | null;
+	last_auto_backup: number | null;
+	next_auto_backup: number | null;
+	/** 现在结束会话是否会触发自动备份 */
+	due: boolean;
+	overrides: BackupSchedule;
+	global: BackupSchedule;
+}
+
+/** 备份目录用量 */
+export interface SavedataUsage {
+	used_bytes: number;
+	limit_bytes: number | null;
+}
+
+/** 备份自检步骤结果 */
+export interface SelfTestStep {
+	name: string;
+	success: boolean;
+	message: string;
+	duration_ms: number;
+}
+
+/** 备份自检报告 */
+export interface SelfTestReport {
+	passed: boolean;
+	backup_root: string;
+	steps: SelfTestStep[];
+}
+
+/** 自动检测到的候选存档目录 */
+export interface SaveCandidate {
+	path: string;
+	confidence: number; // 0-1
+	source: "game_dir" | "user_dir" | "registry";
+	reason: string;
+	save_file_count: number;
+}
+
+/** 备份压缩包内的单个条目 */
+export interface BackupEntry {
+	path: string;
+	size: number;
+	modified: number | null; // Unix 时间戳（秒）
+	is_directory: boolean;
+}
+
+/** 恢复时会被覆盖的本地文件 */
+export interface OverwrittenFile {
+	path: string;
+	backup_size: number;
+	backup_modified: number | null;
+	local_size: number;
+	local_modified: number | null;
+	local_newer: boolean;
+}
+
+/** 恢复冲突报告（dry-run） */
+export interface RestoreConflictReport {
+	target_path: string;
+	overwritten: OverwrittenFile[];
+	added: string[];
+	removed: string[];
+}
+
+export interface BackupContents {
+	entries: BackupEntry[];
+	total_size: number;
+	conflicts: RestoreConflictReport | null;
 }
 
 class SavedataService extends BaseService {
@@ -19,14 +115,34 @@ class SavedataService extends BaseService {
 	 * 创建存档备份
 	 * @param gameId 游戏ID
 	 * @param sourcePath 存档文件夹路径
+	 * @param password 可选密码，提供时生成加密备份
+	 * @param auto 是否为会话结束后的自动备份，为 true 时按自动备份计划清理旧的自动备份
+	 * @param compression 本次备份的压缩设置，未设置的字段沿用全局设置
 	 */
-	async createBackup(gameId: number, sourcePath: string): Promise<BackupInfo> {
+	async createBackup(
+		gameId: number,
+		sourcePath: string,
+		password?: string,
+		auto?: boolean,
+		compression?: BackupCompression,
+	): Promise<BackupInfo> {
 		return this.invoke<BackupInfo>("create_savedata_backup", {
 			gameId,
 			sourcePath,
+			password,
+			auto,
+			compression,
 		});
 	}
 
+	/**
+	 * 获取游戏生效的自动备份计划（合并全局设置与游戏覆盖）
+	 * @param gameId 游戏ID
+	 */
+	async getBackupSettings(gameId: number): Promise<GameBackupSettings> {
+		return this.invoke<GameBackupSettings>("get_backup_settings", { gameId });
+	}
+
 	/**
 	 * 删除备份文件和数据库记录（二合一）
 	 * @param backupId 备份记录ID
@@ -39,17 +155,46 @@ class SavedataService extends BaseService {
 	 * 恢复存档备份
 	 * @param backupFilePath 备份文件完整路径
 	 * @param targetPath 目标恢复路径
+	 * @param password 加密备份的密码
 	 */
 	async restoreBackup(
 		backupFilePath: string,
 		targetPath: string,
+		password?: string,
 	): Promise<void> {
 		return this.invoke<void>("restore_savedata_backup", {
 			backupFilePath,
 			targetPath,
+			password,
+		});
+	}
+
+	/**
+	 * 列出备份内的文件，提供 targetPath 时同时生成恢复冲突报告
+	 * @param backupFilePath 备份文件完整路径
+	 * @param password 加密备份的密码
+	 * @param targetPath 可选恢复目标路径
+	 */
+	async listBackupContents(
+		backupFilePath: string,
+		password?: string,
+		targetPath?: string,
+	): Promise<BackupContents> {
+		return this.invoke<BackupContents>("list_savedata_backup_contents", {
+			backupFilePath,
+			password,
+			targetPath,
 		});
 	}
 
+	/**
+	 * 检查存档备份是否加密
+	 * @param backupFilePath 备份文件完整路径
+	 */
+	async isBackupEncrypted(backupFilePath: string): Promise<boolean> {
+		return this.invoke<boolean>("is_backup_encrypted", { backupFilePath });
+	}
+
 	/**
 	 * 保存存档备份记录
 	 */
@@ -58,12 +203,51 @@ class SavedataService extends BaseService {
 		fileName: string,
 		backupTime: number,
 		fileSize: number,
+		auto = false,
+		sha256?: string,
 	): Promise<number> {
 		return this.invoke<number>("save_savedata_record", {
 			gameId,
 			fileName,
 			backupTime,
 			fileSize,
+			auto,
+			sha256,
+		});
+	}
+
+	/**
+	 * 为没有 SHA-256 的旧备份记录补算哈希
+	 */
+	async rehashExistingBackups(): Promise<RehashResult> {
+		return this.invoke<RehashResult>("rehash_existing_backups");
+	}
+
+	/**
+	 * 获取存档备份目录用量
+	 * @param refresh 为 true 时忽略缓存重新扫描
+	 */
+	async getSavedataUsage(refresh = false): Promise<SavedataUsage> {
+		return this.invoke<SavedataUsage>("get_savedata_usage", { refresh });
+	}
+
+	/**
+	 * 自动检测游戏存档目录，返回按置信度降序排列的候选
+	 * @param gameId 游戏ID
+	 */
+	async detectSavePath(gameId: number): Promise<SaveCandidate[]> {
+		return this.invoke<SaveCandidate[]>("detect_save_path", { gameId });
+	}
+
+	/**
+	 * 一键自检备份/恢复流程（创建 → 校验 → 恢复 → 对比 → 清理）
+	 * @param includeEncryption 是否使用加密压缩包测试
+	 */
+	async selfTestBackupPipeline(
+		includeEncryption = false,
+	): Promise<SelfTestReport> {
+		return this.invoke<SelfTestReport>("self_test_backup_pipeline", {
+			includeEncryption,
 		});
 	}
 
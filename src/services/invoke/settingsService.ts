@@ -3,7 +3,27 @@
  * @description 封装所有用户设置相关的后端调用
  */
 
-import type { BgmAuth, LogLevel, UpdateSettingsParams } from "@/types";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type {
+	AutoClearRules,
+	BackupCompression,
+	BackupSchedule,
+	BgmAuth,
+	CrashReportInfo,
+	HttpApiSettings,
+	LogEntry,
+	LogLevel,
+	LogSettings,
+	MonitorSettings,
+	NotificationSettings,
+	PresenceSettings,
+	SavedataQuota,
+	ScanConfig,
+	SettingsChangedPayload,
+	TraySettings,
+	UpdateSettingsParams,
+	UsageSummary,
+} from "@/types";
 import { BaseService } from "./base";
 
 export interface UserSettings {
@@ -13,15 +33,57 @@ export interface UserSettings {
 	db_backup_path?: string | null;
 	le_path?: string | null;
 	magpie_path?: string | null;
+	auto_clear_rules?: AutoClearRules | null;
+	savedata_quota?: SavedataQuota | null;
+	monitor_settings?: MonitorSettings | null;
+	notification_settings?: NotificationSettings | null;
+	presence_settings?: PresenceSettings | null;
+	backup_schedule?: BackupSchedule | null;
+	scan_config?: ScanConfig | null;
+	http_api?: HttpApiSettings | null;
+	tray_settings?: TraySettings | null;
+	log_settings?: LogSettings | null;
+	backup_compression?: BackupCompression | null;
+	use_trash?: boolean | null;
+	usage_stats_enabled?: boolean | null;
+	max_db_backups?: number | null;
+	max_backup_age_days?: number | null;
+}
+
+export interface HttpApiStatus {
+	running: boolean;
+	port: number | null;
+	file_lock_retries?: number | null;
+	file_lock_retry_delay_ms?: number | null;
 }
 
 export interface ProxyConfig {
 	url: string;
 }
 
+export interface CommandMetrics {
+	command: string;
+	count: number;
+	avg_ms: number;
+	max_ms: number;
+	slowest_recent_ms: number[];
+}
+
+export interface SlowQuery {
+	statement: string;
+	elapsed_ms: number;
+	failed: boolean;
+	recorded_at: number;
+}
+
+export interface PerformanceMetrics {
+	commands: CommandMetrics[];
+	slow_queries: SlowQuery[];
+}
+
 class SettingsService extends BaseService {
 	/**
-	 * 动态设置日志输出级别（不持久化）
+	 * 设置日志输出级别，立即生效并保存，下次启动时沿用
 	 */
 	async setLogLevel(level: LogLevel): Promise<void> {
 		return this.invoke<void>("set_reina_log_level", { level });
@@ -34,6 +96,82 @@ class SettingsService extends BaseService {
 		return this.invoke<LogLevel>("get_reina_log_level");
 	}
 
+	/**
+	 * 读取最近的日志记录（从新到旧）
+	 * @param level 最低级别，如 warn 返回 warn 与 error
+	 * @param limit 返回条数，默认 200，最多 2000
+	 */
+	async getRecentLogs(level?: LogLevel, limit?: number): Promise<LogEntry[]> {
+		return this.invoke<LogEntry[]>("get_recent_logs", { level, limit });
+	}
+
+	/**
+	 * 在文件管理器中打开日志目录
+	 */
+	async openLogDirectory(): Promise<void> {
+		return this.invoke<void>("open_log_directory");
+	}
+
+	/**
+	 * 删除早于指定天数的轮转日志，返回删除的文件数量
+	 */
+	async clearOldLogs(days: number): Promise<number> {
+		return this.invoke<number>("clear_old_logs", { days });
+	}
+
+	/**
+	 * 列出崩溃报告（从新到旧），启动时有新报告可提示用户反馈
+	 */
+	async listCrashReports(): Promise<CrashReportInfo[]> {
+		return this.invoke<CrashReportInfo[]>("list_crash_reports");
+	}
+
+	async deleteCrashReport(fileName: string): Promise<void> {
+		return this.invoke<void>("delete_crash_report", { fileName });
+	}
+
+	/**
+	 * 获取本地使用统计汇总（统计数据只保存在本地数据库）
+	 */
+	async getUsageSummary(): Promise<UsageSummary> {
+		return this.invoke<UsageSummary>("get_usage_summary");
+	}
+
+	/**
+	 * 清空本地使用统计，返回删除的记录数
+	 */
+	async clearUsageStats(): Promise<number> {
+		return this.invoke<number>("clear_usage_stats");
+	}
+
+	/**
+	 * 获取后端命令执行与慢查询耗时统计
+	 * 可与 getInvokeTimings() 的前端往返耗时对比，区分数据库、IPC 与渲染开销
+	 */
+	async getPerformanceMetrics(limit?: number): Promise<PerformanceMetrics> {
+		return this.invoke<PerformanceMetrics>("get_performance_metrics", {
+			limit,
+		});
+	}
+
+	/**
+	 * 清空后端耗时统计
+	 */
+	async resetPerformanceMetrics(): Promise<void> {
+		return this.invoke<void>("reset_performance_metrics");
+	}
+
+	/**
+	 * 发送系统通知（遵循通知设置中的类别开关，主窗口在前台时不发送）
+	 */
+	async sendNotification(
+		category: "backup" | "session_milestone" | "update_available" | "error",
+		title: string,
+		body: string,
+	): Promise<void> {
+		return this.invoke<void>("send_notification", { category, title, body });
+	}
+
 	/**
 	 * 获取所有设置
 	 */
@@ -50,6 +188,17 @@ class SettingsService extends BaseService {
 		});
 	}
 
+	/**
+	 * 监听设置变更（任一窗口调用 updateSettings 后触发），返回取消监听函数
+	 */
+	onSettingsChanged(
+		handler: (payload: SettingsChangedPayload) => void,
+	): Promise<UnlistenFn> {
+		return listen<SettingsChangedPayload>("settings-changed", (event) =>
+			handler(event.payload),
+		);
+	}
+
 	async updateProxyConfig(config: ProxyConfig): Promise<void> {
 		return this.invoke<void>("update_proxy_config", { config });
 	}
@@ -65,6 +214,17 @@ class SettingsService extends BaseService {
 	async bgmOAuthRefreshToken(refreshToken: string): Promise<BgmAuth> {
 		return this.invoke<BgmAuth>("bgm_oauth_refresh_token", { refreshToken });
 	}
+
+	/**
+	 * 按当前设置重新启动或停止局域网 HTTP API，修改 http_api 设置后调用
+	 */
+	async applyHttpApiSettings(): Promise<HttpApiStatus> {
+		return this.invoke<HttpApiStatus>("apply_http_api_settings");
+	}
+
+	async getHttpApiStatus(): Promise<HttpApiStatus> {
+		return this.invoke<HttpApiStatus>("get_http_api_status");
+	}
 }
 
 // 导出单例
@@ -0,0 +1,75 @@
+/**
+ * @file 首次启动向导服务
+ * @description 向导的各个步骤由后端执行并保存进度（见 src-tauri/src/setup.rs），中途关闭后可从未完成的步骤继续
+ */
+
+import { BaseService } from "./base";
+import type { DataMigrationResult } from "./fileService";
+
+export type SetupStep = "data_dir" | "library" | "tools" | "bgm_token";
+
+export interface SetupState {
+	finished: boolean;
+	/** 下一个待完成的步骤，向导已结束时为 null */
+	current_step: SetupStep | null;
+	completed: SetupStep[];
+	skipped: SetupStep[];
+	is_portable: boolean;
+	/** 当前的基础数据目录 */
+	data_dir: string;
+	library_folders: string[];
+	le_path: string | null;
+	magpie_path: string | null;
+	bgm_username: string | null;
+}
+
+export interface SetupStepResult {
+	state: SetupState;
+	/** 选择游戏库目录后开始的扫描会话 ID，结果通过 library-scan-* 事件推送 */
+	scan_ids: number[];
+	migration: DataMigrationResult | null;
+	bgm_user: { username: string; nickname: string } | null;
+}
+
+/** 各步骤的参数，传入 { skip: true } 表示跳过 */
+export interface SetupStepPayloads {
+	data_dir: { portable?: boolean };
+	library: { folders: string[]; scan?: boolean };
+	/** 未传入的路径保持不变，传入空字符串清除 */
+	tools: { le_path?: string; magpie_path?: string };
+	bgm_token: { access_token?: string };
+}
+
+class SetupService extends BaseService {
+	/**
+	 * 获取首次启动向导的状态
+	 */
+	async getSetupState(): Promise<SetupState> {
+		return this.invoke<SetupState>("get_setup_state");
+	}
+
+	/**
+	 * 完成一个步骤并保存进度，失败时不记录进度，可修改参数后重试
+	 */
+	async completeSetupStep<S extends SetupStep>(
+		step: S,
+		payload: SetupStepPayloads[S],
+	): Promise<SetupStepResult> {
+		return this.invoke<SetupStepResult>("complete_setup_step", {
+			step,
+			payload,
+		});
+	}
+
+	/**
+	 * 跳过一个步骤
+	 */
+	async skipSetupStep(step: SetupStep): Promise<SetupStepResult> {
+		return this.invoke<SetupStepResult>("complete_setup_step", {
+			step,
+			payload: { skip: true },
+		});
+	}
+}
+
+export const setupService = new SetupService();
@@ -3,45 +3,228 @@
  * @description 封装所有游戏统计相关的后端调用
  */
 
-import type { GameLastPlayed, GameSession, GameStatistics } from "@/types";
+import type {
+	GameLastPlayed,
+	GameSession,
+	GameStatistics,
+	ProcessStats,
+	RunnerKind,
+} from "@/types";
 import { BaseService } from "./base";
+import type { ActiveSessionInfo } from "./gameService";
 import type { DailyStats } from "./types";
 
 export interface LaunchGameResult {
 	success: boolean;
 	message: string;
 	process_id?: number;
+	/** 游戏已在运行、未再次启动时为其现有会话 */
+	running_session?: ActiveSessionInfo;
+}
+
+/** 启动失败的原因归类 */
+export type LaunchFailureHint =
+	| "needs_elevation"
+	| "bad_executable"
+	| "missing_file"
+	| "permission_denied"
+	| "unknown";
+
+/** 创建游戏进程失败时的 `game-launch-failed` 事件负载 */
+export interface GameLaunchFailedPayload {
+	gameId: number;
+	/** 系统错误码（Windows 为 GetLastError，Linux 为 errno） */
+	osError: number | null;
+	leRequested: boolean;
+	magpieRequested: boolean;
+	hint: LaunchFailureHint;
+	message: string;
+}
+
+/** 检测到的 wine / Proton 安装 */
+export interface AvailableRunner {
+	kind: RunnerKind;
+	/** 显示名称（版本目录名） */
+	name: string;
+	/** 可填入 GameRunner.path 的路径 */
+	path: string;
+}
+
+/** 单个活跃会话的监控诊断信息（时间戳均为秒） */
+export interface SessionHealth {
+	gameId: number;
+	processId: number;
+	startTime: number;
+	totalSeconds: number;
+	/** 最近一次确认游戏进程存活的时间 */
+	lastLivenessCheck: number | null;
+	/** 最近一次检测到游戏在前台的时间 */
+	lastForegroundAt: number | null;
+	pidSwitches: number;
+	consecutiveFailures: number;
+}
+
+/** 正在运行的游戏会话 */
+export interface ActiveSessionSummary {
+	gameId: number;
+	processId: number;
+	startTime: number;
+	/** 自会话开始经过的时间（秒） */
+	elapsedSeconds: number;
+	/** 已累计的前台时间（秒） */
+	totalSeconds: number;
+	/** 是否已暂停计时 */
+	paused: boolean;
+}
+
+export interface PlayHabitBucket {
+	weekday: number; // 0 为周日
+	hour: number;
+	total_duration: number;
+	session_count: number;
+}
+
+export interface PlayHabits {
+	by_weekday: number[];
+	by_hour: number[];
+	buckets: PlayHabitBucket[];
+}
+
+export interface MonthlyPlaytime {
+	month: string; // YYYY-MM
+	playtime: number; // 分钟
+}
+
+export interface TopPlayedGame {
+	game_id: number;
+	total_time: number; // 分钟
+	session_count: number;
+	last_played?: number;
+}
+
+/** 游戏库总览，monthly_playtime 为近 12 个月（按时间顺序） */
+export interface LibraryOverview {
+	total_games: number;
+	cleared_games: number;
+	completion_ratio: number; // 0 ~ 1
+	total_playtime: number; // 分钟
+	monthly_playtime: MonthlyPlaytime[];
+	top_games: TopPlayedGame[];
+	current_streak: number; // 连续游玩天数
+}
+
+export interface NamedPlaytime {
+	name: string;
+	playtime: number; // 分钟
+	game_count: number;
+}
+
+export interface YearReportGame {
+	game_id: number;
+	playtime: number; // 分钟
+	session_count: number;
+}
+
+/** 年度报告，monthly_playtime 为 1 ~ 12 月的游玩时长（分钟） */
+export interface YearReport {
+	year: number;
+	total_playtime: number;
+	monthly_playtime: number[];
+	session_count: number;
+	days_played: number;
+	games_played: number;
+	new_game_ids: number[];
+	longest_session: GameSession | null;
+	top_games: YearReportGame[];
+	top_developers: NamedPlaytime[];
+	top_tags: NamedPlaytime[];
+}
+
+/** 后端渲染的图表描述 */
+export type ChartSpec = (
+	| {
+			kind: "playtime_over_time";
+			start_date: string;
+			end_date: string;
+			granularity?: "day" | "week" | "month";
+	  }
+	| { kind: "genre_distribution"; limit?: number }
+) & {
+	title?: string;
+	width?: number;
+	height?: number;
+	theme?: "light" | "dark";
+};
+
+/** 游戏时间小组件数据（分钟），含正在运行的会话 */
+export interface PlaytimeWidgetData {
+	todayMinutes: number;
+	weekMinutes: number;
+	activeGames: number;
 }
 
 export interface StopGameResult {
 	success: boolean;
 	message: string;
 	terminated_count: number;
+	/** 游戏自行退出（graceful）还是超时后被强制终止（forced） */
+	stage: "graceful" | "forced";
 }
 
 class StatsService extends BaseService {
 	/**
 	 * 启动游戏并开始监控
+	 * @param waitForDriveSecs 游戏所在驱动器未连接时最长等待挂载的秒数；
+	 * 超时仍不可用时抛出 code 为 "drive_unavailable" 的 AppError；
+	 * 进程启动后立即以非零退出码退出时抛出 code 为 "exited_early" 的 AppError，detail 为 stderr 末尾
+	 * @param force 游戏已在运行时仍然再启动一个实例；默认返回 success 为 false 与 running_session
 	 */
 	async launchGame(
 		gameId: number,
 		args: string[] = [],
+		waitForDriveSecs?: number,
+		force?: boolean,
 	): Promise<LaunchGameResult> {
 		return this.invoke<LaunchGameResult>("launch_game", {
 			gameId,
 			args,
+			waitForDriveSecs,
+			force,
 		});
 	}
 
 	/**
-	 * 停止游戏监控
+	 * 停止游戏监控并结束游戏进程
+	 * @param graceSecs 请求关闭后等待游戏自行退出的秒数，超时后强制终止，默认 5 秒
 	 */
-	async stopGame(gameId: number): Promise<StopGameResult> {
+	async stopGame(gameId: number, graceSecs?: number): Promise<StopGameResult> {
 		return this.invoke<StopGameResult>("stop_game", {
 			gameId,
+			graceSecs,
 		});
 	}
 
+	/**
+	 * 列出检测到的 wine 与 Proton 安装（仅 Linux，其他平台返回空列表）
+	 */
+	async listAvailableRunners(): Promise<AvailableRunner[]> {
+		return this.invoke<AvailableRunner[]>("list_available_runners");
+	}
+
+	/**
+	 * 获取监控子系统的诊断信息，用于排查游戏时间不累计的问题
+	 */
+	async getMonitorHealth(): Promise<SessionHealth[]> {
+		return this.invoke<SessionHealth[]>("get_monitor_health");
+	}
+
+	/**
+	 * 获取所有正在运行的游戏会话（可同时运行多个游戏）
+	 */
+	async getActiveSessions(): Promise<ActiveSessionSummary[]> {
+		return this.invoke<ActiveSessionSummary[]>("get_active_sessions");
+	}
+
 	/**
 	 * 记录游戏会话
 	 */
@@ -51,6 +234,7 @@ class StatsService extends BaseService {
 		endTime: number,
 		duration: number,
 		date: string,
+		processStats?: ProcessStats,
 	): Promise<number> {
 		return this.invoke<number>("record_game_session", {
 			gameId,
@@ -58,6 +242,39 @@ class StatsService extends BaseService {
 			endTime,
 			duration,
 			date,
+			processStats: processStats ?? null,
+		});
+	}
+
+	/**
+	 * 手动补录游戏会话
+	 */
+	async addManualSession(
+		gameId: number,
+		startTime: number,
+		endTime: number,
+		note?: string,
+	): Promise<GameSession> {
+		return this.invoke<GameSession>("add_manual_session", {
+			gameId,
+			startTime,
+			endTime,
+			note,
+		});
+	}
+
+	/**
+	 * 调整游戏总时长（分钟，负数表示减少），返回调整后的总时长
+	 */
+	async adjustGameTotalTime(
+		gameId: number,
+		deltaMinutes: number,
+		note?: string,
+	): Promise<number> {
+		return this.invoke<number>("adjust_game_total_time", {
+			gameId,
+			deltaMinutes,
+			note,
 		});
 	}
 
@@ -76,6 +293,19 @@ class StatsService extends BaseService {
 		});
 	}
 
+	/**
+	 * 设置会话备注，传入 null 或空字符串时清除
+	 */
+	async updateSessionNote(
+		sessionId: number,
+		note: string | null,
+	): Promise<GameSession> {
+		return this.invoke<GameSession>("update_session_note", {
+			sessionId,
+			note,
+		});
+	}
+
 	/**
 	 * 获取指定游戏范围内的全局最近会话
 	 */
@@ -89,6 +319,69 @@ class StatsService extends BaseService {
 		});
 	}
 
+	/**
+	 * 获取按星期与小时汇总的游玩习惯
+	 * @param startDate 起始日期 YYYY-MM-DD（含）
+	 * @param endDate 结束日期 YYYY-MM-DD（含）
+	 * @param gameId 为空时统计所有游戏
+	 */
+	async getPlayHabits(
+		startDate?: string,
+		endDate?: string,
+		gameId?: number,
+	): Promise<PlayHabits> {
+		return this.invoke<PlayHabits>("get_play_habits", {
+			startDate,
+			endDate,
+			gameId,
+		});
+	}
+
+	/**
+	 * 一次获取统计面板所需的游戏库总览
+	 */
+	async getLibraryOverview(): Promise<LibraryOverview> {
+		return this.invoke<LibraryOverview>("get_library_overview");
+	}
+
+	/**
+	 * 按日汇总所有游戏的游玩时长（分钟），仅包含有游玩时间的日期，用于日历热力图
+	 * @param startDate 开始日期 YYYY-MM-DD（含）
+	 * @param endDate 结束日期 YYYY-MM-DD（含）
+	 */
+	async getDailyPlaytimeRange(
+		startDate: string,
+		endDate: string,
+	): Promise<Record<string, number>> {
+		return this.invoke<Record<string, number>>("get_daily_playtime_range", {
+			startDate,
+			endDate,
+		});
+	}
+
+	/**
+	 * 生成年度报告（按本地时间统计）
+	 */
+	async generateYearReport(year: number): Promise<YearReport> {
+		return this.invoke<YearReport>("generate_year_report", { year });
+	}
+
+	/**
+	 * 在后端渲染统计图表
+	 * @returns SVG 文本
+	 */
+	async renderChart(spec: ChartSpec): Promise<string> {
+		return this.invoke<string>("render_chart", { spec });
+	}
+
+	/**
+	 * 在后端渲染统计图表
+	 * @returns PNG 图片字节
+	 */
+	async renderChartPng(spec: ChartSpec): Promise<ArrayBuffer> {
+		return this.invoke<ArrayBuffer>("render_chart_png", { spec });
+	}
+
 	// 暂时无用
 	/**
 	 * 删除游戏会话
@@ -166,6 +459,27 @@ class StatsService extends BaseService {
 		return this.invoke<number>("get_today_playtime", { gameId, today });
 	}
 
+	/**
+	 * 打开置顶的游戏时间小组件窗口，已打开时显示到前台
+	 */
+	async openPlaytimeWidget(): Promise<void> {
+		return this.invoke<void>("open_playtime_widget");
+	}
+
+	/**
+	 * 关闭游戏时间小组件窗口
+	 */
+	async closePlaytimeWidget(): Promise<void> {
+		return this.invoke<void>("close_playtime_widget");
+	}
+
+	/**
+	 * 获取游戏时间小组件当前数据，之后由 playtime-widget-update 事件推送
+	 */
+	async getPlaytimeWidgetData(): Promise<PlaytimeWidgetData> {
+		return this.invoke<PlaytimeWidgetData>("get_playtime_widget_data");
+	}
+
 	/**
 	 * 初始化游戏统计记录
 	 */
@@ -1,108 +1,56 @@
-import { version } from "@pkg";
-import { defaultWindowIcon } from "@tauri-apps/api/app";
-import { Menu, MenuItem } from "@tauri-apps/api/menu";
-import type { TrayIconEvent } from "@tauri-apps/api/tray";
-import { TrayIcon } from "@tauri-apps/api/tray";
-import { getCurrentWindow } from "@tauri-apps/api/window";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import i18n from "i18next";
 import { exitCurrentWindowFromTray } from "@/services/appExit";
 
-let trayInstance: TrayIcon | null = null;
-let trayInitPromise: Promise<TrayIcon | null> | null = null;
-const TRAY_ID = "main";
-
-const showUI = async () => {
-	const window = getCurrentWindow();
-	try {
-		await window.show();
-		await window.unminimize();
-		await window.setFocus();
-	} catch (error) {
-		console.error("Failed to show window:", error);
-	}
-};
-
-const createTrayMenu = async () => {
-	// 1. 创建“打开”菜单项
-	const openItem = await MenuItem.new({
-		id: "open",
-		text: i18n.t("components.Tray.open", "打开主窗口"),
-		action: async () => {
-			await showUI();
-		},
-	});
-
-	// 2. 创建“退出”菜单项
-	const quitItem = await MenuItem.new({
-		id: "exit",
-		text: i18n.t("components.Tray.exit", "退出"),
-		action: async () => {
-			console.log("Exiting application...");
-			await exitCurrentWindowFromTray();
+/**
+ * 托盘图标由后端创建（见 src-tauri/src/utils/tray.rs），前端只负责传入当前语言的菜单文字，
+ * 并在点击"退出"后完成退出确认。
+ */
+let trayInitPromise: Promise<void> | null = null;
+let exitListenerReady = false;
+
+const syncTrayLabels = async () => {
+	await invoke("set_tray_labels", {
+		labels: {
+			language: i18n.language,
+			open: i18n.t("components.Tray.open", "打开主窗口"),
+			exit: i18n.t("components.Tray.exit", "退出"),
+			backup: i18n.t("components.Tray.backup", "立即备份"),
+			openLibrary: i18n.t("components.Tray.openLibrary", "打开游戏库文件夹"),
+			recent: i18n.t("components.Tray.recent", "最近游玩"),
+			noRunningGame: i18n.t(
+				"components.Tray.noRunningGame",
+				"没有正在运行的游戏",
+			),
+			paused: i18n.t("components.Tray.paused", "已暂停"),
 		},
 	});
-
-	// 3. 组合成菜单
-	return await Menu.new({
-		items: [openItem, quitItem],
-	});
 };
 
 /**
  * 更新托盘菜单语言
  */
 export const updateTrayLanguage = async () => {
-	if (!trayInstance) return;
 	try {
-		const menu = await createTrayMenu();
-		await trayInstance.setMenu(menu);
+		await syncTrayLabels();
 	} catch (error) {
 		console.error("Failed to update tray menu:", error);
 	}
 };
 
-/**
- * 创建并初始化托盘图标
- */
 const initTrayInner = async () => {
-	try {
-		if (trayInstance) return trayInstance;
-
-		const menu = await createTrayMenu();
-		const windowIcon = await defaultWindowIcon();
-		const tooltipText = `ReinaManager v${version}`;
-		const existingTray = await TrayIcon.getById(TRAY_ID);
-
-		if (existingTray) {
-			await TrayIcon.removeById(TRAY_ID);
-		}
-
-		const tray = await TrayIcon.new({
-			id: TRAY_ID,
-			icon: windowIcon ?? undefined,
-			tooltip: tooltipText, // 显示软件名和版本号
-			menu,
-			showMenuOnLeftClick: false, // 左键不显示菜单
-
-			action: async (event: TrayIconEvent) => {
-				if (
-					event.type === "Click" &&
-					event.button === "Left" &&
-					event.buttonState === "Up"
-				)
-					await showUI();
-			},
+	if (!exitListenerReady) {
+		await listen("tray-exit-requested", async () => {
+			console.log("Exiting application...");
+			await exitCurrentWindowFromTray();
 		});
-
-		trayInstance = tray;
-		i18n.off("languageChanged", updateTrayLanguage); // 避免重复监听
-		i18n.on("languageChanged", updateTrayLanguage);
-
-		return tray;
-	} catch (error) {
-		console.error("Failed to initialize tray icon:", error);
-		return null;
+		exitListenerReady = true;
 	}
+
+	await updateTrayLanguage();
+	i18n.off("languageChanged", updateTrayLanguage); // 避免重复监听
+	i18n.on("languageChanged", updateTrayLanguage);
 };
 
 export const initTray = () => {
@@ -32,19 +32,54 @@ export interface BatchOperationError {
 	message: string;
 }
 
+/** 批量操作中单项的处理结果 */
+export interface BatchItemResult {
+	index: number;
+	/** skipped：本地路径或数据源 ID 与已有游戏（或同一批中靠前的项）重复 */
+	status: "inserted" | "skipped" | "failed";
+	/** 插入后的游戏 ID；跳过时为与之重复的游戏 ID */
+	id: number | null;
+	message: string | null;
+}
+
 export interface BatchOperationResult {
 	total: number;
 	success: number;
 	failed: number;
+	skipped?: number;
 	ids?: number[];
 	games?: FullGameData[];
 	errors: BatchOperationError[];
+	/** 每一项的处理结果，与传入顺序一致 */
+	items?: BatchItemResult[];
 }
 
-export interface ScanResult {
+/** 跨数据源 ID 匹配候选 */
+export interface CrossIdCandidate {
+	source: "bgm" | "vndb" | "ymgal";
+	id: string;
+	title: string;
+	alt_title: string | null;
+	date: string | null;
+	/** 匹配度，0 ~ 1 */
+	score: number;
+}
+
+/** 扫描识别出的游戏目录 */
+export interface ScannedGame {
+	/** 由文件夹名清洗得到的游戏名 */
 	name: string;
 	path: string;
+	/** 可执行文件（相对游戏目录），按可能性排序 */
 	executables: string[];
+	/** 推测的主程序，即 executables 的第一项 */
+	main_executable: string | null;
+	/** 游戏目录的估算大小（字节） */
+	size: number;
+	/** 该目录已作为游戏导入 */
+	already_imported: boolean;
+	/** 按游戏名在 BGM / VNDB 搜索到的候选，未请求元数据匹配时为空 */
+	candidates: CrossIdCandidate[];
 }
 
 export interface BgmAuth {
@@ -151,6 +186,7 @@ export interface CustomData {
 	nsfw?: Nullable<boolean>;
 	user_rating?: Nullable<number>;
 	user_review?: Nullable<string>;
+	presence_title?: Nullable<string>; // 在 Discord 等状态展示中代替真实名称显示的标题
 }
 
 export interface SourceScores {
@@ -208,6 +244,106 @@ interface GameRuntimePayload {
 	clear?: number;
 	le_launch?: number;
 	magpie?: number;
+	/** 商店启动 URI（如 steam://rungameid/...），设置后通过该 URI 启动 */
+	launch_uri?: Nullable<string>;
+	/** 查找商店拉起的游戏进程所用的可执行文件名，未设置时取 localpath 的文件名 */
+	launch_process_name?: Nullable<string>;
+	/** 每次启动都附加的参数，支持 {gamedir}、{gamepath}、{savepath}、{gameid} 占位符 */
+	launch_args?: Nullable<string[]>;
+	/** Linux 上运行该游戏的兼容层，未设置时沿用全局的 linux_launch_command */
+	runner?: Nullable<GameRunner>;
+	/** Linux 上用 firejail / bubblewrap 限制游戏的文件系统与网络访问 */
+	sandbox?: Nullable<SandboxConfig>;
+	/** 会话期间把手柄输入转换为键盘 / 鼠标操作（仅 Windows） */
+	input_mapping?: Nullable<InputMapping>;
+	/** 游戏引擎自行保存截图的目录，相对路径基于游戏可执行文件所在目录 */
+	screenshot_dir?: Nullable<string>;
+	/** 会话结束前记录的游戏窗口位置（仅 Windows，需开启 remember_window_placement） */
+	window_placement?: Nullable<WindowPlacement>;
+	/** 覆盖全局自动备份计划的字段，未设置的字段沿用全局设置 */
+	backup_schedule?: Nullable<BackupSchedule>;
+}
+
+/**
+ * 游戏窗口位置（屏幕物理像素）
+ */
+export interface WindowPlacement {
+	x: number;
+	y: number;
+	width: number;
+	height: number;
+	maximized: boolean;
+	dpi: number;
+}
+
+/** Linux 兼容层类型 */
+export type RunnerKind = "wine" | "proton" | "native";
+
+/**
+ * 单个游戏的 Linux 兼容层设置
+ */
+export interface GameRunner {
+	kind: RunnerKind;
+	/** wine 可执行文件路径或 Proton 安装目录；wine 未设置时使用 linux_launch_command */
+	path?: Nullable<string>;
+	/** WINEPREFIX 或 Proton 的 compatdata 目录 */
+	prefix?: Nullable<string>;
+	/** 额外环境变量，如 DXVK_HUD */
+	env?: Record<string, string>;
+}
+
+/**
+ * 单个游戏的沙盒设置（Linux）
+ * 沙盒内主目录只保留游戏目录、存档目录、兼容层前缀和 writable_paths
+ */
+export interface SandboxConfig {
+	enabled: boolean;
+	tool: "firejail" | "bubblewrap";
+	allow_network: boolean;
+	/** 额外允许读写的目录，支持 ~ 开头 */
+	writable_paths?: string[];
+	/** firejail 的自定义 profile 文件，设置后替代内置的目录规则 */
+	firejail_profile?: Nullable<string>;
+}
+
+/** 手柄按键（XInput） */
+export type GamepadButton =
+	| "a"
+	| "b"
+	| "x"
+	| "y"
+	| "left_shoulder"
+	| "right_shoulder"
+	| "left_trigger"
+	| "right_trigger"
+	| "back"
+	| "start"
+	| "left_thumb"
+	| "right_thumb"
+	| "dpad_up"
+	| "dpad_down"
+	| "dpad_left"
+	| "dpad_right";
+
+/** 按键映射到的操作，key 的 code 为 Windows 虚拟键码 */
+export type MappedAction =
+	| { type: "key"; code: number }
+	| { type: "mouse_left" }
+	| { type: "mouse_right" }
+	| { type: "wheel_up" }
+	| { type: "wheel_down" };
+
+/**
+ * 单个游戏的手柄映射（Windows）
+ * buttons 为空时使用默认映射：A 左键、B 右键、X 空格、Y Ctrl、LB/RB 滚轮、Start Esc、Back 回车、十字键方向键
+ */
+export interface InputMapping {
+	enabled: boolean;
+	buttons?: Partial<Record<GamepadButton, MappedAction>>;
+	/** 用左摇杆移动鼠标 */
+	left_stick_mouse?: boolean;
+	/** 摇杆推到底时每秒移动的像素数 */
+	mouse_speed?: number;
 }
 
 interface GameMetadataPayload {
@@ -287,6 +423,15 @@ export interface UpdateGameParams {
 	clear?: Nullable<number>;
 	le_launch?: Nullable<number>;
 	magpie?: Nullable<number>;
+	launch_uri?: Nullable<string>;
+	launch_process_name?: Nullable<string>;
+	launch_args?: Nullable<string[]>;
+	runner?: Nullable<GameRunner>;
+	sandbox?: Nullable<SandboxConfig>;
+	input_mapping?: Nullable<InputMapping>;
+	screenshot_dir?: Nullable<string>;
+	window_placement?: Nullable<WindowPlacement>;
+	backup_schedule?: Nullable<BackupSchedule>;
 
 	// --- JSON Payload（支持三态） ---
 	bgm_data?: Nullable<BgmData>;
@@ -313,6 +458,197 @@ export interface UpdateSettingsParams {
 	dbBackupPath?: Nullable<string>;
 	lePath?: Nullable<string>;
 	magpiePath?: Nullable<string>;
+	autoClearRules?: Nullable<AutoClearRules>;
+	savedataQuota?: Nullable<SavedataQuota>;
+	monitorSettings?: Nullable<MonitorSettings>;
+	notificationSettings?: Nullable<NotificationSettings>;
+	presenceSettings?: Nullable<PresenceSettings>;
+	backupSchedule?: Nullable<BackupSchedule>;
+	scanConfig?: Nullable<ScanConfig>;
+	httpApi?: Nullable<HttpApiSettings>;
+	traySettings?: Nullable<TraySettings>;
+	logSettings?: Nullable<LogSettings>;
+	backupCompression?: Nullable<BackupCompression>;
+	/** 删除文件、存档备份与自定义封面时是否移到回收站，默认是 */
+	useTrash?: Nullable<boolean>;
+	/** 是否在本地记录功能使用统计，默认关闭 */
+	usageStatsEnabled?: Nullable<boolean>;
+	/** 保留的数据库备份数量上限，未设置或为 0 时不限制 */
+	maxDbBackups?: Nullable<number>;
+	/** 数据库备份的最长保留天数，未设置或为 0 时不限制 */
+	maxBackupAgeDays?: Nullable<number>;
+}
+
+/**
+ * 日志设置，修改后下次启动生效（级别通过 setLogLevel 立即生效）
+ */
+export interface LogSettings {
+	/** 日志级别，未设置时发布版为 info */
+	level?: LogLevel | null;
+	/** 单个日志文件大小上限（KB），未设置时为 1000，范围 100 ~ 102400 */
+	max_file_size_kb?: number | null;
+	/** 保留的日志文件数量，未设置时为 5，范围 1 ~ 50 */
+	max_files?: number | null;
+}
+
+/**
+ * 存档备份压缩设置，字段未设置时沿用上一级设置（单次备份 → 全局设置 → 默认值）
+ */
+export interface BackupCompression {
+	/** 压缩等级，默认 normal */
+	level?: "fast" | "normal" | "max" | null;
+	/** 压缩算法，默认 zstd；lzma2 压缩率更高但更慢 */
+	codec?: "zstd" | "lzma2" | null;
+	/** 是否固实压缩，默认是 */
+	solid?: boolean | null;
+}
+
+/**
+ * 系统托盘设置
+ */
+export interface TraySettings {
+	/** 启动时不显示主窗口，只保留托盘图标 */
+	start_minimized?: boolean | null;
+	/** 托盘菜单中显示的最近游玩游戏数量，未设置时为 5，为 0 时不显示 */
+	recent_games?: number | null;
+	/** 启动游戏后隐藏主窗口，会话结束时恢复 */
+	hide_on_launch?: boolean | null;
+}
+
+/**
+ * main-window-hidden / main-window-restored 事件负载
+ */
+export interface MainWindowVisibilityPayload {
+	gameId: number;
+}
+
+/**
+ * settings-changed 事件负载，keys 为本次变更的设置项
+ */
+export interface SettingsChangedPayload {
+	keys: (keyof UpdateSettingsParams)[];
+}
+
+/**
+ * 局域网 HTTP API 设置，默认关闭
+ */
+export interface HttpApiSettings {
+	/** 在局域网内提供游戏库浏览与启动接口 */
+	enabled?: boolean | null;
+	/** 监听端口，未设置时使用 17380 */
+	port?: number | null;
+	/** 访问令牌，开启时未设置会自动生成 */
+	token?: string | null;
+}
+
+/**
+ * 批量导入时扫描游戏目录的规则，未设置的字段使用内置默认值
+ */
+export interface ScanConfig {
+	/** 除 exe / bat / cmd 外也视为启动程序的扩展名（不含点，如 lnk、sh） */
+	extra_extensions?: string[] | null;
+	/** 额外忽略的目录名或文件名，不区分大小写，支持 * / ? 通配符 */
+	ignore_patterns?: string[] | null;
+	/** 默认扫描深度（2-5），调用时传入的深度优先 */
+	max_depth?: number | null;
+	/** 是否跟随符号链接 / 目录联接，默认不跟随 */
+	follow_symlinks?: boolean | null;
+}
+
+/**
+ * Discord Rich Presence 设置，默认关闭
+ */
+export interface PresenceSettings {
+	/** 游戏运行时在 Discord 展示正在游玩的游戏 */
+	enabled?: boolean | null;
+	/** 是否展示封面（仅支持网络图片），默认展示 */
+	show_cover?: boolean | null;
+	/** 成人内容游戏不展示真实名称和封面（设置了 custom_data.presence_title 的除外），默认开启 */
+	hide_nsfw?: boolean | null;
+	/** Discord 应用的 Client ID，未设置时使用构建时的 DISCORD_CLIENT_ID */
+	client_id?: string | null;
+}
+
+/**
+ * 系统通知开关，未设置的类别默认开启
+ */
+export interface NotificationSettings {
+	/** 存档备份完成 */
+	backup?: boolean | null;
+	/** 游戏累计时长达到里程碑（每 10 小时） */
+	session_milestones?: boolean | null;
+	/** 有可用的应用更新 */
+	update_available?: boolean | null;
+	/** 后台任务出错（如游戏监控失败） */
+	errors?: boolean | null;
+}
+
+/**
+ * 游戏监控设置
+ */
+export interface MonitorSettings {
+	/** 无键鼠输入超过该秒数视为挂机，挂机期间不累计游戏时间；未设置表示不启用（仅 Windows） */
+	idle_timeout_secs?: number | null;
+	/** Wayland 会话下无法判断焦点窗口时是否照常累计游戏时间；未设置时默认照常累计（仅 Linux） */
+	wayland_assume_foreground?: boolean | null;
+	/** 查询焦点窗口 PID 的 shell 命令（如基于 swaymsg / hyprctl 的脚本），监控每秒执行一次（仅 Linux） */
+	foreground_probe_command?: string | null;
+	/** 截取前台游戏窗口的全局快捷键（如 `Ctrl+Shift+S`）；未设置表示不注册（仅 Windows） */
+	screenshot_hotkey?: string | null;
+	/** 停止最近启动的游戏的全局快捷键；未设置表示不注册 */
+	stop_game_hotkey?: string | null;
+	/** 暂停 / 恢复最近启动的游戏计时的全局快捷键；未设置表示不注册 */
+	pause_timing_hotkey?: string | null;
+	/** 是否记住游戏窗口的位置和大小，下次启动时在窗口出现后移回原处；未设置时默认关闭（仅 Windows） */
+	remember_window_placement?: boolean | null;
+	/** 文件被占用时的最大重试次数，默认 5，最多 10 */
+	fileLockRetries?: Nullable<number>;
+	/** 文件被占用时首次重试前的等待时间（毫秒），之后每次翻倍，默认 100 */
+	fileLockRetryDelayMs?: Nullable<number>;
+}
+
+/**
+ * 存档自动备份计划
+ *
+ * 全局设置存储在用户设置中，单个游戏可覆盖其中的字段；字段为 null 时沿用上一级设置
+ */
+export interface BackupSchedule {
+	/** 会话结束后是否自动备份 */
+	enabled?: boolean | null;
+	/** 两次自动备份的最短间隔（分钟），0 表示每次会话结束都备份 */
+	interval_minutes?: number | null;
+	/** 自动备份的保留数量，0 表示只受 maxbackups 限制 */
+	max_auto_backups?: number | null;
+	/** 会话进行中每累计游玩多少分钟自动备份一次，0 表示不在会话中备份 */
+	play_interval_minutes?: number | null;
+}
+
+/**
+ * 存档备份根目录的软配额
+ *
+ * 接近或超出时后端发出 `savedata-quota-warning` 事件
+ */
+export interface SavedataQuota {
+	/** 配额上限（字节） */
+	limit_bytes: number;
+	/** 用量达到上限的该比例时警告 */
+	warn_ratio: number;
+	/** 超出时自动删除最旧的自动检查点 */
+	auto_prune: boolean;
+}
+
+/**
+ * 自动标记游戏状态的规则
+ *
+ * 命中后后端更新游戏状态并发出 `game-auto-cleared` 事件
+ */
+export interface AutoClearRules {
+	/** 外部收藏同步报告为玩过时自动标记 */
+	on_external_finished: boolean;
+	/** 累计时长超过 VNDB 平均时长的倍数时自动标记，未设置表示不启用 */
+	playtime_factor?: number | null;
+	/** 命中后设置的游戏状态 */
+	target_status: number;
 }
 
 /**
@@ -359,6 +695,22 @@ export interface GameSession {
 	end_time?: number;
 	duration?: number; // 分钟
 	date: string;
+	tags?: string[] | null; // 会话上下文标签
+	manual?: boolean; // 手动补录或调整的会话
+	note?: string | null;
+	process_stats?: ProcessStats | null; // 会话期间主进程的内存与 CPU 占用
+}
+
+/**
+ * 会话期间主进程的资源占用，CPU 占用按全部逻辑核心计算（100 表示占满）
+ */
+export interface ProcessStats {
+	memory_min_bytes: number;
+	memory_avg_bytes: number;
+	memory_peak_bytes: number;
+	cpu_min_percent: number;
+	cpu_avg_percent: number;
+	cpu_peak_percent: number;
 }
 
 /**
@@ -422,9 +774,73 @@ export interface SavedataRecord {
 	file: string; // 对应数据库中的 file 列（备份文件名）
 	backup_time: number;
 	file_size: number;
+	auto: boolean; // 是否为游戏结束时自动创建的检查点
+	sha256?: string | null; // 备份压缩包的 SHA-256
+}
+
+/**
+ * 游戏截图记录
+ */
+export interface Screenshot {
+	id: number;
+	game_id: number;
+	path: string; // 图片文件的绝对路径
+	captured_at: number; // 截取时间（Unix 时间戳，秒）
+	width?: number | null;
+	height?: number | null;
+	source_path?: string | null; // 从游戏自带截图目录复制时的原文件路径
+	session_id?: number | null; // 按截取时间关联的游戏会话
 }
 
 /**
  * 日志级别类型
  */
 export type LogLevel = "error" | "warn" | "info" | "debug";
+
+/**
+ * 崩溃报告列表项
+ */
+export interface CrashReportInfo {
+	file_name: string;
+	path: string;
+	/** 写入时间（Unix 时间戳，秒） */
+	created_at: number;
+	size: number;
+	/** panic 信息 */
+	message: string | null;
+}
+
+/**
+ * 单个功能的本地使用统计
+ */
+export interface FeatureUsage {
+	/** game_launch / database_backup / savedata_backup / library_scan */
+	feature: string;
+	total: number;
+	/** 最近 30 天（含今天）的使用次数 */
+	recent: number;
+	days_used: number;
+	/** 最后一次使用的时间（Unix 时间戳，秒） */
+	last_used: number;
+}
+
+/**
+ * 本地使用统计汇总
+ */
+export interface UsageSummary {
+	enabled: boolean;
+	/** 最早的记录日期（YYYY-MM-DD） */
+	since: string | null;
+	features: FeatureUsage[];
+}
+
+/**
+ * 日志文件中的一条记录
+ */
+export interface LogEntry {
+	/** 本地时间，格式为 YYYY-MM-DD HH:MM:SS */
+	timestamp: string;
+	level: LogLevel | "trace";
+	target: string;
+	message: string;
+}
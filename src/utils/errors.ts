@@ -14,7 +14,10 @@ export type AppErrorCode =
 	| "http_response_error"
 	| "http_response_parse_failed"
 	| "api_rate_limited"
-	| "metadata_request_failed";
+	| "metadata_request_failed"
+	| "drive_unavailable"
+	| "exited_early"
+	| "launch_failed";
 
 type ApiRateLimitSource = "bgm" | "vndb" | "ymgal" | "kun";
 